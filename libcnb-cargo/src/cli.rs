@@ -29,6 +29,12 @@ pub(crate) struct PackageArgs {
     /// Directory for packaged buildpacks, defaults to 'packaged' in Cargo workspace root
     #[arg(long)]
     pub(crate) package_dir: Option<PathBuf>,
+    /// Sign packaged buildpacks with cosign and attest their provenance, keyless unless --sign-key is given
+    #[arg(long)]
+    pub(crate) sign: bool,
+    /// Key file or KMS URI for signing packaged buildpacks with cosign, implies --sign
+    #[arg(long)]
+    pub(crate) sign_key: Option<String>,
 }
 
 #[cfg(test)]
@@ -1,13 +1,18 @@
 use crate::cli::PackageArgs;
 use crate::package::error::Error;
 use libcnb_data::buildpack::BuildpackId;
+use libcnb_package::build::build_workspace_buildpack_binaries;
 use libcnb_package::buildpack_dependency_graph::build_libcnb_buildpacks_dependency_graph;
 use libcnb_package::cross_compile::{cross_compile_assistance, CrossCompileAssistance};
 use libcnb_package::dependency_graph::get_dependencies;
 use libcnb_package::output::create_packaged_buildpack_dir_resolver;
+use libcnb_package::signing::{
+    sign_packaged_buildpack, PackageProvenance, SignedBuildpackFiles, SigningMethod,
+};
 use libcnb_package::util::absolutize_path;
 use libcnb_package::{find_cargo_workspace_root_dir, CargoProfile};
 use std::collections::BTreeMap;
+use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -39,26 +44,7 @@ pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
         create_packaged_buildpack_dir_resolver(&package_dir, cargo_profile, &args.target);
 
     eprintln!("🖥️ Gathering Cargo configuration (for {})", args.target);
-    let cargo_build_env = if args.no_cross_compile_assistance {
-        Vec::new()
-    } else {
-        match cross_compile_assistance(&args.target) {
-            CrossCompileAssistance::Configuration { cargo_env } => cargo_env,
-            CrossCompileAssistance::NoAssistance => {
-                eprintln!(
-                    "Couldn't determine automatic cross-compile settings for target triple {}.",
-                    args.target
-                );
-                eprintln!("This is not an error, but without proper cross-compile settings in your Cargo manifest and locally installed toolchains, compilation might fail.");
-                eprintln!("To disable this warning, pass --no-cross-compile-assistance.");
-                Vec::new()
-            }
-            CrossCompileAssistance::HelpText(help_text) => {
-                eprintln!("{help_text}");
-                return Err(Error::CannotConfigureCrossCompilation);
-            }
-        }
-    };
+    let cargo_build_env = cargo_build_env(args)?;
 
     eprintln!("🏗️ Building buildpack dependency graph...");
     let buildpack_dependency_graph = build_libcnb_buildpacks_dependency_graph(&workspace_root_path)
@@ -85,6 +71,21 @@ pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
         return Err(Error::NoBuildpacksFound);
     }
 
+    let buildpack_dirs = build_order
+        .iter()
+        .map(|node| node.path.as_path())
+        .collect::<Vec<_>>();
+
+    eprintln!("🏗️ Compiling libcnb.rs buildpacks...");
+    let buildpack_binaries = build_workspace_buildpack_binaries(
+        &workspace_root_path,
+        &buildpack_dirs,
+        cargo_profile,
+        &cargo_build_env,
+        &args.target,
+    )
+    .map_err(Error::CannotBuildBuildpackBinaries)?;
+
     eprintln!("🚚 Building {} buildpacks...", build_order.len());
     let mut packaged_buildpack_dirs = BTreeMap::new();
     for (node_index, node) in build_order.iter().enumerate() {
@@ -100,6 +101,7 @@ pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
 
         let buildpack_destination_dir = buildpack_dir_resolver(&node.buildpack_id);
         let _ = fs::remove_dir_all(&buildpack_destination_dir);
+        remove_signed_buildpack_files(&buildpack_destination_dir);
         fs::create_dir_all(&buildpack_destination_dir).map_err(|error| {
             Error::CannotCreateBuildpackDestinationDir(buildpack_destination_dir.clone(), error)
         })?;
@@ -111,6 +113,7 @@ pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
             &cargo_build_env,
             &buildpack_destination_dir,
             &packaged_buildpack_dirs,
+            Some(&buildpack_binaries),
         )
         .map_err(Error::CannotPackageBuildpack)?;
 
@@ -119,6 +122,8 @@ pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
         packaged_buildpack_dirs.insert(node.buildpack_id.clone(), buildpack_destination_dir);
     }
 
+    sign_packaged_buildpacks(args, cargo_profile, &packaged_buildpack_dirs, &current_dir)?;
+
     eprint_pack_command_hint(&packaged_buildpack_dirs, &current_dir);
 
     packaged_buildpack_dirs
@@ -131,6 +136,89 @@ pub(crate) fn execute(args: &PackageArgs) -> Result<(), Error> {
     Ok(())
 }
 
+fn cargo_build_env(args: &PackageArgs) -> Result<Vec<(OsString, OsString)>, Error> {
+    if args.no_cross_compile_assistance {
+        Ok(Vec::new())
+    } else {
+        match cross_compile_assistance(&args.target) {
+            CrossCompileAssistance::Configuration { cargo_env } => Ok(cargo_env),
+            CrossCompileAssistance::NoAssistance => {
+                eprintln!(
+                    "Couldn't determine automatic cross-compile settings for target triple {}.",
+                    args.target
+                );
+                eprintln!("This is not an error, but without proper cross-compile settings in your Cargo manifest and locally installed toolchains, compilation might fail.");
+                eprintln!("To disable this warning, pass --no-cross-compile-assistance.");
+                Ok(Vec::new())
+            }
+            CrossCompileAssistance::HelpText(help_text) => {
+                eprintln!("{help_text}");
+                Err(Error::CannotConfigureCrossCompilation)
+            }
+        }
+    }
+}
+
+fn signing_method(args: &PackageArgs) -> Option<SigningMethod> {
+    match &args.sign_key {
+        Some(key) => Some(SigningMethod::Key(key.clone())),
+        None => args.sign.then_some(SigningMethod::Keyless),
+    }
+}
+
+fn sign_packaged_buildpacks(
+    args: &PackageArgs,
+    cargo_profile: CargoProfile,
+    packaged_buildpack_dirs: &BTreeMap<BuildpackId, PathBuf>,
+    current_dir: &Path,
+) -> Result<(), Error> {
+    let Some(signing_method) = signing_method(args) else {
+        return Ok(());
+    };
+
+    for (buildpack_id, packaged_buildpack_dir) in packaged_buildpack_dirs {
+        eprintln!("🔏 Signing {buildpack_id}...");
+
+        let provenance = PackageProvenance {
+            buildpack_id: buildpack_id.clone(),
+            cargo_profile,
+            target_triple: args.target.clone(),
+            builder_id: format!(
+                "{}@{}",
+                env!("CARGO_PKG_REPOSITORY"),
+                env!("CARGO_PKG_VERSION")
+            ),
+        };
+
+        let signed_files =
+            sign_packaged_buildpack(packaged_buildpack_dir, &signing_method, &provenance)
+                .map_err(|error| Error::CannotSignBuildpack(buildpack_id.clone(), error))?;
+
+        eprintln!(
+            "Successfully wrote signature bundle: {}",
+            pathdiff::diff_paths(&signed_files.signature_bundle, current_dir)
+                .unwrap_or(signed_files.signature_bundle)
+                .to_string_lossy()
+        );
+    }
+
+    Ok(())
+}
+
+/// Removes the signature files of a previous packaging run, which no longer match the buildpack.
+fn remove_signed_buildpack_files(buildpack_dir: &Path) {
+    let signed_files = SignedBuildpackFiles::for_buildpack_dir(buildpack_dir);
+
+    for path in [
+        signed_files.digests,
+        signed_files.signature_bundle,
+        signed_files.provenance,
+        signed_files.attestation_bundle,
+    ] {
+        let _ = fs::remove_file(path);
+    }
+}
+
 fn eprint_pack_command_hint(
     packaged_buildpack_dirs: &BTreeMap<BuildpackId, PathBuf>,
     current_dir: &Path,
@@ -152,15 +240,14 @@ fn eprint_pack_command_hint(
 }
 
 fn eprint_compiled_buildpack_success(current_dir: &Path, target_dir: &Path) {
-    let size_string = calculate_dir_size(target_dir)
-        .map(|size_in_bytes| {
+    let size_string =
+        calculate_dir_size(target_dir).map_or(String::from("<unknown>"), |size_in_bytes| {
             // Precision will only be lost for sizes bigger than 52 bits (~4 Petabytes), and even
             // then will only result in a less precise figure, so is not an issue.
             #[allow(clippy::cast_precision_loss)]
             let size_in_mib = size_in_bytes as f64 / (1024.0 * 1024.0);
             format!("{size_in_mib:.2}")
-        })
-        .unwrap_or(String::from("<unknown>"));
+        });
 
     let relative_output_path =
         pathdiff::diff_paths(target_dir, current_dir).unwrap_or_else(|| target_dir.to_path_buf());
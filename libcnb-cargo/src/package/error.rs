@@ -1,7 +1,9 @@
 use libcnb_data::buildpack::BuildpackId;
+use libcnb_package::build::BuildBinariesError;
 use libcnb_package::buildpack_dependency_graph::BuildBuildpackDependencyGraphError;
 use libcnb_package::dependency_graph::GetDependenciesError;
 use libcnb_package::package::PackageBuildpackError;
+use libcnb_package::signing::SignBuildpackError;
 use std::path::PathBuf;
 
 #[derive(thiserror::Error, Debug)]
@@ -18,8 +20,12 @@ pub(crate) enum Error {
     CannotGetDependencies(#[source] GetDependenciesError<BuildpackId>),
     #[error("Failed to create buildpack package directory {0}: {1}")]
     CannotCreateBuildpackDestinationDir(PathBuf, #[source] std::io::Error),
+    #[error("Failed to build buildpack binaries: {0}")]
+    CannotBuildBuildpackBinaries(#[source] BuildBinariesError),
     #[error("Failed to package buildpack: {0}")]
     CannotPackageBuildpack(#[source] PackageBuildpackError),
+    #[error("Failed to sign buildpack {0}: {1}")]
+    CannotSignBuildpack(BuildpackId, #[source] SignBuildpackError),
     #[error("Failed to configure Cargo for cross-compilation")]
     CannotConfigureCrossCompilation,
     #[error("No buildpacks found!")]
@@ -0,0 +1,3 @@
+fn main() {
+    println!("Shared exec.d program");
+}
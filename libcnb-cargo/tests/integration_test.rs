@@ -128,6 +128,35 @@ fn package_single_buildpack_in_monorepo_buildpack_project() {
     validate_packaged_buildpack(&packaged_buildpack_dir, &buildpack_id);
 }
 
+#[test]
+#[ignore = "integration test"]
+fn package_buildpack_with_additional_bin_packages_in_monorepo_buildpack_project() {
+    let fixture_dir = copy_fixture_to_temp_dir("multiple_buildpacks").unwrap();
+    let buildpack_id = buildpack_id!("multiple-buildpacks/two");
+
+    let output = Command::new(CARGO_LIBCNB_BINARY_UNDER_TEST)
+        .args(["libcnb", "package", "--release"])
+        .current_dir(fixture_dir.path().join("buildpacks/two"))
+        .output()
+        .unwrap();
+
+    let packaged_buildpack_dir = create_packaged_buildpack_dir_resolver(
+        &fixture_dir.path().join(DEFAULT_PACKAGE_DIR_NAME),
+        CargoProfile::Release,
+        X86_64_UNKNOWN_LINUX_MUSL,
+    )(&buildpack_id);
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{}\n", packaged_buildpack_dir.to_string_lossy())
+    );
+
+    validate_packaged_buildpack(&packaged_buildpack_dir, &buildpack_id);
+    assert!(packaged_buildpack_dir
+        .join(".libcnb-cargo/additional-bin/shared_exec_d")
+        .exists());
+}
+
 #[test]
 #[ignore = "integration test"]
 fn package_all_buildpacks_in_monorepo_buildpack_project() {
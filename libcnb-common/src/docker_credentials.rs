@@ -0,0 +1,314 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::{env, fs, io};
+
+/// Credentials for a container registry, as stored by `docker login`.
+#[derive(Clone, Eq, PartialEq)]
+pub struct DockerCredentials {
+    pub username: String,
+    /// The password or, for identity tokens, the token.
+    pub secret: String,
+}
+
+impl DockerCredentials {
+    /// The username credential helpers return for identity tokens, which must be exchanged for
+    /// a registry token instead of being used for basic authentication.
+    pub const IDENTITY_TOKEN_USERNAME: &'static str = "<token>";
+
+    #[must_use]
+    pub fn is_identity_token(&self) -> bool {
+        self.username == Self::IDENTITY_TOKEN_USERNAME
+    }
+
+    /// The value of an HTTP `Authorization` header for basic authentication with these
+    /// credentials.
+    #[must_use]
+    pub fn basic_authorization(&self) -> String {
+        format!(
+            "Basic {}",
+            STANDARD.encode(format!("{}:{}", self.username, self.secret))
+        )
+    }
+}
+
+impl std::fmt::Debug for DockerCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DockerCredentials")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DockerCredentialsError {
+    #[error("Invalid Docker configuration {}: {1}", .0.display())]
+    InvalidConfig(PathBuf, serde_json::Error),
+
+    #[error("I/O error while reading Docker configuration {}: {1}", .0.display())]
+    ConfigIoError(PathBuf, io::Error),
+
+    #[error("Invalid credentials for registry {0} in Docker configuration")]
+    InvalidAuth(String),
+
+    #[error("Couldn't run credential helper docker-credential-{0}: {1}")]
+    CredentialHelperIoError(String, io::Error),
+
+    #[error("Credential helper docker-credential-{0} failed: {1}")]
+    CredentialHelperFailed(String, String),
+}
+
+/// The directory of the Docker configuration: `DOCKER_CONFIG` if set, `~/.docker` otherwise.
+#[must_use]
+pub fn docker_config_dir() -> Option<PathBuf> {
+    env::var_os("DOCKER_CONFIG")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".docker")))
+}
+
+/// Resolves the credentials for a registry, such as `ghcr.io` or `docker.io`, like `docker` and
+/// `pack` do.
+///
+/// The `config.json` in [`docker_config_dir`] is checked in this order:
+///
+/// 1. `credHelpers`, which map registries to credential helpers. A credential helper `<name>` is
+///    run as `docker-credential-<name> get`.
+/// 2. `auths`, which contains the credentials stored by `docker login` without a credential store.
+/// 3. `credsStore`, a credential helper for all registries.
+///
+/// Returns `None` if there is no configuration or no credentials for the registry.
+///
+/// # Errors
+///
+/// Will return `Err` if the configuration couldn't be read or is invalid, or a credential helper
+/// failed.
+pub fn resolve_docker_credentials(
+    registry: &str,
+) -> Result<Option<DockerCredentials>, DockerCredentialsError> {
+    docker_config_dir().map_or(Ok(None), |dir| {
+        resolve_docker_credentials_in(registry, &dir)
+    })
+}
+
+/// Like [`resolve_docker_credentials`], but reads the configuration from the given directory.
+///
+/// # Errors
+///
+/// Will return `Err` if the configuration couldn't be read or is invalid, or a credential helper
+/// failed.
+pub fn resolve_docker_credentials_in(
+    registry: &str,
+    docker_config_dir: &Path,
+) -> Result<Option<DockerCredentials>, DockerCredentialsError> {
+    let Some(config) = read_docker_config(&docker_config_dir.join("config.json"))? else {
+        return Ok(None);
+    };
+
+    let hosts = registry_hosts(registry);
+    let server_url = if hosts.contains(&DOCKER_HUB_REGISTRY) {
+        DOCKER_HUB_SERVER_URL
+    } else {
+        registry
+    };
+
+    if let Some(helper) = find_registry_entry(&config.cred_helpers, &hosts) {
+        return run_credential_helper(helper, server_url);
+    }
+
+    if let Some(auth) = find_registry_entry(&config.auths, &hosts)
+        .and_then(|entry| entry.auth.as_ref())
+        .filter(|auth| !auth.is_empty())
+    {
+        let invalid_auth = || DockerCredentialsError::InvalidAuth(String::from(registry));
+
+        return STANDARD
+            .decode(auth)
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|decoded| {
+                decoded
+                    .split_once(':')
+                    .map(|(username, secret)| DockerCredentials {
+                        username: String::from(username),
+                        secret: String::from(secret),
+                    })
+            })
+            .map(Some)
+            .ok_or_else(invalid_auth);
+    }
+
+    config.creds_store.map_or(Ok(None), |helper| {
+        run_credential_helper(&helper, server_url)
+    })
+}
+
+const DOCKER_HUB_REGISTRY: &str = "docker.io";
+const DOCKER_HUB_SERVER_URL: &str = "https://index.docker.io/v1/";
+
+// Docker Hub credentials are stored under several different names.
+fn registry_hosts(registry: &str) -> Vec<&str> {
+    if [
+        DOCKER_HUB_REGISTRY,
+        "index.docker.io",
+        "registry-1.docker.io",
+    ]
+    .contains(&registry)
+    {
+        vec![
+            DOCKER_HUB_REGISTRY,
+            "index.docker.io",
+            "registry-1.docker.io",
+        ]
+    } else {
+        vec![registry]
+    }
+}
+
+// Keys can be plain hosts (`ghcr.io`) or URLs (`https://index.docker.io/v1/`).
+fn find_registry_entry<'a, T>(entries: &'a HashMap<String, T>, hosts: &[&str]) -> Option<&'a T> {
+    entries.iter().find_map(|(key, value)| {
+        let host = key
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .split('/')
+            .next()
+            .unwrap_or_default();
+
+        hosts.contains(&host).then_some(value)
+    })
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, DockerConfigAuth>,
+    #[serde(default)]
+    cred_helpers: HashMap<String, String>,
+    creds_store: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DockerConfigAuth {
+    auth: Option<String>,
+}
+
+fn read_docker_config(path: &Path) -> Result<Option<DockerConfig>, DockerCredentialsError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|error| DockerCredentialsError::InvalidConfig(path.to_path_buf(), error)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(DockerCredentialsError::ConfigIoError(
+            path.to_path_buf(),
+            error,
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CredentialHelperOutput {
+    username: String,
+    secret: String,
+}
+
+fn run_credential_helper(
+    helper: &str,
+    server_url: &str,
+) -> Result<Option<DockerCredentials>, DockerCredentialsError> {
+    let io_error =
+        |error| DockerCredentialsError::CredentialHelperIoError(String::from(helper), error);
+
+    let mut child = Command::new(format!("docker-credential-{helper}"))
+        .arg("get")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(io_error)?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(server_url.as_bytes()).map_err(io_error)?;
+    }
+
+    let output = child.wait_with_output().map_err(io_error)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if output.status.success() {
+        serde_json::from_str::<CredentialHelperOutput>(&stdout)
+            .map(|output| {
+                Some(DockerCredentials {
+                    username: output.username,
+                    secret: output.secret,
+                })
+            })
+            .map_err(|error| {
+                DockerCredentialsError::CredentialHelperFailed(
+                    String::from(helper),
+                    error.to_string(),
+                )
+            })
+    } else if stdout.contains("credentials not found") {
+        Ok(None)
+    } else {
+        Err(DockerCredentialsError::CredentialHelperFailed(
+            String::from(helper),
+            format!("{}{}", stdout, String::from_utf8_lossy(&output.stderr))
+                .trim()
+                .to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_from_docker_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("config.json"),
+            r#"{
+                "auths": {
+                    "https://index.docker.io/v1/": {"auth": "dXNlcjpwYXNz"},
+                    "ghcr.io": {"auth": "invalid"},
+                    "quay.io": {}
+                },
+                "credHelpers": {"gcr.io": "libcnb-missing-helper"}
+            }"#,
+        )
+        .unwrap();
+
+        let credentials = resolve_docker_credentials_in("docker.io", temp_dir.path())
+            .unwrap()
+            .unwrap();
+        assert_eq!(credentials.username, "user");
+        assert_eq!(credentials.secret, "pass");
+        assert_eq!(credentials.basic_authorization(), "Basic dXNlcjpwYXNz");
+        assert!(!credentials.is_identity_token());
+
+        assert!(resolve_docker_credentials_in("quay.io", temp_dir.path())
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            resolve_docker_credentials_in("ghcr.io", temp_dir.path()),
+            Err(DockerCredentialsError::InvalidAuth(registry)) if registry == "ghcr.io"
+        ));
+        assert!(matches!(
+            resolve_docker_credentials_in("gcr.io", temp_dir.path()),
+            Err(DockerCredentialsError::CredentialHelperIoError(helper, _))
+                if helper == "libcnb-missing-helper"
+        ));
+        assert!(
+            resolve_docker_credentials_in("ghcr.io", &temp_dir.path().join("missing"))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
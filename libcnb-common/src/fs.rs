@@ -0,0 +1,140 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Atomically replaces the contents of the file at the given path.
+///
+/// The contents are written to a temporary file in the same directory, which is flushed to disk
+/// and then renamed to the given path. Readers therefore either see the previous or the new
+/// contents of the file, never a partially written file, even if the process is killed during
+/// the write. If the file already exists, its permissions are retained.
+///
+/// Since the file is replaced, writing to a symlink replaces the symlink itself rather than the
+/// file it points to.
+///
+/// # Examples
+/// ```
+/// use libcnb_common::fs::write_atomic;
+///
+/// # let temp_dir = tempfile::tempdir().unwrap();
+/// let path = temp_dir.path().join("launch.toml");
+/// write_atomic(&path, "[[processes]]").unwrap();
+///
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "[[processes]]");
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the temporary file couldn't be written, flushed or renamed. The temporary
+/// file is removed in that case and the file at the given path is left untouched.
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+
+    let mut temp_file_name = OsString::from(".");
+    temp_file_name.push(
+        path.file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name"))?,
+    );
+    temp_file_name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp_path = path.with_file_name(temp_file_name);
+
+    write_synced(&temp_path, path, contents.as_ref())
+        .and_then(|()| fs::rename(&temp_path, path))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp_path);
+        })?;
+
+    sync_parent_dir(path)
+}
+
+fn write_synced(temp_path: &Path, path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = File::create(temp_path)?;
+
+    match fs::metadata(path) {
+        Ok(metadata) => file.set_permissions(metadata.permissions())?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error),
+    }
+
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+// The rename is only durable once the directory entry is flushed to disk as well. Directories
+// can't be opened as files on Windows, where renames are flushed with the file system metadata.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    File::open(parent)?.sync_all()
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_atomic_replaces_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("store.toml");
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn write_atomic_leaves_file_untouched_on_error() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("layer.toml");
+        fs::write(&path, "original").unwrap();
+
+        // Renaming a file over a non-empty directory fails.
+        fs::create_dir(temp_dir.path().join("dir")).unwrap();
+        fs::write(temp_dir.path().join("dir/file"), "").unwrap();
+        assert!(write_atomic(temp_dir.path().join("dir"), "new").is_err());
+
+        assert!(write_atomic(temp_dir.path().join("missing/layer.toml"), "new").is_err());
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_retains_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("script");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+
+        write_atomic(&path, "#!/usr/bin/env bash").unwrap();
+
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o750
+        );
+    }
+}
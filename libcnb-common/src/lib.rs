@@ -1,3 +1,9 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "docker-credentials")]
+pub mod docker_credentials;
+pub mod fs;
 pub mod toml_file;
+
+// Re-exported so that users of the TOML document functions use the same version of `toml_edit`.
+pub use toml_edit;
@@ -1,5 +1,8 @@
+use crate::fs::write_atomic;
 use serde::{de::DeserializeOwned, Serialize};
-use std::{fs, path::Path};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
 
 /// An error that occurred during reading or writing a TOML file.
 #[derive(thiserror::Error, Debug)]
@@ -7,11 +10,20 @@ pub enum TomlFileError {
     #[error("I/O error while reading/writing TOML file: {0}")]
     IoError(#[from] std::io::Error),
 
-    #[error("TOML deserialization error while reading TOML file: {0}")]
-    TomlDeserializationError(#[from] toml::de::Error),
+    #[error("TOML deserialization error while reading TOML file {}:\n{source}", path.display())]
+    TomlDeserializationError {
+        path: PathBuf,
+        source: Box<toml::de::Error>,
+    },
 
     #[error("TOML serialization error while writing TOML file: {0}")]
     TomlSerializationError(#[from] toml::ser::Error),
+
+    #[error("TOML parse error while reading TOML file {}:\n{source}", path.display())]
+    TomlDocumentParseError {
+        path: PathBuf,
+        source: Box<toml_edit::TomlError>,
+    },
 }
 
 /// Serializes the given value as TOML and writes it to the given file path.
@@ -28,12 +40,208 @@ pub fn write_toml_file(
     Ok(())
 }
 
+/// Serializes the given value as TOML and atomically writes it to the given file path.
+///
+/// Unlike [`write_toml_file`], the file is never left partially written, even if the process is
+/// killed during the write. See [`write_atomic`] for details.
+///
+/// # Errors
+///
+/// Will return `Err` if the file couldn't be written or the value couldn't be serialized as a TOML string.
+pub fn write_toml_atomic(
+    value: &impl Serialize,
+    path: impl AsRef<Path>,
+) -> Result<(), TomlFileError> {
+    write_atomic(path, toml::to_string(value)?)?;
+
+    Ok(())
+}
+
 /// Reads the file at the given path and parses it as `A`.
 ///
 /// # Errors
 ///
-/// Will return `Err` if the file couldn't be read or its contents couldn't be deserialized.
+/// Will return `Err` if the file couldn't be read or its contents couldn't be deserialized. In the
+/// latter case, the error contains the path of the file and its message includes the line and
+/// column of the problem, together with a snippet of the offending TOML.
 pub fn read_toml_file<A: DeserializeOwned>(path: impl AsRef<Path>) -> Result<A, TomlFileError> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)?;
+
+    toml::from_str(&contents).map_err(|source| TomlFileError::TomlDeserializationError {
+        path: path.to_path_buf(),
+        source: Box::new(source),
+    })
+}
+
+/// Reads the file at the given path as an editable TOML document.
+///
+/// Unlike [`read_toml_file`], the document retains the formatting, comments and order of the
+/// file, so that it can be modified and written back with [`write_toml_document`] without
+/// unrelated changes to the file. Use [`edit_toml_file`] if the document is modified in place.
+///
+/// # Errors
+///
+/// Will return `Err` if the file couldn't be read or its contents aren't valid TOML. In the latter
+/// case, the error contains the path of the file.
+pub fn read_toml_document(path: impl AsRef<Path>) -> Result<DocumentMut, TomlFileError> {
+    let path = path.as_ref();
     let contents = fs::read_to_string(path)?;
-    Ok(toml::from_str(&contents)?)
+
+    contents
+        .parse()
+        .map_err(|source| TomlFileError::TomlDocumentParseError {
+            path: path.to_path_buf(),
+            source: Box::new(source),
+        })
+}
+
+/// Writes the given TOML document to the given file path, as is.
+///
+/// # Errors
+///
+/// Will return `Err` if the file couldn't be written.
+pub fn write_toml_document(
+    document: &DocumentMut,
+    path: impl AsRef<Path>,
+) -> Result<(), TomlFileError> {
+    fs::write(path, document.to_string())?;
+
+    Ok(())
+}
+
+/// Reads the TOML file at the given path, passes it to `f` for modification and writes it back.
+///
+/// Formatting, comments and the order of keys and tables are preserved for all parts of the
+/// document that aren't modified. This makes it suitable for tooling that updates files which are
+/// maintained by hand, such as bumping the version in `buildpack.toml`.
+///
+/// # Examples
+/// ```no_run
+/// use libcnb_common::toml_edit::value;
+/// use libcnb_common::toml_file::edit_toml_file;
+///
+/// edit_toml_file("buildpack.toml", |document| {
+///     document["buildpack"]["version"] = value("1.2.3");
+/// })
+/// .unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the file couldn't be read or written, or its contents aren't valid TOML.
+pub fn edit_toml_file<R>(
+    path: impl AsRef<Path>,
+    f: impl FnOnce(&mut DocumentMut) -> R,
+) -> Result<R, TomlFileError> {
+    let path = path.as_ref();
+    let mut document = read_toml_document(path)?;
+    let result = f(&mut document);
+    write_toml_document(&document, path)?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_toml_file_error_contains_path_and_snippet() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("buildpack.toml");
+        fs::write(&path, "api = \"0.10\"\n[buildpack\nid = \"foo\"\n").unwrap();
+
+        let error = read_toml_file::<toml::Table>(&path).unwrap_err();
+        let message = error.to_string();
+
+        assert!(matches!(
+            &error,
+            TomlFileError::TomlDeserializationError { path: error_path, .. } if error_path == &path
+        ));
+        assert!(message.contains(&path.display().to_string()));
+        assert!(message.contains("line 2, column 11"));
+        assert!(message.contains("2 | [buildpack"));
+    }
+
+    #[test]
+    fn write_toml_atomic_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("store.toml");
+        let table = toml::Table::from_iter([(String::from("foo"), toml::Value::from("bar"))]);
+
+        write_toml_atomic(&table, &path).unwrap();
+
+        assert_eq!(read_toml_file::<toml::Table>(&path).unwrap(), table);
+    }
+
+    #[test]
+    fn edit_toml_file_preserves_formatting_and_comments() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("package.toml");
+        fs::write(
+            &path,
+            r#"# Packaged by libcnb.rs
+[buildpack]
+uri = "."
+
+[[dependencies]]
+uri  =  "libcnb:heroku/jvm"   # The JVM buildpack
+
+[[dependencies]]
+uri = "docker://docker.io/heroku/procfile-cnb:2.0.0"
+"#,
+        )
+        .unwrap();
+
+        let rewritten = edit_toml_file(&path, |document| {
+            document["dependencies"]
+                .as_array_of_tables_mut()
+                .unwrap()
+                .iter_mut()
+                .filter(|dependency| {
+                    dependency["uri"]
+                        .as_str()
+                        .is_some_and(|uri| uri.starts_with("libcnb:"))
+                })
+                .map(|dependency| {
+                    let uri = dependency["uri"].as_value_mut().unwrap();
+                    let decor = uri.decor().clone();
+                    *uri = toml_edit::Value::from("../heroku_jvm");
+                    *uri.decor_mut() = decor;
+                })
+                .count()
+        })
+        .unwrap();
+
+        assert_eq!(rewritten, 1);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"# Packaged by libcnb.rs
+[buildpack]
+uri = "."
+
+[[dependencies]]
+uri  =  "../heroku_jvm"   # The JVM buildpack
+
+[[dependencies]]
+uri = "docker://docker.io/heroku/procfile-cnb:2.0.0"
+"#
+        );
+    }
+
+    #[test]
+    fn read_toml_document_error_contains_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("buildpack.toml");
+        fs::write(&path, "[buildpack\n").unwrap();
+
+        let error = read_toml_document(&path).unwrap_err();
+
+        assert!(matches!(
+            &error,
+            TomlFileError::TomlDocumentParseError { path: error_path, .. } if error_path == &path
+        ));
+        assert!(error.to_string().contains(&path.display().to_string()));
+    }
 }
@@ -0,0 +1,225 @@
+//! [`proptest`] strategies for the types of this crate, enabled by the `proptest` feature.
+//!
+//! The types implement [`Arbitrary`], so that [`any`] can be used to generate valid values in
+//! property tests of downstream crates, for example to test their own serialization and validation
+//! logic:
+//!
+//! ```
+//! use libcnb_data::launch::Launch;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(launch in any::<Launch>())| {
+//!     let launch_toml = toml::to_string(&launch).unwrap();
+//!     prop_assert!(toml::from_str::<Launch>(&launch_toml).is_ok());
+//! });
+//! ```
+//!
+//! Generated newtype values, such as [`BuildpackId`] or [`LayerName`], always pass the validation
+//! of their [`FromStr`](std::str::FromStr) implementations. They are limited to short, ASCII
+//! values that are also safe to use as file names.
+
+use crate::buildpack::{BuildpackId, BuildpackVersion};
+use crate::exec_d::ExecDProgramOutputKey;
+use crate::extension::ExtensionId;
+use crate::launch::{Label, Launch, Process, ProcessType, Slice, WorkingDirectory};
+use crate::layer::LayerName;
+use crate::layer_content_metadata::LayerTypes;
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::collection::vec;
+use proptest::option;
+use proptest::strategy::{BoxedStrategy, Just, Strategy};
+use proptest::string::string_regex;
+use std::path::PathBuf;
+
+/// Implements [`Arbitrary`] for a newtype, with values generated from the given regular
+/// expression and filtered by the validation of the newtype.
+///
+/// The regular expressions can't use the lookarounds of the validation regular expressions, so
+/// values such as reserved names are filtered instead.
+macro_rules! arbitrary_newtype {
+    ($name:ident, $regex:expr) => {
+        impl Arbitrary for $name {
+            type Parameters = ();
+            type Strategy = BoxedStrategy<Self>;
+
+            fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                string_regex($regex)
+                    .expect("Regular expression should be valid")
+                    .prop_filter_map(concat!("Invalid ", stringify!($name)), |value| {
+                        value.parse::<$name>().ok()
+                    })
+                    .boxed()
+            }
+        }
+    };
+}
+
+arbitrary_newtype!(
+    BuildpackId,
+    "[[:alnum:]][[:alnum:].-]{0,15}(/[[:alnum:]][[:alnum:].-]{0,15})?"
+);
+arbitrary_newtype!(
+    ExtensionId,
+    "[[:alnum:]][[:alnum:].-]{0,15}(/[[:alnum:]][[:alnum:].-]{0,15})?"
+);
+arbitrary_newtype!(LayerName, "[[:alnum:]_-][[:alnum:]._-]{0,31}");
+arbitrary_newtype!(ProcessType, "[[:alnum:]_-][[:alnum:]._-]{0,31}");
+arbitrary_newtype!(ExecDProgramOutputKey, "[A-Za-z_][A-Za-z0-9_-]{0,31}");
+
+impl Arbitrary for BuildpackVersion {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        (any::<u64>(), any::<u64>(), any::<u64>())
+            .prop_map(|(major, minor, patch)| Self::new(major, minor, patch))
+            .boxed()
+    }
+}
+
+impl Arbitrary for LayerTypes {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        (any::<bool>(), any::<bool>(), any::<bool>())
+            .prop_map(|(launch, build, cache)| Self {
+                launch,
+                build,
+                cache,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for WorkingDirectory {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        // Relative paths such as `.` would be read back as `WorkingDirectory::App`.
+        option::of(
+            string_regex("(/[[:alnum:]._-]{1,16}){1,4}")
+                .expect("Regular expression should be valid"),
+        )
+        .prop_map(|path| path.map_or(Self::App, |path| Self::Directory(PathBuf::from(path))))
+        .boxed()
+    }
+}
+
+impl Arbitrary for Process {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        (
+            any::<ProcessType>(),
+            vec(any::<String>(), 1..4),
+            vec(any::<String>(), 0..4),
+            any::<bool>(),
+            any::<WorkingDirectory>(),
+        )
+            .prop_map(|(r#type, command, args, default, working_directory)| Self {
+                r#type,
+                command,
+                args,
+                default,
+                working_directory,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Label {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        (any::<String>(), any::<String>())
+            .prop_map(|(key, value)| Self { key, value })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Slice {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        vec(any::<String>(), 0..4)
+            .prop_map(|path_globs| Self { path_globs })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Launch {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        (
+            vec(any::<Label>(), 0..4),
+            vec(any::<Process>(), 0..4),
+            vec(any::<Slice>(), 0..4),
+        )
+            .prop_flat_map(|(labels, processes, slices)| {
+                // At most one process may be the default process.
+                let process_count = processes.len();
+                (
+                    Just((labels, processes, slices)),
+                    option::of(0..process_count.max(1)),
+                )
+            })
+            .prop_map(|((labels, mut processes, slices), default_index)| {
+                for (index, process) in processes.iter_mut().enumerate() {
+                    process.default = Some(index) == default_index;
+                }
+
+                Self {
+                    labels,
+                    processes,
+                    slices,
+                }
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn newtypes_are_valid(
+            buildpack_id in any::<BuildpackId>(),
+            extension_id in any::<ExtensionId>(),
+            layer_name in any::<LayerName>(),
+            process_type in any::<ProcessType>(),
+            key in any::<ExecDProgramOutputKey>(),
+        ) {
+            prop_assert!(buildpack_id.parse::<BuildpackId>().is_ok());
+            prop_assert!(extension_id.parse::<ExtensionId>().is_ok());
+            prop_assert!(layer_name.parse::<LayerName>().is_ok());
+            prop_assert!(process_type.parse::<ProcessType>().is_ok());
+            prop_assert!(key.parse::<ExecDProgramOutputKey>().is_ok());
+        }
+
+        #[test]
+        fn buildpack_version_round_trip(version in any::<BuildpackVersion>()) {
+            prop_assert_eq!(version.to_string().parse::<BuildpackVersion>().unwrap(), version);
+        }
+
+        #[test]
+        fn launch_round_trip(launch in any::<Launch>()) {
+            prop_assert!(launch.processes.iter().filter(|process| process.default).count() <= 1);
+
+            let launch_toml = toml::to_string(&launch).unwrap();
+            let parsed = toml::from_str::<Launch>(&launch_toml).unwrap();
+            prop_assert_eq!(parsed.labels, launch.labels);
+            prop_assert_eq!(parsed.processes, launch.processes);
+            prop_assert_eq!(parsed.slices, launch.slices);
+        }
+    }
+}
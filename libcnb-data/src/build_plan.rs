@@ -4,6 +4,7 @@ use std::collections::VecDeque;
 use toml::value::Table;
 
 #[derive(Serialize, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema), schemars(default))]
 #[must_use]
 pub struct BuildPlan {
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -75,14 +76,18 @@ impl BuildPlanBuilder {
 }
 
 #[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Or {
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "schemars", schemars(default))]
     provides: Vec<Provide>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "schemars", schemars(default))]
     requires: Vec<Require>,
 }
 
 #[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Provide {
     name: String,
 }
@@ -94,8 +99,10 @@ impl Provide {
 }
 
 #[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Require {
     pub name: String,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::TomlTable"))]
     pub metadata: Table,
 }
 
@@ -131,6 +138,109 @@ impl<S: Into<String>> From<S> for Require {
     }
 }
 
+/// Constructs a [`BuildPlan`] from a declarative description of its provisions and requirements.
+///
+/// Each statement ends with a semicolon:
+///
+/// - `provides "name";` adds a provision, see [`BuildPlanBuilder::provides`].
+/// - `requires "name";` adds a requirement, see [`BuildPlanBuilder::requires`].
+/// - `requires "name" { key = value, ... };` adds a requirement with metadata. Keys are
+///   identifiers or string literals, values can be anything that converts into a TOML value.
+/// - `or;` starts an alternative build plan, see [`BuildPlanBuilder::or`].
+///
+/// Names must be string literals and are validated at compile time: passing an empty name or a
+/// name containing whitespace or control characters will yield a compilation error.
+///
+/// # Examples:
+/// ```
+/// use libcnb_data::build_plan;
+///
+/// let java_version = "17";
+///
+/// let build_plan = build_plan! {
+///     provides "jdk";
+///     requires "jdk" { version = java_version, "allow-prerelease" = false };
+///     or;
+///     provides "jre";
+///     requires "jre";
+/// };
+///
+/// assert_eq!(build_plan.provides.len(), 1);
+/// assert_eq!(build_plan.or.len(), 1);
+/// ```
+///
+/// ```compile_fail
+/// // This would not compile since the name contains whitespace.
+/// let build_plan = libcnb_data::build_plan! { provides "open jdk"; };
+/// ```
+#[macro_export]
+macro_rules! build_plan {
+    ($($statements:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut builder = $crate::build_plan::BuildPlanBuilder::new();
+        $crate::__build_plan_statements!(builder; $($statements)*);
+        builder.build()
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __build_plan_statements {
+    ($builder:ident;) => {};
+    ($builder:ident; provides $name:literal; $($rest:tt)*) => {
+        $builder = $builder.provides($crate::__build_plan_name!($name));
+        $crate::__build_plan_statements!($builder; $($rest)*);
+    };
+    ($builder:ident; requires $name:literal; $($rest:tt)*) => {
+        $builder = $builder.requires($crate::__build_plan_name!($name));
+        $crate::__build_plan_statements!($builder; $($rest)*);
+    };
+    ($builder:ident; requires $name:literal { $($key:tt = $value:expr),* $(,)? }; $($rest:tt)*) => {
+        $builder = $builder.requires({
+            let mut require = $crate::build_plan::Require::new($crate::__build_plan_name!($name));
+            $(
+                require.metadata.insert(
+                    ::std::string::String::from($crate::__build_plan_key!($key)),
+                    $crate::internals::toml::Value::from($value),
+                );
+            )*
+            require
+        });
+        $crate::__build_plan_statements!($builder; $($rest)*);
+    };
+    ($builder:ident; or; $($rest:tt)*) => {
+        $builder = $builder.or();
+        $crate::__build_plan_statements!($builder; $($rest)*);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __build_plan_name {
+    ($name:literal) => {
+        $crate::internals::verify_regex!(
+            r"^[^[:space:][:cntrl:]]+$",
+            $name,
+            $name,
+            compile_error!(concat!(
+                stringify!($name),
+                " is not a valid build plan name!"
+            ))
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __build_plan_key {
+    ($key:ident) => {
+        stringify!($key)
+    };
+    ($key:literal) => {
+        $key
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +272,43 @@ mod tests {
             Some(&toml::Value::String(String::from("bar")))
         );
     }
+
+    #[test]
+    fn build_plan_macro() {
+        let version = 17;
+
+        let build_plan = crate::build_plan! {
+            provides "jdk";
+            requires "jdk" { version = version, "vendor-name" = "heroku" };
+            or;
+            provides "jre";
+            requires "jre";
+        };
+
+        assert_eq!(
+            toml::to_string(&build_plan).unwrap(),
+            r#"[[provides]]
+name = "jdk"
+
+[[requires]]
+name = "jdk"
+
+[requires.metadata]
+vendor-name = "heroku"
+version = 17
+
+[[or]]
+
+[[or.provides]]
+name = "jre"
+
+[[or.requires]]
+name = "jre"
+
+[or.requires.metadata]
+"#
+        );
+
+        assert!(crate::build_plan! {}.provides.is_empty());
+    }
 }
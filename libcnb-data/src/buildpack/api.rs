@@ -1,11 +1,11 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
 /// The Buildpack API version.
 ///
 /// This MUST be in form `<major>.<minor>` or `<major>`, where `<major>` is equivalent to `<major>.0`.
-#[derive(Deserialize, Debug, Eq, PartialEq)]
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
 #[serde(try_from = "String")]
 pub struct BuildpackApi {
     pub major: u64,
@@ -37,6 +37,26 @@ impl Display for BuildpackApi {
     }
 }
 
+impl Serialize for BuildpackApi {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for BuildpackApi {
+    fn schema_name() -> String {
+        String::from("BuildpackApi")
+    }
+
+    fn json_schema(_generator: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        crate::schema::string_with_pattern(r"^\d+(\.\d+)?$")
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum BuildpackApiError {
     #[error("Invalid Buildpack API version: `{0}`")]
@@ -86,4 +86,16 @@ mod tests {
             Err(BuildpackIdError::InvalidValue(String::new()))
         );
     }
+
+    #[test]
+    fn buildpack_id_error_message() {
+        assert_eq!(
+            "heroku jvm".parse::<BuildpackId>().unwrap_err().to_string(),
+            r#"Invalid BuildpackId "heroku jvm": character ' ' at position 7 is not allowed, the value must match ^(?!(app|config|sbom)$)[[:alnum:]./-]+$"#
+        );
+        assert_eq!(
+            "app".parse::<BuildpackId>().unwrap_err().to_string(),
+            r#"Invalid BuildpackId "app": the value must match ^(?!(app|config|sbom)$)[[:alnum:]./-]+$"#
+        );
+    }
 }
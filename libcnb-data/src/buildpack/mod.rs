@@ -1,17 +1,22 @@
 mod api;
 mod id;
+mod order;
 mod stack;
 mod target;
 mod version;
 
 use crate::generic::GenericMetadata;
+use crate::lenient::{self, Lenient};
 use crate::sbom::SbomFormat;
 pub use api::*;
 pub use id::*;
-use serde::Deserialize;
+pub use order::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 pub use stack::*;
 use std::collections::HashSet;
 pub use target::*;
+use toml::Table;
 pub use version::*;
 
 /// Data structures for the Buildpack descriptor (buildpack.toml).
@@ -56,6 +61,11 @@ pub use version::*;
 /// };
 /// ```
 #[derive(Deserialize, Debug)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "BuildpackDescriptor", bound = "")
+)]
 #[serde(untagged)]
 pub enum BuildpackDescriptor<BM = GenericMetadata> {
     Component(ComponentBuildpackDescriptor<BM>),
@@ -69,6 +79,42 @@ impl<BM> BuildpackDescriptor<BM> {
             BuildpackDescriptor::Composite(descriptor) => &descriptor.buildpack,
         }
     }
+
+    /// Validates the cross-field requirements of the spec that deserialization does not enforce.
+    ///
+    /// See [`ComponentBuildpackDescriptor::validate`] and
+    /// [`CompositeBuildpackDescriptor::validate`] for the checks performed.
+    ///
+    /// # Errors
+    ///
+    /// Returns all problems found, not just the first one.
+    pub fn validate(&self) -> Result<(), Vec<BuildpackDescriptorError>> {
+        match self {
+            BuildpackDescriptor::Component(descriptor) => descriptor.validate(),
+            BuildpackDescriptor::Composite(descriptor) => descriptor.validate(),
+        }
+    }
+}
+
+impl<BM: DeserializeOwned> BuildpackDescriptor<BM> {
+    /// Parses a buildpack descriptor, collecting unknown fields instead of rejecting them.
+    ///
+    /// Descriptors with an `order` are parsed as composite buildpacks, all others as component
+    /// buildpacks. See the [`lenient`](crate::lenient) module for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the string is not valid TOML or not a valid buildpack descriptor,
+    /// other than containing unknown fields.
+    pub fn from_str_lenient(toml_str: &str) -> Result<Lenient<Self>, toml::de::Error> {
+        let table = toml::from_str::<Table>(toml_str)?;
+
+        if table.contains_key("order") {
+            lenient::from_table(table).map(|descriptor| descriptor.map(Self::Composite))
+        } else {
+            lenient::from_table(table).map(|descriptor| descriptor.map(Self::Component))
+        }
+    }
 }
 
 /// Data structure for the Buildpack descriptor (buildpack.toml) of a component buildpack.
@@ -117,6 +163,11 @@ impl<BM> BuildpackDescriptor<BM> {
 /// );
 /// ```
 #[derive(Deserialize, Debug)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "ComponentBuildpackDescriptor", bound = "")
+)]
 #[serde(deny_unknown_fields)]
 pub struct ComponentBuildpackDescriptor<BM = GenericMetadata> {
     pub api: BuildpackApi,
@@ -125,6 +176,10 @@ pub struct ComponentBuildpackDescriptor<BM = GenericMetadata> {
     pub stacks: Vec<Stack>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub targets: Vec<BuildpackTarget>,
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "Option<crate::schema::TomlTable>")
+    )]
     pub metadata: BM,
     // As of 2024-02-09, the CNB spec does not forbid component buildpacks
     // to contain `order`. This is a change from buildpack API 0.9 where `order`
@@ -132,6 +187,37 @@ pub struct ComponentBuildpackDescriptor<BM = GenericMetadata> {
     // We believe this to be a spec error and libcnb.rs does intentionally not support this.
 }
 
+impl<BM> ComponentBuildpackDescriptor<BM> {
+    /// Validates the cross-field requirements of the spec that deserialization does not enforce.
+    ///
+    /// Every license must have a `type` or a `uri`, `stacks` and `targets` must not both be
+    /// declared and every target must be valid (see [`BuildpackTarget::validate`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns all problems found, not just the first one.
+    pub fn validate(&self) -> Result<(), Vec<BuildpackDescriptorError>> {
+        let mut errors = self.buildpack.license_errors();
+
+        if !self.stacks.is_empty() && !self.targets.is_empty() {
+            errors.push(BuildpackDescriptorError::StacksAndTargets);
+        }
+
+        errors.extend(
+            self.targets
+                .iter()
+                .filter_map(|target| target.validate().err())
+                .map(BuildpackDescriptorError::InvalidTarget),
+        );
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 /// Data structure for the Buildpack descriptor (buildpack.toml) of a composite buildpack.
 ///
 /// Representation of [buildpack.toml](https://github.com/buildpacks/spec/blob/main/buildpack.md#buildpacktoml-toml)
@@ -173,11 +259,20 @@ pub struct ComponentBuildpackDescriptor<BM = GenericMetadata> {
 /// assert_eq!(buildpack_descriptor.buildpack.id, buildpack_id!("foo/bar"));
 /// ```
 #[derive(Deserialize, Debug)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "CompositeBuildpackDescriptor", bound = "")
+)]
 #[serde(deny_unknown_fields)]
 pub struct CompositeBuildpackDescriptor<BM = GenericMetadata> {
     pub api: BuildpackApi,
     pub buildpack: Buildpack,
     pub order: Vec<Order>,
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "Option<crate::schema::TomlTable>")
+    )]
     pub metadata: BM,
     // As of 2024-02-09, the CNB spec does not forbid composite buildpacks
     // to contain `targets`. This is a change from buildpack API 0.9 where `stack`
@@ -185,7 +280,43 @@ pub struct CompositeBuildpackDescriptor<BM = GenericMetadata> {
     // We believe this to be a spec error and libcnb.rs does intentionally not support this.
 }
 
+impl<BM> CompositeBuildpackDescriptor<BM> {
+    /// Validates the cross-field requirements of the spec that deserialization does not enforce.
+    ///
+    /// Every license must have a `type` or a `uri` and the buildpack must declare at least one
+    /// order. Every order must have a non-empty group that references each buildpack at most
+    /// once (see [`Order::validate`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns all problems found, not just the first one.
+    pub fn validate(&self) -> Result<(), Vec<BuildpackDescriptorError>> {
+        let mut errors = self.buildpack.license_errors();
+
+        if self.order.is_empty() {
+            errors.push(BuildpackDescriptorError::EmptyOrder);
+        }
+
+        for (index, order) in self.order.iter().enumerate() {
+            if order.group.is_empty() {
+                errors.push(BuildpackDescriptorError::EmptyGroup(index));
+            }
+
+            if let Err(error) = order.validate() {
+                errors.push(BuildpackDescriptorError::InvalidOrder(error));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Buildpack {
     pub id: BuildpackId,
@@ -207,26 +338,40 @@ pub struct Buildpack {
     pub sbom_formats: HashSet<SbomFormat>,
 }
 
-#[derive(Deserialize, Debug, Eq, PartialEq)]
+impl Buildpack {
+    fn license_errors(&self) -> Vec<BuildpackDescriptorError> {
+        self.licenses
+            .iter()
+            .enumerate()
+            .filter(|(_, license)| license.r#type.is_none() && license.uri.is_none())
+            .map(|(index, _)| BuildpackDescriptorError::LicenseWithoutTypeOrUri(index))
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct License {
     pub r#type: Option<String>,
     pub uri: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Eq, PartialEq)]
-#[serde(deny_unknown_fields)]
-pub struct Order {
-    pub group: Vec<Group>,
-}
-
-#[derive(Deserialize, Debug, Eq, PartialEq)]
-#[serde(deny_unknown_fields)]
-pub struct Group {
-    pub id: BuildpackId,
-    pub version: BuildpackVersion,
-    #[serde(default)]
-    pub optional: bool,
+/// A problem found when validating a buildpack descriptor.
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum BuildpackDescriptorError {
+    #[error("License at index {0} has neither a type nor a URI")]
+    LicenseWithoutTypeOrUri(usize),
+    #[error("Buildpack declares both stacks and targets")]
+    StacksAndTargets,
+    #[error("Composite buildpack declares no order")]
+    EmptyOrder,
+    #[error("Order at index {0} has an empty group")]
+    EmptyGroup(usize),
+    #[error("Invalid target: {0}")]
+    InvalidTarget(BuildpackTargetError),
+    #[error("Invalid order: {0}")]
+    InvalidOrder(OrderError),
 }
 
 #[cfg(test)]
@@ -360,19 +505,19 @@ checksum = "abc123"
             buildpack_descriptor.stacks,
             [
                 Stack {
-                    id: String::from("heroku-20"),
+                    id: "heroku-20".parse().unwrap(),
                     mixins: Vec::new(),
                 },
                 Stack {
-                    id: String::from("io.buildpacks.stacks.bionic"),
+                    id: "io.buildpacks.stacks.bionic".parse().unwrap(),
                     mixins: Vec::new(),
                 },
                 Stack {
-                    id: String::from("io.buildpacks.stacks.focal"),
+                    id: "io.buildpacks.stacks.focal".parse().unwrap(),
                     mixins: vec![String::from("build:jq"), String::from("wget")]
                 },
                 Stack {
-                    id: String::from("*"),
+                    id: "*".parse().unwrap(),
                     mixins: Vec::new()
                 }
             ]
@@ -699,4 +844,183 @@ version = "0.0.1"
         let err = toml::from_str::<CompositeBuildpackDescriptor>(toml_str).unwrap_err();
         assert!(err.to_string().contains("unknown field `targets`"));
     }
+
+    #[test]
+    fn validate_valid_buildpacks() {
+        let component = r#"
+api = "0.10"
+
+[buildpack]
+id = "foo/bar"
+version = "0.0.1"
+
+[[buildpack.licenses]]
+type = "BSD-3-Clause"
+
+[[targets]]
+os = "linux"
+"#;
+
+        let composite = r#"
+api = "0.10"
+
+[buildpack]
+id = "foo/bar"
+version = "0.0.1"
+
+[[buildpack.licenses]]
+uri = "https://example.tld/LICENSE"
+
+[[order]]
+
+[[order.group]]
+id = "foo/baz"
+version = "0.0.1"
+"#;
+
+        for toml_str in [component, composite] {
+            let buildpack_descriptor = toml::from_str::<BuildpackDescriptor>(toml_str).unwrap();
+            assert_eq!(buildpack_descriptor.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn validate_invalid_component_buildpack() {
+        let toml_str = r#"
+api = "0.10"
+
+[buildpack]
+id = "foo/bar"
+version = "0.0.1"
+
+[[buildpack.licenses]]
+type = "BSD-3-Clause"
+
+[[buildpack.licenses]]
+
+[[stacks]]
+id = "*"
+
+[[targets]]
+os = "linux"
+"#;
+
+        let mut buildpack_descriptor =
+            toml::from_str::<ComponentBuildpackDescriptor>(toml_str).unwrap();
+        buildpack_descriptor.targets.push(BuildpackTarget {
+            os: Some(String::from("linux")),
+            arch: None,
+            variant: Some(String::from("v8")),
+            distros: Vec::new(),
+        });
+
+        assert_eq!(
+            buildpack_descriptor.validate(),
+            Err(vec![
+                BuildpackDescriptorError::LicenseWithoutTypeOrUri(1),
+                BuildpackDescriptorError::StacksAndTargets,
+                BuildpackDescriptorError::InvalidTarget(BuildpackTargetError::VariantWithoutArch(
+                    String::from("v8")
+                )),
+            ])
+        );
+    }
+
+    #[test]
+    fn validate_invalid_composite_buildpack() {
+        let toml_str = r#"
+api = "0.10"
+
+[buildpack]
+id = "foo/bar"
+version = "0.0.1"
+
+[[order]]
+group = []
+
+[[order]]
+
+[[order.group]]
+id = "foo/baz"
+version = "0.0.1"
+
+[[order.group]]
+id = "foo/baz"
+version = "0.0.2"
+"#;
+
+        let buildpack_descriptor = toml::from_str::<BuildpackDescriptor>(toml_str).unwrap();
+
+        assert_eq!(
+            buildpack_descriptor.validate(),
+            Err(vec![
+                BuildpackDescriptorError::EmptyGroup(0),
+                BuildpackDescriptorError::InvalidOrder(OrderError::DuplicateBuildpackId(
+                    "foo/baz".parse().unwrap()
+                )),
+            ])
+        );
+
+        let toml_str = r#"
+api = "0.10"
+order = []
+
+[buildpack]
+id = "foo/bar"
+version = "0.0.1"
+"#;
+
+        let buildpack_descriptor =
+            toml::from_str::<CompositeBuildpackDescriptor>(toml_str).unwrap();
+
+        assert_eq!(
+            buildpack_descriptor.validate(),
+            Err(vec![BuildpackDescriptorError::EmptyOrder])
+        );
+    }
+
+    #[test]
+    fn deserialize_buildpackdescriptor_lenient() {
+        let toml_str = r#"
+api = "0.10"
+
+[buildpack]
+id = "foo/bar"
+version = "0.0.1"
+future-field = true
+
+[[order]]
+
+[[order.group]]
+id = "foo/baz"
+version = "0.0.1"
+future-field = true
+"#;
+
+        assert!(toml::from_str::<BuildpackDescriptor>(toml_str).is_err());
+
+        let buildpack_descriptor =
+            BuildpackDescriptor::<GenericMetadata>::from_str_lenient(toml_str).unwrap();
+        assert!(matches!(
+            buildpack_descriptor.value,
+            BuildpackDescriptor::Composite(_)
+        ));
+        assert_eq!(
+            buildpack_descriptor
+                .unknown_fields
+                .keys()
+                .collect::<Vec<_>>(),
+            ["buildpack.future-field", "order[0].group[0].future-field"]
+        );
+
+        let buildpack_descriptor = BuildpackDescriptor::<GenericMetadata>::from_str_lenient(
+            "api = \"0.10\"\n\n[buildpack]\nid = \"foo/bar\"\nversion = \"0.0.1\"\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            buildpack_descriptor.value,
+            BuildpackDescriptor::Component(_)
+        ));
+        assert!(buildpack_descriptor.unknown_fields.is_empty());
+    }
 }
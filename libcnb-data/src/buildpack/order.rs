@@ -0,0 +1,224 @@
+use crate::buildpack::{BuildpackId, BuildpackVersion};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+#[derive(Deserialize, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Order {
+    pub group: Vec<Group>,
+}
+
+impl Order {
+    /// Validates that no buildpack is referenced more than once in the group of this order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OrderError::DuplicateBuildpackId`] for the first buildpack that is referenced
+    /// more than once.
+    pub fn validate(&self) -> Result<(), OrderError> {
+        let mut ids = HashSet::new();
+
+        self.group
+            .iter()
+            .find(|group| !ids.insert(&group.id))
+            .map_or(Ok(()), |group| {
+                Err(OrderError::DuplicateBuildpackId(group.id.clone()))
+            })
+    }
+}
+
+#[derive(Deserialize, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Group {
+    pub id: BuildpackId,
+    pub version: BuildpackVersion,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Detects circular references between composite buildpacks, starting at the given buildpack.
+///
+/// `orders_of` returns the order of the composite buildpack with the given id, or `None` for
+/// component buildpacks and buildpacks that are unknown to the caller, which are not followed.
+///
+/// # Errors
+///
+/// Returns [`OrderError::CircularReference`] with the buildpack ids that form the first cycle
+/// found, starting and ending with the same id.
+///
+/// # Examples
+/// ```
+/// use libcnb_data::buildpack::{detect_circular_references, Group, Order, OrderError};
+/// use libcnb_data::buildpack::BuildpackVersion;
+/// use libcnb_data::buildpack_id;
+///
+/// let order = |id| {
+///     vec![Order {
+///         group: vec![Group {
+///             id,
+///             version: BuildpackVersion::new(1, 0, 0),
+///             optional: false,
+///         }],
+///     }]
+/// };
+///
+/// let a = order(buildpack_id!("example/b"));
+/// let b = order(buildpack_id!("example/a"));
+///
+/// let result = detect_circular_references(&buildpack_id!("example/a"), |id| {
+///     match id.as_str() {
+///         "example/a" => Some(a.as_slice()),
+///         "example/b" => Some(b.as_slice()),
+///         _ => None,
+///     }
+/// });
+///
+/// assert_eq!(
+///     result,
+///     Err(OrderError::CircularReference(vec![
+///         buildpack_id!("example/a"),
+///         buildpack_id!("example/b"),
+///         buildpack_id!("example/a"),
+///     ]))
+/// );
+/// ```
+pub fn detect_circular_references<'a, F>(
+    buildpack_id: &BuildpackId,
+    orders_of: F,
+) -> Result<(), OrderError>
+where
+    F: Fn(&BuildpackId) -> Option<&'a [Order]>,
+{
+    fn visit<'a>(
+        buildpack_id: &BuildpackId,
+        orders_of: &dyn Fn(&BuildpackId) -> Option<&'a [Order]>,
+        path: &mut Vec<BuildpackId>,
+        finished: &mut HashSet<BuildpackId>,
+    ) -> Result<(), OrderError> {
+        if let Some(position) = path.iter().position(|id| id == buildpack_id) {
+            let mut cycle = path[position..].to_vec();
+            cycle.push(buildpack_id.clone());
+            return Err(OrderError::CircularReference(cycle));
+        }
+
+        if finished.contains(buildpack_id) {
+            return Ok(());
+        }
+
+        path.push(buildpack_id.clone());
+        for order in orders_of(buildpack_id).unwrap_or_default() {
+            for group in &order.group {
+                visit(&group.id, orders_of, path, finished)?;
+            }
+        }
+        path.pop();
+
+        finished.insert(buildpack_id.clone());
+        Ok(())
+    }
+
+    visit(
+        buildpack_id,
+        &orders_of,
+        &mut Vec::new(),
+        &mut HashSet::new(),
+    )
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum OrderError {
+    #[error("Buildpack `{0}` is referenced more than once in the same group")]
+    DuplicateBuildpackId(BuildpackId),
+    #[error("Circular buildpack reference: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(" -> "))]
+    CircularReference(Vec<BuildpackId>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn group(id: &str) -> Group {
+        Group {
+            id: id.parse().unwrap(),
+            version: BuildpackVersion::new(1, 0, 0),
+            optional: false,
+        }
+    }
+
+    #[test]
+    fn validate_duplicate_ids() {
+        let order = Order {
+            group: vec![group("example/a"), group("example/b")],
+        };
+        assert_eq!(order.validate(), Ok(()));
+
+        let order = Order {
+            group: vec![group("example/a"), group("example/b"), group("example/a")],
+        };
+        assert_eq!(
+            order.validate(),
+            Err(OrderError::DuplicateBuildpackId(
+                "example/a".parse().unwrap()
+            ))
+        );
+        assert_eq!(
+            order.validate().unwrap_err().to_string(),
+            "Buildpack `example/a` is referenced more than once in the same group"
+        );
+    }
+
+    #[test]
+    fn detect_cycles() {
+        let orders: HashMap<BuildpackId, Vec<Order>> = [
+            (
+                "example/root",
+                vec![
+                    Order {
+                        group: vec![group("example/a"), group("example/component")],
+                    },
+                    Order {
+                        group: vec![group("example/b")],
+                    },
+                ],
+            ),
+            (
+                "example/a",
+                vec![Order {
+                    group: vec![group("example/component")],
+                }],
+            ),
+            (
+                "example/b",
+                vec![Order {
+                    group: vec![group("example/a"), group("example/c")],
+                }],
+            ),
+            (
+                "example/c",
+                vec![Order {
+                    group: vec![group("example/b")],
+                }],
+            ),
+        ]
+        .into_iter()
+        .map(|(id, orders)| (id.parse().unwrap(), orders))
+        .collect();
+
+        let orders_of = |id: &BuildpackId| orders.get(id).map(Vec::as_slice);
+
+        assert_eq!(
+            detect_circular_references(&"example/a".parse().unwrap(), orders_of),
+            Ok(())
+        );
+
+        let error =
+            detect_circular_references(&"example/root".parse().unwrap(), orders_of).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Circular buildpack reference: example/b -> example/c -> example/b"
+        );
+    }
+}
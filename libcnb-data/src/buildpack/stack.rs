@@ -1,16 +1,60 @@
+use crate::newtypes::libcnb_newtype;
 use serde::Deserialize;
 
 // Stacks are deprecated in Buildpack API 0.10, and libcnb.rs effectively
 // ignores them. However, they are still supported by the Buildpack API, so
 // libcnb should continue to allow them to exist in buildpack.toml.
 #[derive(Deserialize, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Stack {
-    pub id: String,
+    pub id: StackId,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub mixins: Vec<String>,
 }
 
+libcnb_newtype!(
+    buildpack,
+    /// Construct a [`StackId`] value at compile time.
+    ///
+    /// Passing a string that is not a valid `StackId` value will yield a compilation error.
+    ///
+    /// # Examples:
+    /// ```
+    /// use libcnb_data::buildpack::StackId;
+    /// use libcnb_data::stack_id;
+    ///
+    /// let stack_id: StackId = stack_id!("heroku-20");
+    /// ```
+    stack_id,
+    /// The ID of a stack.
+    ///
+    /// It MUST only contain numbers, letters, and the characters `.`, `/`, and `-`, or be `*`,
+    /// which matches any stack.
+    ///
+    /// Use the [`stack_id`](crate::stack_id) macro to construct a `StackId` from a literal
+    /// string. To parse a dynamic string into a `StackId`, use [`str::parse`](str::parse).
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb_data::buildpack::StackId;
+    /// use libcnb_data::stack_id;
+    ///
+    /// let from_literal = stack_id!("io.buildpacks.stacks.jammy");
+    ///
+    /// let input = "io.buildpacks.stacks.jammy";
+    /// let from_dynamic: StackId = input.parse().unwrap();
+    /// assert_eq!(from_dynamic, from_literal);
+    ///
+    /// let input = "io.buildpacks.stacks:jammy";
+    /// let invalid: Result<StackId, _> = input.parse();
+    /// assert!(invalid.is_err());
+    /// ```
+    StackId,
+    StackIdError,
+    r"^([[:alnum:]./-]+|\*)$"
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -23,7 +67,7 @@ id = "heroku-20"
         assert_eq!(
             toml::from_str::<Stack>(toml_str),
             Ok(Stack {
-                id: String::from("heroku-20"),
+                id: stack_id!("heroku-20"),
                 mixins: Vec::new()
             }),
         );
@@ -38,7 +82,7 @@ mixins = ["build:jq", "wget"]
         assert_eq!(
             toml::from_str::<Stack>(toml_str),
             Ok(Stack {
-                id: String::from("io.buildpacks.stacks.focal"),
+                id: stack_id!("io.buildpacks.stacks.focal"),
                 mixins: vec![String::from("build:jq"), String::from("wget")]
             }),
         );
@@ -52,9 +96,27 @@ id = "*"
         assert_eq!(
             toml::from_str::<Stack>(toml_str),
             Ok(Stack {
-                id: String::from("*"),
+                id: stack_id!("*"),
                 mixins: Vec::new(),
             }),
         );
     }
+
+    #[test]
+    fn stack_id_validation() {
+        assert!("heroku-20".parse::<StackId>().is_ok());
+        assert!("*".parse::<StackId>().is_ok());
+
+        for invalid in ["", "heroku:20", "heroku 20", "heroku-*"] {
+            assert_eq!(
+                invalid.parse::<StackId>(),
+                Err(StackIdError::InvalidValue(String::from(invalid)))
+            );
+        }
+    }
+
+    #[test]
+    fn deserialize_invalid_stack_id() {
+        assert!(toml::from_str::<Stack>("id = \"heroku:20\"").is_err());
+    }
 }
@@ -1,7 +1,16 @@
 use serde::Deserialize;
 
+/// A target that a buildpack supports, from the `[[targets]]` table of the buildpack descriptor.
+///
+/// All fields are optional, a missing field matches any value. Targets are validated when
+/// deserialized, see [`BuildpackTarget::validate`].
 #[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
-#[serde(deny_unknown_fields)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(try_from = "UnvalidatedBuildpackTarget")]
 pub struct BuildpackTarget {
     pub os: Option<String>,
     pub arch: Option<String>,
@@ -10,9 +19,91 @@ pub struct BuildpackTarget {
     pub distros: Vec<Distro>,
 }
 
+impl BuildpackTarget {
+    /// Validates the target.
+    ///
+    /// A `variant` is only valid together with an `arch`, and `distros` are only valid for
+    /// operating systems other than `windows`, since Windows has no distributions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first invalid field.
+    pub fn validate(&self) -> Result<(), BuildpackTargetError> {
+        if self.arch.is_none() {
+            if let Some(variant) = &self.variant {
+                return Err(BuildpackTargetError::VariantWithoutArch(variant.clone()));
+            }
+        }
+
+        if self.os.as_deref() == Some("windows") && !self.distros.is_empty() {
+            return Err(BuildpackTargetError::WindowsDistros);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UnvalidatedBuildpackTarget {
+    os: Option<String>,
+    arch: Option<String>,
+    variant: Option<String>,
+    #[serde(default)]
+    distros: Vec<Distro>,
+}
+
+impl TryFrom<UnvalidatedBuildpackTarget> for BuildpackTarget {
+    type Error = BuildpackTargetError;
+
+    fn try_from(value: UnvalidatedBuildpackTarget) -> Result<Self, Self::Error> {
+        let target = Self {
+            os: value.os,
+            arch: value.arch,
+            variant: value.variant,
+            distros: value.distros,
+        };
+
+        target.validate().map(|()| target)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum BuildpackTargetError {
+    #[error("Target has variant `{0}` but no arch")]
+    VariantWithoutArch(String),
+    #[error("Windows targets must not have distros")]
+    WindowsDistros,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Distro {
     pub name: String,
     pub version: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_invalid_targets() {
+        let error =
+            toml::from_str::<BuildpackTarget>("os = \"linux\"\nvariant = \"v8\"").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Target has variant `v8` but no arch"));
+
+        let error = toml::from_str::<BuildpackTarget>(
+            "os = \"windows\"\n[[distros]]\nname = \"ubuntu\"\nversion = \"24.04\"",
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Windows targets must not have distros"));
+
+        assert!(toml::from_str::<BuildpackTarget>("platform = \"linux\"").is_err());
+    }
+}
@@ -1,12 +1,24 @@
 use serde::Deserialize;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 /// The Buildpack version.
 ///
 /// This MUST be in the form `<X>.<Y>.<Z>` where `X`, `Y`, and `Z` are non-negative integers
 /// and must not contain leading zeros.
-#[derive(Deserialize, Debug, Eq, PartialEq)]
+///
+/// Versions are ordered by their major, minor and patch version, in that order. To check a version
+/// against a semver requirement, use [`BuildpackVersionRequirement`].
+///
+/// # Examples
+/// ```
+/// use libcnb_data::buildpack::BuildpackVersion;
+///
+/// let cached_version: BuildpackVersion = "1.9.0".parse().unwrap();
+/// assert!(cached_version < BuildpackVersion::new(1, 10, 0));
+/// ```
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 #[serde(try_from = "String")]
 pub struct BuildpackVersion {
     pub major: u64,
@@ -51,18 +63,126 @@ impl TryFrom<String> for BuildpackVersion {
     }
 }
 
+impl FromStr for BuildpackVersion {
+    type Err = BuildpackVersionError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::try_from(String::from(value))
+    }
+}
+
 impl Display for BuildpackVersion {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         formatter.write_str(&format!("{}.{}.{}", self.major, self.minor, self.patch))
     }
 }
 
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for BuildpackVersion {
+    fn schema_name() -> String {
+        String::from("BuildpackVersion")
+    }
+
+    fn json_schema(_generator: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        crate::schema::string_with_pattern(r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)$")
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum BuildpackVersionError {
     #[error("Invalid buildpack version: `{0}`")]
     InvalidBuildpackVersion(String),
 }
 
+/// A semver version requirement that [`BuildpackVersion`]s can be matched against.
+///
+/// A requirement is a comma-separated list of comparators, all of which must match. Requirements
+/// are parsed with the [`semver`](https://docs.rs/semver) crate, so the syntax and semantics follow [Cargo's version requirements](https://doc.rust-lang.org/cargo/reference/specifying-dependencies.html#version-requirement-syntax):
+///
+/// - `^1.2.3` or `1.2.3`: compatible versions, `>=1.2.3, <2.0.0`.
+/// - `~1.2.3`: patch updates only, `>=1.2.3, <1.3.0`.
+/// - `=1.2`, `1.2.*`: any version starting with `1.2`.
+/// - `>1.2.3`, `>=1.2.3`, `<1.2.3`, `<=1.2.3`: comparisons. Missing components are treated
+///   as wildcards, so `<=1.2` matches `1.2.9`.
+/// - `*`: any version.
+///
+/// # Examples
+/// ```
+/// use libcnb_data::buildpack::{BuildpackVersion, BuildpackVersionRequirement};
+///
+/// let requirement: BuildpackVersionRequirement = ">=1.2, <2".parse().unwrap();
+///
+/// assert!(requirement.matches(&BuildpackVersion::new(1, 2, 0)));
+/// assert!(requirement.matches(&BuildpackVersion::new(1, 99, 0)));
+/// assert!(!requirement.matches(&BuildpackVersion::new(1, 1, 9)));
+/// assert!(!requirement.matches(&BuildpackVersion::new(2, 0, 0)));
+/// ```
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(try_from = "String")]
+pub struct BuildpackVersionRequirement {
+    requirement: String,
+    version_req: semver::VersionReq,
+}
+
+impl BuildpackVersionRequirement {
+    /// Returns true if the given version matches all comparators of this requirement.
+    #[must_use]
+    pub fn matches(&self, version: &BuildpackVersion) -> bool {
+        self.version_req.matches(&semver::Version::new(
+            version.major,
+            version.minor,
+            version.patch,
+        ))
+    }
+}
+
+impl FromStr for BuildpackVersionRequirement {
+    type Err = BuildpackVersionRequirementError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let requirement = value.trim();
+
+        // Buildpack versions never have pre-release suffixes, so requirements for them could
+        // never match.
+        let version_req = semver::VersionReq::parse(requirement)
+            .ok()
+            .filter(|version_req| {
+                version_req
+                    .comparators
+                    .iter()
+                    .all(|comparator| comparator.pre.is_empty())
+            })
+            .ok_or_else(|| {
+                BuildpackVersionRequirementError::InvalidRequirement(String::from(value))
+            })?;
+
+        Ok(Self {
+            requirement: String::from(requirement),
+            version_req,
+        })
+    }
+}
+
+impl TryFrom<String> for BuildpackVersionRequirement {
+    type Error = BuildpackVersionRequirementError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for BuildpackVersionRequirement {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str(&self.requirement)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BuildpackVersionRequirementError {
+    #[error("Invalid buildpack version requirement: `{0}`")]
+    InvalidRequirement(String),
+}
+
 #[cfg(test)]
 mod tests {
     use serde_test::{assert_de_tokens, assert_de_tokens_error, Token};
@@ -203,4 +323,61 @@ mod tests {
             "2000.10.20"
         );
     }
+
+    #[test]
+    fn buildpack_version_ordering() {
+        let mut versions: Vec<BuildpackVersion> = ["1.10.0", "0.9.9", "1.2.10", "1.2.9", "2.0.0"]
+            .iter()
+            .map(|version| version.parse().unwrap())
+            .collect();
+        versions.sort();
+
+        assert_eq!(
+            versions.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["0.9.9", "1.2.9", "1.2.10", "1.10.0", "2.0.0"]
+        );
+    }
+
+    #[test]
+    fn version_requirements() {
+        let matches = |requirement: &str, version: &str| {
+            requirement
+                .parse::<BuildpackVersionRequirement>()
+                .unwrap()
+                .matches(&version.parse().unwrap())
+        };
+
+        assert!(matches("*", "0.0.1"));
+        assert!(matches("1.2.3", "1.9.0"));
+        assert!(!matches("1.2.3", "1.2.2"));
+        assert!(!matches("1.2.3", "2.0.0"));
+        assert!(matches("^0.2.3", "0.2.9"));
+        assert!(!matches("^0.2.3", "0.3.0"));
+        assert!(matches("^0.0.3", "0.0.3"));
+        assert!(!matches("^0.0.3", "0.0.4"));
+        assert!(matches("~1.2.3", "1.2.9"));
+        assert!(!matches("~1.2.3", "1.3.0"));
+        assert!(matches("=1.2", "1.2.7"));
+        assert!(!matches("=1.2", "1.3.0"));
+        assert!(matches("1.2.*", "1.2.7"));
+        assert!(matches("1.x", "1.7.0"));
+        assert!(matches(">1.2", "1.3.0"));
+        assert!(!matches(">1.2", "1.2.9"));
+        assert!(matches(">= 1.2.3", "1.2.3"));
+        assert!(matches("<=1.2", "1.2.9"));
+        assert!(!matches("<1.2", "1.2.0"));
+        assert!(matches(">=1.2, <2", "1.99.0"));
+        assert!(!matches(">=1.2, <2", "2.0.0"));
+        assert!(matches(">=1.*", "1.0.0"));
+    }
+
+    #[test]
+    fn reject_invalid_version_requirements() {
+        for requirement in ["", "a.b.c", "1.2.3.4", "1.*.3", "1.2.3-dev", "1,", "=>1"] {
+            assert!(
+                requirement.parse::<BuildpackVersionRequirement>().is_err(),
+                "{requirement}"
+            );
+        }
+    }
 }
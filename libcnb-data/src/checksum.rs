@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A checksum of some content, consisting of the hash algorithm and the hex-encoded digest.
+///
+/// Checksums are parsed from and formatted as `<algorithm>:<digest>`, such as `sha256:...`, the
+/// format used by OCI image digests. The digest is normalized to lowercase.
+///
+/// # Examples
+/// ```
+/// use libcnb_data::checksum::{Checksum, ChecksumAlgorithm};
+///
+/// let checksum: Checksum = "sha256:7F83B1657FF1FC53B92DC18148A1D65DFC2D4B1FA3D677284ADDD200126D9069"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(checksum.algorithm(), ChecksumAlgorithm::Sha256);
+/// assert_eq!(
+///     checksum.digest(),
+///     "7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069"
+/// );
+/// assert_eq!(
+///     checksum.to_string(),
+///     "sha256:7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069"
+/// );
+///
+/// assert!("sha256:invalid".parse::<Checksum>().is_err());
+/// ```
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+#[serde(try_from = "String")]
+pub struct Checksum {
+    algorithm: ChecksumAlgorithm,
+    digest: String,
+}
+
+impl Checksum {
+    /// Creates a checksum from an algorithm and a hex-encoded digest.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the digest is not hex-encoded or its length doesn't match the algorithm.
+    pub fn new(
+        algorithm: ChecksumAlgorithm,
+        digest: impl AsRef<str>,
+    ) -> Result<Self, ChecksumError> {
+        let digest = digest.as_ref();
+
+        if digest.len() == algorithm.digest_len() && digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(Self {
+                algorithm,
+                digest: digest.to_ascii_lowercase(),
+            })
+        } else {
+            Err(ChecksumError::InvalidDigest(
+                algorithm,
+                String::from(digest),
+            ))
+        }
+    }
+
+    #[must_use]
+    pub fn algorithm(&self) -> ChecksumAlgorithm {
+        self.algorithm
+    }
+
+    /// The hex-encoded digest, in lowercase.
+    #[must_use]
+    pub fn digest(&self) -> &str {
+        &self.digest
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = ChecksumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (algorithm, digest) = value
+            .split_once(':')
+            .ok_or_else(|| ChecksumError::InvalidChecksum(String::from(value)))?;
+
+        Self::new(algorithm.parse()?, digest)
+    }
+}
+
+impl TryFrom<String> for Checksum {
+    type Error = ChecksumError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for Checksum {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}:{}", self.algorithm, self.digest)
+    }
+}
+
+impl Serialize for Checksum {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// The hash algorithm of a [`Checksum`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// The length of the hex-encoded digests of this algorithm.
+    #[must_use]
+    pub fn digest_len(self) -> usize {
+        match self {
+            Self::Sha256 => 64,
+            Self::Sha512 => 128,
+        }
+    }
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = ChecksumError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "sha256" => Ok(Self::Sha256),
+            "sha512" => Ok(Self::Sha512),
+            _ => Err(ChecksumError::UnsupportedAlgorithm(String::from(value))),
+        }
+    }
+}
+
+impl Display for ChecksumAlgorithm {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum ChecksumError {
+    #[error("Invalid checksum, expected `<algorithm>:<digest>`: `{0}`")]
+    InvalidChecksum(String),
+    #[error("Unsupported checksum algorithm: `{0}`")]
+    UnsupportedAlgorithm(String),
+    #[error("Invalid {0} digest: `{1}`")]
+    InvalidDigest(ChecksumAlgorithm, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_test::{assert_de_tokens_error, assert_tokens, Token};
+
+    const SHA256: &str = "7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069";
+
+    #[test]
+    fn parse_checksums() {
+        let checksum = format!("sha256:{SHA256}").parse::<Checksum>().unwrap();
+        assert_eq!(checksum.algorithm(), ChecksumAlgorithm::Sha256);
+        assert_eq!(checksum.digest(), SHA256);
+
+        let sha512 = "a".repeat(128);
+        let checksum = format!("sha512:{sha512}").parse::<Checksum>().unwrap();
+        assert_eq!(checksum.algorithm(), ChecksumAlgorithm::Sha512);
+        assert_eq!(checksum.digest(), sha512);
+    }
+
+    #[test]
+    fn reject_invalid_checksums() {
+        assert_eq!(
+            SHA256.parse::<Checksum>(),
+            Err(ChecksumError::InvalidChecksum(String::from(SHA256)))
+        );
+        assert_eq!(
+            format!("md5:{SHA256}").parse::<Checksum>(),
+            Err(ChecksumError::UnsupportedAlgorithm(String::from("md5")))
+        );
+        assert_eq!(
+            format!("sha512:{SHA256}").parse::<Checksum>(),
+            Err(ChecksumError::InvalidDigest(
+                ChecksumAlgorithm::Sha512,
+                String::from(SHA256)
+            ))
+        );
+        assert_eq!(
+            "sha256:xyz".parse::<Checksum>(),
+            Err(ChecksumError::InvalidDigest(
+                ChecksumAlgorithm::Sha256,
+                String::from("xyz")
+            ))
+        );
+    }
+
+    #[test]
+    fn serde_checksum() {
+        let checksum = format!("sha256:{}", SHA256.to_ascii_uppercase())
+            .parse::<Checksum>()
+            .unwrap();
+
+        assert_tokens(
+            &checksum,
+            &[Token::Str(
+                "sha256:7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069",
+            )],
+        );
+        assert_de_tokens_error::<Checksum>(
+            &[Token::Str("sha256:xyz")],
+            "Invalid sha256 digest: `xyz`",
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Environment variable modifications, as used by layer environments and exec.d programs.
+
+use crate::newtypes::libcnb_newtype;
+use std::cmp::Ordering;
+use std::ffi::{OsStr, OsString};
+
+/// Environment variable modification behavior.
+/// ([CNB spec: Environment Variable Modification Rules](https://github.com/buildpacks/spec/blob/main/buildpack.md#environment-variable-modification-rules))
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum ModificationBehavior {
+    Append,
+    Default,
+    Delimiter,
+    Override,
+    Prepend,
+}
+
+impl ModificationBehavior {
+    /// The file extension of layer environment files with this modification behavior, without
+    /// the leading dot.
+    #[must_use]
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ModificationBehavior::Append => "append",
+            ModificationBehavior::Default => "default",
+            ModificationBehavior::Delimiter => "delim",
+            ModificationBehavior::Override => "override",
+            ModificationBehavior::Prepend => "prepend",
+        }
+    }
+
+    /// Determines the modification behavior from the file extension of a layer environment file.
+    ///
+    /// Returns `None` for unknown extensions. Files without an extension override the variable,
+    /// which callers have to handle themselves.
+    #[must_use]
+    pub fn from_file_extension(file_extension: &str) -> Option<Self> {
+        match file_extension {
+            "append" => Some(ModificationBehavior::Append),
+            "default" => Some(ModificationBehavior::Default),
+            "delim" => Some(ModificationBehavior::Delimiter),
+            "override" => Some(ModificationBehavior::Override),
+            "prepend" => Some(ModificationBehavior::Prepend),
+            _ => None,
+        }
+    }
+}
+
+impl Ord for ModificationBehavior {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Explicit mapping used over macro based approach to avoid tying source order of elements
+        // to ordering logic.
+        fn index(value: &ModificationBehavior) -> i32 {
+            match value {
+                ModificationBehavior::Append => 0,
+                ModificationBehavior::Default => 1,
+                ModificationBehavior::Delimiter => 2,
+                ModificationBehavior::Override => 3,
+                ModificationBehavior::Prepend => 4,
+            }
+        }
+
+        index(self).cmp(&index(other))
+    }
+}
+
+impl PartialOrd for ModificationBehavior {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A typed modification of a single environment variable.
+///
+/// Unlike [`ModificationBehavior`], which mirrors the files of a layer environment directory, the
+/// delimiter of appended and prepended values is part of the value. `V` is usually [`String`] or
+/// [`OsString`].
+///
+/// # Examples
+/// ```
+/// use libcnb_data::env::EnvValue;
+///
+/// let value = EnvValue::Prepend {
+///     value: String::from("/layers/ruby/bin"),
+///     delimiter: String::from(":"),
+/// };
+///
+/// assert_eq!(
+///     value.apply(Some(&String::from("/usr/bin"))),
+///     "/layers/ruby/bin:/usr/bin"
+/// );
+/// assert_eq!(value.apply(None), "/layers/ruby/bin");
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum EnvValue<V = String> {
+    /// Sets the variable, replacing any previous value.
+    Override(V),
+    /// Sets the variable only if it isn't set yet.
+    Default(V),
+    /// Appends the value to a previous, non-empty value, separated by the delimiter.
+    Append { value: V, delimiter: V },
+    /// Prepends the value to a previous, non-empty value, separated by the delimiter.
+    Prepend { value: V, delimiter: V },
+}
+
+impl<V> EnvValue<V> {
+    /// The modification behavior of this value.
+    #[must_use]
+    pub fn modification_behavior(&self) -> ModificationBehavior {
+        match self {
+            EnvValue::Override(_) => ModificationBehavior::Override,
+            EnvValue::Default(_) => ModificationBehavior::Default,
+            EnvValue::Append { .. } => ModificationBehavior::Append,
+            EnvValue::Prepend { .. } => ModificationBehavior::Prepend,
+        }
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &V {
+        match self {
+            EnvValue::Override(value)
+            | EnvValue::Default(value)
+            | EnvValue::Append { value, .. }
+            | EnvValue::Prepend { value, .. } => value,
+        }
+    }
+
+    /// The delimiter of appended and prepended values.
+    #[must_use]
+    pub fn delimiter(&self) -> Option<&V> {
+        match self {
+            EnvValue::Override(_) | EnvValue::Default(_) => None,
+            EnvValue::Append { delimiter, .. } | EnvValue::Prepend { delimiter, .. } => {
+                Some(delimiter)
+            }
+        }
+    }
+
+    /// Converts the value and delimiter with the given function.
+    pub fn map<W>(self, f: impl Fn(V) -> W) -> EnvValue<W> {
+        match self {
+            EnvValue::Override(value) => EnvValue::Override(f(value)),
+            EnvValue::Default(value) => EnvValue::Default(f(value)),
+            EnvValue::Append { value, delimiter } => EnvValue::Append {
+                value: f(value),
+                delimiter: f(delimiter),
+            },
+            EnvValue::Prepend { value, delimiter } => EnvValue::Prepend {
+                value: f(value),
+                delimiter: f(delimiter),
+            },
+        }
+    }
+}
+
+impl<V: Clone + AsRef<OsStr> + Extend<V>> EnvValue<V> {
+    /// Returns the new value of the variable, given its previous value.
+    ///
+    /// This follows the same rules as layer environments: default values are only used if the
+    /// variable isn't set, and appended or prepended values replace empty previous values.
+    #[must_use]
+    pub fn apply(&self, previous_value: Option<&V>) -> V {
+        match (self, previous_value) {
+            (EnvValue::Default(_), Some(previous_value)) => previous_value.clone(),
+            (EnvValue::Append { value, delimiter }, Some(previous_value))
+                if !previous_value.as_ref().is_empty() =>
+            {
+                concat(previous_value, delimiter, value)
+            }
+            (EnvValue::Prepend { value, delimiter }, Some(previous_value))
+                if !previous_value.as_ref().is_empty() =>
+            {
+                concat(value, delimiter, previous_value)
+            }
+            (
+                EnvValue::Override(value)
+                | EnvValue::Default(value)
+                | EnvValue::Append { value, .. }
+                | EnvValue::Prepend { value, .. },
+                _,
+            ) => value.clone(),
+        }
+    }
+}
+
+fn concat<V: Clone + Extend<V>>(first: &V, delimiter: &V, second: &V) -> V {
+    let mut result = first.clone();
+    result.extend([delimiter.clone(), second.clone()]);
+    result
+}
+
+impl From<String> for EnvValue {
+    fn from(value: String) -> Self {
+        EnvValue::Override(value)
+    }
+}
+
+impl From<&str> for EnvValue {
+    fn from(value: &str) -> Self {
+        EnvValue::Override(String::from(value))
+    }
+}
+
+impl From<OsString> for EnvValue<OsString> {
+    fn from(value: OsString) -> Self {
+        EnvValue::Override(value)
+    }
+}
+
+libcnb_newtype!(
+    env,
+    /// Construct an [`EnvVarName`] value at compile time.
+    ///
+    /// Passing a string that is not a valid `EnvVarName` value will yield a compilation error.
+    ///
+    /// # Examples:
+    /// ```
+    /// use libcnb_data::env::EnvVarName;
+    /// use libcnb_data::env_var_name;
+    ///
+    /// let name: EnvVarName = env_var_name!("JAVA_HOME");
+    /// ```
+    env_var_name,
+    /// The name of an environment variable.
+    ///
+    /// It MUST only contain numbers, letters, and the character `_` and MUST NOT start with a
+    /// number, as defined for portable environment variable names by POSIX.
+    ///
+    /// Use the [`env_var_name`](crate::env_var_name) macro to construct an `EnvVarName` from a
+    /// literal string. To parse a dynamic string into an `EnvVarName`, use
+    /// [`str::parse`](str::parse).
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb_data::env::EnvVarName;
+    /// use libcnb_data::env_var_name;
+    ///
+    /// let from_literal = env_var_name!("JAVA_HOME");
+    ///
+    /// let input = "JAVA_HOME";
+    /// let from_dynamic: EnvVarName = input.parse().unwrap();
+    /// assert_eq!(from_dynamic, from_literal);
+    ///
+    /// let input = "JAVA-HOME";
+    /// let invalid: Result<EnvVarName, _> = input.parse();
+    /// assert!(invalid.is_err());
+    /// ```
+    EnvVarName,
+    EnvVarNameError,
+    r"^[A-Za-z_][A-Za-z0-9_]*$"
+);
+
+impl From<EnvVarName> for OsString {
+    fn from(name: EnvVarName) -> Self {
+        OsString::from(name.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let previous = String::from("previous");
+        let empty = String::new();
+
+        assert_eq!(EnvValue::from("new").apply(Some(&previous)), "new");
+        assert_eq!(EnvValue::Default(String::from("new")).apply(None), "new");
+        assert_eq!(
+            EnvValue::Default(String::from("new")).apply(Some(&empty)),
+            ""
+        );
+
+        let append = EnvValue::Append {
+            value: String::from("new"),
+            delimiter: String::from(":"),
+        };
+        assert_eq!(append.apply(Some(&previous)), "previous:new");
+        assert_eq!(append.apply(Some(&empty)), "new");
+
+        let prepend = EnvValue::Prepend {
+            value: OsString::from("new"),
+            delimiter: OsString::from(":"),
+        };
+        assert_eq!(
+            prepend.apply(Some(&OsString::from("previous"))),
+            OsString::from("new:previous")
+        );
+        assert_eq!(prepend.apply(None), OsString::from("new"));
+    }
+
+    #[test]
+    fn file_extensions() {
+        for modification_behavior in [
+            ModificationBehavior::Append,
+            ModificationBehavior::Default,
+            ModificationBehavior::Delimiter,
+            ModificationBehavior::Override,
+            ModificationBehavior::Prepend,
+        ] {
+            assert_eq!(
+                ModificationBehavior::from_file_extension(modification_behavior.file_extension()),
+                Some(modification_behavior)
+            );
+        }
+
+        assert_eq!(ModificationBehavior::from_file_extension("unknown"), None);
+    }
+
+    #[test]
+    fn env_var_name_validation() {
+        for valid in ["PATH", "java_home", "_FOO", "FOO_123"] {
+            assert!(valid.parse::<EnvVarName>().is_ok());
+        }
+
+        for invalid in ["", "1FOO", "FOO-BAR", "FOO BAR", "FOO=BAR", "FÜCHSCHEN"] {
+            assert_eq!(
+                invalid.parse::<EnvVarName>(),
+                Err(EnvVarNameError::InvalidValue(String::from(invalid)))
+            );
+        }
+    }
+
+    #[test]
+    fn env_var_name_into_os_string() {
+        assert_eq!(
+            OsString::from("PATH".parse::<EnvVarName>().unwrap()),
+            OsString::from("PATH")
+        );
+    }
+}
@@ -1,21 +1,74 @@
+use crate::env::EnvValue;
 use crate::newtypes::libcnb_newtype;
-use serde::Serialize;
-use std::collections::HashMap;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 
 /// Output of a CNB exec.d program.
 ///
+/// Values are [`EnvValue`]s, so exec.d programs can append or prepend to variables of the launch
+/// environment just like layer environments. Plain strings override the variable. Since the spec
+/// only allows exec.d programs to output final values, they are resolved against the current
+/// environment with [`ExecDProgramOutput::resolve`] before they are written. Serializing an
+/// `ExecDProgramOutput` resolves the values against the environment of the current process.
+///
 /// See [Cloud Native Buildpack specification](https://github.com/buildpacks/spec/blob/main/buildpack.md#execd)
-#[derive(Serialize, Clone)]
-pub struct ExecDProgramOutput(HashMap<ExecDProgramOutputKey, String>);
+#[derive(Clone, Debug)]
+pub struct ExecDProgramOutput(HashMap<ExecDProgramOutputKey, EnvValue>);
 
 impl ExecDProgramOutput {
     #[must_use]
-    pub fn new(map: HashMap<ExecDProgramOutputKey, String>) -> Self {
+    pub fn new(map: HashMap<ExecDProgramOutputKey, EnvValue>) -> Self {
         Self(map)
     }
+
+    /// Resolves the values of this output to the final values of the variables, given a function
+    /// that returns the current value of a variable.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb_data::env::EnvValue;
+    /// use libcnb_data::exec_d::ExecDProgramOutput;
+    /// use libcnb_data::exec_d_program_output_key;
+    ///
+    /// let output = ExecDProgramOutput::from([
+    ///     (exec_d_program_output_key!("PORT"), EnvValue::from("5000")),
+    ///     (
+    ///         exec_d_program_output_key!("PATH"),
+    ///         EnvValue::Prepend {
+    ///             value: String::from("/layers/app/bin"),
+    ///             delimiter: String::from(":"),
+    ///         },
+    ///     ),
+    /// ]);
+    ///
+    /// let resolved = output.resolve(|key| (key == "PATH").then(|| String::from("/usr/bin")));
+    ///
+    /// assert_eq!(resolved[&exec_d_program_output_key!("PORT")], "5000");
+    /// assert_eq!(
+    ///     resolved[&exec_d_program_output_key!("PATH")],
+    ///     "/layers/app/bin:/usr/bin"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn resolve(
+        &self,
+        current_value: impl Fn(&str) -> Option<String>,
+    ) -> BTreeMap<ExecDProgramOutputKey, String> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.clone(), value.apply(current_value(key).as_ref())))
+            .collect()
+    }
+}
+
+impl Serialize for ExecDProgramOutput {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.resolve(|key| std::env::var(key).ok())
+            .serialize(serializer)
+    }
 }
 
-impl<K: Into<ExecDProgramOutputKey>, V: Into<String>, A: IntoIterator<Item = (K, V)>> From<A>
+impl<K: Into<ExecDProgramOutputKey>, V: Into<EnvValue>, A: IntoIterator<Item = (K, V)>> From<A>
     for ExecDProgramOutput
 {
     fn from(a: A) -> Self {
@@ -73,6 +126,33 @@ libcnb_newtype!(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_test::{assert_ser_tokens, Token};
+
+    #[test]
+    fn exec_d_program_output_serialization() {
+        let output = ExecDProgramOutput::from([(
+            "LIBCNB_EXEC_D_UNSET_VAR"
+                .parse::<ExecDProgramOutputKey>()
+                .unwrap(),
+            EnvValue::Append {
+                value: String::from("/layers/app/bin"),
+                delimiter: String::from(":"),
+            },
+        )]);
+
+        assert_ser_tokens(
+            &output,
+            &[
+                Token::Map { len: Some(1) },
+                Token::NewtypeStruct {
+                    name: "ExecDProgramOutputKey",
+                },
+                Token::Str("LIBCNB_EXEC_D_UNSET_VAR"),
+                Token::Str("/layers/app/bin"),
+                Token::MapEnd,
+            ],
+        );
+    }
 
     #[test]
     fn exec_d_program_output_key_validation_valid() {
@@ -0,0 +1,76 @@
+use crate::newtypes::libcnb_newtype;
+
+libcnb_newtype!(
+    extension,
+    /// Construct an [`ExtensionId`] value at compile time.
+    ///
+    /// Passing a string that is not a valid `ExtensionId` value will yield a compilation error.
+    ///
+    /// # Examples:
+    /// ```
+    /// use libcnb_data::extension::ExtensionId;
+    /// use libcnb_data::extension_id;
+    ///
+    /// let extension_id: ExtensionId = extension_id!("heroku/apt");
+    /// ```
+    extension_id,
+    /// The ID of an image extension.
+    ///
+    /// It MUST only contain numbers, letters, and the characters `.`, `/`, and `-`.
+    /// It also MUST NOT be `config` or `app`.
+    ///
+    /// Use the [`extension_id`](crate::extension_id) macro to construct an `ExtensionId` from a
+    /// literal string. To parse a dynamic string into an `ExtensionId`, use
+    /// [`str::parse`](str::parse).
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb_data::extension::ExtensionId;
+    /// use libcnb_data::extension_id;
+    ///
+    /// let from_literal = extension_id!("heroku/apt");
+    ///
+    /// let input = "heroku/apt";
+    /// let from_dynamic: ExtensionId = input.parse().unwrap();
+    /// assert_eq!(from_dynamic, from_literal);
+    ///
+    /// let input = "app";
+    /// let invalid: Result<ExtensionId, _> = input.parse();
+    /// assert!(invalid.is_err());
+    /// ```
+    ExtensionId,
+    ExtensionIdError,
+    r"^(?!(app|config|sbom)$)[[:alnum:]./-]+$"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension_id_validation_valid() {
+        assert!("heroku/apt".parse::<ExtensionId>().is_ok());
+        assert!("Abc123./-".parse::<ExtensionId>().is_ok());
+        assert!("config-foo".parse::<ExtensionId>().is_ok());
+    }
+
+    #[test]
+    fn extension_id_validation_invalid() {
+        assert_eq!(
+            "heroku_apt".parse::<ExtensionId>(),
+            Err(ExtensionIdError::InvalidValue(String::from("heroku_apt")))
+        );
+        assert_eq!(
+            "app".parse::<ExtensionId>(),
+            Err(ExtensionIdError::InvalidValue(String::from("app")))
+        );
+        assert_eq!(
+            "config".parse::<ExtensionId>(),
+            Err(ExtensionIdError::InvalidValue(String::from("config")))
+        );
+        assert_eq!(
+            "".parse::<ExtensionId>(),
+            Err(ExtensionIdError::InvalidValue(String::new()))
+        );
+    }
+}
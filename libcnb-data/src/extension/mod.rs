@@ -0,0 +1,170 @@
+mod id;
+
+use crate::buildpack::{BuildpackApi, BuildpackTarget, BuildpackVersion, License};
+use crate::generic::GenericMetadata;
+pub use id::*;
+use serde::Deserialize;
+
+/// Data structure for the image extension descriptor (extension.toml).
+///
+/// Representation of [extension.toml](https://github.com/buildpacks/spec/blob/main/image_extension.md#extensiontoml-toml).
+/// Image extensions implement the Image Extension Interface (ie: contain `/bin/detect` and,
+/// optionally, `/bin/generate` executables) to extend the build and run base images.
+///
+/// # Example:
+/// ```
+/// use libcnb_data::extension::ExtensionDescriptor;
+/// use libcnb_data::extension_id;
+///
+/// let toml_str = r#"
+/// api = "0.10"
+///
+/// [extension]
+/// id = "heroku/apt"
+/// name = "Apt Extension"
+/// version = "0.0.1"
+///
+/// [[targets]]
+/// os = "linux"
+/// "#;
+///
+/// let extension_descriptor = toml::from_str::<ExtensionDescriptor>(toml_str).unwrap();
+/// assert_eq!(extension_descriptor.extension.id, extension_id!("heroku/apt"));
+/// ```
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ExtensionDescriptor<EM = GenericMetadata> {
+    pub api: BuildpackApi,
+    pub extension: Extension,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<BuildpackTarget>,
+    pub metadata: EM,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Extension {
+    pub id: ExtensionId,
+    pub name: Option<String>,
+    pub version: BuildpackVersion,
+    pub homepage: Option<String>,
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<License>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_extension() {
+        let toml_str = r#"
+api = "0.10"
+
+[extension]
+id = "heroku/apt"
+name = "Apt Extension"
+version = "0.0.1"
+homepage = "https://example.tld"
+description = "Installs packages with apt"
+keywords = ["apt", "packages"]
+
+[[extension.licenses]]
+type = "BSD-3-Clause"
+
+[[targets]]
+os = "linux"
+arch = "amd64"
+
+[metadata]
+checksum = "abc123"
+        "#;
+
+        let extension_descriptor = toml::from_str::<ExtensionDescriptor>(toml_str).unwrap();
+
+        assert_eq!(
+            extension_descriptor.api,
+            BuildpackApi {
+                major: 0,
+                minor: 10
+            }
+        );
+        assert_eq!(
+            extension_descriptor.extension.id,
+            "heroku/apt".parse().unwrap()
+        );
+        assert_eq!(
+            extension_descriptor.extension.name,
+            Some(String::from("Apt Extension"))
+        );
+        assert_eq!(
+            extension_descriptor.extension.version,
+            BuildpackVersion::new(0, 0, 1)
+        );
+        assert_eq!(
+            extension_descriptor.extension.homepage,
+            Some(String::from("https://example.tld"))
+        );
+        assert_eq!(
+            extension_descriptor.extension.description,
+            Some(String::from("Installs packages with apt"))
+        );
+        assert_eq!(
+            extension_descriptor.extension.keywords,
+            [String::from("apt"), String::from("packages")]
+        );
+        assert_eq!(
+            extension_descriptor.extension.licenses,
+            [License {
+                r#type: Some(String::from("BSD-3-Clause")),
+                uri: None
+            }]
+        );
+        assert_eq!(
+            extension_descriptor.targets,
+            [BuildpackTarget {
+                os: Some(String::from("linux")),
+                arch: Some(String::from("amd64")),
+                variant: None,
+                distros: Vec::new(),
+            }]
+        );
+        assert_eq!(
+            extension_descriptor.metadata.unwrap().get("checksum"),
+            Some(&toml::Value::String(String::from("abc123")))
+        );
+    }
+
+    #[test]
+    fn deserialize_minimal_extension() {
+        let toml_str = r#"
+api = "0.10"
+
+[extension]
+id = "heroku/apt"
+version = "0.0.1"
+        "#;
+
+        let extension_descriptor = toml::from_str::<ExtensionDescriptor>(toml_str).unwrap();
+
+        assert_eq!(extension_descriptor.extension.name, None);
+        assert!(extension_descriptor.targets.is_empty());
+        assert_eq!(extension_descriptor.metadata, None);
+    }
+
+    #[test]
+    fn reject_buildpack_table() {
+        let toml_str = r#"
+api = "0.10"
+
+[buildpack]
+id = "heroku/apt"
+version = "0.0.1"
+        "#;
+
+        assert!(toml::from_str::<ExtensionDescriptor>(toml_str).is_err());
+    }
+}
@@ -6,3 +6,6 @@
 // every crate to explicitly import the `libcnb_proc_macros` crate as crates can't use code from
 // transitive dependencies.
 pub use libcnb_proc_macros::verify_regex;
+
+// Used by the `build_plan` macro to construct build plan requirement metadata.
+pub use toml;
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 
 /// Data Structure for the launch.toml file.
 #[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Launch {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -14,6 +15,59 @@ pub struct Launch {
     pub slices: Vec<Slice>,
 }
 
+impl Launch {
+    /// Validates the processes of the launch configuration, see [`Process::validate`].
+    ///
+    /// # Errors
+    ///
+    /// Returns all problems found, not just the first one.
+    pub fn validate(&self) -> Result<(), Vec<LaunchError>> {
+        let errors = self
+            .processes
+            .iter()
+            .filter_map(|process| {
+                process
+                    .validate()
+                    .err()
+                    .map(|error| LaunchError::InvalidProcess(process.r#type.clone(), error))
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the likely mistakes in the processes of the launch configuration that don't make
+    /// it invalid, see [`Process::warnings`].
+    #[must_use]
+    pub fn warnings(&self) -> Vec<LaunchWarning> {
+        self.processes
+            .iter()
+            .flat_map(|process| {
+                process
+                    .warnings()
+                    .into_iter()
+                    .map(|warning| LaunchWarning::Process(process.r#type.clone(), warning))
+            })
+            .collect()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum LaunchError {
+    #[error("Process {0} is invalid: {1}")]
+    InvalidProcess(ProcessType, ProcessError),
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum LaunchWarning {
+    #[error("Process {0}: {1}")]
+    Process(ProcessType, ProcessWarning),
+}
+
 /// A non-consuming builder for [`Launch`] values.
 ///
 /// # Examples
@@ -97,7 +151,8 @@ impl LaunchBuilder {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Label {
     pub key: String,
@@ -105,6 +160,7 @@ pub struct Label {
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Process {
     pub r#type: ProcessType,
@@ -121,7 +177,68 @@ pub struct Process {
     pub working_directory: WorkingDirectory,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+impl Process {
+    /// Validates the command of the process.
+    ///
+    /// The first element of `command` is the executable and must not be empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command or its executable is empty.
+    pub fn validate(&self) -> Result<(), ProcessError> {
+        match self.command.as_slice() {
+            [] => Err(ProcessError::EmptyCommand),
+            [executable, ..] if executable.trim().is_empty() => Err(ProcessError::EmptyExecutable),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns likely mistakes in the command and arguments of the process that don't make it
+    /// invalid.
+    ///
+    /// Since Buildpack API 0.9, processes are executed directly instead of in a shell. An
+    /// executable that contains whitespace without any further command elements, such as
+    /// `bundle exec rails server`, looks like a shell command line. Use separate elements or an
+    /// explicit shell (`["bash", "-c", "..."]`) instead. Absolute paths are expected to contain
+    /// whitespace occasionally and aren't reported.
+    ///
+    /// `args` are replaced as a whole when users override them. The `--` argument separator
+    /// would then be lost, so it should be added to the end of `command` instead of `args`.
+    #[must_use]
+    pub fn warnings(&self) -> Vec<ProcessWarning> {
+        let mut warnings = Vec::new();
+
+        if let [executable] = self.command.as_slice() {
+            if executable.contains(char::is_whitespace) && !executable.starts_with('/') {
+                warnings.push(ProcessWarning::ShellCommandLine(executable.clone()));
+            }
+        }
+
+        if self.args.iter().any(|arg| arg == "--") {
+            warnings.push(ProcessWarning::ArgumentSeparatorInArgs);
+        }
+
+        warnings
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProcessError {
+    #[error("Command is empty")]
+    EmptyCommand,
+    #[error("Executable (the first element of the command) is empty")]
+    EmptyExecutable,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProcessWarning {
+    #[error("Executable `{0}` looks like a shell command line, but processes are not run in a shell. Split it into separate command elements or use `bash -c`")]
+    ShellCommandLine(String),
+    #[error("Argument separator `--` should be added to the end of the command instead of the args, since args are replaced when users override them")]
+    ArgumentSeparatorInArgs,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum WorkingDirectory {
     // There is no explicitly defined value in the CNB spec that denotes the app directory. Since
@@ -130,6 +247,7 @@ pub enum WorkingDirectory {
     // relative to the app directory, so "." will be the app directory itself. However, types that
     // contain this type (i.e. Process), should always add
     // `#[serde(skip_serializing_if = "WorkingDirectory::is_app")]` to a field of this type.
+    #[default]
     App,
     Directory(PathBuf),
 }
@@ -157,9 +275,15 @@ impl Serialize for WorkingDirectory {
     }
 }
 
-impl Default for WorkingDirectory {
-    fn default() -> Self {
-        Self::App
+// Like Serialize above, the derived schema would describe the untagged App variant as null.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for WorkingDirectory {
+    fn schema_name() -> String {
+        String::from("WorkingDirectory")
+    }
+
+    fn json_schema(generator: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        PathBuf::json_schema(generator)
     }
 }
 
@@ -250,13 +374,25 @@ impl ProcessBuilder {
     }
 
     /// Builds the `Process` based on the configuration of this builder.
+    ///
+    /// The process is not validated, see [`try_build`](Self::try_build).
     #[must_use]
     pub fn build(&self) -> Process {
         self.process.clone()
     }
+
+    /// Builds and validates the `Process` based on the configuration of this builder.
+    ///
+    /// # Errors
+    ///
+    /// Returns the problem found by [`Process::validate`].
+    pub fn try_build(&self) -> Result<Process, ProcessError> {
+        self.process.validate().map(|()| self.process.clone())
+    }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct Slice {
     /// Path globs for this slice.
@@ -360,6 +496,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn process_type_error_message() {
+        assert_eq!(
+            "worker:foo".parse::<ProcessType>().unwrap_err().to_string(),
+            r#"Invalid ProcessType "worker:foo": character ':' at position 7 is not allowed, the value must match ^[[:alnum:]._-]+$"#
+        );
+    }
+
     #[test]
     fn process_with_default_values_deserialization() {
         let toml_str = r#"
@@ -475,6 +619,107 @@ working-directory = "dist"
         );
     }
 
+    #[test]
+    fn process_validation() {
+        for command in [
+            vec!["bundle", "exec", "rails"],
+            vec!["bash", "-c", "bundle exec rails server"],
+            vec!["/workspace/my app/bin/server"],
+            vec!["rake", "--"],
+        ] {
+            assert_eq!(
+                ProcessBuilder::new(process_type!("web"), command)
+                    .arg("-p")
+                    .try_build()
+                    .map(|_| ()),
+                Ok(())
+            );
+        }
+
+        assert_eq!(
+            ProcessBuilder::new(process_type!("web"), Vec::<String>::new()).try_build(),
+            Err(ProcessError::EmptyCommand)
+        );
+        assert_eq!(
+            ProcessBuilder::new(process_type!("web"), [" ", "server"]).try_build(),
+            Err(ProcessError::EmptyExecutable)
+        );
+        assert_eq!(
+            ProcessBuilder::new(process_type!("web"), ["bundle exec rails server"])
+                .args(["--", "-p"])
+                .try_build()
+                .map(|_| ()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn process_warnings() {
+        for command in [
+            vec!["bundle", "exec", "rails"],
+            vec!["bash", "-c", "bundle exec rails server"],
+            vec!["/workspace/my app/bin/server"],
+            vec!["rake", "--"],
+        ] {
+            assert_eq!(
+                ProcessBuilder::new(process_type!("web"), command)
+                    .arg("-p")
+                    .build()
+                    .warnings(),
+                Vec::new()
+            );
+        }
+
+        assert_eq!(
+            ProcessBuilder::new(process_type!("web"), ["bundle exec rails server"])
+                .args(["--", "-p"])
+                .build()
+                .warnings(),
+            vec![
+                ProcessWarning::ShellCommandLine(String::from("bundle exec rails server")),
+                ProcessWarning::ArgumentSeparatorInArgs
+            ]
+        );
+
+        let launch = LaunchBuilder::new()
+            .process(
+                ProcessBuilder::new(process_type!("web"), ["rails"])
+                    .arg("--")
+                    .build(),
+            )
+            .build();
+        assert_eq!(
+            launch.warnings(),
+            vec![LaunchWarning::Process(
+                process_type!("web"),
+                ProcessWarning::ArgumentSeparatorInArgs
+            )]
+        );
+        assert!(launch.warnings()[0]
+            .to_string()
+            .starts_with("Process web: Argument separator `--` should be added"));
+    }
+
+    #[test]
+    fn launch_validation() {
+        let launch = LaunchBuilder::new()
+            .process(ProcessBuilder::new(process_type!("web"), ["web"]).build())
+            .process(ProcessBuilder::new(process_type!("worker"), Vec::<String>::new()).build())
+            .build();
+
+        assert_eq!(
+            launch.validate(),
+            Err(vec![LaunchError::InvalidProcess(
+                process_type!("worker"),
+                ProcessError::EmptyCommand
+            )])
+        );
+        assert_eq!(
+            launch.validate().unwrap_err()[0].to_string(),
+            "Process worker is invalid: Command is empty"
+        );
+    }
+
     #[test]
     fn process_working_directory_serialization() {
         assert_ser_tokens(&WorkingDirectory::App, &[Token::BorrowedStr(".")]);
@@ -73,4 +73,12 @@ mod tests {
             Err(LayerNameError::InvalidValue(String::new()))
         );
     }
+
+    #[test]
+    fn layer_name_error_message() {
+        assert_eq!(
+            "build".parse::<LayerName>().unwrap_err().to_string(),
+            r#"Invalid LayerName "build": the value must match ^(?!(build|launch|store)$).+$"#
+        );
+    }
 }
@@ -0,0 +1,340 @@
+//! Lenient, forward-compatible parsing of TOML files.
+//!
+//! The data structures of this crate reject unknown fields, so that typos in hand-written files
+//! are caught early. However, this also means that files written for a newer version of the spec
+//! can't be parsed until this crate models the new fields. The functions in this module parse such
+//! files anyway and return the fields and tables that are not known alongside the parsed value,
+//! so that they can be inspected or preserved.
+//!
+//! Unknown fields are only collected for structs. Untagged enums, such as
+//! [`BuildpackDescriptor`](crate::buildpack::BuildpackDescriptor), buffer their contents before
+//! choosing a variant and therefore still reject unknown fields. Use
+//! [`BuildpackDescriptor::from_str_lenient`](crate::buildpack::BuildpackDescriptor::from_str_lenient)
+//! for buildpack descriptors.
+
+use serde::de::value::StringDeserializer;
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use toml::value::{Array, Table};
+use toml::Value;
+
+/// A value parsed with [`from_str`], together with the fields that are unknown to its type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lenient<T> {
+    pub value: T,
+    /// The unknown fields and tables, keyed by their path in the TOML file, such as
+    /// `buildpack.foo` or `order[0].group[1].bar`.
+    pub unknown_fields: BTreeMap<String, Value>,
+}
+
+impl<T> Lenient<T> {
+    /// Maps the parsed value, keeping the unknown fields.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Lenient<U> {
+        Lenient {
+            value: f(self.value),
+            unknown_fields: self.unknown_fields,
+        }
+    }
+}
+
+/// Parses the given TOML string as `T`, collecting unknown fields instead of rejecting them.
+///
+/// # Examples
+/// ```
+/// use libcnb_data::launch::Launch;
+/// use libcnb_data::lenient;
+///
+/// let toml_str = r#"
+/// [[processes]]
+/// type = "web"
+/// command = ["bash", "-c", "./bin/web"]
+/// exec-env = ["production"]
+/// "#;
+///
+/// let launch = lenient::from_str::<Launch>(toml_str).unwrap();
+/// assert_eq!(launch.value.processes.len(), 1);
+/// assert_eq!(
+///     launch.unknown_fields["processes[0].exec-env"],
+///     toml::Value::Array(vec![toml::Value::from("production")])
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the string is not valid TOML or doesn't match `T`, other than containing
+/// unknown fields.
+pub fn from_str<T: DeserializeOwned>(toml_str: &str) -> Result<Lenient<T>, toml::de::Error> {
+    toml::from_str(toml_str).and_then(from_table)
+}
+
+/// Deserializes the given TOML table as `T`, collecting unknown fields instead of rejecting them.
+///
+/// # Errors
+///
+/// Will return `Err` if the table doesn't match `T`, other than containing unknown fields.
+pub fn from_table<T: DeserializeOwned>(table: Table) -> Result<Lenient<T>, toml::de::Error> {
+    let unknown_fields = RefCell::new(BTreeMap::new());
+
+    let value = T::deserialize(LenientDeserializer {
+        value: Value::Table(table),
+        path: String::new(),
+        unknown_fields: &unknown_fields,
+    })?;
+
+    Ok(Lenient {
+        value,
+        unknown_fields: unknown_fields.into_inner(),
+    })
+}
+
+struct LenientDeserializer<'a> {
+    value: Value,
+    path: String,
+    unknown_fields: &'a RefCell<BTreeMap<String, Value>>,
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        String::from(key)
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+impl<'de> Deserializer<'de> for LenientDeserializer<'_> {
+    type Error = toml::de::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Table(table) => visitor.visit_map(LenientMapAccess {
+                entries: table.into_iter(),
+                value: None,
+                path: self.path,
+                unknown_fields: self.unknown_fields,
+            }),
+            Value::Array(array) => visitor.visit_seq(LenientSeqAccess {
+                elements: array.into_iter().enumerate(),
+                path: self.path,
+                unknown_fields: self.unknown_fields,
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    // TOML has no null value, so any value that is present is `Some`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Table(table) => {
+                let (known, unknown): (Table, Table) = table
+                    .into_iter()
+                    .partition(|(key, _)| fields.contains(&key.as_str()));
+
+                self.unknown_fields.borrow_mut().extend(
+                    unknown
+                        .into_iter()
+                        .map(|(key, value)| (join_path(&self.path, &key), value)),
+                );
+
+                LenientDeserializer {
+                    value: Value::Table(known),
+                    ..self
+                }
+                .deserialize_any(visitor)
+            }
+            // Other values can't be deserialized as a struct, except for special values like
+            // datetimes that are handled by the `toml` crate.
+            value => value.deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+struct LenientMapAccess<'a> {
+    entries: <Table as IntoIterator>::IntoIter,
+    value: Option<(String, Value)>,
+    path: String,
+    unknown_fields: &'a RefCell<BTreeMap<String, Value>>,
+}
+
+impl<'de> MapAccess<'de> for LenientMapAccess<'_> {
+    type Error = toml::de::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        self.entries
+            .next()
+            .map(|(key, value)| {
+                let key_deserializer: StringDeserializer<Self::Error> =
+                    key.clone().into_deserializer();
+                self.value = Some((key, value));
+                seed.deserialize(key_deserializer)
+            })
+            .transpose()
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| serde::de::Error::custom("value is missing"))?;
+
+        seed.deserialize(LenientDeserializer {
+            value,
+            path: join_path(&self.path, &key),
+            unknown_fields: self.unknown_fields,
+        })
+    }
+}
+
+struct LenientSeqAccess<'a> {
+    elements: std::iter::Enumerate<<Array as IntoIterator>::IntoIter>,
+    path: String,
+    unknown_fields: &'a RefCell<BTreeMap<String, Value>>,
+}
+
+impl<'de> SeqAccess<'de> for LenientSeqAccess<'_> {
+    type Error = toml::de::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.elements
+            .next()
+            .map(|(index, value)| {
+                seed.deserialize(LenientDeserializer {
+                    value,
+                    path: format!("{}[{index}]", self.path),
+                    unknown_fields: self.unknown_fields,
+                })
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildpack::ComponentBuildpackDescriptor;
+
+    #[test]
+    fn collect_unknown_fields() {
+        let toml_str = r#"
+api = "0.10"
+future-field = true
+
+[buildpack]
+id = "foo/bar"
+version = "0.0.1"
+future-field = "value"
+
+[[targets]]
+os = "linux"
+
+[[targets]]
+os = "linux"
+arch = "arm64"
+future-field = 1
+
+[[targets.distros]]
+name = "ubuntu"
+version = "24.04"
+future-field = 2
+
+[future-table]
+key = "value"
+
+[metadata]
+unknown = "but part of the metadata"
+"#;
+
+        let buildpack_descriptor = from_str::<ComponentBuildpackDescriptor>(toml_str).unwrap();
+
+        assert_eq!(
+            buildpack_descriptor.value.buildpack.id,
+            "foo/bar".parse().unwrap()
+        );
+        assert_eq!(buildpack_descriptor.value.targets.len(), 2);
+        assert_eq!(
+            buildpack_descriptor.value.targets[1].distros[0].version,
+            "24.04"
+        );
+        assert_eq!(
+            buildpack_descriptor.value.metadata.unwrap().get("unknown"),
+            Some(&Value::from("but part of the metadata"))
+        );
+        assert_eq!(
+            buildpack_descriptor.unknown_fields,
+            BTreeMap::from([
+                (String::from("future-field"), Value::from(true)),
+                (String::from("buildpack.future-field"), Value::from("value")),
+                (String::from("targets[1].future-field"), Value::from(1)),
+                (
+                    String::from("targets[1].distros[0].future-field"),
+                    Value::from(2)
+                ),
+                (
+                    String::from("future-table"),
+                    Value::Table(Table::from_iter([(
+                        String::from("key"),
+                        Value::from("value")
+                    )]))
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn reject_invalid_values() {
+        let toml_str = r#"
+api = "0.10"
+
+[buildpack]
+id = "foo/bar"
+version = "invalid"
+"#;
+
+        assert!(from_str::<ComponentBuildpackDescriptor>(toml_str).is_err());
+        assert!(from_str::<ComponentBuildpackDescriptor>("api = \"0.10\"").is_err());
+    }
+}
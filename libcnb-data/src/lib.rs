@@ -1,20 +1,35 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
 pub mod build;
 pub mod build_plan;
 pub mod buildpack;
 pub mod buildpack_plan;
+pub mod checksum;
+pub mod env;
 pub mod exec_d;
+pub mod extension;
 pub mod generic;
 pub mod launch;
 pub mod layer;
 pub mod layer_content_metadata;
+pub mod lenient;
+pub mod lifecycle;
 pub mod package_descriptor;
+pub mod project_descriptor;
+pub mod run_image;
 pub mod sbom;
 pub mod store;
 
 mod newtypes;
+#[cfg(feature = "schemars")]
+mod schema;
 
 // Internals that need to be public for macros
 #[doc(hidden)]
 pub mod internals;
+
+// Suppress warnings due to the `unused_crate_dependencies` lint, since only the `schemars` tests use it.
+#[cfg(all(test, not(feature = "schemars")))]
+use serde_json as _;
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use toml::value::Table;
+
+/// Data structure for the results of the analysis phase (analyzed.toml).
+///
+/// # Example
+/// ```
+/// use libcnb_data::lifecycle::Analyzed;
+///
+/// let toml_str = r#"
+/// [previous-image]
+/// reference = "registry.example.com/my-app@sha256:0a1b2c"
+///
+/// [run-image]
+/// reference = "registry.example.com/run@sha256:3d4e5f"
+/// image = "registry.example.com/run:24"
+///
+/// [run-image.target]
+/// os = "linux"
+/// arch = "amd64"
+///
+/// [run-image.target.distro]
+/// name = "ubuntu"
+/// version = "24.04"
+/// "#;
+///
+/// let analyzed = toml::from_str::<Analyzed>(toml_str).unwrap();
+/// assert_eq!(
+///     analyzed.run_image.and_then(|run_image| run_image.target)
+///         .and_then(|target| target.os)
+///         .as_deref(),
+///     Some("linux")
+/// );
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Analyzed {
+    /// The image from the previous build, if any.
+    pub previous_image: Option<AnalyzedPreviousImage>,
+    /// The run image that will be used for the app image.
+    pub run_image: Option<AnalyzedRunImage>,
+    /// The build image, if known.
+    pub build_image: Option<AnalyzedImage>,
+}
+
+/// The image from the previous build.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct AnalyzedPreviousImage {
+    /// The reference of the image, either a digest reference or an image id.
+    pub reference: Option<String>,
+    /// The lifecycle metadata of the image (the `io.buildpacks.lifecycle.metadata` label).
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub metadata: Table,
+}
+
+/// The run image used for the app image.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct AnalyzedRunImage {
+    /// The reference of the run image, either a digest reference or an image id.
+    pub reference: Option<String>,
+    /// The name of the run image, as provided by the platform.
+    pub image: Option<String>,
+    /// Whether the run image will be extended by image extensions.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub extend: bool,
+    /// The target of the run image.
+    pub target: Option<AnalyzedTarget>,
+}
+
+/// An image referenced in [`Analyzed`].
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct AnalyzedImage {
+    /// The reference of the image, either a digest reference or an image id.
+    pub reference: Option<String>,
+}
+
+/// The target of a run image.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct AnalyzedTarget {
+    pub id: Option<String>,
+    pub os: Option<String>,
+    pub arch: Option<String>,
+    pub arch_variant: Option<String>,
+    pub distro: Option<AnalyzedDistro>,
+}
+
+/// The operating system distribution of an [`AnalyzedTarget`].
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct AnalyzedDistro {
+    pub name: String,
+    pub version: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_analyzed() {
+        let toml_str = r#"
+[previous-image]
+reference = "registry.example.com/my-app@sha256:0a1b2c"
+
+[previous-image.metadata]
+app = [{ sha = "sha256:6789" }]
+
+[run-image]
+reference = "registry.example.com/run@sha256:3d4e5f"
+image = "registry.example.com/run:24"
+extend = true
+
+[run-image.target]
+os = "linux"
+arch = "arm64"
+arch-variant = "v8"
+
+[run-image.target.distro]
+name = "ubuntu"
+version = "24.04"
+
+[build-image]
+reference = "registry.example.com/build@sha256:abcdef"
+"#;
+
+        let analyzed = toml::from_str::<Analyzed>(toml_str).unwrap();
+
+        let previous_image = analyzed.previous_image.unwrap();
+        assert_eq!(
+            previous_image.reference.as_deref(),
+            Some("registry.example.com/my-app@sha256:0a1b2c")
+        );
+        assert!(previous_image.metadata.contains_key("app"));
+
+        assert_eq!(
+            analyzed.run_image,
+            Some(AnalyzedRunImage {
+                reference: Some(String::from("registry.example.com/run@sha256:3d4e5f")),
+                image: Some(String::from("registry.example.com/run:24")),
+                extend: true,
+                target: Some(AnalyzedTarget {
+                    id: None,
+                    os: Some(String::from("linux")),
+                    arch: Some(String::from("arm64")),
+                    arch_variant: Some(String::from("v8")),
+                    distro: Some(AnalyzedDistro {
+                        name: String::from("ubuntu"),
+                        version: String::from("24.04"),
+                    }),
+                }),
+            })
+        );
+        assert_eq!(
+            analyzed.build_image,
+            Some(AnalyzedImage {
+                reference: Some(String::from("registry.example.com/build@sha256:abcdef")),
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_empty_analyzed() {
+        assert_eq!(toml::from_str::<Analyzed>("").unwrap(), Analyzed::default());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::buildpack::{BuildpackApi, BuildpackId};
+use crate::extension::ExtensionId;
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the group of buildpacks and image extensions that passed detection
+/// (group.toml).
+///
+/// # Example
+/// ```
+/// use libcnb_data::lifecycle::Group;
+///
+/// let toml_str = r#"
+/// [[group]]
+/// id = "heroku/nodejs"
+/// version = "1.0.0"
+/// api = "0.10"
+/// "#;
+///
+/// let group = toml::from_str::<Group>(toml_str).unwrap();
+/// assert_eq!(group.group[0].version, "1.0.0");
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct Group {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group: Vec<GroupBuildpack>,
+    #[serde(
+        default,
+        rename = "group-extensions",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub group_extensions: Vec<GroupExtension>,
+}
+
+/// A buildpack in a [`Group`].
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct GroupBuildpack {
+    pub id: BuildpackId,
+    pub version: String,
+    pub api: BuildpackApi,
+    pub homepage: Option<String>,
+}
+
+/// An image extension in a [`Group`].
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct GroupExtension {
+    pub id: ExtensionId,
+    pub version: String,
+    pub api: BuildpackApi,
+    pub homepage: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_group() {
+        let toml_str = r#"
+[[group]]
+id = "heroku/nodejs"
+version = "1.0.0"
+api = "0.10"
+homepage = "https://example.tld"
+
+[[group]]
+id = "heroku/procfile"
+version = "2.0.0"
+api = "0.9"
+extension = false
+
+[[group-extensions]]
+id = "heroku/apt"
+version = "0.1.0"
+api = "0.10"
+"#;
+
+        let group = toml::from_str::<Group>(toml_str).unwrap();
+
+        assert_eq!(
+            group.group,
+            [
+                GroupBuildpack {
+                    id: "heroku/nodejs".parse().unwrap(),
+                    version: String::from("1.0.0"),
+                    api: BuildpackApi {
+                        major: 0,
+                        minor: 10
+                    },
+                    homepage: Some(String::from("https://example.tld")),
+                },
+                GroupBuildpack {
+                    id: "heroku/procfile".parse().unwrap(),
+                    version: String::from("2.0.0"),
+                    api: BuildpackApi { major: 0, minor: 9 },
+                    homepage: None,
+                }
+            ]
+        );
+        assert_eq!(
+            group.group_extensions,
+            [GroupExtension {
+                id: "heroku/apt".parse().unwrap(),
+                version: String::from("0.1.0"),
+                api: BuildpackApi {
+                    major: 0,
+                    minor: 10
+                },
+                homepage: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn serialize_group() {
+        let group = Group {
+            group: vec![GroupBuildpack {
+                id: "heroku/nodejs".parse().unwrap(),
+                version: String::from("1.0.0"),
+                api: BuildpackApi {
+                    major: 0,
+                    minor: 10,
+                },
+                homepage: None,
+            }],
+            group_extensions: Vec::new(),
+        };
+
+        assert_eq!(
+            toml::to_string(&group).unwrap(),
+            "[[group]]\nid = \"heroku/nodejs\"\nversion = \"1.0.0\"\napi = \"0.10\"\n"
+        );
+    }
+}
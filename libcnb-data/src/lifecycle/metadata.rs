@@ -0,0 +1,149 @@
+use crate::buildpack::{BuildpackApi, BuildpackId};
+use crate::launch::{Label, ProcessType, Slice};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the metadata of the build (metadata.toml in `<layers>/config`).
+///
+/// # Example
+/// ```
+/// use libcnb_data::lifecycle::BuildMetadata;
+///
+/// let toml_str = r#"
+/// buildpack-default-process-type = "web"
+///
+/// [[buildpacks]]
+/// id = "heroku/nodejs"
+/// version = "1.0.0"
+/// api = "0.10"
+///
+/// [[processes]]
+/// type = "web"
+/// command = ["npm", "start"]
+/// buildpack-id = "heroku/nodejs"
+/// "#;
+///
+/// let build_metadata = toml::from_str::<BuildMetadata>(toml_str).unwrap();
+/// assert_eq!(build_metadata.processes[0].command, ["npm", "start"]);
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct BuildMetadata {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buildpacks: Vec<BuildMetadataBuildpack>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<BuildMetadataBuildpack>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub processes: Vec<BuildMetadataProcess>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<Label>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slices: Vec<Slice>,
+    pub buildpack_default_process_type: Option<ProcessType>,
+}
+
+/// A buildpack or image extension that took part in the build.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct BuildMetadataBuildpack {
+    /// The id of the buildpack or image extension. Unlike the `io.buildpacks.build.metadata` image
+    /// label, which uses `key`, `metadata.toml` uses `id`.
+    pub id: BuildpackId,
+    pub version: String,
+    pub api: BuildpackApi,
+}
+
+/// A process contributed by a buildpack.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct BuildMetadataProcess {
+    pub r#type: ProcessType,
+    pub command: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub direct: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub default: bool,
+    pub buildpack_id: BuildpackId,
+    pub working_dir: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_build_metadata() {
+        let toml_str = r#"
+buildpack-default-process-type = "web"
+
+[[buildpacks]]
+id = "heroku/nodejs"
+version = "1.0.0"
+api = "0.10"
+
+[[extensions]]
+id = "heroku/apt"
+version = "0.1.0"
+api = "0.10"
+
+[[processes]]
+type = "web"
+command = ["npm"]
+args = ["start"]
+default = true
+buildpack-id = "heroku/nodejs"
+working-dir = "/workspace/web"
+
+[[labels]]
+key = "com.example.label"
+value = "value"
+
+[[slices]]
+paths = ["static/*"]
+
+[bom]
+ignored = true
+"#;
+
+        let build_metadata = toml::from_str::<BuildMetadata>(toml_str).unwrap();
+
+        assert_eq!(
+            build_metadata,
+            BuildMetadata {
+                buildpacks: vec![BuildMetadataBuildpack {
+                    id: "heroku/nodejs".parse().unwrap(),
+                    version: String::from("1.0.0"),
+                    api: BuildpackApi {
+                        major: 0,
+                        minor: 10
+                    },
+                }],
+                extensions: vec![BuildMetadataBuildpack {
+                    id: "heroku/apt".parse().unwrap(),
+                    version: String::from("0.1.0"),
+                    api: BuildpackApi {
+                        major: 0,
+                        minor: 10
+                    },
+                }],
+                processes: vec![BuildMetadataProcess {
+                    r#type: "web".parse().unwrap(),
+                    command: vec![String::from("npm")],
+                    args: vec![String::from("start")],
+                    direct: false,
+                    default: true,
+                    buildpack_id: "heroku/nodejs".parse().unwrap(),
+                    working_dir: Some(String::from("/workspace/web")),
+                }],
+                labels: vec![Label {
+                    key: String::from("com.example.label"),
+                    value: String::from("value"),
+                }],
+                slices: vec![Slice {
+                    path_globs: vec![String::from("static/*")],
+                }],
+                buildpack_default_process_type: Some("web".parse().unwrap()),
+            }
+        );
+    }
+}
@@ -0,0 +1,18 @@
+//! Data structures for the intermediate files written by the lifecycle.
+//!
+//! These files are part of the [Platform Interface Specification](https://github.com/buildpacks/spec/blob/main/platform.md)
+//! and are read by platforms and by the lifecycle itself. Buildpacks usually don't need them, but
+//! they are useful for platform tooling and for tests that inspect what the lifecycle produced.
+//!
+//! Unlike the data structures for files written by buildpacks, these data structures ignore
+//! unknown fields, since newer lifecycle versions may add fields to these files.
+
+mod analyzed;
+mod group;
+mod metadata;
+mod plan;
+
+pub use analyzed::*;
+pub use group::*;
+pub use metadata::*;
+pub use plan::*;
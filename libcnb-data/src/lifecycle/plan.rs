@@ -0,0 +1,122 @@
+use crate::buildpack::BuildpackId;
+use serde::{Deserialize, Serialize};
+use toml::value::Table;
+
+/// Data structure for the resolved build plan of the group (plan.toml).
+///
+/// This is the plan written by the lifecycle after detection, containing the requirements of all
+/// buildpacks in the group and the buildpacks that provide them. For the plan that is passed to
+/// a single buildpack, see [`BuildpackPlan`](crate::buildpack_plan::BuildpackPlan).
+///
+/// # Example
+/// ```
+/// use libcnb_data::lifecycle::Plan;
+///
+/// let toml_str = r#"
+/// [[entries]]
+///
+/// [[entries.providers]]
+/// id = "heroku/nodejs"
+/// version = "1.0.0"
+///
+/// [[entries.requires]]
+/// name = "node"
+/// "#;
+///
+/// let plan = toml::from_str::<Plan>(toml_str).unwrap();
+/// assert_eq!(plan.entries[0].requires[0].name, "node");
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<PlanEntry>,
+}
+
+/// An entry of a [`Plan`], pairing requirements with the buildpacks that provide them.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct PlanEntry {
+    #[serde(default)]
+    pub providers: Vec<PlanProvider>,
+    #[serde(default)]
+    pub requires: Vec<PlanRequire>,
+}
+
+/// A buildpack that provides the requirements of a [`PlanEntry`].
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct PlanProvider {
+    pub id: BuildpackId,
+    pub version: String,
+}
+
+/// A requirement of a [`PlanEntry`].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PlanRequire {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub metadata: Table,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_plan() {
+        let toml_str = r#"
+[[entries]]
+
+[[entries.providers]]
+id = "heroku/nodejs"
+version = "1.0.0"
+
+[[entries.requires]]
+name = "node"
+
+[entries.requires.metadata]
+version = "20.x"
+
+[[entries]]
+
+[[entries.providers]]
+id = "heroku/procfile"
+version = "2.0.0"
+
+[[entries.requires]]
+name = "procfile"
+"#;
+
+        let plan = toml::from_str::<Plan>(toml_str).unwrap();
+
+        let mut metadata = Table::new();
+        metadata.insert(
+            String::from("version"),
+            toml::Value::String(String::from("20.x")),
+        );
+
+        assert_eq!(
+            plan.entries,
+            [
+                PlanEntry {
+                    providers: vec![PlanProvider {
+                        id: "heroku/nodejs".parse().unwrap(),
+                        version: String::from("1.0.0"),
+                    }],
+                    requires: vec![PlanRequire {
+                        name: String::from("node"),
+                        metadata,
+                    }],
+                },
+                PlanEntry {
+                    providers: vec![PlanProvider {
+                        id: "heroku/procfile".parse().unwrap(),
+                        version: String::from("2.0.0"),
+                    }],
+                    requires: vec![PlanRequire {
+                        name: String::from("procfile"),
+                        metadata: Table::new(),
+                    }],
+                }
+            ]
+        );
+    }
+}
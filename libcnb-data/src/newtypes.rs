@@ -16,6 +16,7 @@
 /// - [`Borrow<str>`](std::borrow::Borrow<str>)
 /// - [`Deref<Target=String>`]
 /// - [`AsRef<String>`]
+/// - `schemars::JsonSchema` (when the `schemars` feature is enabled)
 ///
 /// This macro also generates another macro that can be used to construct values of the newtype from
 /// literal strings at compile time. Compilation will fail if such a macro is used with a string
@@ -61,6 +62,7 @@ macro_rules! libcnb_newtype {
         $regex:expr
     ) => {
         #[derive(Debug, Eq, PartialEq, ::serde::Serialize, Clone, Hash)]
+        #[cfg_attr(feature = "schemars", derive(::schemars::JsonSchema))]
         $(#[$type_attributes])*
         #[allow(unreachable_pub)]
         pub struct $name(String);
@@ -76,7 +78,7 @@ macro_rules! libcnb_newtype {
             fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
                 match self {
                     Self::InvalidValue(value) => {
-                        ::std::write!(f, "Invalid Value: {}", value)
+                        $crate::newtypes::fmt_invalid_value(f, stringify!($name), $regex, value)
                     }
                 }
             }
@@ -87,7 +89,8 @@ macro_rules! libcnb_newtype {
 
             fn from_str(value: &str) -> Result<Self, Self::Err> {
                 let regex_matches = ::fancy_regex::Regex::new($regex)
-                    .and_then(|regex| regex.is_match(value))
+                    .ok()
+                    .and_then(|regex| regex.is_match(value).ok())
                     .unwrap_or(false);
 
                 if regex_matches {
@@ -188,6 +191,46 @@ macro_rules! libcnb_newtype {
 
 pub(crate) use libcnb_newtype;
 
+/// Formats the error message for a value that doesn't match the regular expression of a newtype.
+///
+/// If possible, the message names the first character that makes the value invalid. If no such
+/// character can be found (i.e. the value is empty or reserved), only the pattern is shown.
+pub(crate) fn fmt_invalid_value(
+    f: &mut std::fmt::Formatter,
+    type_name: &str,
+    regex: &str,
+    value: &str,
+) -> std::fmt::Result {
+    write!(f, "Invalid {type_name} {value:?}: ")?;
+
+    if let Some((index, character)) = first_invalid_character(regex, value) {
+        write!(
+            f,
+            "character {character:?} at position {} is not allowed, ",
+            index + 1
+        )?;
+    }
+
+    write!(f, "the value must match {regex}")
+}
+
+// Finds the first character at which the prefix of the value stops matching the regular
+// expression. Prefixes that match again when their last character is repeated are skipped, since
+// they are only rejected as a whole, like the reserved names of some newtypes.
+fn first_invalid_character(regex: &str, value: &str) -> Option<(usize, char)> {
+    let regex = fancy_regex::Regex::new(regex).ok()?;
+    let is_match = |value: &str| regex.is_match(value).unwrap_or(false);
+
+    value
+        .char_indices()
+        .enumerate()
+        .find(|(_, (byte_index, character))| {
+            let prefix = &value[..byte_index + character.len_utf8()];
+            !is_match(prefix) && !is_match(&format!("{prefix}{character}"))
+        })
+        .map(|(index, (_, character))| (index, character))
+}
+
 #[cfg(test)]
 mod tests {
     use serde_test::{assert_de_tokens, assert_de_tokens_error, Token};
@@ -272,7 +315,17 @@ mod tests {
         assert_de_tokens(&capitalized_name!("Jonas"), &[Token::Str("Jonas")]);
         assert_de_tokens(&capitalized_name!("Johanna"), &[Token::Str("Johanna")]);
 
-        assert_de_tokens_error::<CapitalizedName>(&[Token::Str("Manuel")], "Invalid Value: Manuel");
-        assert_de_tokens_error::<CapitalizedName>(&[Token::Str("katrin")], "Invalid Value: katrin");
+        assert_de_tokens_error::<CapitalizedName>(
+            &[Token::Str("Manuel")],
+            r#"Invalid CapitalizedName "Manuel": the value must match ^(?!Manuel$)[A-Z][a-z]*$"#,
+        );
+        assert_de_tokens_error::<CapitalizedName>(
+            &[Token::Str("KaTrin")],
+            r#"Invalid CapitalizedName "KaTrin": character 'T' at position 3 is not allowed, the value must match ^(?!Manuel$)[A-Z][a-z]*$"#,
+        );
+        assert_de_tokens_error::<CapitalizedName>(
+            &[Token::Str("katrin")],
+            r#"Invalid CapitalizedName "katrin": character 'k' at position 1 is not allowed, the value must match ^(?!Manuel$)[A-Z][a-z]*$"#,
+        );
     }
 }
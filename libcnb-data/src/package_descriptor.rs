@@ -27,11 +27,15 @@ use uriparse::{URIReference, URIReferenceError};
 ///
 /// [platform]
 /// os = "windows"
+///
+/// [[targets]]
+/// os = "windows"
+/// arch = "amd64"
 /// "#;
 ///
 /// toml::from_str::<PackageDescriptor>(toml_str).unwrap();
 /// ```
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Eq, PartialEq, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct PackageDescriptor {
     /// The buildpack to package.
@@ -46,6 +50,12 @@ pub struct PackageDescriptor {
     /// The expected runtime environment for the packaged buildpack.
     #[serde(default)]
     pub platform: Platform,
+
+    /// The targets to create a multi-platform package for.
+    ///
+    /// If empty, a single package is created for the [`platform`](Self::platform).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<PackageDescriptorTarget>,
 }
 
 impl Default for PackageDescriptor {
@@ -55,6 +65,7 @@ impl Default for PackageDescriptor {
                 .expect("a package.toml with buildpack.uri=\".\" should be valid"),
             dependencies: Vec::new(),
             platform: Platform::default(),
+            targets: Vec::new(),
         }
     }
 }
@@ -71,8 +82,9 @@ pub struct PackageDescriptorBuildpackReference {
     pub uri: URIReference<'static>,
 }
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug)]
 pub enum PackageDescriptorBuildpackError {
+    #[error("Invalid URI: {0}")]
     InvalidUri(String),
 }
 
@@ -126,7 +138,7 @@ fn try_uri_from_str(value: &str) -> Result<URIReference<'static>, URIReferenceEr
 }
 
 /// The expected runtime environment for the packaged buildpack.
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Eq, PartialEq, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct Platform {
     /// The operating system type that the packaged buildpack will run on.
@@ -147,6 +159,32 @@ pub enum PlatformOs {
     Windows,
 }
 
+/// A target of a multi-platform package.
+#[derive(Debug, Default, Deserialize, Serialize, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PackageDescriptorTarget {
+    /// The operating system of the target, such as `linux`.
+    pub os: Option<String>,
+    /// The CPU architecture of the target, such as `amd64` or `arm64`.
+    pub arch: Option<String>,
+    /// The variant of the CPU architecture, such as `v8`.
+    pub variant: Option<String>,
+    /// The operating system distributions supported by the target.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub distributions: Vec<PackageDescriptorDistribution>,
+}
+
+/// An operating system distribution supported by a [`PackageDescriptorTarget`].
+#[derive(Debug, Deserialize, Serialize, Eq, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PackageDescriptorDistribution {
+    /// The name of the distribution, such as `ubuntu`.
+    pub name: String,
+    /// The supported versions of the distribution. If empty, all versions are supported.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+}
+
 // Even though `uriparse` has Serde support it only works if the value we are deserializing is an
 // map that contains URI fields like 'path', 'host', 'scheme', etc. The value from package.toml is
 // just a plain string so we need this custom deserializer that will parse the value into
@@ -243,6 +281,7 @@ os = "windows"
                     .unwrap(),
             ],
             platform: Platform::default(),
+            targets: Vec::new(),
         };
 
         let package_descriptor_contents = toml::to_string(&package_descriptor).unwrap();
@@ -270,4 +309,62 @@ os = "linux"
             .trim_start()
         );
     }
+
+    #[test]
+    fn it_parses_targets() {
+        let toml_str = r#"
+[buildpack]
+uri = "."
+
+[[targets]]
+os = "linux"
+arch = "arm64"
+variant = "v8"
+
+[[targets.distributions]]
+name = "ubuntu"
+versions = ["22.04", "24.04"]
+
+[[targets]]
+os = "linux"
+arch = "amd64"
+"#;
+
+        let package_descriptor = toml::from_str::<PackageDescriptor>(toml_str).unwrap();
+        assert_eq!(
+            package_descriptor.targets,
+            [
+                PackageDescriptorTarget {
+                    os: Some(String::from("linux")),
+                    arch: Some(String::from("arm64")),
+                    variant: Some(String::from("v8")),
+                    distributions: vec![PackageDescriptorDistribution {
+                        name: String::from("ubuntu"),
+                        versions: vec![String::from("22.04"), String::from("24.04")],
+                    }],
+                },
+                PackageDescriptorTarget {
+                    os: Some(String::from("linux")),
+                    arch: Some(String::from("amd64")),
+                    ..PackageDescriptorTarget::default()
+                },
+            ]
+        );
+        assert_eq!(
+            toml::from_str::<PackageDescriptor>(&toml::to_string(&package_descriptor).unwrap())
+                .unwrap(),
+            package_descriptor
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_uris() {
+        let error = PackageDescriptorDependency::try_from("http://[invalid").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid URI: http://[invalid");
+
+        assert!(
+            toml::from_str::<PackageDescriptor>("[buildpack]\nuri = \"http://[invalid\"\n")
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,695 @@
+use crate::buildpack::{BuildpackApi, BuildpackId, License};
+use crate::generic::GenericMetadata;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
+use toml::value::Table;
+
+/// Representation of [project.toml](https://github.com/buildpacks/spec/blob/main/extensions/project-descriptor.md),
+/// the project descriptor in which users configure how their application is built.
+///
+/// Both schema versions of the project descriptor are supported and parsed into the same data
+/// structure. A descriptor with a `_` table uses the schema version given in `_.schema-version`,
+/// which must be `0.2`. All other descriptors use the legacy schema version `0.1`. When
+/// serialized, the descriptor is written using its [`schema_version`](Self::schema_version).
+///
+/// Tables of other tools, such as `[com.example]`, are ignored.
+///
+/// # Example
+/// ```
+/// use libcnb_data::buildpack_id;
+/// use libcnb_data::project_descriptor::{ProjectDescriptor, ProjectDescriptorSchemaVersion};
+///
+/// let toml_str = r#"
+/// [_]
+/// schema-version = "0.2"
+/// id = "com.example.my-app"
+/// name = "My App"
+///
+/// [io.buildpacks]
+/// builder = "heroku/builder:24"
+/// exclude = ["*.log"]
+///
+/// [[io.buildpacks.group]]
+/// id = "heroku/nodejs"
+///
+/// [[io.buildpacks.build.env]]
+/// name = "NODE_ENV"
+/// value = "production"
+/// "#;
+///
+/// let project_descriptor = toml::from_str::<ProjectDescriptor>(toml_str).unwrap();
+/// assert_eq!(
+///     project_descriptor.schema_version,
+///     ProjectDescriptorSchemaVersion::V0_2
+/// );
+/// assert_eq!(project_descriptor.project.name.as_deref(), Some("My App"));
+/// assert_eq!(
+///     project_descriptor.build.buildpacks[0].id,
+///     Some(buildpack_id!("heroku/nodejs"))
+/// );
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProjectDescriptor<M = GenericMetadata> {
+    /// The schema version of the descriptor.
+    pub schema_version: ProjectDescriptorSchemaVersion,
+    /// Information about the project, such as its name and licenses.
+    pub project: Project,
+    /// Configuration for building the project with buildpacks.
+    pub build: ProjectBuild,
+    /// Arbitrary metadata about the project.
+    pub metadata: M,
+}
+
+impl<M: Default> Default for ProjectDescriptor<M> {
+    fn default() -> Self {
+        Self {
+            schema_version: ProjectDescriptorSchemaVersion::V0_2,
+            project: Project::default(),
+            build: ProjectBuild::default(),
+            metadata: M::default(),
+        }
+    }
+}
+
+/// The schema version of a project descriptor.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ProjectDescriptorSchemaVersion {
+    /// The legacy schema, using the `[project]`, `[build]` and `[metadata]` tables.
+    V0_1,
+    /// The current schema, using the `[_]` and `[io.buildpacks]` tables.
+    V0_2,
+}
+
+impl Display for ProjectDescriptorSchemaVersion {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::V0_1 => "0.1",
+            Self::V0_2 => "0.2",
+        })
+    }
+}
+
+/// Information about a project.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Project {
+    /// A machine readable identifier for the project.
+    pub id: Option<String>,
+    /// A human readable name for the project.
+    pub name: Option<String>,
+    /// The version of the project.
+    pub version: Option<String>,
+    /// The names and/or email addresses of the authors of the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// A URL that points to the documentation of the project.
+    pub documentation_url: Option<String>,
+    /// A URL that points to the source code of the project.
+    pub source_url: Option<String>,
+    /// The licenses of the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<License>,
+}
+
+/// Configuration for building a project with buildpacks.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ProjectBuild {
+    /// The builder image to use.
+    pub builder: Option<String>,
+    /// Gitignore-style patterns of the files to include in the build. Mutually exclusive with
+    /// [`exclude`](Self::exclude).
+    pub include: Vec<String>,
+    /// Gitignore-style patterns of the files to exclude from the build. Mutually exclusive with
+    /// [`include`](Self::include).
+    pub exclude: Vec<String>,
+    /// The buildpacks to use, replacing the order of the builder.
+    pub buildpacks: Vec<ProjectBuildpackReference>,
+    /// Buildpacks to prepend to every group of the order. Only supported by schema version `0.2`.
+    pub pre_buildpacks: Vec<ProjectBuildpackReference>,
+    /// Buildpacks to append to every group of the order. Only supported by schema version `0.2`.
+    pub post_buildpacks: Vec<ProjectBuildpackReference>,
+    /// Environment variables to set during the build.
+    pub env: Vec<ProjectBuildEnv>,
+}
+
+/// A reference to a buildpack in a project descriptor.
+///
+/// A buildpack is either referenced by its `id` (and optionally its `version`), by its `uri`, or
+/// defined inline with a `script`.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectBuildpackReference {
+    /// The id of the buildpack.
+    pub id: Option<BuildpackId>,
+    /// The version of the buildpack.
+    pub version: Option<String>,
+    /// A URL or path to the buildpack.
+    pub uri: Option<String>,
+    /// An inline buildpack.
+    pub script: Option<ProjectBuildpackScript>,
+}
+
+/// An inline buildpack, defined by a script that is run as the build executable.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectBuildpackScript {
+    /// The Buildpack API version of the inline buildpack.
+    pub api: BuildpackApi,
+    /// The script to run.
+    pub inline: String,
+    /// The shell used to run the script. Defaults to `/bin/sh` if not set.
+    pub shell: Option<String>,
+}
+
+/// An environment variable to set during the build.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectBuildEnv {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProjectDescriptorError {
+    #[error("Unsupported project descriptor schema version: `{0}`")]
+    UnsupportedSchemaVersion(String),
+    #[error("Invalid project descriptor: {0}")]
+    InvalidDescriptor(#[from] toml::de::Error),
+}
+
+impl<M: DeserializeOwned> TryFrom<Table> for ProjectDescriptor<M> {
+    type Error = ProjectDescriptorError;
+
+    fn try_from(mut table: Table) -> Result<Self, Self::Error> {
+        match table.remove("_") {
+            Some(project) => {
+                let mut project = project.try_into::<Table>()?;
+
+                match project.remove("schema-version") {
+                    Some(toml::Value::String(schema_version)) if schema_version == "0.2" => {}
+                    Some(schema_version) => {
+                        return Err(ProjectDescriptorError::UnsupportedSchemaVersion(
+                            schema_version
+                                .as_str()
+                                .map_or_else(|| schema_version.to_string(), String::from),
+                        ))
+                    }
+                    None => {
+                        return Err(ProjectDescriptorError::UnsupportedSchemaVersion(
+                            String::new(),
+                        ))
+                    }
+                }
+
+                let metadata = project.remove("metadata");
+
+                let build = table
+                    .remove("io")
+                    .map(toml::Value::try_into::<Table>)
+                    .transpose()?
+                    .and_then(|mut io| io.remove("buildpacks"))
+                    .map(toml::Value::try_into::<BuildTableV0_2>)
+                    .transpose()?
+                    .unwrap_or_default();
+
+                Ok(Self {
+                    schema_version: ProjectDescriptorSchemaVersion::V0_2,
+                    project: toml::Value::Table(project).try_into()?,
+                    build: build.into(),
+                    metadata: deserialize_metadata(metadata)?,
+                })
+            }
+            None => Ok(Self {
+                schema_version: ProjectDescriptorSchemaVersion::V0_1,
+                project: table
+                    .remove("project")
+                    .map(toml::Value::try_into)
+                    .transpose()?
+                    .unwrap_or_default(),
+                build: table
+                    .remove("build")
+                    .map(toml::Value::try_into::<BuildTableV0_1>)
+                    .transpose()?
+                    .unwrap_or_default()
+                    .into(),
+                metadata: deserialize_metadata(table.remove("metadata"))?,
+            }),
+        }
+    }
+}
+
+// Deserializes the metadata via a table with an optional `metadata` key, so that missing
+// metadata is handled the same way as in other descriptors: `Option` types become `None`, all
+// other types fail to deserialize.
+fn deserialize_metadata<M: DeserializeOwned>(
+    metadata: Option<toml::Value>,
+) -> Result<M, toml::de::Error> {
+    #[derive(Deserialize)]
+    struct MetadataTable<M> {
+        metadata: M,
+    }
+
+    let mut table = Table::new();
+    if let Some(metadata) = metadata {
+        table.insert(String::from("metadata"), metadata);
+    }
+
+    toml::Value::Table(table)
+        .try_into::<MetadataTable<M>>()
+        .map(|table| table.metadata)
+}
+
+impl<'de, M: DeserializeOwned> Deserialize<'de> for ProjectDescriptor<M> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let table = Table::deserialize(deserializer)?;
+        Self::try_from(table).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<M: Serialize> Serialize for ProjectDescriptor<M> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.schema_version {
+            ProjectDescriptorSchemaVersion::V0_1 => ProjectDescriptorV0_1 {
+                project: &self.project,
+                build: BuildTableV0_1Ref {
+                    builder: self.build.builder.as_ref(),
+                    include: &self.build.include,
+                    exclude: &self.build.exclude,
+                    buildpacks: &self.build.buildpacks,
+                    env: &self.build.env,
+                },
+                metadata: &self.metadata,
+            }
+            .serialize(serializer),
+            ProjectDescriptorSchemaVersion::V0_2 => ProjectDescriptorV0_2 {
+                project: ProjectTableV0_2 {
+                    schema_version: "0.2",
+                    project: &self.project,
+                    metadata: &self.metadata,
+                },
+                io: IoTable {
+                    buildpacks: BuildTableV0_2Ref {
+                        builder: self.build.builder.as_ref(),
+                        include: &self.build.include,
+                        exclude: &self.build.exclude,
+                        group: &self.build.buildpacks,
+                        pre: GroupTable {
+                            group: &self.build.pre_buildpacks,
+                        },
+                        post: GroupTable {
+                            group: &self.build.post_buildpacks,
+                        },
+                        build: EnvTable {
+                            env: &self.build.env,
+                        },
+                    },
+                },
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct BuildTableV0_1 {
+    builder: Option<String>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    buildpacks: Vec<ProjectBuildpackReference>,
+    #[serde(default)]
+    env: Vec<ProjectBuildEnv>,
+}
+
+impl From<BuildTableV0_1> for ProjectBuild {
+    fn from(value: BuildTableV0_1) -> Self {
+        Self {
+            builder: value.builder,
+            include: value.include,
+            exclude: value.exclude,
+            buildpacks: value.buildpacks,
+            pre_buildpacks: Vec::new(),
+            post_buildpacks: Vec::new(),
+            env: value.env,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct BuildTableV0_2 {
+    builder: Option<String>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    group: Vec<ProjectBuildpackReference>,
+    #[serde(default)]
+    pre: GroupTable<Vec<ProjectBuildpackReference>>,
+    #[serde(default)]
+    post: GroupTable<Vec<ProjectBuildpackReference>>,
+    #[serde(default)]
+    build: EnvTable<Vec<ProjectBuildEnv>>,
+}
+
+impl From<BuildTableV0_2> for ProjectBuild {
+    fn from(value: BuildTableV0_2) -> Self {
+        Self {
+            builder: value.builder,
+            include: value.include,
+            exclude: value.exclude,
+            buildpacks: value.group,
+            pre_buildpacks: value.pre.group,
+            post_buildpacks: value.post.group,
+            env: value.build.env,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(deny_unknown_fields)]
+struct GroupTable<G> {
+    #[serde(default)]
+    group: G,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(deny_unknown_fields)]
+struct EnvTable<E> {
+    #[serde(default)]
+    env: E,
+}
+
+#[derive(Serialize)]
+struct ProjectDescriptorV0_1<'a, M> {
+    project: &'a Project,
+    build: BuildTableV0_1Ref<'a>,
+    #[serde(skip_serializing_if = "is_none_or_empty")]
+    metadata: &'a M,
+}
+
+#[derive(Serialize)]
+struct BuildTableV0_1Ref<'a> {
+    builder: Option<&'a String>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    include: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    exclude: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    buildpacks: &'a [ProjectBuildpackReference],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    env: &'a [ProjectBuildEnv],
+}
+
+#[derive(Serialize)]
+struct ProjectDescriptorV0_2<'a, M> {
+    #[serde(rename = "_")]
+    project: ProjectTableV0_2<'a, M>,
+    io: IoTable<'a>,
+}
+
+#[derive(Serialize)]
+struct ProjectTableV0_2<'a, M> {
+    #[serde(rename = "schema-version")]
+    schema_version: &'static str,
+    #[serde(flatten)]
+    project: &'a Project,
+    #[serde(skip_serializing_if = "is_none_or_empty")]
+    metadata: &'a M,
+}
+
+#[derive(Serialize)]
+struct IoTable<'a> {
+    buildpacks: BuildTableV0_2Ref<'a>,
+}
+
+#[derive(Serialize)]
+struct BuildTableV0_2Ref<'a> {
+    builder: Option<&'a String>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    include: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    exclude: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    group: &'a [ProjectBuildpackReference],
+    #[serde(skip_serializing_if = "GroupTable::is_empty")]
+    pre: GroupTable<&'a [ProjectBuildpackReference]>,
+    #[serde(skip_serializing_if = "GroupTable::is_empty")]
+    post: GroupTable<&'a [ProjectBuildpackReference]>,
+    #[serde(skip_serializing_if = "EnvTable::is_empty")]
+    build: EnvTable<&'a [ProjectBuildEnv]>,
+}
+
+impl GroupTable<&[ProjectBuildpackReference]> {
+    fn is_empty(&self) -> bool {
+        self.group.is_empty()
+    }
+}
+
+impl EnvTable<&[ProjectBuildEnv]> {
+    fn is_empty(&self) -> bool {
+        self.env.is_empty()
+    }
+}
+
+// Skips serializing metadata that would be written as an empty table, such as `None` for
+// `GenericMetadata`. Serde passes a reference to the `&M` field, hence the double reference.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_none_or_empty<M: Serialize>(metadata: &&M) -> bool {
+    toml::Value::try_from(metadata)
+        .map_or(true, |value| value.as_table().is_some_and(Table::is_empty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_v0_2() {
+        let toml_str = r#"
+[_]
+schema-version = "0.2"
+id = "com.example.my-app"
+name = "My App"
+version = "1.0.2"
+authors = ["Jane Doe <jane@example.com>"]
+documentation-url = "https://example.com/docs"
+source-url = "https://example.com/source"
+
+[[_.licenses]]
+type = "MIT"
+
+[_.metadata]
+foo = "bar"
+
+[io.buildpacks]
+builder = "heroku/builder:24"
+include = ["src/", "Cargo.toml"]
+
+[[io.buildpacks.pre.group]]
+uri = "https://example.com/pre.tgz"
+
+[[io.buildpacks.group]]
+id = "heroku/nodejs"
+version = "1.2.3"
+
+[[io.buildpacks.group]]
+[io.buildpacks.group.script]
+api = "0.10"
+inline = "echo hello"
+
+[[io.buildpacks.post.group]]
+id = "heroku/procfile"
+
+[[io.buildpacks.build.env]]
+name = "NODE_ENV"
+value = "production"
+
+[com.example]
+ignored = true
+"#;
+
+        let project_descriptor = toml::from_str::<ProjectDescriptor>(toml_str).unwrap();
+
+        assert_eq!(
+            project_descriptor,
+            ProjectDescriptor {
+                schema_version: ProjectDescriptorSchemaVersion::V0_2,
+                project: Project {
+                    id: Some(String::from("com.example.my-app")),
+                    name: Some(String::from("My App")),
+                    version: Some(String::from("1.0.2")),
+                    authors: vec![String::from("Jane Doe <jane@example.com>")],
+                    documentation_url: Some(String::from("https://example.com/docs")),
+                    source_url: Some(String::from("https://example.com/source")),
+                    licenses: vec![License {
+                        r#type: Some(String::from("MIT")),
+                        uri: None,
+                    }],
+                },
+                build: ProjectBuild {
+                    builder: Some(String::from("heroku/builder:24")),
+                    include: vec![String::from("src/"), String::from("Cargo.toml")],
+                    exclude: Vec::new(),
+                    buildpacks: vec![
+                        ProjectBuildpackReference {
+                            id: Some("heroku/nodejs".parse().unwrap()),
+                            version: Some(String::from("1.2.3")),
+                            ..ProjectBuildpackReference::default()
+                        },
+                        ProjectBuildpackReference {
+                            script: Some(ProjectBuildpackScript {
+                                api: BuildpackApi {
+                                    major: 0,
+                                    minor: 10
+                                },
+                                inline: String::from("echo hello"),
+                                shell: None,
+                            }),
+                            ..ProjectBuildpackReference::default()
+                        }
+                    ],
+                    pre_buildpacks: vec![ProjectBuildpackReference {
+                        uri: Some(String::from("https://example.com/pre.tgz")),
+                        ..ProjectBuildpackReference::default()
+                    }],
+                    post_buildpacks: vec![ProjectBuildpackReference {
+                        id: Some("heroku/procfile".parse().unwrap()),
+                        ..ProjectBuildpackReference::default()
+                    }],
+                    env: vec![ProjectBuildEnv {
+                        name: String::from("NODE_ENV"),
+                        value: String::from("production"),
+                    }],
+                },
+                metadata: Some(toml::toml! { foo = "bar" }),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_v0_1() {
+        let toml_str = r#"
+[project]
+id = "com.example.my-app"
+name = "My App"
+
+[build]
+exclude = ["*.log"]
+
+[[build.buildpacks]]
+id = "heroku/nodejs"
+
+[[build.env]]
+name = "NODE_ENV"
+value = "production"
+
+[metadata]
+foo = "bar"
+"#;
+
+        let project_descriptor = toml::from_str::<ProjectDescriptor>(toml_str).unwrap();
+
+        assert_eq!(
+            project_descriptor.schema_version,
+            ProjectDescriptorSchemaVersion::V0_1
+        );
+        assert_eq!(project_descriptor.project.name.as_deref(), Some("My App"));
+        assert_eq!(project_descriptor.build.exclude, [String::from("*.log")]);
+        assert_eq!(
+            project_descriptor.build.buildpacks,
+            [ProjectBuildpackReference {
+                id: Some("heroku/nodejs".parse().unwrap()),
+                ..ProjectBuildpackReference::default()
+            }]
+        );
+        assert_eq!(
+            project_descriptor.build.env,
+            [ProjectBuildEnv {
+                name: String::from("NODE_ENV"),
+                value: String::from("production"),
+            }]
+        );
+        assert_eq!(
+            project_descriptor.metadata,
+            Some(toml::toml! { foo = "bar" })
+        );
+    }
+
+    #[test]
+    fn deserialize_empty() {
+        let project_descriptor = toml::from_str::<ProjectDescriptor>("").unwrap();
+
+        assert_eq!(
+            project_descriptor,
+            ProjectDescriptor {
+                schema_version: ProjectDescriptorSchemaVersion::V0_1,
+                ..ProjectDescriptor::default()
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_unsupported_schema_version() {
+        let error =
+            toml::from_str::<ProjectDescriptor>("[_]\nschema-version = \"0.3\"\n").unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Unsupported project descriptor schema version: `0.3`"));
+    }
+
+    #[test]
+    fn deserialize_unknown_field() {
+        assert!(toml::from_str::<ProjectDescriptor>(
+            "[_]\nschema-version = \"0.2\"\n\n[io.buildpacks]\nbuildpacks = []\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn serialize_roundtrip() {
+        for toml_str in [
+            r#"[_]
+schema-version = "0.2"
+name = "My App"
+
+[_.metadata]
+foo = "bar"
+
+[io.buildpacks]
+builder = "heroku/builder:24"
+
+[[io.buildpacks.group]]
+id = "heroku/nodejs"
+
+[[io.buildpacks.post.group]]
+id = "heroku/procfile"
+
+[[io.buildpacks.build.env]]
+name = "NODE_ENV"
+value = "production"
+"#,
+            r#"[project]
+name = "My App"
+
+[[build.buildpacks]]
+id = "heroku/nodejs"
+"#,
+        ] {
+            let project_descriptor = toml::from_str::<ProjectDescriptor>(toml_str).unwrap();
+            assert_eq!(toml::to_string(&project_descriptor).unwrap(), toml_str);
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Data structure for the run images of a builder (run.toml).
+///
+/// The first image is the default run image. Platforms may select any of the other images, for
+/// example to build for a different target.
+///
+/// # Example
+/// ```
+/// use libcnb_data::run_image::RunMetadata;
+///
+/// let toml_str = r#"
+/// [[images]]
+/// image = "heroku/heroku:24"
+/// mirrors = ["registry.example.com/heroku/heroku:24"]
+/// "#;
+///
+/// let run_metadata = toml::from_str::<RunMetadata>(toml_str).unwrap();
+/// assert_eq!(run_metadata.images[0].image.repository(), "heroku/heroku");
+/// assert_eq!(run_metadata.images[0].image.tag(), Some("24"));
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RunMetadata {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<RunImage>,
+}
+
+/// A run image and its mirrors.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RunImage {
+    pub image: ImageReference,
+    /// Mirrors of the image in other registries. Platforms may use a mirror instead of the image,
+    /// for example when the mirror is in the same registry as the app image.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<ImageReference>,
+}
+
+/// A reference to an OCI image.
+///
+/// It MUST be in the form `[<registry>/]<repository>[:<tag>][@<digest>]`, as used by Docker and
+/// other OCI tools. A registry is only recognized as such if it contains a `.` or a `:`, or is
+/// `localhost`. No defaults are applied, `heroku/heroku:24` has no registry.
+///
+/// # Examples
+/// ```
+/// use libcnb_data::run_image::ImageReference;
+///
+/// let image_reference: ImageReference = "registry.example.com:5000/heroku/heroku:24"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(image_reference.registry(), Some("registry.example.com:5000"));
+/// assert_eq!(image_reference.repository(), "heroku/heroku");
+/// assert_eq!(image_reference.tag(), Some("24"));
+/// assert_eq!(image_reference.digest(), None);
+///
+/// assert!("Heroku/Heroku:24".parse::<ImageReference>().is_err());
+/// ```
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+#[serde(try_from = "String")]
+pub struct ImageReference {
+    registry: Option<String>,
+    repository: String,
+    tag: Option<String>,
+    digest: Option<String>,
+}
+
+impl ImageReference {
+    /// The registry of the image, including the port, if any.
+    #[must_use]
+    pub fn registry(&self) -> Option<&str> {
+        self.registry.as_deref()
+    }
+
+    /// The repository of the image, without the registry.
+    #[must_use]
+    pub fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    /// The tag of the image, if any.
+    #[must_use]
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// The digest of the image, such as `sha256:...`, if any.
+    #[must_use]
+    pub fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+}
+
+impl FromStr for ImageReference {
+    type Err = ImageReferenceError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || ImageReferenceError::InvalidImageReference(String::from(value));
+
+        let (name, digest) = match value.split_once('@') {
+            Some((name, digest)) if is_valid_digest(digest) => (name, Some(String::from(digest))),
+            Some(_) => return Err(invalid()),
+            None => (value, None),
+        };
+
+        // A colon after the last slash separates the tag, other colons separate the port of the
+        // registry.
+        let (name, tag) = match name.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => {
+                if !is_valid_tag(tag) {
+                    return Err(invalid());
+                }
+                (name, Some(String::from(tag)))
+            }
+            _ => (name, None),
+        };
+
+        let (registry, repository) = match name.split_once('/') {
+            Some((registry, repository))
+                if registry.contains(['.', ':']) || registry == "localhost" =>
+            {
+                if !is_valid_registry(registry) {
+                    return Err(invalid());
+                }
+                (Some(String::from(registry)), repository)
+            }
+            _ => (None, name),
+        };
+
+        if name.len() > 255 || !repository.split('/').all(is_valid_path_component) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            registry,
+            repository: String::from(repository),
+            tag,
+            digest,
+        })
+    }
+}
+
+impl TryFrom<String> for ImageReference {
+    type Error = ImageReferenceError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for ImageReference {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(registry) = &self.registry {
+            write!(formatter, "{registry}/")?;
+        }
+        formatter.write_str(&self.repository)?;
+        if let Some(tag) = &self.tag {
+            write!(formatter, ":{tag}")?;
+        }
+        if let Some(digest) = &self.digest {
+            write!(formatter, "@{digest}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for ImageReference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum ImageReferenceError {
+    #[error("Invalid image reference: `{0}`")]
+    InvalidImageReference(String),
+}
+
+// Path components are lowercase alphanumeric, separated by `.`, `_`, `__` or any number of `-`.
+fn is_valid_path_component(component: &str) -> bool {
+    let is_alphanumeric = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+
+    component.starts_with(is_alphanumeric)
+        && component.ends_with(is_alphanumeric)
+        && component
+            .split(is_alphanumeric)
+            .filter(|separator| !separator.is_empty())
+            .all(|separator| {
+                matches!(separator, "." | "_" | "__") || separator.chars().all(|c| c == '-')
+            })
+}
+
+fn is_valid_registry(registry: &str) -> bool {
+    let (host, port) = registry.split_once(':').unwrap_or((registry, "80"));
+
+    !host.is_empty()
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && !port.is_empty()
+        && port.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_valid_tag(tag: &str) -> bool {
+    tag.len() <= 128
+        && tag
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+fn is_valid_digest(digest: &str) -> bool {
+    digest.split_once(':').is_some_and(|(algorithm, encoded)| {
+        !algorithm.is_empty()
+            && algorithm.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '+' | '.' | '_' | '-')
+            })
+            && encoded.len() >= 32
+            && encoded.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn parse_image_references() {
+        let image_reference = "heroku/heroku:24".parse::<ImageReference>().unwrap();
+        assert_eq!(image_reference.registry(), None);
+        assert_eq!(image_reference.repository(), "heroku/heroku");
+        assert_eq!(image_reference.tag(), Some("24"));
+        assert_eq!(image_reference.digest(), None);
+
+        let image_reference = "ubuntu".parse::<ImageReference>().unwrap();
+        assert_eq!(image_reference.registry(), None);
+        assert_eq!(image_reference.repository(), "ubuntu");
+        assert_eq!(image_reference.tag(), None);
+
+        let image_reference = format!("localhost:5000/my-org/run_image.v2@{DIGEST}")
+            .parse::<ImageReference>()
+            .unwrap();
+        assert_eq!(image_reference.registry(), Some("localhost:5000"));
+        assert_eq!(image_reference.repository(), "my-org/run_image.v2");
+        assert_eq!(image_reference.tag(), None);
+        assert_eq!(image_reference.digest(), Some(DIGEST));
+
+        let image_reference = format!("gcr.io/project/image:v1.2.3@{DIGEST}")
+            .parse::<ImageReference>()
+            .unwrap();
+        assert_eq!(image_reference.registry(), Some("gcr.io"));
+        assert_eq!(image_reference.repository(), "project/image");
+        assert_eq!(image_reference.tag(), Some("v1.2.3"));
+        assert_eq!(image_reference.digest(), Some(DIGEST));
+    }
+
+    #[test]
+    fn reject_invalid_image_references() {
+        for value in [
+            "",
+            "Heroku/heroku",
+            "heroku//heroku",
+            "heroku/heroku:",
+            "heroku/heroku:-24",
+            "heroku/heroku@sha256:abc",
+            "heroku/heroku@",
+            "-heroku/heroku",
+            "heroku/heroku-",
+            "registry.example.com:port/heroku",
+            "registry..example.com/heroku",
+            "heroku/he..roku",
+            "heroku/heroku:24 ",
+        ] {
+            assert_eq!(
+                value.parse::<ImageReference>(),
+                Err(ImageReferenceError::InvalidImageReference(String::from(
+                    value
+                ))),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn display_image_references() {
+        for value in [
+            "heroku/heroku:24",
+            "ubuntu",
+            "registry.example.com:5000/heroku/heroku:24",
+            &format!("gcr.io/project/image:v1@{DIGEST}"),
+        ] {
+            assert_eq!(value.parse::<ImageReference>().unwrap().to_string(), value);
+        }
+    }
+
+    #[test]
+    fn deserialize_run_metadata() {
+        let toml_str = r#"
+[[images]]
+image = "heroku/heroku:24"
+mirrors = ["registry.example.com/heroku/heroku:24"]
+
+[[images]]
+image = "heroku/heroku:22"
+"#;
+
+        let run_metadata = toml::from_str::<RunMetadata>(toml_str).unwrap();
+
+        assert_eq!(
+            run_metadata,
+            RunMetadata {
+                images: vec![
+                    RunImage {
+                        image: "heroku/heroku:24".parse().unwrap(),
+                        mirrors: vec!["registry.example.com/heroku/heroku:24".parse().unwrap()],
+                    },
+                    RunImage {
+                        image: "heroku/heroku:22".parse().unwrap(),
+                        mirrors: Vec::new(),
+                    },
+                ]
+            }
+        );
+        assert_eq!(
+            toml::to_string(&run_metadata).unwrap(),
+            toml_str.trim_start()
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_run_metadata() {
+        assert!(toml::from_str::<RunMetadata>("[[images]]\nimage = \"Invalid\"\n").is_err());
+    }
+}
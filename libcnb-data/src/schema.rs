@@ -0,0 +1,94 @@
+//! Helpers for the JSON Schemas generated when the `schemars` feature is enabled.
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject, StringValidation};
+use schemars::JsonSchema;
+
+/// Stand-in for [`toml::value::Table`], which does not implement [`JsonSchema`].
+///
+/// Also used for user-defined metadata types, which are not known when the schema is generated.
+pub(crate) struct TomlTable;
+
+impl JsonSchema for TomlTable {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::from("Table")
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            ..SchemaObject::default()
+        }
+        .into()
+    }
+}
+
+/// Schema for a string that must match the given ECMA 262 regular expression.
+pub(crate) fn string_with_pattern(pattern: &str) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(String::from(pattern)),
+            ..StringValidation::default()
+        })),
+        ..SchemaObject::default()
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build_plan::BuildPlan;
+    use crate::buildpack::ComponentBuildpackDescriptor;
+    use crate::launch::Launch;
+    use schemars::schema_for;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn buildpack_descriptor_schema() {
+        let schema = serde_json::to_value(schema_for!(ComponentBuildpackDescriptor)).unwrap();
+
+        assert_eq!(schema["title"], "ComponentBuildpackDescriptor");
+        assert_eq!(schema["required"], json!(["api", "buildpack"]));
+        assert_eq!(schema["additionalProperties"], Value::Bool(false));
+        assert_eq!(
+            schema["properties"]["metadata"],
+            json!({ "type": ["object", "null"] })
+        );
+        assert_eq!(
+            schema["definitions"]["BuildpackVersion"]["pattern"],
+            r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)$"
+        );
+        assert_eq!(schema["definitions"]["BuildpackId"]["type"], "string");
+    }
+
+    #[test]
+    fn launch_schema() {
+        let schema = serde_json::to_value(schema_for!(Launch)).unwrap();
+
+        assert_eq!(schema["required"], Value::Null);
+        assert_eq!(
+            schema["definitions"]["Process"]["required"],
+            json!(["command", "type"])
+        );
+        assert_eq!(
+            schema["definitions"]["WorkingDirectory"],
+            json!({ "type": "string" })
+        );
+    }
+
+    #[test]
+    fn build_plan_schema() {
+        let schema = serde_json::to_value(schema_for!(BuildPlan)).unwrap();
+
+        assert_eq!(schema["required"], Value::Null);
+        assert_eq!(
+            schema["definitions"]["Require"]["properties"]["metadata"],
+            json!({ "type": "object" })
+        );
+    }
+}
@@ -1,8 +1,264 @@
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use toml::value::Table;
 
-#[derive(Clone, Default, Debug, Deserialize, Serialize)]
+#[derive(Clone, Default, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct Store {
     pub metadata: Table,
 }
+
+/// The key of the table in the store metadata that expiring values are stored in.
+pub const EXPIRING_VALUES_KEY: &str = "libcnb-expiring";
+
+const EXPIRING_VALUE_KEY: &str = "value";
+const EXPIRING_EXPIRES_AT_KEY: &str = "expires_at";
+
+impl Store {
+    /// Inserts a value into the store metadata that expires after the given duration.
+    ///
+    /// Expiring values are stored in the reserved [`EXPIRING_VALUES_KEY`] table of the metadata,
+    /// as a table with a `value` and an `expires_at` key, the latter containing the expiry time in
+    /// seconds since the Unix epoch. Use [`Store::get_unexpired`] to read them and
+    /// [`Store::prune_expired`] to remove expired values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the expiry time can't be represented.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb_data::store::Store;
+    /// use std::time::Duration;
+    ///
+    /// let mut store = Store::default();
+    /// store
+    ///     .insert_with_ttl("last-version", "1.2.3", Duration::from_secs(30 * 24 * 60 * 60))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     store.get_unexpired("last-version").and_then(toml::Value::as_str),
+    ///     Some("1.2.3")
+    /// );
+    /// ```
+    pub fn insert_with_ttl(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<toml::Value>,
+        ttl: Duration,
+    ) -> Result<(), ExpiringValueError> {
+        let expires_at = SystemTime::now()
+            .checked_add(ttl)
+            .ok_or(ExpiringValueError::ExpiryOutOfRange)?;
+
+        self.insert_expiring(key, value, expires_at)
+    }
+
+    /// Inserts a value into the store metadata that expires at the given time.
+    ///
+    /// See [`Store::insert_with_ttl`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the expiry time is before the Unix epoch or too far in the future to be
+    /// stored.
+    pub fn insert_expiring(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<toml::Value>,
+        expires_at: SystemTime,
+    ) -> Result<(), ExpiringValueError> {
+        let expires_at = expires_at
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|duration| i64::try_from(duration.as_secs()).ok())
+            .ok_or(ExpiringValueError::ExpiryOutOfRange)?;
+
+        let mut entry = Table::new();
+        entry.insert(String::from(EXPIRING_VALUE_KEY), value.into());
+        entry.insert(
+            String::from(EXPIRING_EXPIRES_AT_KEY),
+            toml::Value::Integer(expires_at),
+        );
+
+        let expiring_values = self
+            .metadata
+            .entry(EXPIRING_VALUES_KEY)
+            .or_insert_with(|| toml::Value::Table(Table::new()));
+
+        // A non-table value under the reserved key isn't an expiring value and is replaced.
+        if !expiring_values.is_table() {
+            *expiring_values = toml::Value::Table(Table::new());
+        }
+
+        if let toml::Value::Table(expiring_values) = expiring_values {
+            expiring_values.insert(key.into(), toml::Value::Table(entry));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the value for the given key if it was inserted as an expiring value and hasn't
+    /// expired yet.
+    #[must_use]
+    pub fn get_unexpired(&self, key: &str) -> Option<&toml::Value> {
+        self.get_unexpired_at(key, SystemTime::now())
+    }
+
+    /// Like [`Store::get_unexpired`], but uses the given time instead of the current time.
+    #[must_use]
+    pub fn get_unexpired_at(&self, key: &str, now: SystemTime) -> Option<&toml::Value> {
+        self.expiring_values()?
+            .get(key)
+            .and_then(expiring_entry)
+            .filter(|(_, expires_at)| *expires_at > unix_seconds(now))
+            .map(|(value, _)| value)
+    }
+
+    /// Removes all expiring values that have expired, returning their keys.
+    ///
+    /// Only values in the reserved [`EXPIRING_VALUES_KEY`] table are removed, other metadata is
+    /// never touched.
+    pub fn prune_expired(&mut self) -> Vec<String> {
+        self.prune_expired_at(SystemTime::now())
+    }
+
+    /// Like [`Store::prune_expired`], but uses the given time instead of the current time.
+    pub fn prune_expired_at(&mut self, now: SystemTime) -> Vec<String> {
+        let now = unix_seconds(now);
+
+        let Some(toml::Value::Table(expiring_values)) = self.metadata.get_mut(EXPIRING_VALUES_KEY)
+        else {
+            return Vec::new();
+        };
+
+        let expired_keys: Vec<String> = expiring_values
+            .iter()
+            .filter_map(|(key, value)| {
+                expiring_entry(value)
+                    .filter(|(_, expires_at)| *expires_at <= now)
+                    .map(|_| key.clone())
+            })
+            .collect();
+
+        for key in &expired_keys {
+            expiring_values.remove(key);
+        }
+
+        if expiring_values.is_empty() {
+            self.metadata.remove(EXPIRING_VALUES_KEY);
+        }
+
+        expired_keys
+    }
+
+    fn expiring_values(&self) -> Option<&Table> {
+        self.metadata.get(EXPIRING_VALUES_KEY)?.as_table()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum ExpiringValueError {
+    #[error("Expiry time is out of the supported range")]
+    ExpiryOutOfRange,
+}
+
+fn expiring_entry(value: &toml::Value) -> Option<(&toml::Value, i64)> {
+    let table = value.as_table()?;
+    let expires_at = table.get(EXPIRING_EXPIRES_AT_KEY)?.as_integer()?;
+    let value = table.get(EXPIRING_VALUE_KEY)?;
+
+    Some((value, expires_at))
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| {
+        i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiring_values() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        let mut store = Store::default();
+        store
+            .insert_expiring("fresh", "a", now + Duration::from_secs(60))
+            .unwrap();
+        store
+            .insert_expiring("stale", "b", now - Duration::from_secs(60))
+            .unwrap();
+        store.metadata.insert(
+            String::from("plain"),
+            toml::Value::String(String::from("c")),
+        );
+
+        assert_eq!(
+            store.get_unexpired_at("fresh", now),
+            Some(&toml::Value::String(String::from("a")))
+        );
+        assert_eq!(store.get_unexpired_at("stale", now), None);
+        assert_eq!(store.get_unexpired_at("plain", now), None);
+
+        assert_eq!(store.prune_expired_at(now), vec![String::from("stale")]);
+        assert!(store.get_unexpired_at("fresh", now).is_some());
+        assert!(store.metadata.contains_key("plain"));
+
+        assert_eq!(
+            store.prune_expired_at(now + Duration::from_secs(60)),
+            vec![String::from("fresh")]
+        );
+        assert!(!store.metadata.contains_key(EXPIRING_VALUES_KEY));
+    }
+
+    #[test]
+    fn pruning_keeps_user_tables_shaped_like_expiring_values() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        let mut store: Store =
+            toml::from_str("[metadata.release]\nvalue = \"1.2.3\"\nexpires_at = 0\n").unwrap();
+
+        assert_eq!(store.get_unexpired_at("release", now), None);
+        assert!(store.prune_expired_at(now).is_empty());
+        assert!(store.metadata.contains_key("release"));
+    }
+
+    #[test]
+    fn expiry_out_of_range() {
+        let mut store = Store::default();
+
+        assert_eq!(
+            store.insert_with_ttl("key", 42, Duration::MAX),
+            Err(ExpiringValueError::ExpiryOutOfRange)
+        );
+        assert_eq!(
+            store.insert_expiring("key", 42, UNIX_EPOCH - Duration::from_secs(1)),
+            Err(ExpiringValueError::ExpiryOutOfRange)
+        );
+        assert!(store.metadata.is_empty());
+    }
+
+    #[test]
+    fn expiring_values_roundtrip_through_toml() {
+        let mut store = Store::default();
+        store
+            .insert_expiring("key", 42, UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+            .unwrap();
+
+        let serialized = toml::to_string(&store).unwrap();
+        assert_eq!(
+            serialized,
+            "[metadata.libcnb-expiring.key]\nexpires_at = 1700000000\nvalue = 42\n"
+        );
+
+        let store: Store = toml::from_str(&serialized).unwrap();
+        assert_eq!(
+            store.get_unexpired_at("key", UNIX_EPOCH),
+            Some(&toml::Value::Integer(42))
+        );
+    }
+}
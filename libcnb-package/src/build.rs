@@ -1,10 +1,12 @@
 use crate::cargo::{
-    cargo_binary_target_names, determine_buildpack_cargo_target_name,
+    additional_bin_package_names, cargo_package_binary_target_names,
+    determine_package_buildpack_cargo_target_name, AdditionalBinPackagesError,
     DetermineBuildpackCargoTargetNameError,
 };
 use crate::CargoProfile;
-use cargo_metadata::Metadata;
-use std::collections::HashMap;
+use cargo_metadata::camino::Utf8Path;
+use cargo_metadata::{Metadata, MetadataCommand, Package};
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
@@ -12,13 +14,15 @@ use std::process::{Command, ExitStatus};
 /// Builds all buildpack binary targets using Cargo.
 ///
 /// It uses libcnb configuration metadata in the Crate's `Cargo.toml` to determine which binary is
-/// the main buildpack binary and which are additional ones.
+/// the main buildpack binary and which are additional ones. Binary targets of the workspace
+/// packages listed in `package.metadata.libcnb.additional-bin-packages` are additional binaries
+/// as well. All binaries are built with a single Cargo invocation.
 ///
-/// See [`build_binary`] for details around the build process.
+/// See [`build_binaries`] for details around the build process.
 ///
 /// # Errors
 ///
-/// Will return `Err` if any build did not finish successfully, the configuration can't be
+/// Will return `Err` if the build did not finish successfully, the configuration can't be
 /// read or the configured main buildpack binary does not exist.
 pub(crate) fn build_buildpack_binaries(
     project_path: impl AsRef<Path>,
@@ -27,54 +31,247 @@ pub(crate) fn build_buildpack_binaries(
     cargo_env: &[(OsString, OsString)],
     target_triple: impl AsRef<str>,
 ) -> Result<BuildpackBinaries, BuildBinariesError> {
-    let binary_target_names = cargo_binary_target_names(cargo_metadata);
-    let buildpack_cargo_target = determine_buildpack_cargo_target_name(cargo_metadata)
-        .map_err(BuildBinariesError::CannotDetermineBuildpackCargoTargetName)?;
+    let root_package = cargo_metadata.root_package().ok_or(
+        BuildBinariesError::CannotDetermineBuildpackCargoTargetName(
+            DetermineBuildpackCargoTargetNameError::NoRootPackage,
+        ),
+    )?;
+
+    let buildpack_targets = buildpack_package_targets(cargo_metadata, root_package)?;
 
-    let buildpack_target_binary_path = if binary_target_names.contains(&buildpack_cargo_target) {
-        build_binary(
-            project_path.as_ref(),
-            cargo_metadata,
-            cargo_profile,
-            cargo_env.to_owned(),
-            target_triple.as_ref(),
-            &buildpack_cargo_target,
-        )
-        .map_err(|error| BuildBinariesError::BuildError(buildpack_cargo_target.clone(), error))
+    // Without additional packages, the package in the project path is built. This avoids
+    // ambiguities when a dependency has the same name as the buildpack package.
+    let package_names = if buildpack_targets.package_names.len() > 1 {
+        buildpack_targets
+            .package_names
+            .iter()
+            .map(String::as_str)
+            .collect()
     } else {
-        Err(BuildBinariesError::MissingBuildpackTarget(
-            buildpack_cargo_target.clone(),
-        ))
-    }?;
-
-    let mut additional_target_binary_paths = HashMap::new();
-    for additional_binary_target_name in binary_target_names
-        .iter()
-        .filter(|name| *name != &buildpack_cargo_target)
+        Vec::new()
+    };
+
+    let binaries_dir = build_binaries(
+        project_path.as_ref(),
+        &package_names,
+        cargo_metadata,
+        cargo_profile,
+        cargo_env.to_owned(),
+        target_triple.as_ref(),
+    )
+    .map_err(|error| {
+        BuildBinariesError::BuildError(buildpack_targets.buildpack_cargo_target.clone(), error)
+    })?;
+
+    Ok(BuildpackBinaries::new(
+        &binaries_dir,
+        &buildpack_targets.buildpack_cargo_target,
+        &buildpack_targets.binary_target_names,
+    ))
+}
+
+/// Builds the binary targets of multiple libcnb.rs buildpacks in a Cargo workspace.
+///
+/// In contrast to packaging each buildpack on its own, all buildpack crates are built with a
+/// single Cargo invocation from the workspace root. This allows Cargo to compile dependencies
+/// shared between the buildpacks only once and to build the buildpack crates in parallel.
+///
+/// Since Cargo unifies the features of dependencies across all packages it builds together, a
+/// dependency is compiled with the union of the features all buildpack crates enable for it. A
+/// buildpack can therefore be built with more dependency features than when it is built on its
+/// own.
+///
+/// All binaries are written to the same target directory. Buildpacks with a binary target that has
+/// the same name as a binary target of another package are left out of the shared build, so that
+/// [`crate::package::package_buildpack`] builds them on their own instead of packaging the binary
+/// of another buildpack.
+///
+/// Directories that don't belong to a package of the workspace, such as composite buildpacks,
+/// are ignored. The returned [`WorkspaceBuildpackBinaries`] can be passed to
+/// [`crate::package::package_buildpack`] which will then skip compilation for the buildpacks
+/// that have already been built.
+///
+/// # Errors
+///
+/// Will return `Err` if the build did not finish successfully, the Cargo metadata can't be
+/// read or the main buildpack binary of one of the buildpacks does not exist.
+pub fn build_workspace_buildpack_binaries(
+    workspace_root_path: &Path,
+    buildpack_directories: &[&Path],
+    cargo_profile: CargoProfile,
+    cargo_env: &[(OsString, OsString)],
+    target_triple: impl AsRef<str>,
+) -> Result<WorkspaceBuildpackBinaries, BuildBinariesError> {
+    let cargo_metadata = MetadataCommand::new()
+        .manifest_path(workspace_root_path.join("Cargo.toml"))
+        .exec()
+        .map_err(BuildBinariesError::CargoMetadataError)?;
+
+    let mut buildpack_packages = Vec::new();
+    for buildpack_directory in buildpack_directories {
+        let Some(package) = cargo_metadata
+            .workspace_packages()
+            .into_iter()
+            .find(|package| {
+                package.manifest_path.parent().map(Utf8Path::as_std_path)
+                    == Some(buildpack_directory)
+            })
+        else {
+            continue;
+        };
+
+        buildpack_packages.push((
+            buildpack_directory.to_path_buf(),
+            buildpack_package_targets(&cargo_metadata, package)?,
+        ));
+    }
+
+    let colliding_binary_target_names = colliding_binary_target_names(
+        &cargo_metadata,
+        buildpack_packages
+            .iter()
+            .flat_map(|(_, buildpack_targets)| &buildpack_targets.package_names),
+    );
+    buildpack_packages.retain(|(_, buildpack_targets)| {
+        !buildpack_targets
+            .binary_target_names
+            .iter()
+            .any(|name| colliding_binary_target_names.contains(name))
+    });
+
+    if buildpack_packages.is_empty() {
+        return Ok(WorkspaceBuildpackBinaries::default());
+    }
+
+    let mut package_names = Vec::new();
+    for (_, buildpack_targets) in &buildpack_packages {
+        for package_name in &buildpack_targets.package_names {
+            if !package_names.contains(&package_name.as_str()) {
+                package_names.push(package_name.as_str());
+            }
+        }
+    }
+
+    let binaries_dir = build_binaries(
+        workspace_root_path,
+        &package_names,
+        &cargo_metadata,
+        cargo_profile,
+        cargo_env.to_owned(),
+        target_triple.as_ref(),
+    )
+    .map_err(|error| BuildBinariesError::BuildError(package_names.join(", "), error))?;
+
+    Ok(WorkspaceBuildpackBinaries {
+        buildpack_binaries: buildpack_packages
+            .iter()
+            .map(|(buildpack_directory, buildpack_targets)| {
+                (
+                    buildpack_directory.clone(),
+                    BuildpackBinaries::new(
+                        &binaries_dir,
+                        &buildpack_targets.buildpack_cargo_target,
+                        &buildpack_targets.binary_target_names,
+                    ),
+                )
+            })
+            .collect(),
+    })
+}
+
+/// The names of binary targets that more than one of the given workspace packages has.
+fn colliding_binary_target_names<'a>(
+    cargo_metadata: &Metadata,
+    package_names: impl IntoIterator<Item = &'a String>,
+) -> HashSet<String> {
+    let package_names = package_names.into_iter().collect::<HashSet<_>>();
+
+    duplicate_names(
+        cargo_metadata
+            .workspace_packages()
+            .into_iter()
+            .filter(|package| package_names.contains(&package.name))
+            .map(cargo_package_binary_target_names),
+    )
+}
+
+/// The names that appear in more than one of the given lists.
+fn duplicate_names(name_lists: impl IntoIterator<Item = Vec<String>>) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = HashSet::new();
+
+    for names in name_lists {
+        for name in names.into_iter().collect::<HashSet<_>>() {
+            if !seen.insert(name.clone()) {
+                duplicates.insert(name);
+            }
+        }
+    }
+
+    duplicates
+}
+
+/// The binary targets of a buildpack package.
+struct BuildpackPackageTargets {
+    /// The names of the buildpack package and its additional bin packages
+    package_names: Vec<String>,
+    buildpack_cargo_target: String,
+    /// The names of all binary targets, including the ones of additional bin packages
+    binary_target_names: Vec<String>,
+}
+
+fn buildpack_package_targets(
+    cargo_metadata: &Metadata,
+    package: &Package,
+) -> Result<BuildpackPackageTargets, BuildBinariesError> {
+    let mut binary_target_names = cargo_package_binary_target_names(package);
+    let buildpack_cargo_target = determine_package_buildpack_cargo_target_name(package)
+        .map_err(BuildBinariesError::CannotDetermineBuildpackCargoTargetName)?;
+
+    if !binary_target_names.contains(&buildpack_cargo_target) {
+        return Err(BuildBinariesError::MissingBuildpackTarget(
+            buildpack_cargo_target,
+        ));
+    }
+
+    let mut package_names = vec![package.name.clone()];
+    for additional_package_name in
+        additional_bin_package_names(package).map_err(BuildBinariesError::AdditionalBinPackages)?
     {
-        additional_target_binary_paths.insert(
-            additional_binary_target_name.clone(),
-            build_binary(
-                project_path.as_ref(),
-                cargo_metadata,
-                cargo_profile,
-                cargo_env.to_owned(),
-                target_triple.as_ref(),
-                additional_binary_target_name,
-            )
-            .map_err(|error| {
-                BuildBinariesError::BuildError(additional_binary_target_name.clone(), error)
-            })?,
-        );
+        let additional_package = cargo_metadata
+            .workspace_packages()
+            .into_iter()
+            .find(|workspace_package| workspace_package.name == additional_package_name)
+            .ok_or_else(|| {
+                BuildBinariesError::AdditionalBinPackages(
+                    AdditionalBinPackagesError::UnknownPackage(additional_package_name.clone()),
+                )
+            })?;
+
+        for binary_target_name in cargo_package_binary_target_names(additional_package) {
+            if binary_target_names.contains(&binary_target_name) {
+                return Err(BuildBinariesError::AdditionalBinPackages(
+                    AdditionalBinPackagesError::DuplicateBinaryTarget(binary_target_name),
+                ));
+            }
+
+            binary_target_names.push(binary_target_name);
+        }
+
+        package_names.push(additional_package_name);
     }
 
-    Ok(BuildpackBinaries {
-        buildpack_target_binary_path,
-        additional_target_binary_paths,
+    Ok(BuildpackPackageTargets {
+        package_names,
+        buildpack_cargo_target,
+        binary_target_names,
     })
 }
 
-/// Builds a binary using Cargo.
+/// Builds binaries using Cargo and returns the directory containing them.
+///
+/// When `package_names` is empty, the package in `project_path` is built. Otherwise, all
+/// given packages are built with a single Cargo invocation.
 ///
 /// It is designed to handle cross-compilation without requiring custom configuration in the Cargo
 /// manifest of the user's buildpack. The triple for the target platform is a mandatory
@@ -95,15 +292,19 @@ pub(crate) fn build_buildpack_binaries(
 /// # Errors
 ///
 /// Will return `Err` if the build did not finish successfully.
-fn build_binary(
+fn build_binaries(
     project_path: impl AsRef<Path>,
+    package_names: &[&str],
     cargo_metadata: &Metadata,
     cargo_profile: CargoProfile,
     mut cargo_env: Vec<(OsString, OsString)>,
     target_triple: impl AsRef<str>,
-    target_name: impl AsRef<str>,
 ) -> Result<PathBuf, BuildError> {
     let mut cargo_args = vec!["build", "--target", target_triple.as_ref()];
+    for package_name in package_names {
+        cargo_args.extend(["--package", package_name]);
+    }
+
     match cargo_profile {
         CargoProfile::Dev => {
             // We enable stripping for dev builds too, since debug builds are extremely
@@ -141,23 +342,22 @@ fn build_binary(
         .map_err(BuildError::CargoProcessIoError)?;
 
     if exit_status.success() {
-        let binary_path = cargo_metadata
+        let binaries_dir = cargo_metadata
             .target_directory
             .join(target_triple.as_ref())
             .join(match cargo_profile {
                 CargoProfile::Dev => "debug",
                 CargoProfile::Release => "release",
             })
-            .join(target_name.as_ref())
             .into_std_path_buf();
 
-        Ok(binary_path)
+        Ok(binaries_dir)
     } else {
         Err(BuildError::UnexpectedCargoExitStatus(exit_status))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct BuildpackBinaries {
     /// The path to the main buildpack binary
     pub(crate) buildpack_target_binary_path: PathBuf,
@@ -165,6 +365,36 @@ pub(crate) struct BuildpackBinaries {
     pub(crate) additional_target_binary_paths: HashMap<String, PathBuf>,
 }
 
+impl BuildpackBinaries {
+    fn new(
+        binaries_dir: &Path,
+        buildpack_cargo_target: &str,
+        binary_target_names: &[String],
+    ) -> Self {
+        Self {
+            buildpack_target_binary_path: binaries_dir.join(buildpack_cargo_target),
+            additional_target_binary_paths: binary_target_names
+                .iter()
+                .filter(|name| *name != buildpack_cargo_target)
+                .map(|name| (name.clone(), binaries_dir.join(name)))
+                .collect(),
+        }
+    }
+}
+
+/// Binaries of libcnb.rs buildpacks that have been built with
+/// [`build_workspace_buildpack_binaries`], keyed by buildpack directory.
+#[derive(Debug, Default)]
+pub struct WorkspaceBuildpackBinaries {
+    buildpack_binaries: HashMap<PathBuf, BuildpackBinaries>,
+}
+
+impl WorkspaceBuildpackBinaries {
+    pub(crate) fn get(&self, buildpack_directory: &Path) -> Option<&BuildpackBinaries> {
+        self.buildpack_binaries.get(buildpack_directory)
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum BuildError {
     #[error("I/O error while running Cargo build process: {0}")]
@@ -177,8 +407,35 @@ pub enum BuildError {
 pub enum BuildBinariesError {
     #[error("Failed to determine Cargo target name for buildpack: {0}")]
     CannotDetermineBuildpackCargoTargetName(#[source] DetermineBuildpackCargoTargetNameError),
-    #[error("Failed to build binary target {0}: {1}")]
+    #[error("Failed to build binary targets of {0}: {1}")]
     BuildError(String, #[source] BuildError),
     #[error("Binary target {0} couldn't be found")]
     MissingBuildpackTarget(String),
+    #[error("Obtaining Cargo metadata failed: {0}")]
+    CargoMetadataError(#[source] cargo_metadata::Error),
+    #[error("Invalid additional binary packages: {0}")]
+    AdditionalBinPackages(#[source] AdditionalBinPackagesError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_binary_target_names() {
+        let names = |names: &[&str]| names.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            duplicate_names([
+                names(&["ruby", "exec-d"]),
+                names(&["nodejs", "exec-d"]),
+                names(&["procfile"]),
+            ]),
+            HashSet::from([String::from("exec-d")])
+        );
+        assert_eq!(
+            duplicate_names([names(&["ruby", "ruby"]), names(&["nodejs"])]),
+            HashSet::new()
+        );
+    }
 }
@@ -6,7 +6,9 @@ use crate::dependency_graph::{
 use crate::find_buildpack_dirs;
 use crate::package_descriptor::buildpack_id_from_libcnb_dependency;
 use libcnb_common::toml_file::{read_toml_file, TomlFileError};
-use libcnb_data::buildpack::{BuildpackDescriptor, BuildpackId, BuildpackIdError};
+use libcnb_data::buildpack::{
+    BuildpackDescriptor, BuildpackDescriptorError, BuildpackId, BuildpackIdError,
+};
 use libcnb_data::package_descriptor::PackageDescriptor;
 use petgraph::Graph;
 use std::convert::Infallible;
@@ -56,23 +58,26 @@ fn build_libcnb_buildpack_dependency_graph_node(
     let buildpack_id =
         read_toml_file::<BuildpackDescriptor>(buildpack_directory.join("buildpack.toml"))
             .map_err(BuildBuildpackDependencyGraphError::ReadBuildpackDescriptorError)
-            .map(|buildpack_descriptor| buildpack_descriptor.buildpack().id.clone())?;
+            .and_then(|buildpack_descriptor| {
+                buildpack_descriptor
+                    .validate()
+                    .map_err(BuildBuildpackDependencyGraphError::InvalidBuildpackDescriptor)
+                    .map(|()| buildpack_descriptor.buildpack().id.clone())
+            })?;
 
     let dependencies = {
         let package_toml_path = buildpack_directory.join("package.toml");
 
-        package_toml_path
-            .is_file()
-            .then(|| {
-                read_toml_file::<PackageDescriptor>(package_toml_path)
-                    .map_err(BuildBuildpackDependencyGraphError::ReadPackageDescriptorError)
-                    .and_then(|package_descriptor| {
-                        get_buildpack_dependencies(&package_descriptor).map_err(
-                            BuildBuildpackDependencyGraphError::InvalidDependencyBuildpackId,
-                        )
-                    })
-            })
-            .unwrap_or(Ok(Vec::new()))
+        if package_toml_path.is_file() {
+            read_toml_file::<PackageDescriptor>(package_toml_path)
+                .map_err(BuildBuildpackDependencyGraphError::ReadPackageDescriptorError)
+                .and_then(|package_descriptor| {
+                    get_buildpack_dependencies(&package_descriptor)
+                        .map_err(BuildBuildpackDependencyGraphError::InvalidDependencyBuildpackId)
+                })
+        } else {
+            Ok(Vec::new())
+        }
     }?;
 
     Ok(BuildpackDependencyGraphNode {
@@ -88,6 +93,8 @@ pub enum BuildBuildpackDependencyGraphError {
     FindBuildpackDirectories(ignore::Error),
     #[error("Couldn't read buildpack.toml: {0}")]
     ReadBuildpackDescriptorError(TomlFileError),
+    #[error("Invalid buildpack.toml: {}", join_errors(.0))]
+    InvalidBuildpackDescriptor(Vec<BuildpackDescriptorError>),
     #[error("Couldn't read package.toml: {0}")]
     ReadPackageDescriptorError(TomlFileError),
     #[error("Dependency uses an invalid buildpack id: {0}")]
@@ -96,6 +103,14 @@ pub enum BuildBuildpackDependencyGraphError {
     CreateDependencyGraphError(CreateDependencyGraphError<BuildpackId, Infallible>),
 }
 
+fn join_errors(errors: &[BuildpackDescriptorError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[derive(Debug)]
 pub struct BuildpackDependencyGraphNode {
     pub buildpack_id: BuildpackId,
@@ -1,20 +1,16 @@
-pub(crate) fn determine_buildpack_cargo_target_name(
-    cargo_metadata: &cargo_metadata::Metadata,
+/// Determines the name of the main buildpack binary target of the given Cargo package.
+pub(crate) fn determine_package_buildpack_cargo_target_name(
+    package: &cargo_metadata::Package,
 ) -> Result<String, DetermineBuildpackCargoTargetNameError> {
-    let root_package = cargo_metadata
-        .root_package()
-        .ok_or(DetermineBuildpackCargoTargetNameError::NoRootPackage)?;
-
-    let mut binary_target_names: Vec<String> =
-        cargo_binary_target_names_from_root_package(root_package);
+    let mut binary_target_names: Vec<String> = cargo_package_binary_target_names(package);
 
     match binary_target_names.len() {
         0 | 1 => binary_target_names
             .pop()
             .ok_or(DetermineBuildpackCargoTargetNameError::NoBinTargets),
         _ => binary_target_names
-            .contains(&root_package.name)
-            .then_some(root_package.name.clone())
+            .contains(&package.name)
+            .then_some(package.name.clone())
             .ok_or(DetermineBuildpackCargoTargetNameError::AmbiguousBinTargets),
     }
 }
@@ -29,18 +25,50 @@ pub enum DetermineBuildpackCargoTargetNameError {
     AmbiguousBinTargets,
 }
 
-/// Determines the names of all binary targets from the given Cargo metadata.
-pub(crate) fn cargo_binary_target_names(cargo_metadata: &cargo_metadata::Metadata) -> Vec<String> {
-    cargo_metadata
-        .root_package()
-        .map(cargo_binary_target_names_from_root_package)
-        .unwrap_or_default()
+/// Determines the names of the workspace packages whose binary targets are added to the buildpack
+/// as additional binaries.
+///
+/// These are configured in the `Cargo.toml` of the buildpack:
+///
+/// ```toml
+/// [package.metadata.libcnb]
+/// additional-bin-packages = ["shared-exec-d-programs"]
+/// ```
+pub(crate) fn additional_bin_package_names(
+    package: &cargo_metadata::Package,
+) -> Result<Vec<String>, AdditionalBinPackagesError> {
+    let Some(value) = package
+        .metadata
+        .get("libcnb")
+        .and_then(|libcnb_metadata| libcnb_metadata.get("additional-bin-packages"))
+    else {
+        return Ok(Vec::new());
+    };
+
+    value
+        .as_array()
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|value| value.as_str().map(String::from))
+                .collect()
+        })
+        .ok_or_else(|| AdditionalBinPackagesError::InvalidConfiguration(package.name.clone()))
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AdditionalBinPackagesError {
+    #[error("package.metadata.libcnb.additional-bin-packages of package {0} must be an array of package names")]
+    InvalidConfiguration(String),
+    #[error("Package {0} configured in additional-bin-packages isn't a member of the workspace")]
+    UnknownPackage(String),
+    #[error("Binary target {0} exists in more than one package of the buildpack")]
+    DuplicateBinaryTarget(String),
 }
 
-fn cargo_binary_target_names_from_root_package(
-    root_package: &cargo_metadata::Package,
-) -> Vec<String> {
-    root_package
+/// Determines the names of all binary targets of the given Cargo package.
+pub(crate) fn cargo_package_binary_target_names(package: &cargo_metadata::Package) -> Vec<String> {
+    package
         .targets
         .iter()
         .filter_map(|target| is_binary_target(target).then_some(target.name.clone()))
@@ -9,6 +9,7 @@ pub mod dependency_graph;
 pub mod output;
 pub mod package;
 pub mod package_descriptor;
+pub mod signing;
 pub mod util;
 
 use crate::build::BuildpackBinaries;
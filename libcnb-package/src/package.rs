@@ -1,4 +1,4 @@
-use crate::build::build_buildpack_binaries;
+use crate::build::{build_buildpack_binaries, WorkspaceBuildpackBinaries};
 use crate::buildpack_kind::{determine_buildpack_kind, BuildpackKind};
 use crate::package_descriptor::{normalize_package_descriptor, NormalizePackageDescriptorError};
 use crate::{assemble_buildpack_directory, CargoProfile};
@@ -13,6 +13,10 @@ use std::path::{Path, PathBuf};
 
 /// Packages either a libcnb.rs or a composite buildpack.
 ///
+/// If `buildpack_binaries` contains binaries for a libcnb.rs buildpack, they are used as-is
+/// instead of compiling the buildpack again. See
+/// [`crate::build::build_workspace_buildpack_binaries`].
+///
 /// # Errors
 ///
 /// Returns `Err` if packaging failed or the given buildpack directory is unsupported.
@@ -23,6 +27,7 @@ pub fn package_buildpack(
     cargo_build_env: &[(OsString, OsString)],
     destination: &Path,
     dependencies: &BTreeMap<BuildpackId, PathBuf>,
+    buildpack_binaries: Option<&WorkspaceBuildpackBinaries>,
 ) -> Result<(), PackageBuildpackError> {
     match determine_buildpack_kind(buildpack_directory) {
         Some(BuildpackKind::LibCnbRs) => package_libcnb_buildpack(
@@ -31,6 +36,7 @@ pub fn package_buildpack(
             target_triple,
             cargo_build_env,
             destination,
+            buildpack_binaries,
         )
         .map_err(PackageBuildpackError::PackageLibcnbBuildpackError),
         Some(BuildpackKind::Composite) => {
@@ -62,20 +68,27 @@ fn package_libcnb_buildpack(
     target_triple: &str,
     cargo_build_env: &[(OsString, OsString)],
     destination: &Path,
+    workspace_buildpack_binaries: Option<&WorkspaceBuildpackBinaries>,
 ) -> Result<(), PackageLibcnbBuildpackError> {
-    let cargo_metadata = MetadataCommand::new()
-        .manifest_path(&buildpack_directory.join("Cargo.toml"))
-        .exec()
-        .map_err(PackageLibcnbBuildpackError::CargoMetadataError)?;
+    let buildpack_binaries = if let Some(buildpack_binaries) =
+        workspace_buildpack_binaries.and_then(|binaries| binaries.get(buildpack_directory))
+    {
+        buildpack_binaries.clone()
+    } else {
+        let cargo_metadata = MetadataCommand::new()
+            .manifest_path(buildpack_directory.join("Cargo.toml"))
+            .exec()
+            .map_err(PackageLibcnbBuildpackError::CargoMetadataError)?;
 
-    let buildpack_binaries = build_buildpack_binaries(
-        buildpack_directory,
-        &cargo_metadata,
-        cargo_profile,
-        cargo_build_env,
-        target_triple,
-    )
-    .map_err(PackageLibcnbBuildpackError::BuildBinariesError)?;
+        build_buildpack_binaries(
+            buildpack_directory,
+            &cargo_metadata,
+            cargo_profile,
+            cargo_build_env,
+            target_triple,
+        )
+        .map_err(PackageLibcnbBuildpackError::BuildBinariesError)?
+    };
 
     assemble_buildpack_directory(
         destination,
@@ -0,0 +1,340 @@
+use crate::CargoProfile;
+use libcnb_data::buildpack::BuildpackId;
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{fs, io};
+
+/// The in-toto predicate type of the provenance attestation.
+const SLSA_PROVENANCE_PREDICATE_TYPE: &str = "slsaprovenance1";
+
+/// How packaged buildpacks are signed with [cosign](https://docs.sigstore.dev/signing/quickstart/).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SigningMethod {
+    /// Keyless signing with a short-lived certificate issued for the OIDC identity of the
+    /// environment, such as a GitHub Actions workflow, or an interactive login.
+    Keyless,
+    /// Signing with a key, given as the path of a cosign key file or a KMS URI such as
+    /// `awskms:///alias/buildpacks`.
+    Key(String),
+}
+
+/// The parameters a buildpack was packaged with, recorded in its provenance attestation.
+#[derive(Clone, Debug)]
+pub struct PackageProvenance {
+    pub buildpack_id: BuildpackId,
+    pub cargo_profile: CargoProfile,
+    pub target_triple: String,
+    /// The identifier of the tool that packaged the buildpack, such as
+    /// `https://github.com/heroku/libcnb.rs/tree/main/libcnb-cargo@0.20.0`.
+    pub builder_id: String,
+}
+
+/// The files written next to a packaged buildpack directory by [`sign_packaged_buildpack`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedBuildpackFiles {
+    /// The SHA-256 digests of all files of the packaged buildpack, in the format of `sha256sum`.
+    pub digests: PathBuf,
+    /// The Sigstore bundle with the signature of the digests file.
+    pub signature_bundle: PathBuf,
+    /// The unsigned SLSA provenance predicate.
+    pub provenance: PathBuf,
+    /// The Sigstore bundle with the signed in-toto attestation of the provenance predicate for
+    /// the digests file.
+    pub attestation_bundle: PathBuf,
+}
+
+impl SignedBuildpackFiles {
+    /// The files for the given packaged buildpack directory, named after the directory.
+    #[must_use]
+    pub fn for_buildpack_dir(buildpack_dir: &Path) -> Self {
+        let sibling = |extension: &str| {
+            let mut path = OsString::from(buildpack_dir);
+            path.push(extension);
+            PathBuf::from(path)
+        };
+
+        Self {
+            digests: sibling(".sha256"),
+            signature_bundle: sibling(".sha256.sigstore.json"),
+            provenance: sibling(".provenance.json"),
+            attestation_bundle: sibling(".intoto.sigstore.json"),
+        }
+    }
+}
+
+/// Signs a packaged buildpack directory with cosign and attests its provenance.
+///
+/// Since a buildpack directory can't be signed as a whole, the SHA-256 digests of all its files
+/// are written to a digests file, which is then signed with `cosign sign-blob` and attested with
+/// `cosign attest-blob`. All files are written next to the buildpack directory, see
+/// [`SignedBuildpackFiles`]. The signature can be verified with `cosign verify-blob` and the files
+/// of the buildpack with `sha256sum --check` in the buildpack directory.
+///
+/// # Errors
+///
+/// Returns `Err` if the files couldn't be written, or cosign isn't installed or failed.
+pub fn sign_packaged_buildpack(
+    buildpack_dir: &Path,
+    signing_method: &SigningMethod,
+    provenance: &PackageProvenance,
+) -> Result<SignedBuildpackFiles, SignBuildpackError> {
+    let files = SignedBuildpackFiles::for_buildpack_dir(buildpack_dir);
+
+    let digests = file_digests(buildpack_dir).map_err(SignBuildpackError::CannotComputeDigests)?;
+    fs::write(&files.digests, digests)
+        .map_err(|error| SignBuildpackError::CannotWriteFile(files.digests.clone(), error))?;
+
+    fs::write(&files.provenance, provenance_predicate(provenance))
+        .map_err(|error| SignBuildpackError::CannotWriteFile(files.provenance.clone(), error))?;
+
+    run_cosign(sign_blob_command(
+        signing_method,
+        &files.digests,
+        &files.signature_bundle,
+    ))?;
+
+    run_cosign(attest_blob_command(
+        signing_method,
+        &files.digests,
+        &files.provenance,
+        &files.attestation_bundle,
+    ))?;
+
+    Ok(files)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SignBuildpackError {
+    #[error("Failed to compute digests of packaged buildpack files: {0}")]
+    CannotComputeDigests(#[source] io::Error),
+    #[error("Failed to write {0}: {1}")]
+    CannotWriteFile(PathBuf, #[source] io::Error),
+    #[error("Couldn't find cosign, install it from https://docs.sigstore.dev/system_config/installation/")]
+    CosignNotFound,
+    #[error("Failed to run cosign: {0}")]
+    CannotRunCosign(#[source] io::Error),
+    #[error("cosign {0} failed: {1}")]
+    CosignFailed(String, String),
+}
+
+/// Lists the SHA-256 digests of all files in a directory in the format of `sha256sum`, sorted by
+/// their relative path. Symlinks are followed.
+fn file_digests(dir: &Path) -> io::Result<String> {
+    let mut paths = Vec::new();
+    collect_file_paths(dir, &mut paths)?;
+    paths.sort();
+
+    paths.iter().try_fold(String::new(), |mut digests, path| {
+        let mut hasher = Sha256::new();
+        io::copy(&mut File::open(path)?, &mut hasher)?;
+
+        let relative_path = path.strip_prefix(dir).unwrap_or(path);
+        let _ = writeln!(
+            digests,
+            "{:x}  ./{}",
+            hasher.finalize(),
+            relative_path.to_string_lossy()
+        );
+        Ok(digests)
+    })
+}
+
+fn collect_file_paths(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            collect_file_paths(&entry.path(), paths)?;
+        } else {
+            paths.push(entry.path());
+        }
+    }
+
+    Ok(())
+}
+
+fn provenance_predicate(provenance: &PackageProvenance) -> String {
+    let predicate = serde_json::json!({
+        "buildDefinition": {
+            "buildType": "https://github.com/heroku/libcnb.rs/libcnb-package@v1",
+            "externalParameters": {
+                "buildpackId": provenance.buildpack_id.to_string(),
+                "cargoProfile": match provenance.cargo_profile {
+                    CargoProfile::Dev => "dev",
+                    CargoProfile::Release => "release",
+                },
+                "targetTriple": provenance.target_triple,
+            },
+        },
+        "runDetails": {
+            "builder": { "id": provenance.builder_id },
+        },
+    });
+
+    predicate.to_string()
+}
+
+fn sign_blob_command(signing_method: &SigningMethod, blob: &Path, bundle: &Path) -> Command {
+    let mut command = cosign_command("sign-blob", signing_method);
+    command.arg("--bundle").arg(bundle).arg(blob);
+    command
+}
+
+fn attest_blob_command(
+    signing_method: &SigningMethod,
+    blob: &Path,
+    predicate: &Path,
+    bundle: &Path,
+) -> Command {
+    let mut command = cosign_command("attest-blob", signing_method);
+    command
+        .arg("--predicate")
+        .arg(predicate)
+        .args(["--type", SLSA_PROVENANCE_PREDICATE_TYPE])
+        .arg("--bundle")
+        .arg(bundle)
+        .arg(blob);
+    command
+}
+
+fn cosign_command(subcommand: &str, signing_method: &SigningMethod) -> Command {
+    let mut command = Command::new("cosign");
+    // Skips the confirmation prompt for uploading to the transparency log.
+    command.args([subcommand, "--yes"]);
+
+    if let SigningMethod::Key(key) = signing_method {
+        command.args(["--key", key]);
+    }
+
+    command
+}
+
+fn run_cosign(mut command: Command) -> Result<(), SignBuildpackError> {
+    let subcommand = command
+        .get_args()
+        .next()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let output = command.output().map_err(|error| {
+        if error.kind() == io::ErrorKind::NotFound {
+            SignBuildpackError::CosignNotFound
+        } else {
+            SignBuildpackError::CannotRunCosign(error)
+        }
+    })?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(SignBuildpackError::CosignFailed(
+            subcommand,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::buildpack_id;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn digests_of_buildpack_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(temp_dir.path().join("bin")).unwrap();
+        fs::write(temp_dir.path().join("bin").join("build"), "binary").unwrap();
+        fs::write(temp_dir.path().join("buildpack.toml"), "").unwrap();
+
+        assert_eq!(
+            file_digests(temp_dir.path()).unwrap(),
+            "9a3a45d01531a20e89ac6ae10b0b0beb0492acd7216a368aa062d1a5fecaf9cd  ./bin/build\ne3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  ./buildpack.toml\n"
+        );
+    }
+
+    #[test]
+    fn signed_buildpack_file_names() {
+        assert_eq!(
+            SignedBuildpackFiles::for_buildpack_dir(Path::new("/packaged/heroku_ruby")),
+            SignedBuildpackFiles {
+                digests: PathBuf::from("/packaged/heroku_ruby.sha256"),
+                signature_bundle: PathBuf::from("/packaged/heroku_ruby.sha256.sigstore.json"),
+                provenance: PathBuf::from("/packaged/heroku_ruby.provenance.json"),
+                attestation_bundle: PathBuf::from("/packaged/heroku_ruby.intoto.sigstore.json"),
+            }
+        );
+    }
+
+    #[test]
+    fn cosign_commands() {
+        let command = sign_blob_command(
+            &SigningMethod::Keyless,
+            Path::new("bp.sha256"),
+            Path::new("bp.sha256.sigstore.json"),
+        );
+        assert_eq!(command.get_program(), "cosign");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            [
+                "sign-blob",
+                "--yes",
+                "--bundle",
+                "bp.sha256.sigstore.json",
+                "bp.sha256"
+            ]
+        );
+
+        let command = attest_blob_command(
+            &SigningMethod::Key(String::from("cosign.key")),
+            Path::new("bp.sha256"),
+            Path::new("bp.provenance.json"),
+            Path::new("bp.intoto.sigstore.json"),
+        );
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            [
+                "attest-blob",
+                "--yes",
+                "--key",
+                "cosign.key",
+                "--predicate",
+                "bp.provenance.json",
+                "--type",
+                "slsaprovenance1",
+                "--bundle",
+                "bp.intoto.sigstore.json",
+                "bp.sha256"
+            ]
+        );
+    }
+
+    #[test]
+    fn provenance() {
+        let predicate = provenance_predicate(&PackageProvenance {
+            buildpack_id: buildpack_id!("heroku/ruby"),
+            cargo_profile: CargoProfile::Release,
+            target_triple: String::from("x86_64-unknown-linux-musl"),
+            builder_id: String::from("libcnb-cargo@0.20.0"),
+        });
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&predicate).unwrap(),
+            serde_json::json!({
+                "buildDefinition": {
+                    "buildType": "https://github.com/heroku/libcnb.rs/libcnb-package@v1",
+                    "externalParameters": {
+                        "buildpackId": "heroku/ruby",
+                        "cargoProfile": "release",
+                        "targetTriple": "x86_64-unknown-linux-musl",
+                    },
+                },
+                "runDetails": { "builder": { "id": "libcnb-cargo@0.20.0" } },
+            })
+        );
+    }
+}
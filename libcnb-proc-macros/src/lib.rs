@@ -2,7 +2,7 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use syn::parse::{Parse, ParseStream};
 use syn::parse_macro_input;
 use syn::Token;
@@ -90,10 +90,34 @@ pub fn verify_bin_target_exists(input: TokenStream) -> TokenStream {
 
     let token_stream = if let Ok(Some(cargo_metadata)) = cargo_metadata {
         if let Some(root_package) = cargo_metadata.root_package() {
+            // Binary targets of the workspace packages configured in
+            // `package.metadata.libcnb.additional-bin-packages` are packaged with the buildpack too.
+            let additional_bin_package_names = root_package
+                .metadata
+                .get("libcnb")
+                .and_then(|libcnb_metadata| libcnb_metadata.get("additional-bin-packages"))
+                .and_then(|value| value.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|value| value.as_str())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
             let valid_target = root_package
                 .targets
                 .iter()
-                .any(|target| target.name == input.target_name.value());
+                .any(|target| target.name == input.target_name.value())
+                || cargo_metadata
+                    .workspace_packages()
+                    .into_iter()
+                    .filter(|package| additional_bin_package_names.contains(&package.name.as_str()))
+                    .flat_map(|package| &package.targets)
+                    .any(|target| {
+                        target.name == input.target_name.value()
+                            && target.kind.contains(&String::from("bin"))
+                    });
 
             let expression = if valid_target {
                 input.expression_when_matched
@@ -139,3 +163,454 @@ impl Parse for VerifyBinTargetExistsInput {
         })
     }
 }
+
+/// Derives `libcnb::layer::LayerMetadata` for a struct, comparing it field by field to the
+/// metadata of a cached layer.
+///
+/// Fields marked with `#[invalidates]` invalidate the cached layer when they differ, fields
+/// marked with `#[ignored]` are never compared. Changes to all other fields cause the cached layer
+/// to be updated. See the documentation of `libcnb::layer::LayerMetadata` for details.
+#[proc_macro_derive(LayerMetadata, attributes(invalidates, ignored))]
+pub fn derive_layer_metadata(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+
+    derive_layer_metadata_impl(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn derive_layer_metadata_impl(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let syn::Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "LayerMetadata can only be derived for structs",
+        ));
+    };
+
+    let mut invalidating_fields = Vec::new();
+    let mut updating_fields = Vec::new();
+
+    for (index, field) in data.fields.iter().enumerate() {
+        let invalidates = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("invalidates"));
+        let ignored = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("ignored"));
+
+        for attr in invalidates.iter().chain(ignored.iter()) {
+            attr.meta.require_path_only()?;
+        }
+
+        let (member, name) = match &field.ident {
+            Some(ident) => (
+                syn::Member::Named(ident.clone()),
+                syn::ext::IdentExt::unraw(ident).to_string(),
+            ),
+            None => (
+                syn::Member::Unnamed(syn::Index::from(index)),
+                index.to_string(),
+            ),
+        };
+
+        match (invalidates, ignored) {
+            (Some(_), Some(ignored)) => {
+                return Err(syn::Error::new_spanned(
+                    ignored,
+                    "A field can't be both #[invalidates] and #[ignored]",
+                ));
+            }
+            (Some(_), None) => invalidating_fields.push((member, name)),
+            (None, None) => updating_fields.push((member, name)),
+            (None, Some(_)) => {}
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let invalidating_changes = changed_fields(&invalidating_fields);
+    let updating_changes = changed_fields(&updating_fields);
+
+    Ok(quote! {
+        impl #impl_generics ::libcnb::layer::LayerMetadata for #ident #type_generics #where_clause {
+            fn invalidating_changes(&self, cached: &Self) -> ::std::vec::Vec<&'static str> {
+                #invalidating_changes
+            }
+
+            fn updating_changes(&self, cached: &Self) -> ::std::vec::Vec<&'static str> {
+                #updating_changes
+            }
+        }
+    })
+}
+
+fn changed_fields(fields: &[(syn::Member, String)]) -> proc_macro2::TokenStream {
+    let (members, names): (Vec<_>, Vec<_>) = fields.iter().cloned().unzip();
+
+    quote! {
+        let mut changes = ::std::vec::Vec::new();
+        #(
+            if self.#members != cached.#members {
+                changes.push(#names);
+            }
+        )*
+        changes
+    }
+}
+
+/// Embeds the files of a directory, relative to `CARGO_MANIFEST_DIR`, into the binary. Expands to
+/// a `libcnb::layer::EmbeddedDir`.
+///
+/// Files are embedded with `include_bytes!` so that changes to their contents cause a rebuild.
+/// Files that are added to or removed from the directory are only picked up once the crate is
+/// rebuilt for another reason.
+#[proc_macro]
+pub fn include_layer_dir(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::LitStr);
+
+    include_layer_dir_impl(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn include_layer_dir_impl(input: &syn::LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let cargo_manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .map_err(|_| syn::Error::new(input.span(), "Couldn't read CARGO_MANIFEST_DIR!"))?;
+
+    let dir = cargo_manifest_dir.join(input.value());
+
+    let mut directories = Vec::new();
+    let mut files = Vec::new();
+    collect_dir_entries(&dir, &dir, &mut directories, &mut files).map_err(|error| {
+        syn::Error::new(
+            input.span(),
+            format!("Couldn't read directory {}: {error}", dir.display()),
+        )
+    })?;
+
+    let files = files.iter().map(|(path, relative_path, mode)| {
+        let path = path.to_string_lossy();
+
+        quote! {
+            ::libcnb::layer::EmbeddedFile::new(#relative_path, include_bytes!(#path), #mode)
+        }
+    });
+
+    Ok(quote! {
+        ::libcnb::layer::EmbeddedDir::new(&[#(#directories),*], &[#(#files),*])
+    })
+}
+
+/// Collects the directories and files within `dir` recursively, sorted by their path relative to
+/// `root`.
+fn collect_dir_entries(
+    root: &std::path::Path,
+    dir: &std::path::Path,
+    directories: &mut Vec<String>,
+    files: &mut Vec<(PathBuf, String, u32)>,
+) -> std::io::Result<()> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    paths.sort();
+
+    for path in paths {
+        let metadata = std::fs::metadata(&path)?;
+
+        let relative_path = path
+            .strip_prefix(root)
+            .map_err(|_| std::io::Error::other("Path is not within the embedded directory"))?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if metadata.is_dir() {
+            directories.push(relative_path);
+            collect_dir_entries(root, &path, directories, files)?;
+        } else {
+            files.push((path, relative_path, file_mode(&metadata)));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(target_family = "unix")]
+fn file_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    if metadata.permissions().mode() & 0o111 == 0 {
+        0o644
+    } else {
+        0o755
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+fn file_mode(_metadata: &std::fs::Metadata) -> u32 {
+    0o644
+}
+
+/// Verifies that the version of the crate being compiled matches the version in its
+/// `buildpack.toml`. Expands to an unnamed `const _: &str = include_str!(...)` of the
+/// `buildpack.toml`, followed by a compile error if the versions differ.
+///
+/// The `include_str!` only makes Cargo track the `buildpack.toml`, so that changing it causes the
+/// crate to be checked again. If the `buildpack.toml` can't be read, the macro expands to just the
+/// compile error.
+#[proc_macro]
+pub fn verify_buildpack_version(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as syn::parse::Nothing);
+
+    let buildpack_version = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| String::from("Couldn't read CARGO_MANIFEST_DIR!"))
+        .and_then(|cargo_manifest_dir| read_buildpack_version(Path::new(&cargo_manifest_dir)));
+
+    let token_stream = match buildpack_version {
+        Ok((buildpack_toml_path, buildpack_version)) => {
+            let crate_version = std::env::var("CARGO_PKG_VERSION").unwrap_or_default();
+            let buildpack_toml_path = buildpack_toml_path.to_string_lossy();
+
+            match check_buildpack_version(&crate_version, &buildpack_version) {
+                Ok(()) => quote! {
+                    const _: &str = include_str!(#buildpack_toml_path);
+                },
+                Err(message) => quote! {
+                    const _: &str = include_str!(#buildpack_toml_path);
+                    compile_error!(#message);
+                },
+            }
+        }
+        Err(message) => quote! {
+            compile_error!(#message);
+        },
+    };
+
+    token_stream.into()
+}
+
+/// Reads the version from the `buildpack.toml` in the given directory, returning the path of the
+/// file together with the version.
+fn read_buildpack_version(cargo_manifest_dir: &Path) -> Result<(PathBuf, String), String> {
+    let buildpack_toml_path = cargo_manifest_dir.join("buildpack.toml");
+
+    let buildpack_toml = std::fs::read_to_string(&buildpack_toml_path)
+        .map_err(|error| format!("Couldn't read buildpack.toml: {error}"))?
+        .parse::<toml::Table>()
+        .map_err(|error| format!("Couldn't parse buildpack.toml: {error}"))?;
+
+    let version = ["buildpack", "extension"]
+        .iter()
+        .find_map(|key| buildpack_toml.get(*key)?.get("version")?.as_str())
+        .ok_or_else(|| String::from("Couldn't find the buildpack version in buildpack.toml!"))?;
+
+    Ok((buildpack_toml_path, String::from(version)))
+}
+
+fn check_buildpack_version(crate_version: &str, buildpack_version: &str) -> Result<(), String> {
+    if crate_version == buildpack_version {
+        Ok(())
+    } else {
+        Err(format!(
+            "The crate version ({crate_version}) doesn't match the version in buildpack.toml ({buildpack_version})!"
+        ))
+    }
+}
+
+/// Derives `libcnb::options::BuildpackOptions` for a struct with named fields. See the
+/// documentation of `libcnb::options::BuildpackOptions` for the supported attributes.
+#[proc_macro_derive(BuildpackOptions, attributes(option))]
+pub fn derive_buildpack_options(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+
+    derive_buildpack_options_impl(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn derive_buildpack_options_impl(
+    input: &syn::DeriveInput,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "BuildpackOptions can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let mut reads = Vec::new();
+    let mut required_fields = Vec::new();
+    let mut field_idents = Vec::new();
+
+    for field in fields {
+        let Some(ident) = &field.ident else {
+            continue;
+        };
+
+        let mut name = format!(
+            "BP_{}",
+            syn::ext::IdentExt::unraw(ident).to_string().to_uppercase()
+        );
+        let mut default = None;
+        let mut aliases = Vec::new();
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("option"))
+        {
+            attr.parse_nested_meta(|meta| {
+                let value = meta.value()?.parse::<syn::LitStr>()?;
+
+                if meta.path.is_ident("name") {
+                    name = value.value();
+                } else if meta.path.is_ident("default") {
+                    default = Some(value.value());
+                } else if meta.path.is_ident("alias") {
+                    aliases.push(value.value());
+                } else {
+                    return Err(meta.error("Expected `name`, `default` or `alias`"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        let default = default.map_or_else(
+            || quote! { ::std::option::Option::None },
+            |default| quote! { ::std::option::Option::Some(#default) },
+        );
+
+        let read = if let Some(inner_type) = option_inner_type(&field.ty) {
+            quote! {
+                let #ident = reader.optional::<#inner_type>(#name, &[#(#aliases),*], #default);
+            }
+        } else {
+            let field_type = &field.ty;
+            required_fields.push(ident);
+
+            quote! {
+                let #ident = reader.required::<#field_type>(#name, &[#(#aliases),*], #default);
+            }
+        };
+
+        reads.push(read);
+        field_idents.push(ident);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::libcnb::options::BuildpackOptions for #ident #type_generics #where_clause {
+            fn from_env(
+                env: &::libcnb::Env,
+                warnings: &::libcnb::Warnings,
+            ) -> ::std::result::Result<Self, ::libcnb::options::BuildpackOptionsError> {
+                let mut reader = ::libcnb::options::OptionsReader::new(env, warnings);
+                #(#reads)*
+
+                match (#(#required_fields,)*) {
+                    (#(::std::option::Option::Some(#required_fields),)*) if reader.is_valid() => {
+                        ::std::result::Result::Ok(Self { #(#field_idents),* })
+                    }
+                    _ => ::std::result::Result::Err(reader.into_error()),
+                }
+            }
+        }
+    })
+}
+
+/// Returns `T` if the given type is `Option<T>`.
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+            match arguments.args.first()? {
+                syn::GenericArgument::Type(inner_type) => Some(inner_type),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_buildpack_version_of_buildpack_and_extension() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let buildpack_toml_path = temp_dir.path().join("buildpack.toml");
+
+        std::fs::write(
+            &buildpack_toml_path,
+            "[buildpack]\nid = \"heroku/ruby\"\nversion = \"1.2.3\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_buildpack_version(temp_dir.path()),
+            Ok((buildpack_toml_path.clone(), String::from("1.2.3")))
+        );
+
+        std::fs::write(
+            &buildpack_toml_path,
+            "[extension]\nid = \"heroku/apt\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_buildpack_version(temp_dir.path()),
+            Ok((buildpack_toml_path.clone(), String::from("0.1.0")))
+        );
+
+        std::fs::write(&buildpack_toml_path, "[buildpack]\nid = \"heroku/ruby\"\n").unwrap();
+        assert_eq!(
+            read_buildpack_version(temp_dir.path()),
+            Err(String::from(
+                "Couldn't find the buildpack version in buildpack.toml!"
+            ))
+        );
+
+        std::fs::write(&buildpack_toml_path, "[buildpack").unwrap();
+        assert!(read_buildpack_version(temp_dir.path())
+            .unwrap_err()
+            .starts_with("Couldn't parse buildpack.toml: "));
+
+        std::fs::remove_file(&buildpack_toml_path).unwrap();
+        assert!(read_buildpack_version(temp_dir.path())
+            .unwrap_err()
+            .starts_with("Couldn't read buildpack.toml: "));
+    }
+
+    #[test]
+    fn check_buildpack_version_requires_exact_match() {
+        assert_eq!(check_buildpack_version("1.2.3", "1.2.3"), Ok(()));
+        assert_eq!(
+            check_buildpack_version("1.2.3", "1.2.4"),
+            Err(String::from(
+                "The crate version (1.2.3) doesn't match the version in buildpack.toml (1.2.4)!"
+            ))
+        );
+        assert!(check_buildpack_version("", "1.2.3").is_err());
+    }
+}
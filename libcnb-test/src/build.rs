@@ -1,5 +1,6 @@
 use libcnb_common::toml_file::{read_toml_file, TomlFileError};
 use libcnb_data::buildpack::{BuildpackDescriptor, BuildpackId};
+use libcnb_package::build::{build_workspace_buildpack_binaries, BuildBinariesError};
 use libcnb_package::buildpack_dependency_graph::{
     build_libcnb_buildpacks_dependency_graph, BuildBuildpackDependencyGraphError,
 };
@@ -71,12 +72,29 @@ pub(crate) fn package_buildpack(
         .node_weights()
         .find(|node| &node.buildpack_id == buildpack_id)
         .ok_or_else(|| {
-            PackageBuildpackError::BuildpackIdNotFound(buildpack_id.clone(), workspace_root_path)
+            PackageBuildpackError::BuildpackIdNotFound(
+                buildpack_id.clone(),
+                workspace_root_path.clone(),
+            )
         })?;
 
     let build_order = get_dependencies(&buildpack_dependency_graph, &[root_node])
         .map_err(PackageBuildpackError::GetDependencies)?;
 
+    let buildpack_dirs = build_order
+        .iter()
+        .map(|node| node.path.as_path())
+        .collect::<Vec<_>>();
+
+    let buildpack_binaries = build_workspace_buildpack_binaries(
+        &workspace_root_path,
+        &buildpack_dirs,
+        cargo_profile,
+        &cargo_build_env,
+        target_triple.as_ref(),
+    )
+    .map_err(PackageBuildpackError::BuildBinaries)?;
+
     let mut packaged_buildpack_dirs = BTreeMap::new();
     for node in &build_order {
         let buildpack_destination_dir = buildpack_dir_resolver(&node.buildpack_id);
@@ -92,6 +110,7 @@ pub(crate) fn package_buildpack(
             &cargo_build_env,
             &buildpack_destination_dir,
             &packaged_buildpack_dirs,
+            Some(&buildpack_binaries),
         )
         .map_err(PackageBuildpackError::PackageBuildpack)?;
 
@@ -111,6 +130,8 @@ pub(crate) enum PackageBuildpackError {
     CannotCreateDirectory(PathBuf, io::Error),
     #[error("Couldn't read buildpack.toml: {0}")]
     CannotReadBuildpackDescriptor(TomlFileError),
+    #[error("Couldn't build buildpack binaries: {0}")]
+    BuildBinaries(BuildBinariesError),
     #[error("Couldn't calculate buildpack dependency graph: {0}")]
     BuildBuildpackDependencyGraph(BuildBuildpackDependencyGraphError),
     #[error("Couldn't find cross-compilation toolchain.\n\n{0}")]
@@ -14,8 +14,10 @@ pub struct BuildConfig {
     pub(crate) builder_name: String,
     pub(crate) buildpacks: Vec<BuildpackReference>,
     pub(crate) env: HashMap<String, String>,
+    pub(crate) registry_credentials: Vec<String>,
     pub(crate) app_dir_preprocessor: Option<Rc<dyn Fn(PathBuf)>>,
     pub(crate) expected_pack_result: PackResult,
+    pub(crate) build_tool: BuildTool,
 }
 
 impl BuildConfig {
@@ -44,8 +46,10 @@ impl BuildConfig {
             builder_name: builder_name.into(),
             buildpacks: vec![BuildpackReference::CurrentCrate],
             env: HashMap::new(),
+            registry_credentials: Vec::new(),
             app_dir_preprocessor: None,
             expected_pack_result: PackResult::Success,
+            build_tool: BuildTool::Pack,
         }
     }
 
@@ -169,6 +173,31 @@ impl BuildConfig {
         self
     }
 
+    /// Forwards the Docker credentials of the host for the given registry, such as `ghcr.io`, to
+    /// the build.
+    ///
+    /// The credentials are resolved like `docker` and `pack` do, including credential helpers, and
+    /// passed to the build as the `CNB_REGISTRY_AUTH` platform environment variable. This allows
+    /// testing buildpacks that pull artifacts from private registries, for example with
+    /// `libherokubuildpack::oci`. The test panics if there are no credentials for the registry.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app")
+    ///         .registry_credentials("ghcr.io"),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn registry_credentials(&mut self, registry: impl Into<String>) -> &mut Self {
+        self.registry_credentials.push(registry.into());
+        self
+    }
+
     /// Sets an app directory preprocessor function.
     ///
     /// It will be run after the app directory has been copied for the current integration test run,
@@ -247,6 +276,30 @@ impl BuildConfig {
         self.expected_pack_result = pack_result;
         self
     }
+
+    /// Sets the tool that builds the app.
+    ///
+    /// Defaults to [`BuildTool::Pack`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, BuildTool, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app").build_tool(
+    ///         BuildTool::Lifecycle {
+    ///             lifecycle_image: Some(String::from("buildpacksio/lifecycle:0.17.5")),
+    ///         },
+    ///     ),
+    ///     |context| {
+    ///         // ...
+    ///     },
+    /// );
+    /// ```
+    pub fn build_tool(&mut self, build_tool: BuildTool) -> &mut Self {
+        self.build_tool = build_tool;
+        self
+    }
 }
 
 /// References a Cloud Native Buildpack.
@@ -262,6 +315,26 @@ pub enum BuildpackReference {
     Other(String),
 }
 
+/// The tool that builds the app.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BuildTool {
+    /// Builds the app with [pack](https://buildpacks.io/docs/tools/pack/), which has to be
+    /// installed on the host.
+    Pack,
+    /// Builds the app by running the lifecycle's `creator` in a container of the builder image,
+    /// which only requires Docker on the host.
+    ///
+    /// Buildpacks referenced with [`BuildpackReference::Other`] have to be either a buildpack of
+    /// the builder, referenced as `<id>` or `<id>@<version>`, or a local buildpack directory.
+    /// [`TestContext::download_sbom_files`](crate::TestContext::download_sbom_files) isn't
+    /// supported, since it requires pack.
+    Lifecycle {
+        /// An image with the lifecycle to build with, such as `buildpacksio/lifecycle:0.17.5`,
+        /// instead of the lifecycle of the builder.
+        lifecycle_image: Option<String>,
+    },
+}
+
 /// Result of a pack execution.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum PackResult {
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
 use std::process::Command;
 
 /// Represents a `docker run` command.
@@ -108,6 +109,96 @@ impl From<DockerRunCommand> for Command {
     }
 }
 
+/// Represents a `docker create` command.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerCreateCommand {
+    command: Option<Vec<String>>,
+    container_name: String,
+    image_name: String,
+    platform: Option<String>,
+}
+
+impl DockerCreateCommand {
+    pub(crate) fn new(image_name: impl Into<String>, container_name: impl Into<String>) -> Self {
+        Self {
+            command: None,
+            container_name: container_name.into(),
+            image_name: image_name.into(),
+            platform: None,
+        }
+    }
+
+    pub(crate) fn command<I: IntoIterator<Item = S>, S: Into<String>>(
+        &mut self,
+        command: I,
+    ) -> &mut Self {
+        self.command = Some(command.into_iter().map(S::into).collect());
+        self
+    }
+
+    pub(crate) fn platform(&mut self, platform: impl Into<String>) -> &mut Self {
+        self.platform = Some(platform.into());
+        self
+    }
+}
+
+impl From<DockerCreateCommand> for Command {
+    fn from(docker_create_command: DockerCreateCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args(["create", "--name", &docker_create_command.container_name]);
+
+        if let Some(platform) = docker_create_command.platform {
+            command.args(["--platform", &platform]);
+        }
+
+        command.arg(docker_create_command.image_name);
+
+        if let Some(container_command) = docker_create_command.command {
+            command.args(container_command);
+        }
+
+        command
+    }
+}
+
+/// Represents a `docker cp` command that copies a file from a container.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerCopyFromContainerCommand {
+    container_name: String,
+    container_path: String,
+    destination: PathBuf,
+}
+
+impl DockerCopyFromContainerCommand {
+    pub(crate) fn new(
+        container_name: impl Into<String>,
+        container_path: impl Into<String>,
+        destination: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            container_name: container_name.into(),
+            container_path: container_path.into(),
+            destination: destination.into(),
+        }
+    }
+}
+
+impl From<DockerCopyFromContainerCommand> for Command {
+    fn from(docker_copy_command: DockerCopyFromContainerCommand) -> Self {
+        let mut command = Command::new("docker");
+        command
+            .args([
+                "cp",
+                &format!(
+                    "{}:{}",
+                    docker_copy_command.container_name, docker_copy_command.container_path
+                ),
+            ])
+            .arg(docker_copy_command.destination);
+        command
+    }
+}
+
 /// Represents a `docker exec` command.
 #[derive(Clone, Debug)]
 pub(crate) struct DockerExecCommand {
@@ -228,6 +319,77 @@ impl From<DockerRemoveContainerCommand> for Command {
     }
 }
 
+/// Represents a `docker image inspect` command.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerImageInspectCommand {
+    image_name: String,
+}
+
+impl DockerImageInspectCommand {
+    pub(crate) fn new(image_name: impl Into<String>) -> Self {
+        Self {
+            image_name: image_name.into(),
+        }
+    }
+}
+
+impl From<DockerImageInspectCommand> for Command {
+    fn from(docker_image_inspect_command: DockerImageInspectCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args(["image", "inspect", &docker_image_inspect_command.image_name]);
+        command
+    }
+}
+
+/// Represents a `docker save` command.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerSaveCommand {
+    image_name: String,
+    output_path: PathBuf,
+}
+
+impl DockerSaveCommand {
+    pub(crate) fn new(image_name: impl Into<String>, output_path: impl Into<PathBuf>) -> Self {
+        Self {
+            image_name: image_name.into(),
+            output_path: output_path.into(),
+        }
+    }
+}
+
+impl From<DockerSaveCommand> for Command {
+    fn from(docker_save_command: DockerSaveCommand) -> Self {
+        let mut command = Command::new("docker");
+        command
+            .args(["save", "--output"])
+            .arg(docker_save_command.output_path)
+            .arg(docker_save_command.image_name);
+        command
+    }
+}
+
+/// Represents a `docker pull` command.
+#[derive(Clone, Debug)]
+pub(crate) struct DockerPullCommand {
+    image_name: String,
+}
+
+impl DockerPullCommand {
+    pub(crate) fn new(image_name: impl Into<String>) -> Self {
+        Self {
+            image_name: image_name.into(),
+        }
+    }
+}
+
+impl From<DockerPullCommand> for Command {
+    fn from(docker_pull_command: DockerPullCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args(["pull", &docker_pull_command.image_name]);
+        command
+    }
+}
+
 /// Represents a `docker rmi` command.
 #[derive(Clone, Debug)]
 pub(crate) struct DockerRemoveImageCommand {
@@ -344,6 +506,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_docker_create_command_to_command() {
+        let mut docker_create_command = DockerCreateCommand::new("my-image", "my-container");
+
+        let command: Command = docker_create_command.clone().into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["create", "--name", "my-container", "my-image"]
+        );
+
+        docker_create_command.command(["true"]);
+        docker_create_command.platform("linux/amd64");
+
+        let command: Command = docker_create_command.into();
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            [
+                "create",
+                "--name",
+                "my-container",
+                "--platform",
+                "linux/amd64",
+                "my-image",
+                "true"
+            ]
+        );
+    }
+
+    #[test]
+    fn from_docker_copy_from_container_command_to_command() {
+        let command: Command = DockerCopyFromContainerCommand::new(
+            "my-container",
+            "/layers/config/metadata.toml",
+            "/tmp/metadata.toml",
+        )
+        .into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            [
+                "cp",
+                "my-container:/layers/config/metadata.toml",
+                "/tmp/metadata.toml"
+            ]
+        );
+    }
+
     #[test]
     fn from_docker_exec_command_to_command() {
         let docker_exec_command = DockerExecCommand::new("my-container", ["ps"]);
@@ -420,4 +630,34 @@ mod tests {
             ["volume", "remove", "volume1", "volume2", "--force"]
         );
     }
+
+    #[test]
+    fn from_docker_image_inspect_command_to_command() {
+        let command: Command = DockerImageInspectCommand::new("my-image").into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["image", "inspect", "my-image"]
+        );
+    }
+
+    #[test]
+    fn from_docker_pull_command_to_command() {
+        let command: Command = DockerPullCommand::new("my-image").into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["pull", "my-image"]
+        );
+    }
+
+    #[test]
+    fn from_docker_save_command_to_command() {
+        let command: Command = DockerSaveCommand::new("my-image", "/tmp/image.tar").into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            ["save", "--output", "/tmp/image.tar", "my-image"]
+        );
+    }
 }
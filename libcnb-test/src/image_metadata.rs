@@ -0,0 +1,229 @@
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The label with the lifecycle metadata of an image, which maps layers to their diff IDs.
+const LIFECYCLE_METADATA_LABEL: &str = "io.buildpacks.lifecycle.metadata";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ImageInspect {
+    config: ImageConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ImageConfig {
+    #[serde(default)]
+    labels: Option<HashMap<String, String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LifecycleMetadata {
+    #[serde(default)]
+    app: Vec<LayerMetadata>,
+    config: Option<LayerMetadata>,
+    launcher: Option<LayerMetadata>,
+    #[serde(rename = "process-types")]
+    process_types: Option<LayerMetadata>,
+    sbom: Option<LayerMetadata>,
+    #[serde(default)]
+    buildpacks: Vec<BuildpackLayersMetadata>,
+}
+
+#[derive(Deserialize)]
+struct BuildpackLayersMetadata {
+    key: String,
+    #[serde(default)]
+    layers: BTreeMap<String, LayerMetadata>,
+}
+
+#[derive(Deserialize)]
+struct LayerMetadata {
+    sha: String,
+    /// The `[metadata]` of the layer's TOML file.
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+/// Reads a label of an image from the output of `docker image inspect`.
+pub(crate) fn image_label(inspect_output: &str, label: &str) -> Result<String, String> {
+    let images = serde_json::from_str::<Vec<ImageInspect>>(inspect_output)
+        .map_err(|error| format!("Couldn't parse image inspect output: {error}"))?;
+
+    images
+        .into_iter()
+        .next()
+        .and_then(|image| image.config.labels)
+        .and_then(|mut labels| labels.remove(label))
+        .ok_or_else(|| format!("Image has no {label} label"))
+}
+
+fn lifecycle_metadata(inspect_output: &str) -> Result<LifecycleMetadata, String> {
+    let metadata = image_label(inspect_output, LIFECYCLE_METADATA_LABEL)?;
+
+    serde_json::from_str(&metadata)
+        .map_err(|error| format!("Couldn't parse {LIFECYCLE_METADATA_LABEL} label: {error}"))
+}
+
+/// Reads the diff IDs of the layers of an image from the output of `docker image inspect`, keyed
+/// by a human-readable layer name such as `heroku/ruby layer 'ruby'`.
+pub(crate) fn image_layers(inspect_output: &str) -> Result<BTreeMap<String, String>, String> {
+    let metadata = lifecycle_metadata(inspect_output)?;
+
+    let mut layers = BTreeMap::new();
+
+    for (index, app_layer) in metadata.app.into_iter().enumerate() {
+        layers.insert(format!("app layer {}", index + 1), app_layer.sha);
+    }
+
+    for (name, layer) in [
+        ("config layer", metadata.config),
+        ("launcher layer", metadata.launcher),
+        ("process types layer", metadata.process_types),
+        ("SBOM layer", metadata.sbom),
+    ] {
+        if let Some(layer) = layer {
+            layers.insert(String::from(name), layer.sha);
+        }
+    }
+
+    for buildpack in metadata.buildpacks {
+        for (layer_name, layer) in buildpack.layers {
+            layers.insert(format!("{} layer '{layer_name}'", buildpack.key), layer.sha);
+        }
+    }
+
+    Ok(layers)
+}
+
+/// Reads the metadata of a buildpack's layer from the output of `docker image inspect`.
+///
+/// Returns `None` if the image doesn't contain the layer, and an empty table if the layer has no
+/// metadata.
+pub(crate) fn layer_metadata(
+    inspect_output: &str,
+    buildpack_id: &str,
+    layer_name: &str,
+) -> Result<Option<serde_json::Value>, String> {
+    Ok(lifecycle_metadata(inspect_output)?
+        .buildpacks
+        .into_iter()
+        .find(|buildpack| buildpack.key == buildpack_id)
+        .and_then(|mut buildpack| buildpack.layers.remove(layer_name))
+        .map(|layer| {
+            layer
+                .data
+                .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()))
+        }))
+}
+
+/// Describes the layers that differ between the layers of two images, as returned by
+/// [`image_layers`].
+pub(crate) fn layer_differences(
+    first: &BTreeMap<String, String>,
+    second: &BTreeMap<String, String>,
+) -> Vec<String> {
+    first
+        .keys()
+        .chain(second.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|name| match (first.get(name), second.get(name)) {
+            (Some(first), Some(second)) if first == second => None,
+            (Some(first), Some(second)) => Some(format!("{name}: {first} != {second}")),
+            (Some(_), None) => Some(format!("{name}: missing from the second build")),
+            (None, _) => Some(format!("{name}: missing from the first build")),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspect_output(ruby_sha: &str) -> String {
+        let metadata = serde_json::json!({
+            "app": [{ "sha": "sha256:app" }],
+            "config": { "sha": "sha256:config" },
+            "launcher": { "sha": "sha256:launcher" },
+            "buildpacks": [{
+                "key": "heroku/ruby",
+                "version": "1.0.0",
+                "layers": {
+                    "ruby": { "sha": ruby_sha, "launch": true, "data": { "version": "3.3.1" } },
+                    "gems": { "sha": "sha256:gems", "launch": true }
+                }
+            }],
+            "runImage": { "topLayer": "sha256:run" }
+        });
+
+        serde_json::json!([{
+            "Id": "sha256:image",
+            "Config": { "Labels": { LIFECYCLE_METADATA_LABEL: metadata.to_string() } }
+        }])
+        .to_string()
+    }
+
+    #[test]
+    fn read_image_layers() {
+        let layers = image_layers(&inspect_output("sha256:ruby")).unwrap();
+
+        assert_eq!(
+            layers,
+            BTreeMap::from([
+                (String::from("app layer 1"), String::from("sha256:app")),
+                (String::from("config layer"), String::from("sha256:config")),
+                (
+                    String::from("heroku/ruby layer 'gems'"),
+                    String::from("sha256:gems")
+                ),
+                (
+                    String::from("heroku/ruby layer 'ruby'"),
+                    String::from("sha256:ruby")
+                ),
+                (
+                    String::from("launcher layer"),
+                    String::from("sha256:launcher")
+                ),
+            ])
+        );
+
+        assert_eq!(
+            image_layers(r#"[{"Config": {"Labels": null}}]"#),
+            Err(format!("Image has no {LIFECYCLE_METADATA_LABEL} label"))
+        );
+    }
+
+    #[test]
+    fn read_layer_metadata() {
+        let output = inspect_output("sha256:ruby");
+
+        assert_eq!(
+            layer_metadata(&output, "heroku/ruby", "ruby"),
+            Ok(Some(serde_json::json!({ "version": "3.3.1" })))
+        );
+        assert_eq!(
+            layer_metadata(&output, "heroku/ruby", "gems"),
+            Ok(Some(serde_json::json!({})))
+        );
+        assert_eq!(layer_metadata(&output, "heroku/ruby", "bundler"), Ok(None));
+        assert_eq!(layer_metadata(&output, "heroku/nodejs", "ruby"), Ok(None));
+    }
+
+    #[test]
+    fn compare_image_layers() {
+        let first = image_layers(&inspect_output("sha256:ruby1")).unwrap();
+        let mut second = image_layers(&inspect_output("sha256:ruby2")).unwrap();
+        second.remove("launcher layer");
+
+        assert_eq!(layer_differences(&first, &first), Vec::<String>::new());
+        assert_eq!(
+            layer_differences(&first, &second),
+            [
+                "heroku/ruby layer 'ruby': sha256:ruby1 != sha256:ruby2",
+                "launcher layer: missing from the second build"
+            ]
+        );
+    }
+}
@@ -6,19 +6,26 @@ mod build_config;
 mod container_config;
 mod container_context;
 mod docker;
+mod image_metadata;
+mod lifecycle;
 mod log;
 mod macros;
+mod oci_layout;
 mod pack;
+mod structured_output;
 mod test_context;
 mod test_runner;
+mod toml_query;
 mod util;
 
 pub use crate::build_config::*;
 pub use crate::container_config::*;
 pub use crate::container_context::*;
 pub use crate::log::*;
+pub use crate::structured_output::*;
 pub use crate::test_context::*;
 pub use crate::test_runner::*;
+pub use crate::toml_query::*;
 
 // Suppress warnings due to the `unused_crate_dependencies` lint not handling integration tests well.
 #[cfg(test)]
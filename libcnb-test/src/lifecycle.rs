@@ -0,0 +1,544 @@
+use crate::docker::{
+    DockerCopyFromContainerCommand, DockerCreateCommand, DockerRemoveContainerCommand,
+};
+use crate::image_metadata::image_label;
+use crate::pack::BuildpackReference;
+use crate::util;
+use libcnb_common::toml_file::read_toml_file;
+use libcnb_data::buildpack::BuildpackDescriptor;
+use libcnb_data::package_descriptor::PackageDescriptor;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The Platform API version `creator` is run with, supported by lifecycle 0.17.0 and newer.
+const PLATFORM_API: &str = "0.12";
+
+/// The label with the metadata of a builder, which lists its buildpacks and run images.
+const BUILDER_METADATA_LABEL: &str = "io.buildpacks.builder.metadata";
+
+/// The directory the staging directory is mounted to in the builder container.
+const STAGING_DIR: &str = "/libcnb-test";
+
+// Buildpacks run as the CNB user of the builder, which usually can't read the directories mounted
+// from the host. Like `pack`, the app, platform files, buildpacks and lifecycle are therefore
+// copied into the container as root, before `creator` drops privileges for the build.
+const CREATOR_SCRIPT: &str = r#"set -e
+cp -R /libcnb-test/app/. /workspace
+chown -R "${CNB_USER_ID}:${CNB_GROUP_ID}" /workspace
+mkdir -p /platform /cnb/buildpacks
+cp -R /libcnb-test/platform/. /platform
+cp -R /libcnb-test/buildpacks/. /cnb/buildpacks
+if [ -d /libcnb-test/lifecycle ]; then cp -R /libcnb-test/lifecycle/. /cnb/lifecycle; fi
+chmod -R a+rX /platform /cnb/buildpacks /cnb/lifecycle
+exec /cnb/lifecycle/creator "$@"
+"#;
+
+/// Represents a `docker run` command that builds an app with the lifecycle's `creator` in a
+/// builder image.
+///
+/// The build uses the Docker daemon of the host, via its socket, like `pack build` with a trusted
+/// builder does.
+#[derive(Clone, Debug)]
+pub(crate) struct CreatorCommand {
+    app_dir: PathBuf,
+    build_cache_volume_name: String,
+    builder: String,
+    /// Buildpack directories on the host, keyed by their path relative to `/cnb/buildpacks`.
+    buildpack_dirs: BTreeMap<String, PathBuf>,
+    image_name: String,
+    launch_cache_volume_name: String,
+    run_image: String,
+    staging_dir: PathBuf,
+}
+
+impl CreatorCommand {
+    pub(crate) fn new(
+        builder: impl Into<String>,
+        app_dir: impl Into<PathBuf>,
+        image_name: impl Into<String>,
+        build_cache_volume_name: impl Into<String>,
+        launch_cache_volume_name: impl Into<String>,
+        run_image: impl Into<String>,
+        staging_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            app_dir: app_dir.into(),
+            build_cache_volume_name: build_cache_volume_name.into(),
+            builder: builder.into(),
+            buildpack_dirs: BTreeMap::new(),
+            image_name: image_name.into(),
+            launch_cache_volume_name: launch_cache_volume_name.into(),
+            run_image: run_image.into(),
+            staging_dir: staging_dir.into(),
+        }
+    }
+
+    pub(crate) fn buildpack_dirs(
+        &mut self,
+        buildpack_dirs: BTreeMap<String, PathBuf>,
+    ) -> &mut Self {
+        self.buildpack_dirs = buildpack_dirs;
+        self
+    }
+}
+
+impl From<CreatorCommand> for Command {
+    fn from(creator_command: CreatorCommand) -> Self {
+        let mut command = Command::new("docker");
+        command.args(["run", "--rm", "--user", "root"]);
+
+        let volumes = [
+            (String::from("/var/run/docker.sock"), "/var/run/docker.sock"),
+            (creator_command.build_cache_volume_name, "/cache"),
+            (creator_command.launch_cache_volume_name, "/launch-cache"),
+            (
+                creator_command.staging_dir.to_string_lossy().to_string(),
+                STAGING_DIR,
+            ),
+            (
+                creator_command.app_dir.to_string_lossy().to_string(),
+                "/libcnb-test/app",
+            ),
+        ];
+
+        for (source, target) in volumes {
+            command.args(["--volume", &format!("{source}:{target}")]);
+        }
+
+        for (path, buildpack_dir) in &creator_command.buildpack_dirs {
+            command.args([
+                "--volume",
+                &format!(
+                    "{}:{STAGING_DIR}/buildpacks/{path}:ro",
+                    buildpack_dir.to_string_lossy()
+                ),
+            ]);
+        }
+
+        command.args([
+            "--env",
+            &format!("CNB_PLATFORM_API={PLATFORM_API}"),
+            "--entrypoint",
+            "/bin/sh",
+            &creator_command.builder,
+            "-c",
+            CREATOR_SCRIPT,
+            "creator",
+            "-daemon",
+            "-app",
+            "/workspace",
+            "-cache-dir",
+            "/cache",
+            "-launch-cache",
+            "/launch-cache",
+            "-layers",
+            "/layers",
+            "-platform",
+            "/platform",
+            "-order",
+            &format!("{STAGING_DIR}/order.toml"),
+            "-run-image",
+            &creator_command.run_image,
+            &creator_command.image_name,
+        ]);
+
+        command
+    }
+}
+
+/// The buildpacks and run image of a builder.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct BuilderMetadata {
+    /// The ids and versions of the buildpacks of the builder.
+    pub(crate) buildpacks: Vec<(String, String)>,
+    pub(crate) run_image: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuilderMetadataLabel {
+    #[serde(default)]
+    buildpacks: Vec<BuilderBuildpack>,
+    #[serde(default)]
+    run_images: Vec<RunImage>,
+    stack: Option<BuilderStack>,
+}
+
+#[derive(Deserialize)]
+struct BuilderBuildpack {
+    id: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuilderStack {
+    run_image: RunImage,
+}
+
+#[derive(Deserialize)]
+struct RunImage {
+    image: String,
+}
+
+/// Reads the metadata of a builder from the output of `docker image inspect`.
+pub(crate) fn builder_metadata(inspect_output: &str) -> Result<BuilderMetadata, String> {
+    let label = image_label(inspect_output, BUILDER_METADATA_LABEL)?;
+    let metadata = serde_json::from_str::<BuilderMetadataLabel>(&label)
+        .map_err(|error| format!("Couldn't parse {BUILDER_METADATA_LABEL} label: {error}"))?;
+
+    let run_image = metadata
+        .run_images
+        .into_iter()
+        .next()
+        .or(metadata.stack.map(|stack| stack.run_image))
+        .map(|run_image| run_image.image)
+        .ok_or_else(|| String::from("Builder has no run image"))?;
+
+    Ok(BuilderMetadata {
+        buildpacks: metadata
+            .buildpacks
+            .into_iter()
+            .map(|buildpack| (buildpack.id, buildpack.version))
+            .collect(),
+        run_image,
+    })
+}
+
+#[derive(Serialize)]
+struct OrderToml {
+    order: Vec<OrderGroup>,
+}
+
+#[derive(Serialize)]
+struct OrderGroup {
+    group: Vec<OrderGroupBuildpack>,
+}
+
+#[derive(Serialize)]
+struct OrderGroupBuildpack {
+    id: String,
+    version: String,
+}
+
+/// Writes the platform files, the `order.toml` with a single group of the given buildpacks and
+/// the lifecycle to a staging directory that is copied into the builder container.
+///
+/// Returns the buildpack directories that have to be mounted, keyed by their path relative to
+/// `/cnb/buildpacks`. These include the buildpacks that composite buildpacks depend on.
+pub(crate) fn stage_build(
+    staging_dir: &Path,
+    buildpacks: &[BuildpackReference],
+    builder_metadata: &BuilderMetadata,
+    env: &BTreeMap<String, String>,
+    lifecycle_image: Option<&str>,
+) -> Result<BTreeMap<String, PathBuf>, String> {
+    let mut buildpack_dirs = BTreeMap::new();
+    let mut group = Vec::new();
+
+    for buildpack in buildpacks {
+        let (id, version) = match buildpack {
+            BuildpackReference::Path(dir) => add_buildpack_dir(dir, &mut buildpack_dirs)?,
+            BuildpackReference::Id(reference) if Path::new(reference).is_dir() => {
+                add_buildpack_dir(Path::new(reference), &mut buildpack_dirs)?
+            }
+            BuildpackReference::Id(reference) => builder_buildpack(reference, builder_metadata)?,
+        };
+        group.push(OrderGroupBuildpack { id, version });
+    }
+
+    let order_toml = toml::to_string(&OrderToml {
+        order: vec![OrderGroup { group }],
+    })
+    .map_err(|error| format!("Couldn't serialize order.toml: {error}"))?;
+
+    let platform_env_dir = staging_dir.join("platform").join("env");
+    fs::create_dir_all(&platform_env_dir)
+        .and_then(|()| fs::create_dir_all(staging_dir.join("buildpacks")))
+        .and_then(|()| fs::write(staging_dir.join("order.toml"), order_toml))
+        .map_err(|error| format!("Couldn't write to staging directory: {error}"))?;
+
+    for (key, value) in env {
+        fs::write(platform_env_dir.join(key), value)
+            .map_err(|error| format!("Couldn't write platform env variable {key}: {error}"))?;
+    }
+
+    if let Some(lifecycle_image) = lifecycle_image {
+        copy_lifecycle(lifecycle_image, &staging_dir.join("lifecycle"))?;
+    }
+
+    Ok(buildpack_dirs)
+}
+
+// Adds a packaged buildpack directory and, for composite buildpacks, the directories of the
+// buildpacks it depends on. Returns the id and version of the buildpack.
+fn add_buildpack_dir(
+    dir: &Path,
+    buildpack_dirs: &mut BTreeMap<String, PathBuf>,
+) -> Result<(String, String), String> {
+    let descriptor = read_toml_file::<BuildpackDescriptor>(dir.join("buildpack.toml"))
+        .map_err(|error| format!("Couldn't read buildpack.toml of {}: {error}", dir.display()))?;
+    let id = descriptor.buildpack().id.to_string();
+    let version = descriptor.buildpack().version.to_string();
+
+    buildpack_dirs.insert(
+        format!("{}/{version}", id.replace('/', "_")),
+        dir.to_path_buf(),
+    );
+
+    let package_toml = dir.join("package.toml");
+    if package_toml.exists() {
+        let package_descriptor = read_toml_file::<PackageDescriptor>(&package_toml)
+            .map_err(|error| format!("Couldn't read {}: {error}", package_toml.display()))?;
+
+        for dependency in package_descriptor.dependencies {
+            match dependency.uri.scheme().map(uriparse::Scheme::as_str) {
+                None | Some("file") => {
+                    let dependency_dir = PathBuf::from(dependency.uri.path().to_string());
+                    add_buildpack_dir(&dir.join(dependency_dir), buildpack_dirs)?;
+                }
+                _ => {
+                    return Err(format!(
+                        "Buildpack dependency {} isn't a local directory, which is required when building with the lifecycle",
+                        dependency.uri
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok((id, version))
+}
+
+// Resolves a buildpack of the builder, referenced as `<id>` or `<id>@<version>`.
+fn builder_buildpack(
+    reference: &str,
+    builder_metadata: &BuilderMetadata,
+) -> Result<(String, String), String> {
+    let (id, version) = reference
+        .split_once('@')
+        .map_or((reference, None), |(id, version)| (id, Some(version)));
+
+    builder_metadata
+        .buildpacks
+        .iter()
+        .find(|(builder_id, builder_version)| {
+            builder_id == id && version.map_or(true, |version| version == builder_version)
+        })
+        .cloned()
+        .ok_or_else(|| format!("Buildpack {reference} isn't a buildpack of the builder, only buildpacks of the builder can be referenced by id when building with the lifecycle"))
+}
+
+fn copy_lifecycle(lifecycle_image: &str, destination: &Path) -> Result<(), String> {
+    let container_name = util::random_docker_identifier();
+
+    let mut docker_create_command = DockerCreateCommand::new(lifecycle_image, &container_name);
+    // Lifecycle images don't have a default command, which `docker create` requires.
+    docker_create_command.command(["/cnb/lifecycle/lifecycle"]);
+    util::run_command(docker_create_command)
+        .map_err(|command_err| format!("Error creating lifecycle container:\n\n{command_err}"))?;
+
+    let copy_result = util::run_command(DockerCopyFromContainerCommand::new(
+        &container_name,
+        "/cnb/lifecycle",
+        destination,
+    ));
+    let _ = util::run_command(DockerRemoveContainerCommand::new(&container_name));
+
+    copy_result
+        .map(|_| ())
+        .map_err(|command_err| format!("Error copying lifecycle from image:\n\n{command_err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn builder() -> BuilderMetadata {
+        BuilderMetadata {
+            buildpacks: vec![
+                (String::from("heroku/procfile"), String::from("3.0.0")),
+                (String::from("heroku/procfile"), String::from("3.1.0")),
+            ],
+            run_image: String::from("heroku/heroku:22-cnb"),
+        }
+    }
+
+    #[test]
+    fn read_builder_metadata() {
+        let inspect_output = |label: serde_json::Value| {
+            serde_json::json!([{
+                "Config": { "Labels": { BUILDER_METADATA_LABEL: label.to_string() } }
+            }])
+            .to_string()
+        };
+
+        assert_eq!(
+            builder_metadata(&inspect_output(serde_json::json!({
+                "buildpacks": [
+                    { "id": "heroku/procfile", "version": "3.0.0", "homepage": "" },
+                    { "id": "heroku/procfile", "version": "3.1.0" }
+                ],
+                "stack": { "runImage": { "image": "heroku/heroku:22-cnb", "mirrors": null } },
+                "lifecycle": { "version": "0.17.5" }
+            }))),
+            Ok(builder())
+        );
+
+        assert_eq!(
+            builder_metadata(&inspect_output(serde_json::json!({
+                "runImages": [{ "image": "heroku/heroku:24" }]
+            })))
+            .map(|metadata| metadata.run_image),
+            Ok(String::from("heroku/heroku:24"))
+        );
+
+        assert_eq!(
+            builder_metadata(&inspect_output(serde_json::json!({}))),
+            Err(String::from("Builder has no run image"))
+        );
+    }
+
+    #[test]
+    fn resolve_builder_buildpacks() {
+        assert_eq!(
+            builder_buildpack("heroku/procfile", &builder()),
+            Ok((String::from("heroku/procfile"), String::from("3.0.0")))
+        );
+        assert_eq!(
+            builder_buildpack("heroku/procfile@3.1.0", &builder()),
+            Ok((String::from("heroku/procfile"), String::from("3.1.0")))
+        );
+        assert!(builder_buildpack("heroku/procfile@4.0.0", &builder()).is_err());
+        assert!(builder_buildpack("heroku/python", &builder()).is_err());
+    }
+
+    #[test]
+    fn stage_build_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let buildpack_dir = temp_dir.path().join("buildpack");
+        let staging_dir = temp_dir.path().join("staging");
+
+        fs::create_dir(&buildpack_dir).unwrap();
+        fs::write(
+            buildpack_dir.join("buildpack.toml"),
+            r#"
+api = "0.10"
+
+[buildpack]
+id = "libcnb-test/buildpack"
+version = "1.2.3"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            buildpack_dir.join("package.toml"),
+            "[buildpack]\nuri = \".\"\n",
+        )
+        .unwrap();
+
+        let buildpack_dirs = stage_build(
+            &staging_dir,
+            &[
+                BuildpackReference::Path(buildpack_dir.clone()),
+                BuildpackReference::Id(String::from("heroku/procfile")),
+            ],
+            &builder(),
+            &BTreeMap::from([(String::from("FOO"), String::from("bar"))]),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            buildpack_dirs,
+            BTreeMap::from([(String::from("libcnb-test_buildpack/1.2.3"), buildpack_dir)])
+        );
+        assert_eq!(
+            fs::read_to_string(staging_dir.join("order.toml")).unwrap(),
+            r#"[[order]]
+
+[[order.group]]
+id = "libcnb-test/buildpack"
+version = "1.2.3"
+
+[[order.group]]
+id = "heroku/procfile"
+version = "3.0.0"
+"#
+        );
+        assert_eq!(
+            fs::read_to_string(staging_dir.join("platform").join("env").join("FOO")).unwrap(),
+            "bar"
+        );
+        assert!(staging_dir.join("buildpacks").is_dir());
+    }
+
+    #[test]
+    fn from_creator_command_to_command() {
+        let mut creator_command = CreatorCommand::new(
+            "heroku/builder:22",
+            "/tmp/app",
+            "my-image",
+            "build-cache-volume",
+            "launch-cache-volume",
+            "heroku/heroku:22-cnb",
+            "/tmp/staging",
+        );
+        creator_command.buildpack_dirs(BTreeMap::from([(
+            String::from("libcnb-test_buildpack/1.2.3"),
+            PathBuf::from("/tmp/buildpack"),
+        )]));
+
+        let command: Command = creator_command.into();
+        assert_eq!(command.get_program(), "docker");
+        assert_eq!(
+            command.get_args().collect::<Vec<&OsStr>>(),
+            [
+                "run",
+                "--rm",
+                "--user",
+                "root",
+                "--volume",
+                "/var/run/docker.sock:/var/run/docker.sock",
+                "--volume",
+                "build-cache-volume:/cache",
+                "--volume",
+                "launch-cache-volume:/launch-cache",
+                "--volume",
+                "/tmp/staging:/libcnb-test",
+                "--volume",
+                "/tmp/app:/libcnb-test/app",
+                "--volume",
+                "/tmp/buildpack:/libcnb-test/buildpacks/libcnb-test_buildpack/1.2.3:ro",
+                "--env",
+                "CNB_PLATFORM_API=0.12",
+                "--entrypoint",
+                "/bin/sh",
+                "heroku/builder:22",
+                "-c",
+                CREATOR_SCRIPT,
+                "creator",
+                "-daemon",
+                "-app",
+                "/workspace",
+                "-cache-dir",
+                "/cache",
+                "-launch-cache",
+                "/launch-cache",
+                "-layers",
+                "/layers",
+                "-platform",
+                "/platform",
+                "-order",
+                "/libcnb-test/order.toml",
+                "-run-image",
+                "heroku/heroku:22-cnb",
+                "my-image",
+            ]
+        );
+    }
+}
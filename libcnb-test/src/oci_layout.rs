@@ -0,0 +1,273 @@
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+const OCI_INDEX_MEDIA_TYPE: &str = "application/vnd.oci.image.index.v1+json";
+const OCI_MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+const OCI_CONFIG_MEDIA_TYPE: &str = "application/vnd.oci.image.config.v1+json";
+const OCI_LAYER_MEDIA_TYPE: &str = "application/vnd.oci.image.layer.v1.tar";
+
+/// The reference name the image is annotated with in `index.json`, matching the tag `docker save`
+/// uses for images it exports as OCI layout itself.
+pub(crate) const OCI_LAYOUT_REF_NAME: &str = "latest";
+
+/// An entry of the `manifest.json` of a `docker save` archive.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerArchiveManifest {
+    config: String,
+    layers: Vec<String>,
+}
+
+/// Writes the image in a `docker save` archive as an OCI image layout to the given directory.
+///
+/// Docker 25 and later already export an OCI image layout, which is written as-is. Archives of
+/// older versions, which only contain a Docker `manifest.json`, are converted by writing their
+/// config and layers as blobs and adding an OCI manifest. An existing `index.json` in the directory
+/// is replaced.
+pub(crate) fn write_oci_layout(docker_archive: &Path, dir: &Path) -> Result<(), String> {
+    let archive_dir = tempfile::tempdir()
+        .map_err(|error| format!("Couldn't create temporary directory: {error}"))?;
+
+    File::open(docker_archive)
+        .and_then(|file| tar::Archive::new(file).unpack(archive_dir.path()))
+        .map_err(|error| format!("Couldn't unpack image archive: {error}"))?;
+
+    fs::create_dir_all(dir.join("blobs").join("sha256"))
+        .map_err(|error| format!("Couldn't create OCI layout directory: {error}"))?;
+
+    if archive_dir.path().join("oci-layout").exists() {
+        copy_oci_layout(archive_dir.path(), dir)
+            .map_err(|error| format!("Couldn't copy OCI layout: {error}"))
+    } else {
+        convert_docker_archive(archive_dir.path(), dir)
+    }
+}
+
+fn copy_oci_layout(archive_dir: &Path, dir: &Path) -> io::Result<()> {
+    fs::copy(archive_dir.join("oci-layout"), dir.join("oci-layout"))?;
+    fs::copy(archive_dir.join("index.json"), dir.join("index.json"))?;
+
+    let blobs_dir = archive_dir.join("blobs");
+    for algorithm_dir in fs::read_dir(&blobs_dir)? {
+        let algorithm_dir = algorithm_dir?.path();
+        let target_dir = dir.join("blobs").join(
+            algorithm_dir
+                .strip_prefix(&blobs_dir)
+                .unwrap_or(&algorithm_dir),
+        );
+        fs::create_dir_all(&target_dir)?;
+
+        for blob in fs::read_dir(&algorithm_dir)? {
+            let blob = blob?;
+            fs::copy(blob.path(), target_dir.join(blob.file_name()))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn convert_docker_archive(archive_dir: &Path, dir: &Path) -> Result<(), String> {
+    let manifest = fs::read_to_string(archive_dir.join("manifest.json"))
+        .map_err(|error| format!("Couldn't read manifest.json of image archive: {error}"))?;
+
+    let manifest = serde_json::from_str::<Vec<DockerArchiveManifest>>(&manifest)
+        .map_err(|error| format!("Couldn't parse manifest.json of image archive: {error}"))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| String::from("Image archive contains no images"))?;
+
+    let config = write_blob_from_file(&archive_dir.join(&manifest.config), dir)?;
+
+    let layers = manifest
+        .layers
+        .iter()
+        .map(|layer| write_blob_from_file(&archive_dir.join(layer), dir))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let oci_manifest = json!({
+        "schemaVersion": 2,
+        "mediaType": OCI_MANIFEST_MEDIA_TYPE,
+        "config": descriptor(OCI_CONFIG_MEDIA_TYPE, &config),
+        "layers": layers
+            .iter()
+            .map(|layer| descriptor(OCI_LAYER_MEDIA_TYPE, layer))
+            .collect::<Vec<_>>(),
+    });
+    let oci_manifest = write_blob(oci_manifest.to_string().as_bytes(), dir)?;
+
+    let mut manifest_descriptor = descriptor(OCI_MANIFEST_MEDIA_TYPE, &oci_manifest);
+    manifest_descriptor["annotations"] =
+        json!({ "org.opencontainers.image.ref.name": OCI_LAYOUT_REF_NAME });
+
+    let index = json!({
+        "schemaVersion": 2,
+        "mediaType": OCI_INDEX_MEDIA_TYPE,
+        "manifests": [manifest_descriptor],
+    });
+
+    fs::write(dir.join("index.json"), index.to_string())
+        .and_then(|()| {
+            fs::write(
+                dir.join("oci-layout"),
+                json!({ "imageLayoutVersion": "1.0.0" }).to_string(),
+            )
+        })
+        .map_err(|error| format!("Couldn't write OCI layout: {error}"))
+}
+
+/// The digest and size of a blob.
+struct Blob {
+    digest: String,
+    size: u64,
+}
+
+fn descriptor(media_type: &str, blob: &Blob) -> serde_json::Value {
+    json!({ "mediaType": media_type, "digest": blob.digest, "size": blob.size })
+}
+
+fn write_blob_from_file(path: &Path, dir: &Path) -> Result<Blob, String> {
+    let blob_error = |error: io::Error| format!("Couldn't write blob {}: {error}", path.display());
+
+    let mut hasher = Sha256::new();
+    let size = File::open(path)
+        .and_then(|mut file| io::copy(&mut file, &mut hasher))
+        .map_err(blob_error)?;
+    let digest = format!("{:x}", hasher.finalize());
+
+    fs::copy(path, dir.join("blobs").join("sha256").join(&digest)).map_err(blob_error)?;
+
+    Ok(Blob {
+        digest: format!("sha256:{digest}"),
+        size,
+    })
+}
+
+fn write_blob(contents: &[u8], dir: &Path) -> Result<Blob, String> {
+    let digest = format!("{:x}", Sha256::digest(contents));
+
+    fs::write(dir.join("blobs").join("sha256").join(&digest), contents)
+        .map_err(|error| format!("Couldn't write blob sha256:{digest}: {error}"))?;
+
+    Ok(Blob {
+        digest: format!("sha256:{digest}"),
+        size: contents.len() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append_file(builder: &mut tar::Builder<File>, path: &str, contents: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, contents).unwrap();
+    }
+
+    fn read_json(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn convert_docker_archive_to_oci_layout() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive_path = temp_dir.path().join("image.tar");
+        let layout_dir = temp_dir.path().join("layout");
+
+        let mut builder = tar::Builder::new(File::create(&archive_path).unwrap());
+        append_file(
+            &mut builder,
+            "manifest.json",
+            br#"[{"Config": "config.json", "RepoTags": ["image:latest"], "Layers": ["1/layer.tar", "2/layer.tar"]}]"#,
+        );
+        append_file(&mut builder, "config.json", b"{}");
+        append_file(&mut builder, "1/layer.tar", b"first");
+        append_file(&mut builder, "2/layer.tar", b"second");
+        builder.into_inner().unwrap();
+
+        write_oci_layout(&archive_path, &layout_dir).unwrap();
+
+        assert_eq!(
+            read_json(&layout_dir.join("oci-layout")),
+            json!({ "imageLayoutVersion": "1.0.0" })
+        );
+
+        let index = read_json(&layout_dir.join("index.json"));
+        assert_eq!(
+            index["manifests"][0]["annotations"]["org.opencontainers.image.ref.name"],
+            "latest"
+        );
+
+        let manifest_digest = index["manifests"][0]["digest"].as_str().unwrap();
+        let manifest = read_json(
+            &layout_dir
+                .join("blobs")
+                .join("sha256")
+                .join(manifest_digest.trim_start_matches("sha256:")),
+        );
+
+        // The SHA-256 digests of `{}`, `first` and `second`.
+        assert_eq!(
+            manifest["config"],
+            json!({
+                "mediaType": OCI_CONFIG_MEDIA_TYPE,
+                "digest": "sha256:44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a",
+                "size": 2
+            })
+        );
+        assert_eq!(
+            manifest["layers"][1],
+            json!({
+                "mediaType": OCI_LAYER_MEDIA_TYPE,
+                "digest": "sha256:16367aacb67a4a017c8da8ab95682ccb390863780f7114dda0a0e0c55644c7c4",
+                "size": 6
+            })
+        );
+        assert_eq!(
+            fs::read(
+                layout_dir
+                    .join("blobs")
+                    .join("sha256")
+                    .join("a7937b64b8caa58f03721bb6bacf5c78cb235febe0e70b1b84cd99541461a08e")
+            )
+            .unwrap(),
+            b"first"
+        );
+    }
+
+    #[test]
+    fn copy_oci_layout_from_docker_archive() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive_path = temp_dir.path().join("image.tar");
+        let layout_dir = temp_dir.path().join("layout");
+
+        let mut builder = tar::Builder::new(File::create(&archive_path).unwrap());
+        append_file(
+            &mut builder,
+            "oci-layout",
+            br#"{"imageLayoutVersion": "1.0.0"}"#,
+        );
+        append_file(&mut builder, "index.json", br#"{"schemaVersion": 2}"#);
+        append_file(&mut builder, "manifest.json", b"[]");
+        append_file(&mut builder, "blobs/sha256/abc", b"blob");
+        builder.into_inner().unwrap();
+
+        write_oci_layout(&archive_path, &layout_dir).unwrap();
+
+        assert_eq!(
+            fs::read(layout_dir.join("index.json")).unwrap(),
+            br#"{"schemaVersion": 2}"#
+        );
+        assert_eq!(
+            fs::read(layout_dir.join("blobs").join("sha256").join("abc")).unwrap(),
+            b"blob"
+        );
+        assert!(!layout_dir.join("manifest.json").exists());
+    }
+}
@@ -13,6 +13,9 @@ pub(crate) struct PackBuildCommand {
     launch_cache_volume_name: String,
     path: PathBuf,
     pull_policy: PullPolicy,
+    /// Environment variables that are passed to `pack` via its environment instead of its
+    /// arguments, so that their values don't show up in process listings.
+    secret_env: BTreeMap<String, String>,
     trust_builder: bool,
 }
 
@@ -64,6 +67,7 @@ impl PackBuildCommand {
             path: path.into(),
             // Prevent redundant image-pulling, which slows tests and risks hitting registry rate limits.
             pull_policy: PullPolicy::IfNotPresent,
+            secret_env: BTreeMap::new(),
             trust_builder: true,
         }
     }
@@ -77,6 +81,11 @@ impl PackBuildCommand {
         self.env.insert(k.into(), v.into());
         self
     }
+
+    pub(crate) fn secret_env(&mut self, k: impl Into<String>, v: impl Into<String>) -> &mut Self {
+        self.secret_env.insert(k.into(), v.into());
+        self
+    }
 }
 
 impl From<PackBuildCommand> for Command {
@@ -122,6 +131,12 @@ impl From<PackBuildCommand> for Command {
             command.args(["--env", &format!("{env_key}={env_value}")]);
         }
 
+        // Without a value, `pack` reads the value of the variable from its own environment.
+        for (env_key, env_value) in &pack_build_command.secret_env {
+            command.args(["--env", env_key]);
+            command.env(env_key, env_value);
+        }
+
         if pack_build_command.trust_builder {
             command.arg("--trust-builder");
         }
@@ -187,6 +202,7 @@ mod tests {
             launch_cache_volume_name: String::from("launch-cache-volume"),
             path: PathBuf::from("/tmp/foo/bar"),
             pull_policy: PullPolicy::IfNotPresent,
+            secret_env: BTreeMap::new(),
             trust_builder: true,
         };
 
@@ -229,6 +245,19 @@ mod tests {
         assert!(!command
             .get_args()
             .any(|arg| arg == OsStr::new("--trust-builder")));
+
+        // Assert secret env values are passed via the environment only:
+        input.secret_env =
+            BTreeMap::from([(String::from("CNB_REGISTRY_AUTH"), String::from("{}"))]);
+        let command: Command = input.into();
+        assert!(command
+            .get_args()
+            .collect::<Vec<&OsStr>>()
+            .ends_with(&[OsStr::new("--env"), OsStr::new("CNB_REGISTRY_AUTH")]));
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            [(OsStr::new("CNB_REGISTRY_AUTH"), Some(OsStr::new("{}")))]
+        );
     }
 
     #[test]
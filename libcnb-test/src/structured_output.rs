@@ -0,0 +1,242 @@
+use serde::Deserialize;
+
+/// The environment variable that switches the `buildpack_output` module of `libherokubuildpack`
+/// to writing structured events as JSON lines instead of text.
+pub const STRUCTURED_OUTPUT_ENV_VAR: &str = "BUILDPACK_OUTPUT_FORMAT";
+
+/// Structured buildpack output, parsed from the JSON lines written by buildpacks that use the
+/// `buildpack_output` module of `libherokubuildpack`.
+///
+/// Asserting on sections, steps and warnings is less brittle than matching substrings of the text
+/// output, since it doesn't depend on formatting, colors or the output of other buildpacks.
+///
+/// The JSON lines are only written if the [`STRUCTURED_OUTPUT_ENV_VAR`] environment variable is
+/// set to `json` for the build, see [`BuildConfig::env`](crate::BuildConfig::env). Use
+/// [`TestContext::structured_output`](crate::TestContext::structured_output) to parse the output
+/// of a build.
+///
+/// # Example
+/// ```
+/// use libcnb_test::StructuredOutput;
+///
+/// let output = StructuredOutput::parse(
+///     r#"===> BUILDING
+/// {"type":"buildpack_start","name":"Ruby Buildpack"}
+/// {"type":"section","message":"Ruby version"}
+/// {"type":"step","message":"Installing Ruby 3.3.1"}
+/// {"type":"warning","message":"No Gemfile.lock found"}
+/// {"type":"buildpack_done","duration_ms":2310}
+/// ===> EXPORTING"#,
+/// );
+///
+/// assert_eq!(output.sections(), ["Ruby version"]);
+/// assert_eq!(output.steps("Ruby version"), ["Installing Ruby 3.3.1"]);
+/// assert_eq!(output.warnings(), ["No Gemfile.lock found"]);
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct StructuredOutput {
+    pub events: Vec<OutputEvent>,
+}
+
+/// An event of [`StructuredOutput`].
+///
+/// Events that only carry timing information, such as the end of a stream, are parsed as
+/// [`OutputEvent::Other`].
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputEvent {
+    BuildpackStart {
+        name: String,
+    },
+    Section {
+        message: String,
+    },
+    Step {
+        message: String,
+    },
+    Debug {
+        message: String,
+    },
+    Warning {
+        message: String,
+    },
+    Important {
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+    StreamStart {
+        message: String,
+    },
+    StreamLine {
+        line: String,
+    },
+    ProgressStart {
+        message: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl StructuredOutput {
+    /// Parses the structured output from the output of `pack`.
+    ///
+    /// Lines that aren't events, such as the output of the lifecycle or of buildpacks that don't
+    /// write structured output, are ignored. Prefixes before the JSON object of an event, such as
+    /// the `[builder]` prefix of older `pack` versions, are ignored as well.
+    #[must_use]
+    pub fn parse(pack_stdout: &str) -> Self {
+        let events = pack_stdout
+            .lines()
+            .filter_map(|line| line.find('{').map(|start| &line[start..]))
+            .filter_map(|json| serde_json::from_str(json.trim_end()).ok())
+            .collect();
+
+        Self { events }
+    }
+
+    /// The names of all buildpacks that started, in order.
+    #[must_use]
+    pub fn buildpacks(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                OutputEvent::BuildpackStart { name } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// All sections, in order.
+    #[must_use]
+    pub fn sections(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                OutputEvent::Section { message } => Some(message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The steps of all sections with the given name, in order.
+    ///
+    /// Streams and progress reports are steps as well, debug messages are not.
+    #[must_use]
+    pub fn steps(&self, section: &str) -> Vec<&str> {
+        let mut in_section = false;
+        let mut steps = Vec::new();
+
+        for event in &self.events {
+            match event {
+                OutputEvent::Section { message } => in_section = message == section,
+                OutputEvent::BuildpackStart { .. } => in_section = false,
+                OutputEvent::Step { message }
+                | OutputEvent::StreamStart { message }
+                | OutputEvent::ProgressStart { message }
+                    if in_section =>
+                {
+                    steps.push(message.as_str());
+                }
+                _ => {}
+            }
+        }
+
+        steps
+    }
+
+    /// All lines of streamed output, such as the output of commands, in order.
+    #[must_use]
+    pub fn stream_lines(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                OutputEvent::StreamLine { line } => Some(line.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// All warnings, in order.
+    #[must_use]
+    pub fn warnings(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                OutputEvent::Warning { message } => Some(message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// All important messages, in order.
+    #[must_use]
+    pub fn important(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                OutputEvent::Important { message } => Some(message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// All errors, in order.
+    #[must_use]
+    pub fn errors(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                OutputEvent::Error { message } => Some(message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn parse_structured_output() {
+        let output = StructuredOutput::parse(indoc! {r#"
+            ===> BUILDING
+            [builder] {"type":"buildpack_start","name":"Ruby Buildpack"}
+            {"type":"section","message":"Ruby version"}
+            {"type":"step","message":"Installing Ruby"}
+            {"type":"stream_start","message":"Running bundle install"}
+            {"type":"stream_line","line":"Fetching gem metadata"}
+            {"type":"stream_done","duration_ms":12}
+            {"type":"section","message":"Default process"}
+            {"type":"step","message":"Using `rails server`"}
+            {"type":"important","message":"Rails detected"}
+            {"type":"buildpack_done","duration_ms":200}
+            Unrelated output with {"type": "broken"
+            {"type":"buildpack_start","name":"Procfile Buildpack"}
+            {"type":"section","message":"Ruby version"}
+            {"type":"error","message":"Unexpected Ruby version"}
+        "#});
+
+        assert_eq!(
+            output.buildpacks(),
+            ["Ruby Buildpack", "Procfile Buildpack"]
+        );
+        assert_eq!(
+            output.sections(),
+            ["Ruby version", "Default process", "Ruby version"]
+        );
+        assert_eq!(
+            output.steps("Ruby version"),
+            ["Installing Ruby", "Running bundle install"]
+        );
+        assert_eq!(output.steps("Missing"), Vec::<&str>::new());
+        assert_eq!(output.stream_lines(), ["Fetching gem metadata"]);
+        assert_eq!(output.important(), ["Rails detected"]);
+        assert_eq!(output.errors(), ["Unexpected Ruby version"]);
+        assert!(output.warnings().is_empty());
+        assert_eq!(output.events[5], OutputEvent::Other);
+    }
+}
@@ -1,14 +1,21 @@
-use crate::docker::DockerRunCommand;
+use crate::docker::{
+    DockerCopyFromContainerCommand, DockerCreateCommand, DockerImageInspectCommand,
+    DockerRemoveContainerCommand, DockerRunCommand, DockerSaveCommand,
+};
+use crate::image_metadata::{image_layers, layer_differences, layer_metadata};
+use crate::oci_layout::write_oci_layout;
 use crate::pack::PackSbomDownloadCommand;
 use crate::{
-    util, BuildConfig, ContainerConfig, ContainerContext, LogOutput, TemporaryDockerResources,
-    TestRunner,
+    util, BuildConfig, ContainerConfig, ContainerContext, LogOutput, StructuredOutput,
+    TemporaryDockerResources, TestRunner, TomlQuery,
 };
 use libcnb_data::buildpack::BuildpackId;
 use libcnb_data::layer::LayerName;
 use libcnb_data::sbom::SbomFormat;
 use std::borrow::Borrow;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use tempfile::tempdir;
 
 /// Context for a currently executing test.
@@ -24,7 +31,32 @@ pub struct TestContext<'a> {
     pub(crate) runner: &'a TestRunner,
 }
 
-impl<'a> TestContext<'a> {
+impl TestContext<'_> {
+    /// Parses the structured output of buildpacks that use the `buildpack_output` module of
+    /// `libherokubuildpack` from the `pack` output.
+    ///
+    /// The structured output has to be enabled for the build by setting the
+    /// [`STRUCTURED_OUTPUT_ENV_VAR`](crate::STRUCTURED_OUTPUT_ENV_VAR) environment variable.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner, STRUCTURED_OUTPUT_ENV_VAR};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app")
+    ///         .env(STRUCTURED_OUTPUT_ENV_VAR, "json"),
+    ///     |context| {
+    ///         let output = context.structured_output();
+    ///         assert_eq!(output.steps("Ruby version"), ["Installing Ruby 3.3.1"]);
+    ///         assert!(output.warnings().is_empty());
+    ///     },
+    /// );
+    /// ```
+    #[must_use]
+    pub fn structured_output(&self) -> StructuredOutput {
+        StructuredOutput::parse(&self.pack_stdout)
+    }
+
     /// Starts a detached container using the provided [`ContainerConfig`].
     ///
     /// After the passed function has returned, the Docker container is removed.
@@ -248,6 +280,149 @@ impl<'a> TestContext<'a> {
         })
     }
 
+    /// Reads the launch metadata of the built image, so that it can be queried with paths.
+    ///
+    /// The launch metadata is written by the lifecycle to `/layers/config/metadata.toml` and
+    /// combines the `launch.toml` files of all buildpacks, such as their `processes`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         let launch_metadata = context.launch_metadata();
+    ///         launch_metadata.assert_toml_eq("processes[0].type", "web");
+    ///         launch_metadata.assert_toml_eq("processes[0].command[0]", "bundle");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file couldn't be read from the image or isn't valid TOML.
+    #[must_use]
+    pub fn launch_metadata(&self) -> TomlQuery {
+        self.read_image_file("/layers/config/metadata.toml")
+            .parse()
+            .unwrap_or_else(|error| panic!("Error parsing launch metadata: {error}"))
+    }
+
+    /// Reads the metadata of a layer of the built image, so that it can be queried with paths.
+    ///
+    /// The metadata is the `[metadata]` table of the layer's TOML file, which the lifecycle stores
+    /// in the image's labels. Only layers that are exported to the image, i.e. have `launch` set
+    /// to `true`, are available.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_data::{buildpack_id, layer_name};
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         context
+    ///             .layer_metadata(buildpack_id!("heroku/ruby"), layer_name!("ruby"))
+    ///             .assert_toml_eq("version", "3.3.1");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be inspected or doesn't contain the layer.
+    #[must_use]
+    pub fn layer_metadata(
+        &self,
+        buildpack_id: impl Borrow<BuildpackId>,
+        layer_name: impl Borrow<LayerName>,
+    ) -> TomlQuery {
+        let (buildpack_id, layer_name) = (buildpack_id.borrow(), layer_name.borrow());
+
+        let output = util::run_command(DockerImageInspectCommand::new(
+            &self.docker_resources.image_name,
+        ))
+        .unwrap_or_else(|command_err| panic!("Error inspecting image:\n\n{command_err}"));
+
+        let metadata = layer_metadata(&output.stdout, buildpack_id, layer_name)
+            .unwrap_or_else(|error| panic!("Error reading layer metadata: {error}"))
+            .unwrap_or_else(|| {
+                panic!("Image doesn't contain layer '{layer_name}' of buildpack {buildpack_id}")
+            });
+
+        TomlQuery::new(
+            toml::Value::try_from(metadata)
+                .unwrap_or_else(|error| panic!("Error converting layer metadata to TOML: {error}")),
+        )
+    }
+
+    // Copies the file out of a created, but not started, container instead of running `cat` in
+    // the image, since run images aren't required to contain a shell or coreutils.
+    fn read_image_file(&self, path: &str) -> String {
+        let container_name = util::random_docker_identifier();
+        let temp_dir = tempdir().expect("Couldn't create temporary directory for image file");
+        let destination = temp_dir.path().join("file");
+
+        let mut docker_create_command =
+            DockerCreateCommand::new(&self.docker_resources.image_name, &container_name);
+        docker_create_command.platform(self.determine_container_platform());
+        util::run_command(docker_create_command)
+            .unwrap_or_else(|command_err| panic!("Error creating container:\n\n{command_err}"));
+
+        let copy_result = util::run_command(DockerCopyFromContainerCommand::new(
+            &container_name,
+            path,
+            &destination,
+        ));
+        let _ = util::run_command(DockerRemoveContainerCommand::new(&container_name));
+        copy_result.unwrap_or_else(|command_err| {
+            panic!("Error copying {path} from image:\n\n{command_err}")
+        });
+
+        fs::read_to_string(&destination)
+            .unwrap_or_else(|error| panic!("Error reading {path} copied from image: {error}"))
+    }
+
+    /// Saves the built image as an [OCI image layout](https://github.com/opencontainers/image-spec/blob/main/image-layout.md)
+    /// to the given directory.
+    ///
+    /// This allows tools that don't have access to the Docker daemon used for the build, such as
+    /// image scanners, signing tools or custom deployment tooling, to consume the image. The
+    /// manifest of the image is annotated with the reference name `latest` in `index.json`, so
+    /// it can be referenced as `oci:<dir>:latest` with tools like `skopeo`. An existing
+    /// `index.json` in the directory is replaced.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         context.save_oci_layout("target/oci-layout");
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image couldn't be exported from Docker or written to the directory.
+    pub fn save_oci_layout(&self, dir: impl AsRef<Path>) {
+        let temp_dir = tempdir().expect("Couldn't create temporary directory for image archive");
+        let archive_path = temp_dir.path().join("image.tar");
+
+        util::run_command(DockerSaveCommand::new(
+            &self.docker_resources.image_name,
+            &archive_path,
+        ))
+        .unwrap_or_else(|command_err| panic!("Error saving image:\n\n{command_err}"));
+
+        write_oci_layout(&archive_path, dir.as_ref())
+            .unwrap_or_else(|error| panic!("Error writing OCI layout: {error}"));
+    }
+
     /// Starts a subsequent integration test build.
     ///
     /// This function behaves exactly like [`TestRunner::build`], but it will reuse the OCI image
@@ -280,6 +455,67 @@ impl<'a> TestContext<'a> {
     pub fn rebuild<C: Borrow<BuildConfig>, F: FnOnce(TestContext)>(self, config: C, f: F) {
         self.runner.build_internal(self.docker_resources, config, f);
     }
+
+    /// Asserts that the image of this build is reproducible.
+    ///
+    /// Builds the app again with the same [`BuildConfig`], but with empty caches, and compares the
+    /// digests of the layers of both images. Layers are identified by the lifecycle metadata of the
+    /// images, so the assertion reports which buildpack layers, app layers or lifecycle layers
+    /// differ. The image of the second build is removed afterwards.
+    ///
+    /// For a meaningful comparison, call this function from the context of a build that didn't
+    /// restore any caches, i.e. not from a [`TestContext::rebuild`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use libcnb_test::{BuildConfig, TestRunner};
+    ///
+    /// TestRunner::default().build(
+    ///     BuildConfig::new("heroku/builder:22", "tests/fixtures/app"),
+    ///     |context| {
+    ///         context.assert_reproducible();
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any layers differ between the two images, if the second build failed or if the
+    /// images couldn't be inspected.
+    pub fn assert_reproducible(&self) {
+        let image_name = util::random_docker_identifier();
+        let docker_resources = TemporaryDockerResources {
+            build_cache_volume_name: format!("{image_name}.build-cache"),
+            launch_cache_volume_name: format!("{image_name}.launch-cache"),
+            image_name,
+        };
+
+        let first_layers = self.image_layers();
+        self.runner
+            .build_internal(docker_resources, &self.config, |second_context| {
+                let differences = layer_differences(&first_layers, &second_context.image_layers());
+
+                assert!(
+                    differences.is_empty(),
+                    "The image isn't reproducible, the following layers differ between two builds from an empty cache:\n\n{}",
+                    differences
+                        .iter()
+                        .map(|difference| format!("- {difference}"))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                );
+            });
+    }
+
+    fn image_layers(&self) -> BTreeMap<String, String> {
+        let output = util::run_command(DockerImageInspectCommand::new(
+            &self.docker_resources.image_name,
+        ))
+        .unwrap_or_else(|command_err| panic!("Error inspecting image:\n\n{command_err}"));
+
+        image_layers(&output.stdout)
+            .unwrap_or_else(|error| panic!("Error reading image layers: {error}"))
+    }
 }
 
 /// Downloaded SBOM files.
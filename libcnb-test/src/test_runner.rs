@@ -1,11 +1,20 @@
-use crate::docker::{DockerRemoveImageCommand, DockerRemoveVolumeCommand};
+use crate::docker::{
+    DockerImageInspectCommand, DockerPullCommand, DockerRemoveImageCommand,
+    DockerRemoveVolumeCommand,
+};
+use crate::lifecycle::CreatorCommand;
 use crate::pack::PackBuildCommand;
 use crate::util::CommandError;
-use crate::{app, build, util, BuildConfig, BuildpackReference, PackResult, TestContext};
+use crate::{
+    app, build, lifecycle, pack, util, BuildConfig, BuildTool, BuildpackReference, PackResult,
+    TestContext,
+};
+use libcnb_common::docker_credentials::resolve_docker_credentials;
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
 use std::env;
-use std::path::PathBuf;
-use tempfile::tempdir;
+use std::path::{Path, PathBuf};
+use tempfile::{tempdir, TempDir};
 
 /// Runner for libcnb integration tests.
 ///
@@ -102,54 +111,65 @@ impl TestRunner {
         let buildpacks_target_dir =
             tempdir().expect("Error creating temporary directory for compiled buildpacks");
 
-        let mut pack_command = PackBuildCommand::new(
-            &config.builder_name,
-            &app_dir,
-            &docker_resources.image_name,
-            &docker_resources.build_cache_volume_name,
-            &docker_resources.launch_cache_volume_name,
-        );
+        let buildpacks =
+            package_buildpacks(config, &cargo_manifest_dir, buildpacks_target_dir.path());
 
-        config.env.iter().for_each(|(key, value)| {
-            pack_command.env(key, value);
-        });
-
-        for buildpack in &config.buildpacks {
-            match buildpack {
-                BuildpackReference::CurrentCrate => {
-                    let crate_buildpack_dir = build::package_crate_buildpack(
-                        config.cargo_profile,
-                        &config.target_triple,
-                        &cargo_manifest_dir,
-                        buildpacks_target_dir.path(),
-                    )
-                    .unwrap_or_else(|error| {
-                        panic!("Error packaging current crate as buildpack: {error}")
-                    });
-                    pack_command.buildpack(crate_buildpack_dir);
+        let mut env = config
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        let pack_result = match &config.build_tool {
+            BuildTool::Pack => {
+                let mut pack_command = PackBuildCommand::new(
+                    &config.builder_name,
+                    &app_dir,
+                    &docker_resources.image_name,
+                    &docker_resources.build_cache_volume_name,
+                    &docker_resources.launch_cache_volume_name,
+                );
+
+                for (key, value) in &env {
+                    pack_command.env(key, value);
                 }
 
-                BuildpackReference::WorkspaceBuildpack(buildpack_id) => {
-                    let buildpack_dir = build::package_buildpack(
-                        buildpack_id,
-                        config.cargo_profile,
-                        &config.target_triple,
-                        &cargo_manifest_dir,
-                        buildpacks_target_dir.path(),
-                    )
-                    .unwrap_or_else(|error| {
-                        panic!("Error packaging buildpack '{buildpack_id}': {error}")
-                    });
-                    pack_command.buildpack(buildpack_dir);
+                if !config.registry_credentials.is_empty() {
+                    pack_command.secret_env(
+                        "CNB_REGISTRY_AUTH",
+                        registry_auth(&config.registry_credentials),
+                    );
                 }
 
-                BuildpackReference::Other(id) => {
-                    pack_command.buildpack(id.clone());
+                for buildpack in buildpacks {
+                    pack_command.buildpack(buildpack);
                 }
-            };
-        }
 
-        let pack_result = util::run_command(pack_command);
+                util::run_command(pack_command)
+            }
+
+            BuildTool::Lifecycle { lifecycle_image } => {
+                // Unlike pack, the lifecycle only reads platform environment variables from
+                // files, so there is no need to keep the credentials out of the arguments.
+                if !config.registry_credentials.is_empty() {
+                    env.insert(
+                        String::from("CNB_REGISTRY_AUTH"),
+                        registry_auth(&config.registry_credentials),
+                    );
+                }
+
+                let (creator_command, _staging_dir) = prepare_lifecycle_build(
+                    config,
+                    app_dir.as_path(),
+                    &docker_resources,
+                    &buildpacks,
+                    &env,
+                    lifecycle_image.as_deref(),
+                );
+
+                util::run_command(creator_command)
+            }
+        };
 
         let output = match (&config.expected_pack_result, pack_result) {
             (PackResult::Success, Ok(output)) => output,
@@ -196,3 +216,112 @@ impl Drop for TemporaryDockerResources {
         ]));
     }
 }
+
+// Packages the buildpacks of the Cargo workspace and resolves the other buildpacks of the config.
+fn package_buildpacks(
+    config: &BuildConfig,
+    cargo_manifest_dir: &Path,
+    buildpacks_target_dir: &Path,
+) -> Vec<pack::BuildpackReference> {
+    config
+        .buildpacks
+        .iter()
+        .map(|buildpack| match buildpack {
+            BuildpackReference::CurrentCrate => build::package_crate_buildpack(
+                config.cargo_profile,
+                &config.target_triple,
+                cargo_manifest_dir,
+                buildpacks_target_dir,
+            )
+            .unwrap_or_else(|error| panic!("Error packaging current crate as buildpack: {error}"))
+            .into(),
+
+            BuildpackReference::WorkspaceBuildpack(buildpack_id) => build::package_buildpack(
+                buildpack_id,
+                config.cargo_profile,
+                &config.target_triple,
+                cargo_manifest_dir,
+                buildpacks_target_dir,
+            )
+            .unwrap_or_else(|error| panic!("Error packaging buildpack '{buildpack_id}': {error}"))
+            .into(),
+
+            BuildpackReference::Other(id) => id.clone().into(),
+        })
+        .collect()
+}
+
+// Prepares running the lifecycle's `creator` in a container of the builder image. The returned
+// staging directory has to be kept until the build has finished.
+fn prepare_lifecycle_build(
+    config: &BuildConfig,
+    app_dir: &Path,
+    docker_resources: &TemporaryDockerResources,
+    buildpacks: &[pack::BuildpackReference],
+    env: &BTreeMap<String, String>,
+    lifecycle_image: Option<&str>,
+) -> (CreatorCommand, TempDir) {
+    let builder_metadata = util::run_command(DockerImageInspectCommand::new(&config.builder_name))
+        .or_else(|_| {
+            util::run_command(DockerPullCommand::new(&config.builder_name)).and_then(|_| {
+                util::run_command(DockerImageInspectCommand::new(&config.builder_name))
+            })
+        })
+        .map_err(|command_err| format!("Error inspecting builder image:\n\n{command_err}"))
+        .and_then(|output| lifecycle::builder_metadata(&output.stdout))
+        .unwrap_or_else(|error| panic!("Error reading builder metadata: {error}"));
+
+    // `creator` only pulls the run image if it isn't available in the daemon, but fails if the
+    // image neither exists locally nor can be pulled anonymously.
+    if util::run_command(DockerImageInspectCommand::new(&builder_metadata.run_image)).is_err() {
+        util::run_command(DockerPullCommand::new(&builder_metadata.run_image))
+            .unwrap_or_else(|command_err| panic!("Error pulling run image:\n\n{command_err}"));
+    }
+
+    let staging_dir =
+        tempdir().expect("Error creating temporary directory for lifecycle build files");
+
+    let buildpack_dirs = lifecycle::stage_build(
+        staging_dir.path(),
+        buildpacks,
+        &builder_metadata,
+        env,
+        lifecycle_image,
+    )
+    .unwrap_or_else(|error| panic!("Error preparing lifecycle build: {error}"));
+
+    let mut creator_command = CreatorCommand::new(
+        &config.builder_name,
+        app_dir,
+        &docker_resources.image_name,
+        &docker_resources.build_cache_volume_name,
+        &docker_resources.launch_cache_volume_name,
+        &builder_metadata.run_image,
+        staging_dir.path(),
+    );
+    creator_command.buildpack_dirs(buildpack_dirs);
+
+    (creator_command, staging_dir)
+}
+
+// Builds a `CNB_REGISTRY_AUTH` value, which maps registries to `Authorization` header values.
+fn registry_auth(registries: &[String]) -> String {
+    let registry_auth = registries
+        .iter()
+        .map(|registry| {
+            let credentials = resolve_docker_credentials(registry)
+                .unwrap_or_else(|error| {
+                    panic!("Error resolving Docker credentials for registry '{registry}': {error}")
+                })
+                .unwrap_or_else(|| {
+                    panic!(
+                        "No Docker credentials for registry '{registry}' found. Log in with `docker login {registry}`."
+                    )
+                });
+
+            (registry.clone(), credentials.basic_authorization())
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    serde_json::to_string(&registry_auth).expect("Error serializing CNB_REGISTRY_AUTH")
+}
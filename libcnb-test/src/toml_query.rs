@@ -0,0 +1,230 @@
+use std::str::FromStr;
+
+/// A TOML document that can be queried with paths such as `processes[0].command[0]`.
+///
+/// Paths consist of keys separated by dots and array indices in brackets. Keys that contain dots
+/// or brackets can be quoted in brackets, such as `labels["org.example.version"]`.
+///
+/// Querying values with paths keeps assertions focused on the values a test cares about, instead
+/// of comparing whole files that change whenever an unrelated value changes.
+///
+/// # Example
+/// ```
+/// use libcnb_test::TomlQuery;
+///
+/// let launch_toml: TomlQuery = r#"
+///     [[processes]]
+///     type = "web"
+///     command = ["bundle", "exec", "puma"]
+/// "#
+/// .parse()
+/// .unwrap();
+///
+/// launch_toml.assert_toml_eq("processes[0].type", "web");
+/// launch_toml.assert_toml_eq("processes[0].command[0]", "bundle");
+/// assert!(launch_toml.get("processes[1]").is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TomlQuery {
+    value: toml::Value,
+}
+
+impl TomlQuery {
+    #[must_use]
+    pub fn new(value: toml::Value) -> Self {
+        Self { value }
+    }
+
+    /// The whole document.
+    #[must_use]
+    pub fn value(&self) -> &toml::Value {
+        &self.value
+    }
+
+    /// Returns the value at the given path, or `None` if there is no value at that path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path is invalid.
+    #[must_use]
+    pub fn get(&self, path: &str) -> Option<&toml::Value> {
+        parse_path(path)
+            .unwrap_or_else(|error| panic!("Invalid TOML path `{path}`: {error}"))
+            .iter()
+            .try_fold(&self.value, |value, segment| match segment {
+                PathSegment::Key(key) => value.get(key),
+                PathSegment::Index(index) => value.get(index),
+            })
+    }
+
+    /// Asserts that the value at the given path equals the expected value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path is invalid, or the value differs or doesn't exist.
+    pub fn assert_toml_eq(&self, path: &str, expected: impl Into<toml::Value>) {
+        let expected = expected.into();
+
+        match self.get(path) {
+            Some(actual) => assert!(
+                actual == &expected,
+                "TOML value at `{path}` differs\n\nexpected: {expected}\n  actual: {actual}"
+            ),
+            None => panic!(
+                "TOML document has no value at `{path}`\n\nexpected: {expected}\n\ndocument:\n{}",
+                self.value
+            ),
+        }
+    }
+}
+
+impl From<toml::Value> for TomlQuery {
+    fn from(value: toml::Value) -> Self {
+        Self::new(value)
+    }
+}
+
+impl FromStr for TomlQuery {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str::<toml::Table>(s).map(|table| Self::new(toml::Value::Table(table)))
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+fn parse_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+
+    loop {
+        let mut key = String::new();
+        while let Some(char) = chars.next_if(|char| *char != '.' && *char != '[') {
+            key.push(char);
+        }
+
+        if !key.is_empty() {
+            segments.push(PathSegment::Key(key));
+        } else if !segments.is_empty() || chars.peek() != Some(&'[') {
+            return Err(String::from("expected a key"));
+        }
+
+        while chars.next_if_eq(&'[').is_some() {
+            segments.push(parse_bracket_segment(&mut chars)?);
+        }
+
+        match chars.next() {
+            None => return Ok(segments),
+            Some('.') => {}
+            Some(char) => return Err(format!("unexpected `{char}`")),
+        }
+    }
+}
+
+// Parses the segment after an opening bracket, up to and including the closing bracket.
+fn parse_bracket_segment(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+) -> Result<PathSegment, String> {
+    let segment = if chars.next_if_eq(&'"').is_some() {
+        let mut key = String::new();
+        loop {
+            match chars.next() {
+                Some('"') => break,
+                Some('\\') => key.push(chars.next().ok_or("unterminated quoted key")?),
+                Some(char) => key.push(char),
+                None => return Err(String::from("unterminated quoted key")),
+            }
+        }
+        PathSegment::Key(key)
+    } else {
+        let mut index = String::new();
+        while let Some(char) = chars.next_if(char::is_ascii_digit) {
+            index.push(char);
+        }
+        PathSegment::Index(
+            index
+                .parse()
+                .map_err(|_| String::from("expected an array index or a quoted key"))?,
+        )
+    };
+
+    chars
+        .next_if_eq(&']')
+        .map(|_| segment)
+        .ok_or_else(|| String::from("expected `]`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_paths() {
+        assert_eq!(
+            parse_path("processes[0].command[12]"),
+            Ok(vec![
+                PathSegment::Key(String::from("processes")),
+                PathSegment::Index(0),
+                PathSegment::Key(String::from("command")),
+                PathSegment::Index(12),
+            ])
+        );
+        assert_eq!(
+            parse_path(r#"labels["org.example.\"version\""][0]"#),
+            Ok(vec![
+                PathSegment::Key(String::from("labels")),
+                PathSegment::Key(String::from("org.example.\"version\"")),
+                PathSegment::Index(0),
+            ])
+        );
+        assert_eq!(parse_path("[1]"), Ok(vec![PathSegment::Index(1)]));
+
+        assert_eq!(parse_path(""), Err(String::from("expected a key")));
+        assert_eq!(parse_path("a..b"), Err(String::from("expected a key")));
+        assert_eq!(parse_path("a."), Err(String::from("expected a key")));
+        assert_eq!(parse_path("a[0]b"), Err(String::from("unexpected `b`")));
+        assert_eq!(
+            parse_path("a[x]"),
+            Err(String::from("expected an array index or a quoted key"))
+        );
+        assert_eq!(parse_path("a[0"), Err(String::from("expected `]`")));
+        assert_eq!(
+            parse_path(r#"a["b]"#),
+            Err(String::from("unterminated quoted key"))
+        );
+    }
+
+    #[test]
+    fn query_values() {
+        let query: TomlQuery = r#"
+            [[processes]]
+            type = "web"
+            command = ["bundle", "exec"]
+            default = true
+
+            [metadata]
+            "ruby.version" = "3.3.1"
+        "#
+        .parse()
+        .unwrap();
+
+        query.assert_toml_eq("processes[0].command[1]", "exec");
+        query.assert_toml_eq("processes[0].default", true);
+        query.assert_toml_eq("processes[0].command", vec!["bundle", "exec"]);
+        query.assert_toml_eq(r#"metadata["ruby.version"]"#, "3.3.1");
+        assert_eq!(query.get("processes[1]"), None);
+        assert_eq!(query.get("metadata.ruby"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "TOML value at `processes[0].type` differs")]
+    fn assert_toml_eq_different_value() {
+        let query: TomlQuery = "[[processes]]\ntype = \"web\"".parse().unwrap();
+        query.assert_toml_eq("processes[0].type", "worker");
+    }
+}
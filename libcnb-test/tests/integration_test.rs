@@ -11,7 +11,7 @@
 use indoc::{formatdoc, indoc};
 use libcnb_data::buildpack_id;
 use libcnb_test::{
-    assert_contains, assert_empty, assert_not_contains, BuildConfig, BuildpackReference,
+    assert_contains, assert_empty, assert_not_contains, BuildConfig, BuildTool, BuildpackReference,
     ContainerConfig, PackResult, TestRunner,
 };
 use std::path::PathBuf;
@@ -44,6 +44,27 @@ fn build_other_buildpack() {
     );
 }
 
+#[test]
+#[ignore = "integration test"]
+fn build_with_lifecycle() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))])
+            .build_tool(BuildTool::Lifecycle {
+                lifecycle_image: None,
+            }),
+        |context| {
+            assert_contains!(
+                context.pack_stdout,
+                indoc! {"
+                    [Discovering process types]
+                    Procfile declares types -> web, worker
+                "}
+            );
+        },
+    );
+}
+
 #[test]
 #[ignore = "integration test"]
 fn build_workspace_component_buildpack() {
@@ -713,3 +734,18 @@ fn address_for_port_when_container_crashed() {
         "}
     );
 }
+
+#[test]
+#[ignore = "integration test"]
+fn launch_metadata() {
+    TestRunner::default().build(
+        BuildConfig::new("heroku/builder:22", "tests/fixtures/procfile")
+            .buildpacks([BuildpackReference::Other(String::from(PROCFILE_URL))]),
+        |context| {
+            let launch_metadata = context.launch_metadata();
+            launch_metadata.assert_toml_eq("processes[0].type", "web");
+            launch_metadata.assert_toml_eq("processes[0].buildpack-id", PROCFILE_URL);
+            launch_metadata.assert_toml_eq("processes[1].type", "worker");
+        },
+    );
+}
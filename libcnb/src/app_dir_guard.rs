@@ -0,0 +1,339 @@
+use crate::{Error, Platform, Warnings};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Write};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The environment variable that enables the app directory guard, either in the platform env
+/// (`<platform>/env`) or in the process environment.
+///
+/// With `warn`, libcnb adds a warning listing the files that the buildpack added, modified or
+/// removed in the app directory during build. With `error`, a build that changed the app directory
+/// fails with [`Error::AppDirModified`](crate::Error::AppDirModified). The guard is disabled by
+/// default, since scanning large app directories takes time.
+///
+/// Some platforms treat the app directory as read-only, and buildpacks that only write to layers
+/// build the same app the same way every time. Enabling the guard in integration tests catches
+/// accidental writes, for example by tools that write caches next to the app's dependency files.
+pub const APP_DIR_GUARD_ENV_VAR: &str = "LIBCNB_APP_DIR_GUARD";
+
+/// How libcnb reacts to changes of the app directory, see [`APP_DIR_GUARD_ENV_VAR`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AppDirGuardMode {
+    Warn,
+    Error,
+}
+
+impl AppDirGuardMode {
+    pub(crate) fn from_env(platform: &impl Platform) -> Option<Self> {
+        platform
+            .env_var(APP_DIR_GUARD_ENV_VAR)
+            .or_else(|| std::env::var(APP_DIR_GUARD_ENV_VAR).ok())
+            .and_then(|value| match value.trim() {
+                "warn" => Some(Self::Warn),
+                "error" => Some(Self::Error),
+                _ => None,
+            })
+    }
+}
+
+/// A change of a file, directory or symlink in the app directory. Paths are relative to the app
+/// directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AppDirChange {
+    Added(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+}
+
+impl Display for AppDirChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppDirChange::Added(path) => write!(f, "added {}", path.display()),
+            AppDirChange::Modified(path) => write!(f, "modified {}", path.display()),
+            AppDirChange::Removed(path) => write!(f, "removed {}", path.display()),
+        }
+    }
+}
+
+/// The state of all files, directories and symlinks in an app directory, to find out which of
+/// them changed between two points in time.
+///
+/// Files are compared by their size and modification time, symlinks by their target and
+/// directories only by their existence, since their modification time changes whenever an entry
+/// is added or removed.
+///
+/// # Examples
+/// ```
+/// use libcnb::{AppDirChange, AppDirSnapshot};
+/// use std::path::PathBuf;
+///
+/// let app_dir = tempfile::tempdir().unwrap();
+/// let before = AppDirSnapshot::capture(app_dir.path()).unwrap();
+///
+/// std::fs::write(app_dir.path().join("Gemfile.lock"), "").unwrap();
+///
+/// let after = AppDirSnapshot::capture(app_dir.path()).unwrap();
+/// assert_eq!(
+///     before.changes(&after),
+///     vec![AppDirChange::Added(PathBuf::from("Gemfile.lock"))]
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppDirSnapshot {
+    entries: BTreeMap<PathBuf, EntryState>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum EntryState {
+    Directory,
+    File {
+        len: u64,
+        modified: Option<SystemTime>,
+    },
+    Symlink(PathBuf),
+}
+
+impl AppDirSnapshot {
+    /// Captures the state of all entries in the given app directory. Symlinks aren't followed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a directory or the metadata of an entry couldn't be read.
+    pub fn capture(app_dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut entries = BTreeMap::new();
+        capture_dir(app_dir.as_ref(), Path::new(""), &mut entries)?;
+        Ok(Self { entries })
+    }
+
+    /// The changes from this snapshot to a later one, sorted by path.
+    #[must_use]
+    pub fn changes(&self, later: &AppDirSnapshot) -> Vec<AppDirChange> {
+        let mut changes = Vec::new();
+
+        for (path, state) in &self.entries {
+            match later.entries.get(path) {
+                None => changes.push(AppDirChange::Removed(path.clone())),
+                Some(later_state) if later_state != state => {
+                    changes.push(AppDirChange::Modified(path.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for path in later.entries.keys() {
+            if !self.entries.contains_key(path) {
+                changes.push(AppDirChange::Added(path.clone()));
+            }
+        }
+
+        changes.sort_by(|a, b| change_path(a).cmp(change_path(b)));
+        changes
+    }
+}
+
+fn change_path(change: &AppDirChange) -> &Path {
+    match change {
+        AppDirChange::Added(path) | AppDirChange::Modified(path) | AppDirChange::Removed(path) => {
+            path
+        }
+    }
+}
+
+fn capture_dir(
+    dir: &Path,
+    relative_dir: &Path,
+    entries: &mut BTreeMap<PathBuf, EntryState>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let relative_path = relative_dir.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        let state = if file_type.is_symlink() {
+            EntryState::Symlink(fs::read_link(entry.path())?)
+        } else if file_type.is_dir() {
+            capture_dir(&entry.path(), &relative_path, entries)?;
+            EntryState::Directory
+        } else {
+            let metadata = entry.metadata()?;
+            EntryState::File {
+                len: metadata.len(),
+                modified: metadata.modified().ok(),
+            }
+        };
+
+        entries.insert(relative_path, state);
+    }
+
+    Ok(())
+}
+
+/// Checks whether the app directory changed between the start of the guard and the end of the
+/// build, see [`APP_DIR_GUARD_ENV_VAR`].
+pub(crate) struct AppDirGuard {
+    mode: AppDirGuardMode,
+    app_dir: PathBuf,
+    snapshot: AppDirSnapshot,
+}
+
+impl AppDirGuard {
+    /// Captures the app directory if the guard is enabled.
+    pub(crate) fn start<E>(
+        platform: &impl Platform,
+        app_dir: &Path,
+    ) -> crate::Result<Option<Self>, E> {
+        AppDirGuardMode::from_env(platform)
+            .map(|mode| {
+                AppDirSnapshot::capture(app_dir)
+                    .map(|snapshot| Self {
+                        mode,
+                        app_dir: app_dir.to_path_buf(),
+                        snapshot,
+                    })
+                    .map_err(Error::CannotReadAppDir)
+            })
+            .transpose()
+    }
+
+    /// Adds a warning or returns an error, depending on the mode, if the app directory changed.
+    pub(crate) fn check<E>(self, warnings: &Warnings) -> crate::Result<(), E> {
+        let later = AppDirSnapshot::capture(&self.app_dir).map_err(Error::CannotReadAppDir)?;
+        let changes = self.snapshot.changes(&later);
+
+        if changes.is_empty() {
+            Ok(())
+        } else {
+            match self.mode {
+                AppDirGuardMode::Warn => {
+                    warnings.push(app_dir_changes_warning(&changes));
+                    Ok(())
+                }
+                AppDirGuardMode::Error => Err(Error::AppDirModified(changes)),
+            }
+        }
+    }
+}
+
+/// Formats the changes of the app directory as a warning, listing at most ten paths.
+pub(crate) fn app_dir_changes_warning(changes: &[AppDirChange]) -> String {
+    const MAX_LISTED_CHANGES: usize = 10;
+
+    let mut warning = String::from(
+        "The buildpack changed the app directory, which some platforms treat as read-only:",
+    );
+
+    for change in changes.iter().take(MAX_LISTED_CHANGES) {
+        warning.push_str("\n- ");
+        warning.push_str(&change.to_string());
+    }
+
+    if changes.len() > MAX_LISTED_CHANGES {
+        let _ = write!(
+            warning,
+            "\n- and {} more",
+            changes.len() - MAX_LISTED_CHANGES
+        );
+    }
+
+    warning
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generic::GenericPlatform;
+    use crate::Env;
+
+    #[test]
+    fn snapshot_changes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let app_dir = temp_dir.path();
+        fs::create_dir(app_dir.join("config")).unwrap();
+        fs::write(app_dir.join("config").join("app.yml"), "a").unwrap();
+        fs::write(app_dir.join("Gemfile"), "gem 'rails'").unwrap();
+        fs::write(app_dir.join("README"), "").unwrap();
+
+        let before = AppDirSnapshot::capture(app_dir).unwrap();
+        assert_eq!(before.changes(&before), Vec::new());
+
+        fs::write(app_dir.join("config").join("app.yml"), "changed").unwrap();
+        fs::remove_file(app_dir.join("README")).unwrap();
+        fs::create_dir(app_dir.join("vendor")).unwrap();
+        fs::write(app_dir.join("vendor").join("cache"), "").unwrap();
+
+        let after = AppDirSnapshot::capture(app_dir).unwrap();
+        assert_eq!(
+            before.changes(&after),
+            vec![
+                AppDirChange::Removed(PathBuf::from("README")),
+                AppDirChange::Modified(PathBuf::from("config/app.yml")),
+                AppDirChange::Added(PathBuf::from("vendor")),
+                AppDirChange::Added(PathBuf::from("vendor/cache")),
+            ]
+        );
+    }
+
+    #[test]
+    fn mode_from_env() {
+        let mode = |value: Option<&str>| {
+            let mut env = Env::new();
+            if let Some(value) = value {
+                env.insert(APP_DIR_GUARD_ENV_VAR, value);
+            }
+            AppDirGuardMode::from_env(&GenericPlatform::new(env))
+        };
+
+        assert_eq!(mode(None), None);
+        assert_eq!(mode(Some("warn")), Some(AppDirGuardMode::Warn));
+        assert_eq!(mode(Some("error\n")), Some(AppDirGuardMode::Error));
+        assert_eq!(mode(Some("true")), None);
+    }
+
+    #[test]
+    fn guard_check() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let platform =
+            |mode: &str| GenericPlatform::new(Env::new().with(APP_DIR_GUARD_ENV_VAR, mode));
+
+        let warnings = Warnings::new();
+        let guard = AppDirGuard::start::<()>(&platform("warn"), temp_dir.path())
+            .unwrap()
+            .unwrap();
+        fs::write(temp_dir.path().join("a"), "").unwrap();
+        assert!(guard.check::<()>(&warnings).is_ok());
+        assert_eq!(
+            warnings.to_vec(),
+            vec![String::from("The buildpack changed the app directory, which some platforms treat as read-only:\n- added a")]
+        );
+
+        let guard = AppDirGuard::start::<()>(&platform("error"), temp_dir.path())
+            .unwrap()
+            .unwrap();
+        assert!(guard.check::<()>(&warnings).is_ok());
+
+        let guard = AppDirGuard::start::<()>(&platform("error"), temp_dir.path())
+            .unwrap()
+            .unwrap();
+        fs::remove_file(temp_dir.path().join("a")).unwrap();
+        assert!(matches!(
+            guard.check::<()>(&warnings),
+            Err(Error::AppDirModified(changes)) if changes == vec![AppDirChange::Removed(PathBuf::from("a"))]
+        ));
+    }
+
+    #[test]
+    fn changes_warning() {
+        let changes = (0..12)
+            .map(|index| AppDirChange::Added(PathBuf::from(format!("file-{index:02}"))))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            app_dir_changes_warning(&changes[..1]),
+            "The buildpack changed the app directory, which some platforms treat as read-only:\n- added file-00"
+        );
+        assert!(app_dir_changes_warning(&changes).ends_with("- added file-09\n- and 2 more"));
+    }
+}
@@ -0,0 +1,433 @@
+//! Benchmarks of the build phase of a buildpack.
+//!
+//! Requires the `testing` feature, which is intended to be enabled for `dev-dependencies` only.
+
+use crate::layer::testing::restore_layers;
+use crate::testing::BuildContextBuilder;
+use crate::Buildpack;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Whether the layers of a previous build are available to a benchmarked build.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CacheScenario {
+    /// Every build starts without any layers, like the first build of an app.
+    #[default]
+    Cold,
+    /// Every build starts with the cached layers of a previous build, like a subsequent build of
+    /// an app. The previous build is not measured.
+    Warm,
+}
+
+impl Display for CacheScenario {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CacheScenario::Cold => "cold cache",
+            CacheScenario::Warm => "warm cache",
+        })
+    }
+}
+
+/// Runs [`Buildpack::build`] repeatedly against contexts from a [`BuildContextBuilder`] and
+/// measures the total duration of each build and the durations recorded in its
+/// [`Timings`](crate::Timings), such as the time spent on each layer.
+///
+/// Every build runs in a new temporary directory. The app directory is shared between builds if
+/// it was set with [`BuildContextBuilder::app_dir`], so builds should not modify it.
+///
+/// [`BuildBenchmark::measure`] has the signature expected by `Bencher::iter_custom` of the
+/// [criterion](https://docs.rs/criterion) crate, so that builds can be benchmarked with criterion:
+///
+/// ```ignore
+/// c.bench_function("build (warm cache)", |bencher| {
+///     let benchmark = BuildBenchmark::new(RubyBuildpack, || {
+///         BuildContextBuilder::new().app_dir("tests/fixtures/rails-app")
+///     })
+///     .cache(CacheScenario::Warm);
+///
+///     bencher.iter_custom(|iterations| benchmark.measure(iterations));
+/// });
+/// ```
+///
+/// Without criterion, [`BuildBenchmark::run`] returns a [`BenchmarkReport`] with the minimum, mean
+/// and maximum durations:
+///
+/// ```no_run
+/// use libcnb::benchmark::{BuildBenchmark, CacheScenario};
+/// use libcnb::testing::BuildContextBuilder;
+/// # use libcnb::build::{BuildContext, BuildResult};
+/// # use libcnb::detect::{DetectContext, DetectResult};
+/// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+///
+/// # struct RubyBuildpack;
+/// #
+/// # impl libcnb::Buildpack for RubyBuildpack {
+/// #     type Platform = GenericPlatform;
+/// #     type Metadata = GenericMetadata;
+/// #     type Error = GenericError;
+/// #
+/// #     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+/// #         unimplemented!()
+/// #     }
+/// #
+/// #     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+/// #         unimplemented!()
+/// #     }
+/// # }
+/// #
+/// let report = BuildBenchmark::new(RubyBuildpack, || {
+///     BuildContextBuilder::new().app_dir("tests/fixtures/rails-app")
+/// })
+/// .cache(CacheScenario::Warm)
+/// .run(10);
+///
+/// println!("{report}");
+/// ```
+pub struct BuildBenchmark<B, F> {
+    buildpack: B,
+    context: F,
+    cache: CacheScenario,
+}
+
+impl<B, F> BuildBenchmark<B, F>
+where
+    B: Buildpack,
+    F: Fn() -> BuildContextBuilder<B>,
+{
+    /// Creates a benchmark of the given buildpack. The function is called for every build to
+    /// configure its context.
+    pub fn new(buildpack: B, context: F) -> Self {
+        Self {
+            buildpack,
+            context,
+            cache: CacheScenario::default(),
+        }
+    }
+
+    /// Sets the cache scenario, which defaults to [`CacheScenario::Cold`].
+    #[must_use]
+    pub fn cache(mut self, cache: CacheScenario) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Runs a single build and returns its measurement.
+    ///
+    /// # Panics
+    ///
+    /// If the temporary directory or the context can't be created, or if a build fails.
+    #[must_use]
+    pub fn run_once(&self) -> BuildMeasurement {
+        let temp_dir = tempfile::tempdir().expect("Couldn't create temporary directory");
+
+        if self.cache == CacheScenario::Warm {
+            self.build_in(temp_dir.path());
+            restore_layers(&temp_dir.path().join("layers"));
+        }
+
+        self.build_in(temp_dir.path())
+    }
+
+    /// Runs the given number of builds and returns a report of their measurements.
+    ///
+    /// # Panics
+    ///
+    /// If the number of iterations is zero, or for the same reasons as
+    /// [`BuildBenchmark::run_once`].
+    #[must_use]
+    pub fn run(&self, iterations: u32) -> BenchmarkReport {
+        assert!(iterations > 0, "At least one iteration is required");
+
+        let measurements = (0..iterations).map(|_| self.run_once()).collect::<Vec<_>>();
+
+        BenchmarkReport::new(self.cache, &measurements)
+    }
+
+    /// Runs the given number of builds and returns the sum of their durations, excluding the
+    /// setup of the directories and of the cache.
+    ///
+    /// # Panics
+    ///
+    /// For the same reasons as [`BuildBenchmark::run_once`].
+    #[must_use]
+    pub fn measure(&self, iterations: u64) -> Duration {
+        (0..iterations).map(|_| self.run_once().total).sum()
+    }
+
+    fn build_in(&self, dir: &Path) -> BuildMeasurement {
+        let context = (self.context)().build_in(dir);
+        let timings = context.timings.clone();
+
+        let start = Instant::now();
+        let result = self.buildpack.build(context);
+        let total = start.elapsed();
+
+        if let Err(error) = result {
+            panic!("Build failed: {error:?}");
+        }
+
+        BuildMeasurement {
+            total,
+            timings: timings.to_vec(),
+        }
+    }
+}
+
+/// The durations measured during a single build of a [`BuildBenchmark`].
+#[derive(Clone, Debug)]
+pub struct BuildMeasurement {
+    /// The duration of [`Buildpack::build`].
+    pub total: Duration,
+    /// The durations recorded in the [`Timings`](crate::Timings) of the build, in the order they
+    /// were recorded. libcnb records the time spent on each layer as `layer <name>`.
+    pub timings: Vec<(String, Duration)>,
+}
+
+/// The minimum, mean and maximum of a duration across the builds of a [`BuildBenchmark`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DurationStats {
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl DurationStats {
+    /// Calculates the statistics of the given durations. Returns `None` if there are none.
+    #[must_use]
+    pub fn from_durations(durations: &[Duration]) -> Option<Self> {
+        let count = u32::try_from(durations.len())
+            .ok()
+            .filter(|count| *count > 0)?;
+
+        Some(Self {
+            min: durations.iter().min().copied()?,
+            mean: durations.iter().sum::<Duration>() / count,
+            max: durations.iter().max().copied()?,
+        })
+    }
+}
+
+impl Display for DurationStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {:.2?}, mean {:.2?}, max {:.2?}",
+            self.min, self.mean, self.max
+        )
+    }
+}
+
+/// The result of [`BuildBenchmark::run`].
+#[derive(Clone, Debug)]
+pub struct BenchmarkReport {
+    pub cache: CacheScenario,
+    pub iterations: usize,
+    /// The statistics of the total duration of the builds.
+    pub total: DurationStats,
+    /// The statistics of each named duration, such as `layer ruby`, in the order they were first
+    /// recorded. Durations that were recorded more than once in a build are summed up.
+    pub timings: Vec<(String, DurationStats)>,
+}
+
+impl BenchmarkReport {
+    fn new(cache: CacheScenario, measurements: &[BuildMeasurement]) -> Self {
+        let mut names = Vec::<&str>::new();
+        for (name, _) in measurements
+            .iter()
+            .flat_map(|measurement| &measurement.timings)
+        {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+
+        let timings = names
+            .into_iter()
+            .filter_map(|name| {
+                let durations = measurements
+                    .iter()
+                    .map(|measurement| {
+                        measurement
+                            .timings
+                            .iter()
+                            .filter(|(timing_name, _)| timing_name == name)
+                            .map(|(_, duration)| *duration)
+                            .sum()
+                    })
+                    .collect::<Vec<_>>();
+
+                DurationStats::from_durations(&durations).map(|stats| (String::from(name), stats))
+            })
+            .collect();
+
+        let totals = measurements
+            .iter()
+            .map(|measurement| measurement.total)
+            .collect::<Vec<_>>();
+
+        Self {
+            cache,
+            iterations: measurements.len(),
+            total: DurationStats::from_durations(&totals).unwrap_or(DurationStats {
+                min: Duration::ZERO,
+                mean: Duration::ZERO,
+                max: Duration::ZERO,
+            }),
+            timings,
+        }
+    }
+}
+
+impl Display for BenchmarkReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "build ({}, {} iterations): {}",
+            self.cache, self.iterations, self.total
+        )?;
+
+        for (name, stats) in &self.timings {
+            write!(f, "\n  {name}: {stats}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::{BuildContext, BuildResult, BuildResultBuilder};
+    use crate::data::buildpack::ComponentBuildpackDescriptor;
+    use crate::data::layer_content_metadata::LayerTypes;
+    use crate::data::layer_name;
+    use crate::detect::{DetectContext, DetectResult};
+    use crate::generic::{GenericError, GenericMetadata, GenericPlatform};
+    use crate::layer::{ExistingLayerStrategy, Layer, LayerData, LayerResult, LayerResultBuilder};
+    use std::cell::Cell;
+    use std::fs;
+
+    thread_local! {
+        // How often the layer was created and reused by the builds on the current thread.
+        static CREATED: Cell<u32> = const { Cell::new(0) };
+        static REUSED: Cell<u32> = const { Cell::new(0) };
+    }
+
+    struct TestBuildpack;
+
+    impl Buildpack for TestBuildpack {
+        type Platform = GenericPlatform;
+        type Metadata = GenericMetadata;
+        type Error = GenericError;
+
+        fn detect(
+            &self,
+            _context: DetectContext<Self>,
+        ) -> crate::Result<DetectResult, Self::Error> {
+            unimplemented!()
+        }
+
+        fn build(&self, context: BuildContext<Self>) -> crate::Result<BuildResult, Self::Error> {
+            context.handle_layer(layer_name!("cached"), CachedLayer)?;
+            BuildResultBuilder::new().build()
+        }
+    }
+
+    struct CachedLayer;
+
+    impl Layer for CachedLayer {
+        type Buildpack = TestBuildpack;
+        type Metadata = GenericMetadata;
+
+        fn types(&self) -> LayerTypes {
+            LayerTypes {
+                launch: true,
+                build: false,
+                cache: true,
+            }
+        }
+
+        fn create(
+            &mut self,
+            _context: &BuildContext<TestBuildpack>,
+            layer_path: &Path,
+        ) -> Result<LayerResult<Self::Metadata>, GenericError> {
+            CREATED.set(CREATED.get() + 1);
+            fs::write(layer_path.join("created"), "").unwrap();
+            LayerResultBuilder::new(None).build()
+        }
+
+        fn existing_layer_strategy(
+            &mut self,
+            _context: &BuildContext<TestBuildpack>,
+            _layer_data: &LayerData<Self::Metadata>,
+        ) -> Result<ExistingLayerStrategy, GenericError> {
+            REUSED.set(REUSED.get() + 1);
+            Ok(ExistingLayerStrategy::Keep)
+        }
+    }
+
+    fn context() -> BuildContextBuilder<TestBuildpack> {
+        BuildContextBuilder::new().buildpack_descriptor(
+            toml::from_str::<ComponentBuildpackDescriptor<GenericMetadata>>(
+                r#"
+                api = "0.10"
+
+                [buildpack]
+                id = "libcnb/test"
+                version = "1.0.0"
+                "#,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn cold_and_warm_cache() {
+        let cold = BuildBenchmark::new(TestBuildpack, context).run(2);
+
+        assert_eq!(cold.iterations, 2);
+        assert_eq!(cold.timings[0].0, "layer cached");
+        assert_eq!((CREATED.get(), REUSED.get()), (2, 0));
+
+        // Each warm build is preceded by a build that creates the layer, which the measured build
+        // then reuses.
+        let warm = BuildBenchmark::new(TestBuildpack, context)
+            .cache(CacheScenario::Warm)
+            .run(2);
+
+        assert_eq!(warm.iterations, 2);
+        assert_eq!(warm.timings[0].0, "layer cached");
+        assert_eq!((CREATED.get(), REUSED.get()), (4, 2));
+        assert!(warm
+            .to_string()
+            .starts_with("build (warm cache, 2 iterations): min "));
+
+        assert_eq!(
+            BuildBenchmark::new(TestBuildpack, context)
+                .cache(CacheScenario::Warm)
+                .run_once()
+                .timings
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn duration_stats() {
+        assert_eq!(
+            DurationStats::from_durations(&[
+                Duration::from_millis(10),
+                Duration::from_millis(30),
+                Duration::from_millis(20)
+            ]),
+            Some(DurationStats {
+                min: Duration::from_millis(10),
+                mean: Duration::from_millis(20),
+                max: Duration::from_millis(30),
+            })
+        );
+        assert_eq!(DurationStats::from_durations(&[]), None);
+    }
+}
@@ -0,0 +1,411 @@
+//! Provides types and helpers to read service bindings.
+//!
+//! Service bindings are a way for platforms to provide credentials and configuration for external
+//! services (databases, APM agents, etc.) to buildpacks. Each binding is a directory that contains
+//! a `type` file, an optional `provider` file and any number of additional secret entries, one per
+//! file. See the [Service Binding Specification for Kubernetes](https://github.com/servicebinding/spec#workload-projection)
+//! and the [CNB platform specification](https://github.com/buildpacks/spec/blob/main/platform.md#bindings)
+//! for details.
+
+use crate::util::is_not_found_error_kind;
+use crate::Env;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A collection of service bindings.
+///
+/// # Example
+/// ```no_run
+/// use libcnb::bindings::Bindings;
+///
+/// let bindings = Bindings::discover("/platform").unwrap();
+///
+/// let username = bindings
+///     .of_type("postgresql")
+///     .next()
+///     .and_then(|binding| binding.get_string_lossy("username"));
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Bindings {
+    inner: Vec<Binding>,
+}
+
+impl Bindings {
+    /// Discovers and reads all bindings available to the current process.
+    ///
+    /// The bindings root directory is determined by the `SERVICE_BINDING_ROOT` environment
+    /// variable, falling back to the legacy `CNB_BINDINGS` environment variable. If neither is set,
+    /// the `bindings` directory in the given platform directory is used.
+    pub fn discover(platform_dir: impl AsRef<Path>) -> Result<Self, BindingsError> {
+        Self::discover_with_env(&Env::from_current(), platform_dir)
+    }
+
+    /// Like [`discover`](Self::discover), but reads the environment variables that determine the
+    /// bindings root directory from the given [`Env`] instead of the current process.
+    pub fn discover_with_env(
+        env: &Env,
+        platform_dir: impl AsRef<Path>,
+    ) -> Result<Self, BindingsError> {
+        Self::read_from_dir(bindings_root(env, platform_dir))
+    }
+
+    /// Reads all bindings from the given bindings root directory.
+    ///
+    /// A missing bindings root directory is not an error, an empty collection will be returned
+    /// instead. Bindings are sorted by name.
+    pub fn read_from_dir(bindings_root: impl AsRef<Path>) -> Result<Self, BindingsError> {
+        let entries = match fs::read_dir(bindings_root.as_ref()) {
+            Ok(entries) => entries,
+            Err(io_error) if is_not_found_error_kind(&io_error) => return Ok(Self::default()),
+            Err(io_error) => return Err(BindingsError::IoError(io_error)),
+        };
+
+        let mut inner = Vec::new();
+
+        for entry in entries {
+            let path = entry?.path();
+
+            // k8s volume mounts add hidden `..data` directories and similar, those are not bindings.
+            if path.is_dir() && !is_hidden(&path) {
+                inner.push(Binding::read_from_dir(path)?);
+            }
+        }
+
+        inner.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self { inner })
+    }
+
+    /// Returns an iterator over all bindings.
+    pub fn iter(&self) -> std::slice::Iter<'_, Binding> {
+        self.inner.iter()
+    }
+
+    /// Returns the binding with the given name, if it exists.
+    #[must_use]
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&Binding> {
+        self.inner
+            .iter()
+            .find(|binding| binding.name == name.as_ref())
+    }
+
+    /// Returns an iterator over all bindings of the given type.
+    ///
+    /// As required by the specification, types are compared case-insensitively.
+    pub fn of_type<'a>(&'a self, r#type: &'a str) -> impl Iterator<Item = &'a Binding> {
+        self.inner
+            .iter()
+            .filter(move |binding| binding.r#type.eq_ignore_ascii_case(r#type))
+    }
+
+    /// Returns an iterator over all bindings of the given type and provider.
+    ///
+    /// As required by the specification, types and providers are compared case-insensitively.
+    pub fn of_type_and_provider<'a>(
+        &'a self,
+        r#type: &'a str,
+        provider: &'a str,
+    ) -> impl Iterator<Item = &'a Binding> {
+        self.of_type(r#type).filter(move |binding| {
+            binding
+                .provider
+                .as_ref()
+                .is_some_and(|binding_provider| binding_provider.eq_ignore_ascii_case(provider))
+        })
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a Bindings {
+    type Item = &'a Binding;
+    type IntoIter = std::slice::Iter<'a, Binding>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A single service binding.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Binding {
+    /// The name of the binding, this is the name of the binding directory.
+    pub name: String,
+    /// The path to the binding directory.
+    pub path: PathBuf,
+    /// The type of the binding, for example `postgresql` or `newrelic`.
+    pub r#type: String,
+    /// The provider of the binding, for example `bitnami`.
+    pub provider: Option<String>,
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl Binding {
+    /// Reads a single binding from the given binding directory.
+    pub fn read_from_dir(binding_dir: impl AsRef<Path>) -> Result<Self, BindingsError> {
+        let binding_dir = binding_dir.as_ref();
+
+        let mut entries = BTreeMap::new();
+        for entry in fs::read_dir(binding_dir)? {
+            let path = entry?.path();
+
+            // k8s volume mounts will mount a directory symlink in, so we need to check
+            // that it's actually a file.
+            if path.is_file() && !is_hidden(&path) {
+                if let Some(file_name) = path.file_name().and_then(OsStr::to_str) {
+                    entries.insert(String::from(file_name), fs::read(&path)?);
+                }
+            }
+        }
+
+        let r#type = entries
+            .remove(TYPE_ENTRY_NAME)
+            .map(|value| trimmed_string(&value))
+            .ok_or_else(|| BindingsError::MissingType(binding_dir.to_path_buf()))?;
+
+        let provider = entries
+            .remove(PROVIDER_ENTRY_NAME)
+            .map(|value| trimmed_string(&value));
+
+        Ok(Self {
+            name: binding_dir
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: binding_dir.to_path_buf(),
+            r#type,
+            provider,
+            entries,
+        })
+    }
+
+    /// Returns the raw value of the secret entry with the given key.
+    ///
+    /// The `type` and `provider` entries are not secret entries, use the corresponding fields
+    /// instead.
+    #[must_use]
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&[u8]> {
+        self.entries.get(key.as_ref()).map(Vec::as_slice)
+    }
+
+    /// Returns the value of the secret entry with the given key, interpreted as Unicode data.
+    ///
+    /// Any non-Unicode sequences are replaced with
+    /// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+    ///
+    /// [U+FFFD]: std::char::REPLACEMENT_CHARACTER
+    #[must_use]
+    pub fn get_string_lossy(&self, key: impl AsRef<str>) -> Option<String> {
+        self.get(key)
+            .map(|value| String::from_utf8_lossy(value).to_string())
+    }
+
+    /// Returns the path of the file that backs the secret entry with the given key.
+    ///
+    /// Useful for tools that expect a path to a file instead of its contents, i.e. certificates.
+    #[must_use]
+    pub fn entry_path(&self, key: impl AsRef<str>) -> Option<PathBuf> {
+        self.entries
+            .contains_key(key.as_ref())
+            .then(|| self.path.join(key.as_ref()))
+    }
+
+    /// Returns true if the binding contains a secret entry with the given key.
+    #[must_use]
+    pub fn contains_key(&self, key: impl AsRef<str>) -> bool {
+        self.entries.contains_key(key.as_ref())
+    }
+
+    /// Returns an iterator over the keys of all secret entries, in sorted order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+/// An error that occurred while reading service bindings.
+#[derive(thiserror::Error, Debug)]
+pub enum BindingsError {
+    #[error("I/O error while reading bindings: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Binding at {0} is missing the required type entry")]
+    MissingType(PathBuf),
+}
+
+/// Determines the bindings root directory based on the given environment and platform directory.
+pub(crate) fn bindings_root(env: &Env, platform_dir: impl AsRef<Path>) -> PathBuf {
+    env.get("SERVICE_BINDING_ROOT")
+        .or_else(|| env.get("CNB_BINDINGS"))
+        .filter(|value| !value.is_empty())
+        .map_or_else(|| platform_dir.as_ref().join("bindings"), PathBuf::from)
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|file_name| file_name.to_string_lossy().starts_with('.'))
+}
+
+fn trimmed_string(value: &[u8]) -> String {
+    String::from_utf8_lossy(value).trim().to_string()
+}
+
+const TYPE_ENTRY_NAME: &str = "type";
+const PROVIDER_ENTRY_NAME: &str = "provider";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_binding(root: &Path, name: &str, entries: &[(&str, &str)]) {
+        let binding_dir = root.join(name);
+        fs::create_dir_all(&binding_dir).unwrap();
+
+        for (key, value) in entries {
+            fs::write(binding_dir.join(key), value).unwrap();
+        }
+    }
+
+    #[test]
+    fn read_from_dir_reads_bindings() {
+        let temp_dir = tempdir().unwrap();
+        write_binding(
+            temp_dir.path(),
+            "primary-db",
+            &[
+                ("type", "postgresql\n"),
+                ("provider", "bitnami"),
+                ("username", "admin"),
+                ("password", "hunter2"),
+            ],
+        );
+        write_binding(
+            temp_dir.path(),
+            "apm",
+            &[("type", "NewRelic"), ("license-key", "abc123")],
+        );
+
+        let bindings = Bindings::read_from_dir(temp_dir.path()).unwrap();
+        assert_eq!(bindings.len(), 2);
+
+        let primary_db = bindings.get("primary-db").unwrap();
+        assert_eq!(primary_db.r#type, "postgresql");
+        assert_eq!(primary_db.provider, Some(String::from("bitnami")));
+        assert_eq!(
+            primary_db.get_string_lossy("username"),
+            Some(String::from("admin"))
+        );
+        assert_eq!(primary_db.get("password"), Some("hunter2".as_bytes()));
+        assert_eq!(primary_db.get("type"), None);
+        assert_eq!(
+            primary_db.keys().collect::<Vec<_>>(),
+            ["password", "username"]
+        );
+        assert_eq!(
+            primary_db.entry_path("password"),
+            Some(temp_dir.path().join("primary-db").join("password"))
+        );
+        assert_eq!(primary_db.entry_path("missing"), None);
+
+        let apm = bindings.get("apm").unwrap();
+        assert_eq!(apm.provider, None);
+        assert!(apm.contains_key("license-key"));
+    }
+
+    #[test]
+    fn of_type_is_case_insensitive() {
+        let temp_dir = tempdir().unwrap();
+        write_binding(temp_dir.path(), "a", &[("type", "PostgreSQL")]);
+        write_binding(
+            temp_dir.path(),
+            "b",
+            &[("type", "postgresql"), ("provider", "Bitnami")],
+        );
+        write_binding(temp_dir.path(), "c", &[("type", "mysql")]);
+
+        let bindings = Bindings::read_from_dir(temp_dir.path()).unwrap();
+
+        assert_eq!(
+            bindings
+                .of_type("postgresql")
+                .map(|binding| binding.name.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+
+        assert_eq!(
+            bindings
+                .of_type_and_provider("POSTGRESQL", "bitnami")
+                .map(|binding| binding.name.as_str())
+                .collect::<Vec<_>>(),
+            ["b"]
+        );
+    }
+
+    #[test]
+    fn read_from_dir_with_missing_root() {
+        let temp_dir = tempdir().unwrap();
+
+        let bindings = Bindings::read_from_dir(temp_dir.path().join("missing")).unwrap();
+        assert!(bindings.is_empty());
+    }
+
+    #[test]
+    fn read_from_dir_with_missing_type() {
+        let temp_dir = tempdir().unwrap();
+        write_binding(temp_dir.path(), "broken", &[("username", "admin")]);
+
+        assert!(matches!(
+            Bindings::read_from_dir(temp_dir.path()),
+            Err(BindingsError::MissingType(path)) if path == temp_dir.path().join("broken")
+        ));
+    }
+
+    #[test]
+    fn read_from_dir_ignores_hidden_entries() {
+        let temp_dir = tempdir().unwrap();
+        write_binding(
+            temp_dir.path(),
+            "db",
+            &[("type", "postgresql"), (".hidden", "secret")],
+        );
+        fs::create_dir_all(temp_dir.path().join("..data")).unwrap();
+
+        let bindings = Bindings::read_from_dir(temp_dir.path()).unwrap();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings.get("db").unwrap().keys().count(), 0);
+    }
+
+    #[test]
+    fn bindings_root_precedence() {
+        let platform_dir = PathBuf::from("/platform");
+
+        assert_eq!(
+            bindings_root(&Env::new(), &platform_dir),
+            PathBuf::from("/platform/bindings")
+        );
+
+        let mut env = Env::new();
+        env.insert("CNB_BINDINGS", "/cnb-bindings");
+        assert_eq!(
+            bindings_root(&env, &platform_dir),
+            PathBuf::from("/cnb-bindings")
+        );
+
+        env.insert("SERVICE_BINDING_ROOT", "/service-bindings");
+        assert_eq!(
+            bindings_root(&env, &platform_dir),
+            PathBuf::from("/service-bindings")
+        );
+    }
+}
@@ -1,15 +1,24 @@
 //! Provides build phase specific types and helpers.
 
 use crate::buildpack::Buildpack;
+use crate::data::buildpack::BuildpackId;
 use crate::data::layer::LayerName;
+use crate::data::lifecycle::{Group, GroupBuildpack};
 use crate::data::store::Store;
 use crate::data::{
     buildpack::ComponentBuildpackDescriptor, buildpack_plan::BuildpackPlan, launch::Launch,
 };
-use crate::layer::{HandleLayerErrorOrBuildpackError, Layer, LayerData};
+use crate::layer::{HandleLayerErrorOrBuildpackError, HandledLayers, Layer, LayerData};
+use crate::platform;
 use crate::sbom::Sbom;
-use crate::Target;
-use std::path::PathBuf;
+use crate::{Deterministic, Target, Timings, Warnings};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub(crate) const LAUNCH_TOML_FILE_NAME: &str = "launch.toml";
+pub(crate) const STORE_TOML_FILE_NAME: &str = "store.toml";
+pub(crate) const BUILD_TOML_FILE_NAME: &str = "build.toml";
+pub(crate) const GROUP_TOML_FILE_NAME: &str = "group.toml";
 
 /// Context for the build phase execution.
 pub struct BuildContext<B: Buildpack + ?Sized> {
@@ -17,13 +26,127 @@ pub struct BuildContext<B: Buildpack + ?Sized> {
     pub app_dir: PathBuf,
     pub buildpack_dir: PathBuf,
     pub target: Target,
+    /// The platform directory, which contains `env/` and any other platform-provided files.
+    pub platform_dir: PathBuf,
     pub platform: B::Platform,
     pub buildpack_plan: BuildpackPlan,
+    /// The path of the buildpack plan file that [`Self::buildpack_plan`] was read from, which the
+    /// lifecycle provides as `CNB_BP_PLAN_PATH`.
+    pub buildpack_plan_path: PathBuf,
     pub buildpack_descriptor: ComponentBuildpackDescriptor<B::Metadata>,
+    /// Non-fatal warnings collected during this phase, see [`Self::warn`].
+    pub warnings: Warnings,
+    /// Durations measured during this phase, see [`Self::time`].
+    pub timings: Timings,
+    /// The layers handled via [`Self::handle_layer`] so far.
+    pub handled_layers: HandledLayers,
     pub store: Option<Store>,
+    /// The settings of the deterministic build mode, if it is enabled via
+    /// [`DETERMINISTIC_ENV_VAR`](crate::DETERMINISTIC_ENV_VAR).
+    pub deterministic: Option<Deterministic>,
+    /// The buildpacks and image extensions that passed detection together with this buildpack, in
+    /// build order, read from the `group.toml` the lifecycle writes next to the layers directories
+    /// of the buildpacks.
+    ///
+    /// The location of `group.toml` isn't part of the buildpack API, so this is `None` if the
+    /// file doesn't exist there or couldn't be read. Buildpacks must keep working without it.
+    pub group: Option<Group>,
 }
 
 impl<B: Buildpack + ?Sized> BuildContext<B> {
+    /// Records a non-fatal warning that is printed in a summary section before the phase exits.
+    ///
+    /// See [`Warnings`] for details.
+    pub fn warn(&self, message: impl Into<String>) {
+        self.warnings.push(message);
+    }
+
+    /// Runs the given function and records the time it took under the given name, to be listed
+    /// in the timing summary.
+    ///
+    /// See [`Timings`] for details.
+    pub fn time<T>(&self, name: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        self.timings.time(name, f)
+    }
+
+    /// Returns whether the given store differs from the store of the previous build.
+    ///
+    /// libcnb only rewrites `store.toml` when the store returned with
+    /// [`BuildResultBuilder::store`] differs from the store of the previous build, so that
+    /// platforms which hash the build output don't see a change. This method allows buildpacks to
+    /// find out whether that is the case, for example to skip expensive work or to log it.
+    #[must_use]
+    pub fn store_changed(&self, store: &Store) -> bool {
+        self.store.as_ref() != Some(store)
+    }
+
+    /// The path `launch.toml` is written to when a [`Launch`] is set on the [`BuildResult`].
+    #[must_use]
+    pub fn launch_toml_path(&self) -> PathBuf {
+        self.layers_dir.join(LAUNCH_TOML_FILE_NAME)
+    }
+
+    /// The path `store.toml` is read from at the start of the build and written to when the
+    /// [`BuildResult`] has a changed store, see [`Self::store_changed`].
+    #[must_use]
+    pub fn store_toml_path(&self) -> PathBuf {
+        self.layers_dir.join(STORE_TOML_FILE_NAME)
+    }
+
+    /// The path of `build.toml`, which libcnb doesn't write itself, for buildpacks that declare
+    /// unmet build plan entries for subsequent buildpacks.
+    #[must_use]
+    pub fn build_toml_path(&self) -> PathBuf {
+        self.layers_dir.join(BUILD_TOML_FILE_NAME)
+    }
+
+    /// Returns the buildpack with the given id from [`Self::group`], which is `None` if the
+    /// buildpack doesn't participate in this build or the group isn't available.
+    ///
+    /// This allows buildpacks to adapt to companion buildpacks without inspecting their layers:
+    ///
+    /// ```no_run
+    /// # fn example<B: libcnb::Buildpack>(context: &libcnb::build::BuildContext<B>) {
+    /// use libcnb::data::buildpack_id;
+    ///
+    /// if context.group_buildpack(&buildpack_id!("heroku/procfile")).is_none() {
+    ///     // Declare a default web process...
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn group_buildpack(&self, id: &BuildpackId) -> Option<&GroupBuildpack> {
+        self.group
+            .as_ref()
+            .and_then(|group| group.group.iter().find(|buildpack| &buildpack.id == id))
+    }
+
+    /// Returns the path of a file or directory relative to the platform directory.
+    #[must_use]
+    pub fn platform_path(&self, relative_path: impl AsRef<Path>) -> PathBuf {
+        platform::platform_path(&self.platform_dir, relative_path)
+    }
+
+    /// Reads a file provided by the platform, relative to the platform directory.
+    ///
+    /// Returns `Ok(None)` if the file does not exist.
+    pub fn read_platform_file(
+        &self,
+        relative_path: impl AsRef<Path>,
+    ) -> io::Result<Option<Vec<u8>>> {
+        platform::read_platform_file(&self.platform_dir, relative_path)
+    }
+
+    /// Reads a file provided by the platform as UTF-8, relative to the platform directory.
+    ///
+    /// Returns `Ok(None)` if the file does not exist.
+    pub fn read_platform_file_to_string(
+        &self,
+        relative_path: impl AsRef<Path>,
+    ) -> io::Result<Option<String>> {
+        platform::read_platform_file_to_string(&self.platform_dir, relative_path)
+    }
+
     /// Handles the given [`Layer`] implementation in this context.
     ///
     /// It will ensure that the layer with the given name is created and/or updated accordingly and
@@ -122,6 +245,16 @@ impl<B: Buildpack + ?Sized> BuildContext<B> {
 #[must_use]
 pub struct BuildResult(pub(crate) InnerBuildResult);
 
+impl BuildResult {
+    /// Returns the launch configuration of this result, if there is one.
+    #[must_use]
+    pub fn launch(&self) -> Option<&Launch> {
+        match &self.0 {
+            InnerBuildResult::Pass { launch, .. } => launch.as_ref(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum InnerBuildResult {
     Pass {
@@ -194,6 +327,10 @@ impl BuildResultBuilder {
         self
     }
 
+    /// Sets the store, which is persisted to subsequent builds.
+    ///
+    /// `store.toml` is only rewritten if the store differs from the store of the previous build,
+    /// see [`BuildContext::store_changed`].
     pub fn store<S: Into<Store>>(mut self, store: S) -> Self {
         self.store = Some(store.into());
         self
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable that, if set, contains the path libcnb appends [`BuildEventRecord`]s to
+/// while detect or build runs. It is read from the platform env, falling back to the process
+/// environment.
+pub const BUILD_EVENTS_PATH_ENV_VAR: &str = "LIBCNB_BUILD_EVENTS_PATH";
+
+/// A notable event during detect or build.
+///
+/// If the [`BUILD_EVENTS_PATH_ENV_VAR`] environment variable is set, libcnb appends each event as
+/// a line of JSON (a [`BuildEventRecord`]) to the file at that path. This allows CI systems to
+/// render structured build timelines without parsing the build log. Since the file is appended
+/// to, all buildpacks of a build can share the same file.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BuildEvent {
+    /// The phase, either `detect` or `build`, started.
+    PhaseStarted { phase: String },
+    /// The phase finished with the given exit code.
+    PhaseFinished {
+        phase: String,
+        exit_code: i32,
+        duration_ms: u64,
+    },
+    /// libcnb started handling a layer.
+    LayerStarted { layer: String },
+    /// libcnb finished handling a layer. The action is one of `create`, `recreate`, `update` or
+    /// `keep`, or `None` if handling the layer failed before an action was chosen.
+    LayerFinished {
+        layer: String,
+        action: Option<String>,
+        success: bool,
+        duration_ms: u64,
+    },
+    /// The buildpack emitted a warning, see [`Warnings`](crate::Warnings).
+    Warning { message: String },
+    /// The phase failed with the given error.
+    Error {
+        phase: String,
+        category: String,
+        message: String,
+    },
+}
+
+/// A [`BuildEvent`] together with the time it occurred and the buildpack that emitted it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BuildEventRecord {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub buildpack_id: Option<String>,
+    #[serde(flatten)]
+    pub event: BuildEvent,
+}
+
+struct BuildEventsFile {
+    file: File,
+    buildpack_id: Option<String>,
+}
+
+static BUILD_EVENTS_FILE: OnceLock<Mutex<BuildEventsFile>> = OnceLock::new();
+
+/// Opens the build events file at the given path. Events emitted afterwards are appended to it.
+/// Has no effect if a build events file has already been opened.
+pub(crate) fn init(path: &Path, buildpack_id: Option<String>) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    let _ = BUILD_EVENTS_FILE.set(Mutex::new(BuildEventsFile { file, buildpack_id }));
+    Ok(())
+}
+
+/// Appends the given event to the build events file, if one has been opened. Failing to write the
+/// event must not fail the build, so errors are ignored.
+pub(crate) fn emit(event: BuildEvent) {
+    if let Some(build_events_file) = BUILD_EVENTS_FILE.get() {
+        let mut build_events_file = build_events_file
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let record = BuildEventRecord {
+            timestamp_ms: unix_millis(SystemTime::now()),
+            buildpack_id: build_events_file.buildpack_id.clone(),
+            event,
+        };
+
+        let _ = write_record(&record, &mut build_events_file.file);
+    }
+}
+
+fn write_record(record: &BuildEventRecord, mut writer: impl Write) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+
+    // Written with a single call so that concurrent writers appending to the same file don't
+    // interleave their lines.
+    writer.write_all(&line)
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| {
+        u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_written_as_json_lines() {
+        let mut output = Vec::new();
+
+        for event in [
+            BuildEvent::PhaseStarted {
+                phase: String::from("build"),
+            },
+            BuildEvent::LayerFinished {
+                layer: String::from("node"),
+                action: Some(String::from("create")),
+                success: true,
+                duration_ms: 1200,
+            },
+        ] {
+            let record = BuildEventRecord {
+                timestamp_ms: 1_700_000_000_000,
+                buildpack_id: Some(String::from("heroku/nodejs")),
+                event,
+            };
+            write_record(&record, &mut output).unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"timestamp_ms\":1700000000000,\"buildpack_id\":\"heroku/nodejs\",\"event\":\"phase_started\",\"phase\":\"build\"}\n\
+             {\"timestamp_ms\":1700000000000,\"buildpack_id\":\"heroku/nodejs\",\"event\":\"layer_finished\",\"layer\":\"node\",\"action\":\"create\",\"success\":true,\"duration_ms\":1200}\n"
+        );
+    }
+
+    #[test]
+    fn records_roundtrip() {
+        let record = BuildEventRecord {
+            timestamp_ms: 42,
+            buildpack_id: None,
+            event: BuildEvent::Warning {
+                message: String::from("Using the default Node.js version"),
+            },
+        };
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BuildEventRecord>(&json).unwrap(),
+            record
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::build::{LAUNCH_TOML_FILE_NAME, STORE_TOML_FILE_NAME};
+use crate::data::launch::Launch;
+use crate::data::store::Store;
+use crate::sbom::{cnb_sbom_path, Sbom};
+use crate::{write_toml_atomic, Deterministic, Error, TomlFileError};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// A file that is written to the layers directory at the end of the build phase.
+pub(crate) enum BuildOutput {
+    Launch(Launch),
+    Store(Store),
+    BuildSbom(Sbom),
+    LaunchSbom(Sbom),
+}
+
+/// An error writing a [`BuildOutput`], converted into the matching [`Error`] variant.
+#[derive(Debug)]
+pub(crate) enum BuildOutputError {
+    Launch(TomlFileError),
+    Store(TomlFileError),
+    BuildSbom(std::io::Error),
+    LaunchSbom(std::io::Error),
+}
+
+impl<E> From<BuildOutputError> for Error<E> {
+    fn from(error: BuildOutputError) -> Self {
+        match error {
+            BuildOutputError::Launch(error) => Error::CannotWriteLaunch(error),
+            BuildOutputError::Store(error) => Error::CannotWriteStore(error),
+            BuildOutputError::BuildSbom(error) => Error::CannotWriteBuildSbom(error),
+            BuildOutputError::LaunchSbom(error) => Error::CannotWriteLaunchSbom(error),
+        }
+    }
+}
+
+impl BuildOutput {
+    /// The path this output is written to.
+    fn path(&self, layers_dir: &Path) -> PathBuf {
+        match self {
+            BuildOutput::Launch(_) => layers_dir.join(LAUNCH_TOML_FILE_NAME),
+            BuildOutput::Store(_) => layers_dir.join(STORE_TOML_FILE_NAME),
+            BuildOutput::BuildSbom(sbom) => cnb_sbom_path(&sbom.format, layers_dir, "build"),
+            BuildOutput::LaunchSbom(sbom) => cnb_sbom_path(&sbom.format, layers_dir, "launch"),
+        }
+    }
+
+    fn write(
+        &self,
+        layers_dir: &Path,
+        deterministic: Option<Deterministic>,
+    ) -> Result<(), BuildOutputError> {
+        let path = self.path(layers_dir);
+
+        match self {
+            BuildOutput::Launch(launch) => {
+                write_toml(launch, &path, deterministic).map_err(BuildOutputError::Launch)
+            }
+            BuildOutput::Store(store) => {
+                write_toml(store, &path, deterministic).map_err(BuildOutputError::Store)
+            }
+            BuildOutput::BuildSbom(sbom) => {
+                write_sbom(sbom, &path, deterministic).map_err(BuildOutputError::BuildSbom)
+            }
+            BuildOutput::LaunchSbom(sbom) => {
+                write_sbom(sbom, &path, deterministic).map_err(BuildOutputError::LaunchSbom)
+            }
+        }
+    }
+}
+
+/// Writes the given outputs to the layers directory, concurrently if there is more than one.
+///
+/// All outputs are written even if some of them fail. The errors are returned in the order of the
+/// given outputs, regardless of the order in which the writes finished, so that the reported
+/// error doesn't depend on timing.
+///
+/// Of several outputs that are written to the same file, such as two build SBOMs of the same
+/// format, only the last one is written, as if the outputs were written one after another.
+pub(crate) fn write_build_outputs(
+    layers_dir: &Path,
+    deterministic: Option<Deterministic>,
+    outputs: &[BuildOutput],
+) -> Vec<BuildOutputError> {
+    let mut paths = HashSet::new();
+    let mut outputs = outputs
+        .iter()
+        .rev()
+        .filter(|output| paths.insert(output.path(layers_dir)))
+        .collect::<Vec<_>>();
+    outputs.reverse();
+
+    let results = if outputs.len() > 1 {
+        thread::scope(|scope| {
+            outputs
+                .iter()
+                .map(|output| scope.spawn(move || output.write(layers_dir, deterministic)))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect::<Vec<_>>()
+        })
+    } else {
+        outputs
+            .iter()
+            .map(|output| output.write(layers_dir, deterministic))
+            .collect()
+    };
+
+    results.into_iter().filter_map(Result::err).collect()
+}
+
+fn write_toml(
+    value: &impl serde::Serialize,
+    path: &Path,
+    deterministic: Option<Deterministic>,
+) -> Result<(), TomlFileError> {
+    write_toml_atomic(value, path)?;
+
+    match deterministic {
+        Some(deterministic) => deterministic
+            .set_mtime(path)
+            .map_err(TomlFileError::IoError),
+        None => Ok(()),
+    }
+}
+
+fn write_sbom(
+    sbom: &Sbom,
+    path: &Path,
+    deterministic: Option<Deterministic>,
+) -> std::io::Result<()> {
+    sbom.write_to(path)?;
+
+    match deterministic {
+        Some(deterministic) => deterministic.set_mtime(path),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::sbom::SbomFormat;
+    use std::fs;
+
+    #[test]
+    fn writes_all_outputs_and_orders_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+
+        let outputs = [
+            BuildOutput::Launch(Launch::default()),
+            BuildOutput::BuildSbom(Sbom::from_bytes(SbomFormat::CycloneDxJson, "{}")),
+            BuildOutput::LaunchSbom(Sbom::from_bytes(SbomFormat::SpdxJson, "{}")),
+        ];
+        assert!(write_build_outputs(layers_dir, None, &outputs).is_empty());
+        for file_name in [
+            "launch.toml",
+            "build.sbom.cdx.json",
+            "launch.sbom.spdx.json",
+        ] {
+            assert!(layers_dir.join(file_name).is_file());
+        }
+
+        // Directories in place of the files make the writes fail.
+        fs::create_dir(layers_dir.join("store.toml")).unwrap();
+        fs::create_dir(layers_dir.join("build.sbom.syft.json")).unwrap();
+        let outputs = [
+            BuildOutput::BuildSbom(Sbom::from_bytes(SbomFormat::SyftJson, "{}")),
+            BuildOutput::Launch(Launch::default()),
+            BuildOutput::Store(Store::default()),
+        ];
+        let errors = write_build_outputs(layers_dir, None, &outputs);
+
+        assert!(matches!(
+            errors.as_slice(),
+            [BuildOutputError::BuildSbom(_), BuildOutputError::Store(_)]
+        ));
+    }
+
+    #[test]
+    fn writes_last_of_outputs_with_the_same_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+
+        let outputs = (0..8)
+            .map(|index| {
+                BuildOutput::BuildSbom(Sbom::from_bytes(
+                    SbomFormat::CycloneDxJson,
+                    format!("{{\"index\": {index}}}").repeat(10_000),
+                ))
+            })
+            .chain([BuildOutput::LaunchSbom(Sbom::from_bytes(
+                SbomFormat::CycloneDxJson,
+                "{}",
+            ))])
+            .collect::<Vec<_>>();
+
+        assert!(write_build_outputs(layers_dir, None, &outputs).is_empty());
+        assert_eq!(
+            fs::read_to_string(layers_dir.join("build.sbom.cdx.json")).unwrap(),
+            "{\"index\": 7}".repeat(10_000)
+        );
+        assert_eq!(
+            fs::read_to_string(layers_dir.join("launch.sbom.cdx.json")).unwrap(),
+            "{}"
+        );
+    }
+}
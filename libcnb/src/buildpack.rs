@@ -1,8 +1,9 @@
 use crate::build::{BuildContext, BuildResult};
 use crate::detect::{DetectContext, DetectResult};
-use crate::Platform;
+use crate::{ErrorContext, Platform};
 use serde::de::DeserializeOwned;
 use std::fmt::Debug;
+use std::io::Write;
 
 /// Represents a buildpack written with the libcnb framework.
 ///
@@ -15,9 +16,9 @@ pub trait Buildpack {
     type Platform: Platform;
 
     /// The metadata type for this buildpack. This is the data within `[metadata]` of the buildpacks
-    /// `buildpack.toml`. The framework will attempt to parse the data and will only continue if
-    /// parsing succeeded. If you wish to use raw, untyped, TOML data instead, use
-    /// [`GenericMetadata`](crate::generic::GenericMetadata).
+    /// `buildpack.toml`. The framework will attempt to parse and validate the data and will only
+    /// continue if both succeeded, see [`Buildpack::validate_metadata`]. If you wish to use raw,
+    /// untyped, TOML data instead, use [`GenericMetadata`](crate::generic::GenericMetadata).
     type Metadata: DeserializeOwned;
 
     /// The error type for buildpack specific errors, usually an enum. Examples of values inside the
@@ -28,12 +29,90 @@ pub trait Buildpack {
 
     /// Detect logic for this buildpack. Directly corresponds to
     /// [detect in the CNB buildpack interface](https://github.com/buildpacks/spec/blob/platform/v0.10/buildpack.md#detection).
+    ///
+    /// Detection can't keep state between builds: the layers directory, and with it the
+    /// `store.toml` of previous builds, is only restored after all buildpacks have been detected.
+    /// Detection should therefore only inspect the application cheaply and leave expensive work,
+    /// which can be cached in layers or the [`Store`](crate::data::store::Store), to the build.
     fn detect(&self, context: DetectContext<Self>) -> crate::Result<DetectResult, Self::Error>;
 
     /// Build logic for this buildpack. Directly corresponds to
     /// [build in the CNB buildpack interface](https://github.com/buildpacks/spec/blob/platform/v0.10/buildpack.md#build).
     fn build(&self, context: BuildContext<Self>) -> crate::Result<BuildResult, Self::Error>;
 
+    /// Validates the metadata within `[metadata]` of the buildpack's `buildpack.toml`, returning
+    /// all invalid fields.
+    ///
+    /// libcnb calls this method after the metadata has been deserialized and before any detect or
+    /// build logic runs. Invalid metadata fails the buildpack with
+    /// [`Error::InvalidBuildpackMetadata`](crate::Error::InvalidBuildpackMetadata). The default
+    /// implementation accepts all metadata that could be deserialized.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libcnb::build::{BuildContext, BuildResult};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericPlatform};
+    /// use libcnb::{Buildpack, InvalidMetadataField};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct JvmBuildpackMetadata {
+    ///     default_version: String,
+    ///     supported_versions: Vec<String>,
+    /// }
+    ///
+    /// struct JvmBuildpack;
+    ///
+    /// impl Buildpack for JvmBuildpack {
+    /// #   type Platform = GenericPlatform;
+    ///     type Metadata = JvmBuildpackMetadata;
+    /// #   type Error = GenericError;
+    /// #
+    /// #   fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #       unimplemented!()
+    /// #   }
+    /// #
+    /// #   fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// #       unimplemented!()
+    /// #   }
+    /// #
+    ///     fn validate_metadata(
+    ///         &self,
+    ///         metadata: &Self::Metadata,
+    ///     ) -> Result<(), Vec<InvalidMetadataField>> {
+    ///         if metadata.supported_versions.contains(&metadata.default_version) {
+    ///             Ok(())
+    ///         } else {
+    ///             Err(vec![InvalidMetadataField::new(
+    ///                 "default_version",
+    ///                 "must be one of supported_versions",
+    ///             )])
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let metadata = JvmBuildpackMetadata {
+    ///     default_version: String::from("21"),
+    ///     supported_versions: vec![String::from("17")],
+    /// };
+    ///
+    /// let error: libcnb::Error<GenericError> = libcnb::Error::InvalidBuildpackMetadata(
+    ///     JvmBuildpack.validate_metadata(&metadata).unwrap_err(),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Invalid metadata in buildpack.toml: default_version must be one of supported_versions"
+    /// );
+    /// ```
+    fn validate_metadata(
+        &self,
+        _metadata: &Self::Metadata,
+    ) -> Result<(), Vec<InvalidMetadataField>> {
+        Ok(())
+    }
+
     /// If an unhandled error occurred within the framework or the buildpack, this method will be
     /// called by the framework to allow custom, buildpack specific, code to run before exiting.
     /// Usually, this method is implemented by logging the error in a user friendly manner.
@@ -41,11 +120,93 @@ pub trait Buildpack {
     /// Implementations are not limited to just logging, for example, buildpacks might want to
     /// collect and send metrics about occurring errors to a central system.
     ///
-    /// The default implementation will simply print the error
-    /// (using its [`Debug`] implementation) to stderr.
+    /// The default implementation prints [`UserFacingError`](crate::UserFacingError) values as a
+    /// formatted error block and all other errors (using their [`Debug`] implementation) to
+    /// stderr.
     fn on_error(&self, error: crate::Error<Self::Error>) {
-        eprintln!("Unhandled error:");
-        eprintln!("> {error:?}");
-        eprintln!("Buildpack will exit!");
+        // Like with `eprintln!`, errors writing to the build log can't be handled here.
+        let mut stderr = crate::output::stderr();
+        let _ = if let crate::Error::UserFacing(user_facing_error) = &error {
+            write!(stderr, "\n{}", user_facing_error.to_block())
+        } else {
+            writeln!(
+                stderr,
+                "Unhandled error:\n> {error:?}\nBuildpack will exit!"
+            )
+        };
+    }
+
+    /// Like [`Buildpack::on_error`], but additionally receives a snapshot of the context the error
+    /// occurred in, such as the phase, buildpack id and target. This allows error handlers to emit
+    /// more actionable messages and to report telemetry.
+    ///
+    /// This is the method libcnb calls when an error occurs. The default implementation ignores
+    /// the context and calls [`Buildpack::on_error`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use libcnb::build::{BuildContext, BuildResult};
+    /// # use libcnb::detect::{DetectContext, DetectResult};
+    /// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+    /// use libcnb::{Buildpack, ErrorContext};
+    ///
+    /// struct ExampleBuildpack;
+    ///
+    /// impl Buildpack for ExampleBuildpack {
+    /// #   type Platform = GenericPlatform;
+    /// #   type Metadata = GenericMetadata;
+    /// #   type Error = GenericError;
+    /// #
+    /// #   fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+    /// #       unimplemented!()
+    /// #   }
+    /// #
+    /// #   fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+    /// #       unimplemented!()
+    /// #   }
+    /// #
+    ///     fn on_error_with_context(&self, error: libcnb::Error<Self::Error>, context: &ErrorContext) {
+    ///         let arch = context
+    ///             .target
+    ///             .as_ref()
+    ///             .map_or("unknown", |target| target.arch.as_str());
+    ///
+    ///         eprintln!("Error during {} for {arch}: {error}", context.phase);
+    ///     }
+    /// }
+    /// ```
+    fn on_error_with_context(&self, error: crate::Error<Self::Error>, _context: &ErrorContext) {
+        self.on_error(error);
+    }
+
+    /// Additional OpenTelemetry resource attributes for the telemetry recorded by libcnb with the
+    /// `trace` feature, such as the language ecosystem or the team owning the buildpack.
+    ///
+    /// These attributes override the `service.name` and `service.version` attributes set by
+    /// libcnb, and are overridden by the attributes configured via the `OTEL_RESOURCE_ATTRIBUTES`
+    /// and `OTEL_SERVICE_NAME` environment variables. The default implementation returns no
+    /// attributes.
+    #[must_use]
+    fn telemetry_resource_attributes(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+/// A field of [`Buildpack::Metadata`] with an invalid value.
+#[derive(thiserror::Error, Clone, Debug, Eq, PartialEq)]
+#[error("{field} {reason}")]
+pub struct InvalidMetadataField {
+    /// The path of the field within `[metadata]`, for example `versions.default`.
+    pub field: String,
+    /// Why the value is invalid, for example `must not be empty`.
+    pub reason: String,
+}
+
+impl InvalidMetadataField {
+    pub fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            reason: reason.into(),
+        }
     }
 }
@@ -0,0 +1,505 @@
+//! Provides a helper for running external commands during detect and build.
+
+mod recording;
+
+pub use recording::*;
+
+use crate::layer_env::{LayerEnv, Scope};
+use crate::output;
+use crate::retry::Retry;
+use crate::Env;
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStderr, ChildStdout, ExitStatus, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Describes what happens with the output of a command run by a [`CommandRunner`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OutputMode {
+    /// Write stdout and stderr of the command to the stdout and stderr of the buildpack, making
+    /// it part of the build log.
+    #[default]
+    Stream,
+    /// Capture stdout and stderr of the command without writing them to the build log.
+    Capture,
+    /// Write stdout and stderr of the command to the build log while also capturing them.
+    StreamAndCapture,
+}
+
+impl OutputMode {
+    fn streams(self) -> bool {
+        matches!(self, OutputMode::Stream | OutputMode::StreamAndCapture)
+    }
+
+    fn captures(self) -> bool {
+        matches!(self, OutputMode::Capture | OutputMode::StreamAndCapture)
+    }
+
+    /// The output mode that additionally captures the output, for recording it.
+    fn with_capture(self) -> Self {
+        match self {
+            OutputMode::Stream | OutputMode::StreamAndCapture => OutputMode::StreamAndCapture,
+            OutputMode::Capture => OutputMode::Capture,
+        }
+    }
+}
+
+/// Runs an external command with options commonly needed by buildpacks.
+///
+/// Compared to using [`std::process::Command`] directly, it supports streaming output to the
+/// build log while capturing it, applying [`LayerEnv`] values, timeouts, and returns a
+/// [`CommandError`] that includes the command line and exit status when the command fails.
+///
+/// # Examples
+/// ```
+/// use libcnb::command::{CommandRunner, OutputMode};
+///
+/// let output = CommandRunner::new("echo")
+///     .arg("Hello World!")
+///     .output_mode(OutputMode::Capture)
+///     .run()
+///     .unwrap();
+///
+/// assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello World!\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct CommandRunner {
+    program: OsString,
+    args: Vec<OsString>,
+    current_dir: Option<PathBuf>,
+    env: Option<Env>,
+    output_mode: OutputMode,
+    timeout: Option<Duration>,
+    recording: Option<CommandRecording>,
+}
+
+impl CommandRunner {
+    pub fn new(program: impl Into<OsString>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            current_dir: None,
+            env: None,
+            output_mode: OutputMode::default(),
+            timeout: None,
+            recording: None,
+        }
+    }
+
+    #[must_use]
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    #[must_use]
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    #[must_use]
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets the complete environment of the command, replacing the inherited one.
+    #[must_use]
+    pub fn env(mut self, env: Env) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// Applies the given [`LayerEnv`] to the environment of the command.
+    ///
+    /// If no environment was set via [`CommandRunner::env`], the environment of the current
+    /// process is used as the base. Can be called multiple times to apply the environments of
+    /// several layers, in order.
+    #[must_use]
+    pub fn layer_env(mut self, layer_env: &LayerEnv, scope: Scope) -> Self {
+        layer_env.apply_to(scope, self.env.get_or_insert_with(Env::from_current));
+        self
+    }
+
+    #[must_use]
+    pub fn output_mode(mut self, output_mode: OutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+
+    /// Kills the command if it doesn't finish within the given duration.
+    ///
+    /// Only the command process itself is killed. If it spawned child processes that inherited
+    /// its stdout or stderr, [`CommandRunner::run`] returns once those have exited as well.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Records the command to, or replays it from, the given [`CommandRecording`] instead of
+    /// only running it.
+    #[must_use]
+    pub fn recording(mut self, recording: &CommandRecording) -> Self {
+        self.recording = Some(recording.clone());
+        self
+    }
+
+    /// Runs the command to completion.
+    ///
+    /// The returned [`Output`] only contains stdout and stderr when the command was run with an
+    /// [`OutputMode`] that captures them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command couldn't be spawned, timed out or exited unsuccessfully,
+    /// or if a [`CommandRecording`] couldn't be written or has no recording of the command.
+    pub fn run(&self) -> Result<Output, CommandError> {
+        let output = match &self.recording {
+            Some(recording) => recording.output_for(self)?,
+            None => self.spawn_and_wait(self.output_mode)?,
+        };
+
+        if output.status.success() {
+            Ok(output)
+        } else {
+            Err(CommandError::UnsuccessfulExit {
+                command: self.command_line(),
+                output,
+            })
+        }
+    }
+
+    /// Runs the command to completion with the given output mode, regardless of its exit status.
+    fn spawn_and_wait(&self, output_mode: OutputMode) -> Result<Output, CommandError> {
+        let command_line = self.command_line();
+
+        let mut command = std::process::Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        if let Some(current_dir) = &self.current_dir {
+            command.current_dir(current_dir);
+        }
+
+        if let Some(env) = &self.env {
+            command.env_clear().envs(env);
+        }
+
+        let mut child = command.spawn().map_err(|source| CommandError::Spawn {
+            command: command_line.clone(),
+            source,
+        })?;
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let (status, stdout, stderr) = thread::scope(|scope| {
+            let stdout_handle =
+                scope.spawn(move || forward_stdout(stdout, output_mode, output::stdout()));
+            let stderr_handle =
+                scope.spawn(move || forward_stderr(stderr, output_mode, output::stderr()));
+
+            let status = wait_with_timeout(&mut child, self.timeout);
+
+            (
+                status,
+                join_output(stdout_handle),
+                join_output(stderr_handle),
+            )
+        });
+
+        let status = status
+            .map_err(|source| CommandError::Io {
+                command: command_line.clone(),
+                source,
+            })?
+            .ok_or_else(|| CommandError::Timeout {
+                command: command_line.clone(),
+                timeout: self.timeout.unwrap_or_default(),
+            })?;
+
+        Ok(Output {
+            status,
+            stdout: stdout.map_err(|source| CommandError::Io {
+                command: command_line.clone(),
+                source,
+            })?,
+            stderr: stderr.map_err(|source| CommandError::Io {
+                command: command_line,
+                source,
+            })?,
+        })
+    }
+
+    /// Runs the command, retrying it according to the given [`Retry`] if it times out or exits
+    /// unsuccessfully.
+    ///
+    /// Commands that couldn't be spawned at all are not retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt if no attempt succeeded, see [`CommandRunner::run`].
+    pub fn run_with_retry(&self, retry: &Retry) -> Result<Output, CommandError> {
+        retry.run_if(
+            |_| self.run(),
+            |error| {
+                matches!(
+                    error,
+                    CommandError::Timeout { .. } | CommandError::UnsuccessfulExit { .. }
+                )
+            },
+        )
+    }
+
+    /// A human-readable representation of the command line, used in errors. Arguments are quoted
+    /// with [`shell::quote`](crate::shell::quote).
+    #[must_use]
+    pub fn command_line(&self) -> String {
+        crate::shell::join(std::iter::once(&self.program).chain(&self.args))
+    }
+}
+
+/// Waits for the child to exit, killing it once the timeout is reached. Returns `None` if the
+/// timeout was reached.
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> io::Result<Option<ExitStatus>> {
+    // A timeout too large to be represented as an `Instant`, such as `Duration::MAX`, is treated
+    // like no timeout.
+    let Some(deadline) = timeout.and_then(|timeout| Instant::now().checked_add(timeout)) else {
+        return child.wait().map(Some);
+    };
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn forward_stdout(
+    stdout: Option<ChildStdout>,
+    output_mode: OutputMode,
+    writer: impl Write,
+) -> io::Result<Vec<u8>> {
+    stdout.map_or_else(
+        || Ok(Vec::new()),
+        |stdout| forward(stdout, output_mode, writer),
+    )
+}
+
+fn forward_stderr(
+    stderr: Option<ChildStderr>,
+    output_mode: OutputMode,
+    writer: impl Write,
+) -> io::Result<Vec<u8>> {
+    stderr.map_or_else(
+        || Ok(Vec::new()),
+        |stderr| forward(stderr, output_mode, writer),
+    )
+}
+
+fn forward(
+    mut reader: impl Read,
+    output_mode: OutputMode,
+    mut writer: impl Write,
+) -> io::Result<Vec<u8>> {
+    let mut captured = Vec::new();
+    let mut buffer = [0; 8192];
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+
+        if read == 0 {
+            break;
+        }
+
+        // Flushing after every chunk shows the output of long running commands as it is written,
+        // even if the writer buffers it.
+        if output_mode.streams() {
+            writer.write_all(&buffer[..read])?;
+            writer.flush()?;
+        }
+
+        if output_mode.captures() {
+            captured.extend_from_slice(&buffer[..read]);
+        }
+    }
+
+    Ok(captured)
+}
+
+fn join_output(handle: thread::ScopedJoinHandle<'_, io::Result<Vec<u8>>>) -> io::Result<Vec<u8>> {
+    handle
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("output forwarding thread panicked")))
+}
+
+/// An error that occurred while running a command via [`CommandRunner`].
+#[derive(thiserror::Error, Debug)]
+pub enum CommandError {
+    #[error("Couldn't spawn command `{command}`: {source}")]
+    Spawn { command: String, source: io::Error },
+
+    #[error("I/O error while running command `{command}`: {source}")]
+    Io { command: String, source: io::Error },
+
+    #[error("Command `{command}` timed out after {}s", timeout.as_secs_f32())]
+    Timeout { command: String, timeout: Duration },
+
+    #[error("Command `{command}` exited unsuccessfully ({}){}", output.status, StderrExcerpt(&output.stderr))]
+    UnsuccessfulExit { command: String, output: Output },
+
+    #[error("Command `{command}` wasn't recorded in {}", path.display())]
+    NotRecorded { command: String, path: PathBuf },
+
+    #[error("Couldn't write command recording {}: {source}", path.display())]
+    Recording { path: PathBuf, source: io::Error },
+}
+
+/// Formats the last lines of captured stderr for inclusion in error messages.
+struct StderrExcerpt<'a>(&'a [u8]);
+
+impl Display for StderrExcerpt<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const MAX_LINES: usize = 10;
+
+        let stderr = String::from_utf8_lossy(self.0);
+        let lines: Vec<&str> = stderr.trim_end().lines().collect();
+
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        write!(f, ":")?;
+        for line in &lines[lines.len().saturating_sub(MAX_LINES)..] {
+            write!(f, "\n  {line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+    use crate::layer_env::ModificationBehavior;
+
+    #[test]
+    fn capture_stdout_and_stderr() {
+        let output = CommandRunner::new("sh")
+            .args(["-c", "echo out; echo err >&2"])
+            .output_mode(OutputMode::Capture)
+            .run()
+            .unwrap();
+
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn unsuccessful_exit_includes_command_line_and_stderr() {
+        let error = CommandRunner::new("sh")
+            .args(["-c", "echo 'something broke' >&2; exit 3"])
+            .output_mode(OutputMode::Capture)
+            .run()
+            .unwrap_err();
+
+        assert!(matches!(error, CommandError::UnsuccessfulExit { .. }));
+        assert_eq!(
+            error.to_string(),
+            "Command `sh -c 'echo '\\''something broke'\\'' >&2; exit 3'` exited unsuccessfully (exit status: 3):\n  something broke"
+        );
+    }
+
+    #[test]
+    fn run_with_retry_retries_unsuccessful_exits() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let marker = temp_dir.path().join("marker");
+
+        // Fails on the first run and succeeds on the second one.
+        let output = CommandRunner::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "if [ -f {0} ]; then echo ok; else touch {0}; exit 1; fi",
+                marker.display()
+            ))
+            .output_mode(OutputMode::Capture)
+            .run_with_retry(&Retry::new().initial_delay(Duration::ZERO))
+            .unwrap();
+
+        assert_eq!(output.stdout, b"ok\n");
+    }
+
+    #[test]
+    fn spawn_error() {
+        let error = CommandRunner::new("this-command-does-not-exist")
+            .run()
+            .unwrap_err();
+
+        assert!(matches!(error, CommandError::Spawn { .. }));
+    }
+
+    #[test]
+    fn timeout_kills_command() {
+        let error = CommandRunner::new("sleep")
+            .arg("10")
+            .timeout(Duration::from_millis(100))
+            .run()
+            .unwrap_err();
+
+        assert!(matches!(error, CommandError::Timeout { .. }));
+    }
+
+    #[test]
+    fn unrepresentable_timeout_is_no_timeout() {
+        let output = CommandRunner::new("true")
+            .timeout(Duration::MAX)
+            .run()
+            .unwrap();
+
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn layer_env_is_applied() {
+        let mut layer_env = LayerEnv::new();
+        layer_env.insert(
+            Scope::All,
+            ModificationBehavior::Override,
+            "LIBCNB_COMMAND_TEST",
+            "from-layer",
+        );
+
+        let output = CommandRunner::new("sh")
+            .args(["-c", "echo $LIBCNB_COMMAND_TEST"])
+            .env(Env::new().with("PATH", std::env::var_os("PATH").unwrap_or_default()))
+            .layer_env(&layer_env, Scope::Build)
+            .output_mode(OutputMode::Capture)
+            .run()
+            .unwrap();
+
+        assert_eq!(output.stdout, b"from-layer\n");
+    }
+}
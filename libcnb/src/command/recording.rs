@@ -0,0 +1,283 @@
+use crate::command::{CommandError, CommandRunner};
+use crate::output;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Set to `true` to record the commands of a [`CommandRecording::fixture`] again, instead of
+/// replaying them.
+pub const RECORD_COMMANDS_ENV_VAR: &str = "LIBCNB_RECORD_COMMANDS";
+
+/// Records the commands run by [`CommandRunner`]s to a fixture file, or replays them from it.
+///
+/// This allows unit testing code that wraps external tools, such as the layer logic around a
+/// `bundle install`, without depending on the tools being installed or on network access. Record
+/// the commands once against the real tools, commit the fixture file, and replay it in tests:
+///
+/// ```no_run
+/// use libcnb::command::{CommandRecording, CommandRunner, OutputMode};
+///
+/// fn bundle_install(recording: Option<&CommandRecording>) -> String {
+///     let mut runner = CommandRunner::new("bundle")
+///         .arg("install")
+///         .output_mode(OutputMode::Capture);
+///
+///     if let Some(recording) = recording {
+///         runner = runner.recording(recording);
+///     }
+///
+///     String::from_utf8_lossy(&runner.run().unwrap().stdout).into_owned()
+/// }
+///
+/// let recording = CommandRecording::fixture("tests/fixtures/bundle_install.json").unwrap();
+/// assert!(bundle_install(Some(&recording)).contains("Bundle complete!"));
+/// ```
+///
+/// Commands are identified by their [`CommandRunner::command_line`], the working directory and
+/// environment are not recorded. When replaying, each command returns the output of the next
+/// recorded invocation of the same command line, so repeated commands can return different
+/// outputs. Output is recorded as UTF-8, invalid sequences are replaced. Commands that couldn't be
+/// spawned or timed out are not recorded.
+///
+/// Cloning a `CommandRecording` yields a handle to the same recording.
+#[derive(Clone, Debug)]
+pub struct CommandRecording {
+    inner: Arc<Mutex<RecordingState>>,
+}
+
+#[derive(Debug)]
+struct RecordingState {
+    mode: RecordingMode,
+    path: PathBuf,
+    fixture: Fixture,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum RecordingMode {
+    Record,
+    Replay,
+}
+
+/// The contents of a fixture file.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Fixture {
+    commands: Vec<RecordedCommand>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RecordedCommand {
+    command: String,
+    /// The exit code of the command, or 1 if it was terminated by a signal.
+    exit_code: i32,
+    stdout: String,
+    stderr: String,
+    #[serde(skip)]
+    replayed: bool,
+}
+
+impl CommandRecording {
+    /// Creates a recording that runs commands and writes their invocations to the fixture file at
+    /// the given path, replacing it.
+    #[must_use]
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(RecordingMode::Record, path.into(), Fixture::default())
+    }
+
+    /// Creates a recording that replays commands from the fixture file at the given path, without
+    /// running them.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the fixture file couldn't be read or parsed.
+    pub fn replay(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let fixture = serde_json::from_slice(&fs::read(&path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+        Ok(Self::new(RecordingMode::Replay, path, fixture))
+    }
+
+    /// Creates a recording for the fixture file at the given path, relative to
+    /// `CARGO_MANIFEST_DIR`, for use in tests.
+    ///
+    /// Commands are replayed if the fixture file exists. They are recorded if it doesn't exist
+    /// yet, or if [`RECORD_COMMANDS_ENV_VAR`] is set to `true`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the fixture file couldn't be read or parsed.
+    pub fn fixture(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = std::env::var_os("CARGO_MANIFEST_DIR").map_or_else(
+            || path.as_ref().to_path_buf(),
+            |dir| PathBuf::from(dir).join(&path),
+        );
+
+        let record = std::env::var(RECORD_COMMANDS_ENV_VAR).is_ok_and(|value| value == "true");
+
+        if record || !path.exists() {
+            Ok(Self::record(path))
+        } else {
+            Self::replay(path)
+        }
+    }
+
+    fn new(mode: RecordingMode, path: PathBuf, fixture: Fixture) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(RecordingState {
+                mode,
+                path,
+                fixture,
+            })),
+        }
+    }
+
+    /// Returns the output of the given command, regardless of its exit status, by either running
+    /// and recording it or by replaying it.
+    pub(super) fn output_for(&self, runner: &CommandRunner) -> Result<Output, CommandError> {
+        let mode = self.lock().mode;
+
+        let output = match mode {
+            RecordingMode::Record => {
+                let output = runner.spawn_and_wait(runner.output_mode.with_capture())?;
+                self.append(runner.command_line(), &output)?;
+                output
+            }
+            RecordingMode::Replay => {
+                let output = self.next_output(runner.command_line())?;
+
+                if runner.output_mode.streams() {
+                    // Like when running the command, errors writing to the build log are ignored.
+                    let _ = output::stdout().write_all(&output.stdout);
+                    let _ = output::stderr().write_all(&output.stderr);
+                }
+
+                output
+            }
+        };
+
+        if runner.output_mode.captures() {
+            Ok(output)
+        } else {
+            Ok(Output {
+                status: output.status,
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            })
+        }
+    }
+
+    fn append(&self, command: String, output: &Output) -> Result<(), CommandError> {
+        let mut state = self.lock();
+
+        state.fixture.commands.push(RecordedCommand {
+            command,
+            exit_code: output.status.code().unwrap_or(1),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            replayed: false,
+        });
+
+        let write_result = serde_json::to_vec_pretty(&state.fixture)
+            .map_err(io::Error::from)
+            .and_then(|json| {
+                if let Some(parent) = state.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&state.path, json)
+            });
+
+        write_result.map_err(|source| CommandError::Recording {
+            path: state.path.clone(),
+            source,
+        })
+    }
+
+    fn next_output(&self, command: String) -> Result<Output, CommandError> {
+        let mut state = self.lock();
+        let path = state.path.clone();
+
+        let recorded_command = state
+            .fixture
+            .commands
+            .iter_mut()
+            .find(|recorded_command| {
+                !recorded_command.replayed && recorded_command.command == command
+            })
+            .ok_or(CommandError::NotRecorded { command, path })?;
+
+        recorded_command.replayed = true;
+
+        Ok(Output {
+            status: exit_status(recorded_command.exit_code),
+            stdout: recorded_command.stdout.clone().into_bytes(),
+            stderr: recorded_command.stderr.clone().into_bytes(),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RecordingState> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn exit_status(exit_code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw((exit_code & 0xff) << 8)
+}
+
+#[cfg(target_family = "windows")]
+#[allow(clippy::cast_sign_loss)]
+fn exit_status(exit_code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(exit_code as u32)
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+    use crate::command::OutputMode;
+
+    #[test]
+    fn record_and_replay() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let fixture_path = temp_dir.path().join("fixtures/commands.json");
+        let marker = temp_dir.path().join("marker");
+        let command = CommandRunner::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "if [ -f {0} ]; then echo second; exit 3; else touch {0}; echo first; fi",
+                marker.display()
+            ))
+            .output_mode(OutputMode::Capture);
+
+        let recording = CommandRecording::record(&fixture_path);
+        let command = command.recording(&recording);
+        assert_eq!(command.run().unwrap().stdout, b"first\n");
+        assert!(matches!(
+            command.run(),
+            Err(CommandError::UnsuccessfulExit { .. })
+        ));
+
+        // The commands aren't run again when replaying, so the marker has no effect.
+        fs::remove_file(&marker).unwrap();
+        let recording = CommandRecording::replay(&fixture_path).unwrap();
+        let command = command.recording(&recording);
+        assert_eq!(command.run().unwrap().stdout, b"first\n");
+        match command.run() {
+            Err(CommandError::UnsuccessfulExit { output, .. }) => {
+                assert_eq!(output.status.code(), Some(3));
+                assert_eq!(output.stdout, b"second\n");
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+        assert!(!marker.exists());
+
+        assert!(matches!(
+            command.run(),
+            Err(CommandError::NotRecorded { .. })
+        ));
+    }
+}
@@ -1,18 +1,72 @@
 //! Provides detect phase specific types and helpers.
 
 use crate::buildpack::Buildpack;
-use crate::Target;
+use crate::platform;
 use crate::{data::build_plan::BuildPlan, data::buildpack::ComponentBuildpackDescriptor};
+use crate::{Target, Timings, Warnings};
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 
 /// Context for the detect phase execution.
 pub struct DetectContext<B: Buildpack + ?Sized> {
     pub app_dir: PathBuf,
     pub buildpack_dir: PathBuf,
     pub target: Target,
+    /// The platform directory, which contains `env/` and any other platform-provided files.
+    pub platform_dir: PathBuf,
     pub platform: B::Platform,
     pub buildpack_descriptor: ComponentBuildpackDescriptor<B::Metadata>,
+    /// The path the build plan of a passing [`DetectResult`] is written to by libcnb, which the
+    /// lifecycle provides as `CNB_BUILD_PLAN_PATH`.
+    pub build_plan_path: PathBuf,
+    /// Non-fatal warnings collected during this phase, see [`Self::warn`].
+    pub warnings: Warnings,
+    /// Durations measured during this phase, see [`Self::time`].
+    pub timings: Timings,
+}
+
+impl<B: Buildpack + ?Sized> DetectContext<B> {
+    /// Records a non-fatal warning that is printed in a summary section before the phase exits.
+    ///
+    /// See [`Warnings`] for details.
+    pub fn warn(&self, message: impl Into<String>) {
+        self.warnings.push(message);
+    }
+
+    /// Runs the given function and records the time it took under the given name, to be listed
+    /// in the timing summary.
+    ///
+    /// See [`Timings`] for details.
+    pub fn time<T>(&self, name: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        self.timings.time(name, f)
+    }
+
+    /// Returns the path of a file or directory relative to the platform directory.
+    #[must_use]
+    pub fn platform_path(&self, relative_path: impl AsRef<Path>) -> PathBuf {
+        platform::platform_path(&self.platform_dir, relative_path)
+    }
+
+    /// Reads a file provided by the platform, relative to the platform directory.
+    ///
+    /// Returns `Ok(None)` if the file does not exist.
+    pub fn read_platform_file(
+        &self,
+        relative_path: impl AsRef<Path>,
+    ) -> io::Result<Option<Vec<u8>>> {
+        platform::read_platform_file(&self.platform_dir, relative_path)
+    }
+
+    /// Reads a file provided by the platform as UTF-8, relative to the platform directory.
+    ///
+    /// Returns `Ok(None)` if the file does not exist.
+    pub fn read_platform_file_to_string(
+        &self,
+        relative_path: impl AsRef<Path>,
+    ) -> io::Result<Option<String>> {
+        platform::read_platform_file_to_string(&self.platform_dir, relative_path)
+    }
 }
 
 /// Describes the result of the detect phase.
@@ -0,0 +1,273 @@
+use crate::Platform;
+use std::fs::{self, File, FileTimes};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// The environment variable that enables the deterministic build mode when set to `true` or `1`,
+/// either in the platform env (`<platform>/env`) or in the process environment.
+pub const DETERMINISTIC_ENV_VAR: &str = "LIBCNB_DETERMINISTIC";
+
+/// The environment variable with the timestamp, in seconds since the Unix epoch, that is used for
+/// all files in the deterministic build mode. See <https://reproducible-builds.org/specs/source-date-epoch/>.
+///
+/// Setting it also enables the deterministic build mode, unless [`DETERMINISTIC_ENV_VAR`] is set
+/// to another value than `true` or `1`. Platforms that set it for every build but don't want
+/// deterministic file times need to set [`DETERMINISTIC_ENV_VAR`] to `false`.
+pub const SOURCE_DATE_EPOCH_ENV_VAR: &str = "SOURCE_DATE_EPOCH";
+
+/// The timestamp used in the deterministic build mode if [`SOURCE_DATE_EPOCH_ENV_VAR`] isn't set:
+/// 1980-01-01 00:00:01 UTC, the same timestamp the lifecycle uses for the files of image layers.
+pub const DEFAULT_DETERMINISTIC_MTIME: u64 = 315_532_801;
+
+/// Settings of the deterministic build mode, in which files are written with a fixed modification
+/// time so that layers are byte-for-byte reproducible across builds.
+///
+/// The mode is enabled by setting [`DETERMINISTIC_ENV_VAR`] to `true` or by setting
+/// [`SOURCE_DATE_EPOCH_ENV_VAR`]. libcnb then sets the
+/// modification time of the files it writes itself, such as layer TOML files, env files, exec.d
+/// programs, SBOM files, `launch.toml` and `store.toml`, to the timestamp in
+/// [`SOURCE_DATE_EPOCH_ENV_VAR`], or [`DEFAULT_DETERMINISTIC_MTIME`] if it isn't set or invalid.
+/// TOML files are always written with sorted keys and env files in sorted order.
+///
+/// The contents of layers are written by the buildpack, so libcnb can't normalize them. Use
+/// [`BuildContext::deterministic`](crate::build::BuildContext::deterministic) and the methods of
+/// this type to do the same for files written by the buildpack:
+///
+/// ```no_run
+/// # fn example<B: libcnb::Buildpack>(
+/// #     context: &libcnb::build::BuildContext<B>,
+/// #     layer_path: &std::path::Path,
+/// # ) -> std::io::Result<()> {
+/// if let Some(deterministic) = &context.deterministic {
+///     deterministic.normalize_dir(layer_path)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Deterministic {
+    mtime: SystemTime,
+}
+
+impl Deterministic {
+    /// Creates settings that use the given modification time for all files.
+    #[must_use]
+    pub fn new(mtime: SystemTime) -> Self {
+        Self { mtime }
+    }
+
+    /// The modification time that is used for all files.
+    #[must_use]
+    pub fn mtime(&self) -> SystemTime {
+        self.mtime
+    }
+
+    /// Returns the settings of the deterministic build mode if it is enabled in the environment of
+    /// the given platform or the process environment.
+    pub(crate) fn from_env(platform: &impl Platform) -> Option<Self> {
+        let source_date_epoch = source_date_epoch(platform);
+
+        read_env_var(platform, DETERMINISTIC_ENV_VAR)
+            .map_or(source_date_epoch.is_some(), |value| {
+                matches!(value.trim(), "true" | "1")
+            })
+            .then(|| {
+                Self::new(source_date_epoch.unwrap_or(
+                    SystemTime::UNIX_EPOCH + Duration::from_secs(DEFAULT_DETERMINISTIC_MTIME),
+                ))
+            })
+    }
+
+    /// Sets the modification time of the file or directory at the given path to the modification
+    /// time of these settings if it is later, like tools that support `SOURCE_DATE_EPOCH` do for
+    /// files they didn't create themselves.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the metadata of the file couldn't be read or its times couldn't be set.
+    pub fn clamp_mtime(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if fs::metadata(&path)?.modified()? > self.mtime {
+            self.set_mtime(path)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sets the access and modification time of the file or directory at the given path.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file couldn't be opened or its times couldn't be set.
+    pub fn set_mtime(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        File::open(path)?.set_times(
+            FileTimes::new()
+                .set_accessed(self.mtime)
+                .set_modified(self.mtime),
+        )
+    }
+
+    /// Writes the given contents to the file at the given path, like [`std::fs::write`], and sets
+    /// its modification time.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file couldn't be written or its times couldn't be set.
+    pub fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        fs::write(&path, contents)?;
+        self.set_mtime(path)
+    }
+
+    /// Sets the modification time of the given directory and everything in it.
+    ///
+    /// Symlinks are not followed and keep their own modification time.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the directory couldn't be read or the times of an entry couldn't be set.
+    pub fn normalize_dir(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+
+            if file_type.is_dir() {
+                self.normalize_dir(entry.path())?;
+            } else if file_type.is_file() {
+                self.set_mtime(entry.path())?;
+            }
+        }
+
+        // Set last, since adding entries changes the modification time of the directory.
+        self.set_mtime(path)
+    }
+}
+
+/// Reads the timestamp in [`SOURCE_DATE_EPOCH_ENV_VAR`] from the environment of the given platform
+/// or the process environment.
+///
+/// Returns `None` if the variable isn't set or isn't a number of seconds since the Unix epoch.
+///
+/// # Examples
+/// ```
+/// use libcnb::generic::GenericPlatform;
+/// use libcnb::{source_date_epoch, Env, SOURCE_DATE_EPOCH_ENV_VAR};
+/// use std::time::{Duration, SystemTime};
+///
+/// let platform = GenericPlatform::new(Env::new().with(SOURCE_DATE_EPOCH_ENV_VAR, "1700000000"));
+/// assert_eq!(
+///     source_date_epoch(&platform),
+///     Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+/// );
+/// ```
+pub fn source_date_epoch(platform: &impl Platform) -> Option<SystemTime> {
+    read_env_var(platform, SOURCE_DATE_EPOCH_ENV_VAR)
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+fn read_env_var(platform: &impl Platform, name: &str) -> Option<String> {
+    platform.env_var(name).or_else(|| std::env::var(name).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generic::GenericPlatform;
+    use crate::Env;
+
+    #[test]
+    fn from_env() {
+        let mut env = Env::new();
+        assert_eq!(
+            Deterministic::from_env(&GenericPlatform::new(env.clone())),
+            None
+        );
+
+        env.insert(DETERMINISTIC_ENV_VAR, "true");
+        assert_eq!(
+            Deterministic::from_env(&GenericPlatform::new(env.clone()))
+                .map(|deterministic| deterministic.mtime()),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(DEFAULT_DETERMINISTIC_MTIME))
+        );
+
+        env.insert(SOURCE_DATE_EPOCH_ENV_VAR, "1700000000");
+        assert_eq!(
+            Deterministic::from_env(&GenericPlatform::new(env.clone()))
+                .map(|deterministic| deterministic.mtime()),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+
+        env.insert(DETERMINISTIC_ENV_VAR, "false");
+        assert_eq!(
+            Deterministic::from_env(&GenericPlatform::new(env.clone())),
+            None
+        );
+
+        env.remove(DETERMINISTIC_ENV_VAR);
+        assert_eq!(
+            Deterministic::from_env(&GenericPlatform::new(env.clone()))
+                .map(|deterministic| deterministic.mtime()),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+    }
+
+    #[test]
+    fn source_date_epoch_from_env() {
+        let platform =
+            |value: &str| GenericPlatform::new(Env::new().with(SOURCE_DATE_EPOCH_ENV_VAR, value));
+
+        assert_eq!(
+            source_date_epoch(&platform("1700000000\n")),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+        assert_eq!(source_date_epoch(&platform("yesterday")), None);
+        assert_eq!(source_date_epoch(&platform("-1")), None);
+    }
+
+    #[test]
+    fn clamp_mtime() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let deterministic =
+            Deterministic::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        let older = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+
+        let new_file = temp_dir.path().join("new");
+        fs::write(&new_file, "").unwrap();
+        deterministic.clamp_mtime(&new_file).unwrap();
+        assert_eq!(
+            fs::metadata(&new_file).unwrap().modified().unwrap(),
+            deterministic.mtime()
+        );
+
+        let old_file = temp_dir.path().join("old");
+        Deterministic::new(older).write(&old_file, "").unwrap();
+        deterministic.clamp_mtime(&old_file).unwrap();
+        assert_eq!(fs::metadata(&old_file).unwrap().modified().unwrap(), older);
+    }
+
+    #[test]
+    fn normalize_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let deterministic =
+            Deterministic::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+
+        fs::create_dir_all(temp_dir.path().join("bin")).unwrap();
+        fs::write(temp_dir.path().join("bin/ruby"), "ruby").unwrap();
+        deterministic
+            .write(temp_dir.path().join("VERSION"), "3.3.1")
+            .unwrap();
+        deterministic.normalize_dir(temp_dir.path()).unwrap();
+
+        for path in ["", "bin", "bin/ruby", "VERSION"] {
+            assert_eq!(
+                fs::metadata(temp_dir.path().join(path))
+                    .unwrap()
+                    .modified()
+                    .unwrap(),
+                deterministic.mtime()
+            );
+        }
+    }
+}
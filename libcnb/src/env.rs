@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use crate::OsFamily;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::env::VarsOs;
 use std::ffi::{OsStr, OsString};
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::Deref;
 
 /// Generic collection of environment variables.
 ///
@@ -25,9 +28,37 @@ use std::ffi::{OsStr, OsString};
 ///     String::from_utf8_lossy(&output.stdout)
 /// );
 /// ```
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+///
+/// # Redaction
+///
+/// The [`Debug`] and [`Display`] implementations never print values of keys that look like they
+/// contain credentials (e.g. `DATABASE_PASSWORD` or `GITHUB_TOKEN`) or that were explicitly marked
+/// with [`Env::redact`]. This prevents accidentally leaking secrets passed via the platform when
+/// printing the environment. Redaction does not affect the actual values.
+///
+/// ```
+/// use libcnb::Env;
+///
+/// let mut env = Env::new().with("API_TOKEN", "hunter2").with("INTERNAL_URL", "https://internal");
+/// env.redact(["INTERNAL_URL"]);
+///
+/// assert_eq!(
+///     env.to_string(),
+///     "API_TOKEN=<redacted>\nINTERNAL_URL=<redacted>\n"
+/// );
+/// assert_eq!(env.get_string_lossy("API_TOKEN"), Some(String::from("hunter2")));
+/// ```
+///
+/// # Case sensitivity
+///
+/// Like the environment of the operating system, the keys of an `Env` are case-insensitive on
+/// Windows, see [`OsFamily::has_case_insensitive_env`]. Inserting `Path` replaces the value of an
+/// existing `PATH` variable there, keeping the original name.
+#[derive(Clone, Default)]
 pub struct Env {
     inner: HashMap<OsString, OsString>,
+    redacted_keys: HashSet<OsString>,
+    os_family: OsFamily,
 }
 
 impl Env {
@@ -52,14 +83,130 @@ impl Env {
     /// Inserts a key-value pair into the environment, overriding the value if `key` was already
     /// present.
     pub fn insert(&mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> &mut Self {
-        self.inner.insert(key.into(), value.into());
+        let key = key.into();
+        let key = self.existing_key(&key).cloned().unwrap_or(key);
+        self.inner.insert(key, value.into());
+        self
+    }
+
+    /// Returns a copy of this `Env` with the given key-value pair inserted, overriding the value if
+    /// `key` was already present.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::Env;
+    ///
+    /// let base = Env::new().with("FOO", "BAR");
+    /// let env = base.with("FOO", "BAZ");
+    ///
+    /// assert_eq!(base.get_string_lossy("FOO"), Some(String::from("BAR")));
+    /// assert_eq!(env.get_string_lossy("FOO"), Some(String::from("BAZ")));
+    /// ```
+    #[must_use]
+    pub fn with(&self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        let mut env = self.clone();
+        env.insert(key, value);
+        env
+    }
+
+    /// Returns a copy of this `Env` with all variables of `other` layered on top. Values from
+    /// `other` take precedence over values in this `Env`. Keys redacted in either `Env` stay
+    /// redacted.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::Env;
+    ///
+    /// let process_env = Env::new().with("FOO", "process").with("BAR", "process");
+    /// let platform_env = Env::new().with("FOO", "platform");
+    ///
+    /// let env = process_env.overlay(&platform_env);
+    /// assert_eq!(env.get_string_lossy("FOO"), Some(String::from("platform")));
+    /// assert_eq!(env.get_string_lossy("BAR"), Some(String::from("process")));
+    /// ```
+    #[must_use]
+    pub fn overlay(&self, other: &Env) -> Self {
+        let mut env = self.clone();
+        for (key, value) in other {
+            env.insert(key, value);
+        }
+        env.redacted_keys
+            .extend(other.redacted_keys.iter().cloned());
+        env
+    }
+
+    /// Removes a key from the environment, returning its value if it was present.
+    pub fn remove(&mut self, key: impl AsRef<OsStr>) -> Option<OsString> {
+        let key = self.existing_key(key.as_ref())?.clone();
+        self.inner.remove(&key)
+    }
+
+    /// Marks the given keys as sensitive so that their values are redacted when this `Env` is
+    /// printed via [`Debug`] or [`Display`].
+    ///
+    /// Keys that look like they contain credentials are redacted automatically, see
+    /// [`Env::is_redacted`].
+    pub fn redact<I, K>(&mut self, keys: I) -> &mut Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<OsString>,
+    {
+        self.redacted_keys.extend(keys.into_iter().map(Into::into));
         self
     }
 
+    /// Returns true if the value of the given key is redacted when printing this `Env`.
+    ///
+    /// This is the case for keys explicitly marked via [`Env::redact`] and for keys that contain
+    /// one of `PASSWORD`, `PASSWD`, `SECRET`, `TOKEN`, `CREDENTIAL`, `API_KEY`, `ACCESS_KEY` or
+    /// `PRIVATE_KEY` (case-insensitive). Keys marked via [`Env::redact`] are compared
+    /// case-insensitively on Windows, like all keys of this `Env`.
+    #[must_use]
+    pub fn is_redacted(&self, key: impl AsRef<OsStr>) -> bool {
+        let key = key.as_ref();
+        let redacted = if self.os_family.has_case_insensitive_env() {
+            self.redacted_keys
+                .iter()
+                .any(|redacted_key| self.os_family.env_keys_equal(redacted_key, key))
+        } else {
+            self.redacted_keys.contains(key)
+        };
+
+        redacted || is_sensitive_key(key)
+    }
+
+    /// Starts a scoped modification of this `Env`.
+    ///
+    /// All modifications made through the returned [`ScopedEnv`] are reverted when it is dropped,
+    /// restoring the previous values of every touched key.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::Env;
+    ///
+    /// let mut env = Env::new().with("FOO", "BAR");
+    ///
+    /// {
+    ///     let mut scoped = env.scoped();
+    ///     scoped.insert("FOO", "BAZ").insert("HELLO", "World");
+    ///     assert_eq!(scoped.get_string_lossy("FOO"), Some(String::from("BAZ")));
+    /// }
+    ///
+    /// assert_eq!(env.get_string_lossy("FOO"), Some(String::from("BAR")));
+    /// assert!(!env.contains_key("HELLO"));
+    /// ```
+    pub fn scoped(&mut self) -> ScopedEnv<'_> {
+        ScopedEnv {
+            env: self,
+            saved: HashMap::new(),
+        }
+    }
+
     /// Returns the value corresponding to the given key.
     #[must_use]
     pub fn get(&self, key: impl AsRef<OsStr>) -> Option<&OsString> {
-        self.inner.get(key.as_ref())
+        self.existing_key(key.as_ref())
+            .and_then(|key| self.inner.get(key))
     }
 
     /// Returns the value corresponding to the given key, interpreted as Unicode data.
@@ -79,19 +226,199 @@ impl Env {
     /// Returns true if the environment contains a value for the specified key.
     #[must_use]
     pub fn contains_key(&self, key: impl AsRef<OsStr>) -> bool {
-        self.inner.contains_key(key.as_ref())
+        self.existing_key(key.as_ref()).is_some()
     }
 
     #[must_use]
     pub fn iter(&self) -> std::collections::hash_map::Iter<'_, OsString, OsString> {
         self.inner.iter()
     }
+
+    /// Creates an empty `Env` that handles keys like the environment of the given operating
+    /// system family, instead of [`OsFamily::current`].
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::{Env, OsFamily};
+    ///
+    /// let mut env = Env::for_os_family(OsFamily::Windows);
+    /// env.insert("Path", "C:\\Windows");
+    ///
+    /// assert_eq!(env.get_string_lossy("PATH"), Some(String::from("C:\\Windows")));
+    /// ```
+    #[must_use]
+    pub fn for_os_family(os_family: OsFamily) -> Self {
+        Self {
+            os_family,
+            ..Self::default()
+        }
+    }
+
+    // The key under which a value for the given key is stored, which differs from the given key in
+    // case only if keys are case-insensitive.
+    fn existing_key(&self, key: &OsStr) -> Option<&OsString> {
+        self.inner
+            .get_key_value(key)
+            .map(|(key, _)| key)
+            .or_else(|| {
+                if self.os_family.has_case_insensitive_env() {
+                    self.inner
+                        .keys()
+                        .find(|existing_key| self.os_family.env_keys_equal(existing_key, key))
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+const SENSITIVE_KEY_PARTS: &[&str] = &[
+    "PASSWORD",
+    "PASSWD",
+    "SECRET",
+    "TOKEN",
+    "CREDENTIAL",
+    "API_KEY",
+    "ACCESS_KEY",
+    "PRIVATE_KEY",
+];
+
+const REDACTED_VALUE: &str = "<redacted>";
+
+fn is_sensitive_key(key: &OsStr) -> bool {
+    let key = key.to_string_lossy().to_uppercase();
+    SENSITIVE_KEY_PARTS.iter().any(|part| key.contains(part))
+}
+
+impl Env {
+    fn printable(&self) -> BTreeMap<String, String> {
+        self.inner
+            .iter()
+            .map(|(key, value)| {
+                let value = if self.is_redacted(key) {
+                    String::from(REDACTED_VALUE)
+                } else {
+                    value.to_string_lossy().to_string()
+                };
+
+                (key.to_string_lossy().to_string(), value)
+            })
+            .collect()
+    }
+}
+
+impl PartialEq for Env {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl Eq for Env {}
+
+impl Debug for Env {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Env")
+            .field("inner", &self.printable())
+            .finish()
+    }
+}
+
+/// Formats the environment as `KEY=VALUE` lines, sorted by key, with sensitive values redacted.
+impl Display for Env {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (key, value) in self.printable() {
+            writeln!(f, "{key}={value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Guard for temporary modifications of an [`Env`], see [`Env::scoped`].
+pub struct ScopedEnv<'a> {
+    env: &'a mut Env,
+    saved: HashMap<OsString, Option<OsString>>,
+}
+
+impl ScopedEnv<'_> {
+    /// Inserts a key-value pair into the underlying environment until this guard is dropped.
+    pub fn insert(&mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> &mut Self {
+        let key = key.into();
+        self.save(&key);
+        self.env.insert(key, value);
+        self
+    }
+
+    /// Removes a key from the underlying environment until this guard is dropped.
+    pub fn remove(&mut self, key: impl AsRef<OsStr>) -> &mut Self {
+        self.save(key.as_ref());
+        self.env.remove(key);
+        self
+    }
+
+    fn save(&mut self, key: &OsStr) {
+        if !self.saved.contains_key(key) {
+            self.saved
+                .insert(key.to_os_string(), self.env.get(key).cloned());
+        }
+    }
+}
+
+impl Debug for ScopedEnv<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // The saved values are redacted like the values of the wrapped `Env`.
+        let saved = self
+            .saved
+            .iter()
+            .map(|(key, value)| {
+                let value = value.as_ref().map(|value| {
+                    if self.env.is_redacted(key) {
+                        String::from(REDACTED_VALUE)
+                    } else {
+                        value.to_string_lossy().to_string()
+                    }
+                });
+
+                (key.to_string_lossy().to_string(), value)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        f.debug_struct("ScopedEnv")
+            .field("env", &self.env)
+            .field("saved", &saved)
+            .finish()
+    }
+}
+
+impl Deref for ScopedEnv<'_> {
+    type Target = Env;
+
+    fn deref(&self) -> &Self::Target {
+        self.env
+    }
+}
+
+impl Drop for ScopedEnv<'_> {
+    fn drop(&mut self) {
+        for (key, value) in self.saved.drain() {
+            match value {
+                Some(value) => {
+                    self.env.insert(key, value);
+                }
+                None => {
+                    self.env.remove(key);
+                }
+            }
+        }
+    }
 }
 
 impl From<VarsOs> for Env {
     fn from(vars_os: VarsOs) -> Self {
         Self {
             inner: vars_os.collect(),
+            redacted_keys: HashSet::new(),
+            os_family: OsFamily::current(),
         }
     }
 }
@@ -107,10 +434,152 @@ impl<'a> IntoIterator for &'a Env {
 
 #[cfg(test)]
 mod tests {
+    use crate::{Env, OsFamily};
+    use std::ffi::OsString;
+
+    #[test]
+    fn overlay_prefers_other() {
+        let base = Env::new().with("A", "base").with("B", "base");
+        let other = Env::new().with("B", "other").with("C", "other");
+
+        let env = base.overlay(&other);
+
+        assert_eq!(env.get_string_lossy("A"), Some(String::from("base")));
+        assert_eq!(env.get_string_lossy("B"), Some(String::from("other")));
+        assert_eq!(env.get_string_lossy("C"), Some(String::from("other")));
+        assert_eq!(base.get_string_lossy("B"), Some(String::from("base")));
+    }
+
+    #[test]
+    fn overlay_keeps_redacted_keys_of_both() {
+        let mut base = Env::new().with("A", "base");
+        base.redact(["A"]);
+        let mut other = Env::new().with("B", "other");
+        other.redact(["B"]);
+
+        let env = base.overlay(&other);
+
+        assert!(env.is_redacted("A"));
+        assert!(env.is_redacted("B"));
+        assert!(!base.is_redacted("B"));
+    }
+
+    #[test]
+    fn scoped_restores_touched_keys() {
+        let mut env = Env::new().with("A", "1").with("B", "2");
+
+        {
+            let mut scoped = env.scoped();
+            scoped
+                .insert("A", "changed")
+                .insert("A", "changed twice")
+                .remove("B")
+                .insert("C", "new");
+
+            assert_eq!(
+                scoped.get_string_lossy("A"),
+                Some(String::from("changed twice"))
+            );
+            assert!(!scoped.contains_key("B"));
+            assert!(scoped.contains_key("C"));
+        }
+
+        assert_eq!(env, Env::new().with("A", "1").with("B", "2"));
+    }
+
+    #[test]
+    fn debug_and_display_redact_sensitive_values() {
+        let mut env = Env::new()
+            .with("PATH", "/usr/bin")
+            .with("db_password", "hunter2")
+            .with("CUSTOM", "secret-value");
+        env.redact(["CUSTOM"]);
+
+        let debug = format!("{env:?}");
+        let display = env.to_string();
+
+        for output in [debug, display] {
+            assert!(output.contains("/usr/bin"));
+            assert!(!output.contains("hunter2"));
+            assert!(!output.contains("secret-value"));
+        }
+
+        assert_eq!(
+            env.to_string(),
+            "CUSTOM=<redacted>\nPATH=/usr/bin\ndb_password=<redacted>\n"
+        );
+    }
+
+    #[test]
+    fn scoped_debug_redacts_saved_values() {
+        let mut env = Env::new()
+            .with("PATH", "/usr/bin")
+            .with("API_TOKEN", "hunter2")
+            .with("CUSTOM", "secret-value");
+        env.redact(["CUSTOM"]);
+
+        let mut scoped = env.scoped();
+        scoped
+            .insert("PATH", "/bin")
+            .insert("API_TOKEN", "changed")
+            .remove("CUSTOM");
+
+        let debug = format!("{scoped:?}");
+        assert!(debug.contains("/usr/bin"));
+        assert!(!debug.contains("hunter2"));
+        assert!(!debug.contains("secret-value"));
+        assert!(!debug.contains("changed"));
+    }
+
+    #[test]
+    fn redaction_of_case_insensitive_keys() {
+        let mut env = Env::for_os_family(OsFamily::Windows);
+        env.insert("Custom", "secret-value").redact(["CUSTOM"]);
+        assert!(env.is_redacted("custom"));
+        assert!(!env.to_string().contains("secret-value"));
+
+        let mut env = Env::for_os_family(OsFamily::Unix);
+        env.insert("Custom", "value").redact(["CUSTOM"]);
+        assert!(!env.is_redacted("Custom"));
+    }
+
+    #[test]
+    fn redaction_does_not_affect_equality() {
+        let mut redacted = Env::new().with("FOO", "BAR");
+        redacted.redact(["FOO"]);
+
+        assert_eq!(redacted, Env::new().with("FOO", "BAR"));
+    }
+
+    #[test]
+    fn case_sensitive_keys() {
+        let mut env = Env::for_os_family(OsFamily::Unix);
+        env.insert("Path", "a").insert("PATH", "b");
+
+        assert_eq!(env.get_string_lossy("Path"), Some(String::from("a")));
+        assert_eq!(env.get_string_lossy("PATH"), Some(String::from("b")));
+        assert!(!env.contains_key("path"));
+    }
+
+    #[test]
+    fn case_insensitive_keys() {
+        let mut env = Env::for_os_family(OsFamily::Windows);
+        env.insert("Path", "a").insert("PATH", "b");
+
+        assert_eq!(env.iter().count(), 1);
+        assert_eq!(env.get_string_lossy("path"), Some(String::from("b")));
+        assert_eq!(
+            env.iter().next(),
+            Some((&OsString::from("Path"), &OsString::from("b")))
+        );
+
+        assert_eq!(env.remove("PATH"), Some(OsString::from("b")));
+        assert!(!env.contains_key("Path"));
+    }
+
     #[test]
     #[cfg(target_family = "unix")]
     fn into_iterator() {
-        use crate::Env;
         use std::process::Command;
 
         let mut env = Env::new();
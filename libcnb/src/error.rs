@@ -1,7 +1,12 @@
-use crate::data::launch::ProcessTypeError;
+use crate::data::buildpack::{BuildpackId, BuildpackVersion};
+use crate::data::launch::{LaunchError, ProcessTypeError};
 use crate::layer::HandleLayerError;
+use crate::options::BuildpackOptionsError;
+use crate::platform::MissingPlatformEnvError;
+use crate::{AppDirChange, InvalidMetadataField, Target};
 use libcnb_common::toml_file::TomlFileError;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
+use std::path::{Path, PathBuf};
 
 /// A specialized Result type for libcnb.
 ///
@@ -32,18 +37,30 @@ pub enum Error<E> {
     #[error("Couldn't create platform from platform path: {0}")]
     CannotCreatePlatformFromPath(std::io::Error),
 
+    #[error("Platform environment is incomplete: {0}")]
+    MissingPlatformEnv(#[from] MissingPlatformEnvError),
+
+    #[error("{0}")]
+    InvalidBuildpackOptions(#[from] BuildpackOptionsError),
+
     #[error("Couldn't read buildpack plan: {0}")]
     CannotReadBuildpackPlan(TomlFileError),
 
     #[error("Couldn't read buildpack.toml: {0}")]
     CannotReadBuildpackDescriptor(TomlFileError),
 
+    #[error("Invalid metadata in buildpack.toml: {}", join_errors(.0))]
+    InvalidBuildpackMetadata(Vec<InvalidMetadataField>),
+
     #[error("Couldn't read store.toml: {0}")]
     CannotReadStore(TomlFileError),
 
     #[error("Couldn't write build plan: {0}")]
     CannotWriteBuildPlan(TomlFileError),
 
+    #[error("Invalid launch.toml: {}", join_errors(.0))]
+    InvalidLaunch(Vec<LaunchError>),
+
     #[error("Couldn't write launch.toml: {0}")]
     CannotWriteLaunch(TomlFileError),
 
@@ -56,13 +73,302 @@ pub enum Error<E> {
     #[error("Couldn't write launch SBOM files: {0}")]
     CannotWriteLaunchSbom(std::io::Error),
 
+    #[error("Couldn't read app directory to check it for changes: {0}")]
+    CannotReadAppDir(std::io::Error),
+
+    #[error("The buildpack changed the app directory: {}", join_errors(.0))]
+    AppDirModified(Vec<AppDirChange>),
+
+    #[error("{0}")]
+    UserFacing(#[from] UserFacingError),
+
+    #[error("{0}")]
+    IoContextError(#[from] IoContextError),
+
     #[error("Buildpack error: {0:?}")]
     BuildpackError(E),
 }
 
+fn join_errors(errors: &[impl Display]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A buildpack phase.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Phase {
+    Detect,
+    Build,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Phase::Detect => "detect",
+            Phase::Build => "build",
+        })
+    }
+}
+
+/// A snapshot of the context in which an error occurred, passed to
+/// [`Buildpack::on_error_with_context`](crate::Buildpack::on_error_with_context).
+///
+/// Since errors can occur before all of the context could be determined (for example when
+/// `buildpack.toml` couldn't be read), most fields are optional.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ErrorContext {
+    pub phase: Phase,
+    pub app_dir: Option<PathBuf>,
+    pub buildpack_id: Option<BuildpackId>,
+    pub buildpack_version: Option<BuildpackVersion>,
+    pub target: Option<Target>,
+}
+
+/// The broad category of a [`UserFacingError`].
+///
+/// Categories help users and tooling to tell apart errors the user can fix (i.e. problems with the
+/// application) from transient or internal ones.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ErrorCategory {
+    /// The error was caused by the application or its configuration and can be fixed by the user.
+    User,
+    /// The error was caused by a network issue and might go away when retrying.
+    Network,
+    /// The error was caused by the platform or the base image the buildpack runs on.
+    Platform,
+    /// The error is a bug or an otherwise unexpected condition in the buildpack.
+    Internal,
+    #[default]
+    Unspecified,
+}
+
+impl Display for ErrorCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ErrorCategory::User => "user",
+            ErrorCategory::Network => "network",
+            ErrorCategory::Platform => "platform",
+            ErrorCategory::Internal => "internal",
+            ErrorCategory::Unspecified => "unspecified",
+        })
+    }
+}
+
+/// An error with a message intended to be read by the user of a buildpack.
+///
+/// When a [`UserFacingError`] is returned from detect or build (it converts into [`Error`] via
+/// `?`), the default implementation of [`Buildpack::on_error`](crate::Buildpack::on_error) prints
+/// it as a formatted block containing the message, detail, cause and suggestion.
+///
+/// # Examples
+/// ```
+/// use libcnb::{ErrorCategory, UserFacingError, UserFacingResultExt};
+///
+/// fn read_config() -> Result<String, UserFacingError> {
+///     std::fs::read_to_string("/does/not/exist/config.toml")
+///         .with_user_message("Couldn't read config.toml")
+///         .map_err(|error| {
+///             error
+///                 .category(ErrorCategory::User)
+///                 .suggestion("Make sure config.toml exists in the root of your app.")
+///         })
+/// }
+///
+/// let error = read_config().unwrap_err();
+/// assert_eq!(error.message, "Couldn't read config.toml");
+/// assert_eq!(error.category, ErrorCategory::User);
+/// ```
+#[derive(Debug)]
+pub struct UserFacingError {
+    pub category: ErrorCategory,
+    pub message: String,
+    pub detail: Option<String>,
+    pub suggestion: Option<String>,
+    pub source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl UserFacingError {
+    #[must_use]
+    pub fn new(category: ErrorCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: message.into(),
+            detail: None,
+            suggestion: None,
+            source: None,
+        }
+    }
+
+    #[must_use]
+    pub fn category(mut self, category: ErrorCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    #[must_use]
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    #[must_use]
+    pub fn suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    #[must_use]
+    pub fn source(mut self, source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Formats this error as a block intended to be printed at the end of a failed build.
+    ///
+    /// ```
+    /// use libcnb::{ErrorCategory, UserFacingError};
+    ///
+    /// let error = UserFacingError::new(ErrorCategory::User, "Invalid Node.js version")
+    ///     .detail("The version '99' in package.json doesn't exist.")
+    ///     .suggestion("Use a version listed on https://nodejs.org/.");
+    ///
+    /// assert_eq!(
+    ///     error.to_block(),
+    ///     "[Error: Invalid Node.js version]\n\
+    ///      The version '99' in package.json doesn't exist.\n\
+    ///      \n\
+    ///      Suggestion: Use a version listed on https://nodejs.org/.\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_block(&self) -> String {
+        format!("[Error: {}]\n{}", self.message, self.body())
+    }
+
+    /// Formats the detail, cause and suggestion of this error, without the message header.
+    #[must_use]
+    pub fn body(&self) -> String {
+        let mut lines = Vec::new();
+
+        if let Some(detail) = &self.detail {
+            lines.push(detail.clone());
+        }
+
+        if let Some(source) = &self.source {
+            lines.push(format!("Cause: {source}"));
+        }
+
+        if let Some(suggestion) = &self.suggestion {
+            lines.push(String::new());
+            lines.push(format!("Suggestion: {suggestion}"));
+        }
+
+        lines
+            .iter()
+            .flat_map(|line| [line.as_str(), "\n"])
+            .collect()
+    }
+}
+
+impl Display for UserFacingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for UserFacingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// Extension trait to turn errors into [`UserFacingError`] values.
+pub trait UserFacingResultExt<T> {
+    /// Converts the error into a [`UserFacingError`] with the given message, keeping the original
+    /// error as its source.
+    fn with_user_message(
+        self,
+        message: impl Into<String>,
+    ) -> std::result::Result<T, UserFacingError>;
+}
+
+impl<T, E> UserFacingResultExt<T> for std::result::Result<T, E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn with_user_message(
+        self,
+        message: impl Into<String>,
+    ) -> std::result::Result<T, UserFacingError> {
+        self.map_err(|error| {
+            UserFacingError::new(ErrorCategory::Unspecified, message).source(error)
+        })
+    }
+}
+
 #[cfg(feature = "anyhow")]
 impl From<anyhow::Error> for Error<anyhow::Error> {
     fn from(error: anyhow::Error) -> Self {
         Self::BuildpackError(error)
     }
 }
+
+/// An [`std::io::Error`] annotated with the operation and path that caused it.
+///
+/// Created via [`IoResultExt::io_context`]. Converts into [`Error`] via `?`. The message only
+/// contains the operation and path, the underlying [`std::io::Error`] is the
+/// [`source`](std::error::Error::source) of the error.
+#[derive(thiserror::Error, Debug)]
+#[error("Couldn't {operation} {}", path.display())]
+pub struct IoContextError {
+    /// The operation that failed, for example `read` or `create directory`.
+    pub operation: String,
+    pub path: PathBuf,
+    pub source: std::io::Error,
+}
+
+/// Extension trait to annotate [`std::io::Error`] values with the operation and path that caused
+/// them.
+///
+/// # Examples
+/// ```
+/// use libcnb::IoResultExt;
+/// use std::error::Error;
+///
+/// let error = std::fs::read_to_string("/does/not/exist/Gemfile.lock")
+///     .io_context("read", "/does/not/exist/Gemfile.lock")
+///     .unwrap_err();
+///
+/// assert_eq!(
+///     error.to_string(),
+///     "Couldn't read /does/not/exist/Gemfile.lock"
+/// );
+/// assert!(error.source().is_some());
+/// ```
+pub trait IoResultExt<T> {
+    /// Annotates the error with the given operation and path.
+    fn io_context(
+        self,
+        operation: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> std::result::Result<T, IoContextError>;
+}
+
+impl<T> IoResultExt<T> for std::io::Result<T> {
+    fn io_context(
+        self,
+        operation: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> std::result::Result<T, IoContextError> {
+        self.map_err(|source| IoContextError {
+            operation: operation.into(),
+            path: path.as_ref().to_path_buf(),
+            source,
+        })
+    }
+}
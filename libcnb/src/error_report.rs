@@ -0,0 +1,97 @@
+use crate::error::{Error, ErrorCategory, Phase};
+use serde::{Deserialize, Serialize};
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+
+/// Environment variable that, if set, contains the path libcnb writes an [`ErrorReport`] to when
+/// detect or build fails. It is read from the platform env, falling back to the process
+/// environment.
+pub const ERROR_REPORT_PATH_ENV_VAR: &str = "LIBCNB_ERROR_REPORT_PATH";
+
+/// A machine-readable report of a failed detect or build phase.
+///
+/// If the [`ERROR_REPORT_PATH_ENV_VAR`] environment variable is set, libcnb writes this report as
+/// JSON to the path it contains when the buildpack fails. This allows CI systems and platforms to
+/// classify build failures without scraping the build log.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ErrorReport {
+    /// The phase that failed, either `detect` or `build`.
+    pub phase: String,
+    /// The [`ErrorCategory`] of the error, for example `user` or `internal`.
+    pub category: String,
+    /// The human-readable error message.
+    pub message: String,
+    /// The [`Debug`] representation of the error.
+    pub debug: String,
+    /// A backtrace captured when the error was handled by libcnb. Only present if backtraces are
+    /// enabled, see [`std::backtrace`].
+    pub backtrace: Option<String>,
+}
+
+impl ErrorReport {
+    pub(crate) fn new<E: Debug>(phase: Phase, error: &Error<E>) -> Self {
+        let category = error_category(error);
+        let backtrace = Backtrace::capture();
+
+        Self {
+            phase: phase.to_string(),
+            category: category.to_string(),
+            message: error.to_string(),
+            debug: format!("{error:?}"),
+            backtrace: (backtrace.status() == BacktraceStatus::Captured)
+                .then(|| backtrace.to_string()),
+        }
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> std::io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Returns the category of the given error. Buildpack errors are unspecified since libcnb can't
+/// know their category, all other framework errors are internal.
+pub(crate) fn error_category<E>(error: &Error<E>) -> ErrorCategory {
+    match error {
+        Error::UserFacing(user_facing_error) => user_facing_error.category,
+        Error::BuildpackError(_) => ErrorCategory::Unspecified,
+        Error::InvalidBuildpackOptions(_) => ErrorCategory::User,
+        _ => ErrorCategory::Internal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UserFacingError;
+
+    #[test]
+    fn report_for_user_facing_error() {
+        let error: Error<()> =
+            Error::UserFacing(UserFacingError::new(ErrorCategory::User, "Invalid Gemfile"));
+
+        let report = ErrorReport::new(Phase::Build, &error);
+
+        assert_eq!(report.phase, "build");
+        assert_eq!(report.category, "user");
+        assert_eq!(report.message, "Invalid Gemfile");
+    }
+
+    #[test]
+    fn report_is_written_as_json() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("report.json");
+
+        let error: Error<&str> = Error::BuildpackError("boom");
+        let report = ErrorReport::new(Phase::Detect, &error);
+        report.write(&path).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(written["phase"], "detect");
+        assert_eq!(written["category"], "unspecified");
+        assert_eq!(written["message"], "Buildpack error: \"boom\"");
+    }
+}
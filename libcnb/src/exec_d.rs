@@ -5,6 +5,9 @@ use std::io::Write;
 
 /// Writes the output of a CNB exec.d program in a spec compliant way.
 ///
+/// Values that append or prepend to a variable are resolved against the environment of the
+/// exec.d program, which is the launch environment, see [`ExecDProgramOutput::resolve`].
+///
 /// # Panics
 ///
 /// Panics if there was an error serializing the TOML output or writing to FD 3.
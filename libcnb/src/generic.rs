@@ -1,9 +1,12 @@
 //! Generic implementations for some libcnb types.
 
-use crate::platform::Platform;
+use crate::platform::{read_platform_env_var, Platform};
+use crate::util::is_not_found_error_kind;
 use crate::{read_platform_env, Env};
 use std::fmt::{Debug, Display, Formatter};
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 pub use libcnb_data::generic::GenericMetadata;
 
@@ -17,23 +20,105 @@ impl Display for GenericError {
 }
 
 /// A generic platform that only provides access to environment variables.
+///
+/// When created with [`Platform::from_path`], the files in `<platform>/env` are only read when
+/// [`Platform::env`] is first called. Buildpacks that don't need the platform environment, such as
+/// many detect implementations, don't pay for reading it. [`Platform::env_var`] reads single
+/// variables without reading the whole environment.
 pub struct GenericPlatform {
-    env: Env,
+    platform_dir: Option<PathBuf>,
+    env: OnceLock<Env>,
 }
 
 impl GenericPlatform {
     #[must_use]
     pub fn new(env: Env) -> Self {
-        Self { env }
+        Self {
+            platform_dir: None,
+            env: OnceLock::from(env),
+        }
     }
 }
 
 impl Platform for GenericPlatform {
+    /// Returns the platform environment, reading it on first use.
+    ///
+    /// # Panics
+    ///
+    /// If the files in `<platform>/env` can't be read. The directory itself is checked by
+    /// [`Platform::from_path`], so this only happens if its contents change during the build.
     fn env(&self) -> &Env {
-        &self.env
+        self.env.get_or_init(|| {
+            self.platform_dir
+                .as_ref()
+                .map(read_platform_env)
+                .transpose()
+                .unwrap_or_else(|error| panic!("Couldn't read platform environment: {error}"))
+                .unwrap_or_default()
+        })
+    }
+
+    fn env_var(&self, name: &str) -> Option<String> {
+        match (self.env.get(), &self.platform_dir) {
+            (Some(env), _) => env.get_string_lossy(name),
+            // Failing to read a single variable is treated like a missing variable, the error is
+            // reported once the whole environment is read.
+            (None, Some(platform_dir)) => read_platform_env_var(platform_dir, name).ok().flatten(),
+            (None, None) => None,
+        }
     }
 
     fn from_path(platform_dir: impl AsRef<Path>) -> std::io::Result<Self> {
-        read_platform_env(platform_dir.as_ref()).map(|env| Self { env })
+        let platform_dir = platform_dir.as_ref();
+
+        // Fail early if the env directory exists but can't be read, like when reading it eagerly.
+        match fs::read_dir(platform_dir.join("env")) {
+            Ok(_) => {}
+            Err(error) if is_not_found_error_kind(&error) => {}
+            Err(error) => return Err(error),
+        }
+
+        Ok(Self {
+            platform_dir: Some(platform_dir.to_path_buf()),
+            env: OnceLock::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_env_lazily() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let env_dir = temp_dir.path().join("env");
+        fs::create_dir(&env_dir).unwrap();
+        fs::write(env_dir.join("RUBY_VERSION"), "3.3.1").unwrap();
+
+        let platform = GenericPlatform::from_path(temp_dir.path()).unwrap();
+        assert!(platform.env.get().is_none());
+
+        fs::write(env_dir.join("BUNDLE_WITHOUT"), "development").unwrap();
+        assert_eq!(
+            platform.env_var("RUBY_VERSION"),
+            Some(String::from("3.3.1"))
+        );
+        assert_eq!(platform.env_var("../env/RUBY_VERSION"), None);
+        assert!(platform.env.get().is_none());
+
+        assert_eq!(
+            platform.env().get_string_lossy("BUNDLE_WITHOUT"),
+            Some(String::from("development"))
+        );
+        fs::remove_file(env_dir.join("RUBY_VERSION")).unwrap();
+        assert_eq!(
+            platform.env_var("RUBY_VERSION"),
+            Some(String::from("3.3.1"))
+        );
+
+        let platform = GenericPlatform::from_path(temp_dir.path().join("missing")).unwrap();
+        assert_eq!(platform.env(), &Env::new());
+        assert_eq!(platform.env_var("RUBY_VERSION"), None);
     }
 }
@@ -4,3 +4,6 @@
 // would require every crate to explicitly import the `libcnb_proc_macros` crate as crates can't
 // use code from transitive dependencies.
 pub use libcnb_proc_macros::verify_bin_target_exists;
+
+// Used by the libcnb::verify_buildpack_version macro.
+pub use libcnb_proc_macros::verify_buildpack_version;
@@ -0,0 +1,223 @@
+use crate::layer_env::{LayerEnv, ModificationBehavior, Scope};
+use crate::OsFamily;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Installs an executable into the `bin` directory of a layer and adds that directory to `PATH`.
+///
+/// The lifecycle adds the `bin` directory of a layer to `PATH` during build if the layer is a
+/// build layer, and at launch if it is a launch layer. `InstallBinary` additionally adds it to the
+/// given [`LayerEnv`] for the configured scope, so that the executable is on `PATH` regardless of
+/// the layer types, for example only for a single process type.
+///
+/// # Examples
+/// ```no_run
+/// use libcnb::layer::{InstallBinary, InstallMethod};
+/// use libcnb::layer_env::{LayerEnv, Scope};
+///
+/// # fn example(layer_path: &std::path::Path) -> std::io::Result<()> {
+/// let mut layer_env = LayerEnv::new();
+///
+/// InstallBinary::new(layer_path.join("jdk-21/bin/java"), "java")
+///     .method(InstallMethod::Symlink)
+///     .scope(Scope::Build)
+///     .install(layer_path, &mut layer_env)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct InstallBinary {
+    source: PathBuf,
+    name: String,
+    method: InstallMethod,
+    scope: Scope,
+}
+
+/// How [`InstallBinary`] puts the executable into the `bin` directory.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InstallMethod {
+    /// Copies the executable and makes the copy executable by everyone.
+    Copy,
+    /// Symlinks the executable, which keeps its own permissions. Use this for executables that
+    /// are already part of the layer, or that find their files relative to their own path.
+    Symlink,
+}
+
+impl InstallBinary {
+    /// Installs the executable at the given path with the given file name, copying it and adding
+    /// the `bin` directory to `PATH` for [`Scope::All`].
+    #[must_use]
+    pub fn new(source: impl Into<PathBuf>, name: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            name: name.into(),
+            method: InstallMethod::Copy,
+            scope: Scope::All,
+        }
+    }
+
+    #[must_use]
+    pub fn method(mut self, method: InstallMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// The scope for which the `bin` directory is added to `PATH`.
+    #[must_use]
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Installs the executable into `<layer_path>/bin`, replacing an existing file with the same
+    /// name, and prepends that directory to `PATH` in the given [`LayerEnv`]. Installing several
+    /// executables with the same scope adds the directory only once.
+    ///
+    /// Returns the path of the installed executable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the name isn't a plain file name, or the executable couldn't be copied,
+    /// linked or made executable.
+    pub fn install(self, layer_path: &Path, layer_env: &mut LayerEnv) -> io::Result<PathBuf> {
+        let mut components = Path::new(&self.name).components();
+        if !matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        ) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid executable name: {:?}", self.name),
+            ));
+        }
+
+        let bin_dir = layer_path.join("bin");
+        let destination = bin_dir.join(&self.name);
+        fs::create_dir_all(&bin_dir)?;
+
+        // Replaces the file instead of writing through it, since it might be a symlink.
+        match fs::remove_file(&destination) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+            _ => {}
+        }
+
+        match self.method {
+            InstallMethod::Copy => {
+                fs::copy(&self.source, &destination)?;
+
+                #[cfg(target_family = "unix")]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+
+                    let mut permissions = fs::metadata(&destination)?.permissions();
+                    permissions.set_mode(permissions.mode() | 0o755);
+                    fs::set_permissions(&destination, permissions)?;
+                }
+            }
+            #[cfg(target_family = "unix")]
+            InstallMethod::Symlink => std::os::unix::fs::symlink(&self.source, &destination)?,
+            #[cfg(target_family = "windows")]
+            InstallMethod::Symlink => {
+                std::os::windows::fs::symlink_file(&self.source, &destination)?
+            }
+        }
+
+        layer_env.insert(
+            self.scope.clone(),
+            ModificationBehavior::Prepend,
+            "PATH",
+            &bin_dir,
+        );
+        layer_env.insert(
+            self.scope,
+            ModificationBehavior::Delimiter,
+            "PATH",
+            OsFamily::current().path_list_separator(),
+        );
+
+        Ok(destination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Env;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn install_copy() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("build-tool");
+        fs::write(&source, "#!/bin/sh").unwrap();
+        fs::set_permissions(&source, fs::Permissions::from_mode(0o600)).unwrap();
+        let layer_path = temp_dir.path().join("layer");
+
+        let mut layer_env = LayerEnv::new();
+        let installed = InstallBinary::new(&source, "tool")
+            .install(&layer_path, &mut layer_env)
+            .unwrap();
+        InstallBinary::new(&source, "tool-alias")
+            .install(&layer_path, &mut layer_env)
+            .unwrap();
+
+        assert_eq!(installed, layer_path.join("bin/tool"));
+        assert_eq!(fs::read_to_string(&installed).unwrap(), "#!/bin/sh");
+        assert_eq!(
+            fs::metadata(&installed).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+
+        let env = layer_env.apply(Scope::Launch, &Env::new().with("PATH", "/usr/bin"));
+        assert_eq!(
+            env.get("PATH").unwrap().to_string_lossy(),
+            format!("{}:/usr/bin", layer_path.join("bin").display())
+        );
+    }
+
+    #[test]
+    fn install_symlink() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layer_path = temp_dir.path();
+        fs::create_dir_all(layer_path.join("jdk/bin")).unwrap();
+        fs::write(layer_path.join("jdk/bin/java"), "").unwrap();
+        fs::create_dir_all(layer_path.join("bin")).unwrap();
+        fs::write(layer_path.join("bin/java"), "old").unwrap();
+
+        let mut layer_env = LayerEnv::new();
+        let installed = InstallBinary::new(layer_path.join("jdk/bin/java"), "java")
+            .method(InstallMethod::Symlink)
+            .scope(Scope::Process(String::from("web")))
+            .install(layer_path, &mut layer_env)
+            .unwrap();
+
+        assert_eq!(
+            fs::read_link(installed).unwrap(),
+            layer_path.join("jdk/bin/java")
+        );
+        assert_eq!(layer_env.apply_to_empty(Scope::Launch).get("PATH"), None);
+        assert_eq!(
+            layer_env
+                .apply_to_empty(Scope::Process(String::from("web")))
+                .get("PATH")
+                .unwrap(),
+            layer_path.join("bin").as_os_str()
+        );
+    }
+
+    #[test]
+    fn invalid_names() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        for name in ["", "..", "sub/tool", "/usr/bin/tool"] {
+            assert_eq!(
+                InstallBinary::new("/bin/sh", name)
+                    .install(temp_dir.path(), &mut LayerEnv::new())
+                    .unwrap_err()
+                    .kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+    }
+}
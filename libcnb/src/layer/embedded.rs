@@ -0,0 +1,166 @@
+use crate::{IoContextError, IoResultExt};
+use std::fs;
+use std::path::Path;
+
+/// A directory of static files embedded into the buildpack binary with
+/// [`include_layer_dir`](crate::include_layer_dir).
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedDir {
+    directories: &'static [&'static str],
+    files: &'static [EmbeddedFile],
+}
+
+impl EmbeddedDir {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(directories: &'static [&'static str], files: &'static [EmbeddedFile]) -> Self {
+        Self { directories, files }
+    }
+
+    /// The embedded files, sorted by their path.
+    #[must_use]
+    pub fn files(&self) -> &'static [EmbeddedFile] {
+        self.files
+    }
+
+    /// Returns the embedded file with the given path, relative to the embedded directory and
+    /// separated by `/`.
+    #[must_use]
+    pub fn get(&self, path: &str) -> Option<&'static EmbeddedFile> {
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// Writes the embedded directories and files into the given directory, usually the path of a
+    /// layer. Missing directories are created and existing files are overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a directory couldn't be created or a file couldn't be written.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> Result<(), IoContextError> {
+        let dir = dir.as_ref();
+
+        for directory in self.directories {
+            let path = dir.join(directory);
+            fs::create_dir_all(&path).io_context("create directory", &path)?;
+        }
+
+        for file in self.files {
+            let path = dir.join(file.path);
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).io_context("create directory", parent)?;
+            }
+
+            fs::write(&path, file.contents).io_context("write", &path)?;
+            set_mode(&path, file.mode).io_context("set permissions of", &path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A file embedded into the buildpack binary as part of an [`EmbeddedDir`].
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedFile {
+    path: &'static str,
+    contents: &'static [u8],
+    mode: u32,
+}
+
+impl EmbeddedFile {
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(path: &'static str, contents: &'static [u8], mode: u32) -> Self {
+        Self {
+            path,
+            contents,
+            mode,
+        }
+    }
+
+    /// The path of the file, relative to the embedded directory and separated by `/`.
+    #[must_use]
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    #[must_use]
+    pub fn contents(&self) -> &'static [u8] {
+        self.contents
+    }
+
+    /// The Unix permissions the file is written with, either `0755` or `0644`.
+    #[must_use]
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(target_family = "unix"))]
+fn set_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+    use crate::include_layer_dir;
+    use std::os::unix::fs::PermissionsExt;
+
+    const FIXTURE: EmbeddedDir = include_layer_dir!("tests/fixtures/embedded_layer_dir");
+
+    #[test]
+    fn include_layer_dir() {
+        assert_eq!(
+            FIXTURE
+                .files()
+                .iter()
+                .map(EmbeddedFile::path)
+                .collect::<Vec<_>>(),
+            vec!["bin/start", "config/app.conf"]
+        );
+        assert_eq!(FIXTURE.get("bin/start").unwrap().mode(), 0o755);
+        assert_eq!(
+            FIXTURE.get("config/app.conf").unwrap().contents(),
+            b"listen = 8080\n"
+        );
+        assert!(FIXTURE.get("missing").is_none());
+    }
+
+    #[test]
+    fn write_to() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layer_path = temp_dir.path().join("layer");
+
+        FIXTURE.write_to(&layer_path).unwrap();
+
+        assert!(layer_path.join("config").is_dir());
+        assert_eq!(
+            fs::read_to_string(layer_path.join("config/app.conf")).unwrap(),
+            "listen = 8080\n"
+        );
+        assert_eq!(
+            fs::metadata(layer_path.join("bin/start"))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o755
+        );
+        assert_eq!(
+            fs::metadata(layer_path.join("config/app.conf"))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o644
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::data::layer::LayerName;
+use crate::util::SharedVec;
+
+/// What libcnb did with a layer handled via
+/// [`BuildContext::handle_layer`](crate::build::BuildContext::handle_layer).
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum LayerAction {
+    /// The layer didn't exist and was created.
+    Create,
+    /// The existing layer was deleted and created again.
+    Recreate,
+    /// The existing layer was updated.
+    Update,
+    /// The existing layer was kept as is.
+    Keep,
+}
+
+impl LayerAction {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Recreate => "recreate",
+            Self::Update => "update",
+            Self::Keep => "keep",
+        }
+    }
+}
+
+/// Collection of the layers handled during build, together with what libcnb did with them.
+///
+/// libcnb adds every layer that was successfully handled via
+/// [`BuildContext::handle_layer`](crate::build::BuildContext::handle_layer), so that buildpacks
+/// can report them, for example in the summary of their build output.
+///
+/// Cloning a `HandledLayers` value yields a handle to the same collection.
+#[derive(Clone, Debug, Default)]
+pub struct HandledLayers {
+    inner: SharedVec<(LayerName, LayerAction)>,
+}
+
+impl HandledLayers {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer to the collection.
+    pub fn push(&self, layer_name: LayerName, action: LayerAction) {
+        self.inner.push((layer_name, action));
+    }
+
+    /// Returns all layers handled so far, in the order they were handled.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<(LayerName, LayerAction)> {
+        self.inner.to_vec()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
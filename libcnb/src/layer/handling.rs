@@ -2,29 +2,156 @@
 #![allow(clippy::similar_names)]
 
 use crate::build::BuildContext;
+use crate::build_events::{self, BuildEvent};
 use crate::data::layer::LayerName;
 use crate::data::layer_content_metadata::LayerContentMetadata;
 use crate::generic::GenericMetadata;
-use crate::layer::{ExistingLayerStrategy, Layer, LayerData, MetadataMigration};
+use crate::layer::{
+    ExistingLayerStrategy, Layer, LayerAction, LayerData, MetadataMigration, PreviousLayer,
+};
 use crate::layer_env::LayerEnv;
 use crate::sbom::{cnb_sbom_path, Sbom};
+use crate::telemetry;
 use crate::util::{default_on_not_found, remove_dir_recursively};
-use crate::Buildpack;
-use crate::{write_toml_file, TomlFileError};
+use crate::{write_toml_atomic, TomlFileError};
+use crate::{Buildpack, Deterministic, Timings};
 use libcnb_data::sbom::SBOM_FORMATS;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 pub(crate) fn handle_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
+    context: &BuildContext<B>,
+    layer_name: LayerName,
+    layer: L,
+) -> Result<LayerData<L::Metadata>, HandleLayerErrorOrBuildpackError<B::Error>> {
+    // Only the parts that depend on the buildpack and layer types are generic, the
+    // instrumentation around them is not. This keeps the code that is duplicated for every layer
+    // type small, which matters for the size of buildpack binaries.
+    let layer_attribute = layer_name.to_string();
+    let start = Instant::now();
+
+    build_events::emit(BuildEvent::LayerStarted {
+        layer: layer_attribute.clone(),
+    });
+
+    let mut activity = LayerActivity::default();
+    let result = telemetry::in_span(format!("layer {layer_name}"), |span| {
+        span.set_attribute("layer.name", layer_name.as_str());
+        let result =
+            handle_layer_inner(context, layer_name, layer, &mut activity).and_then(|layer_data| {
+                match &context.deterministic {
+                    Some(deterministic) => {
+                        normalize_layer_files(deterministic, &context.layers_dir, &layer_data.name)
+                            .map_err(HandleLayerError::IoError)?;
+                        Ok(layer_data)
+                    }
+                    None => Ok(layer_data),
+                }
+            });
+
+        let outcome = match &result {
+            Ok(layer_data) => LayerOutcome::Success(&layer_data.path),
+            Err(HandleLayerErrorOrBuildpackError::HandleLayerError(error)) => {
+                LayerOutcome::Error(error)
+            }
+            Err(HandleLayerErrorOrBuildpackError::BuildpackError(error)) => {
+                LayerOutcome::BuildpackError(format!("{error:?}"))
+            }
+        };
+        record_layer_span(span, &activity, outcome, start);
+
+        result
+    });
+
+    if let (Ok(layer_data), Some(action)) = (&result, activity.action) {
+        context.handled_layers.push(layer_data.name.clone(), action);
+    }
+
+    record_layer_finished(
+        &context.timings,
+        layer_attribute,
+        &activity,
+        start,
+        result.is_ok(),
+    );
+
+    result
+}
+
+/// How handling a layer ended, for telemetry.
+enum LayerOutcome<'a> {
+    Success(&'a Path),
+    Error(&'a HandleLayerError),
+    BuildpackError(String),
+}
+
+fn record_layer_span(
+    span: &telemetry::Span,
+    activity: &LayerActivity,
+    outcome: LayerOutcome<'_>,
+    start: Instant,
+) {
+    if let Some(action) = activity.action {
+        span.set_attribute("layer.action", action.as_str());
+    }
+    if let Some(metadata_migration) = activity.metadata_migration {
+        span.set_attribute("layer.metadata_migration", metadata_migration);
+    }
+
+    match outcome {
+        LayerOutcome::Success(layer_path) => {
+            if span.is_recording() {
+                span.set_attribute("layer.size", directory_size(layer_path));
+            }
+        }
+        LayerOutcome::Error(error) => span.set_error(error),
+        LayerOutcome::BuildpackError(message) => span.set_error_message(message),
+    }
+    span.set_attribute("layer.duration", start.elapsed().as_secs_f64());
+}
+
+fn record_layer_finished(
+    timings: &Timings,
+    layer_attribute: String,
+    activity: &LayerActivity,
+    start: Instant,
+    success: bool,
+) {
+    let duration = start.elapsed();
+    telemetry::histogram("libcnb.layer.duration", "s")
+        .record(duration.as_secs_f64(), &[("layer", &layer_attribute)]);
+    timings.record(format!("layer {layer_attribute}"), duration);
+    build_events::emit(BuildEvent::LayerFinished {
+        layer: layer_attribute,
+        action: activity.action.map(|action| String::from(action.as_str())),
+        success,
+        duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+    });
+}
+
+/// What happened while handling a layer, recorded for telemetry and build events.
+#[derive(Default)]
+struct LayerActivity {
+    action: Option<LayerAction>,
+    /// One of `recreate_layer` or `replace_metadata`, if the layer metadata had to be migrated.
+    metadata_migration: Option<&'static str>,
+}
+
+fn handle_layer_inner<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
     context: &BuildContext<B>,
     layer_name: LayerName,
     mut layer: L,
+    activity: &mut LayerActivity,
 ) -> Result<LayerData<L::Metadata>, HandleLayerErrorOrBuildpackError<B::Error>> {
     match read_layer(&context.layers_dir, &layer_name) {
-        Ok(None) => handle_create_layer(context, &layer_name, &mut layer),
+        Ok(None) => {
+            activity.action = Some(LayerAction::Create);
+            handle_create_layer(context, &layer_name, &mut layer, None)
+        }
         Ok(Some(layer_data)) => {
             let existing_layer_strategy = layer
                 .existing_layer_strategy(context, &layer_data)
@@ -32,13 +159,35 @@ pub(crate) fn handle_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
 
             match existing_layer_strategy {
                 ExistingLayerStrategy::Recreate => {
+                    activity.action = Some(LayerAction::Recreate);
                     delete_layer(&context.layers_dir, &layer_name)?;
-                    handle_create_layer(context, &layer_name, &mut layer)
+                    handle_create_layer(context, &layer_name, &mut layer, None)
+                }
+                ExistingLayerStrategy::RecreateReusingFiles => {
+                    activity.action = Some(LayerAction::Recreate);
+                    let previous_layer = PreviousLayer::stash(&context.layers_dir, &layer_name)
+                        .map_err(HandleLayerError::IoError)?;
+                    delete_layer(&context.layers_dir, &layer_name)?;
+
+                    let result = handle_create_layer(
+                        context,
+                        &layer_name,
+                        &mut layer,
+                        previous_layer.as_ref(),
+                    );
+
+                    if let Some(previous_layer) = previous_layer {
+                        previous_layer.remove().map_err(HandleLayerError::IoError)?;
+                    }
+
+                    result
                 }
                 ExistingLayerStrategy::Update => {
+                    activity.action = Some(LayerAction::Update);
                     handle_update_layer(context, &layer_data, &mut layer)
                 }
                 ExistingLayerStrategy::Keep => {
+                    activity.action = Some(LayerAction::Keep);
                     // We need to rewrite the metadata even if we just want to keep the layer around
                     // since cached layers are restored without their types, causing the layer to be
                     // discarded.
@@ -64,7 +213,7 @@ pub(crate) fn handle_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
                 }
             }
         }
-        Err(ReadLayerError::LayerContentMetadataParseError(_)) => {
+        Err(ReadLayerError::LayerContentMetadataParseError { .. }) => {
             match read_layer::<GenericMetadata, _>(&context.layers_dir, &layer_name) {
                 Ok(Some(generic_layer_data)) => {
                     let metadata_migration_strategy = layer
@@ -76,9 +225,11 @@ pub(crate) fn handle_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
 
                     match metadata_migration_strategy {
                         MetadataMigration::RecreateLayer => {
+                            activity.metadata_migration = Some("recreate_layer");
                             delete_layer(&context.layers_dir, &layer_name)?;
                         }
                         MetadataMigration::ReplaceMetadata(migrated_metadata) => {
+                            activity.metadata_migration = Some("replace_metadata");
                             write_layer(
                                 &context.layers_dir,
                                 &layer_name,
@@ -93,7 +244,7 @@ pub(crate) fn handle_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
                         }
                     }
 
-                    handle_layer(context, layer_name, layer)
+                    handle_layer_inner(context, layer_name, layer, activity)
                 }
                 Ok(None) => Err(HandleLayerError::UnexpectedMissingLayer.into()),
                 Err(read_layer_error) => {
@@ -109,6 +260,7 @@ fn handle_create_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
     context: &BuildContext<B>,
     layer_name: &LayerName,
     layer: &mut L,
+    previous_layer: Option<&PreviousLayer>,
 ) -> Result<LayerData<L::Metadata>, HandleLayerErrorOrBuildpackError<B::Error>> {
     let layer_dir = context.layers_dir.join(layer_name.as_str());
 
@@ -116,9 +268,11 @@ fn handle_create_layer<B: Buildpack + ?Sized, L: Layer<Buildpack = B>>(
         .map_err(HandleLayerError::IoError)
         .map_err(HandleLayerErrorOrBuildpackError::HandleLayerError)?;
 
-    let layer_result = layer
-        .create(context, &layer_dir)
-        .map_err(HandleLayerErrorOrBuildpackError::BuildpackError)?;
+    let layer_result = match previous_layer {
+        Some(previous_layer) => layer.recreate(context, &layer_dir, previous_layer),
+        None => layer.create(context, &layer_dir),
+    }
+    .map_err(HandleLayerErrorOrBuildpackError::BuildpackError)?;
 
     write_layer(
         &context.layers_dir,
@@ -225,8 +379,11 @@ pub enum DeleteLayerError {
 
 #[derive(thiserror::Error, Debug)]
 pub enum ReadLayerError {
-    #[error("Layer content metadata couldn't be parsed!")]
-    LayerContentMetadataParseError(toml::de::Error),
+    #[error("Layer content metadata in {} couldn't be parsed:\n{source}", path.display())]
+    LayerContentMetadataParseError {
+        path: PathBuf,
+        source: Box<toml::de::Error>,
+    },
 
     #[error("Unexpected I/O error while reading layer: {0}")]
     IoError(#[from] std::io::Error),
@@ -304,6 +461,27 @@ fn delete_layer<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Returns the total size in bytes of all files in the given directory and its subdirectories.
+/// Symbolic links are not followed, unreadable entries are ignored.
+fn directory_size(path: &Path) -> i64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+
+            if metadata.is_dir() {
+                Some(directory_size(&entry.path()))
+            } else {
+                i64::try_from(metadata.len()).ok()
+            }
+        })
+        .sum()
+}
+
 fn replace_layer_sboms<P: AsRef<Path>>(
     layers_dir: P,
     layer_name: &LayerName,
@@ -322,10 +500,7 @@ fn replace_layer_sboms<P: AsRef<Path>>(
     }
 
     for sbom in sboms {
-        fs::write(
-            cnb_sbom_path(&sbom.format, layers_dir, layer_name),
-            &sbom.data,
-        )?;
+        sbom.write_to(cnb_sbom_path(&sbom.format, layers_dir, layer_name))?;
     }
 
     Ok(())
@@ -372,6 +547,33 @@ fn replace_layer_exec_d_programs<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Sets the modification time of the files libcnb writes for a layer, for the deterministic
+/// build mode. The contents of the layer itself are left to the buildpack.
+fn normalize_layer_files(
+    deterministic: &Deterministic,
+    layers_dir: &Path,
+    layer_name: &LayerName,
+) -> std::io::Result<()> {
+    deterministic.set_mtime(layers_dir.join(format!("{layer_name}.toml")))?;
+
+    for format in SBOM_FORMATS {
+        let sbom_path = cnb_sbom_path(format, layers_dir, layer_name);
+        if sbom_path.exists() {
+            deterministic.set_mtime(sbom_path)?;
+        }
+    }
+
+    let layer_dir = layers_dir.join(layer_name.as_str());
+    for dir_name in ["env", "env.build", "env.launch", "exec.d"] {
+        let dir = layer_dir.join(dir_name);
+        if dir.is_dir() {
+            deterministic.normalize_dir(dir)?;
+        }
+    }
+
+    deterministic.set_mtime(layer_dir)
+}
+
 fn write_layer_metadata<M: Serialize, P: AsRef<Path>>(
     layers_dir: P,
     layer_name: &LayerName,
@@ -381,7 +583,7 @@ fn write_layer_metadata<M: Serialize, P: AsRef<Path>>(
     fs::create_dir_all(layer_dir)?;
 
     let layer_content_metadata_path = layers_dir.as_ref().join(format!("{layer_name}.toml"));
-    write_toml_file(&layer_content_metadata, layer_content_metadata_path)?;
+    write_toml_atomic(&layer_content_metadata, layer_content_metadata_path)?;
 
     Ok(())
 }
@@ -398,7 +600,23 @@ fn write_layer<M: Serialize, P: AsRef<Path>>(
     let layers_dir = layers_dir.as_ref();
 
     write_layer_metadata(layers_dir, layer_name, layer_content_metadata)?;
+    write_layer_files(
+        layers_dir,
+        layer_name,
+        layer_env,
+        layer_exec_d_programs,
+        layer_sboms,
+    )
+}
 
+/// The part of [`write_layer`] that doesn't depend on the metadata type.
+fn write_layer_files(
+    layers_dir: &Path,
+    layer_name: &LayerName,
+    layer_env: &LayerEnv,
+    layer_exec_d_programs: ExecDPrograms,
+    layer_sboms: Sboms,
+) -> Result<(), WriteLayerError> {
     let layer_dir = layers_dir.join(layer_name.as_str());
     layer_env.write_to_layer_dir(layer_dir)?;
 
@@ -417,8 +635,41 @@ fn read_layer<M: DeserializeOwned, P: AsRef<Path>>(
     layers_dir: P,
     layer_name: &LayerName,
 ) -> Result<Option<LayerData<M>>, ReadLayerError> {
-    let layer_dir_path = layers_dir.as_ref().join(layer_name.as_str());
-    let layer_toml_path = layers_dir.as_ref().join(format!("{layer_name}.toml"));
+    let Some(raw_layer) = read_raw_layer(layers_dir.as_ref(), layer_name)? else {
+        return Ok(None);
+    };
+
+    let layer_content_metadata =
+        toml::from_str::<LayerContentMetadata<M>>(&raw_layer.toml_contents).map_err(|source| {
+            ReadLayerError::LayerContentMetadataParseError {
+                path: raw_layer.toml_path,
+                source: Box::new(source),
+            }
+        })?;
+
+    Ok(Some(LayerData {
+        name: layer_name.clone(),
+        path: raw_layer.dir_path,
+        env: raw_layer.env,
+        content_metadata: layer_content_metadata,
+    }))
+}
+
+/// A layer as read from disk, before its content metadata is parsed.
+struct RawLayer {
+    dir_path: PathBuf,
+    toml_path: PathBuf,
+    toml_contents: String,
+    env: LayerEnv,
+}
+
+/// The part of [`read_layer`] that doesn't depend on the metadata type.
+fn read_raw_layer(
+    layers_dir: &Path,
+    layer_name: &LayerName,
+) -> Result<Option<RawLayer>, ReadLayerError> {
+    let layer_dir_path = layers_dir.join(layer_name.as_str());
+    let layer_toml_path = layers_dir.join(format!("{layer_name}.toml"));
 
     if !layer_dir_path.exists() && !layer_toml_path.exists() {
         return Ok(None);
@@ -446,16 +697,13 @@ fn read_layer<M: DeserializeOwned, P: AsRef<Path>>(
     }
 
     let layer_toml_contents = fs::read_to_string(&layer_toml_path)?;
-    let layer_content_metadata = toml::from_str::<LayerContentMetadata<M>>(&layer_toml_contents)
-        .map_err(ReadLayerError::LayerContentMetadataParseError)?;
-
     let layer_env = LayerEnv::read_from_layer_dir(&layer_dir_path)?;
 
-    Ok(Some(LayerData {
-        name: layer_name.clone(),
-        path: layer_dir_path,
+    Ok(Some(RawLayer {
+        dir_path: layer_dir_path,
+        toml_path: layer_toml_path,
+        toml_contents: layer_toml_contents,
         env: layer_env,
-        content_metadata: layer_content_metadata,
     }))
 }
 
@@ -620,7 +868,7 @@ mod tests {
             other => {
                 panic!("Expected WriteLayerError::MissingExecDFile, but got {other:?}");
             }
-        };
+        }
     }
 
     #[test]
@@ -964,8 +1212,9 @@ mod tests {
         .unwrap();
 
         match super::read_layer::<GenericMetadata, _>(layers_dir, &layer_name) {
-            Err(ReadLayerError::LayerContentMetadataParseError(toml_error)) => {
-                assert_eq!(toml_error.span(), Some(19..20));
+            Err(ReadLayerError::LayerContentMetadataParseError { path, source }) => {
+                assert_eq!(path, layers_dir.join(format!("{layer_name}.toml")));
+                assert_eq!(source.span(), Some(19..20));
             }
             _ => panic!("Expected ReadLayerError::LayerContentMetadataParseError!"),
         }
@@ -1000,8 +1249,8 @@ mod tests {
         .unwrap();
 
         match super::read_layer::<TestLayerMetadata, _>(layers_dir, &layer_name) {
-            Err(ReadLayerError::LayerContentMetadataParseError(toml_error)) => {
-                assert_eq!(toml_error.span(), Some(110..148));
+            Err(ReadLayerError::LayerContentMetadataParseError { source, .. }) => {
+                assert_eq!(source.span(), Some(110..148));
             }
             _ => panic!("Expected ReadLayerError::LayerContentMetadataParseError!"),
         }
@@ -0,0 +1,181 @@
+use crate::layer::ExistingLayerStrategy;
+
+/// Derives [`LayerMetadata`](trait@LayerMetadata) for a struct.
+pub use libcnb_proc_macros::LayerMetadata;
+
+/// Layer metadata that knows which of its changes invalidate a cached layer.
+///
+/// Instead of comparing metadata by hand in [`Layer::existing_layer_strategy`], the metadata type
+/// declares how its fields affect the cached layer and the strategy is derived from the changes
+/// between the metadata of the cached layer and the current metadata:
+///
+/// - If a field that invalidates the layer changed, the layer is recreated.
+/// - Otherwise, if any other compared field changed, the layer is updated.
+/// - Otherwise, the layer is kept as-is.
+///
+/// This trait is usually derived. Fields marked with `#[invalidates]` invalidate the layer,
+/// fields marked with `#[ignored]` are never compared and all other fields cause the layer to be
+/// updated. Compared fields must implement [`PartialEq`].
+///
+/// # Examples
+/// ```
+/// use libcnb::layer::{ExistingLayerStrategy, LayerMetadata};
+///
+/// #[derive(LayerMetadata, Clone)]
+/// struct RubyLayerMetadata {
+///     #[invalidates]
+///     ruby_version: String,
+///     #[invalidates]
+///     stack: String,
+///     gemfile_lock_checksum: String,
+///     #[ignored]
+///     installed_at: String,
+/// }
+///
+/// let cached = RubyLayerMetadata {
+///     ruby_version: String::from("3.3.0"),
+///     stack: String::from("heroku-22"),
+///     gemfile_lock_checksum: String::from("abc"),
+///     installed_at: String::from("2024-04-01"),
+/// };
+///
+/// let current = RubyLayerMetadata {
+///     gemfile_lock_checksum: String::from("def"),
+///     installed_at: String::from("2024-04-02"),
+///     ..cached.clone()
+/// };
+///
+/// assert_eq!(current.updating_changes(&cached), vec!["gemfile_lock_checksum"]);
+/// assert_eq!(
+///     current.existing_layer_strategy(&cached),
+///     ExistingLayerStrategy::Update
+/// );
+/// ```
+///
+/// In a [`Layer`], the cached metadata is passed to
+/// [`Layer::existing_layer_strategy`]:
+///
+/// ```ignore
+/// fn existing_layer_strategy(
+///     &mut self,
+///     context: &BuildContext<Self::Buildpack>,
+///     layer_data: &LayerData<Self::Metadata>,
+/// ) -> Result<ExistingLayerStrategy, <Self::Buildpack as Buildpack>::Error> {
+///     Ok(self
+///         .metadata
+///         .existing_layer_strategy(&layer_data.content_metadata.metadata))
+/// }
+/// ```
+///
+/// Layers that can be updated must also implement [`Layer::update`] to return the current
+/// metadata, since the default implementation keeps the metadata of the cached layer.
+///
+/// [`Layer`]: crate::layer::Layer
+/// [`Layer::existing_layer_strategy`]: crate::layer::Layer::existing_layer_strategy
+/// [`Layer::update`]: crate::layer::Layer::update
+pub trait LayerMetadata {
+    /// Returns the names of the fields that changed compared to `cached` and invalidate the
+    /// cached layer.
+    fn invalidating_changes(&self, cached: &Self) -> Vec<&'static str>;
+
+    /// Returns the names of the fields that changed compared to `cached` and require the cached
+    /// layer to be updated.
+    fn updating_changes(&self, cached: &Self) -> Vec<&'static str>;
+
+    /// Determines the strategy for a cached layer with the given metadata.
+    fn existing_layer_strategy(&self, cached: &Self) -> ExistingLayerStrategy {
+        if !self.invalidating_changes(cached).is_empty() {
+            ExistingLayerStrategy::Recreate
+        } else if !self.updating_changes(cached).is_empty() {
+            ExistingLayerStrategy::Update
+        } else {
+            ExistingLayerStrategy::Keep
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(LayerMetadata, Clone)]
+    struct TestLayerMetadata {
+        #[invalidates]
+        version: String,
+        #[invalidates]
+        r#type: Option<String>,
+        checksum: String,
+        #[ignored]
+        timestamp: u64,
+    }
+
+    #[derive(LayerMetadata)]
+    struct TupleLayerMetadata(#[invalidates] u32, #[ignored] u32, String);
+
+    #[test]
+    fn derived_layer_metadata() {
+        let cached = TestLayerMetadata {
+            version: String::from("1.0.0"),
+            r#type: None,
+            checksum: String::from("abc"),
+            timestamp: 1,
+        };
+
+        let current = TestLayerMetadata {
+            timestamp: 2,
+            ..cached.clone()
+        };
+        assert_ne!(current.timestamp, cached.timestamp);
+        assert!(current.invalidating_changes(&cached).is_empty());
+        assert!(current.updating_changes(&cached).is_empty());
+        assert_eq!(
+            current.existing_layer_strategy(&cached),
+            ExistingLayerStrategy::Keep
+        );
+
+        let current = TestLayerMetadata {
+            checksum: String::from("def"),
+            ..cached.clone()
+        };
+        assert_eq!(current.updating_changes(&cached), vec!["checksum"]);
+        assert_eq!(
+            current.existing_layer_strategy(&cached),
+            ExistingLayerStrategy::Update
+        );
+
+        let current = TestLayerMetadata {
+            version: String::from("2.0.0"),
+            r#type: Some(String::from("full")),
+            checksum: String::from("def"),
+            ..cached.clone()
+        };
+        assert_eq!(
+            current.invalidating_changes(&cached),
+            vec!["version", "type"]
+        );
+        assert_eq!(
+            current.existing_layer_strategy(&cached),
+            ExistingLayerStrategy::Recreate
+        );
+    }
+
+    #[test]
+    fn derived_tuple_layer_metadata() {
+        let cached = TupleLayerMetadata(1, 1, String::from("abc"));
+
+        let current = TupleLayerMetadata(1, 2, String::from("abc"));
+        assert_ne!(current.1, cached.1);
+        assert_eq!(
+            current.existing_layer_strategy(&cached),
+            ExistingLayerStrategy::Keep
+        );
+        assert_eq!(
+            TupleLayerMetadata(1, 1, String::from("def")).updating_changes(&cached),
+            vec!["2"]
+        );
+        assert_eq!(
+            TupleLayerMetadata(2, 1, String::from("abc")).invalidating_changes(&cached),
+            vec!["0"]
+        );
+    }
+}
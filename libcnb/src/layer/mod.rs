@@ -1,10 +1,23 @@
 //! Provides types and helpers to work with layers.
 
+mod bin;
+mod embedded;
+mod handled;
 mod handling;
+mod metadata;
 mod public_interface;
+mod reuse;
+
+#[cfg(feature = "testing")]
+pub mod testing;
 
 #[cfg(test)]
 mod tests;
 
+pub use bin::{InstallBinary, InstallMethod};
+pub use embedded::{EmbeddedDir, EmbeddedFile};
+pub use handled::{HandledLayers, LayerAction};
 pub(crate) use handling::*;
+pub use metadata::LayerMetadata;
 pub use public_interface::*;
+pub use reuse::PreviousLayer;
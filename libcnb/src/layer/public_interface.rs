@@ -2,6 +2,7 @@ use crate::build::BuildContext;
 use crate::data::layer::LayerName;
 use crate::data::layer_content_metadata::{LayerContentMetadata, LayerTypes};
 use crate::generic::GenericMetadata;
+use crate::layer::PreviousLayer;
 use crate::layer_env::LayerEnv;
 use crate::sbom::Sbom;
 use crate::Buildpack;
@@ -50,6 +51,27 @@ pub trait Layer {
         layer_path: &Path,
     ) -> Result<LayerResult<Self::Metadata>, <Self::Buildpack as Buildpack>::Error>;
 
+    /// Recreates the layer from scratch, with access to the contents of the previous layer.
+    ///
+    /// This method is called instead of [`create`](Layer::create) when
+    /// [`existing_layer_strategy`](Layer::existing_layer_strategy) returns
+    /// [`ExistingLayerStrategy::RecreateReusingFiles`]. Like with `create`, `layer_path` will be an
+    /// empty directory. Files that are identical to the ones of the previous layer can be linked
+    /// from `previous` instead of being written again, see [`PreviousLayer`].
+    ///
+    /// The default implementation ignores the previous layer and calls [`create`](Layer::create).
+    ///
+    /// # Implementation Requirements
+    /// Implementations **MUST NOT** write to any other location than `layer_path`.
+    fn recreate(
+        &mut self,
+        context: &BuildContext<Self::Buildpack>,
+        layer_path: &Path,
+        previous: &PreviousLayer,
+    ) -> Result<LayerResult<Self::Metadata>, <Self::Buildpack as Buildpack>::Error> {
+        self.create(context, layer_path)
+    }
+
     /// This method will be called by libcnb when the layer already exists to determine the strategy
     /// used to deal with it. Implementations of this method can use the current layer contents and
     /// metadata to make that decision.
@@ -140,6 +162,10 @@ pub enum ExistingLayerStrategy {
     Keep,
     /// The existing layer should be deleted and then recreated from scratch.
     Recreate,
+    /// The existing layer should be recreated from scratch with the [`Layer::recreate`] method,
+    /// which can reuse files of the existing layer instead of writing them again. The existing
+    /// layer is deleted afterwards.
+    RecreateReusingFiles,
     /// The existing layer contents should be updated with the [`Layer::update`] method.
     Update,
 }
@@ -0,0 +1,237 @@
+use crate::data::layer::LayerName;
+use crate::util::{default_on_not_found, is_not_found_error_kind, remove_dir_recursively};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// The contents of a layer before it was recreated with
+/// [`ExistingLayerStrategy::RecreateReusingFiles`](crate::layer::ExistingLayerStrategy::RecreateReusingFiles).
+///
+/// Files of the previous layer can be linked into the new layer instead of being written again,
+/// which avoids rewriting large amounts of identical data, for example when a language runtime
+/// of the same version is extracted again:
+///
+/// ```no_run
+/// # fn example(previous: &libcnb::layer::PreviousLayer) -> std::io::Result<()> {
+/// // Reuse the gems that are still part of the Gemfile.lock, download the others.
+/// for gem in ["rack-3.0.10", "rails-7.1.3"] {
+///     if !previous.reuse(format!("gems/{gem}"))? {
+///         // Download and install the gem...
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Files are hardlinked if possible. If the previous and the new layer are on different file
+/// systems, files are copied instead, which uses reflinks on file systems that support them. Since
+/// hardlinked files share their contents with the previous layer, they must be replaced rather
+/// than modified in place while the layer is recreated.
+///
+/// The previous layer is removed once the layer has been recreated, or when this value is dropped.
+pub struct PreviousLayer {
+    path: PathBuf,
+    layer_path: PathBuf,
+    // Dropped after `Drop::drop` removed the previous layer, which removes the then empty directory.
+    _stash_dir: TempDir,
+}
+
+impl PreviousLayer {
+    /// Moves the layer directory out of the way so that the layer can be recreated. Returns `None`
+    /// if the layer has no directory.
+    pub(crate) fn stash(layers_dir: &Path, layer_name: &LayerName) -> io::Result<Option<Self>> {
+        let layer_path = layers_dir.join(layer_name.as_str());
+
+        // A new directory with a unique name can't collide with a layer or a previous stash. It is
+        // created in the layers directory so that files can be hardlinked from it.
+        let stash_dir = tempfile::Builder::new()
+            .prefix(".libcnb-previous-")
+            .tempdir_in(layers_dir)?;
+        let path = stash_dir.path().join(layer_name.as_str());
+
+        match fs::rename(&layer_path, &path) {
+            Ok(()) => Ok(Some(Self {
+                path,
+                layer_path,
+                _stash_dir: stash_dir,
+            })),
+            Err(error) if is_not_found_error_kind(&error) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The directory with the contents of the previous layer.
+    ///
+    /// The contents can be read to decide what to reuse, but must not be modified.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Links the file or directory at the given path, relative to the layer directory, from the
+    /// previous layer into the new layer at the same path. Directories are linked recursively,
+    /// missing parent directories in the new layer are created.
+    ///
+    /// Returns `false` if the path doesn't exist in the previous layer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the path already exists in the new layer or if it couldn't be linked.
+    pub fn reuse(&self, relative_path: impl AsRef<Path>) -> io::Result<bool> {
+        self.reuse_as(&relative_path, &relative_path)
+    }
+
+    /// Like [`PreviousLayer::reuse`], but links the file or directory to a different path in the
+    /// new layer, for example when a versioned directory is renamed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the destination already exists in the new layer or if the path
+    /// couldn't be linked.
+    pub fn reuse_as(
+        &self,
+        relative_path: impl AsRef<Path>,
+        destination: impl AsRef<Path>,
+    ) -> io::Result<bool> {
+        let source = self.path.join(relative_path);
+        let destination = self.layer_path.join(destination);
+
+        match source.symlink_metadata() {
+            Ok(_) => {
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                link_recursively(&source, &destination).map(|()| true)
+            }
+            Err(error) if is_not_found_error_kind(&error) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Removes the previous layer.
+    pub(crate) fn remove(self) -> io::Result<()> {
+        default_on_not_found(remove_dir_recursively(&self.path))
+    }
+}
+
+impl Drop for PreviousLayer {
+    fn drop(&mut self) {
+        // Unlike `TempDir`, this also removes directories without write permissions.
+        let _ = remove_dir_recursively(&self.path);
+    }
+}
+
+fn link_recursively(source: &Path, destination: &Path) -> io::Result<()> {
+    let file_type = source.symlink_metadata()?.file_type();
+
+    if file_type.is_dir() {
+        fs::create_dir(destination)?;
+        fs::set_permissions(destination, source.metadata()?.permissions())?;
+
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            link_recursively(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+
+        Ok(())
+    } else if file_type.is_symlink() {
+        symlink(&fs::read_link(source)?, destination)
+    } else {
+        fs::hard_link(source, destination).or_else(|_| fs::copy(source, destination).map(|_| ()))
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(target_family = "windows")]
+fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+    use crate::data::layer_name;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn stash_and_reuse() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+        let layer_path = layers_dir.join("ruby");
+
+        fs::create_dir_all(layer_path.join("lib/ruby")).unwrap();
+        fs::write(layer_path.join("lib/ruby/stdlib.rb"), "stdlib").unwrap();
+        fs::write(layer_path.join("VERSION"), "3.3.0").unwrap();
+        std::os::unix::fs::symlink("lib/ruby", layer_path.join("current")).unwrap();
+
+        let previous = PreviousLayer::stash(layers_dir, &layer_name!("ruby"))
+            .unwrap()
+            .unwrap();
+        assert!(!layer_path.exists());
+        fs::create_dir(&layer_path).unwrap();
+
+        assert!(previous.reuse("lib").unwrap());
+        assert!(previous.reuse("current").unwrap());
+        assert!(previous.reuse_as("VERSION", "share/VERSION").unwrap());
+        assert!(!previous.reuse("missing").unwrap());
+        assert!(previous.reuse("lib").is_err());
+
+        assert_eq!(
+            fs::metadata(layer_path.join("lib/ruby/stdlib.rb"))
+                .unwrap()
+                .ino(),
+            fs::metadata(previous.path().join("lib/ruby/stdlib.rb"))
+                .unwrap()
+                .ino()
+        );
+        assert_eq!(
+            fs::read_link(layer_path.join("current")).unwrap(),
+            Path::new("lib/ruby")
+        );
+
+        let previous_path = previous.path().to_path_buf();
+        previous.remove().unwrap();
+        assert!(!previous_path.exists());
+        assert_eq!(fs::read_dir(layers_dir).unwrap().count(), 1);
+        assert_eq!(
+            fs::read_to_string(layer_path.join("share/VERSION")).unwrap(),
+            "3.3.0"
+        );
+        assert_eq!(
+            fs::read_to_string(layer_path.join("current/stdlib.rb")).unwrap(),
+            "stdlib"
+        );
+
+        assert!(PreviousLayer::stash(layers_dir, &layer_name!("node"))
+            .unwrap()
+            .is_none());
+        assert_eq!(fs::read_dir(layers_dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn stash_next_to_dot_layer() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+        fs::create_dir_all(layers_dir.join("ruby")).unwrap();
+        fs::create_dir_all(layers_dir.join(".previous-ruby")).unwrap();
+        fs::write(layers_dir.join(".previous-ruby/VERSION"), "keep").unwrap();
+
+        let previous = PreviousLayer::stash(layers_dir, &layer_name!("ruby"))
+            .unwrap()
+            .unwrap();
+        drop(previous);
+
+        assert!(!layers_dir.join("ruby").exists());
+        assert_eq!(
+            fs::read_to_string(layers_dir.join(".previous-ruby/VERSION")).unwrap(),
+            "keep"
+        );
+        assert_eq!(fs::read_dir(layers_dir).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,435 @@
+//! Unit-testing support for [`Layer`] implementations.
+//!
+//! Requires the `testing` feature, which is intended to be enabled for `dev-dependencies` only.
+
+use crate::build::BuildContext;
+use crate::data::buildpack::ComponentBuildpackDescriptor;
+use crate::data::layer::LayerName;
+use crate::layer::{Layer, LayerData};
+use crate::testing::BuildContextBuilder;
+use crate::{Buildpack, Platform, Target};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// TOML files in the layers directory that don't belong to a layer.
+const NON_LAYER_TOML_FILES: [&str; 3] = ["launch.toml", "build.toml", "store.toml"];
+
+/// A [`BuildContext`] over temporary directories to drive a [`Layer`] through the same
+/// create/existing layer strategy/update cycles as in a real build, without Docker or `pack`.
+///
+/// Layers are handled with the same logic as [`BuildContext::handle_layer`], so the returned
+/// [`LayerData`] reflects the env files, layer content metadata and exec.d programs written to
+/// disk. Use [`LayerTestContext::next_build`] to simulate a subsequent build, in which only cached
+/// layers are restored.
+///
+/// # Examples
+/// ```no_run
+/// # use libcnb::build::{BuildContext, BuildResult};
+/// # use libcnb::detect::{DetectContext, DetectResult};
+/// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+/// # use libcnb::data::layer_content_metadata::LayerTypes;
+/// # use libcnb::layer::{Layer, LayerResult, LayerResultBuilder};
+/// # use std::path::Path;
+/// use libcnb::data::layer_name;
+/// use libcnb::layer::testing::LayerTestContext;
+///
+/// # struct ExampleBuildpack;
+/// #
+/// # impl libcnb::Buildpack for ExampleBuildpack {
+/// #     type Platform = GenericPlatform;
+/// #     type Metadata = GenericMetadata;
+/// #     type Error = GenericError;
+/// #
+/// #     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+/// #         unimplemented!()
+/// #     }
+/// #
+/// #     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+/// #         unimplemented!()
+/// #     }
+/// # }
+/// #
+/// # struct RubyLayer;
+/// #
+/// # impl Layer for RubyLayer {
+/// #     type Buildpack = ExampleBuildpack;
+/// #     type Metadata = GenericMetadata;
+/// #
+/// #     fn types(&self) -> LayerTypes {
+/// #         unimplemented!()
+/// #     }
+/// #
+/// #     fn create(
+/// #         &mut self,
+/// #         context: &BuildContext<Self::Buildpack>,
+/// #         layer_path: &Path,
+/// #     ) -> Result<LayerResult<Self::Metadata>, GenericError> {
+/// #         unimplemented!()
+/// #     }
+/// # }
+/// #
+/// let mut test_context = LayerTestContext::<ExampleBuildpack>::new();
+///
+/// let layer_data = test_context
+///     .handle_layer(layer_name!("ruby"), RubyLayer)
+///     .unwrap();
+/// assert!(layer_data.path.join("bin/ruby").exists());
+///
+/// test_context.next_build();
+/// test_context
+///     .handle_layer(layer_name!("ruby"), RubyLayer)
+///     .unwrap();
+/// ```
+pub struct LayerTestContext<B: Buildpack> {
+    /// The context that is passed to the layer. Its directories are temporary and removed when
+    /// the test context is dropped, except for `buildpack_dir`.
+    pub context: BuildContext<B>,
+    // Only kept to remove the temporary directories on drop.
+    _temp_dir: TempDir,
+}
+
+impl<B: Buildpack> LayerTestContext<B> {
+    /// Creates a test context for the buildpack of the crate under test, with the `buildpack.toml`
+    /// in `CARGO_MANIFEST_DIR` as the buildpack descriptor and buildpack directory.
+    ///
+    /// # Panics
+    ///
+    /// If `CARGO_MANIFEST_DIR` isn't set or its `buildpack.toml` can't be read, or if the temporary
+    /// directories can't be created.
+    #[must_use]
+    pub fn new() -> Self {
+        let temp_dir = tempfile::tempdir().expect("Couldn't create temporary directory");
+
+        Self {
+            context: BuildContextBuilder::new().build_in(temp_dir.path()),
+            _temp_dir: temp_dir,
+        }
+    }
+
+    /// Creates a test context with the given buildpack directory and descriptor.
+    ///
+    /// # Panics
+    ///
+    /// If the temporary directories or the platform can't be created.
+    #[must_use]
+    pub fn with_descriptor(
+        buildpack_dir: impl Into<PathBuf>,
+        buildpack_descriptor: ComponentBuildpackDescriptor<B::Metadata>,
+    ) -> Self {
+        let temp_dir = tempfile::tempdir().expect("Couldn't create temporary directory");
+
+        Self {
+            context: BuildContextBuilder::new()
+                .buildpack_dir(buildpack_dir)
+                .buildpack_descriptor(buildpack_descriptor)
+                .build_in(temp_dir.path()),
+            _temp_dir: temp_dir,
+        }
+    }
+
+    /// Sets a platform environment variable, as set by users with `pack build --env`.
+    ///
+    /// # Panics
+    ///
+    /// If the variable can't be written to the platform directory or the platform can't be
+    /// recreated from it.
+    #[must_use]
+    pub fn platform_env(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        fs::write(
+            self.context.platform_dir.join("env").join(key.as_ref()),
+            value.as_ref(),
+        )
+        .expect("Couldn't write platform environment variable");
+
+        self.context.platform = B::Platform::from_path(&self.context.platform_dir)
+            .expect("Couldn't create platform from temporary directory");
+        self
+    }
+
+    /// Sets the target of the build, which defaults to `linux/amd64` on Ubuntu 22.04.
+    #[must_use]
+    pub fn target(mut self, target: Target) -> Self {
+        self.context.target = target;
+        self
+    }
+
+    /// Handles the layer like [`BuildContext::handle_layer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`BuildContext::handle_layer`].
+    pub fn handle_layer<L: Layer<Buildpack = B>>(
+        &self,
+        layer_name: LayerName,
+        layer: L,
+    ) -> crate::Result<LayerData<L::Metadata>, B::Error> {
+        self.context.handle_layer(layer_name, layer)
+    }
+
+    /// Simulates the start of a subsequent build, as far as layers are concerned.
+    ///
+    /// Like the lifecycle, layers with `cache = false` are removed, and the layer types of cached
+    /// layers are reset, so that they are only kept if the layer sets them again.
+    ///
+    /// # Panics
+    ///
+    /// If the layers directory can't be read or modified.
+    pub fn next_build(&mut self) {
+        restore_layers(&self.context.layers_dir);
+    }
+
+    /// Returns the path of the layer with the given name.
+    #[must_use]
+    pub fn layer_path(&self, layer_name: &LayerName) -> PathBuf {
+        self.context.layers_dir.join(layer_name.as_str())
+    }
+
+    /// Returns the sorted file names of the exec.d programs of the layer with the given name.
+    #[must_use]
+    pub fn exec_d_programs(&self, layer_name: &LayerName) -> Vec<String> {
+        list_file_names(&self.layer_path(layer_name).join("exec.d"))
+    }
+
+    /// Returns a snapshot of the layers directory for [`assert_snapshot`](crate::testing::assert_snapshot),
+    /// see [`layers_snapshot`](crate::testing::layers_snapshot).
+    ///
+    /// The paths of the layers, app and platform directories are replaced with `<layers_dir>`,
+    /// `<app_dir>` and `<platform_dir>`.
+    #[must_use]
+    pub fn snapshot(&self) -> String {
+        crate::testing::layers_snapshot(
+            &self.context.layers_dir,
+            &[
+                (&self.context.layers_dir, "<layers_dir>"),
+                (&self.context.app_dir, "<app_dir>"),
+                (&self.context.platform_dir, "<platform_dir>"),
+            ],
+        )
+    }
+}
+
+impl<B: Buildpack> Default for LayerTestContext<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Restores the layers in the given layers directory like the lifecycle does for a subsequent
+/// build, see [`LayerTestContext::next_build`].
+///
+/// # Panics
+///
+/// If the layers directory can't be read or modified.
+pub(crate) fn restore_layers(layers_dir: &Path) {
+    for entry in fs::read_dir(layers_dir).expect("Couldn't read layers directory") {
+        let path = entry.expect("Couldn't read layers directory").path();
+        let is_layer_toml = path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+            && !NON_LAYER_TOML_FILES
+                .iter()
+                .any(|name| path.file_name().is_some_and(|file_name| file_name == *name));
+
+        if !is_layer_toml {
+            continue;
+        }
+
+        let mut layer_toml = fs::read_to_string(&path)
+            .expect("Couldn't read layer TOML")
+            .parse::<toml::Table>()
+            .expect("Couldn't parse layer TOML");
+
+        let cached = layer_toml
+            .remove("types")
+            .and_then(|types| types.get("cache").and_then(toml::Value::as_bool))
+            .unwrap_or(false);
+
+        if cached {
+            fs::write(&path, layer_toml.to_string()).expect("Couldn't write layer TOML");
+        } else {
+            let layer_path = path.with_extension("");
+            if layer_path.exists() {
+                fs::remove_dir_all(&layer_path).expect("Couldn't remove layer");
+            }
+            fs::remove_file(&path).expect("Couldn't remove layer TOML");
+        }
+    }
+}
+
+fn list_file_names(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_file())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::BuildResult;
+    use crate::data::layer_content_metadata::LayerTypes;
+    use crate::data::layer_name;
+    use crate::detect::{DetectContext, DetectResult};
+    use crate::generic::{GenericError, GenericMetadata, GenericPlatform};
+    use crate::layer::{ExistingLayerStrategy, LayerResult, LayerResultBuilder};
+    use crate::layer_env::{LayerEnv, ModificationBehavior, Scope};
+    use serde::{Deserialize, Serialize};
+
+    struct TestBuildpack;
+
+    impl Buildpack for TestBuildpack {
+        type Platform = GenericPlatform;
+        type Metadata = GenericMetadata;
+        type Error = GenericError;
+
+        fn detect(
+            &self,
+            _context: DetectContext<Self>,
+        ) -> crate::Result<DetectResult, Self::Error> {
+            unimplemented!()
+        }
+
+        fn build(&self, _context: BuildContext<Self>) -> crate::Result<BuildResult, Self::Error> {
+            unimplemented!()
+        }
+    }
+
+    struct VersionedLayer {
+        version: &'static str,
+        cache: bool,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+    struct VersionedLayerMetadata {
+        version: String,
+    }
+
+    impl Layer for VersionedLayer {
+        type Buildpack = TestBuildpack;
+        type Metadata = VersionedLayerMetadata;
+
+        fn types(&self) -> LayerTypes {
+            LayerTypes {
+                build: true,
+                launch: true,
+                cache: self.cache,
+            }
+        }
+
+        fn create(
+            &mut self,
+            context: &BuildContext<Self::Buildpack>,
+            layer_path: &Path,
+        ) -> Result<LayerResult<Self::Metadata>, GenericError> {
+            fs::write(layer_path.join("version"), self.version).unwrap();
+
+            LayerResultBuilder::new(VersionedLayerMetadata {
+                version: String::from(self.version),
+            })
+            .env(LayerEnv::new().chainable_insert(
+                Scope::All,
+                ModificationBehavior::Override,
+                "VERSION",
+                self.version,
+            ))
+            .exec_d_program("setup", context.buildpack_dir.join("exec-d-program"))
+            .build()
+        }
+
+        fn existing_layer_strategy(
+            &mut self,
+            _context: &BuildContext<Self::Buildpack>,
+            layer_data: &LayerData<Self::Metadata>,
+        ) -> Result<ExistingLayerStrategy, GenericError> {
+            Ok(
+                if layer_data.content_metadata.metadata.version == self.version {
+                    ExistingLayerStrategy::Keep
+                } else {
+                    ExistingLayerStrategy::Recreate
+                },
+            )
+        }
+    }
+
+    fn test_context(buildpack_dir: &Path) -> LayerTestContext<TestBuildpack> {
+        fs::write(buildpack_dir.join("exec-d-program"), "").unwrap();
+
+        LayerTestContext::with_descriptor(
+            buildpack_dir,
+            toml::from_str(
+                r#"
+                api = "0.10"
+
+                [buildpack]
+                id = "libcnb/test"
+                version = "1.0.0"
+                "#,
+            )
+            .unwrap(),
+        )
+        .platform_env("RUBY_VERSION", "3.3.1")
+    }
+
+    #[test]
+    fn layer_cycles() {
+        let buildpack_dir = tempfile::tempdir().unwrap();
+        let mut test_context = test_context(buildpack_dir.path());
+        let layer_name = layer_name!("ruby");
+
+        assert_eq!(
+            test_context
+                .context
+                .platform
+                .env()
+                .get_string_lossy("RUBY_VERSION"),
+            Some(String::from("3.3.1"))
+        );
+
+        let layer_data = test_context
+            .handle_layer(
+                layer_name.clone(),
+                VersionedLayer {
+                    version: "1.0.0",
+                    cache: true,
+                },
+            )
+            .unwrap();
+        assert_eq!(layer_data.content_metadata.metadata.version, "1.0.0");
+        assert_eq!(
+            layer_data.env.apply_to_empty(Scope::Build).get("VERSION"),
+            Some(&"1.0.0".into())
+        );
+        assert_eq!(test_context.exec_d_programs(&layer_name), ["setup"]);
+
+        // The cached layer is kept, even though the new layer would write a different file.
+        test_context.next_build();
+        fs::write(test_context.layer_path(&layer_name).join("version"), "kept").unwrap();
+        test_context
+            .handle_layer(
+                layer_name.clone(),
+                VersionedLayer {
+                    version: "1.0.0",
+                    cache: false,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(test_context.layer_path(&layer_name).join("version")).unwrap(),
+            "kept"
+        );
+
+        // Layers without cache aren't restored.
+        test_context.next_build();
+        assert!(!test_context.layer_path(&layer_name).exists());
+        assert!(test_context.exec_d_programs(&layer_name).is_empty());
+    }
+}
@@ -15,11 +15,14 @@ use crate::data::layer_content_metadata::LayerTypes;
 use crate::detect::{DetectContext, DetectResult, DetectResultBuilder};
 use crate::generic::{GenericMetadata, GenericPlatform};
 use crate::layer::{
-    handle_layer, ExistingLayerStrategy, Layer, LayerData, LayerResult, LayerResultBuilder,
-    MetadataMigration,
+    handle_layer, ExistingLayerStrategy, HandledLayers, Layer, LayerAction, LayerData, LayerResult,
+    LayerResultBuilder, MetadataMigration, PreviousLayer,
 };
 use crate::layer_env::{LayerEnv, ModificationBehavior, Scope};
-use crate::{read_toml_file, Buildpack, Env, Target, LIBCNB_SUPPORTED_BUILDPACK_API};
+use crate::{
+    read_toml_file, Buildpack, Deterministic, Env, Target, Timings, Warnings,
+    LIBCNB_SUPPORTED_BUILDPACK_API,
+};
 use libcnb_data::buildpack::{BuildpackTarget, BuildpackVersion, ComponentBuildpackDescriptor};
 use libcnb_data::buildpack_plan::BuildpackPlan;
 use libcnb_data::layer_content_metadata::LayerContentMetadata;
@@ -29,6 +32,7 @@ use serde::Serialize;
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use tempfile::{tempdir, TempDir};
 
 const TEST_LAYER_LAUNCH: bool = true;
@@ -197,6 +201,45 @@ fn create() {
     assert_eq!(update_file_contents, None);
 }
 
+#[test]
+fn create_deterministic() {
+    let temp_dir = tempdir().unwrap();
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let context = BuildContext {
+        deterministic: Some(Deterministic::new(mtime)),
+        ..build_context(&temp_dir)
+    };
+    let layer_name = layer_name!("my-layer");
+    let test_layer = TestLayer {
+        write_layer_env: Some(LayerEnv::new().chainable_insert(
+            Scope::Launch,
+            ModificationBehavior::Override,
+            "FOO",
+            "bar",
+        )),
+        ..TestLayer::default()
+    };
+
+    let layer_data = handle_layer(&context, layer_name.clone(), test_layer).unwrap();
+
+    let modified = |path: PathBuf| fs::metadata(path).unwrap().modified().unwrap();
+    assert_eq!(
+        modified(context.layers_dir.join(format!("{layer_name}.toml"))),
+        mtime
+    );
+    assert_eq!(modified(layer_data.path.join("env.launch")), mtime);
+    assert_eq!(
+        modified(layer_data.path.join("env.launch/FOO.override")),
+        mtime
+    );
+    assert_eq!(modified(layer_data.path.clone()), mtime);
+    // Files written by the buildpack itself are not normalized.
+    assert_ne!(
+        modified(layer_data.path.join(TEST_LAYER_CREATE_FILE_NAME)),
+        mtime
+    );
+}
+
 #[test]
 fn create_then_update() {
     let temp_dir = tempdir().unwrap();
@@ -454,6 +497,14 @@ fn create_then_keep() {
 
     assert_eq!(update_file_contents, None);
     assert_eq!(residue_file_contents, Some(String::from(residue_file_data)));
+
+    assert_eq!(
+        context.handled_layers.to_vec(),
+        [
+            (layer_name.clone(), LayerAction::Create),
+            (layer_name, LayerAction::Keep)
+        ]
+    );
 }
 
 #[test]
@@ -887,6 +938,141 @@ fn layer_env_read_write() {
     // See the Layer implementation for more asserts
 }
 
+#[test]
+fn create_then_recreate_reusing_files() {
+    struct ReusingTestLayer;
+
+    #[allow(clippy::panic_in_result_fn)]
+    impl Layer for ReusingTestLayer {
+        type Buildpack = TestBuildpack;
+        type Metadata = GenericMetadata;
+
+        fn types(&self) -> LayerTypes {
+            LayerTypes {
+                launch: true,
+                build: true,
+                cache: true,
+            }
+        }
+
+        fn create(
+            &mut self,
+            _context: &BuildContext<Self::Buildpack>,
+            layer_path: &Path,
+        ) -> Result<LayerResult<Self::Metadata>, <Self::Buildpack as Buildpack>::Error> {
+            fs::create_dir(layer_path.join("bin")).expect("Couldn't create directory");
+            fs::write(layer_path.join("bin/ruby"), "ruby").expect("Couldn't write file");
+            fs::write(layer_path.join("RESIDUE.txt"), "RESIDUE DATA").expect("Couldn't write file");
+
+            LayerResultBuilder::new(GenericMetadata::default()).build()
+        }
+
+        fn recreate(
+            &mut self,
+            _context: &BuildContext<Self::Buildpack>,
+            layer_path: &Path,
+            previous: &PreviousLayer,
+        ) -> Result<LayerResult<Self::Metadata>, <Self::Buildpack as Buildpack>::Error> {
+            assert!(fs::read_dir(layer_path).unwrap().next().is_none());
+            assert!(previous.reuse("bin").unwrap());
+
+            LayerResultBuilder::new(GenericMetadata::default()).build()
+        }
+
+        fn existing_layer_strategy(
+            &mut self,
+            _context: &BuildContext<Self::Buildpack>,
+            _layer_data: &LayerData<Self::Metadata>,
+        ) -> Result<ExistingLayerStrategy, <Self::Buildpack as Buildpack>::Error> {
+            Ok(ExistingLayerStrategy::RecreateReusingFiles)
+        }
+    }
+
+    let temp_dir = tempdir().unwrap();
+    let context = build_context(&temp_dir);
+    let layer_name = layer_name!("my-layer");
+
+    handle_layer(&context, layer_name.clone(), ReusingTestLayer).unwrap();
+    let handle_layer_result = handle_layer(&context, layer_name, ReusingTestLayer).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(handle_layer_result.path.join("bin/ruby")).unwrap(),
+        "ruby"
+    );
+    assert!(!handle_layer_result.path.join("RESIDUE.txt").exists());
+    assert_eq!(
+        fs::read_dir(temp_dir.path().join("layers"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<HashSet<_>>(),
+        HashSet::from(["my-layer".into(), "my-layer.toml".into()])
+    );
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn traces_layer_spans() {
+    let temp_dir = tempdir().unwrap();
+    let context = build_context(&temp_dir);
+    let layer_name = layer_name!("traced-layer");
+    let test_layer = TestLayer {
+        existing_layer_strategy: ExistingLayerStrategy::Update,
+        ..TestLayer::default()
+    };
+
+    let telemetry_path = "/tmp/libcnb-telemetry/libcnb_test-layer.jsonl";
+    _ = fs::remove_file(telemetry_path);
+
+    {
+        let _trace = crate::tracing::start_trace(
+            &context.buildpack_descriptor.buildpack,
+            "layer",
+            &context.platform_dir,
+            &[],
+        );
+        handle_layer(&context, layer_name.clone(), test_layer.clone()).unwrap();
+        handle_layer(&context, layer_name.clone(), test_layer).unwrap();
+    }
+
+    let contents = fs::read_to_string(telemetry_path).unwrap();
+    let spans: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter_map(|export| {
+            export["resourceSpans"][0]["scopeSpans"][0]["spans"]
+                .as_array()
+                .cloned()
+        })
+        .flatten()
+        .filter(|span| span["name"] == "layer traced-layer")
+        .collect();
+
+    let attribute = |span: &serde_json::Value, key: &str| {
+        span["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|attribute| attribute["key"] == key)
+            .map_or_else(
+                || panic!("Missing attribute {key} in {span}"),
+                |attribute| attribute["value"].clone(),
+            )
+    };
+
+    assert_eq!(spans.len(), 2);
+    assert_eq!(
+        attribute(&spans[0], "layer.action")["stringValue"],
+        "create"
+    );
+    assert_eq!(attribute(&spans[0], "layer.size")["intValue"], 3);
+    assert_eq!(
+        attribute(&spans[1], "layer.action")["stringValue"],
+        "update"
+    );
+    assert_eq!(attribute(&spans[1], "layer.size")["intValue"], 6);
+    assert!(attribute(&spans[1], "layer.duration")["doubleValue"].is_f64());
+}
+
 fn build_context(temp_dir: &TempDir) -> BuildContext<TestBuildpack> {
     let layers_dir = temp_dir.path().join("layers");
     let app_dir = temp_dir.path().join("app");
@@ -907,10 +1093,17 @@ fn build_context(temp_dir: &TempDir) -> BuildContext<TestBuildpack> {
             distro_name: Some(String::from("ubuntu")),
             distro_version: Some(String::from("22.04")),
         },
+        platform_dir: temp_dir.path().join("platform"),
+        warnings: Warnings::new(),
+        timings: Timings::new(),
+        handled_layers: HandledLayers::new(),
+        deterministic: None,
+        group: None,
         platform: GenericPlatform::new(Env::new()),
         buildpack_plan: BuildpackPlan {
             entries: Vec::new(),
         },
+        buildpack_plan_path: temp_dir.path().join("buildpack-plan.toml"),
         buildpack_descriptor: ComponentBuildpackDescriptor {
             api: LIBCNB_SUPPORTED_BUILDPACK_API,
             buildpack: crate::data::buildpack::Buildpack {
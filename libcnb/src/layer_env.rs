@@ -1,10 +1,10 @@
 //! Type-safe, in-memory, layer environment variables.
 
-use crate::Env;
-use std::cmp::Ordering;
+use crate::{Env, OsFamily};
+pub use libcnb_data::env::{EnvValue, ModificationBehavior};
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::path::Path;
 
@@ -137,23 +137,51 @@ impl LayerEnv {
     /// ```
     #[must_use]
     pub fn apply(&self, scope: Scope, env: &Env) -> Env {
-        let deltas = match scope {
-            Scope::All => vec![&self.all],
-            Scope::Build => vec![&self.all, &self.build, &self.layer_paths_build],
-            Scope::Launch => vec![&self.all, &self.launch, &self.layer_paths_launch],
+        let mut result_env = env.clone();
+        self.apply_to(scope, &mut result_env);
+        result_env
+    }
+
+    /// Applies this [`LayerEnv`] to the given [`Env`] in place for the given [`Scope`].
+    ///
+    /// Unlike [`apply`](Self::apply), this doesn't copy the [`Env`], which is cheaper when
+    /// applying the environments of many layers to a large environment:
+    ///
+    /// ```
+    /// use libcnb::layer_env::{LayerEnv, ModificationBehavior, Scope};
+    /// use libcnb::Env;
+    ///
+    /// let layer_envs = [
+    ///     LayerEnv::new().chainable_insert(Scope::All, ModificationBehavior::Prepend, "PATH", "/layers/ruby/bin"),
+    ///     LayerEnv::new().chainable_insert(Scope::All, ModificationBehavior::Prepend, "PATH", "/layers/gems/bin"),
+    /// ];
+    ///
+    /// let mut env = Env::new();
+    /// for layer_env in &layer_envs {
+    ///     layer_env.apply_to(Scope::Build, &mut env);
+    /// }
+    ///
+    /// assert_eq!(env.get("PATH").unwrap(), "/layers/gems/bin/layers/ruby/bin");
+    /// ```
+    pub fn apply_to(&self, scope: Scope, env: &mut Env) {
+        self.all.apply_to(env);
+
+        match scope {
+            Scope::All => {}
+            Scope::Build => {
+                self.build.apply_to(env);
+                self.layer_paths_build.apply_to(env);
+            }
+            Scope::Launch => {
+                self.launch.apply_to(env);
+                self.layer_paths_launch.apply_to(env);
+            }
             Scope::Process(process) => {
-                let mut process_deltas = vec![&self.all];
                 if let Some(process_specific_delta) = self.process.get(&process) {
-                    process_deltas.push(process_specific_delta);
+                    process_specific_delta.apply_to(env);
                 }
-
-                process_deltas
             }
-        };
-
-        deltas
-            .iter()
-            .fold(env.clone(), |env, delta| delta.apply(&env))
+        }
     }
 
     /// Applies this [`LayerEnv`] to an empty [`Env`] for the given [`Scope`].
@@ -240,6 +268,54 @@ impl LayerEnv {
         self
     }
 
+    /// Inserts a typed [`EnvValue`] into this `LayerEnv`.
+    ///
+    /// Unlike [`LayerEnv::insert`], the delimiter of appended and prepended values is inserted
+    /// together with the value.
+    ///
+    /// # Example:
+    /// ```
+    /// use libcnb::layer_env::{EnvValue, LayerEnv, Scope};
+    /// use libcnb::Env;
+    ///
+    /// let mut layer_env = LayerEnv::new();
+    /// layer_env.insert_value(
+    ///     Scope::All,
+    ///     "PATH",
+    ///     EnvValue::Prepend {
+    ///         value: "/layers/ruby/bin",
+    ///         delimiter: ":",
+    ///     },
+    /// );
+    ///
+    /// let mut env = Env::new();
+    /// env.insert("PATH", "/usr/bin");
+    ///
+    /// let modified_env = layer_env.apply(Scope::Build, &env);
+    /// assert_eq!(modified_env.get("PATH").unwrap(), "/layers/ruby/bin:/usr/bin");
+    /// ```
+    pub fn insert_value(
+        &mut self,
+        scope: Scope,
+        name: impl Into<OsString>,
+        value: EnvValue<impl Into<OsString>>,
+    ) {
+        let name = name.into();
+        let value = value.map(Into::into);
+
+        if let Some(delimiter) = value.delimiter() {
+            self.insert(
+                scope.clone(),
+                ModificationBehavior::Delimiter,
+                name.clone(),
+                delimiter.clone(),
+            );
+        }
+
+        let modification_behavior = value.modification_behavior();
+        self.insert(scope, modification_behavior, name, value.value().clone());
+    }
+
     /// Constructs a `LayerEnv` based on the given layer directory.
     ///
     /// Follows the rules described in the Cloud Native Buildpacks specification and adds implicit
@@ -303,7 +379,11 @@ impl LayerEnv {
                 };
 
                 target_delta.insert(ModificationBehavior::Prepend, name, path);
-                target_delta.insert(ModificationBehavior::Delimiter, name, PATH_LIST_SEPARATOR);
+                target_delta.insert(
+                    ModificationBehavior::Delimiter,
+                    name,
+                    OsFamily::current().path_list_separator(),
+                );
             }
         }
 
@@ -378,41 +458,6 @@ impl LayerEnv {
     }
 }
 
-/// Environment variable modification behavior.
-/// ([CNB spec: Environment Variable Modification Rules](https://github.com/buildpacks/spec/blob/main/buildpack.md#environment-variable-modification-rules))
-#[derive(Eq, PartialEq, Debug, Clone)]
-pub enum ModificationBehavior {
-    Append,
-    Default,
-    Delimiter,
-    Override,
-    Prepend,
-}
-
-impl Ord for ModificationBehavior {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // Explicit mapping used over macro based approach to avoid tying source order of elements
-        // to ordering logic.
-        fn index(value: &ModificationBehavior) -> i32 {
-            match value {
-                ModificationBehavior::Append => 0,
-                ModificationBehavior::Default => 1,
-                ModificationBehavior::Delimiter => 2,
-                ModificationBehavior::Override => 3,
-                ModificationBehavior::Prepend => 4,
-            }
-        }
-
-        index(self).cmp(&index(other))
-    }
-}
-
-impl PartialOrd for ModificationBehavior {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 /// The scope of an environment variable modification.
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub enum Scope {
@@ -432,55 +477,40 @@ impl LayerEnvDelta {
         Self::default()
     }
 
-    fn apply(&self, env: &Env) -> Env {
-        let mut result_env = env.clone();
-
+    /// Applies the delta to the given [`Env`] in place, following the rules of
+    /// [`EnvValue::apply`].
+    fn apply_to(&self, env: &mut Env) {
         for ((modification_behavior, name), value) in &self.entries {
-            match modification_behavior {
-                ModificationBehavior::Override => {
-                    result_env.insert(name, value);
-                }
-                ModificationBehavior::Default => {
-                    if !result_env.contains_key(name) {
-                        result_env.insert(name, value);
-                    }
-                }
-                ModificationBehavior::Append => {
-                    let mut previous_value = result_env.get(name).cloned().unwrap_or_default();
-
-                    if previous_value.len() > 0 {
-                        previous_value.push(self.delimiter_for(name));
-                    }
-
-                    previous_value.push(value);
-
-                    result_env.insert(name, previous_value);
-                }
-                ModificationBehavior::Prepend => {
-                    let previous_value = result_env.get(name).cloned().unwrap_or_default();
-
-                    let mut new_value = OsString::new();
-                    new_value.push(value);
-
-                    if !previous_value.is_empty() {
-                        new_value.push(self.delimiter_for(name));
-                        new_value.push(previous_value);
-                    }
-
-                    result_env.insert(name, new_value);
-                }
-                ModificationBehavior::Delimiter => (),
+            let value = value.clone();
+            let env_value = match modification_behavior {
+                ModificationBehavior::Override => EnvValue::Override(value),
+                ModificationBehavior::Default => EnvValue::Default(value),
+                ModificationBehavior::Append => EnvValue::Append {
+                    value,
+                    delimiter: self.delimiter_for(name).to_os_string(),
+                },
+                ModificationBehavior::Prepend => EnvValue::Prepend {
+                    value,
+                    delimiter: self.delimiter_for(name).to_os_string(),
+                },
+                ModificationBehavior::Delimiter => continue,
             };
-        }
 
-        result_env
+            let new_value = env_value.apply(env.get(name));
+            env.insert(name, new_value);
+        }
     }
 
-    fn delimiter_for(&self, key: impl Into<OsString>) -> OsString {
+    fn delimiter_for(&self, key: &OsStr) -> &OsStr {
+        // Delimiter entries are next to each other in the map, which is ordered by modification
+        // behavior first. Searching them avoids allocating an owned key for the lookup.
         self.entries
-            .get(&(ModificationBehavior::Delimiter, key.into()))
-            .cloned()
-            .unwrap_or_default()
+            .range((ModificationBehavior::Delimiter, OsString::new())..)
+            .take_while(|((modification_behavior, _), _)| {
+                *modification_behavior == ModificationBehavior::Delimiter
+            })
+            .find(|((_, name), _)| name == key)
+            .map_or(OsStr::new(""), |(_, delimiter)| delimiter.as_os_str())
     }
 
     fn read_from_env_dir(path: impl AsRef<Path>) -> Result<Self, std::io::Error> {
@@ -517,16 +547,11 @@ impl LayerEnvDelta {
             if let Some(file_name_stem) = file_name_stem {
                 let modification_behavior = match file_name_extension {
                     None => Some(ModificationBehavior::Override),
-                    Some(file_name_extension) => match file_name_extension.to_str() {
-                        Some("append") => Some(ModificationBehavior::Append),
-                        Some("default") => Some(ModificationBehavior::Default),
-                        Some("delim") => Some(ModificationBehavior::Delimiter),
-                        Some("override") => Some(ModificationBehavior::Override),
-                        Some("prepend") => Some(ModificationBehavior::Prepend),
-                        // Note: This IS NOT the case where we have no extension. This handles
-                        // the case of an unknown or non-UTF-8 extension.
-                        Some(_) | None => None,
-                    },
+                    // Note: This IS NOT the case where we have no extension. Unknown or non-UTF-8
+                    // extensions result in `None`.
+                    Some(file_name_extension) => file_name_extension
+                        .to_str()
+                        .and_then(ModificationBehavior::from_file_extension),
                 };
 
                 if let Some(modification_behavior) = modification_behavior {
@@ -554,16 +579,9 @@ impl LayerEnvDelta {
             fs::create_dir_all(path.as_ref())?;
 
             for ((modification_behavior, name), value) in &self.entries {
-                let file_extension = match modification_behavior {
-                    ModificationBehavior::Append => ".append",
-                    ModificationBehavior::Default => ".default",
-                    ModificationBehavior::Delimiter => ".delim",
-                    ModificationBehavior::Override => ".override",
-                    ModificationBehavior::Prepend => ".prepend",
-                };
-
                 let mut file_name = name.clone();
-                file_name.push(file_extension);
+                file_name.push(".");
+                file_name.push(modification_behavior.file_extension());
 
                 let file_path = path.as_ref().join(file_name);
 
@@ -594,12 +612,6 @@ impl LayerEnvDelta {
     }
 }
 
-#[cfg(target_family = "unix")]
-const PATH_LIST_SEPARATOR: &str = ":";
-
-#[cfg(target_family = "windows")]
-const PATH_LIST_SEPARATOR: &str = ";";
-
 #[cfg(test)]
 mod tests {
     use std::cmp::Ordering;
@@ -654,7 +666,8 @@ mod tests {
         original_env.insert("VAR_OVERRIDE", "value-override-orig");
 
         let layer_env_delta = LayerEnvDelta::read_from_env_dir(temp_dir.path()).unwrap();
-        let modified_env = layer_env_delta.apply(&original_env);
+        let mut modified_env = original_env.clone();
+        layer_env_delta.apply_to(&mut modified_env);
 
         assert_eq!(
             vec![
@@ -704,7 +717,8 @@ mod tests {
         original_env.insert("VAR_NORMAL_DELIM", "value-normal-delim-orig");
 
         let layer_env_delta = LayerEnvDelta::read_from_env_dir(temp_dir.path()).unwrap();
-        let modified_env = layer_env_delta.apply(&original_env);
+        let mut modified_env = original_env.clone();
+        layer_env_delta.apply_to(&mut modified_env);
 
         assert_eq!(
             vec![
@@ -921,6 +935,44 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn read_from_layer_dir_layer_paths_unix_delimiter() {
+        let temp_dir = tempdir().unwrap();
+        let layer_dir = temp_dir.path();
+        fs::create_dir_all(layer_dir.join("bin")).unwrap();
+
+        let layer_env = LayerEnv::read_from_layer_dir(layer_dir).unwrap();
+
+        let mut env = Env::new();
+        env.insert("PATH", "/usr/bin");
+        let env = layer_env.apply(Scope::Build, &env);
+
+        assert_eq!(
+            env.get_string_lossy("PATH").unwrap(),
+            format!("{}:/usr/bin", layer_dir.join("bin").display())
+        );
+    }
+
+    #[test]
+    #[cfg(target_family = "windows")]
+    fn read_from_layer_dir_layer_paths_windows_delimiter() {
+        let temp_dir = tempdir().unwrap();
+        let layer_dir = temp_dir.path();
+        fs::create_dir_all(layer_dir.join("bin")).unwrap();
+
+        let layer_env = LayerEnv::read_from_layer_dir(layer_dir).unwrap();
+
+        let mut env = Env::new();
+        env.insert("Path", "C:\\Windows\\System32");
+        let env = layer_env.apply(Scope::Build, &env);
+
+        assert_eq!(
+            env.get_string_lossy("PATH").unwrap(),
+            format!("{};C:\\Windows\\System32", layer_dir.join("bin").display())
+        );
+    }
+
     fn environment_as_sorted_vector(environment: &Env) -> Vec<(&str, &str)> {
         let mut result: Vec<(&str, &str)> = environment
             .iter()
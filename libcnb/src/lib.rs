@@ -1,43 +1,101 @@
 #![doc = include_str!("../README.md")]
 
+// Allows derive macros that expand to `::libcnb` paths to be used in this crate's tests.
+#[cfg(test)]
+extern crate self as libcnb;
+
+#[cfg(feature = "testing")]
+pub mod benchmark;
+pub mod bindings;
 pub mod build;
+pub mod command;
 pub mod detect;
 pub mod exec_d;
 pub mod generic;
 pub mod layer;
 pub mod layer_env;
+#[cfg(feature = "logging")]
+pub mod logging;
+pub mod options;
+pub mod output;
+pub mod retry;
 pub mod sbom;
+pub mod shell;
+pub mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 // Internals that need to be public for macros
 #[doc(hidden)]
 pub mod internals;
 
+mod app_dir_guard;
+mod build_events;
+mod build_output;
 mod buildpack;
+mod deterministic;
 mod env;
 mod error;
+mod error_report;
 mod exit_code;
+mod os;
 mod platform;
 mod runtime;
 mod target;
+mod timings;
 #[cfg(feature = "trace")]
 mod tracing;
 mod util;
+mod warnings;
 
-pub use buildpack::Buildpack;
+pub use app_dir_guard::{AppDirChange, AppDirGuardMode, AppDirSnapshot, APP_DIR_GUARD_ENV_VAR};
+pub use build_events::*;
+pub use buildpack::{Buildpack, InvalidMetadataField};
+pub use deterministic::{
+    source_date_epoch, Deterministic, DEFAULT_DETERMINISTIC_MTIME, DETERMINISTIC_ENV_VAR,
+    SOURCE_DATE_EPOCH_ENV_VAR,
+};
 pub use env::*;
 pub use error::*;
+pub use error_report::*;
+pub use libcnb_common::fs::write_atomic;
 pub use libcnb_common::toml_file::*;
+pub use os::*;
 pub use platform::*;
 pub use runtime::*;
 pub use target::*;
-
-#[cfg(all(test, not(feature = "trace")))]
-use serde_json as _;
+pub use timings::{Timings, TIMING_SUMMARY_ENV_VAR};
+pub use warnings::Warnings;
 
 /// Provides types for CNB data formats. Is a re-export of the `libcnb-data` crate.
 #[doc(inline)]
 pub use libcnb_data as data;
 
+/// Embeds a directory of static files into the buildpack binary.
+///
+/// The path is relative to the directory containing the buildpack's `Cargo.toml`. All files and
+/// directories within it are embedded recursively and can be written to a layer with
+/// [`EmbeddedDir::write_to`](crate::layer::EmbeddedDir::write_to). This removes the need to ship
+/// loose files alongside the buildpack binary, for example configuration files or wrapper scripts.
+///
+/// Executable files are written with mode `0755`, all other files with mode `0644`.
+///
+/// Changes to the contents of embedded files cause the buildpack to be recompiled. Files that are
+/// added to or removed from the directory are only picked up when the buildpack is recompiled for
+/// another reason.
+///
+/// # Example:
+/// ```ignore
+/// use libcnb::include_layer_dir;
+/// use libcnb::layer::EmbeddedDir;
+///
+/// const NGINX_CONFIG: EmbeddedDir = include_layer_dir!("assets/nginx");
+///
+/// // In `Layer::create`:
+/// NGINX_CONFIG.write_to(layer_path)?;
+/// ```
+pub use libcnb_proc_macros::include_layer_dir;
+
 const LIBCNB_SUPPORTED_BUILDPACK_API: data::buildpack::BuildpackApi =
     data::buildpack::BuildpackApi {
         major: 0,
@@ -93,6 +151,15 @@ macro_rules! buildpack_main {
 /// Cargo will [automatically configure it as a binary target](https://doc.rust-lang.org/cargo/reference/cargo-targets.html#target-auto-discovery)
 /// with the name of file.
 ///
+/// Binaries of other packages in the same Cargo workspace, for example a crate with exec.d
+/// programs shared by several buildpacks, can be used as well. Their packages need to be listed
+/// in the buildpack's `Cargo.toml`, so that their binary targets are packaged with the buildpack:
+///
+/// ```toml
+/// [package.metadata.libcnb]
+/// additional-bin-packages = ["shared-exec-d-programs"]
+/// ```
+///
 /// **Note**: This only works properly if the buildpack is packaged with `libcnb-cargo`/`libcnb-test`.
 ///
 /// ```no_run,compile_fail
@@ -122,9 +189,34 @@ macro_rules! additional_buildpack_binary_path {
             {
                 compile_error!(concat!(
                     $target_name,
-                    " is not a valid binary target in this buildpack crate!"
+                    " is not a valid binary target in this buildpack crate or its additional-bin-packages!"
                 ))
             }
         )
     };
 }
+
+// Suppress warnings due to the `unused_crate_dependencies` lint, `tracing` is only used by the
+// tests of the `logging` feature.
+#[cfg(all(test, not(feature = "logging")))]
+use ::tracing as _;
+
+/// Verifies at compile time that the version of the buildpack crate in `Cargo.toml` matches the
+/// version in `buildpack.toml`.
+///
+/// Compilation fails if the versions drift apart, which prevents publishing a buildpack whose
+/// `buildpack.toml` wasn't updated along with the crate, or vice versa. Changes to
+/// `buildpack.toml` cause the check to run again.
+///
+/// ```no_run,compile_fail
+/// use libcnb::verify_buildpack_version;
+///
+/// // This would not compile in this doctest since there is no `buildpack.toml`.
+/// verify_buildpack_version!();
+/// ```
+#[macro_export]
+macro_rules! verify_buildpack_version {
+    () => {
+        ::libcnb::internals::verify_buildpack_version!();
+    };
+}
@@ -0,0 +1,111 @@
+//! Integration with the [`tracing`](https://docs.rs/tracing) and [`log`](https://docs.rs/log)
+//! facades, available with the `logging` feature.
+//!
+//! With the `logging` feature enabled, libcnb installs a subscriber at the start of detect and
+//! build that writes events to the build log. Buildpacks can then use the standard
+//! `tracing::info!` (or `log::info!`) macros, and logs of libraries used by the buildpack show up
+//! in the build log as well.
+//!
+//! Events are written to stdout as `LEVEL message`, one line at a time via [`crate::output`],
+//! without timestamps since the platform usually adds them. Only events at `info` level and above
+//! are written by default. The level can be changed via the [`LOG_LEVEL_ENV_VAR`] environment
+//! variable, for example with `pack build --env LIBCNB_LOG_LEVEL=debug`.
+
+use crate::{Env, Platform};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// The environment variable that sets the maximum level of events written to the build log.
+/// Valid values are `off`, `error`, `warn`, `info`, `debug` and `trace`. It is read from the
+/// platform env (`<platform>/env`), falling back to the process environment.
+pub const LOG_LEVEL_ENV_VAR: &str = "LIBCNB_LOG_LEVEL";
+
+/// Installs the global subscriber that writes `tracing` events and `log` records to the build log.
+///
+/// libcnb calls this automatically at the start of detect and build. Calling it again, or after
+/// another global subscriber has been installed, has no effect.
+pub fn init(platform_env: &Env) {
+    init_with_level(platform_env.get_string_lossy(LOG_LEVEL_ENV_VAR));
+}
+
+/// Like [`init`], but only reads the variable that sets the level from the platform.
+pub(crate) fn init_from_platform(platform: &impl Platform) {
+    init_with_level(platform.env_var(LOG_LEVEL_ENV_VAR));
+}
+
+fn init_with_level(platform_level: Option<String>) {
+    let _ = subscriber(level(platform_level), crate::output::stdout).try_init();
+}
+
+fn level(platform_level: Option<String>) -> LevelFilter {
+    platform_level
+        .or_else(|| std::env::var(LOG_LEVEL_ENV_VAR).ok())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(LevelFilter::INFO)
+}
+
+fn subscriber<W>(level: LevelFilter, writer: W) -> impl SubscriberInitExt
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .without_time()
+        .with_target(false)
+        .with_max_level(level)
+        .with_writer(writer)
+        .finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct TestWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for TestWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'writer> MakeWriter<'writer> for TestWriter {
+        type Writer = Self;
+
+        fn make_writer(&'writer self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn writes_events_at_configured_level() {
+        let writer = TestWriter::default();
+
+        {
+            let _guard =
+                subscriber(level(Some(String::from("debug\n"))), writer.clone()).set_default();
+            tracing::info!("Installing Node.js {}", "20.11.1");
+            tracing::debug!(attempt = 2, "Retrying download");
+            tracing::trace!("Not written");
+        }
+
+        assert_eq!(
+            String::from_utf8(writer.0.lock().unwrap().clone()).unwrap(),
+            " INFO Installing Node.js 20.11.1\n\
+             DEBUG Retrying download attempt=2\n"
+        );
+    }
+
+    #[test]
+    fn defaults_to_info() {
+        assert_eq!(level(None), LevelFilter::INFO);
+        assert_eq!(level(Some(String::from("nonsense"))), LevelFilter::INFO);
+    }
+}
@@ -0,0 +1,269 @@
+//! User-configurable buildpack options, read from `BP_*` environment variables.
+
+use crate::{Env, Warnings};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Derives [`BuildpackOptions`](trait@BuildpackOptions) for a struct with named fields.
+///
+/// Each field is read from an environment variable. Fields can be configured with the `option`
+/// attribute:
+///
+/// - `name = "BP_..."`: the name of the environment variable. Defaults to `BP_` followed by the
+///   upper-cased field name.
+/// - `default = "..."`: the value used if the variable isn't set. It is parsed like a value of the
+///   variable.
+/// - `alias = "BP_..."`: a deprecated name of the variable that is still accepted. Can be given
+///   multiple times.
+///
+/// Fields of type `Option<T>` are optional, all other fields without a default are required.
+/// Values are parsed with [`FromStr`].
+pub use libcnb_proc_macros::BuildpackOptions;
+
+/// Options that users can configure via environment variables, usually prefixed with `BP_`.
+///
+/// This trait is usually derived, see [`BuildpackOptions`](derive@BuildpackOptions) for the
+/// available attributes.
+///
+/// # Examples
+/// ```
+/// use libcnb::options::BuildpackOptions;
+/// use libcnb::{Env, Warnings};
+///
+/// #[derive(BuildpackOptions)]
+/// struct JvmOptions {
+///     #[option(default = "21", alias = "BP_JAVA_VERSION")]
+///     jvm_version: u32,
+///     #[option(name = "BP_JVM_TYPE", default = "jre")]
+///     jvm_type: String,
+///     maven_settings_path: Option<String>,
+/// }
+///
+/// let mut env = Env::new();
+/// env.insert("BP_JAVA_VERSION", "17");
+///
+/// let warnings = Warnings::new();
+/// let options = JvmOptions::from_env(&env, &warnings).unwrap();
+///
+/// assert_eq!(options.jvm_version, 17);
+/// assert_eq!(options.jvm_type, "jre");
+/// assert_eq!(options.maven_settings_path, None);
+/// assert_eq!(
+///     warnings.to_vec(),
+///     vec!["BP_JAVA_VERSION is deprecated, use BP_JVM_VERSION instead."]
+/// );
+///
+/// env.insert("BP_JVM_VERSION", "latest");
+///
+/// assert_eq!(
+///     JvmOptions::from_env(&env, &warnings).err().unwrap().to_string(),
+///     "Invalid buildpack options: BP_JVM_VERSION has an invalid value \"latest\": invalid digit found in string"
+/// );
+/// ```
+///
+/// In a buildpack, options are usually read from the platform environment:
+///
+/// ```ignore
+/// let options = JvmOptions::from_env(context.platform.env(), &context.warnings)?;
+/// ```
+pub trait BuildpackOptions: Sized {
+    /// Reads the options from the given environment.
+    ///
+    /// Variables set to an empty value are treated like unset variables. Usages of deprecated
+    /// aliases are recorded as warnings.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` listing all required variables that aren't set and all values that
+    /// couldn't be parsed.
+    fn from_env(env: &Env, warnings: &Warnings) -> Result<Self, BuildpackOptionsError>;
+}
+
+/// An error reading [`BuildpackOptions`], listing every invalid option.
+#[derive(thiserror::Error, Clone, Debug, Eq, PartialEq)]
+#[error("Invalid buildpack options: {}", join_invalid_options(.errors))]
+pub struct BuildpackOptionsError {
+    pub errors: Vec<InvalidOption>,
+}
+
+fn join_invalid_options(errors: &[InvalidOption]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A single invalid option of a [`BuildpackOptionsError`].
+#[derive(thiserror::Error, Clone, Debug, Eq, PartialEq)]
+pub enum InvalidOption {
+    #[error("{name} is required but not set")]
+    Missing { name: String },
+    #[error("{name} has an invalid value \"{value}\": {reason}")]
+    InvalidValue {
+        name: String,
+        value: String,
+        reason: String,
+    },
+}
+
+/// Reads options from an environment and collects errors. Used by the code generated by the
+/// [`BuildpackOptions`](derive@BuildpackOptions) derive macro.
+#[doc(hidden)]
+pub struct OptionsReader<'a> {
+    env: &'a Env,
+    warnings: &'a Warnings,
+    errors: Vec<InvalidOption>,
+}
+
+impl<'a> OptionsReader<'a> {
+    #[must_use]
+    pub fn new(env: &'a Env, warnings: &'a Warnings) -> Self {
+        Self {
+            env,
+            warnings,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Reads an option that has no value if neither the variable, one of its aliases nor a
+    /// default is set.
+    pub fn optional<T>(&mut self, name: &str, aliases: &[&str], default: Option<&str>) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.value(name, aliases).or(default.map(String::from))?;
+
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(error) => {
+                self.errors.push(InvalidOption::InvalidValue {
+                    name: String::from(name),
+                    value,
+                    reason: error.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Reads an option that must have a value. Records an error if it hasn't.
+    pub fn required<T>(&mut self, name: &str, aliases: &[&str], default: Option<&str>) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let errors = self.errors.len();
+        let value = self.optional(name, aliases, default);
+
+        if value.is_none() && self.errors.len() == errors {
+            self.errors.push(InvalidOption::Missing {
+                name: String::from(name),
+            });
+        }
+
+        value
+    }
+
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    #[must_use]
+    pub fn into_error(self) -> BuildpackOptionsError {
+        BuildpackOptionsError {
+            errors: self.errors,
+        }
+    }
+
+    fn value(&self, name: &str, aliases: &[&str]) -> Option<String> {
+        if let Some(value) = non_empty_value(self.env, name) {
+            return Some(value);
+        }
+
+        aliases.iter().find_map(|alias| {
+            non_empty_value(self.env, alias).inspect(|_| {
+                self.warnings
+                    .push(format!("{alias} is deprecated, use {name} instead."));
+            })
+        })
+    }
+}
+
+fn non_empty_value(env: &Env, name: &str) -> Option<String> {
+    env.get_string_lossy(name).filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(BuildpackOptions, Debug)]
+    struct TestOptions {
+        #[option(default = "3")]
+        retries: u32,
+        #[option(name = "BP_TEST_MODE", alias = "BP_OLD_MODE", alias = "BP_OLDER_MODE")]
+        mode: String,
+        debug: Option<bool>,
+    }
+
+    #[test]
+    fn from_env() {
+        let mut env = Env::new();
+        env.insert("BP_OLDER_MODE", "strict");
+        env.insert("BP_DEBUG", "");
+
+        let warnings = Warnings::new();
+        let options = TestOptions::from_env(&env, &warnings).unwrap();
+
+        assert_eq!(options.retries, 3);
+        assert_eq!(options.mode, "strict");
+        assert_eq!(options.debug, None);
+        assert_eq!(
+            warnings.to_vec(),
+            vec!["BP_OLDER_MODE is deprecated, use BP_TEST_MODE instead."]
+        );
+
+        env.insert("BP_TEST_MODE", "lenient");
+        env.insert("BP_RETRIES", "5");
+        env.insert("BP_DEBUG", "true");
+
+        let warnings = Warnings::new();
+        let options = TestOptions::from_env(&env, &warnings).unwrap();
+
+        assert_eq!(options.retries, 5);
+        assert_eq!(options.mode, "lenient");
+        assert_eq!(options.debug, Some(true));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn from_env_reports_all_errors() {
+        let mut env = Env::new();
+        env.insert("BP_RETRIES", "-1");
+        env.insert("BP_DEBUG", "yes");
+
+        let error = TestOptions::from_env(&env, &Warnings::new()).unwrap_err();
+
+        assert_eq!(
+            error.errors,
+            vec![
+                InvalidOption::InvalidValue {
+                    name: String::from("BP_RETRIES"),
+                    value: String::from("-1"),
+                    reason: String::from("invalid digit found in string"),
+                },
+                InvalidOption::Missing {
+                    name: String::from("BP_TEST_MODE"),
+                },
+                InvalidOption::InvalidValue {
+                    name: String::from("BP_DEBUG"),
+                    value: String::from("yes"),
+                    reason: String::from("provided string was not `true` or `false`"),
+                },
+            ]
+        );
+    }
+}
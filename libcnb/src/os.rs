@@ -0,0 +1,217 @@
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// The operating system family a buildpack runs on, which determines the path list separator of
+/// environment variables such as `PATH`, whether environment variable names are case-sensitive and
+/// the names of executables.
+///
+/// libcnb uses [`OsFamily::current`], the family the buildpack was compiled for, which matches the
+/// family of the build image since buildpacks are compiled for their target.
+///
+/// # Examples
+/// ```
+/// use libcnb::OsFamily;
+/// use std::ffi::OsString;
+/// use std::path::PathBuf;
+///
+/// let path = OsFamily::Windows.join_paths(["C:\\layers\\ruby\\bin", "C:\\Windows\\System32"]);
+/// assert_eq!(path, OsString::from("C:\\layers\\ruby\\bin;C:\\Windows\\System32"));
+///
+/// assert_eq!(
+///     OsFamily::Unix.split_paths("/layers/ruby/bin:/usr/bin"),
+///     vec![PathBuf::from("/layers/ruby/bin"), PathBuf::from("/usr/bin")]
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OsFamily {
+    Unix,
+    Windows,
+}
+
+impl OsFamily {
+    /// The family the buildpack was compiled for.
+    #[must_use]
+    pub const fn current() -> Self {
+        if cfg!(target_family = "windows") {
+            Self::Windows
+        } else {
+            Self::Unix
+        }
+    }
+
+    /// The separator of lists of paths in environment variables such as `PATH`.
+    #[must_use]
+    pub const fn path_list_separator(self) -> &'static str {
+        match self {
+            Self::Unix => ":",
+            Self::Windows => ";",
+        }
+    }
+
+    /// Returns true if environment variable names are case-insensitive, as they are on Windows.
+    #[must_use]
+    pub const fn has_case_insensitive_env(self) -> bool {
+        matches!(self, Self::Windows)
+    }
+
+    /// Joins paths with the path list separator of this family.
+    ///
+    /// Unlike [`std::env::join_paths`], paths that contain the separator aren't rejected, since the
+    /// CNB spec doesn't escape them either.
+    #[must_use]
+    pub fn join_paths<I, P>(self, paths: I) -> OsString
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<OsStr>,
+    {
+        let mut joined = OsString::new();
+
+        for (index, path) in paths.into_iter().enumerate() {
+            if index > 0 {
+                joined.push(self.path_list_separator());
+            }
+            joined.push(path);
+        }
+
+        joined
+    }
+
+    /// Splits a list of paths, such as the value of `PATH`, with the path list separator of this
+    /// family. Empty entries are skipped. Paths that aren't valid Unicode are kept as is.
+    #[must_use]
+    pub fn split_paths(self, value: impl AsRef<OsStr>) -> Vec<PathBuf> {
+        let separator = self.path_list_separator().as_bytes()[0];
+
+        value
+            .as_ref()
+            .as_encoded_bytes()
+            .split(|byte| *byte == separator)
+            .filter(|path| !path.is_empty())
+            .map(|path| {
+                // SAFETY: The bytes were split at an ASCII separator, which results in valid
+                // encoded bytes, see `OsStr::from_encoded_bytes_unchecked`.
+                #[allow(unsafe_code)]
+                let path = unsafe { OsStr::from_encoded_bytes_unchecked(path) };
+                PathBuf::from(path)
+            })
+            .collect()
+    }
+
+    /// The name of an executable without the `.exe` extension it has on Windows, such as `build`
+    /// for `bin/build.exe`.
+    #[must_use]
+    pub fn executable_name(self, path: &Path) -> Option<&str> {
+        let is_windows_executable = self == Self::Windows
+            && path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("exe"));
+
+        if is_windows_executable {
+            path.file_stem()
+        } else {
+            path.file_name()
+        }
+        .and_then(OsStr::to_str)
+    }
+
+    /// Returns true if two environment variable names refer to the same variable.
+    pub(crate) fn env_keys_equal(self, a: &OsStr, b: &OsStr) -> bool {
+        if self.has_case_insensitive_env() {
+            a.as_encoded_bytes()
+                .eq_ignore_ascii_case(b.as_encoded_bytes())
+        } else {
+            a == b
+        }
+    }
+}
+
+impl Default for OsFamily {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_and_split_paths() {
+        assert_eq!(
+            OsFamily::Unix.join_paths(["/a", "/b"]),
+            OsString::from("/a:/b")
+        );
+        assert_eq!(
+            OsFamily::Windows.join_paths(["C:\\a", "C:\\b"]),
+            OsString::from("C:\\a;C:\\b")
+        );
+        assert_eq!(
+            OsFamily::Unix.join_paths(Vec::<&str>::new()),
+            OsString::new()
+        );
+
+        assert_eq!(
+            OsFamily::Unix.split_paths("/a::/b"),
+            vec![PathBuf::from("/a"), PathBuf::from("/b")]
+        );
+        assert_eq!(
+            OsFamily::Windows.split_paths("C:\\a;C:\\b"),
+            vec![PathBuf::from("C:\\a"), PathBuf::from("C:\\b")]
+        );
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn split_non_unicode_paths() {
+        use std::os::unix::ffi::OsStrExt;
+
+        assert_eq!(
+            OsFamily::Unix.split_paths(OsStr::from_bytes(b"/a\xff:/b")),
+            vec![
+                PathBuf::from(OsStr::from_bytes(b"/a\xff")),
+                PathBuf::from("/b")
+            ]
+        );
+    }
+
+    #[test]
+    fn executable_names() {
+        assert_eq!(
+            OsFamily::Unix.executable_name(Path::new("/cnb/buildpacks/bin/build")),
+            Some("build")
+        );
+        assert_eq!(
+            OsFamily::Unix.executable_name(Path::new("bin/build.exe")),
+            Some("build.exe")
+        );
+        assert_eq!(
+            OsFamily::Windows.executable_name(Path::new("bin/build.EXE")),
+            Some("build")
+        );
+        assert_eq!(
+            OsFamily::Windows.executable_name(Path::new("bin/detect")),
+            Some("detect")
+        );
+    }
+
+    #[test]
+    fn env_keys() {
+        assert!(OsFamily::Windows.env_keys_equal(OsStr::new("Path"), OsStr::new("PATH")));
+        assert!(!OsFamily::Unix.env_keys_equal(OsStr::new("Path"), OsStr::new("PATH")));
+        assert!(OsFamily::Unix.env_keys_equal(OsStr::new("PATH"), OsStr::new("PATH")));
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn current_unix() {
+        assert_eq!(OsFamily::current(), OsFamily::Unix);
+        assert_eq!(OsFamily::current().path_list_separator(), ":");
+    }
+
+    #[test]
+    #[cfg(target_family = "windows")]
+    fn current_windows() {
+        assert_eq!(OsFamily::current(), OsFamily::Windows);
+        assert_eq!(OsFamily::current().path_list_separator(), ";");
+    }
+}
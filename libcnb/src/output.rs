@@ -0,0 +1,172 @@
+//! Line-buffered writers for the build log.
+//!
+//! The build log is the combined stdout and stderr of the buildpack, including the output of the
+//! commands it runs. When several writers, such as libcnb itself, the buildpack and the threads
+//! that forward the output of a [`CommandRunner`](crate::command::CommandRunner), write to it at
+//! the same time, their output can be interleaved in the middle of a line. This is especially
+//! noticeable under `pack`, where stdout and stderr are pipes and not a TTY.
+//!
+//! The writers of this module buffer output until a line is complete and then write and flush the
+//! complete lines at once, while holding the locks of both stdout and stderr. Lines are therefore
+//! never torn apart, and lines written to stdout and stderr appear in the order they were
+//! completed:
+//!
+//! ```
+//! use std::io::Write;
+//!
+//! let mut stdout = libcnb::output::stdout();
+//! write!(stdout, "Installing Ruby ")?;
+//! writeln!(stdout, "3.3.1")?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! To keep several lines together, for example a section header and its body, use [`lock`].
+
+use std::io::{self, StderrLock, StdoutLock, Write};
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+
+/// Returns a line-buffered writer for stdout.
+#[must_use]
+pub fn stdout() -> BuildLogWriter {
+    BuildLogWriter::new(Target::Stdout)
+}
+
+/// Returns a line-buffered writer for stderr.
+#[must_use]
+pub fn stderr() -> BuildLogWriter {
+    BuildLogWriter::new(Target::Stderr)
+}
+
+/// Locks stdout and stderr until the returned guard is dropped, so that output written by the
+/// current thread in the meantime isn't interleaved with output of other threads.
+///
+/// The locks are reentrant, so the current thread can keep using the writers of this module, as
+/// well as `println!` and `eprintln!`, while holding the guard. Output of commands run by a
+/// [`CommandRunner`](crate::command::CommandRunner) is forwarded by other threads, which block
+/// until the guard is dropped. Don't run commands that stream their output while holding the
+/// guard, since they can stall once the pipes to them are full.
+#[must_use]
+pub fn lock() -> OutputLock {
+    OutputLock {
+        stdout: io::stdout().lock(),
+        stderr: io::stderr().lock(),
+    }
+}
+
+/// The guard returned by [`lock`].
+pub struct OutputLock {
+    stdout: StdoutLock<'static>,
+    stderr: StderrLock<'static>,
+}
+
+impl Drop for OutputLock {
+    fn drop(&mut self) {
+        let _ = self.stdout.flush();
+        let _ = self.stderr.flush();
+    }
+}
+
+/// A writer that buffers output until a line is complete, returned by [`stdout`] and [`stderr`].
+///
+/// Every complete line is flushed immediately. A trailing incomplete line is written when the
+/// writer is explicitly flushed or dropped.
+pub struct BuildLogWriter {
+    target: Target,
+    buffer: Vec<u8>,
+}
+
+enum Target {
+    Stdout,
+    Stderr,
+    #[cfg(test)]
+    Buffer(Arc<Mutex<Vec<u8>>>),
+}
+
+impl BuildLogWriter {
+    fn new(target: Target) -> Self {
+        Self {
+            target,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn write_through(&self, bytes: &[u8]) -> io::Result<()> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        // Always locking stdout first, even when writing to stderr, orders lines across both
+        // streams and can't deadlock with other writers.
+        let mut stdout = io::stdout().lock();
+
+        match &self.target {
+            Target::Stdout => {
+                stdout.write_all(bytes)?;
+                stdout.flush()
+            }
+            Target::Stderr => {
+                let mut stderr = io::stderr().lock();
+                stderr.write_all(bytes)?;
+                stderr.flush()
+            }
+            #[cfg(test)]
+            Target::Buffer(buffer) => buffer
+                .lock()
+                .map_err(|_| io::Error::other("Poisoned buffer"))?
+                .write_all(bytes),
+        }
+    }
+}
+
+impl Write for BuildLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        if let Some(last_newline) = self.buffer.iter().rposition(|byte| *byte == b'\n') {
+            let lines = self.buffer.drain(..=last_newline).collect::<Vec<_>>();
+            self.write_through(&lines)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let partial_line = std::mem::take(&mut self.buffer);
+        self.write_through(&partial_line)
+    }
+}
+
+impl Drop for BuildLogWriter {
+    fn drop(&mut self) {
+        // Errors writing to the build log can't be handled here, like with `print!`.
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_complete_lines() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let mut writer = BuildLogWriter::new(Target::Buffer(output.clone()));
+        let written = || String::from_utf8(output.lock().unwrap().clone()).unwrap();
+
+        write!(writer, "Installing").unwrap();
+        assert_eq!(written(), "");
+
+        write!(writer, " Ruby\nDownloading").unwrap();
+        assert_eq!(written(), "Installing Ruby\n");
+
+        write!(writer, "...\nDone\nCleaning up").unwrap();
+        assert_eq!(written(), "Installing Ruby\nDownloading...\nDone\n");
+
+        drop(writer);
+        assert_eq!(
+            written(),
+            "Installing Ruby\nDownloading...\nDone\nCleaning up"
+        );
+    }
+}
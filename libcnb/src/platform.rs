@@ -1,7 +1,8 @@
+use crate::util::default_on_not_found;
 use crate::Env;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Represents a Cloud Native Buildpack platform.
 ///
@@ -18,6 +19,16 @@ where
     /// all platforms have to provide.
     fn env(&self) -> &Env;
 
+    /// Returns the value of a single platform environment variable, interpreted as Unicode data.
+    ///
+    /// libcnb uses this method to read its own settings, such as
+    /// [`TIMING_SUMMARY_ENV_VAR`](crate::TIMING_SUMMARY_ENV_VAR), so that a platform which loads
+    /// its environment lazily doesn't have to load all of it. The default implementation looks up
+    /// the variable in [`Platform::env`].
+    fn env_var(&self, name: &str) -> Option<String> {
+        self.env().get_string_lossy(name)
+    }
+
     /// Initializes the platform from the given platform directory.
     ///
     /// # Examples
@@ -27,10 +38,150 @@ where
     /// let platform = GenericPlatform::from_path("/platform").unwrap();
     /// ```
     fn from_path(platform_dir: impl AsRef<Path>) -> io::Result<Self>;
+
+    /// Declares the platform environment variables this platform depends on.
+    ///
+    /// libcnb validates the declared requirements against [`Platform::env`] right after the
+    /// platform has been created and before detect or build is invoked. If any required
+    /// variables are missing, the buildpack fails with a single error that lists all of them,
+    /// instead of failing on the first missing variable somewhere mid-build.
+    ///
+    /// The default implementation declares no requirements.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::{read_platform_env, Env, Platform, PlatformEnvRequirements};
+    /// use std::path::Path;
+    ///
+    /// struct MyPlatform {
+    ///     env: Env,
+    /// }
+    ///
+    /// impl Platform for MyPlatform {
+    ///     fn env(&self) -> &Env {
+    ///         &self.env
+    ///     }
+    ///
+    ///     fn from_path(platform_dir: impl AsRef<Path>) -> std::io::Result<Self> {
+    ///         read_platform_env(platform_dir).map(|env| Self { env })
+    ///     }
+    ///
+    ///     fn env_requirements() -> PlatformEnvRequirements {
+    ///         PlatformEnvRequirements::new()
+    ///             .required("DATABASE_URL")
+    ///             .optional("LOG_LEVEL")
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    fn env_requirements() -> PlatformEnvRequirements {
+        PlatformEnvRequirements::default()
+    }
+}
+
+/// Declaration of required and optional platform environment variables.
+///
+/// See [`Platform::env_requirements`] for details.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PlatformEnvRequirements {
+    required: Vec<String>,
+    optional: Vec<String>,
+}
+
+impl PlatformEnvRequirements {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a variable that must be present in the platform environment.
+    #[must_use]
+    pub fn required(mut self, name: impl Into<String>) -> Self {
+        self.required.push(name.into());
+        self
+    }
+
+    /// Declares a variable that may be present in the platform environment.
+    ///
+    /// Optional variables are not validated, but are part of the declaration so that they can be
+    /// documented and inspected alongside the required ones.
+    #[must_use]
+    pub fn optional(mut self, name: impl Into<String>) -> Self {
+        self.optional.push(name.into());
+        self
+    }
+
+    #[must_use]
+    pub fn required_vars(&self) -> &[String] {
+        &self.required
+    }
+
+    #[must_use]
+    pub fn optional_vars(&self) -> &[String] {
+        &self.optional
+    }
+
+    /// Validates the given environment against this declaration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing all required variables that are missing from the given
+    /// environment, in declaration order.
+    pub fn validate(&self, env: &Env) -> Result<(), MissingPlatformEnvError> {
+        let missing: Vec<String> = self
+            .required
+            .iter()
+            .filter(|name| !env.contains_key(name.as_str()))
+            .cloned()
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingPlatformEnvError { missing })
+        }
+    }
+}
+
+/// An error that occurs when required platform environment variables are missing.
+#[derive(thiserror::Error, Clone, Debug, Eq, PartialEq)]
+#[error("Missing required platform environment variable(s): {}", .missing.join(", "))]
+pub struct MissingPlatformEnvError {
+    pub missing: Vec<String>,
+}
+
+/// Returns the path of a file or directory relative to the given platform directory.
+pub(crate) fn platform_path(platform_dir: &Path, relative_path: impl AsRef<Path>) -> PathBuf {
+    platform_dir.join(relative_path)
+}
+
+/// Reads a file from the given platform directory, returning `None` if it doesn't exist.
+pub(crate) fn read_platform_file(
+    platform_dir: &Path,
+    relative_path: impl AsRef<Path>,
+) -> io::Result<Option<Vec<u8>>> {
+    default_on_not_found(fs::read(platform_path(platform_dir, relative_path)).map(Some))
+}
+
+/// Like [`read_platform_file`], but fails with [`io::ErrorKind::InvalidData`] if the file isn't
+/// valid UTF-8.
+pub(crate) fn read_platform_file_to_string(
+    platform_dir: &Path,
+    relative_path: impl AsRef<Path>,
+) -> io::Result<Option<String>> {
+    read_platform_file(platform_dir, relative_path)?
+        .map(|bytes| {
+            String::from_utf8(bytes)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+        })
+        .transpose()
 }
 
 /// Initializes a new `Env` based on the given platform directory.
-pub(crate) fn read_platform_env(platform_dir: impl AsRef<Path>) -> std::io::Result<Env> {
+///
+/// Variables are read from the files in `<platform>/env`. A missing `env` directory results in an
+/// empty `Env`.
+pub fn read_platform_env(platform_dir: impl AsRef<Path>) -> std::io::Result<Env> {
     let env_path = platform_dir.as_ref().join("env");
     let mut env_vars = Env::new();
 
@@ -61,6 +212,27 @@ pub(crate) fn read_platform_env(platform_dir: impl AsRef<Path>) -> std::io::Resu
     Ok(env_vars)
 }
 
+/// Reads a single variable from the given platform directory, without reading the other files in
+/// `<platform>/env`.
+pub(crate) fn read_platform_env_var(
+    platform_dir: impl AsRef<Path>,
+    name: &str,
+) -> std::io::Result<Option<String>> {
+    // Names with path separators can't be platform environment variables, and must not be used to
+    // read files outside of `<platform>/env`.
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Ok(None);
+    }
+
+    let path = platform_dir.as_ref().join("env").join(name);
+
+    if path.is_file() {
+        fs::read_to_string(path).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,6 +282,71 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn read_platform_file_reads_nested_files() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmpdir.path().join("config")).unwrap();
+        fs::write(tmpdir.path().join("config/settings.toml"), "foo = 1").unwrap();
+
+        assert_eq!(
+            read_platform_file(tmpdir.path(), "config/settings.toml").unwrap(),
+            Some(b"foo = 1".to_vec())
+        );
+        assert_eq!(
+            read_platform_file(tmpdir.path(), "config/missing.toml").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn read_platform_file_to_string_rejects_invalid_utf8() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        fs::write(tmpdir.path().join("valid"), "foo").unwrap();
+        fs::write(tmpdir.path().join("invalid"), [0xFF, 0xFE]).unwrap();
+
+        assert_eq!(
+            read_platform_file_to_string(tmpdir.path(), "valid").unwrap(),
+            Some(String::from("foo"))
+        );
+        assert_eq!(
+            read_platform_file_to_string(tmpdir.path(), "invalid")
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            read_platform_file_to_string(tmpdir.path(), "missing").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn platform_env_requirements_lists_all_missing_vars() {
+        let mut env = Env::new();
+        env.insert("PRESENT", "1");
+
+        let requirements = PlatformEnvRequirements::new()
+            .required("MISSING_A")
+            .required("PRESENT")
+            .optional("MISSING_OPTIONAL")
+            .required("MISSING_B");
+
+        let error = requirements.validate(&env).unwrap_err();
+        assert_eq!(error.missing, vec!["MISSING_A", "MISSING_B"]);
+        assert_eq!(
+            error.to_string(),
+            "Missing required platform environment variable(s): MISSING_A, MISSING_B"
+        );
+    }
+
+    #[test]
+    fn platform_env_requirements_default_accepts_empty_env() {
+        assert_eq!(
+            PlatformEnvRequirements::default().validate(&Env::new()),
+            Ok(())
+        );
+    }
+
     #[test]
     fn read_platform_env_does_not_blow_up_if_platform_env_is_missing() {
         let tmpdir = tempfile::tempdir().unwrap();
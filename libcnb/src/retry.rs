@@ -0,0 +1,220 @@
+//! Provides a retry helper with exponential backoff for transient failures.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::Duration;
+
+/// Retries fallible operations with exponential backoff and jitter.
+///
+/// Intended for operations that can fail transiently, such as downloads or flaky subprocesses.
+/// See [`CommandRunner::run_with_retry`](crate::command::CommandRunner::run_with_retry) for
+/// retrying commands.
+///
+/// # Examples
+/// ```
+/// use libcnb::retry::Retry;
+/// use std::time::Duration;
+///
+/// let retry = Retry::new()
+///     .max_attempts(5)
+///     .initial_delay(Duration::from_millis(1));
+///
+/// let mut calls = 0;
+/// let result: Result<&str, &str> = retry.run_if(
+///     |_attempt| {
+///         calls += 1;
+///         if calls < 3 { Err("connection reset") } else { Ok("done") }
+///     },
+///     |error| *error == "connection reset",
+/// );
+///
+/// assert_eq!(result, Ok("done"));
+/// assert_eq!(calls, 3);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Retry {
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl Retry {
+    /// Creates a new `Retry` with 3 attempts, an initial delay of one second, a maximum delay of
+    /// 30 seconds and jitter enabled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of attempts, including the first one. Values below `1` are treated
+    /// as `1`.
+    #[must_use]
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the second attempt. The delay doubles for every subsequent attempt.
+    #[must_use]
+    pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Sets the upper bound for the delay between two attempts.
+    #[must_use]
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Enables or disables jitter. With jitter, each delay is randomly chosen between half and
+    /// the full computed delay to avoid many builds retrying in lockstep.
+    #[must_use]
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Runs the given operation, retrying on any error until it succeeds or the maximum number
+    /// of attempts is reached.
+    ///
+    /// The operation is passed the current attempt number, starting at `1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt if no attempt succeeded.
+    pub fn run<T, E>(&self, operation: impl FnMut(u32) -> Result<T, E>) -> Result<T, E> {
+        self.run_if(operation, |_| true)
+    }
+
+    /// Runs the given operation, retrying while it fails with errors for which `should_retry`
+    /// returns `true`, until the maximum number of attempts is reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error for which `should_retry` returns `false`, or the error of the last
+    /// attempt if no attempt succeeded.
+    pub fn run_if<T, E>(
+        &self,
+        mut operation: impl FnMut(u32) -> Result<T, E>,
+        should_retry: impl Fn(&E) -> bool,
+    ) -> Result<T, E> {
+        let mut attempt = 1;
+
+        loop {
+            match operation(attempt) {
+                Err(error) if attempt < self.max_attempts && should_retry(&error) => {
+                    thread::sleep(self.delay_before_attempt(attempt + 1));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns the delay to wait before the given attempt (starting at `1`).
+    #[must_use]
+    pub fn delay_before_attempt(&self, attempt: u32) -> Duration {
+        if attempt <= 1 {
+            return Duration::ZERO;
+        }
+
+        let delay = self
+            .initial_delay
+            .saturating_mul(2_u32.saturating_pow(attempt - 2))
+            .min(self.max_delay);
+
+        if self.jitter {
+            let half = delay / 2;
+            half + half.mul_f64(random_fraction())
+        } else {
+            delay
+        }
+    }
+}
+
+/// Returns a pseudo-random value in `[0, 1)`, which is good enough for jitter and avoids pulling in
+/// a dependency on a random number generator.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+
+    #[allow(clippy::cast_precision_loss)]
+    let fraction = (random >> 11) as f64 / (1_u64 << 53) as f64;
+    fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let retry = Retry::new().max_attempts(3).initial_delay(Duration::ZERO);
+
+        let mut attempts = Vec::new();
+        let result: Result<(), u32> = retry.run(|attempt| {
+            attempts.push(attempt);
+            Err(attempt)
+        });
+
+        assert_eq!(result, Err(3));
+        assert_eq!(attempts, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn does_not_retry_when_predicate_rejects_error() {
+        let retry = Retry::new().max_attempts(3).initial_delay(Duration::ZERO);
+
+        let mut calls = 0;
+        let result: Result<(), &str> = retry.run_if(
+            |_| {
+                calls += 1;
+                Err("permanent")
+            },
+            |error| *error != "permanent",
+        );
+
+        assert_eq!(result, Err("permanent"));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn exponential_backoff_is_capped() {
+        let retry = Retry::new()
+            .initial_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(5))
+            .jitter(false);
+
+        assert_eq!(retry.delay_before_attempt(1), Duration::ZERO);
+        assert_eq!(retry.delay_before_attempt(2), Duration::from_secs(1));
+        assert_eq!(retry.delay_before_attempt(3), Duration::from_secs(2));
+        assert_eq!(retry.delay_before_attempt(4), Duration::from_secs(4));
+        assert_eq!(retry.delay_before_attempt(5), Duration::from_secs(5));
+        assert_eq!(retry.delay_before_attempt(50), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let retry = Retry::new().initial_delay(Duration::from_secs(4));
+
+        for _ in 0..100 {
+            let delay = retry.delay_before_attempt(2);
+            assert!(delay >= Duration::from_secs(2));
+            assert!(delay <= Duration::from_secs(4));
+        }
+    }
+}
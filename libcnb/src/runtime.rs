@@ -1,24 +1,38 @@
-use crate::build::{BuildContext, InnerBuildResult};
+use crate::app_dir_guard::AppDirGuard;
+use crate::build::{
+    BuildContext, BuildResult, InnerBuildResult, GROUP_TOML_FILE_NAME, STORE_TOML_FILE_NAME,
+};
+use crate::build_events::{self, BuildEvent, BUILD_EVENTS_PATH_ENV_VAR};
+use crate::build_output::{write_build_outputs, BuildOutput};
 use crate::buildpack::Buildpack;
-use crate::data::buildpack::BuildpackApi;
+use crate::data::buildpack::{BuildpackApi, BuildpackId, BuildpackVersion};
 use crate::detect::{DetectContext, InnerDetectResult};
-use crate::error::Error;
-use crate::platform::Platform;
-use crate::sbom::cnb_sbom_path;
+use crate::error::{Error, ErrorContext, Phase};
+use crate::error_report::{error_category, ErrorReport, ERROR_REPORT_PATH_ENV_VAR};
+use crate::layer::HandledLayers;
+use crate::output;
+use crate::platform::{read_platform_env_var, Platform};
+use crate::timings::{timing_summary_enabled, write_timings_summary};
 #[cfg(feature = "trace")]
 use crate::tracing::start_trace;
 use crate::util::is_not_found_error_kind;
-use crate::{exit_code, Target, TomlFileError, LIBCNB_SUPPORTED_BUILDPACK_API};
-use libcnb_common::toml_file::{read_toml_file, write_toml_file};
+use crate::warnings::write_warnings_summary;
+use crate::{
+    exit_code, Deterministic, OsFamily, Target, Timings, TomlFileError, Warnings,
+    LIBCNB_SUPPORTED_BUILDPACK_API,
+};
+use libcnb_common::toml_file::{read_toml_file, write_toml_atomic};
 use libcnb_data::buildpack::ComponentBuildpackDescriptor;
+use libcnb_data::launch::Launch;
 use libcnb_data::store::Store;
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use std::ffi::OsStr;
+use std::env;
+use std::ffi::OsString;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 use std::process::exit;
-use std::{env, fs};
+use std::time::Instant;
 
 /// Main entry point for this framework.
 ///
@@ -68,31 +82,53 @@ pub fn libcnb_runtime<B: Buildpack>(buildpack: &B) {
     let current_exe = args.first();
     let current_exe_file_name = current_exe
         .map(Path::new)
-        .and_then(Path::file_name)
-        .and_then(OsStr::to_str);
-
-    let result = match current_exe_file_name {
-        Some("detect") => libcnb_runtime_detect(
-            buildpack,
-            DetectArgs::parse(&args).unwrap_or_else(|parse_error| match parse_error {
-                DetectArgsParseError::InvalidArguments => {
-                    eprintln!("Usage: detect <platform_dir> <buildplan>");
-                    eprintln!(
-                        "https://github.com/buildpacks/spec/blob/main/buildpack.md#detection"
-                    );
-                    exit(exit_code::GENERIC_UNSPECIFIED_ERROR);
-                }
-            }),
+        .and_then(|path| OsFamily::current().executable_name(path));
+
+    // Settings of libcnb itself are needed before the arguments of the phase are parsed.
+    let platform_dir = platform_dir_arg(current_exe_file_name, &args).map(Path::new);
+
+    if let Some(build_events_path) = setting(platform_dir, BUILD_EVENTS_PATH_ENV_VAR) {
+        let buildpack_id = read_buildpack_descriptor::<BuildpackDescriptorIdOnly, ()>()
+            .ok()
+            .map(|descriptor| descriptor.buildpack.id.to_string());
+
+        if let Err(io_error) = build_events::init(Path::new(&build_events_path), buildpack_id) {
+            eprintln!("Warning: Couldn't open build events file: {io_error}");
+        }
+    }
+
+    let start = Instant::now();
+
+    let (phase, result) = match current_exe_file_name {
+        Some("detect") => (
+            Phase::Detect,
+            libcnb_runtime_detect(
+                buildpack,
+                DetectArgs::parse(&args).unwrap_or_else(|parse_error| match parse_error {
+                    DetectArgsParseError::InvalidArguments => {
+                        eprintln!("Usage: detect <platform_dir> <buildplan>");
+                        eprintln!(
+                            "https://github.com/buildpacks/spec/blob/main/buildpack.md#detection"
+                        );
+                        exit(exit_code::GENERIC_UNSPECIFIED_ERROR);
+                    }
+                }),
+            ),
         ),
-        Some("build") => libcnb_runtime_build(
-            buildpack,
-            BuildArgs::parse(&args).unwrap_or_else(|parse_error| match parse_error {
-                BuildArgsParseError::InvalidArguments => {
-                    eprintln!("Usage: build <layers> <platform> <plan>");
-                    eprintln!("https://github.com/buildpacks/spec/blob/main/buildpack.md#build");
-                    exit(exit_code::GENERIC_UNSPECIFIED_ERROR);
-                }
-            }),
+        Some("build") => (
+            Phase::Build,
+            libcnb_runtime_build(
+                buildpack,
+                BuildArgs::parse(&args).unwrap_or_else(|parse_error| match parse_error {
+                    BuildArgsParseError::InvalidArguments => {
+                        eprintln!("Usage: build <layers> <platform> <plan>");
+                        eprintln!(
+                            "https://github.com/buildpacks/spec/blob/main/buildpack.md#build"
+                        );
+                        exit(exit_code::GENERIC_UNSPECIFIED_ERROR);
+                    }
+                }),
+            ),
         ),
         other => {
             eprintln!(
@@ -105,13 +141,29 @@ pub fn libcnb_runtime<B: Buildpack>(buildpack: &B) {
         }
     };
 
-    match result {
-        Ok(code) => exit(code),
+    let exit_code = match result {
+        Ok(code) => code,
         Err(libcnb_error) => {
-            buildpack.on_error(libcnb_error);
-            exit(exit_code::GENERIC_UNSPECIFIED_ERROR);
+            build_events::emit(BuildEvent::Error {
+                phase: phase.to_string(),
+                category: error_category(&libcnb_error).to_string(),
+                message: libcnb_error.to_string(),
+            });
+
+            write_error_report(platform_dir, phase, &libcnb_error);
+
+            buildpack.on_error_with_context(libcnb_error, &error_context(phase));
+            exit_code::GENERIC_UNSPECIFIED_ERROR
         }
-    }
+    };
+
+    build_events::emit(BuildEvent::PhaseFinished {
+        phase: phase.to_string(),
+        exit_code,
+        duration_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+    });
+
+    exit(exit_code);
 }
 
 /// Detect entry point for this framework.
@@ -122,6 +174,11 @@ pub fn libcnb_runtime_detect<B: Buildpack>(
     buildpack: &B,
     args: DetectArgs,
 ) -> crate::Result<i32, B::Error> {
+    let start = Instant::now();
+    build_events::emit(BuildEvent::PhaseStarted {
+        phase: Phase::Detect.to_string(),
+    });
+
     let app_dir = env::current_dir().map_err(Error::CannotDetermineAppDirectory)?;
 
     let buildpack_dir = read_buildpack_dir()?;
@@ -129,8 +186,17 @@ pub fn libcnb_runtime_detect<B: Buildpack>(
     let buildpack_descriptor: ComponentBuildpackDescriptor<<B as Buildpack>::Metadata> =
         read_buildpack_descriptor()?;
 
+    buildpack
+        .validate_metadata(&buildpack_descriptor.metadata)
+        .map_err(Error::InvalidBuildpackMetadata)?;
+
     #[cfg(feature = "trace")]
-    let mut trace = start_trace(&buildpack_descriptor.buildpack, "detect");
+    let mut trace = start_trace(
+        &buildpack_descriptor.buildpack,
+        "detect",
+        &args.platform_dir_path,
+        &buildpack.telemetry_resource_attributes(),
+    );
 
     #[cfg(feature = "trace")]
     let mut trace_error = |err: &dyn std::error::Error| {
@@ -140,25 +206,37 @@ pub fn libcnb_runtime_detect<B: Buildpack>(
     #[cfg(not(feature = "trace"))]
     let mut trace_error = |_: &dyn std::error::Error| {};
 
-    let platform = B::Platform::from_path(&args.platform_dir_path)
-        .map_err(Error::CannotCreatePlatformFromPath)
-        .inspect_err(|err| trace_error(err))?;
+    let platform =
+        read_platform::<B>(&args.platform_dir_path).inspect_err(|err| trace_error(err))?;
 
-    let build_plan_path = args.build_plan_path;
+    #[cfg(feature = "logging")]
+    crate::logging::init_from_platform(&platform);
 
     let target = context_target().inspect_err(|err| trace_error(err))?;
 
+    let warnings = Warnings::new();
+    let timings = Timings::new();
+    let print_timings = timing_summary_enabled(&platform);
+
     let detect_context = DetectContext {
         app_dir,
         buildpack_dir,
         target,
+        platform_dir: args.platform_dir_path,
         platform,
         buildpack_descriptor,
+        build_plan_path: args.build_plan_path,
+        warnings: warnings.clone(),
+        timings: timings.clone(),
     };
 
-    let detect_result = buildpack
-        .detect(detect_context)
-        .inspect_err(|err| trace_error(err))?;
+    let build_plan_path = detect_context.build_plan_path.clone();
+    let detect_result = buildpack.detect(detect_context);
+    if print_timings {
+        print_timings_summary("detect", start, &timings);
+    }
+    print_warnings_summary(&warnings);
+    let detect_result = detect_result.inspect_err(|err| trace_error(err))?;
 
     match detect_result.0 {
         InnerDetectResult::Fail => {
@@ -168,7 +246,7 @@ pub fn libcnb_runtime_detect<B: Buildpack>(
         }
         InnerDetectResult::Pass { build_plan } => {
             if let Some(build_plan) = build_plan {
-                write_toml_file(&build_plan, build_plan_path)
+                write_toml_atomic(&build_plan, build_plan_path)
                     .map_err(Error::CannotWriteBuildPlan)
                     .inspect_err(|err| trace_error(err))?;
             }
@@ -188,6 +266,11 @@ pub fn libcnb_runtime_build<B: Buildpack>(
     buildpack: &B,
     args: BuildArgs,
 ) -> crate::Result<i32, B::Error> {
+    let start = Instant::now();
+    build_events::emit(BuildEvent::PhaseStarted {
+        phase: Phase::Build.to_string(),
+    });
+
     let layers_dir = args.layers_dir_path;
 
     let app_dir = env::current_dir().map_err(Error::CannotDetermineAppDirectory)?;
@@ -197,8 +280,17 @@ pub fn libcnb_runtime_build<B: Buildpack>(
     let buildpack_descriptor: ComponentBuildpackDescriptor<<B as Buildpack>::Metadata> =
         read_buildpack_descriptor()?;
 
+    buildpack
+        .validate_metadata(&buildpack_descriptor.metadata)
+        .map_err(Error::InvalidBuildpackMetadata)?;
+
     #[cfg(feature = "trace")]
-    let mut trace = start_trace(&buildpack_descriptor.buildpack, "build");
+    let mut trace = start_trace(
+        &buildpack_descriptor.buildpack,
+        "build",
+        &args.platform_dir_path,
+        &buildpack.telemetry_resource_attributes(),
+    );
 
     #[cfg(feature = "trace")]
     let mut trace_error = |err: &dyn std::error::Error| {
@@ -208,37 +300,76 @@ pub fn libcnb_runtime_build<B: Buildpack>(
     #[cfg(not(feature = "trace"))]
     let mut trace_error = |_: &dyn std::error::Error| {};
 
-    let platform = Platform::from_path(&args.platform_dir_path)
-        .map_err(Error::CannotCreatePlatformFromPath)
-        .inspect_err(|err| trace_error(err))?;
+    let platform =
+        read_platform::<B>(&args.platform_dir_path).inspect_err(|err| trace_error(err))?;
+
+    #[cfg(feature = "logging")]
+    crate::logging::init_from_platform(&platform);
 
     let buildpack_plan = read_toml_file(&args.buildpack_plan_path)
         .map_err(Error::CannotReadBuildpackPlan)
         .inspect_err(|err| trace_error(err))?;
 
-    let store = match read_toml_file::<Store>(layers_dir.join("store.toml")) {
+    let store = match read_toml_file::<Store>(layers_dir.join(STORE_TOML_FILE_NAME)) {
         Err(TomlFileError::IoError(io_error)) if is_not_found_error_kind(&io_error) => Ok(None),
         other => other.map(Some),
     }
     .map_err(Error::CannotReadStore)
     .inspect_err(|err| trace_error(err))?;
 
+    // A snapshot of the store of the previous build, so that store.toml is only rewritten when
+    // the buildpack returns a different store.
+    let previous_store = store.clone();
+
+    // The lifecycle writes group.toml to the parent of the layers directories of the buildpacks.
+    let group = layers_dir
+        .parent()
+        .and_then(|dir| read_toml_file(dir.join(GROUP_TOML_FILE_NAME)).ok());
+
     let target = context_target().inspect_err(|err| trace_error(err))?;
 
+    let warnings = Warnings::new();
+    let timings = Timings::new();
+    let print_timings = timing_summary_enabled(&platform);
+    let deterministic = Deterministic::from_env(&platform);
+    let app_dir_guard =
+        AppDirGuard::start(&platform, &app_dir).inspect_err(|err| trace_error(err))?;
+
     let build_context = BuildContext {
         layers_dir: layers_dir.clone(),
         app_dir,
+        platform_dir: args.platform_dir_path,
         platform,
         target,
         buildpack_plan,
+        buildpack_plan_path: args.buildpack_plan_path,
         buildpack_dir,
         buildpack_descriptor,
         store,
+        warnings: warnings.clone(),
+        timings: timings.clone(),
+        handled_layers: HandledLayers::new(),
+        deterministic,
+        group,
     };
 
-    let build_result = buildpack
-        .build(build_context)
-        .inspect_err(|err| trace_error(err))?;
+    let build_result = buildpack.build(build_context);
+    if let Ok(BuildResult(InnerBuildResult::Pass {
+        launch: Some(launch),
+        ..
+    })) = &build_result
+    {
+        for warning in launch.warnings() {
+            warnings.push(warning.to_string());
+        }
+    }
+    let app_dir_result = app_dir_guard.map_or(Ok(()), |guard| guard.check(&warnings));
+    if print_timings {
+        print_timings_summary("build", start, &timings);
+    }
+    print_warnings_summary(&warnings);
+    let build_result = build_result.inspect_err(|err| trace_error(err))?;
+    app_dir_result.inspect_err(|err| trace_error(err))?;
 
     match build_result.0 {
         InnerBuildResult::Pass {
@@ -247,34 +378,33 @@ pub fn libcnb_runtime_build<B: Buildpack>(
             build_sboms,
             launch_sboms,
         } => {
-            if let Some(launch) = launch {
-                write_toml_file(&launch, layers_dir.join("launch.toml"))
-                    .map_err(Error::CannotWriteLaunch)
-                    .inspect_err(|err| trace_error(err))?;
-            };
-
-            if let Some(store) = store {
-                write_toml_file(&store, layers_dir.join("store.toml"))
-                    .map_err(Error::CannotWriteStore)
-                    .inspect_err(|err| trace_error(err))?;
-            };
-
-            for build_sbom in build_sboms {
-                fs::write(
-                    cnb_sbom_path(&build_sbom.format, &layers_dir, "build"),
-                    &build_sbom.data,
-                )
-                .map_err(Error::CannotWriteBuildSbom)
-                .inspect_err(|err| trace_error(err))?;
+            if let Some(Err(errors)) = launch.as_ref().map(Launch::validate) {
+                let error = Error::InvalidLaunch(errors);
+                trace_error(&error);
+                return Err(error);
             }
 
-            for launch_sbom in launch_sboms {
-                fs::write(
-                    cnb_sbom_path(&launch_sbom.format, &layers_dir, "launch"),
-                    &launch_sbom.data,
+            let outputs = launch
+                .map(BuildOutput::Launch)
+                .into_iter()
+                .chain(
+                    store
+                        .filter(|store| Some(store) != previous_store.as_ref())
+                        .map(BuildOutput::Store),
                 )
-                .map_err(Error::CannotWriteLaunchSbom)
-                .inspect_err(|err| trace_error(err))?;
+                .chain(build_sboms.into_iter().map(BuildOutput::BuildSbom))
+                .chain(launch_sboms.into_iter().map(BuildOutput::LaunchSbom))
+                .collect::<Vec<_>>();
+
+            // All outputs are written, even if some fail. Every error is traced and the first one,
+            // in the order of the outputs above, is returned.
+            let mut errors = write_build_outputs(&layers_dir, deterministic, &outputs)
+                .into_iter()
+                .map(Error::from);
+            if let Some(error) = errors.next() {
+                trace_error(&error);
+                errors.for_each(|error| trace_error(&error));
+                return Err(error);
             }
 
             #[cfg(feature = "trace")]
@@ -292,6 +422,19 @@ struct BuildpackDescriptorApiOnly {
     api: BuildpackApi,
 }
 
+// A partial representation of buildpack.toml used to enrich the context passed to error handlers,
+// which must not fail if the buildpack's custom metadata doesn't parse.
+#[derive(Deserialize)]
+struct BuildpackDescriptorIdOnly {
+    buildpack: BuildpackIdAndVersion,
+}
+
+#[derive(Deserialize)]
+struct BuildpackIdAndVersion {
+    id: BuildpackId,
+    version: BuildpackVersion,
+}
+
 #[doc(hidden)]
 pub struct DetectArgs {
     pub platform_dir_path: PathBuf,
@@ -344,6 +487,84 @@ pub enum BuildArgsParseError {
     InvalidArguments,
 }
 
+fn print_timings_summary(phase: &str, start: Instant, timings: &Timings) {
+    // Failing to print the timings must not fail the phase itself.
+    let _ = write_timings_summary(phase, start.elapsed(), timings, output::stdout());
+}
+
+fn print_warnings_summary(warnings: &Warnings) {
+    // Failing to print the warnings must not fail the phase itself.
+    let _ = write_warnings_summary(warnings, output::stderr());
+}
+
+fn read_platform<B: Buildpack>(platform_dir: &Path) -> crate::Result<B::Platform, B::Error> {
+    let platform =
+        B::Platform::from_path(platform_dir).map_err(Error::CannotCreatePlatformFromPath)?;
+
+    // Only read the whole platform env if there is something to validate, since platforms can
+    // read it lazily.
+    let env_requirements = B::Platform::env_requirements();
+    if !env_requirements.required_vars().is_empty() {
+        env_requirements.validate(platform.env())?;
+    }
+
+    Ok(platform)
+}
+
+/// Writes an [`ErrorReport`] to the path in [`ERROR_REPORT_PATH_ENV_VAR`], if set.
+fn write_error_report<E: Debug>(platform_dir: Option<&Path>, phase: Phase, error: &Error<E>) {
+    if let Some(report_path) = setting(platform_dir, ERROR_REPORT_PATH_ENV_VAR) {
+        if let Err(io_error) = ErrorReport::new(phase, error).write(Path::new(&report_path)) {
+            eprintln!("Warning: Couldn't write error report: {io_error}");
+        }
+    }
+}
+
+/// Reads a setting of libcnb itself from the platform env, falling back to the process
+/// environment.
+///
+/// Only the file of the setting is read, not the whole platform env. A file that can't be read is
+/// reported and treated like a missing setting.
+fn setting(platform_dir: Option<&Path>, name: &str) -> Option<OsString> {
+    platform_dir
+        .and_then(
+            |platform_dir| match read_platform_env_var(platform_dir, name) {
+                Ok(value) => value.map(OsString::from),
+                Err(io_error) => {
+                    eprintln!("Warning: Couldn't read {name} from the platform env: {io_error}");
+                    None
+                }
+            },
+        )
+        .or_else(|| env::var_os(name))
+}
+
+/// Returns the platform directory argument of the phase with the given executable name.
+fn platform_dir_arg<'a>(executable_name: Option<&str>, args: &'a [String]) -> Option<&'a str> {
+    match (executable_name, args) {
+        (Some("detect"), [_, platform_dir, _]) | (Some("build"), [_, _, platform_dir, _]) => {
+            Some(platform_dir)
+        }
+        _ => None,
+    }
+}
+
+/// Gathers as much context as possible about the current buildpack execution, ignoring any parts
+/// that can't be determined.
+fn error_context(phase: Phase) -> ErrorContext {
+    let buildpack = read_buildpack_descriptor::<BuildpackDescriptorIdOnly, ()>()
+        .ok()
+        .map(|descriptor| descriptor.buildpack);
+
+    ErrorContext {
+        phase,
+        app_dir: env::current_dir().ok(),
+        buildpack_id: buildpack.as_ref().map(|buildpack| buildpack.id.clone()),
+        buildpack_version: buildpack.map(|buildpack| buildpack.version),
+        target: context_target::<()>().ok(),
+    }
+}
+
 fn read_buildpack_dir<E: Debug>() -> crate::Result<PathBuf, E> {
     env::var("CNB_BUILDPACK_DIR")
         .map_err(Error::CannotDetermineBuildpackDirectory)
@@ -375,3 +596,25 @@ where
         distro_version,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::platform_dir_arg;
+
+    #[test]
+    fn platform_dir_arg_of_phase() {
+        let detect_args = ["detect", "/platform", "/plan.toml"].map(String::from);
+        let build_args = ["build", "/layers", "/platform", "/plan.toml"].map(String::from);
+
+        assert_eq!(
+            platform_dir_arg(Some("detect"), &detect_args),
+            Some("/platform")
+        );
+        assert_eq!(
+            platform_dir_arg(Some("build"), &build_args),
+            Some("/platform")
+        );
+        assert_eq!(platform_dir_arg(Some("build"), &detect_args), None);
+        assert_eq!(platform_dir_arg(None, &build_args), None);
+    }
+}
@@ -1,7 +1,8 @@
 //! Software Bill of Materials (SBOM) support.
 
 use libcnb_data::sbom::SbomFormat;
-use std::fs;
+use std::fs::{self, File};
+use std::io;
 use std::path::{Path, PathBuf};
 
 /// A software bill of materials (SBOM).
@@ -16,16 +17,46 @@ use std::path::{Path, PathBuf};
 #[derive(Debug, Clone)]
 pub struct Sbom {
     pub format: SbomFormat,
-    pub data: Vec<u8>,
+    pub data: SbomData,
+}
+
+/// The contents of an [`Sbom`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SbomData {
+    /// The SBOM is held in memory.
+    Bytes(Vec<u8>),
+    /// The SBOM is stored in a file. It is only read when the SBOM is written, by streaming it
+    /// into the SBOM file expected by the CNB lifecycle, so that large SBOMs are never held in
+    /// memory as a whole.
+    File(PathBuf),
 }
 
 impl Sbom {
     /// Constructs an `Sbom` from the given path, treating it as the SBOM format specified.
     ///
+    /// The file is not read into memory. It is copied when libcnb.rs writes the SBOM, so it must
+    /// not be removed or modified until the build or layer that the SBOM belongs to is done.
+    ///
     /// Note that there is no validation performed by libcnb.rs, the CNB lifecycle will error at
     /// runtime should the SBOM be invalid.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the path doesn't exist or isn't a file.
     pub fn from_path<P: AsRef<Path>>(format: SbomFormat, path: P) -> std::io::Result<Self> {
-        fs::read(path.as_ref()).map(|data| Self { format, data })
+        let path = path.as_ref();
+
+        if fs::metadata(path)?.is_file() {
+            Ok(Self {
+                format,
+                data: SbomData::File(path.to_path_buf()),
+            })
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("SBOM path {} is not a file", path.display()),
+            ))
+        }
     }
 
     /// Constructs an `Sbom` from the given bytes, treating it as the SBOM format specified.
@@ -35,7 +66,24 @@ impl Sbom {
     pub fn from_bytes<D: Into<Vec<u8>>>(format: SbomFormat, data: D) -> Self {
         Self {
             format,
-            data: data.into(),
+            data: SbomData::Bytes(data.into()),
+        }
+    }
+
+    /// Writes the SBOM to the given path. SBOMs stored in a file are streamed, without reading
+    /// them into memory as a whole.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the SBOM couldn't be read or written.
+    pub fn write_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        match &self.data {
+            SbomData::Bytes(bytes) => fs::write(path, bytes),
+            // Copying between files uses `copy_file_range` or `sendfile` where available, so the
+            // contents don't even pass through a userspace buffer.
+            SbomData::File(source) => {
+                io::copy(&mut File::open(source)?, &mut File::create(path)?).map(|_| ())
+            }
         }
     }
 }
@@ -51,7 +99,7 @@ impl TryFrom<cyclonedx_bom::models::bom::Bom> for Sbom {
 
         Ok(Self {
             format: SbomFormat::CycloneDxJson,
-            data,
+            data: SbomData::Bytes(data),
         })
     }
 }
@@ -71,3 +119,35 @@ pub(crate) fn cnb_sbom_path<P: AsRef<Path>>(
         .as_ref()
         .join(format!("{base_name}.sbom.{suffix}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_path_streams_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source_path = temp_dir.path().join("source.json");
+        let sbom_path = temp_dir.path().join("launch.sbom.cdx.json");
+        fs::write(&source_path, r#"{"bomFormat":"CycloneDX"}"#).unwrap();
+
+        let sbom = Sbom::from_path(SbomFormat::CycloneDxJson, &source_path).unwrap();
+        assert_eq!(sbom.data, SbomData::File(source_path.clone()));
+
+        fs::write(
+            &source_path,
+            r#"{"bomFormat":"CycloneDX","specVersion":"1.3"}"#,
+        )
+        .unwrap();
+        sbom.write_to(&sbom_path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&sbom_path).unwrap(),
+            r#"{"bomFormat":"CycloneDX","specVersion":"1.3"}"#
+        );
+
+        assert!(
+            Sbom::from_path(SbomFormat::CycloneDxJson, temp_dir.path().join("missing")).is_err()
+        );
+        assert!(Sbom::from_path(SbomFormat::CycloneDxJson, temp_dir.path()).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! Quoting of values for POSIX shell scripts, such as the `profile.d` scripts of a layer or
+//! wrapper scripts written by a buildpack.
+//!
+//! Values are quoted with single quotes, in which the shell doesn't expand anything. Values that
+//! only consist of characters without a special meaning are left as is, so that scripts stay
+//! readable.
+//!
+//! # Examples
+//! ```
+//! use libcnb::shell;
+//!
+//! assert_eq!(shell::quote("/layers/ruby/bin"), "/layers/ruby/bin");
+//! assert_eq!(shell::quote("it's $HOME"), r"'it'\''s $HOME'");
+//!
+//! let script = format!(
+//!     "#!/usr/bin/env bash\n{}\nexec {} \"$@\"\n",
+//!     shell::export("JAVA_OPTS", "-Xmx512m -Dfile.encoding=UTF-8").unwrap(),
+//!     shell::join(["/layers/jdk/bin/java", "-jar", "/workspace/my app.jar"]),
+//! );
+//! assert_eq!(
+//!     script,
+//!     "#!/usr/bin/env bash\nexport JAVA_OPTS='-Xmx512m -Dfile.encoding=UTF-8'\nexec /layers/jdk/bin/java -jar '/workspace/my app.jar' \"$@\"\n"
+//! );
+//! ```
+use std::ffi::OsStr;
+
+/// Quotes a value so that a POSIX shell reads it as a single word with the exact same contents.
+///
+/// Values containing `=` are always quoted, so that they aren't read as variable assignments
+/// when they are the first word of a command.
+///
+/// On unix, bytes of values that aren't valid UTF-8 are written as octal escapes for `printf`
+/// in a command substitution. On other platforms, such values are converted lossily.
+pub fn quote(value: impl AsRef<OsStr>) -> String {
+    let value = value.as_ref();
+
+    match value.to_str() {
+        Some(value) => quote_str(value),
+        None => quote_non_utf8(value),
+    }
+}
+
+fn quote_str(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:,+@%".contains(c))
+    {
+        String::from(value)
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(unix)]
+fn quote_non_utf8(value: &OsStr) -> String {
+    use std::fmt::Write;
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut quoted = String::new();
+    let mut rest = value.as_bytes();
+
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(_) => (rest, &[][..]),
+            Err(error) => {
+                let (valid, invalid) = rest.split_at(error.valid_up_to());
+                let (invalid, remaining) =
+                    invalid.split_at(error.error_len().unwrap_or(invalid.len()));
+                rest = remaining;
+                (valid, invalid)
+            }
+        };
+
+        if !valid.is_empty() {
+            quoted.push_str(&quote_str(&String::from_utf8_lossy(valid)));
+        }
+
+        if invalid.is_empty() {
+            break;
+        }
+
+        quoted.push_str("\"$(printf '");
+        for byte in invalid {
+            let _ = write!(quoted, "\\{byte:03o}");
+        }
+        quoted.push_str("')\"");
+    }
+
+    quoted
+}
+
+#[cfg(not(unix))]
+fn quote_non_utf8(value: &OsStr) -> String {
+    quote_str(&value.to_string_lossy())
+}
+
+/// Quotes all values with [`quote`] and joins them with spaces, for example to write a command
+/// line to a script.
+pub fn join<I, S>(values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    values.into_iter().map(quote).collect::<Vec<_>>().join(" ")
+}
+
+/// A statement that exports the given environment variable with the given value, such as
+/// `export PATH='/layers/ruby/bin:/usr/bin'`.
+///
+/// # Errors
+///
+/// Will return `Err` if the name isn't a valid shell variable name, which consists of ASCII
+/// letters, digits and underscores and doesn't start with a digit.
+pub fn export(
+    name: impl AsRef<str>,
+    value: impl AsRef<OsStr>,
+) -> Result<String, InvalidVariableName> {
+    let name = name.as_ref();
+
+    if is_valid_variable_name(name) {
+        Ok(format!("export {name}={}", quote(value)))
+    } else {
+        Err(InvalidVariableName(name.to_string()))
+    }
+}
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+#[error("Invalid shell variable name: {0:?}")]
+pub struct InvalidVariableName(pub String);
+
+fn is_valid_variable_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn quote_values() {
+        assert_eq!(quote("bundle"), "bundle");
+        assert_eq!(quote("--path=vendor/bundle"), "'--path=vendor/bundle'");
+        assert_eq!(quote("FOO=bar"), "'FOO=bar'");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("my app"), "'my app'");
+        assert_eq!(quote("$HOME"), "'$HOME'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote("''"), r"''\'''\'''");
+        assert_eq!(quote("~"), "'~'");
+        assert_eq!(quote("a\nb"), "'a\nb'");
+    }
+
+    #[test]
+    fn export_statements() {
+        assert_eq!(
+            export("RUBY_VERSION", "3.3.1"),
+            Ok(String::from("export RUBY_VERSION=3.3.1"))
+        );
+        assert_eq!(
+            export("_OPTS", "-a -b"),
+            Ok(String::from("export _OPTS='-a -b'"))
+        );
+        assert_eq!(
+            export("1VAR", "value"),
+            Err(InvalidVariableName(String::from("1VAR")))
+        );
+        assert_eq!(
+            export("VAR; rm -rf /", "value"),
+            Err(InvalidVariableName(String::from("VAR; rm -rf /")))
+        );
+        assert!(export("", "value").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn round_trip_through_shell() {
+        let values = [
+            "plain",
+            "",
+            "with space",
+            "single ' quote",
+            "double \" quote",
+            "$HOME `id` $(id) \\ * ? [a] ; & | < > ! #",
+            "multi\nline\ttab",
+            "'",
+        ];
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!("printf '%s\\0' {}", join(values)))
+            .output()
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            values.map(|value| format!("{value}\0")).concat()
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn round_trip_non_utf8_through_shell() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let value = OsStr::from_bytes(b"caf\xE9 it's \xFF\xFE");
+        assert_eq!(
+            quote(value),
+            r#"caf"$(printf '\351')"' it'\''s '"$(printf '\377')""$(printf '\376')""#
+        );
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!("printf '%s' {}", quote(value)))
+            .output()
+            .unwrap();
+
+        assert_eq!(output.stdout, value.as_bytes());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn assignment_is_not_read_as_variable() {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "{} 2>/dev/null; echo $FOO",
+                join(["FOO=bar", "true"])
+            ))
+            .output()
+            .unwrap();
+
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "\n");
+    }
+}
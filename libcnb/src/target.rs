@@ -1,3 +1,8 @@
+use libcnb_data::buildpack::BuildpackTarget;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Target {
     /// The name of the target operating system.
     ///
@@ -33,3 +38,400 @@ pub struct Target {
     /// CNB `lifecycle` sources this value from the build OCI image's `io.buildpacks.base.distro.version` label.
     pub distro_version: Option<String>,
 }
+
+impl Target {
+    /// The target CPU architecture as an [`Arch`].
+    #[must_use]
+    pub fn architecture(&self) -> Arch {
+        Arch::from(self.arch.as_str())
+    }
+
+    /// Returns true if the target operating system is `linux`.
+    #[must_use]
+    pub fn is_linux(&self) -> bool {
+        self.os == "linux"
+    }
+
+    /// Returns true if the target operating system is `windows`.
+    #[must_use]
+    pub fn is_windows(&self) -> bool {
+        self.os == "windows"
+    }
+
+    /// Returns true if the target distribution has the given name and its version satisfies the
+    /// given requirement.
+    ///
+    /// The requirement is a version, optionally prefixed with one of the comparison operators
+    /// `=`, `>`, `>=`, `<` or `<=`. A requirement without an operator matches the exact version,
+    /// an empty requirement or `*` matches any version. Versions are compared component-wise,
+    /// numerically where possible, so `22.10` is greater than `22.04`.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::Target;
+    ///
+    /// let target = Target {
+    ///     os: String::from("linux"),
+    ///     arch: String::from("amd64"),
+    ///     arch_variant: None,
+    ///     distro_name: Some(String::from("ubuntu")),
+    ///     distro_version: Some(String::from("22.04")),
+    /// };
+    ///
+    /// assert!(target.is_distro("ubuntu", ">=22.04"));
+    /// assert!(target.is_distro("ubuntu", "22.04"));
+    /// assert!(!target.is_distro("ubuntu", "<22.04"));
+    /// assert!(!target.is_distro("alpine", "*"));
+    /// ```
+    #[must_use]
+    pub fn is_distro(&self, name: &str, version_requirement: &str) -> bool {
+        if self.distro_name.as_deref() != Some(name) {
+            return false;
+        }
+
+        let version_requirement = version_requirement.trim();
+        if version_requirement.is_empty() || version_requirement == "*" {
+            return true;
+        }
+
+        let Some(distro_version) = &self.distro_version else {
+            return false;
+        };
+
+        let (accepted_orderings, required_version): (&[Ordering], &str) =
+            if let Some(version) = version_requirement.strip_prefix(">=") {
+                (&[Ordering::Greater, Ordering::Equal], version)
+            } else if let Some(version) = version_requirement.strip_prefix("<=") {
+                (&[Ordering::Less, Ordering::Equal], version)
+            } else if let Some(version) = version_requirement.strip_prefix('>') {
+                (&[Ordering::Greater], version)
+            } else if let Some(version) = version_requirement.strip_prefix('<') {
+                (&[Ordering::Less], version)
+            } else if let Some(version) = version_requirement.strip_prefix('=') {
+                (&[Ordering::Equal], version)
+            } else {
+                (&[Ordering::Equal], version_requirement)
+            };
+
+        accepted_orderings.contains(&compare_versions(distro_version, required_version.trim()))
+    }
+
+    /// Formats the target as an OCI platform string, for example `linux/amd64` or `linux/arm/v7`.
+    #[must_use]
+    pub fn oci_platform(&self) -> String {
+        match &self.arch_variant {
+            Some(variant) => format!("{}/{}/{variant}", self.os, self.arch),
+            None => format!("{}/{}", self.os, self.arch),
+        }
+    }
+
+    /// Returns the Rust target triple for this target, for example `x86_64-unknown-linux-gnu`.
+    ///
+    /// Targets with the `alpine` distribution use the `musl` environment. 32-bit ARM is only
+    /// supported on Linux with the `v7` variant, which uses the hard-float ABI, for example
+    /// `armv7-unknown-linux-gnueabihf`. Returns `None` for operating systems or architectures
+    /// without a well-known triple.
+    #[must_use]
+    pub fn rust_target_triple(&self) -> Option<String> {
+        let (arch, abi) = match self.architecture() {
+            Arch::Arm if self.arch_variant.as_deref() == Some("v7") => ("armv7", "eabihf"),
+            architecture => (architecture.rust_name()?, ""),
+        };
+
+        match self.os.as_str() {
+            "linux" => {
+                let env = if self.distro_name.as_deref() == Some("alpine") {
+                    "musl"
+                } else {
+                    "gnu"
+                };
+
+                Some(format!("{arch}-unknown-linux-{env}{abi}"))
+            }
+            "windows" if abi.is_empty() => Some(format!("{arch}-pc-windows-msvc")),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this target matches the given target declared in a buildpack descriptor.
+    ///
+    /// Fields that are not set in the declared target match any value. If the declared target
+    /// has distros, the distribution of this target must match one of them exactly.
+    ///
+    /// # Examples
+    /// ```
+    /// use libcnb::data::buildpack::{BuildpackTarget, Distro};
+    /// use libcnb::Target;
+    ///
+    /// let target = Target {
+    ///     os: String::from("linux"),
+    ///     arch: String::from("arm64"),
+    ///     arch_variant: None,
+    ///     distro_name: Some(String::from("ubuntu")),
+    ///     distro_version: Some(String::from("24.04")),
+    /// };
+    ///
+    /// let buildpack_target = BuildpackTarget {
+    ///     os: Some(String::from("linux")),
+    ///     arch: None,
+    ///     variant: None,
+    ///     distros: vec![Distro {
+    ///         name: String::from("ubuntu"),
+    ///         version: String::from("24.04"),
+    ///     }],
+    /// };
+    ///
+    /// assert!(target.matches(&buildpack_target));
+    /// ```
+    #[must_use]
+    pub fn matches(&self, buildpack_target: &BuildpackTarget) -> bool {
+        let field_matches = |declared: &Option<String>, actual: Option<&str>| {
+            declared
+                .as_deref()
+                .map_or(true, |declared| Some(declared) == actual)
+        };
+
+        field_matches(&buildpack_target.os, Some(&self.os))
+            && field_matches(&buildpack_target.arch, Some(&self.arch))
+            && field_matches(&buildpack_target.variant, self.arch_variant.as_deref())
+            && (buildpack_target.distros.is_empty()
+                || buildpack_target.distros.iter().any(|distro| {
+                    self.distro_name.as_deref() == Some(distro.name.as_str())
+                        && self.distro_version.as_deref() == Some(distro.version.as_str())
+                }))
+    }
+
+    /// Returns true if this target matches any of the given targets declared in a buildpack
+    /// descriptor, see [`Target::matches`]. A buildpack that declares no targets supports all
+    /// targets.
+    #[must_use]
+    pub fn matches_any(&self, buildpack_targets: &[BuildpackTarget]) -> bool {
+        buildpack_targets.is_empty()
+            || buildpack_targets
+                .iter()
+                .any(|buildpack_target| self.matches(buildpack_target))
+    }
+}
+
+/// Compares two dot-separated versions component-wise. Components that are numeric on both sides
+/// are compared numerically, all others lexicographically. Missing components are treated as `0`.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut a_components = a.split('.');
+    let mut b_components = b.split('.');
+
+    loop {
+        let ordering = match (a_components.next(), b_components.next()) {
+            (None, None) => return Ordering::Equal,
+            (a_component, b_component) => {
+                let a_component = a_component.unwrap_or("0");
+                let b_component = b_component.unwrap_or("0");
+
+                match (a_component.parse::<u64>(), b_component.parse::<u64>()) {
+                    (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number),
+                    _ => a_component.cmp(b_component),
+                }
+            }
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// A CPU architecture, as used in [`Target::arch`].
+///
+/// Known architectures use the names of [Go's `$GOARCH`](https://golang.org/doc/install/source#environment).
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Arch {
+    Amd64,
+    Arm64,
+    Arm,
+    I386,
+    Ppc64le,
+    S390x,
+    Other(String),
+}
+
+impl Arch {
+    /// The name of the architecture as used in Rust target triples, for example `x86_64`.
+    ///
+    /// Returns `None` for [`Arch::Arm`], since its name in target triples depends on the
+    /// variant, see [`Target::rust_target_triple`].
+    #[must_use]
+    pub fn rust_name(&self) -> Option<&'static str> {
+        match self {
+            Arch::Amd64 => Some("x86_64"),
+            Arch::Arm64 => Some("aarch64"),
+            Arch::I386 => Some("i686"),
+            Arch::Ppc64le => Some("powerpc64le"),
+            Arch::S390x => Some("s390x"),
+            Arch::Arm | Arch::Other(_) => None,
+        }
+    }
+}
+
+impl From<&str> for Arch {
+    fn from(value: &str) -> Self {
+        match value {
+            "amd64" => Arch::Amd64,
+            "arm64" => Arch::Arm64,
+            "arm" => Arch::Arm,
+            "386" => Arch::I386,
+            "ppc64le" => Arch::Ppc64le,
+            "s390x" => Arch::S390x,
+            other => Arch::Other(String::from(other)),
+        }
+    }
+}
+
+impl Display for Arch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Arch::Amd64 => "amd64",
+            Arch::Arm64 => "arm64",
+            Arch::Arm => "arm",
+            Arch::I386 => "386",
+            Arch::Ppc64le => "ppc64le",
+            Arch::S390x => "s390x",
+            Arch::Other(other) => other,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libcnb_data::buildpack::Distro;
+
+    fn linux_target(arch: &str, distro_name: &str, distro_version: &str) -> Target {
+        Target {
+            os: String::from("linux"),
+            arch: String::from(arch),
+            arch_variant: None,
+            distro_name: Some(String::from(distro_name)),
+            distro_version: Some(String::from(distro_version)),
+        }
+    }
+
+    #[test]
+    fn is_distro_version_requirements() {
+        let target = linux_target("amd64", "ubuntu", "22.10");
+
+        assert!(target.is_distro("ubuntu", ""));
+        assert!(target.is_distro("ubuntu", "*"));
+        assert!(target.is_distro("ubuntu", "22.10"));
+        assert!(target.is_distro("ubuntu", "=22.10"));
+        assert!(target.is_distro("ubuntu", ">=22.04"));
+        assert!(target.is_distro("ubuntu", "> 22.04"));
+        assert!(target.is_distro("ubuntu", "<24.04"));
+        assert!(target.is_distro("ubuntu", "<=22.10"));
+        assert!(!target.is_distro("ubuntu", ">22.10"));
+        assert!(!target.is_distro("ubuntu", "22.04"));
+        assert!(!target.is_distro("debian", "*"));
+    }
+
+    #[test]
+    fn compare_versions_handles_differing_lengths() {
+        assert_eq!(compare_versions("3.19", "3.19.0"), Ordering::Equal);
+        assert_eq!(compare_versions("3.19.1", "3.19"), Ordering::Greater);
+        assert_eq!(compare_versions("3.9", "3.19"), Ordering::Less);
+    }
+
+    #[test]
+    fn architecture_round_trip() {
+        for name in [
+            "amd64", "arm64", "arm", "386", "ppc64le", "s390x", "riscv64",
+        ] {
+            assert_eq!(Arch::from(name).to_string(), name);
+        }
+
+        assert_eq!(
+            linux_target("arm64", "ubuntu", "22.04").architecture(),
+            Arch::Arm64
+        );
+    }
+
+    #[test]
+    fn formatting_helpers() {
+        let mut target = linux_target("arm64", "ubuntu", "22.04");
+        assert_eq!(target.oci_platform(), "linux/arm64");
+        assert_eq!(
+            target.rust_target_triple(),
+            Some(String::from("aarch64-unknown-linux-gnu"))
+        );
+
+        target.arch = String::from("arm");
+        target.arch_variant = Some(String::from("v7"));
+        assert_eq!(target.oci_platform(), "linux/arm/v7");
+        assert_eq!(
+            target.rust_target_triple(),
+            Some(String::from("armv7-unknown-linux-gnueabihf"))
+        );
+        target.distro_name = Some(String::from("alpine"));
+        assert_eq!(
+            target.rust_target_triple(),
+            Some(String::from("armv7-unknown-linux-musleabihf"))
+        );
+        target.arch_variant = Some(String::from("v6"));
+        assert_eq!(target.rust_target_triple(), None);
+        target.arch_variant = None;
+        assert_eq!(target.rust_target_triple(), None);
+
+        let alpine = linux_target("amd64", "alpine", "3.19");
+        assert_eq!(
+            alpine.rust_target_triple(),
+            Some(String::from("x86_64-unknown-linux-musl"))
+        );
+
+        assert_eq!(
+            linux_target("riscv64", "ubuntu", "24.04").rust_target_triple(),
+            None
+        );
+    }
+
+    #[test]
+    fn matches_buildpack_targets() {
+        let target = linux_target("arm", "ubuntu", "22.04");
+        let target = Target {
+            arch_variant: Some(String::from("v7")),
+            ..target
+        };
+
+        let buildpack_target =
+            |os: Option<&str>, arch: Option<&str>, variant: Option<&str>| BuildpackTarget {
+                os: os.map(String::from),
+                arch: arch.map(String::from),
+                variant: variant.map(String::from),
+                distros: Vec::new(),
+            };
+
+        assert!(target.matches(&buildpack_target(None, None, None)));
+        assert!(target.matches(&buildpack_target(Some("linux"), None, None)));
+        assert!(target.matches(&buildpack_target(Some("linux"), Some("arm"), Some("v7"))));
+        assert!(!target.matches(&buildpack_target(Some("windows"), None, None)));
+        assert!(!target.matches(&buildpack_target(Some("linux"), Some("amd64"), None)));
+        assert!(!target.matches(&buildpack_target(Some("linux"), Some("arm"), Some("v8"))));
+
+        let with_distros = |versions: &[&str]| BuildpackTarget {
+            distros: versions
+                .iter()
+                .map(|version| Distro {
+                    name: String::from("ubuntu"),
+                    version: String::from(*version),
+                })
+                .collect(),
+            ..buildpack_target(Some("linux"), None, None)
+        };
+
+        assert!(target.matches(&with_distros(&["20.04", "22.04"])));
+        assert!(!target.matches(&with_distros(&["24.04"])));
+
+        assert!(target.matches_any(&[]));
+        assert!(target.matches_any(&[
+            buildpack_target(Some("windows"), None, None),
+            buildpack_target(Some("linux"), None, None)
+        ]));
+        assert!(!target.matches_any(&[buildpack_target(Some("windows"), None, None)]));
+    }
+}
@@ -0,0 +1,451 @@
+//! Provides spans, events and metrics that buildpacks can use to add detail to the telemetry
+//! recorded by libcnb.
+//!
+//! When the `trace` feature is enabled, libcnb records a trace with a span for the detect or build
+//! phase. The spans created with this module become children of that span, or of the enclosing
+//! span created by [`in_span`]. Metrics recorded with [`counter`] and [`histogram`] are exported
+//! alongside the trace when the phase ends. Without the `trace` feature, or when used outside of
+//! the thread that runs detect or build, all functions in this module are no-ops, so buildpacks
+//! can use them unconditionally.
+//!
+//! Metrics are only written to the file export. The OTLP export of the `trace-otlp` feature only
+//! exports spans, so metrics are dropped if the file export is disabled via
+//! `OTEL_TRACES_EXPORTER`. libcnb prints a warning when that happens while spans are exported via
+//! OTLP.
+//!
+//! # Examples
+//! ```
+//! use libcnb::telemetry;
+//!
+//! let version = telemetry::in_span("resolve-version", |span| {
+//!     span.set_attribute("requirement", "^1.2");
+//!     let version = "1.2.3";
+//!     span.set_attribute("version", version);
+//!     version
+//! });
+//!
+//! telemetry::add_event("download-started");
+//!
+//! telemetry::counter("cache.hits", "{hit}").add(1, &[("cache", "dependencies")]);
+//! telemetry::histogram("compile.duration", "s").record(12.5, &[]);
+//! ```
+
+#[cfg(feature = "trace")]
+use opentelemetry::{
+    metrics::Unit,
+    trace::{get_active_span, Status, TraceContextExt},
+    Context, KeyValue,
+};
+
+/// The value of a span attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        Self::String(String::from(value))
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<i64> for AttributeValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for AttributeValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// A span created by a buildpack. The span ends when it is dropped.
+#[derive(Debug)]
+pub struct Span {
+    #[cfg(feature = "trace")]
+    context: Option<Context>,
+}
+
+impl Span {
+    /// Adds an event with the given name to this span.
+    pub fn add_event(&self, name: impl Into<String>) {
+        #[cfg(feature = "trace")]
+        if let Some(context) = &self.context {
+            context.span().add_event(name.into(), Vec::new());
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = name.into();
+    }
+
+    /// Sets an attribute on this span.
+    pub fn set_attribute(&self, key: impl Into<String>, value: impl Into<AttributeValue>) {
+        #[cfg(feature = "trace")]
+        if let Some(context) = &self.context {
+            let value = match value.into() {
+                AttributeValue::String(value) => opentelemetry::Value::from(value),
+                AttributeValue::Int(value) => opentelemetry::Value::from(value),
+                AttributeValue::Float(value) => opentelemetry::Value::from(value),
+                AttributeValue::Bool(value) => opentelemetry::Value::from(value),
+            };
+
+            context
+                .span()
+                .set_attribute(KeyValue::new(key.into(), value));
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = (key.into(), value.into());
+    }
+
+    /// Returns whether this span is recorded. Use this to skip computing expensive attributes
+    /// when tracing is disabled.
+    #[must_use]
+    pub fn is_recording(&self) -> bool {
+        #[cfg(feature = "trace")]
+        {
+            self.context
+                .as_ref()
+                .is_some_and(|context| context.span().is_recording())
+        }
+
+        #[cfg(not(feature = "trace"))]
+        false
+    }
+
+    /// Marks this span as failed and records the given error on it.
+    pub fn set_error(&self, error: &dyn std::error::Error) {
+        #[cfg(feature = "trace")]
+        if let Some(context) = &self.context {
+            let span = context.span();
+            span.set_status(Status::error(error.to_string()));
+            span.record_error(error);
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = error;
+    }
+
+    /// Marks this span as failed with the given message. Use this for errors that don't
+    /// implement [`std::error::Error`].
+    pub fn set_error_message(&self, message: impl Into<String>) {
+        #[cfg(feature = "trace")]
+        if let Some(context) = &self.context {
+            context.span().set_status(Status::error(message.into()));
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = message.into();
+    }
+
+    /// Ends this span. Equivalent to dropping it.
+    pub fn end(self) {}
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        #[cfg(feature = "trace")]
+        if let Some(context) = &self.context {
+            context.span().end();
+        }
+    }
+}
+
+/// Starts a new span with the given name as a child of the currently active span.
+///
+/// The returned span isn't made the active span, spans started while it is alive are siblings
+/// rather than children. Use [`in_span`] to create nested spans.
+#[must_use]
+pub fn span(name: impl Into<String>) -> Span {
+    #[cfg(feature = "trace")]
+    {
+        Span {
+            context: crate::tracing::start_child_span(name.into()),
+        }
+    }
+
+    #[cfg(not(feature = "trace"))]
+    {
+        let _ = name.into();
+        Span {}
+    }
+}
+
+/// Runs the given function in a new span with the given name. The span is the active span while
+/// the function runs, so spans and events created by the function are nested in it.
+///
+/// If the function returns an [`Err`](Result::Err), use [`Span::set_error`] to mark the span as
+/// failed.
+pub fn in_span<T>(name: impl Into<String>, f: impl FnOnce(&Span) -> T) -> T {
+    let span = span(name);
+
+    #[cfg(feature = "trace")]
+    let _guard = span.context.clone().map(Context::attach);
+
+    f(&span)
+}
+
+/// Adds an event with the given name to the currently active span.
+pub fn add_event(name: impl Into<String>) {
+    #[cfg(feature = "trace")]
+    get_active_span(|span| span.add_event(name.into(), Vec::new()));
+
+    #[cfg(not(feature = "trace"))]
+    let _ = name.into();
+}
+
+/// A counter for monotonically increasing values, created with [`counter`].
+#[derive(Clone, Debug)]
+pub struct Counter {
+    #[cfg(feature = "trace")]
+    inner: Option<opentelemetry::metrics::Counter<u64>>,
+}
+
+impl Counter {
+    /// Adds the given value to the counter, with the given attributes.
+    pub fn add(&self, value: u64, attributes: &[(&str, &str)]) {
+        #[cfg(feature = "trace")]
+        if let Some(inner) = &self.inner {
+            inner.add(value, &key_values(attributes));
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = (value, attributes);
+    }
+}
+
+/// A histogram for recording the distribution of values, such as durations or sizes, created
+/// with [`histogram`].
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    #[cfg(feature = "trace")]
+    inner: Option<opentelemetry::metrics::Histogram<f64>>,
+}
+
+impl Histogram {
+    /// Records the given value, with the given attributes.
+    pub fn record(&self, value: f64, attributes: &[(&str, &str)]) {
+        #[cfg(feature = "trace")]
+        if let Some(inner) = &self.inner {
+            inner.record(value, &key_values(attributes));
+        }
+
+        #[cfg(not(feature = "trace"))]
+        let _ = (value, attributes);
+    }
+}
+
+/// Returns the counter with the given name and unit, such as `By` for bytes or `{file}` for a
+/// number of files. See <https://opentelemetry.io/docs/specs/semconv/general/metrics/> for
+/// naming conventions.
+#[must_use]
+pub fn counter(name: impl Into<String>, unit: impl Into<String>) -> Counter {
+    #[cfg(feature = "trace")]
+    {
+        Counter {
+            inner: crate::tracing::current_meter().map(|meter| {
+                meter
+                    .u64_counter(name.into())
+                    .with_unit(Unit::new(unit.into()))
+                    .init()
+            }),
+        }
+    }
+
+    #[cfg(not(feature = "trace"))]
+    {
+        let _ = (name.into(), unit.into());
+        Counter {}
+    }
+}
+
+/// Returns the histogram with the given name and unit, such as `s` for seconds or `By` for
+/// bytes. See <https://opentelemetry.io/docs/specs/semconv/general/metrics/> for naming
+/// conventions.
+#[must_use]
+pub fn histogram(name: impl Into<String>, unit: impl Into<String>) -> Histogram {
+    #[cfg(feature = "trace")]
+    {
+        Histogram {
+            inner: crate::tracing::current_meter().map(|meter| {
+                meter
+                    .f64_histogram(name.into())
+                    .with_unit(Unit::new(unit.into()))
+                    .init()
+            }),
+        }
+    }
+
+    #[cfg(not(feature = "trace"))]
+    {
+        let _ = (name.into(), unit.into());
+        Histogram {}
+    }
+}
+
+#[cfg(feature = "trace")]
+fn key_values(attributes: &[(&str, &str)]) -> Vec<KeyValue> {
+    attributes
+        .iter()
+        .map(|(key, value)| KeyValue::new(String::from(*key), String::from(*value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn nests_spans_under_phase_span() {
+        use crate::tracing::start_trace;
+        use libcnb_data::{
+            buildpack::{Buildpack, BuildpackVersion},
+            buildpack_id,
+        };
+        use serde_json::Value;
+        use std::{collections::HashSet, fs, path::Path};
+
+        let buildpack = Buildpack {
+            id: buildpack_id!("company.com/telemetry"),
+            version: BuildpackVersion::new(0, 0, 1),
+            name: None,
+            homepage: None,
+            clear_env: false,
+            description: None,
+            keywords: Vec::new(),
+            licenses: Vec::new(),
+            sbom_formats: HashSet::new(),
+        };
+        let telemetry_path = "/tmp/libcnb-telemetry/company_com_telemetry-build.jsonl";
+        _ = fs::remove_file(telemetry_path);
+
+        {
+            let _trace = start_trace(&buildpack, "build", Path::new("/does-not-exist"), &[]);
+            in_span("outer", |outer| {
+                outer.set_attribute("answer", "42");
+                add_event("outer-event");
+                span("inner").add_event("inner-event");
+            });
+        }
+
+        let contents = fs::read_to_string(telemetry_path).unwrap();
+        let spans: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .flat_map(|export| {
+                export["resourceSpans"][0]["scopeSpans"][0]["spans"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let span_named = |name: &str| {
+            spans
+                .iter()
+                .find(|span| span["name"] == name)
+                .unwrap_or_else(|| panic!("Missing span {name} in {contents}"))
+        };
+        let phase = span_named("company_com_telemetry-build");
+        let outer = span_named("outer");
+        let inner = span_named("inner");
+
+        assert_eq!(outer["parentSpanId"], phase["spanId"]);
+        assert_eq!(inner["parentSpanId"], outer["spanId"]);
+        assert_eq!(outer["events"][0]["name"], "outer-event");
+        assert_eq!(inner["events"][0]["name"], "inner-event");
+        assert!(contents.contains("{\"key\":\"answer\",\"value\":{\"stringValue\":\"42\"}}"));
+    }
+
+    #[test]
+    fn is_noop_without_running_trace() {
+        let span = span("no-trace");
+        span.set_attribute("key", "value");
+        span.add_event("event");
+        add_event("event");
+        assert_eq!(in_span("no-trace", |_| 1), 1);
+        counter("no-trace", "1").add(1, &[]);
+        histogram("no-trace", "s").record(1.0, &[]);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn exports_metrics_with_trace() {
+        use crate::tracing::start_trace;
+        use libcnb_data::{
+            buildpack::{Buildpack, BuildpackVersion},
+            buildpack_id,
+        };
+        use serde_json::Value;
+        use std::{collections::HashSet, fs, path::Path};
+
+        let buildpack = Buildpack {
+            id: buildpack_id!("company.com/metrics"),
+            version: BuildpackVersion::new(0, 0, 1),
+            name: None,
+            homepage: None,
+            clear_env: false,
+            description: None,
+            keywords: Vec::new(),
+            licenses: Vec::new(),
+            sbom_formats: HashSet::new(),
+        };
+        let telemetry_path = "/tmp/libcnb-telemetry/company_com_metrics-build.jsonl";
+        _ = fs::remove_file(telemetry_path);
+
+        {
+            let _trace = start_trace(&buildpack, "build", Path::new("/does-not-exist"), &[]);
+            let hits = counter("cache.hits", "{hit}");
+            hits.add(2, &[("cache", "deps")]);
+            hits.add(3, &[("cache", "deps")]);
+            histogram("compile.duration", "s").record(1.5, &[]);
+        }
+
+        let contents = fs::read_to_string(telemetry_path).unwrap();
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect();
+
+        // The spans are written before the metrics.
+        assert!(lines[0].get("resourceSpans").is_some());
+        let metrics = &lines.last().unwrap()["resourceMetrics"];
+        let metric_named = |name: &str| {
+            metrics["scopeMetrics"][0]["metrics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|metric| metric["name"] == name)
+                .unwrap_or_else(|| panic!("Missing metric {name} in {contents}"))
+                .clone()
+        };
+
+        let hits = metric_named("cache.hits");
+        assert_eq!(hits["unit"], "{hit}");
+        assert_eq!(hits["sum"]["dataPoints"][0]["value"], 5);
+
+        let duration = metric_named("compile.duration");
+        assert_eq!(duration["histogram"]["dataPoints"][0]["count"], 1);
+        assert!(contents.contains(
+            "{\"key\":\"service.name\",\"value\":{\"stringValue\":\"company.com/metrics\"}}"
+        ));
+    }
+}
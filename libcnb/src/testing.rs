@@ -0,0 +1,780 @@
+//! Builders for [`BuildContext`] and [`DetectContext`] values in unit tests.
+//!
+//! Requires the `testing` feature, which is intended to be enabled for `dev-dependencies` only.
+//! For unit tests of [`Layer`](crate::layer::Layer) implementations, see
+//! [`LayerTestContext`](crate::layer::testing::LayerTestContext).
+
+use crate::build::BuildContext;
+use crate::data::buildpack::ComponentBuildpackDescriptor;
+use crate::data::buildpack_plan::{BuildpackPlan, Entry};
+use crate::data::lifecycle::Group;
+use crate::data::store::Store;
+use crate::detect::DetectContext;
+use crate::layer::HandledLayers;
+use crate::{read_toml_file, Buildpack, Deterministic, Platform, Target, Timings, Warnings};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Builds a [`BuildContext`] for unit tests.
+///
+/// All directories are created in the directory passed to [`BuildContextBuilder::build_in`],
+/// usually a temporary directory, except for the buildpack directory. It defaults to
+/// `CARGO_MANIFEST_DIR`, so that the `buildpack.toml` of the crate under test is used.
+///
+/// # Examples
+/// ```no_run
+/// use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+/// use libcnb::testing::BuildContextBuilder;
+/// use libcnb::Platform;
+/// # use libcnb::build::{BuildContext, BuildResult};
+/// # use libcnb::detect::{DetectContext, DetectResult};
+///
+/// # struct ExampleBuildpack;
+/// #
+/// # impl libcnb::Buildpack for ExampleBuildpack {
+/// #     type Platform = GenericPlatform;
+/// #     type Metadata = GenericMetadata;
+/// #     type Error = GenericError;
+/// #
+/// #     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+/// #         unimplemented!()
+/// #     }
+/// #
+/// #     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+/// #         unimplemented!()
+/// #     }
+/// # }
+/// #
+/// let temp_dir = tempfile::tempdir().unwrap();
+///
+/// let context = BuildContextBuilder::<ExampleBuildpack>::new()
+///     .platform_env("RUBY_VERSION", "3.3.1")
+///     .buildpack_plan_entry("ruby", toml::Table::new())
+///     .build_in(temp_dir.path());
+///
+/// assert_eq!(context.buildpack_plan.entries[0].name, "ruby");
+/// assert!(context.platform.env().contains_key("RUBY_VERSION"));
+/// ```
+pub struct BuildContextBuilder<B: Buildpack> {
+    config: ContextConfig<B>,
+    buildpack_plan: BuildpackPlan,
+    store: Option<Store>,
+    deterministic: Option<Deterministic>,
+    group: Option<Group>,
+}
+
+impl<B: Buildpack> BuildContextBuilder<B> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            config: ContextConfig::default(),
+            buildpack_plan: BuildpackPlan {
+                entries: Vec::new(),
+            },
+            store: None,
+            deterministic: None,
+            group: None,
+        }
+    }
+
+    /// Sets the buildpack directory, which defaults to `CARGO_MANIFEST_DIR`.
+    #[must_use]
+    pub fn buildpack_dir(mut self, buildpack_dir: impl Into<PathBuf>) -> Self {
+        self.config.buildpack_dir = Some(buildpack_dir.into());
+        self
+    }
+
+    /// Sets the buildpack descriptor, which defaults to the `buildpack.toml` in the buildpack
+    /// directory.
+    #[must_use]
+    pub fn buildpack_descriptor(
+        mut self,
+        buildpack_descriptor: ComponentBuildpackDescriptor<B::Metadata>,
+    ) -> Self {
+        self.config.buildpack_descriptor = Some(buildpack_descriptor);
+        self
+    }
+
+    /// Sets the app directory, such as a fixture directory. Defaults to an empty `app` directory.
+    #[must_use]
+    pub fn app_dir(mut self, app_dir: impl Into<PathBuf>) -> Self {
+        self.config.app_dir = Some(app_dir.into());
+        self
+    }
+
+    /// Sets the target, which defaults to `linux/amd64` on Ubuntu 22.04.
+    #[must_use]
+    pub fn target(mut self, target: Target) -> Self {
+        self.config.target = target;
+        self
+    }
+
+    /// Sets a platform environment variable, as set by users with `pack build --env`.
+    #[must_use]
+    pub fn platform_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.platform_env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Adds an entry to the buildpack plan.
+    #[must_use]
+    pub fn buildpack_plan_entry(mut self, name: impl Into<String>, metadata: toml::Table) -> Self {
+        self.buildpack_plan.entries.push(Entry {
+            name: name.into(),
+            metadata,
+        });
+        self
+    }
+
+    /// Sets the store of a previous build.
+    #[must_use]
+    pub fn store(mut self, store: Store) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Enables the deterministic build mode with the given settings.
+    #[must_use]
+    pub fn deterministic(mut self, deterministic: Deterministic) -> Self {
+        self.deterministic = Some(deterministic);
+        self
+    }
+
+    /// Sets the group of buildpacks that passed detection, which defaults to `None`, as if the
+    /// lifecycle didn't provide it.
+    #[must_use]
+    pub fn group(mut self, group: Group) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Creates the `layers`, `app` and `platform` directories in the given directory and builds
+    /// the context.
+    ///
+    /// # Panics
+    ///
+    /// If the directories or the platform can't be created, or if no buildpack descriptor was set
+    /// and the `buildpack.toml` in the buildpack directory can't be read.
+    #[must_use]
+    pub fn build_in(self, dir: impl AsRef<Path>) -> BuildContext<B> {
+        let layers_dir = dir.as_ref().join("layers");
+        fs::create_dir_all(&layers_dir).expect("Couldn't create layers directory");
+
+        let context = self.config.build_in(dir.as_ref());
+
+        BuildContext {
+            layers_dir,
+            app_dir: context.app_dir,
+            buildpack_dir: context.buildpack_dir,
+            target: context.target,
+            platform_dir: context.platform_dir,
+            platform: context.platform,
+            buildpack_plan: self.buildpack_plan,
+            buildpack_plan_path: dir.as_ref().join("buildpack-plan.toml"),
+            buildpack_descriptor: context.buildpack_descriptor,
+            warnings: Warnings::new(),
+            timings: Timings::new(),
+            handled_layers: HandledLayers::new(),
+            store: self.store,
+            deterministic: self.deterministic,
+            group: self.group,
+        }
+    }
+}
+
+impl<B: Buildpack> Default for BuildContextBuilder<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a [`DetectContext`] for unit tests.
+///
+/// See [`BuildContextBuilder`] for details.
+///
+/// # Examples
+/// ```no_run
+/// use libcnb::testing::DetectContextBuilder;
+/// # use libcnb::build::{BuildContext, BuildResult};
+/// # use libcnb::detect::{DetectContext, DetectResult};
+/// # use libcnb::generic::{GenericError, GenericMetadata, GenericPlatform};
+///
+/// # struct ExampleBuildpack;
+/// #
+/// # impl libcnb::Buildpack for ExampleBuildpack {
+/// #     type Platform = GenericPlatform;
+/// #     type Metadata = GenericMetadata;
+/// #     type Error = GenericError;
+/// #
+/// #     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+/// #         unimplemented!()
+/// #     }
+/// #
+/// #     fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+/// #         unimplemented!()
+/// #     }
+/// # }
+/// #
+/// let temp_dir = tempfile::tempdir().unwrap();
+///
+/// let context = DetectContextBuilder::<ExampleBuildpack>::new()
+///     .app_dir("tests/fixtures/rails-app")
+///     .build_in(temp_dir.path());
+///
+/// assert!(context.app_dir.join("Gemfile").exists());
+/// ```
+pub struct DetectContextBuilder<B: Buildpack> {
+    config: ContextConfig<B>,
+}
+
+impl<B: Buildpack> DetectContextBuilder<B> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            config: ContextConfig::default(),
+        }
+    }
+
+    /// Sets the buildpack directory, which defaults to `CARGO_MANIFEST_DIR`.
+    #[must_use]
+    pub fn buildpack_dir(mut self, buildpack_dir: impl Into<PathBuf>) -> Self {
+        self.config.buildpack_dir = Some(buildpack_dir.into());
+        self
+    }
+
+    /// Sets the buildpack descriptor, which defaults to the `buildpack.toml` in the buildpack
+    /// directory.
+    #[must_use]
+    pub fn buildpack_descriptor(
+        mut self,
+        buildpack_descriptor: ComponentBuildpackDescriptor<B::Metadata>,
+    ) -> Self {
+        self.config.buildpack_descriptor = Some(buildpack_descriptor);
+        self
+    }
+
+    /// Sets the app directory, such as a fixture directory. Defaults to an empty `app` directory.
+    #[must_use]
+    pub fn app_dir(mut self, app_dir: impl Into<PathBuf>) -> Self {
+        self.config.app_dir = Some(app_dir.into());
+        self
+    }
+
+    /// Sets the target, which defaults to `linux/amd64` on Ubuntu 22.04.
+    #[must_use]
+    pub fn target(mut self, target: Target) -> Self {
+        self.config.target = target;
+        self
+    }
+
+    /// Sets a platform environment variable, as set by users with `pack build --env`.
+    #[must_use]
+    pub fn platform_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.platform_env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Creates the `app` and `platform` directories in the given directory and builds the
+    /// context.
+    ///
+    /// # Panics
+    ///
+    /// If the directories or the platform can't be created, or if no buildpack descriptor was set
+    /// and the `buildpack.toml` in the buildpack directory can't be read.
+    #[must_use]
+    pub fn build_in(self, dir: impl AsRef<Path>) -> DetectContext<B> {
+        let context = self.config.build_in(dir.as_ref());
+
+        DetectContext {
+            app_dir: context.app_dir,
+            buildpack_dir: context.buildpack_dir,
+            target: context.target,
+            platform_dir: context.platform_dir,
+            platform: context.platform,
+            buildpack_descriptor: context.buildpack_descriptor,
+            build_plan_path: dir.as_ref().join("build-plan.toml"),
+            warnings: Warnings::new(),
+            timings: Timings::new(),
+        }
+    }
+}
+
+impl<B: Buildpack> Default for DetectContextBuilder<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The configuration shared by [`BuildContextBuilder`] and [`DetectContextBuilder`].
+struct ContextConfig<B: Buildpack> {
+    buildpack_dir: Option<PathBuf>,
+    buildpack_descriptor: Option<ComponentBuildpackDescriptor<B::Metadata>>,
+    app_dir: Option<PathBuf>,
+    target: Target,
+    platform_env: Vec<(String, String)>,
+}
+
+/// The fields shared by [`BuildContext`] and [`DetectContext`].
+struct CommonContext<B: Buildpack> {
+    app_dir: PathBuf,
+    buildpack_dir: PathBuf,
+    target: Target,
+    platform_dir: PathBuf,
+    platform: B::Platform,
+    buildpack_descriptor: ComponentBuildpackDescriptor<B::Metadata>,
+}
+
+impl<B: Buildpack> Default for ContextConfig<B> {
+    fn default() -> Self {
+        Self {
+            buildpack_dir: None,
+            buildpack_descriptor: None,
+            app_dir: None,
+            target: Target {
+                os: String::from("linux"),
+                arch: String::from("amd64"),
+                arch_variant: None,
+                distro_name: Some(String::from("ubuntu")),
+                distro_version: Some(String::from("22.04")),
+            },
+            platform_env: Vec::new(),
+        }
+    }
+}
+
+impl<B: Buildpack> ContextConfig<B> {
+    fn build_in(self, dir: &Path) -> CommonContext<B> {
+        let app_dir = self.app_dir.unwrap_or_else(|| {
+            let app_dir = dir.join("app");
+            fs::create_dir_all(&app_dir).expect("Couldn't create app directory");
+            app_dir
+        });
+
+        let platform_dir = dir.join("platform");
+        let platform_env_dir = platform_dir.join("env");
+        fs::create_dir_all(&platform_env_dir).expect("Couldn't create platform directory");
+        for (key, value) in &self.platform_env {
+            fs::write(platform_env_dir.join(key), value)
+                .expect("Couldn't write platform environment variable");
+        }
+
+        let buildpack_dir = self.buildpack_dir.unwrap_or_else(|| {
+            std::env::var_os("CARGO_MANIFEST_DIR")
+                .map(PathBuf::from)
+                .expect("CARGO_MANIFEST_DIR should be set when running tests with Cargo")
+        });
+
+        let buildpack_descriptor = self.buildpack_descriptor.unwrap_or_else(|| {
+            read_toml_file(buildpack_dir.join("buildpack.toml")).unwrap_or_else(|error| {
+                panic!(
+                    "Couldn't read buildpack.toml in {}: {error}",
+                    buildpack_dir.display()
+                )
+            })
+        });
+
+        CommonContext {
+            app_dir,
+            buildpack_dir,
+            target: self.target,
+            platform: B::Platform::from_path(&platform_dir)
+                .expect("Couldn't create platform from platform directory"),
+            platform_dir,
+            buildpack_descriptor,
+        }
+    }
+}
+
+/// Set to `true` to update the snapshots of [`assert_snapshot`] instead of comparing them.
+pub const UPDATE_SNAPSHOTS_ENV_VAR: &str = "LIBCNB_UPDATE_SNAPSHOTS";
+
+/// The maximum size of files whose contents are included in [`layers_snapshot`].
+const MAX_SNAPSHOT_FILE_SIZE: u64 = 4096;
+
+/// Compares a snapshot with the golden file at the given path, relative to `CARGO_MANIFEST_DIR`.
+///
+/// If the golden file doesn't exist yet, or [`UPDATE_SNAPSHOTS_ENV_VAR`] is set to `true`, the
+/// golden file is written instead. Golden files are never written if the `CI` environment variable
+/// is set, so that missing golden files fail the build there. Commit golden files to the
+/// repository, so that changes to them show up in code review.
+///
+/// # Examples
+/// ```no_run
+/// use libcnb::testing::{assert_snapshot, toml_snapshot};
+/// use libcnb::data::launch::{LaunchBuilder, ProcessBuilder};
+/// use libcnb::data::process_type;
+///
+/// let launch = LaunchBuilder::new()
+///     .process(ProcessBuilder::new(process_type!("web"), ["bundle", "exec", "rails", "server"]).build())
+///     .build();
+///
+/// assert_snapshot("tests/snapshots/launch.toml.snap", toml_snapshot(&launch));
+/// ```
+///
+/// # Panics
+///
+/// If the snapshot differs from the golden file, with a line diff between them, or if the golden
+/// file can't be read or written.
+pub fn assert_snapshot(path: impl AsRef<Path>, actual: impl AsRef<str>) {
+    let path = std::env::var_os("CARGO_MANIFEST_DIR").map_or_else(
+        || path.as_ref().to_path_buf(),
+        |dir| PathBuf::from(dir).join(&path),
+    );
+    let actual = actual.as_ref();
+
+    let update = std::env::var(UPDATE_SNAPSHOTS_ENV_VAR).is_ok_and(|value| value == "true");
+    let ci = std::env::var_os("CI").is_some();
+
+    match fs::read_to_string(&path) {
+        Ok(expected) if !update || ci => {
+            assert!(
+                expected == actual,
+                "Snapshot {} doesn't match (- expected, + actual):\n{}\nSet {UPDATE_SNAPSHOTS_ENV_VAR}=true to update it.",
+                path.display(),
+                line_diff(&expected, actual)
+            );
+        }
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+            panic!("Couldn't read snapshot {}: {error}", path.display());
+        }
+        _ if ci => panic!("Snapshot {} doesn't exist", path.display()),
+        _ => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect("Couldn't create snapshot directory");
+            }
+            fs::write(&path, actual).expect("Couldn't write snapshot");
+        }
+    }
+}
+
+/// Serializes a value, such as a [`Launch`](crate::data::launch::Launch), as TOML with sorted
+/// keys for [`assert_snapshot`].
+///
+/// # Panics
+///
+/// If the value can't be serialized as TOML.
+pub fn toml_snapshot(value: &impl serde::Serialize) -> String {
+    let table = toml::Table::try_from(value).expect("Couldn't serialize value as TOML");
+    sorted_table(&table).to_string()
+}
+
+/// Renders the contents of a layers directory, such as [`BuildContext::layers_dir`], as text for
+/// [`assert_snapshot`].
+///
+/// Every file is listed with its path relative to the layers directory, sorted by path. Layer TOML
+/// files are normalized with sorted keys. The contents of env files and other text files of up to
+/// 4 KiB are included, larger and binary files are listed with their size. Occurrences of the
+/// given paths, such as temporary directories, are replaced with their names, so that the
+/// snapshot is the same for every run.
+///
+/// # Panics
+///
+/// If the layers directory can't be read.
+pub fn layers_snapshot(layers_dir: impl AsRef<Path>, replacements: &[(&Path, &str)]) -> String {
+    let layers_dir = layers_dir.as_ref();
+    let mut files = Vec::new();
+    collect_files(layers_dir, &mut files);
+    files.sort();
+
+    files
+        .iter()
+        .map(|path| {
+            let relative_path = path
+                .strip_prefix(layers_dir)
+                .unwrap_or(path)
+                .to_string_lossy();
+
+            let contents = if path.is_symlink() {
+                fs::read_link(path).map_or_else(
+                    |_| String::from("<unreadable symlink>\n"),
+                    |target| format!("-> {}\n", target.display()),
+                )
+            } else {
+                file_snapshot(path)
+            };
+
+            let contents = replacements
+                .iter()
+                .fold(contents, |contents, (path, name)| {
+                    contents.replace(&*path.to_string_lossy(), name)
+                });
+
+            format!("## {relative_path}\n{contents}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Couldn't read directory") {
+        let path = entry.expect("Couldn't read directory").path();
+
+        if path.is_dir() && !path.is_symlink() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+fn file_snapshot(path: &Path) -> String {
+    let Ok(bytes) = fs::read(path) else {
+        return String::from("<unreadable>\n");
+    };
+
+    let is_toml = path
+        .extension()
+        .is_some_and(|extension| extension == "toml");
+    let text = String::from_utf8(bytes.clone()).ok();
+
+    match text {
+        Some(text) if is_toml => text
+            .parse::<toml::Table>()
+            .map_or(text, |table| sorted_table(&table).to_string()),
+        Some(text) if (bytes.len() as u64) <= MAX_SNAPSHOT_FILE_SIZE => {
+            if text.is_empty() || text.ends_with('\n') {
+                text
+            } else {
+                format!("{text}\n<no newline at end of file>\n")
+            }
+        }
+        _ => format!("<{} bytes>\n", bytes.len()),
+    }
+}
+
+fn sorted_table(table: &toml::Table) -> toml::Table {
+    let mut keys = table.keys().collect::<Vec<_>>();
+    keys.sort();
+
+    keys.into_iter()
+        .map(|key| (key.clone(), sorted_value(&table[key])))
+        .collect()
+}
+
+fn sorted_value(value: &toml::Value) -> toml::Value {
+    match value {
+        toml::Value::Table(table) => toml::Value::Table(sorted_table(table)),
+        toml::Value::Array(values) => toml::Value::Array(values.iter().map(sorted_value).collect()),
+        value => value.clone(),
+    }
+}
+
+/// A line diff of two texts, based on their longest common subsequence of lines.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+
+    // lengths[i][j] is the length of the longest common subsequence of expected[i..] and actual[j..].
+    let mut lengths = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lengths[i][j] = if expected[i] == actual[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff.push(format!("  {}", expected[i]));
+            i += 1;
+            j += 1;
+        } else if j < actual.len()
+            && (i == expected.len() || lengths[i][j + 1] >= lengths[i + 1][j])
+        {
+            diff.push(format!("+ {}", actual[j]));
+            j += 1;
+        } else {
+            diff.push(format!("- {}", expected[i]));
+            i += 1;
+        }
+    }
+
+    diff.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::BuildResult;
+    use crate::data::buildpack_id;
+    use crate::detect::DetectResult;
+    use crate::generic::{GenericError, GenericMetadata, GenericPlatform};
+    use crate::Platform;
+
+    struct TestBuildpack;
+
+    impl Buildpack for TestBuildpack {
+        type Platform = GenericPlatform;
+        type Metadata = GenericMetadata;
+        type Error = GenericError;
+
+        fn detect(
+            &self,
+            _context: DetectContext<Self>,
+        ) -> crate::Result<DetectResult, Self::Error> {
+            unimplemented!()
+        }
+
+        fn build(&self, _context: BuildContext<Self>) -> crate::Result<BuildResult, Self::Error> {
+            unimplemented!()
+        }
+    }
+
+    const GROUP_TOML: &str = r#"
+[[group]]
+id = "libcnb/test"
+version = "1.0.0"
+api = "0.10"
+
+[[group]]
+id = "heroku/procfile"
+version = "3.1.1"
+api = "0.10"
+"#;
+
+    #[test]
+    fn build_contexts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let buildpack_dir = temp_dir.path().join("buildpack");
+        fs::create_dir_all(&buildpack_dir).unwrap();
+        fs::write(
+            buildpack_dir.join("buildpack.toml"),
+            "api = \"0.10\"\n\n[buildpack]\nid = \"libcnb/test\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+
+        let build_context = BuildContextBuilder::<TestBuildpack>::new()
+            .buildpack_dir(&buildpack_dir)
+            .platform_env("RUBY_VERSION", "3.3.1")
+            .buildpack_plan_entry("ruby", toml::Table::new())
+            .group(toml::from_str(GROUP_TOML).unwrap())
+            .build_in(temp_dir.path().join("build"));
+
+        assert_eq!(
+            build_context.buildpack_descriptor.buildpack.id,
+            buildpack_id!("libcnb/test")
+        );
+        assert_eq!(
+            build_context
+                .platform
+                .env()
+                .get_string_lossy("RUBY_VERSION"),
+            Some(String::from("3.3.1"))
+        );
+        assert_eq!(build_context.buildpack_plan.entries[0].name, "ruby");
+        assert!(build_context.layers_dir.is_dir());
+        assert!(build_context.app_dir.is_dir());
+        assert_eq!(
+            build_context.launch_toml_path(),
+            temp_dir
+                .path()
+                .join("build")
+                .join("layers")
+                .join("launch.toml")
+        );
+        assert_eq!(
+            build_context.store_toml_path(),
+            build_context.layers_dir.join("store.toml")
+        );
+        assert_eq!(
+            build_context
+                .group_buildpack(&buildpack_id!("heroku/procfile"))
+                .map(|buildpack| buildpack.version.as_str()),
+            Some("3.1.1")
+        );
+        assert_eq!(
+            build_context.group_buildpack(&buildpack_id!("heroku/nodejs")),
+            None
+        );
+
+        let detect_context = DetectContextBuilder::<TestBuildpack>::new()
+            .buildpack_dir(&buildpack_dir)
+            .app_dir(&buildpack_dir)
+            .build_in(temp_dir.path().join("detect"));
+
+        assert_eq!(detect_context.app_dir, buildpack_dir);
+        assert_eq!(detect_context.target.os, "linux");
+        assert!(!detect_context.platform.env().contains_key("RUBY_VERSION"));
+    }
+
+    #[test]
+    fn store_changed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let buildpack_dir = temp_dir.path().join("buildpack");
+        fs::create_dir_all(&buildpack_dir).unwrap();
+        fs::write(
+            buildpack_dir.join("buildpack.toml"),
+            "api = \"0.10\"\n\n[buildpack]\nid = \"libcnb/test\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+
+        let mut store = Store::default();
+        store
+            .metadata
+            .insert(String::from("version"), toml::Value::from("3.3.1"));
+
+        let build_context = BuildContextBuilder::<TestBuildpack>::new()
+            .buildpack_dir(&buildpack_dir)
+            .store(store.clone())
+            .build_in(temp_dir.path().join("build"));
+
+        assert!(!build_context.store_changed(&store));
+
+        store
+            .metadata
+            .insert(String::from("version"), toml::Value::from("3.3.2"));
+        assert!(build_context.store_changed(&store));
+        assert!(build_context.store_changed(&Store::default()));
+    }
+
+    #[test]
+    fn snapshot_layers() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layers_dir = temp_dir.path();
+        fs::create_dir_all(layers_dir.join("ruby/env")).unwrap();
+        fs::write(
+            layers_dir.join("ruby.toml"),
+            "[types]\nlaunch = true\nbuild = false\n\n[metadata]\nversion = \"3.3.1\"\n",
+        )
+        .unwrap();
+        fs::write(
+            layers_dir.join("ruby/env/GEM_HOME.override"),
+            layers_dir.join("ruby/gems").to_string_lossy().as_bytes(),
+        )
+        .unwrap();
+        fs::write(layers_dir.join("ruby/ruby"), [0, 159, 146, 150]).unwrap();
+
+        assert_eq!(
+            layers_snapshot(layers_dir, &[(layers_dir, "<layers_dir>")]),
+            "## ruby/env/GEM_HOME.override
+<layers_dir>/ruby/gems
+<no newline at end of file>
+
+## ruby/ruby
+<4 bytes>
+
+## ruby.toml
+[metadata]
+version = \"3.3.1\"
+
+[types]
+build = false
+launch = true
+"
+        );
+    }
+
+    #[test]
+    fn diff_lines() {
+        assert_eq!(
+            line_diff("a\nb\nc\nd", "a\nc\nx\nd"),
+            "  a\n- b\n  c\n+ x\n  d"
+        );
+        assert_eq!(line_diff("", "a"), "+ a");
+    }
+}
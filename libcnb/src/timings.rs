@@ -0,0 +1,131 @@
+use crate::util::SharedVec;
+use crate::Platform;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// The environment variable that enables the timing summary when set to `true` or `1`, either in
+/// the platform env (`<platform>/env`) or in the process environment.
+pub const TIMING_SUMMARY_ENV_VAR: &str = "LIBCNB_TIMING_SUMMARY";
+
+/// Collection of named durations measured during detect or build.
+///
+/// Durations are recorded via [`DetectContext::time`](crate::detect::DetectContext::time) and
+/// [`BuildContext::time`](crate::build::BuildContext::time). libcnb additionally records the time
+/// spent handling each layer. When the timing summary is enabled via [`TIMING_SUMMARY_ENV_VAR`],
+/// libcnb prints all durations and the total time of the phase in a summary section right before
+/// the phase exits, regardless of whether it succeeded.
+///
+/// Timings are collected independently of the `trace` feature. Like [`Warnings`](crate::Warnings),
+/// clones share the same collection.
+#[derive(Clone, Debug, Default)]
+pub struct Timings {
+    inner: SharedVec<(String, Duration)>,
+}
+
+impl Timings {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a named duration to the collection.
+    pub fn record(&self, name: impl Into<String>, duration: Duration) {
+        self.inner.push((name.into(), duration));
+    }
+
+    /// Runs the given function and records the time it took under the given name.
+    pub fn time<T>(&self, name: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(name, start.elapsed());
+        result
+    }
+
+    /// Returns all durations recorded so far, in the order they were recorded.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<(String, Duration)> {
+        self.inner.to_vec()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// Returns whether the timing summary is enabled in the given platform env or the process
+/// environment.
+pub(crate) fn timing_summary_enabled(platform: &impl Platform) -> bool {
+    platform
+        .env_var(TIMING_SUMMARY_ENV_VAR)
+        .or_else(|| std::env::var(TIMING_SUMMARY_ENV_VAR).ok())
+        .is_some_and(|value| matches!(value.trim(), "true" | "1"))
+}
+
+/// Writes the summary section for the given phase and timings.
+pub(crate) fn write_timings_summary(
+    phase: &str,
+    total: Duration,
+    timings: &Timings,
+    mut writer: impl Write,
+) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "\n[Timing: {phase} took {}]",
+        format_duration(total)
+    )?;
+
+    for (name, duration) in timings.to_vec() {
+        writeln!(writer, "- {name}: {}", format_duration(duration))?;
+    }
+
+    writer.flush()
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generic::GenericPlatform;
+    use crate::Env;
+
+    #[test]
+    fn summary_lists_all_timings() {
+        let timings = Timings::new();
+        timings.record("layer node", Duration::from_millis(12_345));
+        assert_eq!(timings.clone().time("install deps", || 42), 42);
+
+        let mut output = Vec::new();
+        write_timings_summary("build", Duration::from_secs(13), &timings, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with(
+            "\n[Timing: build took 13.00s]\n\
+             - layer node: 12.35s\n\
+             - install deps: "
+        ));
+        assert!(output.ends_with("ms\n"));
+    }
+
+    #[test]
+    fn summary_is_opt_in() {
+        let enabled = |value: Option<&str>| {
+            let mut env = Env::new();
+            if let Some(value) = value {
+                env.insert(TIMING_SUMMARY_ENV_VAR, value);
+            }
+            timing_summary_enabled(&GenericPlatform::new(env))
+        };
+
+        assert!(!enabled(None));
+        assert!(!enabled(Some("false")));
+        assert!(enabled(Some("true\n")));
+    }
+}
@@ -1,14 +1,33 @@
+use crate::platform::read_platform_env;
+use crate::Env;
 use libcnb_data::buildpack::Buildpack;
 use opentelemetry::{
     global,
-    trace::{Span as SpanTrait, Status, Tracer, TracerProvider as TracerProviderTrait},
-    KeyValue,
+    metrics::{Meter, MeterProvider as MeterProviderTrait},
+    propagation::TextMapPropagator,
+    trace::{
+        Span as SpanTrait, Status, TraceContextExt, Tracer, TracerProvider as TracerProviderTrait,
+    },
+    Context, ContextGuard, KeyValue,
 };
 use opentelemetry_sdk::{
-    trace::{Config, Span, TracerProvider},
+    metrics::{
+        data::{ResourceMetrics, Temporality},
+        exporter::PushMetricsExporter,
+        reader::{AggregationSelector, MetricReader, TemporalitySelector},
+        Aggregation, InstrumentKind, ManualReader, MeterProvider, Pipeline,
+    },
+    propagation::TraceContextPropagator,
+    trace::{Config, Sampler, Tracer as SdkTracer, TracerProvider},
     Resource,
 };
-use std::{io::BufWriter, path::Path};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex, Weak},
+};
 
 // This is the directory in which `BuildpackTrace` stores OpenTelemetry File
 // Exports. Services which intend to export the tracing data from libcnb.rs
@@ -19,56 +38,296 @@ use std::{io::BufWriter, path::Path};
 #[cfg(target_family = "unix")]
 const TELEMETRY_EXPORT_ROOT: &str = "/tmp/libcnb-telemetry";
 
+const TRACEPARENT_ENV_VAR: &str = "TRACEPARENT";
+const TRACESTATE_ENV_VAR: &str = "TRACESTATE";
+const OTEL_SDK_DISABLED_ENV_VAR: &str = "OTEL_SDK_DISABLED";
+const OTEL_TRACES_EXPORTER_ENV_VAR: &str = "OTEL_TRACES_EXPORTER";
+const OTEL_RESOURCE_ATTRIBUTES_ENV_VAR: &str = "OTEL_RESOURCE_ATTRIBUTES";
+const OTEL_SERVICE_NAME_ENV_VAR: &str = "OTEL_SERVICE_NAME";
+const OTEL_TRACES_SAMPLER_ENV_VAR: &str = "OTEL_TRACES_SAMPLER";
+const OTEL_TRACES_SAMPLER_ARG_ENV_VAR: &str = "OTEL_TRACES_SAMPLER_ARG";
+
+thread_local! {
+    // The tracer of the trace that is currently running on this thread, used to create the spans
+    // of the public `crate::telemetry` API.
+    static CURRENT_TRACER: RefCell<Option<SdkTracer>> = const { RefCell::new(None) };
+    // The meter of the trace that is currently running on this thread, used to create the
+    // instruments of the public `crate::telemetry` API.
+    static CURRENT_METER: RefCell<Option<Meter>> = const { RefCell::new(None) };
+}
+
+/// Starts a span as a child of the currently active span, using the tracer of the trace running
+/// on this thread. Returns `None` if no trace is running on this thread.
+pub(crate) fn start_child_span(name: String) -> Option<Context> {
+    CURRENT_TRACER.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .map(|tracer| Context::current_with_span(tracer.start(name)))
+    })
+}
+
+/// Extracts the W3C trace context (<https://www.w3.org/TR/trace-context/>) provided by the
+/// platform, so that the spans of a buildpack become part of the platform's trace of the
+/// overall build.
+///
+/// The `TRACEPARENT` and `TRACESTATE` variables are read from the platform env, falling back to
+/// the process environment. Returns an empty context if no valid `TRACEPARENT` is provided.
+fn platform_trace_context(platform_env: &Env) -> Context {
+    let carrier: HashMap<String, String> = [
+        (TRACEPARENT_ENV_VAR, "traceparent"),
+        (TRACESTATE_ENV_VAR, "tracestate"),
+    ]
+    .into_iter()
+    .filter_map(|(name, header)| {
+        env_var(platform_env, name).map(|value| (String::from(header), value))
+    })
+    .collect();
+
+    TraceContextPropagator::new().extract(&carrier)
+}
+
+/// Telemetry configuration, read from the standard OpenTelemetry environment variables
+/// (<https://opentelemetry.io/docs/specs/otel/configuration/sdk-environment-variables/>) in the
+/// platform env, falling back to the process environment.
+#[derive(Debug, PartialEq)]
+struct TelemetryConfig {
+    /// Whether to write spans and metrics to the file export in [`TELEMETRY_EXPORT_ROOT`].
+    file_export: bool,
+    /// Whether to export spans via OTLP. `None` exports via OTLP only if an endpoint is configured.
+    otlp_export: Option<bool>,
+    /// Resource attributes that override the defaults set by libcnb and the buildpack.
+    resource_attributes: Vec<KeyValue>,
+    /// The sampler that decides which traces are recorded and exported.
+    sampler: SamplerConfig,
+}
+
+impl TelemetryConfig {
+    fn from_env(platform_env: &Env) -> Self {
+        let disabled = env_var(platform_env, OTEL_SDK_DISABLED_ENV_VAR)
+            .is_some_and(|value| value.eq_ignore_ascii_case("true"));
+
+        // `file` is specific to libcnb and refers to its file export. Unknown exporters are
+        // ignored.
+        let (file_export, otlp_export) = if disabled {
+            (false, Some(false))
+        } else if let Some(exporters) = env_var(platform_env, OTEL_TRACES_EXPORTER_ENV_VAR) {
+            let exporters: Vec<&str> = exporters.split(',').map(str::trim).collect();
+            (
+                exporters.contains(&"file"),
+                Some(exporters.contains(&"otlp")),
+            )
+        } else {
+            (true, None)
+        };
+
+        let mut resource_attributes: Vec<KeyValue> =
+            env_var(platform_env, OTEL_RESOURCE_ATTRIBUTES_ENV_VAR)
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, value)| KeyValue::new(key.trim().to_string(), value.trim().to_string()))
+                .filter(|key_value| !key_value.key.as_str().is_empty())
+                .collect();
+
+        if let Some(service_name) = env_var(platform_env, OTEL_SERVICE_NAME_ENV_VAR) {
+            resource_attributes.push(KeyValue::new("service.name", service_name));
+        }
+
+        Self {
+            file_export,
+            otlp_export,
+            resource_attributes,
+            sampler: SamplerConfig::from_env(platform_env),
+        }
+    }
+}
+
+/// The sampler configured via `OTEL_TRACES_SAMPLER` and `OTEL_TRACES_SAMPLER_ARG`.
+///
+/// Supports the `always_on`, `always_off` and `traceidratio` samplers and their `parentbased_`
+/// variants, which follow the sampling decision of the platform's trace if there is one (see
+/// [`platform_trace_context`]). Unknown samplers fall back to the default,
+/// `parentbased_always_on`, and invalid or missing ratios fall back to `1.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct SamplerConfig {
+    parent_based: bool,
+    root: RootSampler,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RootSampler {
+    AlwaysOn,
+    AlwaysOff,
+    TraceIdRatio(f64),
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        Self {
+            parent_based: true,
+            root: RootSampler::AlwaysOn,
+        }
+    }
+}
+
+impl SamplerConfig {
+    fn from_env(platform_env: &Env) -> Self {
+        let Some(sampler) = env_var(platform_env, OTEL_TRACES_SAMPLER_ENV_VAR) else {
+            return Self::default();
+        };
+
+        let (parent_based, root) = match sampler.strip_prefix("parentbased_") {
+            Some(root) => (true, root),
+            None => (false, sampler.as_str()),
+        };
+
+        let root = match root {
+            "always_on" => RootSampler::AlwaysOn,
+            "always_off" => RootSampler::AlwaysOff,
+            "traceidratio" => RootSampler::TraceIdRatio(
+                env_var(platform_env, OTEL_TRACES_SAMPLER_ARG_ENV_VAR)
+                    .and_then(|ratio| ratio.parse::<f64>().ok())
+                    .filter(|ratio| (0.0..=1.0).contains(ratio))
+                    .unwrap_or(1.0),
+            ),
+            _ => return Self::default(),
+        };
+
+        Self { parent_based, root }
+    }
+
+    fn sampler(self) -> Sampler {
+        let root = match self.root {
+            RootSampler::AlwaysOn => Sampler::AlwaysOn,
+            RootSampler::AlwaysOff => Sampler::AlwaysOff,
+            RootSampler::TraceIdRatio(ratio) => Sampler::TraceIdRatioBased(ratio),
+        };
+
+        if self.parent_based {
+            Sampler::ParentBased(Box::new(root))
+        } else {
+            root
+        }
+    }
+}
+
+/// Returns the trimmed value of the given variable from the platform env, falling back to the
+/// process environment.
+fn env_var(platform_env: &Env, name: &str) -> Option<String> {
+    platform_env
+        .get_string_lossy(name)
+        .or_else(|| std::env::var(name).ok())
+        .map(|value| value.trim().to_string())
+}
+
+/// Returns the meter of the trace running on this thread, or `None` if no trace is running on
+/// this thread.
+pub(crate) fn current_meter() -> Option<Meter> {
+    CURRENT_METER.with(|current| current.borrow().clone())
+}
+
 /// Represents an OpenTelemetry tracer provider and single span tracing
 /// a single CNB build or detect phase.
 pub(crate) struct BuildpackTrace {
     provider: TracerProvider,
-    span: Span,
+    // The context containing the phase span. It is attached as the current context while the
+    // trace is alive, so that spans created by buildpacks (see `crate::telemetry`) become
+    // children of the phase span.
+    context: Context,
+    _context_guard: ContextGuard,
+    meter_provider: MeterProvider,
+    metrics_reader: SharedReader,
+    metrics_exporter: Option<opentelemetry_stdout::MetricsExporter>,
+    // Metrics are only exported to the file export. Dropping them is reported if the spans are
+    // exported via OTLP, since metrics are expected to be exported as well in that case.
+    warn_on_dropped_metrics: bool,
+    // The OTLP batch span processor requires a Tokio runtime to drive its exports. It must outlive
+    // the provider, which is ensured by declaring it after the provider.
+    #[cfg(feature = "trace-otlp")]
+    _otlp_runtime: Option<tokio::runtime::Runtime>,
 }
 
 /// Start an OpenTelemetry trace and span that exports to an
 /// OpenTelemetry file export. The resulting trace provider and span are
 /// enriched with data from the buildpack and the rust environment.
-pub(crate) fn start_trace(buildpack: &Buildpack, phase_name: &'static str) -> BuildpackTrace {
+///
+/// If the platform provides a W3C trace context (see [`platform_trace_context`]), the span joins
+/// that trace instead of starting a new one. Exporters and resource attributes can be configured
+/// via the environment, see [`TelemetryConfig`]. The given resource attributes are added to the
+/// defaults set by libcnb.
+pub(crate) fn start_trace(
+    buildpack: &Buildpack,
+    phase_name: &'static str,
+    platform_dir: &Path,
+    resource_attributes: &[(String, String)],
+) -> BuildpackTrace {
+    // Failing to read the platform env must not fail the build, the same error is reported when
+    // libcnb reads the platform.
+    let platform_env = read_platform_env(platform_dir).unwrap_or_default();
+    let config = TelemetryConfig::from_env(&platform_env);
+
     let trace_name = format!(
         "{}-{phase_name}",
         buildpack.id.replace(['/', '.', '-'], "_")
     );
     let tracing_file_path = Path::new(TELEMETRY_EXPORT_ROOT).join(format!("{trace_name}.jsonl"));
 
-    // Ensure tracing file path parent exists by creating it.
-    if let Some(parent_dir) = tracing_file_path.parent() {
-        let _ = std::fs::create_dir_all(parent_dir);
+    let writer = config
+        .file_export
+        .then(|| open_export_file(&tracing_file_path))
+        .flatten();
+
+    let mut provider_builder = TracerProvider::builder();
+    if let Some(writer) = &writer {
+        provider_builder = provider_builder.with_simple_exporter(
+            opentelemetry_stdout::SpanExporter::builder()
+                .with_writer(writer.clone())
+                .build(),
+        );
     }
-    let exporter = match std::fs::File::options()
-        .create(true)
-        .append(true)
-        .open(&tracing_file_path)
+
+    #[cfg(feature = "trace-otlp")]
+    let (provider_builder, otlp_runtime) = match config
+        .otlp_export
+        .unwrap_or_else(|| otlp::endpoint_configured(&platform_env))
+        .then(|| otlp::span_processor(&platform_env, &otlp::BatchSettings::from_env(&platform_env)))
+        .flatten()
     {
-        // Write tracing data to a file, which may be read by other
-        // services. Wrap with a BufWriter to prevent serde from sending each
-        // JSON token to IO, and instead send entire JSON objects to IO.
-        Ok(file) => opentelemetry_stdout::SpanExporter::builder()
-            .with_writer(BufWriter::new(file))
-            .build(),
-        // Failed tracing shouldn't fail a build, and any logging here would
-        // likely confuse the user, so send telemetry to /dev/null on errors.
-        Err(_) => opentelemetry_stdout::SpanExporter::builder()
-            .with_writer(std::io::sink())
-            .build(),
+        Some((otlp_processor, otlp_runtime)) => (
+            provider_builder.with_span_processor(otlp_processor),
+            Some(otlp_runtime),
+        ),
+        None => (provider_builder, None),
     };
 
-    let provider = TracerProvider::builder()
-        .with_simple_exporter(exporter)
-        .with_config(Config::default().with_resource(Resource::new([
-            // Associate the tracer provider with service attributes. The buildpack
-            // name/version seems to map well to the suggestion here
-            // https://opentelemetry.io/docs/specs/semconv/resource/#service.
-            KeyValue::new("service.name", buildpack.id.to_string()),
-            KeyValue::new("service.version", buildpack.version.to_string()),
-        ])))
+    let resource = resource(buildpack, resource_attributes, config.resource_attributes);
+
+    let provider = provider_builder
+        .with_config(
+            Config::default()
+                .with_sampler(config.sampler.sampler())
+                .with_resource(resource.clone()),
+        )
         .build();
 
+    // Metrics are collected once, when the trace ends, and written to the same file as the spans.
+    let metrics_reader = SharedReader(Arc::new(ManualReader::builder().build()));
+    let meter_provider = MeterProvider::builder()
+        .with_resource(resource)
+        .with_reader(metrics_reader.clone())
+        .build();
+    let metrics_exporter = writer.map(|writer| {
+        opentelemetry_stdout::MetricsExporter::builder()
+            .with_writer(writer)
+            .build()
+    });
+
+    #[cfg(feature = "trace-otlp")]
+    let warn_on_dropped_metrics = metrics_exporter.is_none() && otlp_runtime.is_some();
+    #[cfg(not(feature = "trace-otlp"))]
+    let warn_on_dropped_metrics = false;
+
     // Set the global tracer provider so that buildpacks may use it.
     global::set_tracer_provider(provider.clone());
 
@@ -82,7 +341,8 @@ pub(crate) fn start_trace(buildpack: &Buildpack, phase_name: &'static str) -> Bu
         None,
     );
 
-    let mut span = tracer.start(trace_name);
+    let parent_context = platform_trace_context(&platform_env);
+    let mut span = tracer.start_with_context(trace_name, &parent_context);
     span.set_attributes([
         KeyValue::new("buildpack_id", buildpack.id.to_string()),
         KeyValue::new("buildpack_name", buildpack.name.clone().unwrap_or_default()),
@@ -92,43 +352,439 @@ pub(crate) fn start_trace(buildpack: &Buildpack, phase_name: &'static str) -> Bu
             buildpack.homepage.clone().unwrap_or_default(),
         ),
     ]);
-    BuildpackTrace { provider, span }
+    let context = parent_context.with_span(span);
+    let context_guard = context.clone().attach();
+    CURRENT_TRACER.with(|current| *current.borrow_mut() = Some(tracer));
+    CURRENT_METER.with(|current| {
+        *current.borrow_mut() = Some(meter_provider.versioned_meter(
+            env!("CARGO_PKG_NAME"),
+            Some(env!("CARGO_PKG_VERSION")),
+            None as Option<&str>,
+            None,
+        ));
+    });
+
+    BuildpackTrace {
+        provider,
+        context,
+        _context_guard: context_guard,
+        meter_provider,
+        metrics_reader,
+        metrics_exporter,
+        warn_on_dropped_metrics,
+        #[cfg(feature = "trace-otlp")]
+        _otlp_runtime: otlp_runtime,
+    }
 }
 
 impl BuildpackTrace {
     /// Set the status for the underlying span to error, and record
     /// an exception on the span.
     pub(crate) fn set_error(&mut self, err: &dyn std::error::Error) {
-        self.span.set_status(Status::error(format!("{err:?}")));
-        self.span.record_error(err);
+        let span = self.context.span();
+        span.set_status(Status::error(format!("{err:?}")));
+        span.record_error(err);
     }
     /// Add a named event to the underlying span.
     pub(crate) fn add_event(&mut self, name: &'static str) {
-        self.span.add_event(name, Vec::new());
+        self.context.span().add_event(name, Vec::new());
     }
 }
 
 impl Drop for BuildpackTrace {
     fn drop(&mut self) {
-        self.span.end();
+        CURRENT_TRACER.with(|current| current.borrow_mut().take());
+        CURRENT_METER.with(|current| current.borrow_mut().take());
+        self.context.span().end();
         self.provider.force_flush();
         global::shutdown_tracer_provider();
+
+        // Export the metrics after all spans have been exported, since both share the same
+        // writer. Failing to export metrics must not fail the build.
+        let mut metrics = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: Vec::new(),
+        };
+        if self.metrics_reader.collect(&mut metrics).is_ok() && !metrics.scope_metrics.is_empty() {
+            if let Some(metrics_exporter) = &self.metrics_exporter {
+                let _ = futures_executor::block_on(metrics_exporter.export(&mut metrics));
+            } else if self.warn_on_dropped_metrics {
+                eprintln!(
+                    "Warning: Metrics are only exported with the file exporter, add `file` to {OTEL_TRACES_EXPORTER_ENV_VAR} to export them."
+                );
+            }
+        }
+        let _ = self.meter_provider.shutdown();
+    }
+}
+
+/// Creates the resource of the tracer and meter providers.
+///
+/// The providers are associated with service attributes. The buildpack name/version seems to map
+/// well to the suggestion here <https://opentelemetry.io/docs/specs/semconv/resource/#service>.
+/// Attributes from the buildpack and the environment override these defaults, in that order.
+fn resource(
+    buildpack: &Buildpack,
+    buildpack_attributes: &[(String, String)],
+    env_attributes: Vec<KeyValue>,
+) -> Resource {
+    Resource::new(
+        [
+            KeyValue::new("service.name", buildpack.id.to_string()),
+            KeyValue::new("service.version", buildpack.version.to_string()),
+        ]
+        .into_iter()
+        .chain(
+            buildpack_attributes
+                .iter()
+                .map(|(key, value)| KeyValue::new(key.clone(), value.clone())),
+        )
+        .chain(env_attributes),
+    )
+}
+
+/// Opens the file export at the given path for appending.
+fn open_export_file(path: &Path) -> Option<SharedWriter> {
+    // Ensure tracing file path parent exists by creating it.
+    if let Some(parent_dir) = path.parent() {
+        let _ = std::fs::create_dir_all(parent_dir);
+    }
+
+    std::fs::File::options()
+        .create(true)
+        .append(true)
+        .open(path)
+        // Write tracing data to a file, which may be read by other
+        // services. Wrap with a BufWriter to prevent serde from sending each
+        // JSON token to IO, and instead send entire JSON objects to IO.
+        .map(|file| SharedWriter::new(BufWriter::new(file)))
+        // Failed tracing shouldn't fail a build, and any logging here would
+        // likely confuse the user, so don't export telemetry on errors.
+        .ok()
+}
+
+/// A writer that can be shared between the span and metrics exporters, so that both write to the
+/// same file without interleaving their buffered output.
+#[derive(Clone)]
+struct SharedWriter(Arc<Mutex<Box<dyn Write + Send>>>);
+
+impl SharedWriter {
+    fn new(writer: impl Write + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(Box::new(writer))))
+    }
+}
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("poisoned telemetry writer"))?
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("poisoned telemetry writer"))?
+            .flush()
+    }
+}
+
+/// A [`ManualReader`] that can be shared between the meter provider, which it is registered
+/// with, and `BuildpackTrace`, which collects the metrics when the trace ends.
+#[derive(Clone, Debug)]
+struct SharedReader(Arc<ManualReader>);
+
+impl TemporalitySelector for SharedReader {
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.0.temporality(kind)
+    }
+}
+
+impl AggregationSelector for SharedReader {
+    fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+        self.0.aggregation(kind)
+    }
+}
+
+impl MetricReader for SharedReader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.0.register_pipeline(pipeline);
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> opentelemetry::metrics::Result<()> {
+        self.0.collect(rm)
+    }
+
+    fn force_flush(&self) -> opentelemetry::metrics::Result<()> {
+        self.0.force_flush()
+    }
+
+    fn shutdown(&self) -> opentelemetry::metrics::Result<()> {
+        self.0.shutdown()
+    }
+}
+
+/// Export of tracing data via OTLP, configured with the standard `OTEL_EXPORTER_OTLP_*`
+/// environment variables. See <https://opentelemetry.io/docs/specs/otel/protocol/exporter/>.
+#[cfg(feature = "trace-otlp")]
+mod otlp {
+    use super::env_var;
+    use crate::Env;
+    use async_trait::async_trait;
+    use opentelemetry_http::{Bytes, HttpClient, HttpError, Request, Response};
+    use opentelemetry_otlp::{
+        WithExportConfig, OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_PROTOCOL,
+        OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+    };
+    use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+    use opentelemetry_sdk::{
+        runtime,
+        trace::{BatchConfig, BatchSpanProcessor},
+    };
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::time::Duration;
+    use tokio::runtime::Runtime;
+
+    const OTEL_EXPORTER_OTLP_TRACES_PROTOCOL: &str = "OTEL_EXPORTER_OTLP_TRACES_PROTOCOL";
+    const OTEL_BSP_SCHEDULE_DELAY: &str = "OTEL_BSP_SCHEDULE_DELAY";
+    const OTEL_BSP_EXPORT_TIMEOUT: &str = "OTEL_BSP_EXPORT_TIMEOUT";
+    const OTEL_BSP_MAX_QUEUE_SIZE: &str = "OTEL_BSP_MAX_QUEUE_SIZE";
+    const OTEL_BSP_MAX_EXPORT_BATCH_SIZE: &str = "OTEL_BSP_MAX_EXPORT_BATCH_SIZE";
+
+    /// Batch span processor settings, configured via the `OTEL_BSP_*` variables in the platform
+    /// env, falling back to the process environment. Unset or invalid values use the defaults
+    /// of the OpenTelemetry SDK.
+    #[derive(Debug, Default, PartialEq)]
+    pub(super) struct BatchSettings {
+        scheduled_delay: Option<Duration>,
+        export_timeout: Option<Duration>,
+        max_queue_size: Option<usize>,
+        max_export_batch_size: Option<usize>,
+    }
+
+    impl BatchSettings {
+        pub(super) fn from_env(platform_env: &Env) -> Self {
+            let millis = |name| {
+                env_var(platform_env, name)
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_millis)
+            };
+            let size = |name| {
+                env_var(platform_env, name)
+                    .and_then(|value| value.parse().ok())
+                    .filter(|size| *size > 0)
+            };
+
+            Self {
+                scheduled_delay: millis(OTEL_BSP_SCHEDULE_DELAY),
+                export_timeout: millis(OTEL_BSP_EXPORT_TIMEOUT),
+                max_queue_size: size(OTEL_BSP_MAX_QUEUE_SIZE),
+                max_export_batch_size: size(OTEL_BSP_MAX_EXPORT_BATCH_SIZE),
+            }
+        }
+
+        fn batch_config(&self) -> BatchConfig {
+            let mut config = BatchConfig::default();
+            if let Some(scheduled_delay) = self.scheduled_delay {
+                config = config.with_scheduled_delay(scheduled_delay);
+            }
+            if let Some(export_timeout) = self.export_timeout {
+                config = config.with_max_export_timeout(export_timeout);
+            }
+            if let Some(max_queue_size) = self.max_queue_size {
+                config = config.with_max_queue_size(max_queue_size);
+            }
+            if let Some(max_export_batch_size) = self.max_export_batch_size {
+                config = config.with_max_export_batch_size(max_export_batch_size);
+            }
+            config
+        }
+    }
+
+    /// Returns whether an OTLP endpoint is configured in the platform env or the process
+    /// environment.
+    pub(super) fn endpoint_configured(platform_env: &Env) -> bool {
+        env_var(platform_env, OTEL_EXPORTER_OTLP_TRACES_ENDPOINT).is_some()
+            || env_var(platform_env, OTEL_EXPORTER_OTLP_ENDPOINT).is_some()
+    }
+
+    /// Returns the URL traces are sent to, read from the platform env, falling back to the
+    /// process environment.
+    ///
+    /// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is used as is, the `/v1/traces` path is only appended
+    /// to `OTEL_EXPORTER_OTLP_ENDPOINT`.
+    fn traces_endpoint(platform_env: &Env) -> Option<String> {
+        env_var(platform_env, OTEL_EXPORTER_OTLP_TRACES_ENDPOINT).or_else(|| {
+            env_var(platform_env, OTEL_EXPORTER_OTLP_ENDPOINT)
+                .map(|endpoint| format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        })
+    }
+
+    /// HTTP client that sends all requests to the given endpoint.
+    ///
+    /// The HTTP exporter appends `/v1/traces` to endpoints passed to it, which would break
+    /// endpoints configured via `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` in the platform env.
+    #[derive(Debug)]
+    struct EndpointClient {
+        client: reqwest::Client,
+        endpoint: String,
+    }
+
+    #[async_trait]
+    impl HttpClient for EndpointClient {
+        async fn send(&self, mut request: Request<Vec<u8>>) -> Result<Response<Bytes>, HttpError> {
+            *request.uri_mut() = self.endpoint.parse()?;
+            self.client.send(request).await
+        }
+    }
+
+    /// Span exporter that drops the errors of the wrapped exporter, since exporting is
+    /// best-effort and reporting errors would only clutter the build log.
+    ///
+    /// Unlike a global error handler, this doesn't hide errors of other OpenTelemetry users in the
+    /// same process.
+    #[derive(Debug)]
+    struct BestEffortExporter<E>(E);
+
+    impl<E: SpanExporter> SpanExporter for BestEffortExporter<E> {
+        fn export(
+            &mut self,
+            batch: Vec<SpanData>,
+        ) -> Pin<Box<dyn Future<Output = ExportResult> + Send + 'static>> {
+            let export = self.0.export(batch);
+            Box::pin(async move {
+                let _ = export.await;
+                Ok(())
+            })
+        }
+
+        fn shutdown(&mut self) {
+            self.0.shutdown();
+        }
+    }
+
+    /// Creates a batch span processor that exports via OTLP. Returns `None` if the exporter
+    /// couldn't be created, since failing telemetry must not fail the build. Without a configured
+    /// endpoint, the exporter uses the default endpoint of the protocol on `localhost`.
+    ///
+    /// The `grpc` protocol uses gRPC, all other protocols use HTTP with protobuf encoding. gRPC
+    /// requests always use the path of the trace service, only the scheme, host and port of the
+    /// endpoint are used. The returned Tokio runtime drives the processor and must be kept alive for as long as the
+    /// processor is in use.
+    pub(super) fn span_processor(
+        platform_env: &Env,
+        settings: &BatchSettings,
+    ) -> Option<(BatchSpanProcessor<runtime::Tokio>, Runtime)> {
+        let protocol = env_var(platform_env, OTEL_EXPORTER_OTLP_TRACES_PROTOCOL)
+            .or_else(|| env_var(platform_env, OTEL_EXPORTER_OTLP_PROTOCOL))
+            .unwrap_or_default();
+        let endpoint = traces_endpoint(platform_env);
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .ok()?;
+
+        let processor = {
+            let _guard = runtime.enter();
+            let exporter = if protocol == "grpc" {
+                let mut builder = opentelemetry_otlp::new_exporter().tonic();
+                if let Some(endpoint) = endpoint {
+                    builder = builder.with_endpoint(endpoint);
+                }
+                builder.build_span_exporter()
+            } else {
+                let mut builder = opentelemetry_otlp::new_exporter().http();
+                if let Some(endpoint) = endpoint {
+                    builder = builder.with_http_client(EndpointClient {
+                        client: reqwest::Client::new(),
+                        endpoint,
+                    });
+                }
+                builder.build_span_exporter()
+            }
+            .ok()?;
+
+            BatchSpanProcessor::builder(BestEffortExporter(exporter), runtime::Tokio)
+                .with_batch_config(settings.batch_config())
+                .build()
+        };
+
+        Some((processor, runtime))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{endpoint_configured, traces_endpoint, BatchSettings};
+        use crate::Env;
+        use std::time::Duration;
+
+        #[test]
+        fn test_endpoint_configured() {
+            let platform_env =
+                Env::new().with("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318");
+            assert!(endpoint_configured(&platform_env));
+        }
+
+        #[test]
+        fn test_traces_endpoint() {
+            let mut platform_env = Env::new();
+
+            platform_env.insert("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318/");
+            assert_eq!(
+                traces_endpoint(&platform_env),
+                Some(String::from("http://collector:4318/v1/traces"))
+            );
+
+            platform_env.insert(
+                "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+                "http://traces:4318/custom",
+            );
+            assert_eq!(
+                traces_endpoint(&platform_env),
+                Some(String::from("http://traces:4318/custom"))
+            );
+        }
+
+        #[test]
+        fn test_batch_settings() {
+            let mut platform_env = Env::new();
+            assert_eq!(
+                BatchSettings::from_env(&platform_env),
+                BatchSettings::default()
+            );
+
+            platform_env.insert("OTEL_BSP_SCHEDULE_DELAY", "250");
+            platform_env.insert("OTEL_BSP_EXPORT_TIMEOUT", "invalid");
+            platform_env.insert("OTEL_BSP_MAX_QUEUE_SIZE", "4096");
+            platform_env.insert("OTEL_BSP_MAX_EXPORT_BATCH_SIZE", "0");
+            assert_eq!(
+                BatchSettings::from_env(&platform_env),
+                BatchSettings {
+                    scheduled_delay: Some(Duration::from_millis(250)),
+                    export_timeout: None,
+                    max_queue_size: Some(4096),
+                    max_export_batch_size: None,
+                }
+            );
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::start_trace;
+    use super::{start_trace, RootSampler, SamplerConfig, TelemetryConfig};
+    use crate::Env;
     use libcnb_data::{
         buildpack::{Buildpack, BuildpackVersion},
         buildpack_id,
     };
+    use opentelemetry::KeyValue;
     use serde_json::Value;
-    use std::{
-        collections::HashSet,
-        fs,
-        io::{Error, ErrorKind},
-    };
+    use std::{collections::HashSet, fs, io::Error, path::Path};
 
     #[test]
     fn test_tracing() {
@@ -147,9 +803,9 @@ mod tests {
         _ = fs::remove_file(telemetry_path);
 
         {
-            let mut trace = start_trace(&buildpack, "bar");
+            let mut trace = start_trace(&buildpack, "bar", Path::new("/does-not-exist"), &[]);
             trace.add_event("baz-event");
-            trace.set_error(&Error::new(ErrorKind::Other, "it's broken"));
+            trace.set_error(&Error::other("it's broken"));
         }
         let tracing_contents = fs::read_to_string(telemetry_path)
             .expect("Expected telemetry file to exist, but couldn't read it");
@@ -192,4 +848,252 @@ mod tests {
             .contains("\"message\":\"Custom { kind: Other, error: \\\"it's broken\\\" }"));
         assert!(tracing_contents.contains("\"code\":1"));
     }
+
+    #[test]
+    fn test_platform_trace_context() {
+        let buildpack = Buildpack {
+            id: buildpack_id!("company.com/propagation"),
+            version: BuildpackVersion::new(0, 0, 1),
+            name: None,
+            homepage: None,
+            clear_env: false,
+            description: None,
+            keywords: Vec::new(),
+            licenses: Vec::new(),
+            sbom_formats: HashSet::new(),
+        };
+        let telemetry_path = "/tmp/libcnb-telemetry/company_com_propagation-build.jsonl";
+        _ = fs::remove_file(telemetry_path);
+
+        let platform_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(platform_dir.path().join("env")).unwrap();
+        fs::write(
+            platform_dir.path().join("env/TRACEPARENT"),
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01\n",
+        )
+        .unwrap();
+        fs::write(platform_dir.path().join("env/TRACESTATE"), "vendor=value").unwrap();
+
+        {
+            let _trace = start_trace(&buildpack, "build", platform_dir.path(), &[]);
+        }
+
+        let tracing_contents = fs::read_to_string(telemetry_path).unwrap();
+        let tracing_data: Value = serde_json::from_str(&tracing_contents).unwrap();
+        let span = &tracing_data["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+
+        assert_eq!(span["traceId"], "0af7651916cd43dd8448eb211c80319c");
+        assert_eq!(span["parentSpanId"], "b7ad6b7169203331");
+        assert_eq!(span["traceState"], "vendor=value");
+    }
+
+    #[test]
+    fn test_telemetry_config() {
+        let mut platform_env = Env::new();
+        assert_eq!(
+            TelemetryConfig::from_env(&platform_env),
+            TelemetryConfig {
+                file_export: true,
+                otlp_export: None,
+                resource_attributes: Vec::new(),
+                sampler: SamplerConfig::default(),
+            }
+        );
+
+        platform_env.insert("OTEL_TRACES_EXPORTER", "otlp");
+        platform_env.insert(
+            "OTEL_RESOURCE_ATTRIBUTES",
+            "deployment.environment=staging, team = languages,invalid",
+        );
+        platform_env.insert("OTEL_SERVICE_NAME", "custom-service\n");
+        assert_eq!(
+            TelemetryConfig::from_env(&platform_env),
+            TelemetryConfig {
+                file_export: false,
+                otlp_export: Some(true),
+                resource_attributes: vec![
+                    KeyValue::new("deployment.environment", "staging"),
+                    KeyValue::new("team", "languages"),
+                    KeyValue::new("service.name", "custom-service"),
+                ],
+                sampler: SamplerConfig::default(),
+            }
+        );
+
+        platform_env.insert("OTEL_SDK_DISABLED", "true");
+        let config = TelemetryConfig::from_env(&platform_env);
+        assert!(!config.file_export);
+        assert_eq!(config.otlp_export, Some(false));
+    }
+
+    #[test]
+    fn test_sampler_config() {
+        let sampler_config = |sampler: &str, arg: Option<&str>| {
+            let mut platform_env = Env::new();
+            platform_env.insert("OTEL_TRACES_SAMPLER", sampler);
+            if let Some(arg) = arg {
+                platform_env.insert("OTEL_TRACES_SAMPLER_ARG", arg);
+            }
+            SamplerConfig::from_env(&platform_env)
+        };
+
+        assert_eq!(
+            sampler_config("always_off", None),
+            SamplerConfig {
+                parent_based: false,
+                root: RootSampler::AlwaysOff,
+            }
+        );
+        assert_eq!(
+            sampler_config("parentbased_traceidratio", Some("0.25")),
+            SamplerConfig {
+                parent_based: true,
+                root: RootSampler::TraceIdRatio(0.25),
+            }
+        );
+        assert_eq!(
+            sampler_config("traceidratio", Some("2")),
+            SamplerConfig {
+                parent_based: false,
+                root: RootSampler::TraceIdRatio(1.0),
+            }
+        );
+        assert_eq!(
+            sampler_config("jaeger_remote", None),
+            SamplerConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_resource_attributes() {
+        let buildpack = Buildpack {
+            id: buildpack_id!("company.com/resource"),
+            version: BuildpackVersion::new(0, 0, 1),
+            name: None,
+            homepage: None,
+            clear_env: false,
+            description: None,
+            keywords: Vec::new(),
+            licenses: Vec::new(),
+            sbom_formats: HashSet::new(),
+        };
+        let telemetry_path = "/tmp/libcnb-telemetry/company_com_resource-build.jsonl";
+        _ = fs::remove_file(telemetry_path);
+
+        let platform_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(platform_dir.path().join("env")).unwrap();
+        fs::write(
+            platform_dir.path().join("env/OTEL_RESOURCE_ATTRIBUTES"),
+            "team=platform",
+        )
+        .unwrap();
+
+        {
+            let _trace = start_trace(
+                &buildpack,
+                "build",
+                platform_dir.path(),
+                &[
+                    (String::from("team"), String::from("languages")),
+                    (String::from("ecosystem"), String::from("rust")),
+                ],
+            );
+        }
+
+        let tracing_contents = fs::read_to_string(telemetry_path).unwrap();
+        assert!(tracing_contents
+            .contains("{\"key\":\"ecosystem\",\"value\":{\"stringValue\":\"rust\"}}"));
+        assert!(tracing_contents
+            .contains("{\"key\":\"team\",\"value\":{\"stringValue\":\"platform\"}}"));
+        assert!(!tracing_contents.contains("languages"));
+
+        // Disabling telemetry skips the file export entirely.
+        _ = fs::remove_file(telemetry_path);
+        fs::write(platform_dir.path().join("env/OTEL_SDK_DISABLED"), "true").unwrap();
+        {
+            let _trace = start_trace(&buildpack, "build", platform_dir.path(), &[]);
+        }
+        assert!(!Path::new(telemetry_path).exists());
+    }
+
+    #[cfg(feature = "trace-otlp")]
+    #[test]
+    fn test_otlp_export() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        // Accepts connections and reports the first bytes received on each of them. HTTP requests
+        // are answered with an empty successful response.
+        fn start_collector() -> (String, mpsc::Receiver<Vec<u8>>) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = listener.local_addr().unwrap();
+            let (sender, receiver) = mpsc::channel();
+
+            std::thread::spawn(move || {
+                for mut stream in listener.incoming().flatten() {
+                    let mut buffer = [0; 1024];
+                    let read = stream.read(&mut buffer).unwrap_or_default();
+                    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n");
+                    let _ = sender.send(buffer[..read].to_vec());
+                }
+            });
+
+            (format!("http://{address}"), receiver)
+        }
+
+        let buildpack = Buildpack {
+            id: buildpack_id!("company.com/otlp"),
+            version: BuildpackVersion::new(0, 0, 1),
+            name: None,
+            homepage: None,
+            clear_env: false,
+            description: None,
+            keywords: Vec::new(),
+            licenses: Vec::new(),
+            sbom_formats: HashSet::new(),
+        };
+
+        for (protocol, expected_prefix) in [
+            ("http/protobuf", b"POST /collector/traces".as_slice()),
+            ("grpc", b"PRI * HTTP/2.0".as_slice()),
+        ] {
+            let (endpoint, received) = start_collector();
+            let traces_endpoint = if protocol == "grpc" {
+                endpoint
+            } else {
+                format!("{endpoint}/collector/traces")
+            };
+
+            let platform_dir = tempfile::tempdir().unwrap();
+            fs::create_dir_all(platform_dir.path().join("env")).unwrap();
+            fs::write(
+                platform_dir
+                    .path()
+                    .join("env/OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"),
+                traces_endpoint,
+            )
+            .unwrap();
+            fs::write(
+                platform_dir
+                    .path()
+                    .join("env/OTEL_EXPORTER_OTLP_TRACES_PROTOCOL"),
+                protocol,
+            )
+            .unwrap();
+
+            {
+                let mut trace = start_trace(&buildpack, "otlp", platform_dir.path(), &[]);
+                trace.add_event("otlp-event");
+            }
+
+            let request = received.recv_timeout(Duration::from_secs(10)).unwrap();
+            assert!(
+                request.starts_with(expected_prefix),
+                "Unexpected {protocol} request: {}",
+                String::from_utf8_lossy(&request)
+            );
+        }
+    }
 }
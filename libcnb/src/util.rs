@@ -1,6 +1,47 @@
 use std::fs;
 use std::fs::Permissions;
 use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// A list that can be appended to through any of its clones, such as the collections of warnings
+/// and timings that are shared between libcnb and a buildpack.
+#[derive(Debug)]
+pub(crate) struct SharedVec<T>(Arc<Mutex<Vec<T>>>);
+
+impl<T> SharedVec<T> {
+    pub(crate) fn push(&self, value: T) {
+        self.lock().push(value);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<T>> {
+        // A poisoned lock only means another thread panicked while pushing a value. The values
+        // are still valid, so there is no reason to propagate the panic.
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Clone for SharedVec<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Default for SharedVec<T> {
+    fn default() -> Self {
+        Self(Arc::default())
+    }
+}
 
 /// Removes [`std::io::Error`] values from a [`Result`] that have the
 /// [`std::io::ErrorKind::NotFound`] error kind by replacing them with the default value for `T`.
@@ -0,0 +1,100 @@
+use crate::build_events::{self, BuildEvent};
+use crate::util::SharedVec;
+use std::io::Write;
+
+/// Collection of non-fatal warnings emitted during detect or build.
+///
+/// Warnings are collected via [`DetectContext::warn`](crate::detect::DetectContext::warn) and
+/// [`BuildContext::warn`](crate::build::BuildContext::warn) and printed by libcnb in a summary
+/// section right before the phase exits, regardless of whether it succeeded. This ensures important
+/// notices are not lost in verbose build logs.
+///
+/// Cloning a `Warnings` value yields a handle to the same collection.
+#[derive(Clone, Debug, Default)]
+pub struct Warnings {
+    inner: SharedVec<String>,
+}
+
+impl Warnings {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a warning to the collection.
+    pub fn push(&self, message: impl Into<String>) {
+        let message = message.into();
+        build_events::emit(BuildEvent::Warning {
+            message: message.clone(),
+        });
+        self.inner.push(message);
+    }
+
+    /// Returns all warnings collected so far, in the order they were added.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<String> {
+        self.inner.to_vec()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// Writes the summary section for the given warnings. Writes nothing if there are no warnings.
+pub(crate) fn write_warnings_summary(
+    warnings: &Warnings,
+    mut writer: impl Write,
+) -> std::io::Result<()> {
+    let warnings = warnings.to_vec();
+
+    if warnings.is_empty() {
+        return Ok(());
+    }
+
+    let plural = if warnings.len() == 1 { "" } else { "s" };
+    writeln!(writer, "\n[Warning: {} warning{plural}]", warnings.len())?;
+
+    for warning in warnings {
+        let mut lines = warning.lines();
+        writeln!(writer, "- {}", lines.next().unwrap_or_default())?;
+
+        for line in lines {
+            writeln!(writer, "  {line}")?;
+        }
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_lists_all_warnings() {
+        let warnings = Warnings::new();
+        warnings.clone().push("Using the default Node.js version");
+        warnings.push("Your lockfile is outdated.\nRun `npm install` to update it.");
+
+        let mut output = Vec::new();
+        write_warnings_summary(&warnings, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\n[Warning: 2 warnings]\n\
+             - Using the default Node.js version\n\
+             - Your lockfile is outdated.\n  \
+             Run `npm install` to update it.\n"
+        );
+    }
+
+    #[test]
+    fn summary_is_empty_without_warnings() {
+        let mut output = Vec::new();
+        write_warnings_summary(&Warnings::new(), &mut output).unwrap();
+
+        assert!(output.is_empty());
+    }
+}
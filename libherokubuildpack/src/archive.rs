@@ -0,0 +1,576 @@
+use flate2::read::GzDecoder;
+use libcnb::Deterministic;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+/// The format of an archive that can be extracted with [`extract_archive_with_format`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArchiveFormat {
+    /// An uncompressed tar archive.
+    Tar,
+    TarGz,
+    TarXz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Determines the format of an archive from its file name, such as `runtime.tar.gz`.
+    ///
+    /// Recognizes the `.tar.gz`, `.tgz`, `.tar.xz`, `.txz` and `.zip` extensions. Uncompressed
+    /// [`ArchiveFormat::Tar`] archives are not recognized.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let file_name = path.as_ref().file_name()?.to_str()?;
+
+        [
+            (".tar.gz", Self::TarGz),
+            (".tgz", Self::TarGz),
+            (".tar.xz", Self::TarXz),
+            (".txz", Self::TarXz),
+            (".zip", Self::Zip),
+        ]
+        .into_iter()
+        .find(|(extension, _)| file_name.ends_with(extension))
+        .map(|(_, format)| format)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExtractError {
+    #[error("Unknown archive format: {}", .0.display())]
+    UnknownFormat(PathBuf),
+
+    #[error("I/O error while extracting archive: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("Zip error while extracting archive: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+
+    #[error("Archive entry would be extracted outside of the destination: {}", .0.display())]
+    PathTraversal(PathBuf),
+}
+
+/// Extracts an archive into the given directory, usually the path of a layer.
+///
+/// The format of the archive is determined from its file name, see [`ArchiveFormat::from_path`].
+/// See [`extract_archive_with_format`] for details.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::archive::extract_archive;
+///
+/// # let layer_path = std::path::PathBuf::from("/layers/heroku_example/runtime");
+/// // Extracts `node-v20.12.2-linux-x64/bin/node` to `<layer>/bin/node`.
+/// extract_archive("/tmp/node-v20.12.2-linux-x64.tar.xz", &layer_path, 1).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the format of the archive is unknown, or the archive couldn't be extracted.
+pub fn extract_archive(
+    archive: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+    strip_components: usize,
+) -> Result<(), ExtractError> {
+    let archive = archive.as_ref();
+
+    ArchiveFormat::from_path(archive)
+        .ok_or_else(|| ExtractError::UnknownFormat(archive.to_path_buf()))
+        .and_then(|format| {
+            extract_archive_with_format(archive, format, destination, strip_components)
+        })
+}
+
+/// Extracts an archive into the given directory, like [`extract_archive`], and sets the
+/// modification time of all extracted files and directories that are later than the timestamp of
+/// the given deterministic build mode settings to that timestamp.
+///
+/// Entries keep earlier modification times from the archive, like tools that support
+/// `SOURCE_DATE_EPOCH` do. Symlinks keep their own modification time.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::archive::{extract_archive, extract_archive_deterministic};
+///
+/// # fn example<B: libcnb::Buildpack>(
+/// #     context: &libcnb::build::BuildContext<B>,
+/// #     layer_path: &std::path::Path,
+/// # ) -> Result<(), libherokubuildpack::archive::ExtractError> {
+/// let archive = "/tmp/node-v20.12.2-linux-x64.tar.xz";
+///
+/// match &context.deterministic {
+///     Some(deterministic) => extract_archive_deterministic(archive, layer_path, 1, deterministic),
+///     None => extract_archive(archive, layer_path, 1),
+/// }
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the format of the archive is unknown, the archive couldn't be extracted,
+/// or the times of an extracted entry couldn't be set.
+pub fn extract_archive_deterministic(
+    archive: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+    strip_components: usize,
+    deterministic: &Deterministic,
+) -> Result<(), ExtractError> {
+    let archive = archive.as_ref();
+
+    let format = ArchiveFormat::from_path(archive)
+        .ok_or_else(|| ExtractError::UnknownFormat(archive.to_path_buf()))?;
+    let (destination, extracted) = extract(archive, format, destination, strip_components)?;
+
+    // Children come after their parents, so iterating in reverse sets the time of each directory
+    // after its entries were changed.
+    for path in extracted.iter().rev() {
+        if !fs::symlink_metadata(destination.join(path))?.is_symlink() {
+            deterministic.clamp_mtime(destination.join(path))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Extracts an archive of the given format into the given directory, usually the path of a layer.
+///
+/// Like `tar --strip-components`, the given number of leading path components is removed from the
+/// paths of all entries, and entries that are within these components are skipped.
+///
+/// Permissions and symlinks are preserved. Entries with absolute paths or `..` components, and
+/// entries that would be written through a previously extracted symlink that points outside of the
+/// destination, are rejected.
+///
+/// # Errors
+///
+/// Will return `Err` if the archive couldn't be read, is invalid, or contains an entry that would
+/// be extracted outside of the destination. Entries that were extracted before the error are not
+/// removed.
+pub fn extract_archive_with_format(
+    archive: impl AsRef<Path>,
+    format: ArchiveFormat,
+    destination: impl AsRef<Path>,
+    strip_components: usize,
+) -> Result<(), ExtractError> {
+    extract(archive, format, destination, strip_components).map(|_| ())
+}
+
+// Returns the canonical destination and the paths of all extracted entries and the directories
+// that contain them, relative to the destination.
+fn extract(
+    archive: impl AsRef<Path>,
+    format: ArchiveFormat,
+    destination: impl AsRef<Path>,
+    strip_components: usize,
+) -> Result<(PathBuf, BTreeSet<PathBuf>), ExtractError> {
+    let file = File::open(archive.as_ref())?;
+
+    fs::create_dir_all(destination.as_ref())?;
+    let destination = destination.as_ref().canonicalize()?;
+
+    let mut extracted = BTreeSet::new();
+    match format {
+        ArchiveFormat::Tar => extract_tar(file, &destination, strip_components, &mut extracted),
+        ArchiveFormat::TarGz => extract_tar(
+            GzDecoder::new(file),
+            &destination,
+            strip_components,
+            &mut extracted,
+        ),
+        ArchiveFormat::TarXz => extract_tar(
+            XzDecoder::new(file),
+            &destination,
+            strip_components,
+            &mut extracted,
+        ),
+        ArchiveFormat::Zip => extract_zip(file, &destination, strip_components, &mut extracted),
+    }?;
+
+    Ok((destination, extracted))
+}
+
+fn extract_tar(
+    reader: impl Read,
+    destination: &Path,
+    strip_components: usize,
+    extracted: &mut BTreeSet<PathBuf>,
+) -> Result<(), ExtractError> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    archive.set_overwrite(true);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        let Some(path) = strip_path(&entry.path()?, strip_components)? else {
+            continue;
+        };
+        let target = prepare_target(destination, &path)?;
+        record_extracted(extracted, &path);
+
+        // Hard links are relative to the root of the archive, so their link names need to be
+        // stripped as well. All other entry types are handled by the `tar` crate.
+        if entry.header().entry_type().is_hard_link() {
+            let link_name = entry.link_name()?.unwrap_or_default().into_owned();
+            let link_path = strip_path(&link_name, strip_components)?
+                .ok_or(ExtractError::PathTraversal(link_name))?;
+
+            remove_existing_file(&target)?;
+            fs::hard_link(prepare_target(destination, &link_path)?, &target)?;
+        } else {
+            entry.unpack(&target)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_zip(
+    file: File,
+    destination: &Path,
+    strip_components: usize,
+    extracted: &mut BTreeSet<PathBuf>,
+) -> Result<(), ExtractError> {
+    let mut archive = ZipArchive::new(file)?;
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+
+        let path = entry
+            .enclosed_name()
+            .ok_or_else(|| ExtractError::PathTraversal(PathBuf::from(entry.name())))?;
+        let Some(path) = strip_path(&path, strip_components)? else {
+            continue;
+        };
+        let target = prepare_target(destination, &path)?;
+        record_extracted(extracted, &path);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            remove_existing_file(&target)?;
+
+            #[cfg(unix)]
+            if entry.is_symlink() {
+                let mut link_target = String::new();
+                entry.read_to_string(&mut link_target)?;
+                std::os::unix::fs::symlink(link_target, &target)?;
+                continue;
+            }
+
+            io::copy(&mut entry, &mut File::create(&target)?)?;
+        }
+
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&target, fs::Permissions::from_mode(mode & 0o7777))?;
+        }
+    }
+
+    Ok(())
+}
+
+// Removes the leading components from the path of an entry. Returns `None` if nothing remains.
+fn strip_path(path: &Path, strip_components: usize) -> Result<Option<PathBuf>, ExtractError> {
+    let mut components = Vec::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(component) => components.push(component),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(ExtractError::PathTraversal(path.to_path_buf()))
+            }
+        }
+    }
+
+    Ok(Some(
+        components
+            .into_iter()
+            .skip(strip_components)
+            .collect::<PathBuf>(),
+    )
+    .filter(|path| path.components().next().is_some()))
+}
+
+// Records the path of an entry and of the directories that contain it, which might have been
+// created for it.
+fn record_extracted(extracted: &mut BTreeSet<PathBuf>, path: &Path) {
+    for ancestor in path.ancestors() {
+        if ancestor.components().next().is_some() {
+            extracted.insert(ancestor.to_path_buf());
+        }
+    }
+}
+
+// Creates the parent directories of an entry, after making sure that they don't resolve to a
+// location outside of the destination through symlinks that were extracted before.
+fn prepare_target(destination: &Path, path: &Path) -> Result<PathBuf, ExtractError> {
+    let target = destination.join(path);
+    let parent = target.parent().unwrap_or(destination);
+
+    let existing_ancestor = parent
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(destination);
+
+    if existing_ancestor.canonicalize()?.starts_with(destination) {
+        fs::create_dir_all(parent)?;
+        Ok(target)
+    } else {
+        Err(ExtractError::PathTraversal(path.to_path_buf()))
+    }
+}
+
+// Prevents writing through existing symlinks, which might point outside of the destination.
+fn remove_existing_file(path: &Path) -> Result<(), io::Error> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if !metadata.is_dir() => fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn tar_archive(
+        writer: impl Write + 'static,
+        build: impl FnOnce(&mut tar::Builder<Box<dyn Write>>),
+    ) {
+        let mut builder = tar::Builder::new(Box::new(writer) as Box<dyn Write>);
+        build(&mut builder);
+        builder.finish().unwrap();
+    }
+
+    fn append_file(builder: &mut tar::Builder<impl Write>, path: &str, contents: &str, mode: u32) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(mode);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, contents.as_bytes())
+            .unwrap();
+    }
+
+    fn append_link(
+        builder: &mut tar::Builder<impl Write>,
+        entry_type: tar::EntryType,
+        path: &str,
+        target: &str,
+    ) {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_size(0);
+        header.set_mode(0o777);
+        builder.append_link(&mut header, path, target).unwrap();
+    }
+
+    fn runtime_tarball(builder: &mut tar::Builder<impl Write>) {
+        append_file(builder, "runtime-1.0/bin/runtime", "#!/bin/sh", 0o755);
+        append_file(builder, "runtime-1.0/README", "Read me!", 0o644);
+        append_link(
+            builder,
+            tar::EntryType::Symlink,
+            "runtime-1.0/bin/rt",
+            "runtime",
+        );
+        append_link(
+            builder,
+            tar::EntryType::Link,
+            "runtime-1.0/README.md",
+            "runtime-1.0/README",
+        );
+    }
+
+    fn assert_runtime_extracted(destination: &Path) {
+        assert_eq!(
+            fs::read_to_string(destination.join("bin/runtime")).unwrap(),
+            "#!/bin/sh"
+        );
+        assert_eq!(
+            fs::metadata(destination.join("bin/runtime"))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o755
+        );
+        assert_eq!(
+            fs::read_link(destination.join("bin/rt")).unwrap(),
+            PathBuf::from("runtime")
+        );
+        assert_eq!(
+            fs::read_to_string(destination.join("README.md")).unwrap(),
+            "Read me!"
+        );
+    }
+
+    #[test]
+    fn extract_tar_gz_and_tar_xz() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let gz_path = temp_dir.path().join("runtime.tar.gz");
+        tar_archive(
+            GzEncoder::new(File::create(&gz_path).unwrap(), Compression::default()),
+            runtime_tarball,
+        );
+        extract_archive(&gz_path, temp_dir.path().join("gz"), 1).unwrap();
+        assert_runtime_extracted(&temp_dir.path().join("gz"));
+
+        let xz_path = temp_dir.path().join("runtime.txz");
+        tar_archive(
+            xz2::write::XzEncoder::new(File::create(&xz_path).unwrap(), 6),
+            runtime_tarball,
+        );
+        extract_archive(&xz_path, temp_dir.path().join("xz"), 1).unwrap();
+        assert_runtime_extracted(&temp_dir.path().join("xz"));
+    }
+
+    #[test]
+    fn extract_zip() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("runtime.zip");
+
+        let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+        zip.add_directory("runtime-1.0/bin", SimpleFileOptions::default())
+            .unwrap();
+        zip.start_file(
+            "runtime-1.0/bin/runtime",
+            SimpleFileOptions::default().unix_permissions(0o755),
+        )
+        .unwrap();
+        zip.write_all(b"#!/bin/sh").unwrap();
+        zip.add_symlink(
+            "runtime-1.0/bin/rt",
+            "runtime",
+            SimpleFileOptions::default(),
+        )
+        .unwrap();
+        zip.start_file("runtime-1.0/README.md", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"Read me!").unwrap();
+        zip.finish().unwrap();
+
+        let destination = temp_dir.path().join("zip");
+        extract_archive(&zip_path, &destination, 1).unwrap();
+        assert_runtime_extracted(&destination);
+    }
+
+    #[test]
+    fn extract_deterministic() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("runtime.tar.gz");
+        tar_archive(
+            GzEncoder::new(File::create(&archive_path).unwrap(), Compression::default()),
+            |builder| {
+                runtime_tarball(builder);
+
+                let mut header = tar::Header::new_gnu();
+                header.set_size(0);
+                header.set_mode(0o644);
+                header.set_mtime(1_000_000_000);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, "runtime-1.0/share/old", io::empty())
+                    .unwrap();
+            },
+        );
+
+        let deterministic =
+            Deterministic::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        let destination = temp_dir.path().join("layer");
+        extract_archive_deterministic(&archive_path, &destination, 1, &deterministic).unwrap();
+        assert_runtime_extracted(&destination);
+
+        let mtime = |path: &str| {
+            fs::metadata(destination.join(path))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+        // The directories have no entries in the archive and were created during extraction.
+        for path in ["bin", "share"] {
+            assert_eq!(mtime(path), deterministic.mtime());
+        }
+        assert_eq!(
+            mtime("share/old"),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn reject_path_traversal() {
+        let temp_dir = TempDir::new().unwrap();
+        let destination = temp_dir.path().join("layer");
+
+        let parent_dir = temp_dir.path().join("parent-dir.tar.gz");
+        let file = File::create(&parent_dir).unwrap();
+        tar_archive(GzEncoder::new(file, Compression::default()), |builder| {
+            // `append_data` rejects `..`, so the path is written to the header directly.
+            let mut header = tar::Header::new_gnu();
+            header.as_gnu_mut().unwrap().name[..13].copy_from_slice(b"../escape.txt");
+            header.set_size(4);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, "evil".as_bytes()).unwrap();
+        });
+
+        assert!(matches!(
+            extract_archive(&parent_dir, &destination, 0),
+            Err(ExtractError::PathTraversal(_))
+        ));
+        assert!(!temp_dir.path().join("escape.txt").exists());
+
+        let symlink = temp_dir.path().join("symlink.tar.gz");
+        let file = File::create(&symlink).unwrap();
+        tar_archive(GzEncoder::new(file, Compression::default()), |builder| {
+            append_link(builder, tar::EntryType::Symlink, "outside", "..");
+            append_file(builder, "outside/escape.txt", "evil", 0o644);
+        });
+
+        assert!(matches!(
+            extract_archive(&symlink, &destination, 0),
+            Err(ExtractError::PathTraversal(_))
+        ));
+        assert!(!temp_dir.path().join("escape.txt").exists());
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(
+            ArchiveFormat::from_path("/tmp/node-v20.tar.gz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_path("ruby.tgz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_path("python.tar.xz"),
+            Some(ArchiveFormat::TarXz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_path("gradle.zip"),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(ArchiveFormat::from_path("runtime.tar"), None);
+
+        assert!(matches!(
+            extract_archive("runtime.rar", "/tmp", 0),
+            Err(ExtractError::UnknownFormat(_))
+        ));
+    }
+}
@@ -26,6 +26,33 @@ pub(crate) fn wrap_ansi_escape_each_line(ansi: &ANSI, body: impl AsRef<str>) ->
         .join("\n")
 }
 
+/// Removes ANSI escape sequences (CSI sequences such as colors) from the given text.
+pub(crate) fn strip_ansi_escapes(contents: &str) -> String {
+    String::from_utf8_lossy(&strip_ansi_escape_bytes(contents.as_bytes())).into_owned()
+}
+
+/// Removes ANSI escape sequences (CSI sequences such as colors) from the given bytes.
+pub(crate) fn strip_ansi_escape_bytes(contents: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(contents.len());
+    let mut bytes = contents.iter();
+
+    while let Some(&byte) = bytes.next() {
+        if byte == b'\x1B' {
+            // Skips the `[`, parameters and intermediate bytes up to and including the final byte.
+            let _ = bytes.next();
+            for &byte in bytes.by_ref() {
+                if byte.is_ascii_alphabetic() || byte == b'~' {
+                    break;
+                }
+            }
+        } else {
+            result.push(byte);
+        }
+    }
+
+    result
+}
+
 const RESET: &str = "\x1B[0m";
 const RED: &str = "\x1B[0;31m";
 const YELLOW: &str = "\x1B[0;33m";
@@ -56,6 +83,13 @@ impl ANSI {
 mod test {
     use super::*;
 
+    #[test]
+    fn strip_escapes() {
+        let colored = wrap_ansi_escape_each_line(&ANSI::Red, "hello\nworld");
+        assert_eq!(strip_ansi_escapes(&colored), "hello\nworld");
+        assert_eq!(strip_ansi_escapes("\x1B[1;35mtext\x1B[K"), "text");
+    }
+
     #[test]
     fn empty_line() {
         let actual = wrap_ansi_escape_each_line(&ANSI::Red, "\n");
@@ -0,0 +1,110 @@
+//! Detection of the styling capabilities of the environment the output is rendered in.
+//!
+//! Buildpack output is usually not written to a terminal but to a log that is rendered later,
+//! for example by `pack` or the build log UI of a platform. Colors are therefore enabled by
+//! default, unless the environment indicates that they can't be rendered:
+//!
+//! * The [`COLOR_ENV_VAR`] environment variable is set to `always` or `never`, which overrides
+//!   any other detection.
+//! * The `NO_COLOR` environment variable is set to a non-empty value, see <https://no-color.org>.
+//! * The `TERM` environment variable is set to `dumb`.
+
+use std::env;
+
+/// Set to `always` or `never` to override the detection of color support.
+pub const COLOR_ENV_VAR: &str = "BUILDPACK_OUTPUT_COLOR";
+
+/// Environment variables set by common CI systems, whose log viewers render colors and unicode.
+const CI_ENV_VARS: [&str; 6] = [
+    "CI",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "BUILDKITE",
+    "BUILD_NUMBER",
+    "RUN_ID",
+];
+
+/// The styling capabilities of the environment the output is rendered in.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Capabilities {
+    /// Whether ANSI escape codes for colors and text styles can be rendered. If not, they are
+    /// removed from everything written through [`BuildpackOutput`](super::BuildpackOutput).
+    pub color: bool,
+    /// Whether non-ASCII characters, such as symbols for spinners or bullet points, can be
+    /// rendered.
+    pub unicode: bool,
+    /// Whether the output is logged by a CI system, which appends lines instead of redrawing them
+    /// in place, even if it is attached to a pseudo-terminal.
+    pub ci: bool,
+}
+
+impl Capabilities {
+    /// Detects the capabilities from the environment variables of the current process.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::from_env_vars(|name| env::var(name).ok())
+    }
+
+    pub(crate) fn from_env_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let term_is_dumb = var("TERM").is_some_and(|term| term == "dumb");
+
+        let color = match var(COLOR_ENV_VAR).as_deref().map(str::trim) {
+            Some("always") => true,
+            Some("never") => false,
+            _ => !term_is_dumb && var("NO_COLOR").map_or(true, |value| value.is_empty()),
+        };
+
+        let utf8_locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()))
+            .is_some_and(|locale| {
+                let locale = locale.to_ascii_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            });
+        let ci = CI_ENV_VARS.iter().any(|name| var(name).is_some());
+
+        Self {
+            color,
+            unicode: !term_is_dumb && (utf8_locale || ci),
+            ci,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn capabilities(vars: &[(&str, &str)]) -> Capabilities {
+        let vars = vars.iter().copied().collect::<HashMap<_, _>>();
+        Capabilities::from_env_vars(|name| vars.get(name).map(|value| String::from(*value)))
+    }
+
+    #[test]
+    fn detect_color() {
+        assert!(capabilities(&[]).color);
+        assert!(capabilities(&[("NO_COLOR", "")]).color);
+        assert!(!capabilities(&[("NO_COLOR", "1")]).color);
+        assert!(!capabilities(&[("TERM", "dumb")]).color);
+        assert!(capabilities(&[("TERM", "dumb"), (COLOR_ENV_VAR, "always")]).color);
+        assert!(!capabilities(&[("TERM", "xterm"), (COLOR_ENV_VAR, "never")]).color);
+    }
+
+    #[test]
+    fn detect_unicode() {
+        assert!(!capabilities(&[]).unicode);
+        assert!(capabilities(&[("LANG", "en_US.UTF-8")]).unicode);
+        assert!(!capabilities(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]).unicode);
+        assert!(capabilities(&[("LC_ALL", ""), ("LC_CTYPE", "C.utf8")]).unicode);
+        assert!(capabilities(&[("CI", "true")]).unicode);
+        assert!(!capabilities(&[("CI", "true"), ("TERM", "dumb")]).unicode);
+    }
+
+    #[test]
+    fn detect_ci() {
+        assert!(!capabilities(&[]).ci);
+        assert!(capabilities(&[("CI", "true")]).ci);
+        assert!(capabilities(&[("BUILD_NUMBER", "42")]).ci);
+    }
+}
@@ -0,0 +1,292 @@
+//! Consistent, user-facing messages for common errors.
+//!
+//! Use [`ErrorMessage`] to format errors for [`BuildpackOutput::error`](super::BuildpackOutput::error)
+//! in `Buildpack::on_error`, instead of formatting each error by hand. Messages consist of a header,
+//! details such as the cause or exit code, an optional body such as the error output of a command,
+//! and an optional suggestion on how to fix the error:
+//!
+//! ```text
+//! ! Couldn't read `/workspace/Gemfile.lock`
+//! !
+//! ! Cause: No such file or directory (os error 2)
+//! !
+//! ! Check that the path exists and is spelled correctly.
+//! ```
+
+use crate::buildpack_output::{style, RunCommandError};
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::Path;
+use std::process::ExitStatus;
+
+/// The number of lines at the end of the error output of a failed command that are shown.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// A multi-line, user-facing error message.
+///
+/// ```rust
+/// use libherokubuildpack::buildpack_output::error_message::ErrorMessage;
+/// use libherokubuildpack::buildpack_output::BuildpackOutput;
+///
+/// let error = std::fs::read_to_string("/workspace/Gemfile.lock").unwrap_err();
+///
+/// BuildpackOutput::new(std::io::stdout())
+///     .start("Example Buildpack")
+///     .error(ErrorMessage::io("read", "/workspace/Gemfile.lock", &error).to_string());
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ErrorMessage {
+    header: String,
+    details: Vec<(String, String)>,
+    body: Option<String>,
+    suggestion: Option<String>,
+}
+
+impl ErrorMessage {
+    /// Creates a message with the given header, which should describe what went wrong in a
+    /// single line.
+    #[must_use]
+    pub fn new(header: impl AsRef<str>) -> Self {
+        Self {
+            header: String::from(header.as_ref().trim()),
+            details: Vec::new(),
+            body: None,
+            suggestion: None,
+        }
+    }
+
+    /// Adds a detail, such as the cause of the error, which is shown as `label: value`.
+    #[must_use]
+    pub fn detail(mut self, label: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.details.push((
+            String::from(label.as_ref().trim()),
+            String::from(value.as_ref().trim()),
+        ));
+        self
+    }
+
+    /// Sets a body that is shown after the details, such as the output of a command.
+    #[must_use]
+    pub fn body(mut self, body: impl AsRef<str>) -> Self {
+        self.body = Some(String::from(body.as_ref().trim_end()));
+        self
+    }
+
+    /// Sets a suggestion on how to fix the error, which is shown at the end.
+    #[must_use]
+    pub fn suggestion(mut self, suggestion: impl AsRef<str>) -> Self {
+        self.suggestion = Some(String::from(suggestion.as_ref().trim()));
+        self
+    }
+
+    /// Creates a message for an I/O error of an operation on a path, such as `read` or
+    /// `create directory`.
+    #[must_use]
+    pub fn io(operation: &str, path: impl AsRef<Path>, error: &io::Error) -> Self {
+        let message = Self::new(format!(
+            "Couldn't {operation} {}",
+            style::value(path.as_ref().to_string_lossy())
+        ))
+        .detail("Cause", error.to_string());
+
+        match error.kind() {
+            io::ErrorKind::NotFound => {
+                message.suggestion("Check that the path exists and is spelled correctly.")
+            }
+            io::ErrorKind::PermissionDenied => message
+                .suggestion("Check the permissions of the path and of its parent directories."),
+            _ => message,
+        }
+    }
+
+    /// Creates a message for a failed HTTP request. `status` is the HTTP status code of the
+    /// response, if a response was received.
+    #[must_use]
+    pub fn http(url: &str, status: Option<u16>, cause: impl Display) -> Self {
+        let mut message = Self::new(format!("Request to {} failed", style::url(url)));
+
+        if let Some(status) = status {
+            message = message.detail("Status", status.to_string());
+        }
+        message = message.detail("Cause", cause.to_string());
+
+        match status {
+            Some(404 | 410) => {
+                message.suggestion("Check that the URL is correct and the resource still exists.")
+            }
+            Some(401 | 403) => message
+                .suggestion("Check that the credentials for the URL are configured and valid."),
+            Some(429 | 500..=599) | None => message.suggestion(
+                "This is usually a temporary problem with the server or the network. If it \
+                persists, check the status of the server.",
+            ),
+            Some(_) => message,
+        }
+    }
+
+    /// Creates a message for a command that exited with a non-zero status. The end of the error
+    /// output of the command is shown in the body.
+    #[must_use]
+    pub fn command(command: &str, status: ExitStatus, stderr: &[u8]) -> Self {
+        let message = Self::new(format!("Command {} failed", style::command(command))).detail(
+            "Exit code",
+            status
+                .code()
+                .map_or_else(|| status.to_string(), |code| code.to_string()),
+        );
+
+        let stderr = String::from_utf8_lossy(stderr);
+        let lines = stderr.trim_end().lines().collect::<Vec<_>>();
+
+        if lines.is_empty() {
+            message
+        } else {
+            let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..]
+                .iter()
+                .map(|line| format!("    {line}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            message.body(format!("Error output:\n\n{tail}"))
+        }
+    }
+
+    /// Creates a message for a failed download of the given URL.
+    #[cfg(feature = "download")]
+    #[must_use]
+    pub fn download(url: &str, error: &crate::download::DownloadError) -> Self {
+        use crate::download::DownloadError;
+
+        match error {
+            DownloadError::HttpError(http_error) => match http_error.as_ref() {
+                ureq::Error::Status(status, response) => {
+                    Self::http(url, Some(*status), response.status_text())
+                }
+                ureq::Error::Transport(transport) => Self::http(url, None, transport),
+            },
+            DownloadError::IoError(io_error) => {
+                Self::new(format!("Couldn't download {}", style::url(url)))
+                    .detail("Cause", io_error.to_string())
+            }
+            DownloadError::ChecksumMismatch { expected, actual } => {
+                Self::new(format!("Checksum mismatch for {}", style::url(url)))
+                    .detail("Expected", expected.to_string())
+                    .detail("Actual", actual.to_string())
+                    .suggestion(
+                        "The downloaded file doesn't match the expected checksum. It may have \
+                        been modified or corrupted.",
+                    )
+            }
+            DownloadError::UnsupportedUri(_) => {
+                Self::new(format!("Couldn't download {}", style::url(url)))
+                    .detail("Cause", "The URI isn't supported by the download backend.")
+            }
+        }
+    }
+}
+
+impl From<&RunCommandError> for ErrorMessage {
+    fn from(error: &RunCommandError) -> Self {
+        match error {
+            RunCommandError::Io { command, source } => {
+                let message =
+                    Self::new(format!("Couldn't run command {}", style::command(command)))
+                        .detail("Cause", source.to_string());
+
+                if source.kind() == io::ErrorKind::NotFound {
+                    message.suggestion("Check that the executable is installed and on the `PATH`.")
+                } else {
+                    message
+                }
+            }
+            RunCommandError::NonZeroExitStatus { command, output } => {
+                Self::command(command, output.status, &output.stderr)
+            }
+        }
+    }
+}
+
+impl Display for ErrorMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.header)?;
+
+        if !self.details.is_empty() {
+            writeln!(f)?;
+            for (label, value) in &self.details {
+                write!(f, "\n{label}: {value}")?;
+            }
+        }
+
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{body}")?;
+        }
+
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n\n{suggestion}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::buildpack_output::ansi_escape::strip_ansi_escapes;
+    use indoc::indoc;
+    use std::process::Command;
+
+    #[test]
+    fn format_io_error() {
+        let error = io::Error::from(io::ErrorKind::NotFound);
+
+        assert_eq!(
+            strip_ansi_escapes(
+                &ErrorMessage::io("read", "/workspace/Gemfile.lock", &error).to_string()
+            ),
+            indoc! {"
+                Couldn't read `/workspace/Gemfile.lock`
+
+                Cause: entity not found
+
+                Check that the path exists and is spelled correctly."}
+        );
+    }
+
+    #[test]
+    fn format_http_error() {
+        assert_eq!(
+            strip_ansi_escapes(
+                &ErrorMessage::http("https://example.com/ruby.tgz", Some(404), "Not Found")
+                    .to_string()
+            ),
+            indoc! {"
+                Request to https://example.com/ruby.tgz failed
+
+                Status: 404
+                Cause: Not Found
+
+                Check that the URL is correct and the resource still exists."}
+        );
+    }
+
+    #[test]
+    fn format_command_error() {
+        let output = Command::new("bash")
+            .args(["-c", "seq 1 25 >&2; exit 3"])
+            .output()
+            .unwrap();
+        let error = RunCommandError::NonZeroExitStatus {
+            command: String::from("bundle install"),
+            output,
+        };
+
+        let message = strip_ansi_escapes(&ErrorMessage::from(&error).to_string());
+        assert!(message.starts_with("Command `bundle install` failed\n\nExit code: 3\n\n"));
+        let tail = (6..=25)
+            .map(|line| format!("    {line}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(message.ends_with(&format!("Error output:\n\n{tail}")));
+    }
+}
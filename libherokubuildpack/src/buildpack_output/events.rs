@@ -0,0 +1,78 @@
+//! Machine-readable output as JSON lines.
+//!
+//! Platforms that want to build a richer UI on top of the buildpack output can request structured
+//! events instead of, or in addition to, the human-readable text. Each event is a JSON object on a
+//! single line with a `type` field, such as:
+//!
+//! ```json
+//! {"type":"section","message":"Ruby version"}
+//! {"type":"step","message":"Installing Ruby"}
+//! {"type":"stream_line","line":"Fetching gem metadata from https://rubygems.org/"}
+//! {"type":"stream_done","duration_ms":2310}
+//! ```
+//!
+//! The event types are `buildpack_start`, `buildpack_done`, `section`, `step`, `step_done`,
+//! `debug`, `warning`, `important`, `error`, `stream_start`, `stream_line`, `stream_done`,
+//! `progress_start`, `progress` and `progress_done`. Messages don't contain ANSI escape codes and
+//! secrets registered with [`BuildpackOutput::redact`](super::BuildpackOutput::redact) are masked.
+//!
+//! In integration tests, `libcnb_test::StructuredOutput` parses the events from the `pack` output,
+//! so that tests can assert on sections, steps and warnings.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::Duration;
+
+/// Set to `json` to write JSON lines instead of text to the buildpack output.
+pub const OUTPUT_FORMAT_ENV_VAR: &str = "BUILDPACK_OUTPUT_FORMAT";
+
+/// Set to a file path to append JSON lines to that file, in addition to the text output.
+pub const OUTPUT_EVENTS_PATH_ENV_VAR: &str = "BUILDPACK_OUTPUT_EVENTS_PATH";
+
+/// Where events are written to.
+#[derive(Debug)]
+pub(crate) enum EventSink {
+    /// The writer of the buildpack output, instead of the text output.
+    Inner,
+    File(File),
+}
+
+impl EventSink {
+    /// Reads the event configuration from the environment. Returns the sink, if any, and whether
+    /// text output is enabled.
+    pub(crate) fn from_env() -> (Option<Self>, bool) {
+        Self::from_env_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Like [`EventSink::from_env`], but reads the variables with the given function.
+    pub(crate) fn from_env_vars(var: impl Fn(&str) -> Option<String>) -> (Option<Self>, bool) {
+        if var(OUTPUT_FORMAT_ENV_VAR).is_some_and(|format| format.trim() == "json") {
+            return (Some(Self::Inner), false);
+        }
+
+        // Structured output is an optional addition, so a file that can't be opened must not
+        // break the build.
+        let file = var(OUTPUT_EVENTS_PATH_ENV_VAR)
+            .and_then(|path| OpenOptions::new().create(true).append(true).open(path).ok());
+
+        (file.map(Self::File), true)
+    }
+}
+
+/// Serializes an event as a single JSON line.
+pub(crate) fn json_line(event: &serde_json::Value) -> Vec<u8> {
+    let mut line = event.to_string().into_bytes();
+    line.push(b'\n');
+    line
+}
+
+pub(crate) fn duration_ms(duration: &Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+pub(crate) fn write_event<W: Write>(destination: &mut W, event: &serde_json::Value) {
+    destination
+        .write_all(&json_line(event))
+        .and_then(|()| destination.flush())
+        .expect("Output error: UI writer closed");
+}
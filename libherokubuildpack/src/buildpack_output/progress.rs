@@ -0,0 +1,87 @@
+use crate::buildpack_output::capabilities::Capabilities;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// How progress is rendered.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum ProgressStyle {
+    /// The line is redrawn in place with a progress bar or spinner.
+    Interactive,
+    /// Percentages or dots are appended to the line, since redrawing would garble logs.
+    Plain,
+}
+
+impl ProgressStyle {
+    pub(crate) fn detect(capabilities: Capabilities) -> Self {
+        if std::io::stdout().is_terminal() && !capabilities.ci {
+            Self::Interactive
+        } else {
+            Self::Plain
+        }
+    }
+}
+
+/// The minimum time between two updates that don't change the percentage, such as spinner frames
+/// or dots.
+pub(crate) const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+const SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+const UNICODE_SPINNER_FRAMES: [char; 4] = ['◐', '◓', '◑', '◒'];
+const BAR_WIDTH: u64 = 20;
+
+/// The width of a rendered progress bar, which is wider than a spinner.
+pub(crate) const RENDERED_WIDTH: usize = 27;
+
+/// The completed percentage, clamped to 100.
+pub(crate) fn percentage(done: u64, total: u64) -> u64 {
+    if total == 0 {
+        100
+    } else {
+        (u128::from(done.min(total)) * 100 / u128::from(total))
+            .try_into()
+            .unwrap_or(100)
+    }
+}
+
+pub(crate) fn bar(percentage: u64) -> String {
+    let filled = percentage * BAR_WIDTH / 100;
+    format!(
+        "[{}{}] {percentage:>3}%",
+        "#".repeat(usize::try_from(filled).unwrap_or_default()),
+        " ".repeat(usize::try_from(BAR_WIDTH - filled).unwrap_or_default())
+    )
+}
+
+pub(crate) fn spinner(tick: u64, unicode: bool) -> char {
+    let frames = if unicode {
+        UNICODE_SPINNER_FRAMES
+    } else {
+        SPINNER_FRAMES
+    };
+    frames[usize::try_from(tick % 4).unwrap_or_default()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_percentage_and_bar() {
+        assert_eq!(percentage(0, 200), 0);
+        assert_eq!(percentage(150, 200), 75);
+        assert_eq!(percentage(300, 200), 100);
+        assert_eq!(percentage(0, 0), 100);
+        assert_eq!(percentage(u64::MAX - 1, u64::MAX), 99);
+
+        assert_eq!(bar(0), "[                    ]   0%");
+        assert_eq!(bar(55), "[###########         ]  55%");
+        assert_eq!(bar(100), "[####################] 100%");
+        assert_eq!(bar(100).len(), RENDERED_WIDTH);
+    }
+
+    #[test]
+    fn test_spinner() {
+        assert_eq!(spinner(1, false), '/');
+        assert_eq!(spinner(5, true), '◓');
+    }
+}
@@ -0,0 +1,82 @@
+//! Types for the summary that is written at the end of the buildpack output.
+
+use crate::buildpack_output::style;
+use libcnb::layer::LayerAction;
+use std::fmt::{self, Display, Formatter};
+
+/// What happened to a layer during the build, for the summary of the buildpack output.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LayerStatus {
+    /// The layer was created from scratch, such as on the first build or after a cache miss.
+    Created,
+    /// A layer from a previous build was updated.
+    Updated,
+    /// A layer from a previous build was reused as is.
+    Reused,
+}
+
+impl From<LayerAction> for LayerStatus {
+    fn from(action: LayerAction) -> Self {
+        match action {
+            LayerAction::Create | LayerAction::Recreate => LayerStatus::Created,
+            LayerAction::Update => LayerStatus::Updated,
+            LayerAction::Keep => LayerStatus::Reused,
+        }
+    }
+}
+
+impl Display for LayerStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LayerStatus::Created => "created",
+            LayerStatus::Updated => "updated",
+            LayerStatus::Reused => "reused",
+        })
+    }
+}
+
+/// The contents of the summary, collected while the buildpack output is written.
+#[derive(Debug, Default)]
+pub(crate) struct Summary {
+    /// Whether the summary is written when the buildpack output is finished.
+    pub(crate) enabled: bool,
+    pub(crate) entries: Vec<(String, String)>,
+    pub(crate) layers: Vec<(String, LayerStatus)>,
+    /// The first line of each warning.
+    pub(crate) warnings: Vec<String>,
+}
+
+impl Summary {
+    /// The lines of the summary, each of which is written as a step of the summary section.
+    pub(crate) fn lines(&self) -> Vec<String> {
+        let mut lines = self
+            .entries
+            .iter()
+            .map(|(label, value)| format!("{label}: {value}"))
+            .collect::<Vec<_>>();
+
+        if !self.layers.is_empty() {
+            let layers = self
+                .layers
+                .iter()
+                .map(|(name, status)| {
+                    format!(
+                        "{} {}",
+                        style::value(name),
+                        style::details(status.to_string())
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!("Layers: {layers}"));
+        }
+
+        lines.extend(
+            self.warnings
+                .iter()
+                .map(|warning| format!("Warning: {warning}")),
+        );
+
+        lines
+    }
+}
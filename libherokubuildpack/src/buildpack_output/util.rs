@@ -1,3 +1,11 @@
+use crate::buildpack_output::ansi_escape::{strip_ansi_escape_bytes, strip_ansi_escapes};
+use crate::buildpack_output::capabilities::Capabilities;
+use crate::buildpack_output::events::{json_line, write_event, EventSink};
+use crate::buildpack_output::summary::Summary;
+use crate::buildpack_output::verbosity::Verbosity;
+use regex::bytes::Regex;
+use serde_json::json;
+use std::borrow::Cow;
 use std::fmt::Debug;
 use std::io::Write;
 
@@ -57,11 +65,27 @@ pub(crate) fn prefix_lines<F: Fn(usize, &str) -> String>(contents: &str, f: F) -
 ///
 /// This writer seeks to solve that problem by preserving knowledge of prior newline writes and
 /// exposing that information to the caller.
+///
+/// It also masks any registered secrets in the written contents. Secrets are only masked if they
+/// are contained in a single write, which is the case for all writes of [`BuildpackOutput`](super::BuildpackOutput)
+/// since streamed output is written line by line.
+///
+/// If colors are disabled, ANSI escape sequences are removed from the written contents, including
+/// those in streamed output of commands.
 #[derive(Debug)]
 pub(crate) struct ParagraphInspectWrite<W> {
     pub(crate) inner: W,
     pub(crate) was_paragraph: bool,
     pub(crate) newlines_since_last_char: usize,
+    pub(crate) redactions: Vec<Redaction>,
+    pub(crate) capabilities: Capabilities,
+    pub(crate) verbosity: Verbosity,
+    pub(crate) summary: Summary,
+    pub(crate) events: Option<EventSink>,
+    /// Whether text is written to `inner`, which is not the case when events are written instead.
+    pub(crate) text: bool,
+    /// Whether writes are lines of streamed output, which are emitted as `stream_line` events.
+    pub(crate) streaming: bool,
 }
 
 impl<W> ParagraphInspectWrite<W> {
@@ -70,6 +94,56 @@ impl<W> ParagraphInspectWrite<W> {
             inner: io,
             newlines_since_last_char: 0,
             was_paragraph: false,
+            redactions: Vec::new(),
+            capabilities: Capabilities {
+                color: true,
+                unicode: true,
+                ci: false,
+            },
+            verbosity: Verbosity::Info,
+            summary: Summary::default(),
+            events: None,
+            text: true,
+            streaming: false,
+        }
+    }
+
+    fn redact(&self, contents: &str) -> String {
+        let redacted = self
+            .redactions
+            .iter()
+            .fold(contents.as_bytes().to_vec(), |contents, redaction| {
+                redaction.apply(&contents).into_owned()
+            });
+
+        String::from_utf8_lossy(&redacted).into_owned()
+    }
+}
+
+impl<W: Write> ParagraphInspectWrite<W> {
+    /// Writes a structured event, if events are enabled.
+    ///
+    /// ANSI escape sequences are removed from and secrets are masked in all string fields.
+    pub(crate) fn emit(&mut self, mut event: serde_json::Value) {
+        if self.events.is_none() {
+            return;
+        }
+
+        if let Some(fields) = event.as_object_mut() {
+            for value in fields.values_mut() {
+                if let serde_json::Value::String(string) = value {
+                    *string = self.redact(&strip_ansi_escapes(string));
+                }
+            }
+        }
+
+        match &mut self.events {
+            Some(EventSink::Inner) => write_event(&mut self.inner, &event),
+            // Events written to a file are an optional addition and must not break the build.
+            Some(EventSink::File(file)) => {
+                let _ = file.write_all(&json_line(&event));
+            }
+            None => {}
         }
     }
 }
@@ -78,6 +152,57 @@ impl<W: Write> Write for ParagraphInspectWrite<W> {
     /// We need to track newlines across multiple writes to eliminate the double empty newline
     /// problem described above.
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.streaming {
+            let line = String::from_utf8_lossy(buf);
+            let line = line.trim_end_matches('\n');
+            self.emit(json!({
+                "type": "stream_line",
+                "line": line.strip_prefix(STREAM_INDENT).unwrap_or(line),
+            }));
+        }
+
+        // Streamed output is only written at the info verbosity, other contents are filtered
+        // before they are written.
+        if !self.text || (self.streaming && self.verbosity < Verbosity::Info) {
+            self.track_newlines(buf);
+            return Ok(buf.len());
+        }
+
+        if self.capabilities.color && self.redactions.is_empty() {
+            self.track_newlines(buf);
+            return self.inner.write(buf);
+        }
+
+        let contents = if !self.capabilities.color && buf.contains(&b'\x1B') {
+            Cow::Owned(strip_ansi_escape_bytes(buf))
+        } else {
+            Cow::Borrowed(buf)
+        };
+
+        let redacted = self
+            .redactions
+            .iter()
+            .fold(contents, |contents, redaction| {
+                match redaction.apply(&contents) {
+                    Cow::Borrowed(_) => contents,
+                    Cow::Owned(replaced) => Cow::Owned(replaced),
+                }
+            });
+
+        // Partially written stripped or redacted contents can't be mapped back to the original
+        // contents, so they are always written completely.
+        self.track_newlines(&redacted);
+        self.inner.write_all(&redacted)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W> ParagraphInspectWrite<W> {
+    fn track_newlines(&mut self, buf: &[u8]) {
         let trailing_newline_count = buf.iter().rev().take_while(|&&c| c == b'\n').count();
         // The buffer contains only newlines
         if buf.len() == trailing_newline_count {
@@ -87,11 +212,48 @@ impl<W: Write> Write for ParagraphInspectWrite<W> {
         }
 
         self.was_paragraph = self.newlines_since_last_char > 1;
-        self.inner.write(buf)
     }
+}
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        self.inner.flush()
+/// The indentation of streamed output lines.
+pub(crate) const STREAM_INDENT: &str = "      ";
+
+/// The text that redacted secrets are replaced with.
+const REDACTED: &[u8] = b"<redacted>";
+
+/// A secret that is masked by [`ParagraphInspectWrite`].
+#[derive(Debug)]
+pub(crate) enum Redaction {
+    /// A non-empty secret value.
+    Value(Vec<u8>),
+    Pattern(Regex),
+}
+
+impl Redaction {
+    fn apply<'a>(&self, contents: &'a [u8]) -> Cow<'a, [u8]> {
+        match self {
+            Redaction::Value(value) => {
+                let mut redacted = Vec::new();
+                let mut rest = contents;
+
+                while let Some(index) = rest
+                    .windows(value.len())
+                    .position(|window| window == value.as_slice())
+                {
+                    redacted.extend_from_slice(&rest[..index]);
+                    redacted.extend_from_slice(REDACTED);
+                    rest = &rest[index + value.len()..];
+                }
+
+                if rest.len() == contents.len() {
+                    Cow::Borrowed(contents)
+                } else {
+                    redacted.extend_from_slice(rest);
+                    Cow::Owned(redacted)
+                }
+            }
+            Redaction::Pattern(pattern) => pattern.replace_all(contents, REDACTED),
+        }
     }
 }
 
@@ -192,6 +354,25 @@ mod test {
         assert!(inspect_write.was_paragraph);
     }
 
+    #[test]
+    fn test_paragraph_inspect_write_without_color() {
+        let mut inspect_write = ParagraphInspectWrite::new(Vec::new());
+        inspect_write.capabilities.color = false;
+        inspect_write
+            .redactions
+            .push(Redaction::Value(b"hunter2".to_vec()));
+
+        inspect_write
+            .write_all(b"\x1B[1;36mhunter2\x1B[0m and \x1B[0;33mplain\x1B[0m\n\n")
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&inspect_write.inner),
+            "<redacted> and plain\n\n"
+        );
+        assert!(inspect_write.was_paragraph);
+    }
+
     #[test]
     fn test_prefix_first_rest_lines() {
         assert_eq!("- hello", &prefix_first_rest_lines("- ", "  ", "hello"));
@@ -0,0 +1,104 @@
+//! Verbosity of the buildpack output.
+//!
+//! The verbosity is read from the first of the [`VERBOSITY_ENV_VARS`] that contains a known level,
+//! so that users can turn on detailed diagnostics, or turn off everything but warnings and errors,
+//! by setting an environment variable for their build:
+//!
+//! * `quiet` (or `off`, `error`, `warn`): Only warnings, important messages and errors are written.
+//! * `info`: The default, sections, steps and streamed output are written as well.
+//! * `debug` (or `trace`): Messages written with `BuildpackOutput::debug` are written as well.
+//!
+//! Levels are case-insensitive, so the levels of `LIBCNB_LOG_LEVEL`, which sets the level of the
+//! `logging` feature of libcnb, are understood as well. It is read last, so that a level set for
+//! the buildpack output alone takes precedence.
+//!
+//! The verbosity only affects the text output, all events except for debug messages are written
+//! regardless of it, see the [`events`](super::events) module.
+
+use libcnb::Platform;
+use std::env;
+
+/// The environment variables the verbosity is read from, in order of precedence.
+pub const VERBOSITY_ENV_VARS: [&str; 3] = ["BP_LOG_LEVEL", "CNB_LOG_LEVEL", "LIBCNB_LOG_LEVEL"];
+
+/// How much of the buildpack output is written.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Verbosity {
+    /// Only warnings, important messages and errors.
+    Quiet,
+    /// All output except for debug messages.
+    #[default]
+    Info,
+    /// All output, including debug messages.
+    Debug,
+}
+
+impl Verbosity {
+    /// Reads the verbosity from the environment variables of the current process.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::from_env_vars(|name| env::var(name).ok())
+    }
+
+    /// Reads the verbosity from the platform env (`<platform>/env`), falling back to the
+    /// environment variables of the current process, like libcnb does for its own settings.
+    #[must_use]
+    pub fn detect_from_platform(platform: &impl Platform) -> Self {
+        Self::from_env_vars(|name| platform.env_var(name).or_else(|| env::var(name).ok()))
+    }
+
+    pub(crate) fn from_env_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        VERBOSITY_ENV_VARS
+            .iter()
+            .find_map(|name| var(name).and_then(|value| Self::parse(&value)))
+            .unwrap_or_default()
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "quiet" | "off" | "error" | "warn" | "warning" => Some(Self::Quiet),
+            "info" => Some(Self::Info),
+            "debug" | "trace" => Some(Self::Debug),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use libcnb::generic::GenericPlatform;
+    use libcnb::Env;
+    use std::collections::HashMap;
+
+    fn verbosity(vars: &[(&str, &str)]) -> Verbosity {
+        let vars = vars.iter().copied().collect::<HashMap<_, _>>();
+        Verbosity::from_env_vars(|name| vars.get(name).map(|value| String::from(*value)))
+    }
+
+    #[test]
+    fn detect_verbosity() {
+        assert_eq!(verbosity(&[]), Verbosity::Info);
+        assert_eq!(verbosity(&[("BP_LOG_LEVEL", "DEBUG")]), Verbosity::Debug);
+        assert_eq!(verbosity(&[("CNB_LOG_LEVEL", "warn")]), Verbosity::Quiet);
+        assert_eq!(
+            verbosity(&[("BP_LOG_LEVEL", "quiet"), ("CNB_LOG_LEVEL", "debug")]),
+            Verbosity::Quiet
+        );
+        assert_eq!(
+            verbosity(&[("BP_LOG_LEVEL", "verbose"), ("CNB_LOG_LEVEL", "debug")]),
+            Verbosity::Debug
+        );
+        assert_eq!(verbosity(&[("LIBCNB_LOG_LEVEL", "off")]), Verbosity::Quiet);
+        assert_eq!(
+            verbosity(&[("CNB_LOG_LEVEL", "info"), ("LIBCNB_LOG_LEVEL", "debug")]),
+            Verbosity::Info
+        );
+    }
+
+    #[test]
+    fn detect_verbosity_from_platform() {
+        let platform = GenericPlatform::new(Env::new().with("CNB_LOG_LEVEL", "debug"));
+        assert_eq!(Verbosity::detect_from_platform(&platform), Verbosity::Debug);
+    }
+}
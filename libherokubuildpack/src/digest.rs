@@ -1,9 +1,10 @@
+use libcnb::data::checksum::{Checksum, ChecksumAlgorithm};
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt::Write;
 use std::fs;
 use std::io::Read;
 use std::path::Path;
 
-use sha2::{Digest, Sha256};
-
 /// Obtains the SHA256 checksum of a file as a hex string
 ///
 /// # Examples
@@ -23,15 +24,57 @@ use sha2::{Digest, Sha256};
 /// );
 /// ```
 pub fn sha256(path: impl AsRef<Path>) -> Result<String, std::io::Error> {
-    let mut file = fs::File::open(path.as_ref())?;
+    hex_digest::<Sha256>(path.as_ref())
+}
+
+/// Obtains the checksum of a file, using the given algorithm.
+///
+/// # Examples
+/// ```
+/// use libcnb::data::checksum::ChecksumAlgorithm;
+/// use libherokubuildpack::digest::checksum;
+/// use std::fs::write;
+/// use tempfile::tempdir;
+///
+/// let temp_dir = tempdir().unwrap();
+/// let temp_file = temp_dir.path().join("test.txt");
+///
+/// write(&temp_file, "Hello World!").unwrap();
+/// let checksum = checksum(&temp_file, ChecksumAlgorithm::Sha256).unwrap();
+/// assert_eq!(
+///     checksum.to_string(),
+///     "sha256:7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069"
+/// );
+/// ```
+pub fn checksum(
+    path: impl AsRef<Path>,
+    algorithm: ChecksumAlgorithm,
+) -> Result<Checksum, std::io::Error> {
+    let digest = match algorithm {
+        ChecksumAlgorithm::Sha256 => hex_digest::<Sha256>(path.as_ref()),
+        ChecksumAlgorithm::Sha512 => hex_digest::<Sha512>(path.as_ref()),
+    }?;
+
+    Checksum::new(algorithm, digest).map_err(std::io::Error::other)
+}
+
+fn hex_digest<D: Digest>(path: &Path) -> Result<String, std::io::Error> {
+    let mut file = fs::File::open(path)?;
     let mut buffer = [0x00; 10 * 1024];
-    let mut sha256: Sha256 = sha2::Sha256::default();
+    let mut digest = D::new();
 
     let mut read = file.read(&mut buffer)?;
     while read > 0 {
-        Digest::update(&mut sha256, &buffer[..read]);
+        digest.update(&buffer[..read]);
         read = file.read(&mut buffer)?;
     }
 
-    Ok(format!("{:x}", sha256.finalize()))
+    Ok(digest
+        .finalize()
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            // Writing to a String never fails.
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        }))
 }
@@ -0,0 +1,165 @@
+use super::backend::{encode_path, metadata_agent, split_uri, BackendRequest, DownloadBackend};
+use super::DownloadError;
+use serde::Deserialize;
+use std::env;
+
+/// The version of the Blob service REST API. Bearer tokens require at least `2017-11-09`.
+const AZURE_STORAGE_API_VERSION: &str = "2020-04-08";
+
+/// Downloads `az://<account>/<container>/<blob>` URIs from Azure Blob Storage.
+///
+/// Requests are authorized in this order:
+///
+/// 1. A shared access signature (SAS) configured with [`AzureBlobBackend::sas_token`], or read
+///    from the `AZURE_STORAGE_SAS_TOKEN` environment variable.
+/// 2. An access token configured with [`AzureBlobBackend::access_token`].
+/// 3. A token of the managed identity from the Azure instance metadata service. If
+///    `AZURE_CLIENT_ID` is set, the token of that user-assigned identity is requested.
+///
+/// If none is available, the request is sent anonymously, which works for public containers.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::download::{AzureBlobBackend, Download};
+///
+/// Download::new(
+///     "az://exampleaccount/runtimes/runtime-1.2.3.tar.gz",
+///     "sha256:ea8fac7c65fb589b0d53560f5251f74f9e9b243478dcb6b3ea79b5e36449c8d9"
+///         .parse()
+///         .unwrap(),
+/// )
+/// .backend(AzureBlobBackend::new().sas_token("sv=2022-11-02&ss=b&sig=..."))
+/// .fetch("/tmp/runtime.tar.gz")
+/// .unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct AzureBlobBackend {
+    endpoint: Option<String>,
+    sas_token: Option<String>,
+    access_token: Option<String>,
+}
+
+impl AzureBlobBackend {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the endpoint, such as the URL of the Azurite emulator. Accounts are addressed by
+    /// path (`<endpoint>/<account>/<container>/<blob>`) instead of by host name.
+    #[must_use]
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Sets a shared access signature, the query string of a SAS URL without the leading `?`.
+    #[must_use]
+    pub fn sas_token(mut self, sas_token: impl Into<String>) -> Self {
+        self.sas_token = Some(sas_token.into());
+        self
+    }
+
+    /// Sets an OAuth access token for the `https://storage.azure.com/` resource.
+    #[must_use]
+    pub fn access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+}
+
+impl std::fmt::Debug for AzureBlobBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AzureBlobBackend")
+            .field("endpoint", &self.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DownloadBackend for AzureBlobBackend {
+    fn request(&self, uri: &str) -> Result<BackendRequest, DownloadError> {
+        let (account, blob_path) = split_uri(uri, "az")?;
+        let blob_path = encode_path(blob_path);
+
+        let url = match &self.endpoint {
+            Some(endpoint) => format!("{}/{account}/{blob_path}", endpoint.trim_end_matches('/')),
+            None => format!("https://{account}.blob.core.windows.net/{blob_path}"),
+        };
+
+        if let Some(sas_token) = self
+            .sas_token
+            .clone()
+            .or_else(|| env::var("AZURE_STORAGE_SAS_TOKEN").ok())
+        {
+            return Ok(BackendRequest::new(format!(
+                "{url}?{}",
+                sas_token.trim_start_matches('?')
+            )));
+        }
+
+        let request = BackendRequest::new(url).header("x-ms-version", AZURE_STORAGE_API_VERSION);
+
+        Ok(
+            match self
+                .access_token
+                .clone()
+                .or_else(managed_identity_access_token)
+            {
+                Some(access_token) => {
+                    request.header("Authorization", format!("Bearer {access_token}"))
+                }
+                None => request,
+            },
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+}
+
+fn managed_identity_access_token() -> Option<String> {
+    let mut request = metadata_agent()
+        .get("http://169.254.169.254/metadata/identity/oauth2/token")
+        .query("api-version", "2018-02-01")
+        .query("resource", "https://storage.azure.com/")
+        .set("Metadata", "true");
+    if let Ok(client_id) = env::var("AZURE_CLIENT_ID") {
+        request = request.query("client_id", &client_id);
+    }
+
+    let response = request.call().ok()?;
+    serde_json::from_reader::<_, AccessTokenResponse>(response.into_reader())
+        .ok()
+        .map(|response| response.access_token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn azure_request() {
+        assert_eq!(
+            AzureBlobBackend::new()
+                .sas_token("?sv=2022-11-02&sig=abc")
+                .request("az://exampleaccount/runtimes/runtime 1.0.tgz")
+                .unwrap(),
+            BackendRequest::new(
+                "https://exampleaccount.blob.core.windows.net/runtimes/runtime%201.0.tgz?sv=2022-11-02&sig=abc"
+            )
+        );
+
+        assert_eq!(
+            AzureBlobBackend::new()
+                .endpoint("http://127.0.0.1:10000/")
+                .access_token("token")
+                .request("az://devstoreaccount1/runtimes/runtime.tgz")
+                .unwrap(),
+            BackendRequest::new("http://127.0.0.1:10000/devstoreaccount1/runtimes/runtime.tgz")
+                .header("x-ms-version", AZURE_STORAGE_API_VERSION)
+                .header("Authorization", "Bearer token")
+        );
+    }
+}
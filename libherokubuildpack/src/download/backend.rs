@@ -0,0 +1,131 @@
+use super::azure::AzureBlobBackend;
+use super::gcs::GcsBackend;
+use super::s3::S3Backend;
+use super::DownloadError;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A storage backend that [`Download`](super::Download) fetches files from.
+///
+/// A backend turns the URI of a file into an HTTP(S) request, adding credentials or signatures
+/// as required. The built-in backends are selected by the scheme of the URI:
+///
+/// - `s3://<bucket>/<key>`: [`S3Backend`]
+/// - `gs://<bucket>/<object>`: [`GcsBackend`]
+/// - `az://<account>/<container>/<blob>`: [`AzureBlobBackend`]
+/// - All other URIs, including pre-signed URLs of any object store, are requested as-is.
+///
+/// Implement this trait to download from other private stores.
+pub trait DownloadBackend: Debug + Send + Sync {
+    /// Returns the request for the file with the given URI.
+    ///
+    /// Called before every attempt of a download, so that short-lived credentials and signatures
+    /// are always current.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the URI isn't supported by the backend, or the request couldn't be
+    /// authorized.
+    fn request(&self, uri: &str) -> Result<BackendRequest, DownloadError>;
+}
+
+/// An HTTP(S) `GET` request, as returned by a [`DownloadBackend`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BackendRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl BackendRequest {
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Requests URIs as-is, without any credentials.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HttpBackend;
+
+impl DownloadBackend for HttpBackend {
+    fn request(&self, uri: &str) -> Result<BackendRequest, DownloadError> {
+        Ok(BackendRequest::new(uri))
+    }
+}
+
+pub(super) fn default_backend(uri: &str) -> Arc<dyn DownloadBackend> {
+    match uri.split_once("://").map(|(scheme, _)| scheme) {
+        Some("s3") => Arc::new(S3Backend::new()),
+        Some("gs") => Arc::new(GcsBackend::new()),
+        Some("az") => Arc::new(AzureBlobBackend::new()),
+        _ => Arc::new(HttpBackend),
+    }
+}
+
+/// Splits a URI such as `s3://bucket/path/to/key` into its first path component and the rest.
+pub(super) fn split_uri<'a>(
+    uri: &'a str,
+    scheme: &str,
+) -> Result<(&'a str, &'a str), DownloadError> {
+    uri.strip_prefix(scheme)
+        .and_then(|uri| uri.strip_prefix("://"))
+        .and_then(|uri| uri.split_once('/'))
+        .filter(|(first, rest)| !first.is_empty() && !rest.is_empty())
+        .ok_or_else(|| DownloadError::UnsupportedUri(String::from(uri)))
+}
+
+/// Percent-encodes a path, keeping `/` separators. Only unreserved characters are kept as-is, as
+/// required for AWS signatures.
+pub(super) fn encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                String::from(byte as char)
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// An agent for requests to instance metadata services, which are only reachable on the
+/// respective cloud. The short timeout keeps downloads elsewhere from stalling.
+pub(super) fn metadata_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(2))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_encode_uri() {
+        assert_eq!(
+            split_uri("s3://bucket/path/to/my key.tgz", "s3").unwrap(),
+            ("bucket", "path/to/my key.tgz")
+        );
+        assert!(matches!(
+            split_uri("s3://bucket", "s3"),
+            Err(DownloadError::UnsupportedUri(_))
+        ));
+        assert!(matches!(
+            split_uri("gs://bucket/key", "s3"),
+            Err(DownloadError::UnsupportedUri(_))
+        ));
+        assert_eq!(
+            encode_path("path/to/my key+1~.tgz"),
+            "path/to/my%20key%2B1~.tgz"
+        );
+    }
+}
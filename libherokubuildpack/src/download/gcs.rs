@@ -0,0 +1,132 @@
+use super::backend::{encode_path, metadata_agent, split_uri, BackendRequest, DownloadBackend};
+use super::DownloadError;
+use serde::Deserialize;
+use std::env;
+
+/// Downloads `gs://<bucket>/<object>` URIs from Google Cloud Storage.
+///
+/// The access token is resolved in this order:
+///
+/// 1. A token configured with [`GcsBackend::access_token`].
+/// 2. The `GOOGLE_OAUTH_ACCESS_TOKEN` environment variable.
+/// 3. The token of the default service account from the GCE metadata server. Its host can be
+///    overridden with `GCE_METADATA_HOST`.
+///
+/// If no token is found, the request is sent anonymously, which works for public buckets.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::download::{Download, GcsBackend};
+///
+/// Download::new(
+///     "gs://example-bucket/runtimes/runtime-1.2.3.tar.gz",
+///     "sha256:ea8fac7c65fb589b0d53560f5251f74f9e9b243478dcb6b3ea79b5e36449c8d9"
+///         .parse()
+///         .unwrap(),
+/// )
+/// .backend(GcsBackend::new().access_token(std::env::var("GCS_TOKEN").unwrap()))
+/// .fetch("/tmp/runtime.tar.gz")
+/// .unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct GcsBackend {
+    endpoint: Option<String>,
+    access_token: Option<String>,
+}
+
+impl GcsBackend {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the endpoint, such as the URL of an emulator. Defaults to
+    /// `https://storage.googleapis.com`.
+    #[must_use]
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    #[must_use]
+    pub fn access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+
+    fn resolve_access_token(&self) -> Option<String> {
+        self.access_token
+            .clone()
+            .or_else(|| env::var("GOOGLE_OAUTH_ACCESS_TOKEN").ok())
+            .or_else(metadata_server_access_token)
+    }
+}
+
+impl std::fmt::Debug for GcsBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcsBackend")
+            .field("endpoint", &self.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DownloadBackend for GcsBackend {
+    fn request(&self, uri: &str) -> Result<BackendRequest, DownloadError> {
+        let (bucket, object) = split_uri(uri, "gs")?;
+        let endpoint = self
+            .endpoint
+            .as_deref()
+            .unwrap_or("https://storage.googleapis.com")
+            .trim_end_matches('/');
+
+        let request = BackendRequest::new(format!("{endpoint}/{bucket}/{}", encode_path(object)));
+
+        Ok(match self.resolve_access_token() {
+            Some(access_token) => request.header("Authorization", format!("Bearer {access_token}")),
+            None => request,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+}
+
+fn metadata_server_access_token() -> Option<String> {
+    let host =
+        env::var("GCE_METADATA_HOST").unwrap_or_else(|_| String::from("metadata.google.internal"));
+
+    let response = metadata_agent()
+        .get(&format!(
+            "http://{host}/computeMetadata/v1/instance/service-accounts/default/token"
+        ))
+        .set("Metadata-Flavor", "Google")
+        .call()
+        .ok()?;
+
+    serde_json::from_reader::<_, AccessTokenResponse>(response.into_reader())
+        .ok()
+        .map(|response| response.access_token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcs_request() {
+        let request = GcsBackend::new()
+            .access_token("token")
+            .request("gs://example-bucket/runtimes/runtime 1.0.tgz")
+            .unwrap();
+
+        assert_eq!(
+            request,
+            BackendRequest::new(
+                "https://storage.googleapis.com/example-bucket/runtimes/runtime%201.0.tgz"
+            )
+            .header("Authorization", "Bearer token")
+        );
+    }
+}
@@ -0,0 +1,648 @@
+mod azure;
+mod backend;
+mod gcs;
+mod s3;
+
+pub use azure::AzureBlobBackend;
+pub use backend::{BackendRequest, DownloadBackend, HttpBackend};
+pub use gcs::GcsBackend;
+pub use s3::{AwsCredentials, S3Backend};
+
+use crate::digest::checksum;
+use libcnb::data::checksum::Checksum;
+use libcnb::retry::Retry;
+use libcnb::telemetry;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::{fs, io};
+
+#[derive(thiserror::Error, Debug)]
+pub enum DownloadError {
+    // Boxed to prevent `large_enum_variant` errors since `ureq::Error` is massive.
+    #[error("HTTP error while downloading file: {0}")]
+    HttpError(#[from] Box<ureq::Error>),
+
+    #[error("I/O error while downloading file: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Checksum mismatch for downloaded file, expected {expected}, but got {actual}")]
+    ChecksumMismatch {
+        expected: Checksum,
+        actual: Checksum,
+    },
+
+    #[error("Unsupported download URI: {0}")]
+    UnsupportedUri(String),
+}
+
+/// Downloads a file via HTTP(S) to a local path
+///
+/// # Examples
+/// ```
+/// use libherokubuildpack::digest::sha256;
+/// use libherokubuildpack::download::download_file;
+/// use tempfile::tempdir;
+///
+/// let temp_dir = tempdir().unwrap();
+/// let temp_file = temp_dir.path().join("result.bin");
+///
+/// download_file("https://example.com/", &temp_file).unwrap();
+/// assert_eq!(
+///     sha256(&temp_file).unwrap(),
+///     "ea8fac7c65fb589b0d53560f5251f74f9e9b243478dcb6b3ea79b5e36449c8d9"
+/// );
+/// ```
+pub fn download_file(
+    uri: impl AsRef<str>,
+    destination: impl AsRef<std::path::Path>,
+) -> Result<(), DownloadError> {
+    let response = ureq::get(uri.as_ref()).call().map_err(Box::new)?;
+    let mut reader = response.into_reader();
+    let mut file = fs::File::create(destination.as_ref())?;
+    let size = io::copy(&mut reader, &mut file)?;
+
+    #[allow(clippy::cast_precision_loss)]
+    telemetry::histogram("libherokubuildpack.download.size", "By").record(size as f64, &[]);
+
+    Ok(())
+}
+
+/// Downloads a file via HTTP(S) to a local path, retrying on transient failures.
+///
+/// Connection errors, I/O errors and responses with status `429` or `5xx` are retried according to
+/// the given [`Retry`]. Other HTTP errors, such as `404`, fail immediately.
+///
+/// # Examples
+/// ```no_run
+/// use libcnb::retry::Retry;
+/// use libherokubuildpack::download::download_file_with_retry;
+///
+/// download_file_with_retry(
+///     "https://example.com/",
+///     "/tmp/result.bin",
+///     &Retry::new().max_attempts(5),
+/// )
+/// .unwrap();
+/// ```
+pub fn download_file_with_retry(
+    uri: impl AsRef<str>,
+    destination: impl AsRef<std::path::Path>,
+    retry: &Retry,
+) -> Result<(), DownloadError> {
+    retry.run_if(
+        |_| download_file(uri.as_ref(), destination.as_ref()),
+        is_transient_download_error,
+    )
+}
+
+/// Downloads a file via HTTP(S) to a local path and verifies its checksum.
+///
+/// The file is removed if its checksum doesn't match the expected checksum.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::download::download_file_with_checksum;
+///
+/// download_file_with_checksum(
+///     "https://example.com/",
+///     "/tmp/result.bin",
+///     &"sha256:ea8fac7c65fb589b0d53560f5251f74f9e9b243478dcb6b3ea79b5e36449c8d9"
+///         .parse()
+///         .unwrap(),
+/// )
+/// .unwrap();
+/// ```
+pub fn download_file_with_checksum(
+    uri: impl AsRef<str>,
+    destination: impl AsRef<std::path::Path>,
+    expected: &Checksum,
+) -> Result<(), DownloadError> {
+    download_file(uri, destination.as_ref())?;
+    verify_checksum(destination.as_ref(), expected)
+}
+
+/// A download of a file via HTTP(S) or from an object store, with mandatory checksum
+/// verification.
+///
+/// Besides HTTP(S) URLs, including pre-signed URLs, files can be downloaded from private buckets
+/// with `s3://`, `gs://` and `az://` URIs, see [`DownloadBackend`] for details. Other stores are
+/// supported by configuring a custom [`DownloadBackend`].
+///
+/// Connection errors, I/O errors and responses with status `429` or `5xx` are retried according to
+/// the configured [`Retry`]. Retries resume an interrupted download where it stopped if the server
+/// supports range requests, and start over otherwise. Once complete, the checksum of the file is
+/// verified and the file is removed if it doesn't match.
+///
+/// # Examples
+/// ```no_run
+/// use libcnb::retry::Retry;
+/// use libherokubuildpack::download::Download;
+///
+/// # let layer_path = std::path::PathBuf::from("/layers/heroku_example/runtime");
+/// Download::new(
+///     "https://example.com/runtime.tar.gz",
+///     "sha256:ea8fac7c65fb589b0d53560f5251f74f9e9b243478dcb6b3ea79b5e36449c8d9"
+///         .parse()
+///         .unwrap(),
+/// )
+/// .retry(Retry::new().max_attempts(5))
+/// .fetch_with_progress(layer_path.join("runtime.tar.gz"), &mut std::io::stdout())
+/// .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Download {
+    uri: String,
+    checksum: Checksum,
+    retry: Retry,
+    backend: Arc<dyn DownloadBackend>,
+}
+
+impl Download {
+    /// Creates a download of the given URI, whose contents must match the given checksum.
+    ///
+    /// Uses the default [`Retry`] configuration and the backend for the scheme of the URI.
+    #[must_use]
+    pub fn new(uri: impl Into<String>, checksum: Checksum) -> Self {
+        let uri = uri.into();
+
+        Self {
+            backend: backend::default_backend(&uri),
+            uri,
+            checksum,
+            retry: Retry::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Downloads the file with the given backend instead of the one for the scheme of the URI.
+    #[must_use]
+    pub fn backend(mut self, backend: impl DownloadBackend + 'static) -> Self {
+        self.backend = Arc::new(backend);
+        self
+    }
+
+    /// Downloads the file to the given path, replacing any existing file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the download failed after all retries, or the checksum of the downloaded
+    /// file doesn't match.
+    pub fn fetch(&self, destination: impl AsRef<Path>) -> Result<(), DownloadError> {
+        self.fetch_with_progress(destination, &mut io::sink())
+    }
+
+    /// Like [`fetch`](Self::fetch), but writes a line to `progress` after every 10% of the file,
+    /// or every 10 MiB if the size of the file is unknown.
+    ///
+    /// `progress` is usually the build log, such as [`std::io::stdout`] or a stream of
+    /// [`BuildpackOutput`](crate::buildpack_output::BuildpackOutput).
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the download failed after all retries, or the checksum of the downloaded
+    /// file doesn't match.
+    pub fn fetch_with_progress(
+        &self,
+        destination: impl AsRef<Path>,
+        progress: &mut impl Write,
+    ) -> Result<(), DownloadError> {
+        let destination = destination.as_ref();
+        fs::File::create(destination)?;
+
+        self.retry.run_if(
+            |_| self.fetch_remaining(destination, progress),
+            is_transient_download_error,
+        )?;
+
+        verify_checksum(destination, &self.checksum)
+    }
+
+    fn fetch_remaining(
+        &self,
+        destination: &Path,
+        progress: &mut impl Write,
+    ) -> Result<(), DownloadError> {
+        let offset = fs::metadata(destination)?.len();
+
+        let backend_request = self.backend.request(&self.uri)?;
+        let call = |offset: u64| {
+            let mut request = backend_request
+                .headers
+                .iter()
+                .fold(ureq::get(&backend_request.url), |request, (name, value)| {
+                    request.set(name, value)
+                });
+            if offset > 0 {
+                request = request.set("Range", &format!("bytes={offset}-"));
+            }
+            request.call().map_err(Box::new)
+        };
+
+        let response = match call(offset) {
+            // A partial response that doesn't continue at the end of the file can't be appended.
+            Ok(response)
+                if response.status() == 206
+                    && content_range(&response).and_then(|(start, _)| start) != Some(offset) =>
+            {
+                call(0)
+            }
+            Err(error) if offset > 0 => match *error {
+                ureq::Error::Status(416, response) => {
+                    // The previous attempt already downloaded the whole file.
+                    if content_range(&response).and_then(|(_, length)| length) == Some(offset) {
+                        return Ok(());
+                    }
+                    call(0)
+                }
+                error => Err(Box::new(error)),
+            },
+            result => result,
+        }?;
+
+        // Servers that don't support range requests respond with the whole file instead.
+        let (mut file, mut downloaded) = if response.status() == 206 {
+            (OpenOptions::new().append(true).open(destination)?, offset)
+        } else {
+            (fs::File::create(destination)?, 0)
+        };
+
+        let size = response
+            .header("Content-Length")
+            .and_then(|content_length| content_length.parse::<u64>().ok())
+            .map(|content_length| downloaded + content_length);
+
+        let mut reader = response.into_reader();
+        let mut buffer = [0x00; 10 * 1024];
+        let mut reporter = ProgressReporter::new(size, downloaded);
+
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+
+            file.write_all(&buffer[..read])?;
+            downloaded += read as u64;
+            reporter.report(downloaded, progress);
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        telemetry::histogram("libherokubuildpack.download.size", "By")
+            .record(downloaded as f64, &[]);
+
+        Ok(())
+    }
+}
+
+/// Parses the `Content-Range` header of a response into the position of the first byte, if the
+/// response contains a range, and the complete length of the file, if known.
+fn content_range(response: &ureq::Response) -> Option<(Option<u64>, Option<u64>)> {
+    let (range, length) = response
+        .header("Content-Range")?
+        .trim()
+        .strip_prefix("bytes ")?
+        .split_once('/')?;
+
+    let start = match range {
+        "*" => None,
+        range => Some(range.split_once('-')?.0.parse().ok()?),
+    };
+    let length = match length {
+        "*" => None,
+        length => Some(length.parse().ok()?),
+    };
+
+    Some((start, length))
+}
+
+const PROGRESS_INTERVAL_UNKNOWN_SIZE: u64 = 10 * 1024 * 1024;
+
+struct ProgressReporter {
+    size: Option<u64>,
+    next_report: u64,
+}
+
+impl ProgressReporter {
+    fn new(size: Option<u64>, downloaded: u64) -> Self {
+        let mut reporter = Self {
+            size,
+            next_report: 0,
+        };
+        reporter.next_report = reporter.next_report_after(downloaded);
+        reporter
+    }
+
+    fn interval(&self) -> u64 {
+        self.size
+            .map_or(PROGRESS_INTERVAL_UNKNOWN_SIZE, |size| (size / 10).max(1))
+    }
+
+    fn next_report_after(&self, downloaded: u64) -> u64 {
+        (downloaded / self.interval() + 1) * self.interval()
+    }
+
+    fn report(&mut self, downloaded: u64, progress: &mut impl Write) {
+        if downloaded < self.next_report {
+            return;
+        }
+        self.next_report = self.next_report_after(downloaded);
+
+        let line = match self.size {
+            Some(size) if size > 0 => format!(
+                "Downloaded {} of {} ({}%)",
+                format_bytes(downloaded),
+                format_bytes(size),
+                downloaded * 100 / size
+            ),
+            _ => format!("Downloaded {}", format_bytes(downloaded)),
+        };
+
+        // Progress is informational only, failing to write it must not fail the download.
+        let _ = writeln!(progress, "{line}").and_then(|()| progress.flush());
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        1_048_576..=1_073_741_823 => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+        _ => format!("{:.1} GiB", bytes as f64 / 1_073_741_824.0),
+    }
+}
+
+fn verify_checksum(path: &Path, expected: &Checksum) -> Result<(), DownloadError> {
+    let actual = checksum(path, expected.algorithm())?;
+
+    if &actual == expected {
+        Ok(())
+    } else {
+        fs::remove_file(path)?;
+        Err(DownloadError::ChecksumMismatch {
+            expected: expected.clone(),
+            actual,
+        })
+    }
+}
+
+fn is_transient_download_error(error: &DownloadError) -> bool {
+    match error {
+        DownloadError::HttpError(http_error) => match http_error.as_ref() {
+            ureq::Error::Status(status, _) => *status == 429 || *status >= 500,
+            ureq::Error::Transport(_) => true,
+        },
+        DownloadError::IoError(_) => true,
+        DownloadError::ChecksumMismatch { .. } | DownloadError::UnsupportedUri(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    const CHECKSUM: &str =
+        "sha256:7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069";
+
+    // Serves the given raw HTTP responses, one per connection, and returns the received requests.
+    fn serve(responses: &[&str]) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}/file", listener.local_addr().unwrap());
+        let responses = responses
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        let handle = thread::spawn(move || {
+            responses
+                .iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buffer = [0x00; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let read = stream.read(&mut buffer).unwrap();
+                        request.extend_from_slice(&buffer[..read]);
+                    }
+                    stream.write_all(response.as_bytes()).unwrap();
+                    String::from_utf8(request).unwrap().to_lowercase()
+                })
+                .collect()
+        });
+
+        (uri, handle)
+    }
+
+    fn retry() -> Retry {
+        Retry::new().initial_delay(Duration::ZERO).jitter(false)
+    }
+
+    #[test]
+    fn format_byte_sizes() {
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1_048_575), "1024.0 KiB");
+        assert_eq!(format_bytes(1_048_576), "1.0 MiB");
+        assert_eq!(format_bytes(1_073_741_824), "1.0 GiB");
+    }
+
+    #[test]
+    fn download_resumes_interrupted_download() {
+        let (uri, server) = serve(&[
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\nHello ",
+            "HTTP/1.1 206 Partial Content\r\nContent-Length: 6\r\nContent-Range: bytes 6-11/12\r\nConnection: close\r\n\r\nWorld!",
+        ]);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.txt");
+        let mut progress = Vec::new();
+
+        Download::new(uri, CHECKSUM.parse().unwrap())
+            .retry(retry())
+            .fetch_with_progress(&path, &mut progress)
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].contains("range:"));
+        assert!(requests[1].contains("range: bytes=6-"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Hello World!");
+        assert!(String::from_utf8(progress)
+            .unwrap()
+            .ends_with("Downloaded 12 B of 12 B (100%)\n"));
+    }
+
+    #[test]
+    fn download_restarts_without_range_support() {
+        let (uri, server) = serve(&[
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\nHello ",
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\nHello World!",
+        ]);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.txt");
+
+        Download::new(uri, CHECKSUM.parse().unwrap())
+            .retry(retry())
+            .fetch(&path)
+            .unwrap();
+
+        server.join().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Hello World!");
+    }
+
+    #[test]
+    fn download_restarts_on_mismatching_content_range() {
+        let (uri, server) = serve(&[
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\nHello ",
+            "HTTP/1.1 206 Partial Content\r\nContent-Length: 6\r\nContent-Range: bytes 0-5/12\r\nConnection: close\r\n\r\nHello ",
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\nHello World!",
+        ]);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.txt");
+
+        Download::new(uri, CHECKSUM.parse().unwrap())
+            .retry(retry())
+            .fetch(&path)
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[1].contains("range: bytes=6-"));
+        assert!(!requests[2].contains("range:"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Hello World!");
+    }
+
+    #[test]
+    fn download_completes_on_unsatisfiable_range_of_complete_file() {
+        let (uri, server) = serve(&[
+            "HTTP/1.1 200 OK\r\nContent-Length: 13\r\nConnection: close\r\n\r\nHello World!",
+            "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */12\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.txt");
+
+        Download::new(uri, CHECKSUM.parse().unwrap())
+            .retry(retry())
+            .fetch(&path)
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[1].contains("range: bytes=12-"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Hello World!");
+    }
+
+    #[derive(Debug)]
+    struct TestBackend(String);
+
+    impl DownloadBackend for TestBackend {
+        fn request(&self, uri: &str) -> Result<BackendRequest, DownloadError> {
+            let path = uri
+                .strip_prefix("test://")
+                .ok_or_else(|| DownloadError::UnsupportedUri(String::from(uri)))?;
+
+            Ok(BackendRequest::new(format!("{}/{path}", self.0)).header("Authorization", "secret"))
+        }
+    }
+
+    #[test]
+    fn download_with_backend() {
+        let (uri, server) = serve(&[
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\nHello World!",
+        ]);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.txt");
+
+        Download::new("test://bucket/test.txt", CHECKSUM.parse().unwrap())
+            .backend(TestBackend(uri))
+            .retry(retry())
+            .fetch(&path)
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("get /file/bucket/test.txt "));
+        assert!(requests[0].contains("authorization: secret"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Hello World!");
+
+        assert!(matches!(
+            Download::new("https://example.com/test.txt", CHECKSUM.parse().unwrap())
+                .backend(TestBackend(String::new()))
+                .fetch(&path),
+            Err(DownloadError::UnsupportedUri(_))
+        ));
+    }
+
+    #[test]
+    fn download_fails_on_checksum_mismatch() {
+        let (uri, server) = serve(&[
+            "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\nHello Earth!",
+        ]);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.txt");
+
+        let result = Download::new(uri, CHECKSUM.parse().unwrap())
+            .retry(retry())
+            .fetch(&path);
+
+        server.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(DownloadError::ChecksumMismatch { .. })
+        ));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn format_progress() {
+        let mut progress = Vec::new();
+        let mut reporter = ProgressReporter::new(Some(2_097_152), 0);
+        for downloaded in (0..=2_097_152).step_by(65_536) {
+            reporter.report(downloaded, &mut progress);
+        }
+
+        let mut unknown_size_progress = Vec::new();
+        let mut reporter = ProgressReporter::new(None, 0);
+        reporter.report(10_485_760, &mut unknown_size_progress);
+        reporter.report(10_485_761, &mut unknown_size_progress);
+
+        let progress = String::from_utf8(progress).unwrap();
+        assert_eq!(progress.lines().count(), 10);
+        assert_eq!(
+            progress.lines().next(),
+            Some("Downloaded 256.0 KiB of 2.0 MiB (12%)")
+        );
+        assert_eq!(
+            String::from_utf8(unknown_size_progress).unwrap(),
+            "Downloaded 10.0 MiB\n"
+        );
+    }
+
+    #[test]
+    fn verify_checksum_removes_mismatching_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.txt");
+        fs::write(&path, "Hello World!").unwrap();
+
+        let checksum = "sha256:7f83b1657ff1fc53b92dc18148a1d65dfc2d4b1fa3d677284addd200126d9069"
+            .parse::<Checksum>()
+            .unwrap();
+        assert!(verify_checksum(&path, &checksum).is_ok());
+        assert!(path.exists());
+
+        let other_checksum = format!("sha256:{}", "0".repeat(64))
+            .parse::<Checksum>()
+            .unwrap();
+        assert!(matches!(
+            verify_checksum(&path, &other_checksum),
+            Err(DownloadError::ChecksumMismatch { expected, actual })
+                if expected == other_checksum && actual == checksum
+        ));
+        assert!(!path.exists());
+    }
+}
@@ -62,6 +62,12 @@ where
 {
     match error {
         libcnb::Error::BuildpackError(buildpack_error) => f(buildpack_error),
+        libcnb::Error::UserFacing(user_facing_error) => {
+            log_error(
+                &user_facing_error.message,
+                user_facing_error.body().trim_end(),
+            );
+        }
         libcnb_error => {
             log_error("Internal Buildpack Error", libcnb_error.to_string());
         }
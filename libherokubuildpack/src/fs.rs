@@ -1,6 +1,8 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use std::fs;
-use std::io::ErrorKind;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 /// Moves all contents of a directory into another directory, leaving `src_dir` empty.
 ///
@@ -30,7 +32,7 @@ pub fn move_directory_contents(
     for dir_entry in fs::read_dir(src_dir.as_ref())? {
         let dir_entry = dir_entry?;
         let relative_path = pathdiff::diff_paths(dir_entry.path(), src_dir.as_ref())
-            .ok_or_else(|| std::io::Error::new(ErrorKind::Other, "std::fs::read_dir unexpectedly returned an entry that is not in the directory that was read."))?;
+            .ok_or_else(|| std::io::Error::other("std::fs::read_dir unexpectedly returned an entry that is not in the directory that was read."))?;
 
         fs::rename(dir_entry.path(), dst_dir.as_ref().join(relative_path))?;
     }
@@ -38,6 +40,298 @@ pub fn move_directory_contents(
     Ok(())
 }
 
+/// Recursively copies the contents of a directory into a layer directory.
+///
+/// Unlike a naive recursive copy, this function retains the structure that runtimes such as Python
+/// virtual environments or Node.js corepack shims rely on:
+///
+/// * Symlinks are copied as symlinks instead of copying the file or directory they point to.
+///   Relative targets are retained as is. Absolute targets that point into `src_dir` are
+///   rewritten to point to the corresponding path in `layer_dir`, all other targets are retained.
+/// * The permissions of files and directories, such as the executable bit, are retained.
+/// * Existing files in `layer_dir` are overwritten, other existing files are left untouched.
+///
+/// Paths can be excluded with glob patterns, such as `__pycache__` or `.git/**`. Patterns without a
+/// `/` match the file name at any depth, like in a `.gitignore` file. All other patterns match the
+/// path relative to `src_dir`. Excluded directories are skipped including all of their contents.
+///
+/// # Examples:
+/// ```no_run
+/// use libherokubuildpack::fs::copy_dir_to_layer;
+///
+/// copy_dir_to_layer("/workspace/.venv", "/layers/python/venv", &["__pycache__", "*.pyc"]).unwrap();
+/// ```
+///
+/// # Errors:
+/// Will return `Err` if any exclusion pattern is invalid, or any file, directory or symlink
+/// couldn't be read or created.
+///
+/// # Atomicity:
+/// This functions makes no atomicity guarantees. It is possible that this function errors after
+/// some files already have been copied.
+pub fn copy_dir_to_layer(
+    src_dir: impl AsRef<Path>,
+    layer_dir: impl AsRef<Path>,
+    exclude: &[&str],
+) -> Result<(), CopyDirError> {
+    let mut glob_set_builder = GlobSetBuilder::new();
+    for pattern in exclude {
+        let pattern = if pattern.contains('/') {
+            String::from(*pattern)
+        } else {
+            format!("**/{pattern}")
+        };
+
+        glob_set_builder.add(
+            GlobBuilder::new(&pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(CopyDirError::InvalidExcludePattern)?,
+        );
+    }
+
+    let copy = DirCopy {
+        src_dir: src_dir.as_ref(),
+        dst_dir: layer_dir.as_ref(),
+        exclude: glob_set_builder
+            .build()
+            .map_err(CopyDirError::InvalidExcludePattern)?,
+    };
+
+    copy.copy_dir(Path::new("")).map_err(CopyDirError::IoError)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CopyDirError {
+    #[error("Invalid exclude pattern: {0}")]
+    InvalidExcludePattern(globset::Error),
+    #[error("I/O error while copying directory: {0}")]
+    IoError(io::Error),
+}
+
+struct DirCopy<'a> {
+    src_dir: &'a Path,
+    dst_dir: &'a Path,
+    exclude: GlobSet,
+}
+
+impl DirCopy<'_> {
+    fn copy_dir(&self, relative_path: &Path) -> io::Result<()> {
+        let src_path = self.src_dir.join(relative_path);
+        let dst_path = self.dst_dir.join(relative_path);
+
+        fs::create_dir_all(&dst_path)?;
+
+        for dir_entry in fs::read_dir(&src_path)? {
+            let dir_entry = dir_entry?;
+            let entry_relative_path = relative_path.join(dir_entry.file_name());
+
+            if self.exclude.is_match(&entry_relative_path) {
+                continue;
+            }
+
+            let file_type = dir_entry.file_type()?;
+            if file_type.is_symlink() {
+                self.copy_symlink(&entry_relative_path)?;
+            } else if file_type.is_dir() {
+                self.copy_dir(&entry_relative_path)?;
+            } else {
+                let entry_dst_path = self.dst_dir.join(&entry_relative_path);
+                remove_symlink(&entry_dst_path)?;
+                fs::copy(dir_entry.path(), entry_dst_path)?;
+            }
+        }
+
+        // Permissions are set last, so that read-only directories can be populated first.
+        fs::set_permissions(&dst_path, fs::metadata(&src_path)?.permissions())
+    }
+
+    fn copy_symlink(&self, relative_path: &Path) -> io::Result<()> {
+        let target = fs::read_link(self.src_dir.join(relative_path))?;
+        let target = match target.strip_prefix(self.src_dir) {
+            Ok(target_relative_path) if target.is_absolute() => {
+                self.dst_dir.join(target_relative_path)
+            }
+            _ => target,
+        };
+
+        let dst_path = self.dst_dir.join(relative_path);
+        match fs::symlink_metadata(&dst_path) {
+            Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(&dst_path)?,
+            Ok(_) => fs::remove_file(&dst_path)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
+        }
+
+        create_symlink(&target, &dst_path)
+    }
+}
+
+/// Removes all entries of a cache directory that haven't been modified within the given duration.
+///
+/// The direct children of `dir` are treated as cache entries, such as downloaded artifacts or
+/// directories of extracted artifacts. The modification time of a directory entry is the latest
+/// modification time of any file within it.
+///
+/// Returns the paths of the removed entries.
+///
+/// # Examples:
+/// ```no_run
+/// use libherokubuildpack::fs::prune_dir_by_age;
+/// use std::time::Duration;
+///
+/// // Remove downloads that haven't been used in the last 30 days.
+/// prune_dir_by_age("/layers/downloads/cache", Duration::from_secs(30 * 24 * 60 * 60)).unwrap();
+/// ```
+///
+/// # Errors:
+/// Will return `Err` if the directory couldn't be read or an entry couldn't be removed.
+pub fn prune_dir_by_age(
+    dir: impl AsRef<Path>,
+    max_age: Duration,
+) -> Result<Vec<PathBuf>, std::io::Error> {
+    let now = SystemTime::now();
+    let mut removed_paths = Vec::new();
+
+    for cache_entry in read_cache_entries(dir.as_ref())? {
+        // Entries modified in the future, due to clock skew, are never too old.
+        if now
+            .duration_since(cache_entry.modified)
+            .is_ok_and(|age| age > max_age)
+        {
+            remove_path(&cache_entry.path)?;
+            removed_paths.push(cache_entry.path);
+        }
+    }
+
+    Ok(removed_paths)
+}
+
+/// Removes the least recently modified entries of a cache directory until the total size of the
+/// directory is at most `max_size` bytes.
+///
+/// The direct children of `dir` are treated as cache entries, see [`prune_dir_by_age`] for
+/// details. Entries are removed as a whole, from the least to the most recently modified one.
+///
+/// Returns the paths of the removed entries.
+///
+/// # Examples:
+/// ```no_run
+/// use libherokubuildpack::fs::prune_dir_to_size;
+///
+/// // Keep the most recently used downloads, up to 1 GiB.
+/// prune_dir_to_size("/layers/downloads/cache", 1024 * 1024 * 1024).unwrap();
+/// ```
+///
+/// # Errors:
+/// Will return `Err` if the directory couldn't be read or an entry couldn't be removed.
+pub fn prune_dir_to_size(
+    dir: impl AsRef<Path>,
+    max_size: u64,
+) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut cache_entries = read_cache_entries(dir.as_ref())?;
+    cache_entries.sort_by_key(|cache_entry| cache_entry.modified);
+
+    let mut total_size: u64 = cache_entries
+        .iter()
+        .map(|cache_entry| cache_entry.size)
+        .sum();
+    let mut removed_paths = Vec::new();
+
+    for cache_entry in cache_entries {
+        if total_size <= max_size {
+            break;
+        }
+
+        remove_path(&cache_entry.path)?;
+        total_size -= cache_entry.size;
+        removed_paths.push(cache_entry.path);
+    }
+
+    Ok(removed_paths)
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn read_cache_entries(dir: &Path) -> io::Result<Vec<CacheEntry>> {
+    fs::read_dir(dir)?
+        .map(|dir_entry| {
+            let path = dir_entry?.path();
+            let (size, modified) = size_and_modified(&path)?;
+
+            Ok(CacheEntry {
+                path,
+                size,
+                modified,
+            })
+        })
+        .collect()
+}
+
+// Symlinks are not followed, so that files outside of the cache directory are not accounted for.
+fn size_and_modified(path: &Path) -> io::Result<(u64, SystemTime)> {
+    let metadata = fs::symlink_metadata(path)?;
+    let mut size = metadata.len();
+    let mut modified = metadata.modified()?;
+
+    // The modification time of a directory itself changes whenever an entry is added or removed,
+    // so only the contents are considered, unless the directory is empty.
+    if metadata.is_dir() {
+        size = 0;
+        let mut contents_modified = None;
+
+        for dir_entry in fs::read_dir(path)? {
+            let (entry_size, entry_modified) = size_and_modified(&dir_entry?.path())?;
+            size += entry_size;
+            contents_modified = contents_modified.max(Some(entry_modified));
+        }
+
+        modified = contents_modified.unwrap_or(modified);
+    }
+
+    Ok((size, modified))
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// Files are copied through symlinks otherwise, modifying the file the symlink points to.
+fn remove_symlink(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_symlink() => fs::remove_file(path),
+        Ok(_) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+    let resolved_target = path
+        .parent()
+        .map_or_else(|| target.to_path_buf(), |parent| parent.join(target));
+
+    if resolved_target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, path)
+    } else {
+        std::os::windows::fs::symlink_file(target, path)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
@@ -87,4 +381,123 @@ mod test {
 
         assert!(fs::read_dir(src_dir.path()).unwrap().next().is_none());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_dir_to_layer_retains_symlinks_and_permissions() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let src_dir = tempdir().unwrap();
+        let layer_dir = tempdir().unwrap();
+        let src = src_dir.path();
+
+        fs::create_dir_all(src.join("bin")).unwrap();
+        fs::create_dir_all(src.join("lib/__pycache__")).unwrap();
+        fs::write(src.join("bin/activate"), "#!/usr/bin/env bash").unwrap();
+        fs::set_permissions(src.join("bin/activate"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(src.join("lib/module.py"), "").unwrap();
+        fs::write(src.join("lib/module.pyc"), "").unwrap();
+        fs::write(src.join("lib/__pycache__/module.pyc"), "").unwrap();
+        symlink("activate", src.join("bin/relative")).unwrap();
+        symlink(src.join("lib"), src.join("bin/absolute")).unwrap();
+        symlink("/usr/bin/python3", src.join("bin/python")).unwrap();
+
+        // Existing symlinks in the layer are replaced, not followed.
+        let outside_file = layer_dir.path().join("outside");
+        fs::write(&outside_file, "outside").unwrap();
+        fs::create_dir_all(layer_dir.path().join("venv/lib")).unwrap();
+        symlink(&outside_file, layer_dir.path().join("venv/lib/module.py")).unwrap();
+
+        let dst = layer_dir.path().join("venv");
+        copy_dir_to_layer(src, &dst, &["__pycache__", "*.pyc"]).unwrap();
+
+        assert_eq!(
+            fs::metadata(dst.join("bin/activate"))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o755
+        );
+        assert_eq!(
+            fs::read_link(dst.join("bin/relative")).unwrap(),
+            PathBuf::from("activate")
+        );
+        assert_eq!(
+            fs::read_link(dst.join("bin/absolute")).unwrap(),
+            dst.join("lib")
+        );
+        assert_eq!(
+            fs::read_link(dst.join("bin/python")).unwrap(),
+            PathBuf::from("/usr/bin/python3")
+        );
+        assert!(dst.join("lib/module.py").is_file());
+        assert!(!dst.join("lib/module.py").is_symlink());
+        assert_eq!(fs::read_to_string(outside_file).unwrap(), "outside");
+        assert!(!dst.join("lib/module.pyc").exists());
+        assert!(!dst.join("lib/__pycache__").exists());
+    }
+
+    fn write_cache_entry(path: &Path, size: usize, age: Duration) {
+        fs::write(path, "x".repeat(size)).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - age)
+            .unwrap();
+    }
+
+    #[test]
+    fn prune_dir_by_age_removes_old_entries() {
+        let cache_dir = tempdir().unwrap();
+        let cache = cache_dir.path();
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        write_cache_entry(&cache.join("old.tgz"), 1, day * 10);
+        write_cache_entry(&cache.join("new.tgz"), 1, day);
+        fs::create_dir(cache.join("extracted")).unwrap();
+        write_cache_entry(&cache.join("extracted/old"), 1, day * 10);
+        write_cache_entry(&cache.join("extracted/new"), 1, day);
+
+        assert_eq!(
+            prune_dir_by_age(cache, day * 7).unwrap(),
+            vec![cache.join("old.tgz")]
+        );
+        assert!(cache.join("new.tgz").exists());
+        assert!(cache.join("extracted/old").exists());
+    }
+
+    #[test]
+    fn prune_dir_to_size_removes_least_recently_modified_entries() {
+        let cache_dir = tempdir().unwrap();
+        let cache = cache_dir.path();
+        let minute = Duration::from_secs(60);
+
+        write_cache_entry(&cache.join("a"), 100, minute * 4);
+        fs::create_dir(cache.join("b")).unwrap();
+        write_cache_entry(&cache.join("b/1"), 100, minute * 5);
+        write_cache_entry(&cache.join("b/2"), 100, minute);
+        write_cache_entry(&cache.join("c"), 100, minute * 3);
+        write_cache_entry(&cache.join("d"), 100, minute * 2);
+
+        let mut removed_paths = prune_dir_to_size(cache, 300).unwrap();
+        removed_paths.sort();
+        assert_eq!(removed_paths, vec![cache.join("a"), cache.join("c")]);
+        assert!(cache.join("b/1").exists());
+        assert!(cache.join("d").exists());
+
+        assert!(prune_dir_to_size(cache, 300).unwrap().is_empty());
+    }
+
+    #[test]
+    fn copy_dir_to_layer_rejects_invalid_patterns() {
+        let src_dir = tempdir().unwrap();
+        let layer_dir = tempdir().unwrap();
+
+        assert!(matches!(
+            copy_dir_to_layer(&src_dir, &layer_dir, &["[invalid"]),
+            Err(CopyDirError::InvalidExcludePattern(_))
+        ));
+    }
 }
@@ -0,0 +1,365 @@
+use libcnb::build::BuildContext;
+use libcnb::data::layer_content_metadata::LayerTypes;
+use libcnb::layer::{ExistingLayerStrategy, Layer, LayerData, LayerResult, LayerResultBuilder};
+use libcnb::Buildpack;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::process::Command;
+use std::{fs, io};
+
+/// A git repository and the reference that should be checked out.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GitRepository {
+    /// The URL of the repository, in any format supported by `git fetch`.
+    pub url: String,
+    /// A branch, tag or full commit SHA.
+    pub reference: String,
+}
+
+/// Clones or updates a git repository in the given directory and checks out the given reference.
+///
+/// Only the commit of the reference is fetched (a shallow fetch), without any history. If the
+/// directory already contains a clone of the repository, such as a cached layer from a previous
+/// build, the reference is fetched again and the working tree is reset to it. Any changes to the
+/// working tree are discarded.
+///
+/// Requires the `git` executable to be available on the `PATH`.
+///
+/// Returns the SHA of the checked out commit.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::git::{fetch_git_repository, GitRepository};
+///
+/// let repository = GitRepository {
+///     url: String::from("https://github.com/heroku/heroku-buildpack-python"),
+///     reference: String::from("main"),
+/// };
+///
+/// let commit = fetch_git_repository(&repository, "/layers/python/buildpack").unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the reference starts with `-`, the directory couldn't be created, `git`
+/// couldn't be run, or any git command failed. In the latter case, the error contains the output
+/// of the command.
+pub fn fetch_git_repository(
+    repository: &GitRepository,
+    dir: impl AsRef<Path>,
+) -> Result<String, GitError> {
+    // A reference starting with `-` would be interpreted as an option of `git fetch`.
+    if repository.reference.starts_with('-') {
+        return Err(GitError::InvalidReference(repository.reference.clone()));
+    }
+
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).map_err(GitError::IoError)?;
+
+    if !dir.join(".git").exists() {
+        run_git(dir, &["init", "--quiet"])?;
+    }
+
+    // Setting the URL every time supports directories that were cloned from a different URL.
+    run_git(dir, &["config", "remote.origin.url", &repository.url])?;
+    run_git(
+        dir,
+        &[
+            "fetch",
+            "--quiet",
+            "--depth",
+            "1",
+            "--no-tags",
+            "origin",
+            &repository.reference,
+        ],
+    )?;
+    run_git(
+        dir,
+        &["checkout", "--quiet", "--force", "--detach", "FETCH_HEAD"],
+    )?;
+    run_git(dir, &["clean", "--quiet", "--force", "-d", "-x"])?;
+
+    run_git(dir, &["rev-parse", "HEAD"]).map(|stdout| String::from(stdout.trim()))
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        // Prevents git from waiting for credentials on stdin if the repository requires them.
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(GitError::IoError)?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(GitError::CommandFailed {
+            command: format!("git {}", args.join(" ")),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GitError {
+    #[error("I/O error while running git: {0}")]
+    IoError(io::Error),
+    #[error("Invalid git reference: {0}")]
+    InvalidReference(String),
+    #[error("Command `{command}` failed: {stderr}")]
+    CommandFailed { command: String, stderr: String },
+}
+
+/// A [`Layer`] that contains a checkout of a git repository.
+///
+/// The checkout is cached across builds if the layer is a cache layer. Cached checkouts of
+/// branches and tags are updated with [`fetch_git_repository`], checkouts of a pinned commit SHA
+/// are kept as is. The layer is recreated if the repository URL changes.
+///
+/// # Examples
+/// ```no_run
+/// # use libcnb::build::{BuildContext, BuildResult, BuildResultBuilder};
+/// # use libcnb::detect::{DetectContext, DetectResult, DetectResultBuilder};
+/// # use libcnb::generic::{GenericMetadata, GenericPlatform};
+/// # use libcnb::Buildpack;
+/// use libcnb::data::layer_content_metadata::LayerTypes;
+/// use libcnb::data::layer_name;
+/// use libherokubuildpack::git::{GitError, GitLayer, GitRepository};
+///
+/// # struct ExampleBuildpack;
+/// #
+/// # #[derive(Debug)]
+/// # struct ExampleBuildpackError(GitError);
+/// #
+/// # impl From<GitError> for ExampleBuildpackError {
+/// #     fn from(error: GitError) -> Self {
+/// #         Self(error)
+/// #     }
+/// # }
+/// #
+/// # impl Buildpack for ExampleBuildpack {
+/// #     type Platform = GenericPlatform;
+/// #     type Metadata = GenericMetadata;
+/// #     type Error = ExampleBuildpackError;
+/// #
+/// #     fn detect(&self, context: DetectContext<Self>) -> libcnb::Result<DetectResult, Self::Error> {
+/// #         DetectResultBuilder::pass().build()
+/// #     }
+/// #
+/// fn build(&self, context: BuildContext<Self>) -> libcnb::Result<BuildResult, Self::Error> {
+///     let layer_data = context.handle_layer(
+///         layer_name!("nginx-config"),
+///         GitLayer::new(
+///             GitRepository {
+///                 url: String::from("https://github.com/example/nginx-config"),
+///                 reference: String::from("v1.2.0"),
+///             },
+///             LayerTypes {
+///                 build: false,
+///                 launch: true,
+///                 cache: true,
+///             },
+///         ),
+///     )?;
+///
+///     println!("Using commit {}", layer_data.content_metadata.metadata.commit);
+///     BuildResultBuilder::new().build()
+/// }
+/// # }
+/// ```
+pub struct GitLayer<B> {
+    repository: GitRepository,
+    layer_types: LayerTypes,
+    buildpack: PhantomData<fn() -> B>,
+}
+
+impl<B> GitLayer<B> {
+    #[must_use]
+    pub fn new(repository: GitRepository, layer_types: LayerTypes) -> Self {
+        Self {
+            repository,
+            layer_types,
+            buildpack: PhantomData,
+        }
+    }
+
+    fn fetch(&self, layer_path: &Path) -> Result<LayerResult<GitLayerMetadata>, GitError> {
+        let commit = fetch_git_repository(&self.repository, layer_path)?;
+
+        LayerResultBuilder::new(GitLayerMetadata {
+            url: self.repository.url.clone(),
+            reference: self.repository.reference.clone(),
+            commit,
+        })
+        .build()
+    }
+}
+
+/// The layer metadata of a [`GitLayer`].
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct GitLayerMetadata {
+    pub url: String,
+    pub reference: String,
+    /// The SHA of the checked out commit.
+    pub commit: String,
+}
+
+impl<B> Layer for GitLayer<B>
+where
+    B: Buildpack,
+    B::Error: From<GitError>,
+{
+    type Buildpack = B;
+    type Metadata = GitLayerMetadata;
+
+    fn types(&self) -> LayerTypes {
+        self.layer_types
+    }
+
+    fn create(
+        &mut self,
+        _context: &BuildContext<Self::Buildpack>,
+        layer_path: &Path,
+    ) -> Result<LayerResult<Self::Metadata>, B::Error> {
+        self.fetch(layer_path).map_err(B::Error::from)
+    }
+
+    fn existing_layer_strategy(
+        &mut self,
+        _context: &BuildContext<Self::Buildpack>,
+        layer_data: &LayerData<Self::Metadata>,
+    ) -> Result<ExistingLayerStrategy, B::Error> {
+        let metadata = &layer_data.content_metadata.metadata;
+
+        Ok(if metadata.url != self.repository.url {
+            ExistingLayerStrategy::Recreate
+        } else if metadata.commit == self.repository.reference {
+            ExistingLayerStrategy::Keep
+        } else {
+            ExistingLayerStrategy::Update
+        })
+    }
+
+    fn update(
+        &mut self,
+        _context: &BuildContext<Self::Buildpack>,
+        layer_data: &LayerData<Self::Metadata>,
+    ) -> Result<LayerResult<Self::Metadata>, B::Error> {
+        self.fetch(&layer_data.path).map_err(B::Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn commit_file(repository_dir: &Path, contents: &str) -> String {
+        fs::write(repository_dir.join("file.txt"), contents).unwrap();
+        run_git(repository_dir, &["add", "file.txt"]).unwrap();
+        run_git(
+            repository_dir,
+            &[
+                "-c",
+                "user.name=libcnb",
+                "-c",
+                "user.email=libcnb@example.com",
+                "commit",
+                "--quiet",
+                "--message",
+                contents,
+            ],
+        )
+        .unwrap();
+
+        String::from(
+            run_git(repository_dir, &["rev-parse", "HEAD"])
+                .unwrap()
+                .trim(),
+        )
+    }
+
+    #[test]
+    fn fetch_and_update_repository() {
+        let upstream_dir = tempdir().unwrap();
+        let upstream = upstream_dir.path();
+        run_git(upstream, &["init", "--quiet", "--initial-branch", "main"]).unwrap();
+        let first_commit = commit_file(upstream, "first");
+        let second_commit = commit_file(upstream, "second");
+
+        let checkout_dir = tempdir().unwrap();
+        let checkout = checkout_dir.path().join("checkout");
+        let mut repository = GitRepository {
+            url: format!("file://{}", upstream.display()),
+            reference: String::from("main"),
+        };
+
+        assert_eq!(
+            fetch_git_repository(&repository, &checkout).unwrap(),
+            second_commit
+        );
+        assert_eq!(
+            fs::read_to_string(checkout.join("file.txt")).unwrap(),
+            "second"
+        );
+        // Only the checked out commit is fetched.
+        assert!(run_git(&checkout, &["cat-file", "-e", &first_commit]).is_err());
+
+        fs::write(checkout.join("untracked.txt"), "").unwrap();
+        let third_commit = commit_file(upstream, "third");
+        assert_eq!(
+            fetch_git_repository(&repository, &checkout).unwrap(),
+            third_commit
+        );
+        assert!(!checkout.join("untracked.txt").exists());
+
+        repository.reference.clone_from(&first_commit);
+        assert_eq!(
+            fetch_git_repository(&repository, &checkout).unwrap(),
+            first_commit
+        );
+        assert_eq!(
+            fs::read_to_string(checkout.join("file.txt")).unwrap(),
+            "first"
+        );
+    }
+
+    #[test]
+    fn report_failed_git_commands() {
+        let checkout_dir = tempdir().unwrap();
+        let repository = GitRepository {
+            url: format!("file://{}", checkout_dir.path().join("missing").display()),
+            reference: String::from("main"),
+        };
+
+        match fetch_git_repository(&repository, checkout_dir.path().join("checkout")) {
+            Err(GitError::CommandFailed { command, stderr }) => {
+                assert!(command.starts_with("git fetch"));
+                assert!(!stderr.is_empty());
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn reject_references_starting_with_a_dash() {
+        let checkout_dir = tempdir().unwrap();
+        let checkout = checkout_dir.path().join("checkout");
+        let repository = GitRepository {
+            url: format!("file://{}", checkout_dir.path().join("missing").display()),
+            reference: String::from("--upload-pack=touch pwned"),
+        };
+
+        match fetch_git_repository(&repository, &checkout) {
+            Err(GitError::InvalidReference(reference)) => {
+                assert_eq!(reference, "--upload-pack=touch pwned");
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+        assert!(!checkout.exists());
+    }
+}
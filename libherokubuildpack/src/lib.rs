@@ -1,5 +1,7 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "archive")]
+pub mod archive;
 #[cfg(feature = "buildpack_output")]
 pub mod buildpack_output;
 #[cfg(feature = "command")]
@@ -12,10 +14,20 @@ pub mod download;
 pub mod error;
 #[cfg(feature = "fs")]
 pub mod fs;
+#[cfg(feature = "git")]
+pub mod git;
+#[cfg(feature = "lock")]
+pub mod lock;
 #[cfg(feature = "log")]
 pub mod log;
+#[cfg(feature = "oci")]
+pub mod oci;
+#[cfg(feature = "package_registry")]
+pub mod package_registry;
 #[cfg(feature = "tar")]
 pub mod tar;
+#[cfg(feature = "template")]
+pub mod template;
 #[cfg(feature = "toml")]
 pub mod toml;
 #[cfg(feature = "write")]
@@ -0,0 +1,168 @@
+use fs4::FileExt;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An advisory lock on a path, such as a layer directory or `store.toml`.
+///
+/// The lock is held on a separate lock file next to the path, named after the path with a `.lock`
+/// suffix, so that it doesn't become part of a layer. Lock files are not removed, since doing so
+/// would race with other processes acquiring the lock.
+///
+/// Locks are advisory: they only serialize processes that also use them, and don't prevent any
+/// other access to the path. They are released when dropped.
+///
+/// # Examples
+/// ```
+/// use libherokubuildpack::lock::PathLock;
+///
+/// # let temp_dir = tempfile::tempdir().unwrap();
+/// let layer_path = temp_dir.path().join("dependencies");
+/// {
+///     let _lock = PathLock::exclusive(&layer_path).unwrap();
+///     // Populate the shared cache while no other buildpack process does.
+/// }
+///
+/// assert!(PathLock::try_exclusive(&layer_path).unwrap().is_some());
+/// ```
+#[derive(Debug)]
+pub struct PathLock {
+    file: File,
+    lock_file_path: PathBuf,
+}
+
+impl PathLock {
+    /// Acquires an exclusive lock on the given path, waiting until no other lock is held on it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the lock file couldn't be created or locked.
+    pub fn exclusive(path: impl AsRef<Path>) -> io::Result<Self> {
+        let lock = Self::open(path.as_ref())?;
+        FileExt::lock_exclusive(&lock.file)?;
+        Ok(lock)
+    }
+
+    /// Acquires a shared lock on the given path, waiting until no exclusive lock is held on it.
+    ///
+    /// Any number of shared locks can be held on a path at the same time, for example by
+    /// processes that only read a cache.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the lock file couldn't be created or locked.
+    pub fn shared(path: impl AsRef<Path>) -> io::Result<Self> {
+        let lock = Self::open(path.as_ref())?;
+        FileExt::lock_shared(&lock.file)?;
+        Ok(lock)
+    }
+
+    /// Acquires an exclusive lock on the given path, if no other lock is held on it.
+    ///
+    /// Returns `Ok(None)` instead of waiting if another lock is held.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the lock file couldn't be created or locked.
+    pub fn try_exclusive(path: impl AsRef<Path>) -> io::Result<Option<Self>> {
+        let lock = Self::open(path.as_ref())?;
+
+        match FileExt::try_lock_exclusive(&lock.file) {
+            Ok(()) => Ok(Some(lock)),
+            Err(error) if error.kind() == fs4::lock_contended_error().kind() => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The path of the lock file.
+    #[must_use]
+    pub fn lock_file_path(&self) -> &Path {
+        &self.lock_file_path
+    }
+
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut lock_file_name = path
+            .file_name()
+            .map(OsString::from)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name"))?;
+        lock_file_name.push(".lock");
+
+        let lock_file_path = path.with_file_name(lock_file_name);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_file_path)?;
+
+        Ok(Self {
+            file,
+            lock_file_path,
+        })
+    }
+}
+
+impl Drop for PathLock {
+    fn drop(&mut self) {
+        // Closing the file releases the lock as well, unlocking explicitly just makes it obvious.
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// Runs the given function while holding an exclusive lock on the given path.
+///
+/// See [`PathLock`] for details.
+///
+/// # Errors
+///
+/// Will return `Err` if the lock couldn't be acquired. The function is not run in that case.
+pub fn with_exclusive_lock<R>(path: impl AsRef<Path>, f: impl FnOnce() -> R) -> io::Result<R> {
+    let _lock = PathLock::exclusive(path)?;
+    Ok(f())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn exclusive_lock_excludes_other_locks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("layer");
+
+        let lock = PathLock::exclusive(&path).unwrap();
+        assert_eq!(lock.lock_file_path(), temp_dir.path().join("layer.lock"));
+        assert!(PathLock::try_exclusive(&path).unwrap().is_none());
+
+        let (sender, receiver) = mpsc::channel();
+        let waiting_path = path.clone();
+        let waiting = thread::spawn(move || {
+            let _lock = PathLock::shared(waiting_path).unwrap();
+            sender.send(()).unwrap();
+        });
+
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+        drop(lock);
+        receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        waiting.join().unwrap();
+
+        assert!(PathLock::try_exclusive(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn shared_locks_exclude_exclusive_locks_only() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("store.toml");
+
+        let first = PathLock::shared(&path).unwrap();
+        let second = PathLock::shared(&path).unwrap();
+        assert!(PathLock::try_exclusive(&path).unwrap().is_none());
+
+        drop((first, second));
+        assert_eq!(with_exclusive_lock(&path, || 42).unwrap(), 42);
+    }
+}
@@ -0,0 +1,143 @@
+use libcnb_common::docker_credentials::{
+    resolve_docker_credentials, DockerCredentials, DockerCredentialsError,
+};
+use std::collections::HashMap;
+use std::env;
+
+/// Credentials for an OCI registry, in the form of an HTTP `Authorization` header value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryCredentials {
+    authorization: String,
+}
+
+impl RegistryCredentials {
+    /// Creates credentials from a username and password, using HTTP basic authentication.
+    #[must_use]
+    pub fn basic(username: &str, password: &str) -> Self {
+        Self::from(DockerCredentials {
+            username: String::from(username),
+            secret: String::from(password),
+        })
+    }
+
+    /// Creates credentials from an HTTP `Authorization` header value, such as `Basic ...` or
+    /// `Bearer ...`.
+    #[must_use]
+    pub fn authorization(authorization: impl Into<String>) -> Self {
+        Self {
+            authorization: authorization.into(),
+        }
+    }
+
+    /// The HTTP `Authorization` header value.
+    #[must_use]
+    pub fn header_value(&self) -> &str {
+        &self.authorization
+    }
+
+    /// Resolves the credentials for the given registry, such as `ghcr.io`.
+    ///
+    /// The `CNB_REGISTRY_AUTH` environment variable is checked first, see
+    /// [`RegistryCredentials::from_registry_auth`]. Otherwise, the credentials are resolved from
+    /// the Docker configuration like `docker` and `pack` do, see [`resolve_docker_credentials`].
+    ///
+    /// Returns `None` if no credentials are configured for the registry, in which case the
+    /// registry is accessed anonymously.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `CNB_REGISTRY_AUTH` or the Docker configuration are invalid, or a
+    /// credential helper failed.
+    pub fn resolve(registry: &str) -> Result<Option<Self>, CredentialsError> {
+        if let Ok(registry_auth) = env::var("CNB_REGISTRY_AUTH") {
+            if let Some(credentials) = Self::from_registry_auth(&registry_auth, registry)? {
+                return Ok(Some(credentials));
+            }
+        }
+
+        Ok(resolve_docker_credentials(registry)?.map(Self::from))
+    }
+
+    /// Reads the credentials for the given registry from the value of `CNB_REGISTRY_AUTH`, a
+    /// JSON object that maps registries to `Authorization` header values. CNB platforms set it
+    /// in the environment of the build, or as a platform environment variable with
+    /// `pack build --env`, in which case it can be read from
+    /// [`Platform::env`](libcnb::Platform::env).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value isn't a JSON object of strings.
+    pub fn from_registry_auth(
+        registry_auth: &str,
+        registry: &str,
+    ) -> Result<Option<Self>, CredentialsError> {
+        let registry_auth = serde_json::from_str::<HashMap<String, String>>(registry_auth)
+            .map_err(CredentialsError::InvalidRegistryAuth)?;
+
+        let hosts = if registry == "docker.io" {
+            vec!["docker.io", "index.docker.io", "registry-1.docker.io"]
+        } else {
+            vec![registry]
+        };
+
+        Ok(registry_auth.iter().find_map(|(key, authorization)| {
+            let host = key
+                .trim_start_matches("https://")
+                .trim_start_matches("http://")
+                .split('/')
+                .next()
+                .unwrap_or_default();
+
+            hosts
+                .contains(&host)
+                .then(|| Self::authorization(authorization))
+        }))
+    }
+}
+
+impl From<DockerCredentials> for RegistryCredentials {
+    fn from(credentials: DockerCredentials) -> Self {
+        Self::authorization(credentials.basic_authorization())
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CredentialsError {
+    #[error("Invalid CNB_REGISTRY_AUTH: {0}")]
+    InvalidRegistryAuth(serde_json::Error),
+
+    #[error(transparent)]
+    DockerCredentialsError(#[from] DockerCredentialsError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_from_registry_auth() {
+        let registry_auth =
+            r#"{"ghcr.io": "Bearer token", "https://index.docker.io/v1/": "Basic abc"}"#;
+
+        assert_eq!(
+            RegistryCredentials::from_registry_auth(registry_auth, "ghcr.io").unwrap(),
+            Some(RegistryCredentials::authorization("Bearer token"))
+        );
+        assert_eq!(
+            RegistryCredentials::from_registry_auth(registry_auth, "docker.io").unwrap(),
+            Some(RegistryCredentials::authorization("Basic abc"))
+        );
+        assert_eq!(
+            RegistryCredentials::from_registry_auth(registry_auth, "quay.io").unwrap(),
+            None
+        );
+        assert!(matches!(
+            RegistryCredentials::from_registry_auth("[]", "ghcr.io"),
+            Err(CredentialsError::InvalidRegistryAuth(_))
+        ));
+        assert_eq!(
+            RegistryCredentials::basic("user", "pass"),
+            RegistryCredentials::authorization("Basic dXNlcjpwYXNz")
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Pulling artifacts from OCI registries into layers.
+//!
+//! Besides container images, OCI registries can store arbitrary artifacts, such as tarballs of
+//! runtimes pushed with [ORAS](https://oras.land). This allows buildpacks to distribute their
+//! binaries through the same registries as their images, with the same credentials.
+
+mod credentials;
+mod registry;
+
+pub use credentials::{CredentialsError, RegistryCredentials};
+pub use libcnb::data::run_image::{ImageReference, ImageReferenceError};
+pub use libcnb_common::docker_credentials::{
+    resolve_docker_credentials, DockerCredentials, DockerCredentialsError,
+};
+
+use crate::archive::{extract_archive_with_format, ArchiveFormat, ExtractError};
+use libcnb::retry::Retry;
+use registry::{Descriptor, RegistryClient};
+use std::fs;
+use std::path::Path;
+
+/// The annotation with the file name of a layer, as set by ORAS.
+const TITLE_ANNOTATION: &str = "org.opencontainers.image.title";
+/// The annotation ORAS sets on layers that contain a packed directory.
+const UNPACK_ANNOTATION: &str = "io.deis.oras.content.unpack";
+
+/// An artifact or image in an OCI registry that can be pulled into a layer.
+///
+/// The layers of the artifact are processed in order:
+///
+/// - Layers with an `org.opencontainers.image.title` annotation, such as files pushed with ORAS,
+///   are written to a file of that name. Directories pushed with ORAS are extracted instead.
+/// - Other layers must be tar archives, optionally gzip compressed, and are extracted.
+///
+/// If the reference points to an image index, the manifest for the configured platform is used.
+///
+/// Credentials for the registry are resolved with [`RegistryCredentials::resolve`], supporting
+/// both `CNB_REGISTRY_AUTH` and the Docker configuration including credential helpers. Connection
+/// errors and responses with status `429` or `5xx` are retried according to the configured
+/// [`Retry`]. The digests of all downloaded blobs are verified.
+///
+/// # Examples
+/// ```no_run
+/// use libcnb::retry::Retry;
+/// use libherokubuildpack::oci::OciArtifact;
+///
+/// # let layer_path = std::path::PathBuf::from("/layers/heroku_example/runtime");
+/// let artifact = OciArtifact::new("ghcr.io/heroku/example-runtime:1.2.3")
+///     .unwrap()
+///     .retry(Retry::new().max_attempts(5));
+///
+/// // The digest can be stored in the layer metadata to detect changes of the artifact.
+/// let pulled = artifact.pull(&layer_path).unwrap();
+/// println!("Pulled {}", pulled.digest);
+/// ```
+#[derive(Clone, Debug)]
+pub struct OciArtifact {
+    reference: ImageReference,
+    os: String,
+    architecture: String,
+    credentials: Option<RegistryCredentials>,
+    retry: Retry,
+}
+
+/// The result of [`OciArtifact::pull`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PulledArtifact {
+    /// The digest of the pulled manifest, such as `sha256:...`.
+    pub digest: String,
+}
+
+impl OciArtifact {
+    /// Creates an artifact from a reference such as `ghcr.io/heroku/jdk:21`, see
+    /// [`ImageReference`].
+    ///
+    /// References are resolved like Docker does: references without a registry refer to Docker
+    /// Hub, single-component repositories on Docker Hub are prefixed with `library/`, and
+    /// references without tag or digest refer to the `latest` tag.
+    ///
+    /// Uses the platform of the running buildpack and the default [`Retry`] configuration.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the reference is invalid.
+    pub fn new(reference: &str) -> Result<Self, ImageReferenceError> {
+        Ok(Self {
+            reference: reference.parse()?,
+            os: String::from(std::env::consts::OS),
+            architecture: String::from(match std::env::consts::ARCH {
+                "x86_64" => "amd64",
+                "aarch64" => "arm64",
+                architecture => architecture,
+            }),
+            credentials: None,
+            retry: Retry::default(),
+        })
+    }
+
+    /// Sets the platform that is used if the reference points to an image index, using OCI
+    /// names such as `linux` and `amd64`.
+    #[must_use]
+    pub fn platform(mut self, os: impl Into<String>, architecture: impl Into<String>) -> Self {
+        self.os = os.into();
+        self.architecture = architecture.into();
+        self
+    }
+
+    /// Uses the given credentials instead of resolving them with
+    /// [`RegistryCredentials::resolve`].
+    #[must_use]
+    pub fn credentials(mut self, credentials: RegistryCredentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    #[must_use]
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Resolves the digest of the manifest for the configured platform without pulling the
+    /// artifact. Compare it with a previously pulled digest to determine if a cached layer is
+    /// outdated.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the credentials couldn't be resolved, or the manifest couldn't be
+    /// fetched.
+    pub fn digest(&self) -> Result<String, OciError> {
+        let mut client = self.client()?;
+        self.platform_manifest(&mut client)
+            .map(|(_, digest)| digest)
+    }
+
+    /// Pulls the artifact into the given directory, usually the path of a layer. Existing files
+    /// are overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the credentials couldn't be resolved, the artifact couldn't be
+    /// downloaded or extracted, or contains a layer of an unsupported media type.
+    pub fn pull(&self, destination: impl AsRef<Path>) -> Result<PulledArtifact, OciError> {
+        let destination = destination.as_ref();
+        fs::create_dir_all(destination)?;
+
+        let mut client = self.client()?;
+        let (manifest, digest) = self.platform_manifest(&mut client)?;
+
+        for layer in &manifest.layers {
+            self.pull_layer(&mut client, layer, destination)?;
+        }
+
+        Ok(PulledArtifact { digest })
+    }
+
+    fn client(&self) -> Result<RegistryClient, OciError> {
+        let credentials = match &self.credentials {
+            Some(credentials) => Some(credentials.clone()),
+            None => RegistryCredentials::resolve(registry::registry(&self.reference))?,
+        };
+
+        Ok(RegistryClient::new(&self.reference, credentials))
+    }
+
+    fn platform_manifest(
+        &self,
+        client: &mut RegistryClient,
+    ) -> Result<(registry::Manifest, String), OciError> {
+        let (manifest, digest) = self.retry.run_if(
+            |_| client.manifest(registry::tag_or_digest(&self.reference)),
+            is_transient_oci_error,
+        )?;
+
+        if manifest.manifests.is_empty() {
+            return Ok((manifest, digest));
+        }
+
+        let platform_digest = manifest
+            .manifests
+            .iter()
+            .find(|descriptor| {
+                descriptor.platform.as_ref().is_some_and(|platform| {
+                    platform.os == self.os && platform.architecture == self.architecture
+                })
+            })
+            .map(|descriptor| descriptor.digest.clone())
+            .ok_or_else(|| OciError::NoMatchingManifest {
+                reference: self.reference.to_string(),
+                os: self.os.clone(),
+                architecture: self.architecture.clone(),
+            })?;
+
+        self.retry.run_if(
+            |_| client.manifest(&platform_digest),
+            is_transient_oci_error,
+        )
+    }
+
+    fn pull_layer(
+        &self,
+        client: &mut RegistryClient,
+        layer: &Descriptor,
+        destination: &Path,
+    ) -> Result<(), OciError> {
+        let title = layer.annotations.get(TITLE_ANNOTATION);
+        let unpack = layer
+            .annotations
+            .get(UNPACK_ANNOTATION)
+            .is_some_and(|unpack| unpack == "true");
+
+        if let (Some(title), false) = (title, unpack) {
+            if title.is_empty() || title.contains(['/', '\\']) || title == ".." {
+                return Err(OciError::InvalidManifest(format!(
+                    "Invalid title of layer {}: {title}",
+                    layer.digest
+                )));
+            }
+
+            return self.retry.run_if(
+                |_| client.blob(&layer.digest, &destination.join(title)),
+                is_transient_oci_error,
+            );
+        }
+
+        let format = match layer.media_type.as_str() {
+            "application/vnd.oci.image.layer.v1.tar+gzip"
+            | "application/vnd.oci.image.layer.nondistributable.v1.tar+gzip"
+            | "application/vnd.docker.image.rootfs.diff.tar.gzip" => ArchiveFormat::TarGz,
+            "application/vnd.oci.image.layer.v1.tar"
+            | "application/vnd.oci.image.layer.nondistributable.v1.tar"
+            | "application/vnd.docker.image.rootfs.diff.tar" => ArchiveFormat::Tar,
+            _ => {
+                return Err(OciError::UnsupportedMediaType {
+                    digest: layer.digest.clone(),
+                    media_type: layer.media_type.clone(),
+                })
+            }
+        };
+
+        let blob_path = destination.join(format!(".oci-blob-{}", layer.digest.replace(':', "-")));
+        self.retry.run_if(
+            |_| client.blob(&layer.digest, &blob_path),
+            is_transient_oci_error,
+        )?;
+
+        let result = extract_archive_with_format(&blob_path, format, destination, 0);
+        fs::remove_file(&blob_path)?;
+        result.map_err(OciError::ExtractError)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum OciError {
+    #[error("Couldn't resolve registry credentials: {0}")]
+    CredentialsError(#[from] CredentialsError),
+
+    // Boxed to prevent `large_enum_variant` errors since `ureq::Error` is massive.
+    #[error("HTTP error while pulling OCI artifact: {0}")]
+    HttpError(#[from] Box<ureq::Error>),
+
+    #[error("I/O error while pulling OCI artifact: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Registry authentication failed: {0}")]
+    Unauthorized(String),
+
+    #[error("Invalid OCI manifest: {0}")]
+    InvalidManifest(String),
+
+    #[error("Digest mismatch while pulling OCI artifact, expected {expected}, but got {actual}")]
+    DigestMismatch { expected: String, actual: String },
+
+    #[error("No manifest for platform {os}/{architecture} in {reference}")]
+    NoMatchingManifest {
+        reference: String,
+        os: String,
+        architecture: String,
+    },
+
+    #[error("Unsupported media type of layer {digest}: {media_type}")]
+    UnsupportedMediaType { digest: String, media_type: String },
+
+    #[error("Error while extracting layer of OCI artifact: {0}")]
+    ExtractError(ExtractError),
+}
+
+fn is_transient_oci_error(error: &OciError) -> bool {
+    match error {
+        OciError::HttpError(http_error) => match http_error.as_ref() {
+            ureq::Error::Status(status, _) => *status == 429 || *status >= 500,
+            ureq::Error::Transport(_) => true,
+        },
+        OciError::IoError(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use sha2::{Digest, Sha256};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    fn sha256(bytes: &[u8]) -> String {
+        format!("sha256:{:x}", Sha256::digest(bytes))
+    }
+
+    fn response(status: &str, headers: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    // Serves the given raw HTTP responses, one per connection, and returns the received requests.
+    fn serve(listener: TcpListener, responses: Vec<Vec<u8>>) -> JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            responses
+                .iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buffer = [0x00; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let read = stream.read(&mut buffer).unwrap();
+                        request.extend_from_slice(&buffer[..read]);
+                    }
+                    stream.write_all(response).unwrap();
+                    String::from_utf8(request).unwrap().to_lowercase()
+                })
+                .collect()
+        })
+    }
+
+    fn tar_gz(path: &str, contents: &[u8]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder.append_data(&mut header, path, contents).unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn pull_artifact() {
+        let archive = tar_gz("bin/tool", b"#!/bin/sh\n");
+        let config = b"Hello World!";
+
+        let manifest = serde_json::json!({
+            "schemaVersion": 2,
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "layers": [
+                {
+                    "mediaType": "application/vnd.oci.image.layer.v1.tar+gzip",
+                    "digest": sha256(&archive),
+                    "size": archive.len(),
+                },
+                {
+                    "mediaType": "text/plain",
+                    "digest": sha256(config),
+                    "size": config.len(),
+                    "annotations": {"org.opencontainers.image.title": "config.txt"},
+                },
+            ],
+        })
+        .to_string();
+
+        let index = serde_json::json!({
+            "schemaVersion": 2,
+            "mediaType": "application/vnd.oci.image.index.v1+json",
+            "manifests": [
+                {
+                    "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                    "digest": sha256(b"other"),
+                    "platform": {"os": "linux", "architecture": "amd64"},
+                },
+                {
+                    "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                    "digest": sha256(manifest.as_bytes()),
+                    "platform": {"os": "linux", "architecture": "arm64"},
+                },
+            ],
+        })
+        .to_string();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = serve(
+            listener,
+            vec![
+                response(
+                    "401 Unauthorized",
+                    &format!("WWW-Authenticate: Bearer realm=\"http://{address}/token\",service=\"registry\"\r\n"),
+                    b"",
+                ),
+                response("200 OK", "", br#"{"token": "secret-token"}"#),
+                response("200 OK", "", index.as_bytes()),
+                response("503 Service Unavailable", "", b""),
+                response("200 OK", "", manifest.as_bytes()),
+                response("200 OK", "", &archive),
+                response("200 OK", "", config),
+            ],
+        );
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let pulled = OciArtifact::new(&format!("{address}/tools/example:1.0"))
+            .unwrap()
+            .platform("linux", "arm64")
+            .credentials(RegistryCredentials::basic("user", "pass"))
+            .retry(Retry::new().initial_delay(Duration::ZERO).jitter(false))
+            .pull(temp_dir.path())
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("get /v2/tools/example/manifests/1.0 "));
+        assert!(requests[1]
+            .starts_with("get /token?scope=repository%3atools%2fexample%3apull&service=registry "));
+        assert!(requests[1].contains("authorization: basic dxnlcjpwyxnz"));
+        assert!(requests[2].contains("authorization: bearer secret-token"));
+        assert!(requests[4].starts_with(&format!(
+            "get /v2/tools/example/manifests/{} ",
+            sha256(manifest.as_bytes())
+        )));
+
+        assert_eq!(pulled.digest, sha256(manifest.as_bytes()));
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("bin/tool")).unwrap(),
+            "#!/bin/sh\n"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("config.txt")).unwrap(),
+            "Hello World!"
+        );
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,361 @@
+use super::credentials::RegistryCredentials;
+use super::OciError;
+use libcnb::data::run_image::ImageReference;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub(super) const MANIFEST_MEDIA_TYPES: &str = "application/vnd.oci.image.index.v1+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json, \
+    application/vnd.docker.distribution.manifest.v2+json";
+
+const DOCKER_HUB_REGISTRY: &str = "docker.io";
+
+/// The registry of the reference. References without a registry refer to Docker Hub.
+pub(super) fn registry(reference: &ImageReference) -> &str {
+    reference.registry().unwrap_or(DOCKER_HUB_REGISTRY)
+}
+
+/// The repository of the reference within its registry. Single-component repositories on Docker
+/// Hub are official images in the `library` namespace.
+fn repository(reference: &ImageReference) -> String {
+    if reference.registry().is_none() && !reference.repository().contains('/') {
+        format!("library/{}", reference.repository())
+    } else {
+        String::from(reference.repository())
+    }
+}
+
+/// The tag or digest of the manifest the reference points to. The digest takes precedence over
+/// the tag, references with neither point to the `latest` tag.
+pub(super) fn tag_or_digest(reference: &ImageReference) -> &str {
+    reference
+        .digest()
+        .or_else(|| reference.tag())
+        .unwrap_or("latest")
+}
+
+/// The base URL of the registry API. Registries on `localhost` are accessed via plain HTTP, all
+/// others via HTTPS.
+fn registry_url(registry: &str) -> String {
+    let host = if registry == DOCKER_HUB_REGISTRY {
+        "registry-1.docker.io"
+    } else {
+        registry
+    };
+
+    let scheme = if host == "localhost"
+        || host.starts_with("localhost:")
+        || host == "127.0.0.1"
+        || host.starts_with("127.0.0.1:")
+    {
+        "http"
+    } else {
+        "https"
+    };
+
+    format!("{scheme}://{host}")
+}
+
+/// A client for the OCI distribution API of a single repository.
+pub(super) struct RegistryClient {
+    agent: ureq::Agent,
+    repository_url: String,
+    repository: String,
+    credentials: Option<RegistryCredentials>,
+    authorization: Option<String>,
+}
+
+impl RegistryClient {
+    pub(super) fn new(
+        reference: &ImageReference,
+        credentials: Option<RegistryCredentials>,
+    ) -> Self {
+        let repository = repository(reference);
+
+        Self {
+            agent: ureq::agent(),
+            repository_url: format!("{}/v2/{repository}", registry_url(registry(reference))),
+            repository,
+            credentials,
+            authorization: None,
+        }
+    }
+
+    /// Fetches a manifest or index by tag or digest. Returns the manifest and its digest.
+    pub(super) fn manifest(&mut self, tag_or_digest: &str) -> Result<(Manifest, String), OciError> {
+        let mut body = Vec::new();
+        self.get(&format!("manifests/{tag_or_digest}"), MANIFEST_MEDIA_TYPES)?
+            .into_reader()
+            .read_to_end(&mut body)?;
+
+        let digest = format!("sha256:{:x}", Sha256::digest(&body));
+        if tag_or_digest.contains(':') && tag_or_digest != digest {
+            return Err(OciError::DigestMismatch {
+                expected: String::from(tag_or_digest),
+                actual: digest,
+            });
+        }
+
+        serde_json::from_slice(&body)
+            .map(|manifest| (manifest, digest))
+            .map_err(|error| OciError::InvalidManifest(error.to_string()))
+    }
+
+    /// Downloads a blob to the given path and verifies its digest.
+    pub(super) fn blob(&mut self, digest: &str, destination: &Path) -> Result<(), OciError> {
+        let mut reader = self
+            .get(&format!("blobs/{digest}"), "application/octet-stream")?
+            .into_reader();
+        let mut file = fs::File::create(destination)?;
+        io::copy(&mut reader, &mut file)?;
+
+        let expected = digest
+            .parse()
+            .map_err(|_| OciError::InvalidManifest(format!("Invalid digest {digest}")))?;
+        let actual = crate::digest::checksum(
+            destination,
+            libcnb::data::checksum::ChecksumAlgorithm::Sha256,
+        )?;
+
+        if actual == expected {
+            Ok(())
+        } else {
+            fs::remove_file(destination)?;
+            Err(OciError::DigestMismatch {
+                expected: String::from(digest),
+                actual: actual.to_string(),
+            })
+        }
+    }
+
+    fn get(&mut self, path: &str, accept: &str) -> Result<ureq::Response, OciError> {
+        let url = format!("{}/{path}", self.repository_url);
+
+        match self.request(&url, accept) {
+            Err(error) => match *error {
+                ureq::Error::Status(401, response) => {
+                    let challenge = response
+                        .header("WWW-Authenticate")
+                        .unwrap_or_default()
+                        .to_string();
+                    self.authenticate(&challenge)?;
+                    self.request(&url, accept)
+                }
+                error => Err(Box::new(error)),
+            },
+            result => result,
+        }
+        .map_err(OciError::HttpError)
+    }
+
+    fn request(&self, url: &str, accept: &str) -> Result<ureq::Response, Box<ureq::Error>> {
+        let mut request = self.agent.get(url).set("Accept", accept);
+        if let Some(authorization) = &self.authorization {
+            request = request.set("Authorization", authorization);
+        }
+        request.call().map_err(Box::new)
+    }
+
+    // See https://distribution.github.io/distribution/spec/auth/token/
+    fn authenticate(&mut self, challenge: &str) -> Result<(), OciError> {
+        let (scheme, parameters) = parse_challenge(challenge);
+
+        self.authorization = match scheme.to_ascii_lowercase().as_str() {
+            "bearer" => match &self.credentials {
+                Some(credentials) if credentials.header_value().starts_with("Bearer ") => {
+                    String::from(credentials.header_value())
+                }
+                credentials => {
+                    let realm = parameters.get("realm").ok_or_else(|| {
+                        OciError::Unauthorized(format!("Invalid challenge: {challenge}"))
+                    })?;
+                    let scope = parameters
+                        .get("scope")
+                        .cloned()
+                        .unwrap_or_else(|| format!("repository:{}:pull", self.repository));
+
+                    let mut request = self.agent.get(realm).query("scope", &scope);
+                    if let Some(service) = parameters.get("service") {
+                        request = request.query("service", service);
+                    }
+                    if let Some(credentials) = credentials {
+                        request = request.set("Authorization", credentials.header_value());
+                    }
+
+                    let response = request
+                        .call()
+                        .map_err(|error| OciError::HttpError(Box::new(error)))?;
+                    let token = serde_json::from_reader::<_, TokenResponse>(response.into_reader())
+                        .map_err(|error| OciError::Unauthorized(error.to_string()))?;
+
+                    format!(
+                        "Bearer {}",
+                        token.token.or(token.access_token).ok_or_else(|| {
+                            OciError::Unauthorized(String::from("Token response without token"))
+                        })?
+                    )
+                }
+            },
+            "basic" => self
+                .credentials
+                .as_ref()
+                .map(|credentials| String::from(credentials.header_value()))
+                .ok_or_else(|| {
+                    OciError::Unauthorized(String::from("The registry requires credentials"))
+                })?,
+            _ => {
+                return Err(OciError::Unauthorized(format!(
+                    "Unsupported challenge: {challenge}"
+                )))
+            }
+        }
+        .into();
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// An image manifest or image index. Only the fields required for pulling artifacts are
+/// deserialized.
+#[derive(Deserialize, Debug)]
+pub(super) struct Manifest {
+    #[serde(default)]
+    pub(super) manifests: Vec<Descriptor>,
+    #[serde(default)]
+    pub(super) layers: Vec<Descriptor>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct Descriptor {
+    pub(super) media_type: String,
+    pub(super) digest: String,
+    #[serde(default)]
+    pub(super) annotations: HashMap<String, String>,
+    pub(super) platform: Option<Platform>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct Platform {
+    pub(super) os: String,
+    pub(super) architecture: String,
+}
+
+// Parses a `WWW-Authenticate` header such as `Bearer realm="https://auth.example.com/token",service="registry"`.
+fn parse_challenge(challenge: &str) -> (&str, HashMap<String, String>) {
+    let (scheme, rest) = challenge.trim().split_once(' ').unwrap_or((challenge, ""));
+    let mut parameters = HashMap::new();
+    let mut chars = rest.chars().peekable();
+
+    loop {
+        let key = chars
+            .by_ref()
+            .skip_while(|c| *c == ',' || c.is_whitespace())
+            .take_while(|c| *c != '=')
+            .collect::<String>();
+        if key.is_empty() {
+            break;
+        }
+
+        let value = if chars.peek() == Some(&'"') {
+            chars.next();
+            let value = chars.by_ref().take_while(|c| *c != '"').collect();
+            chars.by_ref().take_while(|c| *c != ',').for_each(drop);
+            value
+        } else {
+            chars.by_ref().take_while(|c| *c != ',').collect::<String>()
+        };
+
+        parameters.insert(key.trim().to_ascii_lowercase(), value);
+    }
+
+    (scheme, parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_reference() {
+        const DIGEST: &str =
+            "sha256:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+        for (value, expected_registry_url, expected_repository, expected_tag_or_digest) in [
+            (
+                "ubuntu",
+                "https://registry-1.docker.io",
+                "library/ubuntu",
+                "latest",
+            ),
+            (
+                "heroku/heroku:22",
+                "https://registry-1.docker.io",
+                "heroku/heroku",
+                "22",
+            ),
+            (
+                "ghcr.io/heroku/jdk:21",
+                "https://ghcr.io",
+                "heroku/jdk",
+                "21",
+            ),
+            (
+                "localhost:5000/tools/maven",
+                "http://localhost:5000",
+                "tools/maven",
+                "latest",
+            ),
+            (
+                &format!("registry.example.com/maven:3@{DIGEST}"),
+                "https://registry.example.com",
+                "maven",
+                DIGEST,
+            ),
+        ] {
+            let reference = value.parse::<ImageReference>().unwrap();
+
+            assert_eq!(registry_url(registry(&reference)), expected_registry_url);
+            assert_eq!(repository(&reference), expected_repository);
+            assert_eq!(tag_or_digest(&reference), expected_tag_or_digest);
+        }
+    }
+
+    #[test]
+    fn parse_www_authenticate_challenge() {
+        let (scheme, parameters) = parse_challenge(
+            r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:heroku/jdk:pull,push""#,
+        );
+
+        assert_eq!(scheme, "Bearer");
+        assert_eq!(
+            parameters,
+            HashMap::from([
+                (String::from("realm"), String::from("https://ghcr.io/token")),
+                (String::from("service"), String::from("ghcr.io")),
+                (
+                    String::from("scope"),
+                    String::from("repository:heroku/jdk:pull,push")
+                ),
+            ])
+        );
+
+        let (scheme, parameters) = parse_challenge("Basic realm=registry");
+        assert_eq!(scheme, "Basic");
+        assert_eq!(
+            parameters,
+            HashMap::from([(String::from("realm"), String::from("registry"))])
+        );
+    }
+}
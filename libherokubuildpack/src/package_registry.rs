@@ -0,0 +1,449 @@
+//! Querying the available versions of packages from Maven, npm and `PyPI` registries.
+//!
+//! Buildpacks that resolve version requirements, such as "the latest 3.x release", need the list of
+//! published versions. This module fetches these lists without each buildpack embedding the
+//! registry-specific formats. Use [`VersionCache`] to cache the lists in a layer across builds.
+
+use libcnb::retry::Retry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use std::{fs, io};
+
+/// A package registry that versions can be queried from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PackageRegistry {
+    /// A Maven repository. Packages are named `<groupId>:<artifactId>`, such as
+    /// `org.apache.maven:apache-maven`.
+    Maven { url: String },
+    /// An npm registry. Packages are named like in `package.json`, such as `@types/node`.
+    Npm { url: String },
+    /// A Python package index with the `PyPI` JSON API. Packages are named like on `PyPI`, such as
+    /// `setuptools`.
+    PyPi { url: String },
+}
+
+impl PackageRegistry {
+    /// Maven Central, at `https://repo1.maven.org/maven2`.
+    #[must_use]
+    pub fn maven_central() -> Self {
+        Self::Maven {
+            url: String::from("https://repo1.maven.org/maven2"),
+        }
+    }
+
+    /// The public npm registry, at `https://registry.npmjs.org`.
+    #[must_use]
+    pub fn npm() -> Self {
+        Self::Npm {
+            url: String::from("https://registry.npmjs.org"),
+        }
+    }
+
+    /// The Python Package Index, at `https://pypi.org/pypi`.
+    #[must_use]
+    pub fn pypi() -> Self {
+        Self::PyPi {
+            url: String::from("https://pypi.org/pypi"),
+        }
+    }
+
+    /// Fetches the published versions of a package.
+    ///
+    /// Maven versions are returned in the order of the repository metadata, which is usually the
+    /// order of publication. npm and `PyPI` versions are sorted as strings. Pre-releases are
+    /// included, `PyPI` releases without files or with only yanked files are not. Use a version
+    /// parser such as `semver` to select a matching version.
+    ///
+    /// Connection errors and responses with status `429` or `5xx` are retried with the default
+    /// [`Retry`] configuration.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use libherokubuildpack::package_registry::PackageRegistry;
+    ///
+    /// let versions = PackageRegistry::maven_central()
+    ///     .versions("org.apache.maven:apache-maven")
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the package name is invalid for the registry, the metadata couldn't be
+    /// fetched, or the registry returned metadata in an unexpected format.
+    pub fn versions(&self, package: &str) -> Result<Vec<String>, PackageRegistryError> {
+        let url = self.metadata_url(package)?;
+        let accept = match self {
+            Self::Maven { .. } => "application/xml",
+            // The abbreviated metadata is considerably smaller than the full package document.
+            Self::Npm { .. } => "application/vnd.npm.install-v1+json",
+            Self::PyPi { .. } => "application/json",
+        };
+
+        let body = Retry::default().run_if(
+            |_| {
+                ureq::get(&url)
+                    .set("Accept", accept)
+                    .call()
+                    .map_err(|error| PackageRegistryError::HttpError(Box::new(error)))?
+                    .into_string()
+                    .map_err(PackageRegistryError::IoError)
+            },
+            is_transient_package_registry_error,
+        )?;
+
+        match self {
+            Self::Maven { .. } => parse_maven_metadata(&body),
+            Self::Npm { .. } => parse_npm_metadata(&body),
+            Self::PyPi { .. } => parse_pypi_metadata(&body),
+        }
+    }
+
+    fn metadata_url(&self, package: &str) -> Result<String, PackageRegistryError> {
+        let invalid_name = || PackageRegistryError::InvalidPackageName(String::from(package));
+        let is_valid_component = |component: &str| {
+            !component.is_empty()
+                && component
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '~'))
+        };
+
+        match self {
+            Self::Maven { url } => {
+                let (group_id, artifact_id) = package
+                    .split_once(':')
+                    .filter(|(group_id, artifact_id)| {
+                        is_valid_component(group_id) && is_valid_component(artifact_id)
+                    })
+                    .ok_or_else(invalid_name)?;
+
+                Ok(format!(
+                    "{}/{}/{artifact_id}/maven-metadata.xml",
+                    url.trim_end_matches('/'),
+                    group_id.replace('.', "/")
+                ))
+            }
+            Self::Npm { url } => {
+                let (scope, name) = match package.strip_prefix('@') {
+                    Some(scoped) => scoped
+                        .split_once('/')
+                        .map(|(scope, name)| (Some(scope), name))
+                        .ok_or_else(invalid_name)?,
+                    None => (None, package),
+                };
+
+                if !scope.map_or(true, is_valid_component) || !is_valid_component(name) {
+                    return Err(invalid_name());
+                }
+
+                Ok(match scope {
+                    Some(scope) => format!("{}/@{scope}%2F{name}", url.trim_end_matches('/')),
+                    None => format!("{}/{name}", url.trim_end_matches('/')),
+                })
+            }
+            Self::PyPi { url } => {
+                if !is_valid_component(package) {
+                    return Err(invalid_name());
+                }
+
+                Ok(format!("{}/{package}/json", url.trim_end_matches('/')))
+            }
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum PackageRegistryError {
+    #[error("Invalid package name: {0}")]
+    InvalidPackageName(String),
+
+    // Boxed to prevent `large_enum_variant` errors since `ureq::Error` is massive.
+    #[error("HTTP error while querying package registry: {0}")]
+    HttpError(#[from] Box<ureq::Error>),
+
+    #[error("I/O error while querying package registry: {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("Invalid package metadata: {0}")]
+    InvalidMetadata(String),
+}
+
+fn is_transient_package_registry_error(error: &PackageRegistryError) -> bool {
+    match error {
+        PackageRegistryError::HttpError(http_error) => match http_error.as_ref() {
+            ureq::Error::Status(status, _) => *status == 429 || *status >= 500,
+            ureq::Error::Transport(_) => true,
+        },
+        PackageRegistryError::IoError(_) => true,
+        PackageRegistryError::InvalidPackageName(_) | PackageRegistryError::InvalidMetadata(_) => {
+            false
+        }
+    }
+}
+
+fn parse_maven_metadata(xml: &str) -> Result<Vec<String>, PackageRegistryError> {
+    let versions = xml
+        .split_once("<versions>")
+        .and_then(|(_, rest)| rest.split_once("</versions>"))
+        .map(|(versions, _)| versions)
+        .ok_or_else(|| {
+            PackageRegistryError::InvalidMetadata(String::from(
+                "maven-metadata.xml doesn't contain versions",
+            ))
+        })?;
+
+    Ok(versions
+        .split("<version>")
+        .skip(1)
+        .filter_map(|version| version.split_once("</version>"))
+        .map(|(version, _)| String::from(version.trim()))
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct NpmMetadata {
+    versions: HashMap<String, serde_json::Value>,
+}
+
+fn parse_npm_metadata(json: &str) -> Result<Vec<String>, PackageRegistryError> {
+    let metadata = serde_json::from_str::<NpmMetadata>(json)
+        .map_err(|error| PackageRegistryError::InvalidMetadata(error.to_string()))?;
+
+    let mut versions = metadata.versions.into_keys().collect::<Vec<_>>();
+    versions.sort();
+    Ok(versions)
+}
+
+#[derive(Deserialize)]
+struct PyPiMetadata {
+    releases: HashMap<String, Vec<PyPiFile>>,
+}
+
+#[derive(Deserialize)]
+struct PyPiFile {
+    #[serde(default)]
+    yanked: bool,
+}
+
+fn parse_pypi_metadata(json: &str) -> Result<Vec<String>, PackageRegistryError> {
+    let metadata = serde_json::from_str::<PyPiMetadata>(json)
+        .map_err(|error| PackageRegistryError::InvalidMetadata(error.to_string()))?;
+
+    let mut versions = metadata
+        .releases
+        .into_iter()
+        .filter(|(_, files)| files.iter().any(|file| !file.yanked))
+        .map(|(version, _)| version)
+        .collect::<Vec<_>>();
+    versions.sort();
+    Ok(versions)
+}
+
+/// Caches the version lists of packages in a directory, usually a cached layer.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::package_registry::{PackageRegistry, VersionCache};
+/// use std::time::Duration;
+///
+/// # let layer_path = std::path::PathBuf::from("/layers/heroku_example/versions");
+/// let cache = VersionCache::new(&layer_path, Duration::from_secs(60 * 60));
+/// let versions = cache
+///     .versions(&PackageRegistry::npm(), "typescript")
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct VersionCache {
+    dir: PathBuf,
+    max_age: Duration,
+}
+
+impl VersionCache {
+    /// Creates a cache in the given directory. Cached lists older than `max_age` are fetched
+    /// again.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>, max_age: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            max_age,
+        }
+    }
+
+    /// Returns the versions of a package, see [`PackageRegistry::versions`]. Uses the cached list
+    /// if it's recent enough, and fetches and caches the list otherwise.
+    ///
+    /// Cache entries that can't be read are ignored and replaced.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the versions had to be fetched and that failed, or the fetched list
+    /// couldn't be written to the cache.
+    pub fn versions(
+        &self,
+        registry: &PackageRegistry,
+        package: &str,
+    ) -> Result<Vec<String>, PackageRegistryError> {
+        let path = self.entry_path(registry, package)?;
+        let now = SystemTime::now();
+
+        if let Some(entry) = read_cache_entry(&path) {
+            let age = SystemTime::UNIX_EPOCH + Duration::from_secs(entry.fetched_at);
+            if now.duration_since(age).is_ok_and(|age| age <= self.max_age) {
+                return Ok(entry.versions);
+            }
+        }
+
+        let versions = registry.versions(package)?;
+
+        fs::create_dir_all(&self.dir)?;
+        let entry = CacheEntry {
+            fetched_at: now
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            versions,
+        };
+        fs::write(&path, serde_json::to_vec(&entry).map_err(io::Error::other)?)?;
+
+        Ok(entry.versions)
+    }
+
+    fn entry_path(
+        &self,
+        registry: &PackageRegistry,
+        package: &str,
+    ) -> Result<PathBuf, PackageRegistryError> {
+        // The metadata URL identifies both the registry and the package.
+        let file_name = registry
+            .metadata_url(package)?
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+
+        Ok(self.dir.join(format!("{file_name}.json")))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    fetched_at: u64,
+    versions: Vec<String>,
+}
+
+fn read_cache_entry(path: &Path) -> Option<CacheEntry> {
+    fs::read(path)
+        .ok()
+        .and_then(|contents| serde_json::from_slice(&contents).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn metadata_urls() {
+        assert_eq!(
+            PackageRegistry::maven_central()
+                .metadata_url("org.apache.maven:apache-maven")
+                .unwrap(),
+            "https://repo1.maven.org/maven2/org/apache/maven/apache-maven/maven-metadata.xml"
+        );
+        assert_eq!(
+            PackageRegistry::npm().metadata_url("@types/node").unwrap(),
+            "https://registry.npmjs.org/@types%2Fnode"
+        );
+        assert_eq!(
+            PackageRegistry::pypi().metadata_url("setuptools").unwrap(),
+            "https://pypi.org/pypi/setuptools/json"
+        );
+
+        for (registry, package) in [
+            (PackageRegistry::maven_central(), "apache-maven"),
+            (PackageRegistry::maven_central(), "org.apache:../maven"),
+            (PackageRegistry::npm(), "@types"),
+            (PackageRegistry::npm(), "../typescript"),
+            (PackageRegistry::pypi(), "setuptools/../pip"),
+        ] {
+            assert!(matches!(
+                registry.metadata_url(package),
+                Err(PackageRegistryError::InvalidPackageName(name)) if name == package
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_metadata() {
+        assert_eq!(
+            parse_maven_metadata(
+                "<metadata><versioning><latest>3.9.6</latest><versions>\n\
+                <version>3.8.8</version>\n<version> 3.9.6 </version>\n\
+                </versions></versioning></metadata>"
+            )
+            .unwrap(),
+            vec!["3.8.8", "3.9.6"]
+        );
+        assert!(matches!(
+            parse_maven_metadata("<metadata></metadata>"),
+            Err(PackageRegistryError::InvalidMetadata(_))
+        ));
+
+        assert_eq!(
+            parse_npm_metadata(
+                r#"{"name": "typescript", "dist-tags": {"latest": "5.4.5"}, "versions": {"5.4.5": {}, "5.3.3": {}}}"#
+            )
+            .unwrap(),
+            vec!["5.3.3", "5.4.5"]
+        );
+
+        assert_eq!(
+            parse_pypi_metadata(
+                r#"{"releases": {"69.0.0": [{"yanked": false}], "68.0.0": [{"yanked": true}], "67.0.0": []}}"#
+            )
+            .unwrap(),
+            vec!["69.0.0"]
+        );
+    }
+
+    #[test]
+    fn version_cache() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let registry = PackageRegistry::Npm {
+            url: format!("http://{}", listener.local_addr().unwrap()),
+        };
+
+        // Only a single request is served, the second lookup must use the cache.
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0x00; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let body = r#"{"versions": {"1.0.0": {}}}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = VersionCache::new(temp_dir.path(), Duration::from_secs(60));
+
+        assert_eq!(cache.versions(&registry, "example").unwrap(), vec!["1.0.0"]);
+        server.join().unwrap();
+        assert_eq!(cache.versions(&registry, "example").unwrap(), vec!["1.0.0"]);
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,251 @@
+use std::borrow::Borrow;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::hash::{BuildHasher, Hash};
+use std::path::Path;
+use std::{fs, io};
+
+/// Renders a template by substituting `{{ name }}` placeholders with the given values.
+///
+/// Placeholder names consist of ASCII letters, digits, `_`, `-` and `.`, whitespace around the name
+/// is optional. Use `\{{` for a literal `{{` that doesn't start a placeholder. This is the only
+/// escape sequence, a `\` anywhere else is kept as is.
+///
+/// Values are inserted verbatim. They are not escaped for the format of the rendered file, such as
+/// quotes in a config file, and placeholders in values aren't substituted. Values that may contain
+/// characters with a special meaning in the rendered file must be escaped before rendering.
+///
+/// This is intended for config files that are written into layers, such as `nginx.conf` or JVM
+/// options files, where build-time data needs to be inserted into an otherwise static file.
+///
+/// # Examples
+/// ```
+/// use libherokubuildpack::template::render_template;
+/// use std::collections::HashMap;
+///
+/// let rendered = render_template(
+///     "listen {{ port }};\nroot {{app_dir}}/public;\n",
+///     &HashMap::from([("port", "8080"), ("app_dir", "/workspace")]),
+/// )
+/// .unwrap();
+///
+/// assert_eq!(rendered, "listen 8080;\nroot /workspace/public;\n");
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the template contains an unclosed or invalid placeholder, or if there is no
+/// value for one or more placeholders. Unclosed and invalid placeholders are reported with their
+/// line and column. All placeholders without values are reported together.
+pub fn render_template<K, V, S>(
+    template: &str,
+    values: &HashMap<K, V, S>,
+) -> Result<String, TemplateError>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<str>,
+    S: BuildHasher,
+{
+    let mut rendered = String::with_capacity(template.len());
+    let mut missing_values = BTreeSet::new();
+    let mut position = Position::default();
+    let mut offset = 0;
+
+    while let Some(start) = template[offset..].find("{{").map(|start| offset + start) {
+        if template[offset..start].ends_with('\\') {
+            rendered.push_str(&template[offset..start - 1]);
+            rendered.push_str("{{");
+            offset = start + 2;
+            continue;
+        }
+
+        rendered.push_str(&template[offset..start]);
+        position.advance_to(template, start);
+
+        let end = template[start..].find("}}").map(|end| start + end).ok_or(
+            TemplateError::UnclosedPlaceholder {
+                line: position.line,
+                column: position.column(template),
+            },
+        )?;
+
+        let name = template[start + 2..end].trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return Err(TemplateError::InvalidPlaceholder {
+                placeholder: String::from(&template[start..end + 2]),
+                line: position.line,
+                column: position.column(template),
+            });
+        }
+
+        match values.get(name) {
+            Some(value) => rendered.push_str(value.as_ref()),
+            None => {
+                missing_values.insert(String::from(name));
+            }
+        }
+
+        offset = end + 2;
+    }
+
+    rendered.push_str(&template[offset..]);
+
+    if missing_values.is_empty() {
+        Ok(rendered)
+    } else {
+        Err(TemplateError::MissingValues(missing_values))
+    }
+}
+
+/// The line and column of a placeholder, tracked while the template is scanned so that each part
+/// of the template is only searched for line breaks once.
+struct Position {
+    line: usize,
+    line_start: usize,
+    scanned: usize,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self {
+            line: 1,
+            line_start: 0,
+            scanned: 0,
+        }
+    }
+}
+
+impl Position {
+    /// Moves the position forward to the given byte offset of the template.
+    fn advance_to(&mut self, template: &str, offset: usize) {
+        let scanned = &template[self.scanned..offset];
+
+        if let Some(last_line_break) = scanned.rfind('\n') {
+            self.line += scanned.matches('\n').count();
+            self.line_start = self.scanned + last_line_break + 1;
+        }
+
+        self.scanned = offset;
+    }
+
+    /// The column of the position, counted in characters.
+    fn column(&self, template: &str) -> usize {
+        template[self.line_start..self.scanned].chars().count() + 1
+    }
+}
+
+/// Renders a template with [`render_template`] and writes the result to the given path.
+///
+/// # Examples
+/// ```no_run
+/// use libherokubuildpack::template::write_template;
+/// use std::collections::HashMap;
+///
+/// write_template(
+///     "-Xmx{{ heap_size }}\n",
+///     &HashMap::from([("heap_size", "512m")]),
+///     "/layers/jvm/jvm-options",
+/// )
+/// .unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the template couldn't be rendered or the file couldn't be written.
+pub fn write_template<K, V, S>(
+    template: &str,
+    values: &HashMap<K, V, S>,
+    path: impl AsRef<Path>,
+) -> Result<(), TemplateError>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<str>,
+    S: BuildHasher,
+{
+    fs::write(path, render_template(template, values)?).map_err(TemplateError::IoError)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TemplateError {
+    #[error("Unclosed placeholder in template on line {line}, column {column}")]
+    UnclosedPlaceholder { line: usize, column: usize },
+    #[error("Invalid placeholder `{placeholder}` in template on line {line}, column {column}")]
+    InvalidPlaceholder {
+        placeholder: String,
+        line: usize,
+        column: usize,
+    },
+    #[error("Missing values for template placeholders: {}", format_names(.0))]
+    MissingValues(BTreeSet<String>),
+    #[error("I/O error while writing rendered template: {0}")]
+    IoError(io::Error),
+}
+
+fn format_names(names: &BTreeSet<String>) -> String {
+    names.iter().fold(String::new(), |mut output, name| {
+        if !output.is_empty() {
+            output.push_str(", ");
+        }
+        let _ = write!(output, "`{name}`");
+        output
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_placeholders_and_escapes() {
+        let values = HashMap::from([
+            ("port", String::from("8080")),
+            ("jvm.heap", String::from("512m")),
+        ]);
+
+        assert_eq!(
+            render_template("-Xmx{{jvm.heap}} \\{{ port }} {{  port  }}", &values).unwrap(),
+            "-Xmx512m {{ port }} 8080"
+        );
+        assert_eq!(
+            render_template("C:\\{{port}}\\x", &values).unwrap(),
+            "C:{{port}}\\x"
+        );
+        assert_eq!(
+            render_template("{{ port }}", &HashMap::from([("port", "{{ port }}")])).unwrap(),
+            "{{ port }}"
+        );
+        assert_eq!(render_template("{ port }}", &values).unwrap(), "{ port }}");
+    }
+
+    #[test]
+    fn report_template_errors() {
+        let values = HashMap::from([("port", "8080")]);
+
+        assert!(matches!(
+            render_template("server {\n  listen {{ port;\n}", &values),
+            Err(TemplateError::UnclosedPlaceholder {
+                line: 2,
+                column: 10
+            })
+        ));
+        assert!(matches!(
+            render_template("{{ port }}\n\n{{ port }} {{ the port }}", &values),
+            Err(TemplateError::InvalidPlaceholder { placeholder, line: 3, column: 12 }) if placeholder == "{{ the port }}"
+        ));
+        assert!(matches!(
+            render_template("\\{{ port }}\nü {{ port", &values),
+            Err(TemplateError::UnclosedPlaceholder { line: 2, column: 3 })
+        ));
+
+        let error =
+            render_template("{{ host }}:{{ port }} {{ workers }} {{ host }}", &values).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Missing values for template placeholders: `host`, `workers`"
+        );
+    }
+}